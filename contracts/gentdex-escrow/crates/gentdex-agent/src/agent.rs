@@ -0,0 +1,219 @@
+//! The tick loop tying a strategy, a session and a venue together.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use gentdex_client::rpc::GentdexRpc;
+use tracing::{info, warn};
+
+use crate::risk::{LocalLimits, Rejection, Risk};
+use crate::session::Session;
+use crate::strategy::{Order, Strategy, Tick};
+use crate::venue::{Execution, Fill, Venue};
+use crate::AgentError;
+
+/// What one tick did.
+#[derive(Debug, Default)]
+pub struct Step {
+    pub fills: Vec<(Order, Fill)>,
+    pub rejections: Vec<(Order, Rejection)>,
+}
+
+pub struct Agent<S, V> {
+    pub session: Session,
+    pub risk: Risk,
+    pub strategy: S,
+    pub venue: V,
+}
+
+impl<S: Strategy, V: Venue> Agent<S, V> {
+    pub fn new(session: Session, strategy: S, venue: V) -> Self {
+        Self {
+            session,
+            risk: Risk::default(),
+            strategy,
+            venue,
+        }
+    }
+
+    /// Also enforce the operator's `local` limits.
+    pub fn with_local_limits(mut self, local: LocalLimits) -> Self {
+        self.risk = Risk::new(local);
+        self
+    }
+
+    /// Run one tick at unix time `now`: price the strategy's markets, take
+    /// its orders, and execute the ones that pass the risk checks. Does
+    /// nothing once the session can't trade.
+    pub async fn step(&mut self, now: i64) -> Result<Step, AgentError> {
+        let mut step = Step::default();
+        if !self.session.is_tradable(now) {
+            return Ok(step);
+        }
+
+        let mut prices = HashMap::new();
+        for mint in self.strategy.markets() {
+            prices.insert(mint, self.venue.price(&mint).await?);
+        }
+        let orders = self.strategy.on_tick(&Tick {
+            now,
+            session: &self.session,
+            prices: &prices,
+        });
+
+        for order in orders {
+            let outcome = match self.risk.check(&self.session, &order, &self.venue, &prices, now) {
+                Ok(()) => match self.venue.execute(&self.session, &order).await? {
+                    Execution::Filled(fill) => Ok(fill),
+                    Execution::Rejected(reason) => Err(Rejection::Policy(reason)),
+                },
+                Err(rejection) => Err(rejection),
+            };
+            match outcome {
+                Ok(fill) => {
+                    self.session.record_fill(&fill);
+                    self.risk.record(&fill, now);
+                    self.strategy.on_fill(&order, &fill);
+                    step.fills.push((order, fill));
+                }
+                Err(rejection) => {
+                    self.strategy.on_rejected(&order, &rejection);
+                    step.rejections.push((order, rejection));
+                }
+            }
+        }
+        Ok(step)
+    }
+}
+
+/// Drive `agent` live every `interval`, syncing its session from `rpc` before
+/// each tick. Errors are logged and retried next tick; returns once the
+/// session can no longer trade (paused, expired, withdrawn).
+pub async fn run<S: Strategy, V: Venue>(agent: &mut Agent<S, V>, rpc: &GentdexRpc, interval: Duration) {
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        if let Err(err) = agent.session.refresh(rpc).await {
+            warn!(%err, "could not sync session; skipping tick");
+            continue;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64);
+        if !agent.session.is_tradable(now) {
+            info!(vault = %agent.session.address, status = ?agent.session.status, "session can no longer trade; stopping");
+            return;
+        }
+        match agent.step(now).await {
+            Ok(step) => {
+                for (order, fill) in &step.fills {
+                    info!(
+                        mint = %order.output_mint,
+                        amount_in = fill.amount_in,
+                        amount_out = fill.amount_out,
+                        signature = fill.signature.as_deref().unwrap_or_default(),
+                        "filled"
+                    );
+                }
+                for (order, rejection) in &step.rejections {
+                    info!(mint = %order.output_mint, amount_in = order.amount_in, ?rejection, "rejected");
+                }
+            }
+            Err(err) => warn!(%err, "tick failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use gentdex_client::program::VaultStatus;
+
+    use super::*;
+    use crate::risk::Limits;
+
+    struct Buyer(Pubkey);
+
+    impl Strategy for Buyer {
+        fn markets(&self) -> Vec<Pubkey> {
+            vec![self.0]
+        }
+
+        fn on_tick(&mut self, tick: &Tick) -> Vec<Order> {
+            assert_eq!(tick.prices[&self.0], 2.0);
+            vec![Order {
+                output_mint: self.0,
+                amount_in: 400,
+                slippage_bps: 50,
+                memo: None,
+            }]
+        }
+    }
+
+    struct Fixed(Pubkey);
+
+    impl Venue for Fixed {
+        fn dex_program(&self) -> Pubkey {
+            self.0
+        }
+
+        async fn price(&mut self, _mint: &Pubkey) -> Result<f64, AgentError> {
+            Ok(2.0)
+        }
+
+        async fn execute(&mut self, _session: &Session, order: &Order) -> Result<Execution, AgentError> {
+            Ok(Execution::Filled(Fill {
+                signature: None,
+                output_mint: order.output_mint,
+                amount_in: order.amount_in,
+                amount_out: order.amount_in / 2,
+                fee: 0,
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn fills_orders_until_the_session_refuses_them() {
+        let dex = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let session = Session {
+            address: Pubkey::new_unique(),
+            session_id: [1; 16],
+            user: Pubkey::new_unique(),
+            status: VaultStatus::Active,
+            expires_at: 1_000,
+            sol: true,
+            resigned: false,
+            balance: 1_000,
+            deployed: 0,
+            daily_compute_fee: 0,
+            last_compute_deduction: 0,
+            slippage_consumed: 0,
+            positions: Default::default(),
+            limits: Limits {
+                whitelist: vec![dex],
+                max_positions: 4,
+                ..Default::default()
+            },
+        };
+        let mut agent = Agent::new(session, Buyer(mint), Fixed(dex)).with_local_limits(LocalLimits {
+            cooldown: 60,
+            ..Default::default()
+        });
+
+        let first = agent.step(10).await.unwrap();
+        assert_eq!(first.fills.len(), 1);
+        assert_eq!(agent.session.balance, 600);
+        assert_eq!(agent.session.positions[&mint], 200);
+
+        let cooling = agent.step(20).await.unwrap();
+        assert_eq!(cooling.rejections[0].1, Rejection::Cooldown);
+        assert_eq!(agent.step(70).await.unwrap().fills.len(), 1);
+        let broke = agent.step(130).await.unwrap();
+        assert!(broke.fills.is_empty());
+        assert!(matches!(broke.rejections[0].1, Rejection::Policy(_)));
+
+        // Nothing is priced or ordered after expiry
+        let expired = agent.step(1_000).await.unwrap();
+        assert!(expired.fills.is_empty() && expired.rejections.is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! Backtesting over historical candles.
+//!
+//! [`Candles`] replays OHLC data as paper [`Quotes`]: strategies and marks see
+//! each candle's close, and fills pay a [`Slippage`] model on top of it. A
+//! [`Backtest`] runs a strategy through a paper [`Simulation`] at every candle,
+//! so results are net of the setup fee, compute fees and any swap fee, and
+//! records the equity curve for drawdown stats.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::paper::{FeeSchedule, PaperVenue, Quotes, Report, Simulation};
+use crate::risk::Limits;
+use crate::strategy::Strategy;
+use crate::{AgentError, BPS_DENOMINATOR};
+
+/// One period of a market, priced in lamports per raw unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candle {
+    /// Unix time the period opened
+    pub time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Lamports traded in the period, 0 if unknown
+    pub volume: f64,
+}
+
+/// Candles from CSV rows of `time,open,high,low,close[,volume]`, with or
+/// without a header row.
+pub fn parse_csv(text: &str) -> Result<Vec<Candle>, AgentError> {
+    let mut candles = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let Ok(time) = fields[0].parse::<i64>() else {
+            if index == 0 {
+                continue;
+            }
+            return Err(AgentError::Data(format!("line {}: invalid time {:?}", index + 1, fields[0])));
+        };
+        if !(5..=6).contains(&fields.len()) {
+            return Err(AgentError::Data(format!("line {}: expected 5 or 6 fields", index + 1)));
+        }
+        let number = |field: &str| {
+            field
+                .parse::<f64>()
+                .map_err(|_| AgentError::Data(format!("line {}: invalid number {field:?}", index + 1)))
+        };
+        candles.push(Candle {
+            time,
+            open: number(fields[1])?,
+            high: number(fields[2])?,
+            low: number(fields[3])?,
+            close: number(fields[4])?,
+            volume: fields.get(5).map(|field| number(field)).transpose()?.unwrap_or_default(),
+        });
+    }
+    Ok(candles)
+}
+
+/// What a fill pays over the candle's close.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Slippage {
+    /// Paid on every fill
+    pub spread_bps: u16,
+    /// Further bps per percent of the candle's volume the order takes
+    pub impact_bps_per_pct: f64,
+}
+
+impl Slippage {
+    /// Lamports per raw unit paid buying with `amount_in` lamports in `candle`.
+    pub fn fill_price(&self, candle: &Candle, amount_in: u64) -> f64 {
+        let mut bps = self.spread_bps as f64;
+        if candle.volume > 0.0 {
+            bps += self.impact_bps_per_pct * amount_in as f64 / candle.volume * 100.0;
+        }
+        candle.close * (1.0 + bps / BPS_DENOMINATOR as f64)
+    }
+}
+
+/// Historical candles for any number of mints.
+#[derive(Clone, Debug, Default)]
+pub struct Candles {
+    series: HashMap<Pubkey, Vec<Candle>>,
+    slippage: Slippage,
+}
+
+impl Candles {
+    pub fn new(slippage: Slippage) -> Self {
+        Self {
+            series: HashMap::new(),
+            slippage,
+        }
+    }
+
+    /// Add `mint`'s candles, in any order.
+    pub fn insert(&mut self, mint: Pubkey, candles: impl IntoIterator<Item = Candle>) {
+        let series = self.series.entry(mint).or_default();
+        series.extend(candles);
+        series.sort_by_key(|candle| candle.time);
+    }
+
+    /// The candle `mint` is in at `now`: the latest opened at or before it.
+    pub fn candle(&self, mint: &Pubkey, now: i64) -> Option<&Candle> {
+        let series = self.series.get(mint)?;
+        let index = series.partition_point(|candle| candle.time <= now);
+        index.checked_sub(1).map(|index| &series[index])
+    }
+
+    /// Every candle's open time, ascending, for ticking a backtest.
+    pub fn times(&self) -> Vec<i64> {
+        let mut times: Vec<i64> = self.series.values().flatten().map(|candle| candle.time).collect();
+        times.sort_unstable();
+        times.dedup();
+        times
+    }
+}
+
+impl Quotes for Candles {
+    async fn price(&mut self, mint: &Pubkey, now: i64) -> Result<f64, AgentError> {
+        match self.candle(mint, now) {
+            Some(candle) if candle.close > 0.0 => Ok(candle.close),
+            _ => Err(AgentError::NoPrice(*mint)),
+        }
+    }
+
+    async fn quote(&mut self, mint: &Pubkey, amount_in: u64, now: i64) -> Result<u64, AgentError> {
+        match self.candle(mint, now) {
+            Some(candle) if candle.close > 0.0 => Ok((amount_in as f64 / self.slippage.fill_price(candle, amount_in)) as u64),
+            _ => Err(AgentError::NoPrice(*mint)),
+        }
+    }
+}
+
+/// The worst peak-to-trough fall of an equity curve.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Drawdown {
+    /// Lamports lost from the peak
+    pub depth: u64,
+    /// `depth` as a fraction of the peak
+    pub fraction: f64,
+    pub peak_at: i64,
+    pub trough_at: i64,
+    /// When equity first got back to the peak, if it did
+    pub recovered_at: Option<i64>,
+}
+
+/// A finished backtest.
+pub struct Backtest {
+    pub report: Report,
+    /// `(unix time, balance plus marked positions)` at every candle
+    pub equity: Vec<(i64, u64)>,
+}
+
+impl Backtest {
+    /// Fund a paper session with `deposit` lamports at the first candle and
+    /// run `strategy` through every candle after, marking each time.
+    pub async fn run<S: Strategy>(
+        strategy: S,
+        candles: Candles,
+        fees: FeeSchedule,
+        limits: Limits,
+        deposit: u64,
+        duration_days: u16,
+    ) -> Result<Self, AgentError> {
+        let times = candles.times();
+        let start = times.first().copied().unwrap_or_default();
+        let mut simulation = Simulation::new(strategy, PaperVenue::new(candles, fees), deposit, duration_days, limits, start);
+
+        let mut equity = Vec::with_capacity(times.len());
+        for now in times {
+            simulation.step(now).await?;
+            equity.push((now, simulation.mark(now).await?.value()));
+        }
+        Ok(Self {
+            report: simulation.report,
+            equity,
+        })
+    }
+
+    /// Net result as a fraction of the deposit.
+    pub fn total_return(&self) -> f64 {
+        match self.report.deposit {
+            0 => 0.0,
+            deposit => self.report.net() as f64 / deposit as f64,
+        }
+    }
+
+    /// The deepest drawdown, measured from the deposit as the first peak.
+    pub fn max_drawdown(&self) -> Drawdown {
+        let mut worst = Drawdown::default();
+        let (mut peak, mut peak_at) = (self.report.deposit, self.equity.first().map_or(0, |&(time, _)| time));
+        for &(time, value) in &self.equity {
+            if value >= peak {
+                if worst.peak_at == peak_at && worst.depth > 0 && worst.recovered_at.is_none() {
+                    worst.recovered_at = Some(time);
+                }
+                (peak, peak_at) = (value, time);
+            } else if peak - value > worst.depth {
+                worst = Drawdown {
+                    depth: peak - value,
+                    fraction: (peak - value) as f64 / peak as f64,
+                    peak_at,
+                    trough_at: time,
+                    recovered_at: None,
+                };
+            }
+        }
+        worst
+    }
+
+    /// Write the equity curve as `time,equity` CSV.
+    pub fn write_equity_csv(&self, mut out: impl Write) -> std::io::Result<()> {
+        writeln!(out, "time,equity")?;
+        for (time, value) in &self.equity {
+            writeln!(out, "{time},{value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gentdex_client::jupiter::JUPITER_PROGRAM_ID;
+
+    use super::*;
+    use crate::strategy::{Order, Tick};
+
+    /// Buys once, at the first tick.
+    struct Once(Pubkey, bool);
+
+    impl Strategy for Once {
+        fn markets(&self) -> Vec<Pubkey> {
+            vec![self.0]
+        }
+
+        fn on_tick(&mut self, _tick: &Tick) -> Vec<Order> {
+            if std::mem::replace(&mut self.1, true) {
+                return vec![];
+            }
+            vec![Order {
+                output_mint: self.0,
+                amount_in: 500,
+                slippage_bps: 50,
+                memo: None,
+            }]
+        }
+    }
+
+    #[test]
+    fn parses_csv_and_prices_slippage() {
+        let candles = parse_csv("time,open,high,low,close,volume\n0,2,2.5,1.5,2,100000\n60,2,3,2,2.5\n").unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[1].close, 2.5);
+        assert_eq!(candles[1].volume, 0.0);
+        assert!(matches!(parse_csv("0,1,1,1\n"), Err(AgentError::Data(_))));
+        assert!(matches!(parse_csv("0,1,1,1,1\nx,1,1,1,1\n"), Err(AgentError::Data(_))));
+
+        let slippage = Slippage {
+            spread_bps: 10,
+            impact_bps_per_pct: 5.0,
+        };
+        // 10 bps spread plus 1% of the volume at 5 bps per percent
+        let price = slippage.fill_price(&candles[0], 1_000);
+        assert!((price - 2.0 * 1.0015).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn tracks_equity_and_drawdown() {
+        let mint = Pubkey::new_unique();
+        let mut candles = Candles::new(Slippage::default());
+        candles.insert(
+            mint,
+            [(0, 2.0), (60, 1.0), (120, 4.0)].map(|(time, close)| Candle {
+                time,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0.0,
+            }),
+        );
+        let limits = Limits {
+            whitelist: vec![JUPITER_PROGRAM_ID],
+            max_positions: 4,
+            ..Default::default()
+        };
+
+        let backtest = Backtest::run(Once(mint, false), candles, FeeSchedule::default(), limits, 1_000, 1)
+            .await
+            .unwrap();
+        // 500 left, plus 250 units marked at 2, 1 and 4
+        assert_eq!(backtest.equity, vec![(0, 1_000), (60, 750), (120, 1_500)]);
+        assert_eq!(
+            backtest.max_drawdown(),
+            Drawdown {
+                depth: 250,
+                fraction: 0.25,
+                peak_at: 0,
+                trough_at: 60,
+                recovered_at: Some(120),
+            }
+        );
+        assert_eq!(backtest.total_return(), 0.5);
+
+        let mut csv = Vec::new();
+        backtest.write_equity_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "time,equity\n0,1000\n60,750\n120,1500\n");
+    }
+}
@@ -0,0 +1,51 @@
+//! Framework for GentDex bot operators.
+//!
+//! A bot plugs a [`Strategy`] into an [`Agent`], which each tick prices the
+//! strategy's markets, asks it for orders, checks them against the session's
+//! and the operator's limits and hands the ones that pass to a [`Venue`] for
+//! execution:
+//!
+//! - [`strategy`]: the `Strategy` trait and the orders it produces
+//! - [`session`]: a local copy of the vault's balance, positions and swap
+//!   policy
+//! - [`risk`]: pre-trade checks that mirror `execute_swap`'s, so orders the
+//!   program would refuse never cost a transaction, plus operator limits
+//!   (daily volume, cooldown, mint allowlist) it doesn't enforce
+//! - [`venue`]: where orders are executed; [`JupiterVenue`] trades through
+//!   the SDK's Jupiter route builder
+//! - [`agent`]: the tick loop, and [`run`] to drive it live against a cluster
+//! - [`paper`]: a simulated session and venue, to estimate a strategy's net
+//!   performance after the protocol's fees without touching the chain
+//! - [`backtest`]: paper trading over historical candles with a slippage
+//!   model, reporting the equity curve and drawdown
+
+pub mod agent;
+pub mod backtest;
+pub mod paper;
+pub mod risk;
+pub mod session;
+pub mod strategy;
+pub mod venue;
+
+use gentdex_client::ClientError;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const BPS_DENOMINATOR: u64 = 10_000;
+
+pub use agent::{run, Agent, Step};
+pub use risk::{Limits, LocalLimits, Rejection, Risk};
+pub use session::Session;
+pub use strategy::{Order, Strategy, Tick};
+pub use venue::{Execution, Fill, JupiterVenue, Venue};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AgentError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+    #[error("no price for {0}")]
+    NoPrice(anchor_lang::prelude::Pubkey),
+    #[error("transaction {0} landed without a swap event for the session")]
+    MissingEvent(String),
+    #[error("market data: {0}")]
+    Data(String),
+}
@@ -0,0 +1,380 @@
+//! Paper trading: run a strategy against recorded or live quotes without
+//! touching the chain, to estimate what a session would net before funding
+//! it.
+//!
+//! A [`Simulation`] funds a local [`Session`] the way `deposit` would, taking
+//! the setup fee, deducts the daily compute fee as the keeper would, and
+//! fills orders through a [`PaperVenue`] at quoted prices. The program takes
+//! no per-swap fee; [`FeeSchedule::swap_fee_bps`] is there to model DEX or
+//! aggregator fees the quotes don't already include.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_client::jupiter::{JupiterClient, JUPITER_PROGRAM_ID};
+use gentdex_client::program::{ProtocolConfig, VaultStatus};
+
+use crate::agent::{Agent, Step};
+use crate::risk::Limits;
+use crate::session::Session;
+use crate::strategy::{Order, Strategy};
+use crate::venue::{Execution, Fill, Venue};
+use crate::{AgentError, BPS_DENOMINATOR, SECONDS_PER_DAY};
+
+/// Slippage allowed on live quotes, which are never executed
+const LIVE_QUOTE_SLIPPAGE_BPS: u16 = 50;
+
+/// What a session pays, and when.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FeeSchedule {
+    /// Taken from each deposit (after any stake discount)
+    pub setup_fee_bps: u16,
+    /// Lamports deducted per whole day the session runs
+    pub daily_compute_fee: u64,
+    /// Charged on each swap's `amount_in`, on top of it
+    pub swap_fee_bps: u16,
+}
+
+impl FeeSchedule {
+    /// The protocol's current fees, undiscounted.
+    pub fn from_config(config: &ProtocolConfig) -> Self {
+        Self {
+            setup_fee_bps: config.fee_bps,
+            daily_compute_fee: config.daily_compute_fee,
+            swap_fee_bps: 0,
+        }
+    }
+
+    /// `(fee, trading balance)` for a deposit of `amount` lamports.
+    pub fn split_deposit(&self, amount: u64) -> (u64, u64) {
+        let fee = bps_of(amount, self.setup_fee_bps);
+        (fee, amount - fee)
+    }
+
+    pub fn swap_fee(&self, amount_in: u64) -> u64 {
+        bps_of(amount_in, self.swap_fee_bps)
+    }
+}
+
+fn bps_of(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Where paper fills get their prices.
+pub trait Quotes {
+    /// Lamports per raw unit of `mint` at unix time `now`, for strategies and
+    /// marking positions.
+    fn price(&mut self, mint: &Pubkey, now: i64) -> impl Future<Output = Result<f64, AgentError>>;
+
+    /// Raw units of `mint` that `amount_in` lamports buy at unix time `now`.
+    fn quote(&mut self, mint: &Pubkey, amount_in: u64, now: i64) -> impl Future<Output = Result<u64, AgentError>>;
+}
+
+/// Recorded prices to replay: for each mint, lamports per raw unit from a
+/// point in time until the next point.
+#[derive(Clone, Debug, Default)]
+pub struct Recorded {
+    series: HashMap<Pubkey, Vec<(i64, f64)>>,
+}
+
+impl Recorded {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `mint` trading at `price` lamports per raw unit from `at`.
+    pub fn insert(&mut self, mint: Pubkey, at: i64, price: f64) {
+        let points = self.series.entry(mint).or_default();
+        let index = points.partition_point(|&(time, _)| time <= at);
+        points.insert(index, (at, price));
+    }
+
+    /// The price of `mint` at `now`: its latest point at or before it.
+    pub fn price_at(&self, mint: &Pubkey, now: i64) -> Option<f64> {
+        let points = self.series.get(mint)?;
+        let index = points.partition_point(|&(time, _)| time <= now);
+        index.checked_sub(1).map(|index| points[index].1)
+    }
+
+    /// Every recorded time, ascending, for ticking a replay.
+    pub fn times(&self) -> Vec<i64> {
+        let mut times: Vec<i64> = self.series.values().flatten().map(|&(time, _)| time).collect();
+        times.sort_unstable();
+        times.dedup();
+        times
+    }
+}
+
+impl Quotes for Recorded {
+    async fn price(&mut self, mint: &Pubkey, now: i64) -> Result<f64, AgentError> {
+        match self.price_at(mint, now) {
+            Some(price) if price > 0.0 => Ok(price),
+            _ => Err(AgentError::NoPrice(*mint)),
+        }
+    }
+
+    async fn quote(&mut self, mint: &Pubkey, amount_in: u64, now: i64) -> Result<u64, AgentError> {
+        Ok((amount_in as f64 / self.price(mint, now).await?) as u64)
+    }
+}
+
+/// Live Jupiter quotes, never executed.
+pub struct LiveQuotes {
+    jupiter: JupiterClient,
+    /// Lamports quoted when pricing a market
+    probe_lamports: u64,
+}
+
+impl LiveQuotes {
+    /// Price markets by quoting `probe_lamports` of SOL for them.
+    pub fn new(jupiter: JupiterClient, probe_lamports: u64) -> Self {
+        Self { jupiter, probe_lamports }
+    }
+}
+
+impl Quotes for LiveQuotes {
+    async fn price(&mut self, mint: &Pubkey, now: i64) -> Result<f64, AgentError> {
+        match self.quote(mint, self.probe_lamports, now).await? {
+            0 => Err(AgentError::NoPrice(*mint)),
+            amount_out => Ok(self.probe_lamports as f64 / amount_out as f64),
+        }
+    }
+
+    async fn quote(&mut self, mint: &Pubkey, amount_in: u64, _now: i64) -> Result<u64, AgentError> {
+        Ok(self.jupiter.quote(mint, amount_in, LIVE_QUOTE_SLIPPAGE_BPS).await?.out_amount)
+    }
+}
+
+/// Fills every order that passes the risk checks at its quote, less the
+/// schedule's swap fee. Stands in for Jupiter unless routed otherwise.
+pub struct PaperVenue<Q> {
+    quotes: Q,
+    fees: FeeSchedule,
+    dex_program: Pubkey,
+    protected: bool,
+    /// The simulation's clock
+    now: i64,
+}
+
+impl<Q: Quotes> PaperVenue<Q> {
+    pub fn new(quotes: Q, fees: FeeSchedule) -> Self {
+        Self {
+            quotes,
+            fees,
+            dex_program: JUPITER_PROGRAM_ID,
+            protected: false,
+            now: 0,
+        }
+    }
+
+    /// Stand in for a venue routing through `dex_program`, whose swaps are
+    /// `protected` or not.
+    pub fn routed_as(mut self, dex_program: Pubkey, protected: bool) -> Self {
+        self.dex_program = dex_program;
+        self.protected = protected;
+        self
+    }
+
+    pub fn fees(&self) -> &FeeSchedule {
+        &self.fees
+    }
+}
+
+impl<Q: Quotes> Venue for PaperVenue<Q> {
+    fn dex_program(&self) -> Pubkey {
+        self.dex_program
+    }
+
+    fn protected(&self) -> bool {
+        self.protected
+    }
+
+    async fn price(&mut self, mint: &Pubkey) -> Result<f64, AgentError> {
+        self.quotes.price(mint, self.now).await
+    }
+
+    async fn execute(&mut self, _session: &Session, order: &Order) -> Result<Execution, AgentError> {
+        let amount_out = self.quotes.quote(&order.output_mint, order.amount_in, self.now).await?;
+        Ok(Execution::Filled(Fill {
+            signature: None,
+            output_mint: order.output_mint,
+            amount_in: order.amount_in,
+            amount_out,
+            fee: self.fees.swap_fee(order.amount_in),
+        }))
+    }
+}
+
+/// A paper session's results so far, in lamports.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub deposit: u64,
+    pub setup_fee: u64,
+    pub compute_fees: u64,
+    pub swap_fees: u64,
+    pub fills: usize,
+    pub rejections: usize,
+    /// Lamports sold across fills
+    pub volume: u64,
+    /// Trading balance left
+    pub balance: u64,
+    /// Positions at the last marked prices
+    pub positions_value: u64,
+}
+
+impl Report {
+    /// Balance plus marked positions.
+    pub fn value(&self) -> u64 {
+        self.balance + self.positions_value
+    }
+
+    /// What the session made over its deposit, net of every fee.
+    pub fn net(&self) -> i128 {
+        self.value() as i128 - self.deposit as i128
+    }
+}
+
+/// A strategy trading a paper session.
+pub struct Simulation<S, Q> {
+    pub agent: Agent<S, PaperVenue<Q>>,
+    pub report: Report,
+}
+
+impl<S: Strategy, Q: Quotes> Simulation<S, Q> {
+    /// Fund a paper session with `deposit` lamports at unix time `start`, to
+    /// run `duration_days` under the vault policy `limits`.
+    pub fn new(strategy: S, venue: PaperVenue<Q>, deposit: u64, duration_days: u16, limits: Limits, start: i64) -> Self {
+        let fees = *venue.fees();
+        let (setup_fee, balance) = fees.split_deposit(deposit);
+        let session = Session {
+            address: Pubkey::default(),
+            session_id: [0; 16],
+            user: Pubkey::default(),
+            status: VaultStatus::Active,
+            expires_at: start + duration_days as i64 * SECONDS_PER_DAY,
+            sol: true,
+            resigned: false,
+            balance,
+            deployed: 0,
+            daily_compute_fee: fees.daily_compute_fee,
+            last_compute_deduction: start,
+            slippage_consumed: 0,
+            positions: Default::default(),
+            limits,
+        };
+        Self {
+            agent: Agent::new(session, strategy, venue),
+            report: Report {
+                deposit,
+                setup_fee,
+                balance,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Advance the clock to `now`, deduct any compute fee due and run a tick.
+    pub async fn step(&mut self, now: i64) -> Result<Step, AgentError> {
+        self.agent.venue.now = now;
+        self.report.compute_fees += self.agent.session.deduct_compute_fee(now);
+        let step = self.agent.step(now).await?;
+
+        self.report.fills += step.fills.len();
+        self.report.rejections += step.rejections.len();
+        for (_, fill) in &step.fills {
+            self.report.swap_fees += fill.fee;
+            self.report.volume += fill.amount_in;
+        }
+        self.report.balance = self.agent.session.balance;
+        Ok(step)
+    }
+
+    /// Value the session's positions at `now`'s prices.
+    pub async fn mark(&mut self, now: i64) -> Result<&Report, AgentError> {
+        self.agent.venue.now = now;
+        let mut value = 0.0;
+        for (mint, amount) in &self.agent.session.positions {
+            if *amount > 0 {
+                value += *amount as f64 * self.agent.venue.price(mint).await?;
+            }
+        }
+        self.report.positions_value = value as u64;
+        Ok(&self.report)
+    }
+
+    /// Step through `ticks` (unix times, ascending) and mark at the last.
+    pub async fn run(&mut self, ticks: impl IntoIterator<Item = i64>) -> Result<&Report, AgentError> {
+        let mut last = None;
+        for now in ticks {
+            self.step(now).await?;
+            last = Some(now);
+        }
+        match last {
+            Some(now) => self.mark(now).await,
+            None => Ok(&self.report),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::Tick;
+
+    /// Buys once, at the first tick.
+    struct Once(Pubkey, bool);
+
+    impl Strategy for Once {
+        fn markets(&self) -> Vec<Pubkey> {
+            vec![self.0]
+        }
+
+        fn on_tick(&mut self, _tick: &Tick) -> Vec<Order> {
+            if std::mem::replace(&mut self.1, true) {
+                return vec![];
+            }
+            vec![Order {
+                output_mint: self.0,
+                amount_in: 500,
+                slippage_bps: 50,
+                memo: None,
+            }]
+        }
+    }
+
+    #[tokio::test]
+    async fn nets_out_setup_compute_and_swap_fees() {
+        let mint = Pubkey::new_unique();
+        let mut recorded = Recorded::new();
+        recorded.insert(mint, 0, 2.0);
+        recorded.insert(mint, 2 * SECONDS_PER_DAY, 4.0);
+        assert_eq!(recorded.price_at(&mint, SECONDS_PER_DAY), Some(2.0));
+        assert_eq!(recorded.price_at(&mint, -1), None);
+
+        let fees = FeeSchedule {
+            setup_fee_bps: 100,
+            daily_compute_fee: 10,
+            swap_fee_bps: 200,
+        };
+        let limits = Limits {
+            whitelist: vec![JUPITER_PROGRAM_ID],
+            max_positions: 4,
+            ..Default::default()
+        };
+        let venue = PaperVenue::new(recorded, fees);
+        let mut simulation = Simulation::new(Once(mint, false), venue, 1_000, 7, limits, 0);
+
+        let ticks = [0, SECONDS_PER_DAY, 2 * SECONDS_PER_DAY];
+        let report = simulation.run(ticks).await.unwrap().clone();
+        assert_eq!(report.setup_fee, 10);
+        assert_eq!(report.fills, 1);
+        assert_eq!(report.swap_fees, 10);
+        assert_eq!(report.compute_fees, 20);
+        // 990 funded, less 500 sold, 10 swap fee and 20 compute fee
+        assert_eq!(report.balance, 460);
+        // 250 units bought at 2, marked at 4
+        assert_eq!(report.positions_value, 1_000);
+        assert_eq!(report.net(), 460);
+    }
+}
@@ -0,0 +1,359 @@
+//! Pre-trade risk checks.
+//!
+//! [`Limits`] are the vault's own swap policy, loaded with the session, and
+//! [`Risk::check`] applies them the way `execute_swap` would, so an order the
+//! program would refuse or reject never costs a transaction. [`LocalLimits`]
+//! add operator limits the program doesn't know about (daily volume,
+//! cooldowns, a mint allowlist); they're only ever enforced here.
+
+use std::collections::{HashMap, VecDeque};
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_client::program::{ProtocolConfig, SwapRejectReason, Vault, VaultStatus, MAX_POSITIONS};
+
+use crate::session::Session;
+use crate::strategy::Order;
+use crate::venue::{Fill, Venue};
+use crate::{BPS_DENOMINATOR, SECONDS_PER_DAY};
+
+/// Why an order won't be executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    /// The session isn't `Active`; `execute_swap` would fail
+    Inactive(VaultStatus),
+    /// The session ran out its duration; `execute_swap` would fail
+    Expired,
+    /// The DEX isn't on the whitelist or the session's allowed set; `execute_swap` would fail
+    DexNotWhitelisted,
+    /// Only SOL sessions can swap
+    NotSol,
+    /// The program would reject the swap with this reason
+    Policy(SwapRejectReason),
+    /// Buying a new mint would exceed the session's position cap
+    PositionLimit,
+    /// The session caps exposure but the config has no price feed for the mint
+    NoPriceFeed,
+    /// The position would exceed the session's exposure cap at current prices
+    ExposureCap,
+    /// The operator's mint allowlist doesn't include the mint
+    MintNotAllowed,
+    /// Too soon after the last fill for the operator's cooldown
+    Cooldown,
+    /// Over the operator's daily volume limit
+    DailyVolume,
+}
+
+/// The vault's swap policy, as `execute_swap` applies it.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    /// Per-swap `amount_in` cap, 0 = no limit
+    pub max_trade_lamports: u64,
+    /// DEX programs the protocol allows this session: the current whitelist
+    /// and entries grandfathered for its whitelist version
+    pub whitelist: Vec<Pubkey>,
+    /// The session's own subset of the whitelist, empty = all
+    pub allowed_dexes: Vec<Pubkey>,
+    pub disabled_dexes: Vec<Pubkey>,
+    /// Distinct mints the vault may hold
+    pub max_positions: usize,
+    /// Max share of portfolio value in one token, 0 = no cap
+    pub max_exposure_bps: u16,
+    /// Mints the config has price feeds for, which an exposure cap needs
+    pub priced_mints: Vec<Pubkey>,
+    /// Cumulative slippage allowed, in lamports, 0 = no budget
+    pub slippage_budget: u64,
+    /// Swaps must show a recent quote slot or a Jito tip
+    pub requires_protection: bool,
+}
+
+impl Limits {
+    pub fn from_chain(vault: &Vault, config: &ProtocolConfig) -> Self {
+        let whitelist = config
+            .whitelist
+            .iter()
+            .chain(config.grandfathered.iter().map(|entry| &entry.program_id))
+            .filter(|program| config.allows_dex(program, vault.whitelist_version))
+            .copied()
+            .collect();
+        Self {
+            max_trade_lamports: vault.max_trade_lamports,
+            whitelist,
+            allowed_dexes: vault.allowed_dexes.clone(),
+            disabled_dexes: vault.disabled_dexes.clone(),
+            max_positions: match vault.max_positions {
+                0 => MAX_POSITIONS,
+                max => max as usize,
+            },
+            max_exposure_bps: vault.max_exposure_bps,
+            priced_mints: config.price_feeds.iter().map(|feed| feed.mint).collect(),
+            slippage_budget: vault.slippage_budget,
+            requires_protection: vault.max_slot_age > 0 || vault.require_jito_tip,
+        }
+    }
+
+    fn allows_dex(&self, dex_program: &Pubkey) -> bool {
+        self.whitelist.contains(dex_program)
+            && (self.allowed_dexes.is_empty() || self.allowed_dexes.contains(dex_program))
+    }
+}
+
+/// Operator limits on top of the vault's. Not enforced on-chain.
+#[derive(Clone, Debug, Default)]
+pub struct LocalLimits {
+    /// Lamports sold per rolling 24 hours, 0 = no limit
+    pub max_daily_volume: u64,
+    /// Seconds to wait after a fill before the next order, 0 = none
+    pub cooldown: i64,
+    /// Mints the bot may buy, empty = any
+    pub allowed_mints: Vec<Pubkey>,
+}
+
+/// Checks orders against a session's limits and the operator's, remembering
+/// recent fills for the rolling ones.
+#[derive(Clone, Debug, Default)]
+pub struct Risk {
+    pub local: LocalLimits,
+    /// `(unix time, lamports sold)` of the last day's fills, oldest first
+    fills: VecDeque<(i64, u64)>,
+}
+
+impl Risk {
+    pub fn new(local: LocalLimits) -> Self {
+        Self {
+            local,
+            fills: VecDeque::new(),
+        }
+    }
+
+    /// Check `order` from `session`, routed through `venue`, at `now`.
+    /// `prices` (lamports per raw unit) estimate the exposure an order would
+    /// leave; unpriced mints skip that check and leave it to the program.
+    pub fn check(
+        &self,
+        session: &Session,
+        order: &Order,
+        venue: &impl Venue,
+        prices: &HashMap<Pubkey, f64>,
+        now: i64,
+    ) -> Result<(), Rejection> {
+        if session.status != VaultStatus::Active {
+            return Err(Rejection::Inactive(session.status));
+        }
+        if !session.sol {
+            return Err(Rejection::NotSol);
+        }
+        if now >= session.expires_at {
+            return Err(Rejection::Expired);
+        }
+
+        let limits = &session.limits;
+        let dex_program = venue.dex_program();
+        if !limits.allows_dex(&dex_program) {
+            return Err(Rejection::DexNotWhitelisted);
+        }
+
+        // Policy checks, in the program's order
+        let policy = if session.resigned {
+            Some(SwapRejectReason::BotResigned)
+        } else if order.amount_in > session.balance {
+            Some(SwapRejectReason::InsufficientBalance)
+        } else if limits.disabled_dexes.contains(&dex_program) {
+            Some(SwapRejectReason::DexDisabled)
+        } else if limits.slippage_budget > 0 && session.slippage_consumed >= limits.slippage_budget {
+            Some(SwapRejectReason::SlippageBudgetExhausted)
+        } else if limits.requires_protection && !venue.protected() {
+            Some(SwapRejectReason::Unprotected)
+        } else if limits.max_trade_lamports > 0 && order.amount_in > limits.max_trade_lamports {
+            Some(SwapRejectReason::TradeLimitExceeded)
+        } else {
+            None
+        };
+        if let Some(reason) = policy {
+            return Err(Rejection::Policy(reason));
+        }
+
+        // Then what the program checks after the swap
+        let held = session.positions.get(&order.output_mint).copied();
+        if held.is_none() && session.positions.len() >= limits.max_positions {
+            return Err(Rejection::PositionLimit);
+        }
+        if limits.max_exposure_bps > 0 {
+            if !limits.priced_mints.contains(&order.output_mint) {
+                return Err(Rejection::NoPriceFeed);
+            }
+            if let Some(price) = prices.get(&order.output_mint) {
+                let position = held.unwrap_or_default() as f64 * price + order.amount_in as f64;
+                let total = (session.balance - order.amount_in + session.deployed) as f64 + position;
+                if position > total * limits.max_exposure_bps as f64 / BPS_DENOMINATOR as f64 {
+                    return Err(Rejection::ExposureCap);
+                }
+            }
+        }
+
+        let local = &self.local;
+        if !local.allowed_mints.is_empty() && !local.allowed_mints.contains(&order.output_mint) {
+            return Err(Rejection::MintNotAllowed);
+        }
+        if let Some(&(last, _)) = self.fills.back() {
+            if local.cooldown > 0 && now < last + local.cooldown {
+                return Err(Rejection::Cooldown);
+            }
+        }
+        if local.max_daily_volume > 0 && self.volume_since(now - SECONDS_PER_DAY) + order.amount_in > local.max_daily_volume
+        {
+            return Err(Rejection::DailyVolume);
+        }
+        Ok(())
+    }
+
+    /// Count a fill at `now` towards the rolling limits.
+    pub fn record(&mut self, fill: &Fill, now: i64) {
+        while self.fills.front().is_some_and(|&(at, _)| at <= now - SECONDS_PER_DAY) {
+            self.fills.pop_front();
+        }
+        self.fills.push_back((now, fill.amount_in));
+    }
+
+    fn volume_since(&self, since: i64) -> u64 {
+        self.fills.iter().filter(|&&(at, _)| at > since).map(|&(_, amount)| amount).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venue::Execution;
+    use crate::AgentError;
+
+    struct Dex(Pubkey);
+
+    impl Venue for Dex {
+        fn dex_program(&self) -> Pubkey {
+            self.0
+        }
+
+        async fn price(&mut self, _mint: &Pubkey) -> Result<f64, AgentError> {
+            unreachable!()
+        }
+
+        async fn execute(&mut self, _session: &Session, _order: &Order) -> Result<Execution, AgentError> {
+            unreachable!()
+        }
+    }
+
+    fn session(dex: Pubkey) -> Session {
+        Session {
+            address: Pubkey::new_unique(),
+            session_id: [1; 16],
+            user: Pubkey::new_unique(),
+            status: VaultStatus::Active,
+            expires_at: 100_000,
+            sol: true,
+            resigned: false,
+            balance: 1_000,
+            deployed: 0,
+            daily_compute_fee: 0,
+            last_compute_deduction: 0,
+            slippage_consumed: 0,
+            positions: Default::default(),
+            limits: Limits {
+                max_trade_lamports: 500,
+                whitelist: vec![dex],
+                max_positions: 1,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn fill(amount_in: u64) -> Fill {
+        Fill {
+            signature: None,
+            output_mint: Pubkey::default(),
+            amount_in,
+            amount_out: 0,
+            fee: 0,
+        }
+    }
+
+    #[test]
+    fn mirrors_the_programs_checks_in_order() {
+        let dex = Dex(Pubkey::new_unique());
+        let mut session = session(dex.0);
+        let risk = Risk::default();
+        let prices = HashMap::new();
+        let mut order = Order {
+            output_mint: Pubkey::new_unique(),
+            amount_in: 400,
+            slippage_bps: 50,
+            memo: None,
+        };
+
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 10), Ok(()));
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 100_000), Err(Rejection::Expired));
+        assert_eq!(
+            risk.check(&session, &order, &Dex(Pubkey::new_unique()), &prices, 10),
+            Err(Rejection::DexNotWhitelisted)
+        );
+
+        // Balance is checked before the per-trade limit
+        order.amount_in = 2_000;
+        assert_eq!(
+            risk.check(&session, &order, &dex, &prices, 10),
+            Err(Rejection::Policy(SwapRejectReason::InsufficientBalance))
+        );
+        order.amount_in = 600;
+        assert_eq!(
+            risk.check(&session, &order, &dex, &prices, 10),
+            Err(Rejection::Policy(SwapRejectReason::TradeLimitExceeded))
+        );
+
+        // Adding to a held mint is fine; a second one is over the cap
+        order.amount_in = 400;
+        session.positions.insert(order.output_mint, 100);
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 10), Ok(()));
+        let mut other = order.clone();
+        other.output_mint = Pubkey::new_unique();
+        assert_eq!(risk.check(&session, &other, &dex, &prices, 10), Err(Rejection::PositionLimit));
+
+        // 100 held at 2 lamports plus 400 bought is 600 of a 1_200 portfolio
+        session.limits.max_exposure_bps = 4_000;
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 10), Err(Rejection::NoPriceFeed));
+        session.limits.priced_mints = vec![order.output_mint];
+        let prices = HashMap::from([(order.output_mint, 2.0)]);
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 10), Err(Rejection::ExposureCap));
+        session.limits.max_exposure_bps = 5_000;
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 10), Ok(()));
+    }
+
+    #[test]
+    fn enforces_local_limits_over_a_rolling_day() {
+        let dex = Dex(Pubkey::new_unique());
+        let session = session(dex.0);
+        let prices = HashMap::new();
+        let order = Order {
+            output_mint: Pubkey::new_unique(),
+            amount_in: 400,
+            slippage_bps: 50,
+            memo: None,
+        };
+
+        let mut risk = Risk::new(LocalLimits {
+            allowed_mints: vec![Pubkey::new_unique()],
+            ..Default::default()
+        });
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 10), Err(Rejection::MintNotAllowed));
+
+        risk.local = LocalLimits {
+            max_daily_volume: 1_000,
+            cooldown: 60,
+            allowed_mints: vec![],
+        };
+        risk.record(&fill(400), 0);
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 30), Err(Rejection::Cooldown));
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 60), Ok(()));
+        risk.record(&fill(400), 60);
+        assert_eq!(risk.check(&session, &order, &dex, &prices, 120), Err(Rejection::DailyVolume));
+        // The first fill rolls off a day later
+        assert_eq!(risk.check(&session, &order, &dex, &prices, SECONDS_PER_DAY), Ok(()));
+    }
+}
@@ -0,0 +1,162 @@
+//! A bot's local view of its session.
+//!
+//! Mirrors the vault's balance, open positions and swap policy so orders can
+//! be checked (see [`risk`](crate::risk)) before they're sent. Fills update it
+//! immediately; [`Session::sync`]
+//! brings it back in line with the chain (compute fee deductions, the user
+//! pausing or changing limits, ...).
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_client::program::{ProtocolConfig, Vault, VaultStatus};
+use gentdex_client::rpc::GentdexRpc;
+use gentdex_client::{pda, ClientError};
+
+use crate::risk::Limits;
+use crate::venue::Fill;
+use crate::SECONDS_PER_DAY;
+
+/// One session as a bot tracks it.
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The vault PDA
+    pub address: Pubkey,
+    pub session_id: [u8; 16],
+    pub user: Pubkey,
+    pub status: VaultStatus,
+    pub expires_at: i64,
+    pub sol: bool,
+    /// The bot gave notice; the program refuses its swaps
+    pub resigned: bool,
+    /// Trading balance, in lamports
+    pub balance: u64,
+    /// SOL in perps collateral and lending, counted in the portfolio value
+    pub deployed: u64,
+    pub daily_compute_fee: u64,
+    pub last_compute_deduction: i64,
+    pub slippage_consumed: u64,
+    /// Raw token amounts bought, by mint. Mints the vault held before the
+    /// bot started tracking it start at 0.
+    pub positions: BTreeMap<Pubkey, u64>,
+    pub limits: Limits,
+}
+
+impl Session {
+    pub fn from_chain(address: Pubkey, vault: &Vault, config: &ProtocolConfig) -> Self {
+        Self {
+            address,
+            session_id: vault.session_id,
+            user: vault.user,
+            status: vault.status,
+            expires_at: vault.expires_at,
+            sol: vault.is_sol_session(),
+            resigned: vault.resigned_at != 0,
+            balance: vault.balance,
+            deployed: vault.perps_collateral.saturating_add(vault.lent_amount),
+            daily_compute_fee: vault.daily_compute_fee,
+            last_compute_deduction: vault.last_compute_deduction,
+            slippage_consumed: vault.slippage_consumed,
+            positions: vault.position_mints.iter().map(|mint| (*mint, 0)).collect(),
+            limits: Limits::from_chain(vault, config),
+        }
+    }
+
+    /// Fetch the vault at `address` and the protocol config.
+    pub async fn load(rpc: &GentdexRpc, address: Pubkey) -> Result<Self, ClientError> {
+        let vault: Vault = rpc.fetch(&address).await?;
+        let config: ProtocolConfig = rpc.fetch(&pda::config_address().0).await?;
+        Ok(Self::from_chain(address, &vault, &config))
+    }
+
+    /// Replace the local state with the chain's, keeping tracked amounts of
+    /// the positions the vault still holds.
+    pub fn sync(&mut self, vault: &Vault, config: &ProtocolConfig) {
+        let mut synced = Self::from_chain(self.address, vault, config);
+        for (mint, amount) in synced.positions.iter_mut() {
+            *amount = self.positions.get(mint).copied().unwrap_or_default();
+        }
+        *self = synced;
+    }
+
+    /// Refetch the vault and config and [`sync`](Self::sync) to them.
+    pub async fn refresh(&mut self, rpc: &GentdexRpc) -> Result<(), ClientError> {
+        let vault: Vault = rpc.fetch(&self.address).await?;
+        let config: ProtocolConfig = rpc.fetch(&pda::config_address().0).await?;
+        self.sync(&vault, &config);
+        Ok(())
+    }
+
+    /// Whether swaps can still be placed at all.
+    pub fn is_tradable(&self, now: i64) -> bool {
+        self.status == VaultStatus::Active && self.sol && now < self.expires_at
+    }
+
+    /// Deduct the compute fee accrued by `now` the way the keeper's crank
+    /// would, returning it. Live sessions pick deductions up on sync instead.
+    pub fn deduct_compute_fee(&mut self, now: i64) -> u64 {
+        let days = (now.min(self.expires_at) - self.last_compute_deduction) / SECONDS_PER_DAY;
+        if days <= 0 {
+            return 0;
+        }
+        let fee = (days as u64).saturating_mul(self.daily_compute_fee).min(self.balance);
+        self.balance -= fee;
+        self.last_compute_deduction += days * SECONDS_PER_DAY;
+        // A deduction that empties the vault expires it
+        if self.balance == 0 {
+            self.status = VaultStatus::Expired;
+        }
+        fee
+    }
+
+    /// Apply a fill ahead of the next sync.
+    pub fn record_fill(&mut self, fill: &Fill) {
+        self.balance = self.balance.saturating_sub(fill.amount_in + fill.fee);
+        *self.positions.entry(fill.output_mint).or_default() += fill.amount_out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountDeserialize, Discriminator};
+    use anchor_spl::token::spl_token::native_mint;
+
+    use super::*;
+
+    fn account<T: AccountDeserialize + Discriminator>() -> T {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.resize(4096, 0);
+        T::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn syncing_keeps_tracked_amounts_of_held_positions() {
+        let mut vault: Vault = account();
+        vault.status = VaultStatus::Active;
+        vault.base_mint = native_mint::ID;
+        vault.balance = 1_000;
+        let config: ProtocolConfig = account();
+        let mut session = Session::from_chain(Pubkey::new_unique(), &vault, &config);
+        assert!(session.sol);
+
+        let (held, sold) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for mint in [held, sold] {
+            session.record_fill(&Fill {
+                signature: None,
+                output_mint: mint,
+                amount_in: 200,
+                amount_out: 7,
+                fee: 0,
+            });
+        }
+        assert_eq!(session.balance, 600);
+        assert_eq!(session.positions[&held], 7);
+
+        vault.position_mints = vec![held];
+        vault.balance = 590;
+        session.sync(&vault, &config);
+        assert_eq!(session.balance, 590);
+        assert_eq!(session.positions.len(), 1);
+        assert_eq!(session.positions[&held], 7);
+    }
+}
@@ -0,0 +1,44 @@
+//! Strategies: the part of a bot that decides what to trade.
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::risk::Rejection;
+use crate::session::Session;
+use crate::venue::Fill;
+
+/// Sell `amount_in` lamports of the session's SOL for `output_mint`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Order {
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    pub slippage_bps: u16,
+    /// Tags the swap's events, e.g. with a strategy or signal id
+    pub memo: Option<[u8; 32]>,
+}
+
+/// What a strategy sees each tick.
+pub struct Tick<'a> {
+    /// Unix time of the tick
+    pub now: i64,
+    pub session: &'a Session,
+    /// Lamports per raw unit of each of the strategy's markets
+    pub prices: &'a HashMap<Pubkey, f64>,
+}
+
+/// Turns market data into orders. The agent checks the orders against the
+/// session before executing them, so a strategy needn't repeat those checks.
+pub trait Strategy {
+    /// Mints the strategy wants priced each tick.
+    fn markets(&self) -> Vec<Pubkey>;
+
+    /// Orders to place this tick, executed in order.
+    fn on_tick(&mut self, tick: &Tick) -> Vec<Order>;
+
+    /// An order filled.
+    fn on_fill(&mut self, _order: &Order, _fill: &Fill) {}
+
+    /// An order was refused, before submission or by the program's policy.
+    fn on_rejected(&mut self, _order: &Order, _rejection: &Rejection) {}
+}
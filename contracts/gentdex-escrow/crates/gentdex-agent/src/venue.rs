@@ -0,0 +1,131 @@
+//! Venues: where an agent's orders are priced and executed.
+
+use std::future::Future;
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_client::events::{self, Event};
+use gentdex_client::jupiter::{self, JupiterClient, JUPITER_PROGRAM_ID};
+use gentdex_client::program::SwapRejectReason;
+use gentdex_client::rpc::GentdexRpc;
+use solana_keypair::Keypair;
+
+use crate::session::Session;
+use crate::strategy::Order;
+use crate::AgentError;
+
+/// Slippage allowed on the quotes used for pricing, which are never executed
+const PRICE_QUOTE_SLIPPAGE_BPS: u16 = 50;
+
+/// An executed order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fill {
+    /// The swap's transaction, `None` for simulated fills
+    pub signature: Option<String>,
+    pub output_mint: Pubkey,
+    /// Lamports sold
+    pub amount_in: u64,
+    /// Raw token units bought
+    pub amount_out: u64,
+    /// Lamports charged on top of `amount_in`
+    pub fee: u64,
+}
+
+/// What became of an order a venue was given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Execution {
+    Filled(Fill),
+    /// The program's policy rejected it (the transaction still landed)
+    Rejected(SwapRejectReason),
+}
+
+/// Prices markets and executes orders for one session.
+pub trait Venue {
+    /// The DEX program orders are routed through, for the session's whitelist.
+    fn dex_program(&self) -> Pubkey;
+
+    /// Whether the venue's swaps carry a recent quote slot or a Jito tip.
+    fn protected(&self) -> bool {
+        false
+    }
+
+    /// Lamports per raw unit of `mint`.
+    fn price(&mut self, mint: &Pubkey) -> impl Future<Output = Result<f64, AgentError>>;
+
+    /// Execute `order` from `session`, which it has already passed the
+    /// session's checks.
+    fn execute(&mut self, session: &Session, order: &Order) -> impl Future<Output = Result<Execution, AgentError>>;
+}
+
+/// Trades live through Jupiter, signing as the session's bot.
+pub struct JupiterVenue<'a> {
+    rpc: &'a GentdexRpc,
+    jupiter: JupiterClient,
+    bot: Keypair,
+    /// Lamports quoted when pricing a market
+    probe_lamports: u64,
+}
+
+impl<'a> JupiterVenue<'a> {
+    /// Price markets by quoting `probe_lamports` of SOL for them.
+    pub fn new(rpc: &'a GentdexRpc, jupiter: JupiterClient, bot: Keypair, probe_lamports: u64) -> Self {
+        Self {
+            rpc,
+            jupiter,
+            bot,
+            probe_lamports,
+        }
+    }
+}
+
+impl Venue for JupiterVenue<'_> {
+    fn dex_program(&self) -> Pubkey {
+        JUPITER_PROGRAM_ID
+    }
+
+    async fn price(&mut self, mint: &Pubkey) -> Result<f64, AgentError> {
+        let quote = self.jupiter.quote(mint, self.probe_lamports, PRICE_QUOTE_SLIPPAGE_BPS).await?;
+        if quote.out_amount == 0 {
+            return Err(AgentError::NoPrice(*mint));
+        }
+        Ok(quote.in_amount as f64 / quote.out_amount as f64)
+    }
+
+    async fn execute(&mut self, session: &Session, order: &Order) -> Result<Execution, AgentError> {
+        let (tx, last_valid_block_height) = jupiter::swap_transaction(
+            self.rpc,
+            &self.jupiter,
+            &self.bot,
+            session.address,
+            &order.output_mint,
+            order.amount_in,
+            order.slippage_bps,
+            order.memo,
+        )
+        .await?;
+        let signature = self.rpc.send_and_confirm_versioned(&tx, last_valid_block_height).await?;
+
+        let logs = self
+            .rpc
+            .get_transaction(&signature)
+            .await?
+            .ok_or_else(|| AgentError::MissingEvent(signature.clone()))?;
+        for event in events::parse_logs(&logs.logs) {
+            match event {
+                Event::SwapExecuted(e) if e.session_id == session.session_id => {
+                    return Ok(Execution::Filled(Fill {
+                        signature: Some(signature),
+                        output_mint: e.output_mint,
+                        amount_in: e.amount_in,
+                        amount_out: e.amount_out,
+                        fee: 0,
+                    }));
+                }
+                Event::SwapRejected(e) if e.session_id == session.session_id => {
+                    return Ok(Execution::Rejected(e.reason));
+                }
+                _ => {}
+            }
+        }
+        Err(AgentError::MissingEvent(signature))
+    }
+}
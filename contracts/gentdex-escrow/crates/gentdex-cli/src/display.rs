@@ -0,0 +1,141 @@
+//! Human units for terminal output: SOL, dates and durations.
+
+use chrono::DateTime;
+use gentdex_client::program::{Vault, VaultStatus};
+
+use crate::CliError;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// `lamports` as SOL, without trailing zeros: `1.5 SOL`.
+pub fn sol(lamports: u64) -> String {
+    format!("{} SOL", sol_decimal(lamports))
+}
+
+/// `lamports` as a bare SOL decimal: `1.5`.
+pub fn sol_decimal(lamports: u64) -> String {
+    let fraction = format!("{:09}", lamports % LAMPORTS_PER_SOL);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (lamports / LAMPORTS_PER_SOL).to_string()
+    } else {
+        format!("{}.{fraction}", lamports / LAMPORTS_PER_SOL)
+    }
+}
+
+/// Parse a SOL amount like `1.5` into lamports.
+pub fn parse_sol(amount: &str) -> Result<u64, CliError> {
+    let invalid = || CliError::Invalid(format!("invalid SOL amount {amount:?}"));
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > 9 || (whole.is_empty() && fraction.is_empty()) {
+        return Err(invalid());
+    }
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<9}").parse().map_err(|_| invalid())?
+    };
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+/// An amount in the session's base currency: SOL, or raw units of its mint.
+pub fn amount(vault: &Vault, units: u64) -> String {
+    if vault.is_sol_session() {
+        sol(units)
+    } else {
+        format!("{units} units of {}", vault.base_mint)
+    }
+}
+
+/// Seconds since the unix epoch, by the local clock.
+pub fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// A unix timestamp in UTC, or `-` for unset (0) timestamps.
+pub fn date(timestamp: i64) -> String {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(date) if timestamp != 0 => date.format("%Y-%m-%d %H:%M UTC").to_string(),
+        _ => "-".to_string(),
+    }
+}
+
+/// A duration in its two largest units: `3d 4h`, `5h 12m`, `40m`.
+pub fn duration(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Print `rows` under `headers` in left-aligned, space-padded columns.
+pub fn table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    for row in rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+pub fn status(status: VaultStatus) -> &'static str {
+    match status {
+        VaultStatus::Pending => "Pending",
+        VaultStatus::Active => "Active",
+        VaultStatus::Paused => "Paused",
+        VaultStatus::Expired => "Expired",
+        VaultStatus::Withdrawn => "Withdrawn",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_sol_and_lamports() {
+        assert_eq!(parse_sol("1.5").unwrap(), 1_500_000_000);
+        assert_eq!(parse_sol("0.000000001").unwrap(), 1);
+        assert_eq!(parse_sol(".25").unwrap(), 250_000_000);
+        assert_eq!(parse_sol("2").unwrap(), 2 * LAMPORTS_PER_SOL);
+        assert!(parse_sol("0.0000000001").is_err());
+        assert!(parse_sol("1.2.3").is_err());
+        assert!(parse_sol(".").is_err());
+        assert!(parse_sol("99999999999999").is_err());
+
+        assert_eq!(sol(1_500_000_000), "1.5 SOL");
+        assert_eq!(sol(2 * LAMPORTS_PER_SOL), "2 SOL");
+        assert_eq!(sol(1), "0.000000001 SOL");
+    }
+
+    #[test]
+    fn formats_durations_in_two_units() {
+        assert_eq!(duration(3 * 86_400 + 4 * 3_600 + 59), "3d 4h");
+        assert_eq!(duration(5 * 3_600 + 12 * 60), "5h 12m");
+        assert_eq!(duration(40 * 60), "40m");
+        assert_eq!(duration(-5), "0m");
+    }
+}
@@ -0,0 +1,244 @@
+//! Session history export.
+//!
+//! Walks a vault's signature history, decodes the GentDex events of each
+//! successful transaction and keeps the ones that move the session's money:
+//! deposits, swaps, compute fees, withdrawals and transfers between sessions.
+//! Amounts are SOL decimals for SOL sessions and raw base-mint units
+//! otherwise; `currency` says which. Swap output is in raw units of
+//! `output_mint`.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use chrono::{DateTime, SecondsFormat};
+use clap::ValueEnum;
+use gentdex_client::events::Event;
+use gentdex_client::program::Vault;
+use gentdex_client::statement;
+use serde::Serialize;
+
+use crate::display;
+use crate::{CliError, Context};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Record {
+    pub time: String,
+    pub signature: String,
+    pub slot: u64,
+    pub kind: &'static str,
+    pub amount: String,
+    pub fee: String,
+    pub currency: String,
+    pub output_mint: String,
+    pub output_amount: String,
+    pub dex_program: String,
+}
+
+const CSV_HEADER: &str = "time,signature,slot,kind,amount,fee,currency,output_mint,output_amount,dex_program";
+
+impl Record {
+    /// No field can contain a comma or quote, so none needs escaping.
+    fn csv_row(&self) -> String {
+        [
+            self.time.as_str(),
+            &self.signature,
+            &self.slot.to_string(),
+            self.kind,
+            &self.amount,
+            &self.fee,
+            &self.currency,
+            &self.output_mint,
+            &self.output_amount,
+            &self.dex_program,
+        ]
+        .join(",")
+    }
+}
+
+/// The transaction a record comes from.
+struct Source<'a> {
+    signature: &'a str,
+    slot: u64,
+    time: String,
+}
+
+/// Write `vault_address`'s history, oldest first, to `output` or stdout.
+pub async fn export(
+    ctx: &Context,
+    vault_address: Pubkey,
+    format: Format,
+    output: Option<PathBuf>,
+) -> Result<(), CliError> {
+    let vault: Vault = ctx.rpc().fetch(&vault_address).await?;
+
+    let history = statement::history(ctx.rpc(), &vault_address).await?;
+    for signature in &history.unavailable {
+        eprintln!("warning: {signature} is no longer available from this node; skipped");
+    }
+
+    let mut records = Vec::new();
+    for tx in &history.transactions {
+        let source = Source {
+            signature: &tx.signature,
+            slot: tx.slot,
+            time: tx.block_time.map(timestamp).unwrap_or_default(),
+        };
+        records.extend(session_records(&vault, &source, &tx.events));
+    }
+
+    let target = output.as_ref().map_or("stdout".to_string(), |path| path.display().to_string());
+    let write_error = |err: io::Error| CliError::Invalid(format!("writing {target}: {err}"));
+    let mut out: Box<dyn Write> = match &output {
+        Some(path) => Box::new(File::create(path).map_err(write_error)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let written = match format {
+        Format::Csv => write_csv(&mut out, &records),
+        Format::Json => serde_json::to_writer_pretty(&mut out, &records)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(out)),
+    };
+    written.map_err(write_error)?;
+    if output.is_some() {
+        eprintln!("Wrote {} records to {target}", records.len());
+    }
+    Ok(())
+}
+
+fn write_csv(out: &mut impl Write, records: &[Record]) -> io::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    for record in records {
+        writeln!(out, "{}", record.csv_row())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn timestamp(time: i64) -> String {
+    DateTime::from_timestamp(time, 0)
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// Records for the events in one transaction that belong to `vault`.
+fn session_records(vault: &Vault, source: &Source, events: &[Event]) -> Vec<Record> {
+    let units = |amount: u64| {
+        if vault.is_sol_session() {
+            display::sol_decimal(amount)
+        } else {
+            amount.to_string()
+        }
+    };
+    let record = |kind, amount, fee| Record {
+        time: source.time.clone(),
+        signature: source.signature.to_string(),
+        slot: source.slot,
+        kind,
+        amount: units(amount),
+        fee: units(fee),
+        currency: if vault.is_sol_session() { "SOL".to_string() } else { vault.base_mint.to_string() },
+        output_mint: String::new(),
+        output_amount: String::new(),
+        dex_program: String::new(),
+    };
+
+    let session = vault.session_id;
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Deposited(e) if e.session_id == session => Some(record("deposit", e.amount, e.fee)),
+            Event::SwapExecuted(e) if e.session_id == session => Some(Record {
+                output_mint: e.output_mint.to_string(),
+                output_amount: e.amount_out.to_string(),
+                dex_program: e.dex_program.to_string(),
+                ..record("swap", e.amount_in, 0)
+            }),
+            Event::ComputeFeeDeducted(e) if e.session_id == session => Some(record("compute_fee", 0, e.fee)),
+            Event::Withdrawn(e) if e.session_id == session => Some(record("withdraw", e.amount, e.compute_fee)),
+            Event::SessionTransferred(e) if e.source_session_id == session => {
+                Some(record("transfer_out", e.amount, e.compute_fee))
+            }
+            Event::SessionTransferred(e) if e.destination_session_id == session => {
+                Some(record("transfer_in", e.amount, 0))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountDeserialize, Discriminator};
+    use anchor_spl::token::spl_token::native_mint;
+    use gentdex_client::program::{Deposited, SwapExecuted, Withdrawn};
+
+    use super::*;
+
+    fn vault(session_id: [u8; 16]) -> Vault {
+        // Build from zeroed account data, then set what the export reads
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(Vault::DISCRIMINATOR.len() + 2048, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.session_id = session_id;
+        vault.base_mint = native_mint::ID;
+        vault
+    }
+
+    #[test]
+    fn keeps_the_sessions_money_movements() {
+        let session_id = [7; 16];
+        let dex = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let events = vec![
+            Event::Deposited(Deposited {
+                session_id,
+                vault: Pubkey::new_unique(),
+                amount: 1_000_000_000,
+                fee: 10_000_000,
+                trading_balance: 990_000_000,
+                expires_at: 0,
+            }),
+            Event::SwapExecuted(SwapExecuted {
+                session_id,
+                vault: Pubkey::new_unique(),
+                bot: Pubkey::new_unique(),
+                dex_program: dex,
+                amount_in: 500_000_000,
+                minimum_amount_out: 0,
+                timestamp: 0,
+                output_mint: mint,
+                amount_out: 75_000_000,
+                memo: [0; 32],
+            }),
+            // Another session's withdrawal in the same transaction
+            Event::Withdrawn(Withdrawn {
+                session_id: [8; 16],
+                vault: Pubkey::new_unique(),
+                amount: 1,
+                compute_fee: 0,
+                user: Pubkey::new_unique(),
+            }),
+        ];
+        let source = Source {
+            signature: "sig",
+            slot: 42,
+            time: timestamp(1_700_000_000),
+        };
+
+        let records = session_records(&vault(session_id), &source, &events);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].csv_row(), "2023-11-14T22:13:20Z,sig,42,deposit,1,0.01,SOL,,,");
+        assert_eq!(records[1].kind, "swap");
+        assert_eq!(records[1].amount, "0.5");
+        assert_eq!(records[1].output_mint, mint.to_string());
+        assert_eq!(records[1].output_amount, "75000000");
+        assert_eq!(records[1].dex_program, dex.to_string());
+    }
+}
@@ -0,0 +1,33 @@
+//! Signing with a Ledger running the Solana app.
+
+use gentdex_client::signer::LedgerSigner;
+
+use crate::CliError;
+
+/// Connect to the Ledger key `ACCOUNT` or `ACCOUNT/CHANGE`, i.e.
+/// `m/44'/501'/ACCOUNT'[/CHANGE']`, the same keys `solana-keygen` reads
+/// from `usb://ledger?key=...`.
+pub fn connect(key: &str) -> Result<LedgerSigner, CliError> {
+    let (account, change) = parse_key(key).ok_or_else(|| CliError::LedgerKey(key.to_string()))?;
+    Ok(LedgerSigner::connect(account, change)?)
+}
+
+fn parse_key(key: &str) -> Option<(u32, Option<u32>)> {
+    match key.split_once('/') {
+        Some((account, change)) => Some((account.parse().ok()?, Some(change.parse().ok()?))),
+        None => Some((key.parse().ok()?, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keygen_style_keys() {
+        assert_eq!(parse_key("0"), Some((0, None)));
+        assert_eq!(parse_key("2/1"), Some((2, Some(1))));
+        assert_eq!(parse_key("x"), None);
+        assert_eq!(parse_key("1/"), None);
+    }
+}
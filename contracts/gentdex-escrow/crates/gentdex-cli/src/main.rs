@@ -0,0 +1,264 @@
+//! `gentdex-cli`: open, fund, pause, extend and wind down GentDex sessions
+//! from the terminal, signing with a local keypair file or a Ledger (feature
+//! `ledger`), and inspect sessions and transactions.
+
+mod display;
+mod export;
+#[cfg(feature = "ledger")]
+mod ledger;
+mod session;
+mod statement;
+mod tx;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use clap::{Parser, Subcommand};
+use gentdex_client::cluster::Cluster;
+use gentdex_client::rpc::GentdexRpc;
+use gentdex_client::signer::WalletSigner;
+use gentdex_client::{ClientError, ProgramError};
+use solana_keypair::read_keypair_file;
+
+#[derive(Parser)]
+#[command(name = "gentdex-cli", version, about = "Manage GentDex trading sessions")]
+struct Cli {
+    /// JSON-RPC endpoint
+    #[arg(long, short = 'u', global = true, env = "GENTDEX_RPC_URL", default_value = Cluster::BUILD.default_rpc_url())]
+    url: String,
+    /// Keypair file that signs and pays [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', global = true, env = "GENTDEX_KEYPAIR")]
+    keypair: Option<PathBuf>,
+    /// Sign with the Ledger key at m/44'/501'/ACCOUNT'[/CHANGE'] instead [default: 0]
+    #[cfg(feature = "ledger")]
+    #[arg(
+        long,
+        global = true,
+        value_name = "ACCOUNT[/CHANGE]",
+        num_args = 0..=1,
+        default_missing_value = "0"
+    )]
+    ledger: Option<String>,
+    /// Commitment to read and confirm at
+    #[arg(long, global = true, default_value = "confirmed")]
+    commitment: String,
+    /// Simulate instead of sending, and print the logs
+    #[arg(long, global = true)]
+    dry_run: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Open a new session; prints its vault address
+    Init {
+        /// Session length in days
+        #[arg(long)]
+        days: u16,
+        /// Bot key allowed to trade the session
+        #[arg(long)]
+        bot: Pubkey,
+    },
+    /// Fund a pending session
+    Deposit {
+        vault: Pubkey,
+        /// Amount in SOL
+        amount: String,
+    },
+    /// Stop the bot from trading
+    Pause { vault: Pubkey },
+    /// Let the bot trade again
+    Resume { vault: Pubkey },
+    /// Push a session's expiry back
+    Extend {
+        vault: Pubkey,
+        /// Days to add
+        days: u16,
+    },
+    /// Withdraw the session's balance, settling accrued compute fees
+    Withdraw { vault: Pubkey },
+    /// Withdraw the session's balance and close the vault, reclaiming its rent
+    Close { vault: Pubkey },
+    /// Show a session's state
+    Status { vault: Pubkey },
+    /// List every session a wallet owns
+    Sessions {
+        /// Wallet that owns the sessions
+        user: Pubkey,
+    },
+    /// Print the GentDex events a transaction emitted
+    DecodeTx {
+        signature: String,
+        /// Also print the transaction's log messages
+        #[arg(long)]
+        logs: bool,
+    },
+    /// Export a session's deposits, swaps, fees and withdrawals
+    Export {
+        /// The session's vault address
+        #[arg(long)]
+        session: Pubkey,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: export::Format,
+        /// File to write instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Summarize a session: deposits, fees, trades, realized PnL and payout
+    Statement {
+        /// The session's vault address
+        #[arg(long)]
+        session: Pubkey,
+        #[arg(long, value_enum, default_value = "text")]
+        format: statement::Format,
+        /// File to write instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Export a session's cost-basis lots for tax reporting
+    Lots {
+        /// The session's vault address
+        #[arg(long)]
+        session: Pubkey,
+        /// Which lots a disposal uses up first
+        #[arg(long, value_enum, default_value = "fifo")]
+        method: statement::LotMethod,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: statement::LotFormat,
+        /// File to write instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+    #[error("could not read keypair {path}: {message}")]
+    Keypair { path: PathBuf, message: String },
+    #[cfg(feature = "ledger")]
+    #[error("invalid Ledger key {0}; expected ACCOUNT or ACCOUNT/CHANGE")]
+    LedgerKey(String),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// Everything a command needs: the RPC client and, once asked for, the signer.
+pub struct Context {
+    rpc: GentdexRpc,
+    keypair_path: PathBuf,
+    #[cfg(feature = "ledger")]
+    ledger: Option<String>,
+    dry_run: bool,
+    signer: Option<Box<dyn WalletSigner>>,
+}
+
+impl Context {
+    pub fn rpc(&self) -> &GentdexRpc {
+        &self.rpc
+    }
+
+    /// The signer, loaded on first use so read-only commands don't need one.
+    pub fn signer(&mut self) -> Result<&dyn WalletSigner, CliError> {
+        if self.signer.is_none() {
+            self.signer = Some(self.load_signer()?);
+        }
+        Ok(self.signer.as_deref().expect("signer was just loaded"))
+    }
+
+    fn load_signer(&self) -> Result<Box<dyn WalletSigner>, CliError> {
+        #[cfg(feature = "ledger")]
+        if let Some(key) = &self.ledger {
+            return Ok(Box::new(ledger::connect(key)?));
+        }
+        let keypair = read_keypair_file(&self.keypair_path).map_err(|err| CliError::Keypair {
+            path: self.keypair_path.clone(),
+            message: err.to_string(),
+        })?;
+        Ok(Box::new(keypair))
+    }
+
+    /// Send `instructions` signed by the keypair or Ledger, or simulate them
+    /// with `--dry-run`.
+    pub async fn submit(&mut self, instructions: &[Instruction]) -> Result<(), CliError> {
+        self.signer()?;
+        let signer = self.signer.as_deref().expect("signer was just loaded");
+        if self.dry_run {
+            let simulation = self.rpc.simulate(instructions, &signer.pubkey()).await?;
+            for line in &simulation.logs {
+                println!("  {line}");
+            }
+            match simulation.units_consumed {
+                Some(units) => println!("Simulation succeeded ({units} compute units)"),
+                None => println!("Simulation succeeded"),
+            }
+            return Ok(());
+        }
+        // A devnet build's transactions mean nothing on mainnet, and the reverse
+        self.rpc.check_cluster().await?;
+        let signature = self.rpc.send_and_confirm_with(instructions, &[signer]).await?;
+        println!("Signature {signature}");
+        Ok(())
+    }
+}
+
+fn default_keypair_path() -> PathBuf {
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".config/solana/id.json")
+}
+
+async fn run(cli: Cli) -> Result<(), CliError> {
+    let mut ctx = Context {
+        rpc: GentdexRpc::new(cli.url).with_commitment(cli.commitment),
+        keypair_path: cli.keypair.unwrap_or_else(default_keypair_path),
+        #[cfg(feature = "ledger")]
+        ledger: cli.ledger,
+        dry_run: cli.dry_run,
+        signer: None,
+    };
+
+    match cli.command {
+        Command::Init { days, bot } => session::init(&mut ctx, days, bot).await,
+        Command::Deposit { vault, amount } => session::deposit(&mut ctx, vault, &amount).await,
+        Command::Pause { vault } => session::pause(&mut ctx, vault).await,
+        Command::Resume { vault } => session::resume(&mut ctx, vault).await,
+        Command::Extend { vault, days } => session::extend(&mut ctx, vault, days).await,
+        Command::Withdraw { vault } => session::withdraw(&mut ctx, vault, false).await,
+        Command::Close { vault } => session::withdraw(&mut ctx, vault, true).await,
+        Command::Status { vault } => session::status(&ctx, vault).await,
+        Command::Sessions { user } => session::list(&ctx, user).await,
+        Command::DecodeTx { signature, logs } => tx::decode(&ctx, &signature, logs).await,
+        Command::Export { session, format, output } => export::export(&ctx, session, format, output).await,
+        Command::Statement { session, format, output } => {
+            statement::statement(&ctx, session, format, output).await
+        }
+        Command::Lots { session, method, format, output } => {
+            statement::lots(&ctx, session, method, format, output).await
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            if let CliError::Client(ClientError::Program { error, logs, .. }) = &err {
+                if let Some(hint) = error.hint() {
+                    eprintln!("hint: {hint}");
+                }
+                if matches!(error, ProgramError::Other { .. }) {
+                    for line in logs {
+                        eprintln!("  {line}");
+                    }
+                }
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Session lifecycle commands.
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_client::program::{accrued_compute_fee, ProtocolConfig, SessionSummary, UserRegistry, Vault};
+use gentdex_client::{instructions, pda, ClientError};
+
+use crate::display;
+use crate::{CliError, Context};
+
+/// Open the signer's next indexed session, so it can be found again from the
+/// wallet alone.
+pub async fn init(ctx: &mut Context, days: u16, bot: Pubkey) -> Result<(), CliError> {
+    let user = ctx.signer()?.pubkey();
+    let config: ProtocolConfig = ctx.rpc().fetch(&pda::config_address().0).await?;
+    let index = match ctx.rpc().fetch::<UserRegistry>(&pda::registry_address(&user).0).await {
+        Ok(registry) => registry.session_count,
+        Err(ClientError::AccountNotFound(_)) => 0,
+        Err(err) => return Err(err.into()),
+    };
+
+    let (ix, vault) = instructions::initialize_indexed(user, config.treasury, index, days, bot, user);
+    ctx.submit(&[ix]).await?;
+    println!("Session {vault} ({days} days, bot {bot})");
+    Ok(())
+}
+
+pub async fn deposit(ctx: &mut Context, vault_address: Pubkey, amount: &str) -> Result<(), CliError> {
+    let lamports = display::parse_sol(amount)?;
+    let user = ctx.signer()?.pubkey();
+    let vault: Vault = ctx.rpc().fetch(&vault_address).await?;
+    if !vault.is_sol_session() {
+        return Err(CliError::Invalid(format!("{vault_address} is a token session; deposit {}", vault.base_mint)));
+    }
+
+    let template = (vault.template != Pubkey::default()).then_some(vault.template);
+    let verified_bot = vault.verified_bot.then_some(vault.bot);
+    let ix = instructions::deposit(user, vault_address, vault.treasury, lamports, template, verified_bot);
+    ctx.submit(&[ix]).await?;
+    println!("Deposited {}", display::sol(lamports));
+    Ok(())
+}
+
+pub async fn pause(ctx: &mut Context, vault: Pubkey) -> Result<(), CliError> {
+    let user = ctx.signer()?.pubkey();
+    ctx.submit(&[instructions::pause(user, vault)]).await
+}
+
+pub async fn resume(ctx: &mut Context, vault: Pubkey) -> Result<(), CliError> {
+    let user = ctx.signer()?.pubkey();
+    ctx.submit(&[instructions::resume(user, vault)]).await
+}
+
+pub async fn extend(ctx: &mut Context, vault_address: Pubkey, days: u16) -> Result<(), CliError> {
+    let user = ctx.signer()?.pubkey();
+    let vault: Vault = ctx.rpc().fetch(&vault_address).await?;
+    ctx.submit(&[instructions::extend_session(user, vault_address, days)]).await?;
+    let expires_at = vault.expires_at.saturating_add(days as i64 * 86_400);
+    println!("Extended by {days} days, to {}", display::date(expires_at));
+    Ok(())
+}
+
+pub async fn withdraw(ctx: &mut Context, vault_address: Pubkey, close: bool) -> Result<(), CliError> {
+    let user = ctx.signer()?.pubkey();
+    let vault: Vault = ctx.rpc().fetch(&vault_address).await?;
+    let withdraw = if close { instructions::withdraw_and_close } else { instructions::withdraw };
+    ctx.submit(&[withdraw(user, vault_address, vault.treasury, vault.bot, user)]).await?;
+    println!("Withdrew {}", display::amount(&vault, vault.balance));
+    if close {
+        println!("Closed {vault_address}");
+    }
+    Ok(())
+}
+
+pub async fn status(ctx: &Context, vault_address: Pubkey) -> Result<(), CliError> {
+    let vault: Vault = ctx.rpc().fetch(&vault_address).await?;
+    // Simulated with the owner as payer; unavailable if their wallet is empty
+    let summary: Option<SessionSummary> = ctx
+        .rpc()
+        .view(instructions::get_session_summary(vault_address), &vault.user)
+        .await
+        .ok();
+
+    println!("Session      {vault_address}");
+    println!("Status       {}", display::status(vault.status));
+    println!("Owner        {}", vault.user);
+    println!("Bot          {}", vault.bot);
+    println!("Balance      {}", display::amount(&vault, vault.balance));
+    if let Some(summary) = &summary {
+        println!("Value        {}", display::amount(&vault, summary.value));
+        println!("Accrued fee  {}", display::amount(&vault, summary.accrued_compute_fee));
+        println!("Drawdown     {}", display::amount(&vault, summary.drawdown));
+    }
+    println!("Fees paid    {}", display::amount(&vault, vault.fee_collected.saturating_add(vault.compute_fees_paid)));
+    println!("Volume       {}", display::sol(vault.total_volume));
+    println!("Created      {}", display::date(vault.created_at));
+    println!("Funded       {}", display::date(vault.funded_at));
+    match summary {
+        Some(summary) if summary.seconds_remaining > 0 => println!(
+            "Expires      {} ({} left)",
+            display::date(vault.expires_at),
+            display::duration(summary.seconds_remaining)
+        ),
+        _ => println!("Expires      {}", display::date(vault.expires_at)),
+    }
+    Ok(())
+}
+
+/// Every session `user` owns, oldest first. Accrued fees are computed
+/// against the local clock.
+pub async fn list(ctx: &Context, user: Pubkey) -> Result<(), CliError> {
+    let mut vaults = ctx.rpc().vaults_by_user(&user).await?;
+    if vaults.is_empty() {
+        println!("No sessions for {user}");
+        return Ok(());
+    }
+    vaults.sort_by_key(|(_, vault)| vault.created_at);
+
+    let now = display::now();
+    let rows: Vec<Vec<String>> = vaults
+        .iter()
+        .map(|(address, vault)| {
+            let accrued = accrued_compute_fee(vault, now).map_or(0, |(_, fee)| fee);
+            vec![
+                address.to_string(),
+                display::status(vault.status).to_string(),
+                display::amount(vault, vault.balance),
+                display::date(vault.expires_at),
+                display::amount(vault, accrued),
+            ]
+        })
+        .collect();
+    display::table(&["VAULT", "STATUS", "BALANCE", "EXPIRES", "ACCRUED FEE"], &rows);
+    Ok(())
+}
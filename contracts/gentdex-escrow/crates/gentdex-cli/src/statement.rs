@@ -0,0 +1,285 @@
+//! End-of-session statements and cost-basis lots (see
+//! `gentdex_client::statement` and `gentdex_client::lots`).
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use clap::ValueEnum;
+use gentdex_client::events::Event;
+use gentdex_client::lots::{LotReport, Method};
+use gentdex_client::program::Vault;
+use gentdex_client::statement::{self, SessionTransaction, Statement};
+
+use crate::display;
+use crate::export::timestamp;
+use crate::{CliError, Context};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Write `vault_address`'s statement to `output` or stdout.
+pub async fn statement(
+    ctx: &Context,
+    vault_address: Pubkey,
+    format: Format,
+    output: Option<PathBuf>,
+) -> Result<(), CliError> {
+    let vault: Vault = ctx.rpc().fetch(&vault_address).await?;
+    let history = statement::history(ctx.rpc(), &vault_address).await?;
+    if !history.unavailable.is_empty() {
+        eprintln!(
+            "warning: {} transactions are no longer available from this node; the statement leaves them out",
+            history.unavailable.len()
+        );
+    }
+
+    let statement = Statement::build(&vault_address, &vault, &history.transactions);
+    let rendered = match format {
+        Format::Text => statement.to_text(),
+        Format::Json => serde_json::to_string_pretty(&statement).expect("statements serialize") + "\n",
+    };
+    let written = match &output {
+        Some(path) => fs::write(path, rendered),
+        None => io::stdout().lock().write_all(rendered.as_bytes()),
+    };
+    let target = output.as_ref().map_or("stdout".to_string(), |path| path.display().to_string());
+    written.map_err(|err| CliError::Invalid(format!("writing {target}: {err}")))?;
+    if output.is_some() {
+        eprintln!("Wrote the statement to {target}");
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+}
+
+impl From<LotMethod> for Method {
+    fn from(method: LotMethod) -> Self {
+        match method {
+            LotMethod::Fifo => Method::Fifo,
+            LotMethod::Lifo => Method::Lifo,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LotFormat {
+    /// One row per disposal, then one per open lot
+    Csv,
+    Json,
+    /// Sent/received rows in the universal CSV layout tax tools import
+    Universal,
+}
+
+const LOTS_HEADER: &str =
+    "kind,asset,amount,cost_basis,currency,acquired,acquired_signature,disposed,disposed_signature,term";
+const UNIVERSAL_HEADER: &str = "Date,Sent Amount,Sent Currency,Received Amount,Received Currency,Fee Amount,Fee Currency,\
+Net Worth Amount,Net Worth Currency,Label,Description,TxHash";
+
+/// Write `vault_address`'s cost-basis lots, matched with `method`, to
+/// `output` or stdout.
+pub async fn lots(
+    ctx: &Context,
+    vault_address: Pubkey,
+    method: LotMethod,
+    format: LotFormat,
+    output: Option<PathBuf>,
+) -> Result<(), CliError> {
+    let vault: Vault = ctx.rpc().fetch(&vault_address).await?;
+    let history = statement::history(ctx.rpc(), &vault_address).await?;
+    if !history.unavailable.is_empty() {
+        eprintln!(
+            "warning: {} transactions are no longer available from this node; their lots are missing",
+            history.unavailable.len()
+        );
+    }
+
+    let method = Method::from(method);
+    let rendered = match format {
+        LotFormat::Csv => lots_csv(&vault, &LotReport::build(&vault, &history.transactions, method)),
+        LotFormat::Json => {
+            let report = LotReport::build(&vault, &history.transactions, method);
+            serde_json::to_string_pretty(&report).expect("lot reports serialize") + "\n"
+        }
+        LotFormat::Universal => universal_csv(&vault, &history.transactions),
+    };
+    let written = match &output {
+        Some(path) => fs::write(path, rendered),
+        None => io::stdout().lock().write_all(rendered.as_bytes()),
+    };
+    let target = output.as_ref().map_or("stdout".to_string(), |path| path.display().to_string());
+    written.map_err(|err| CliError::Invalid(format!("writing {target}: {err}")))?;
+    if output.is_some() {
+        eprintln!("Wrote lots to {target}");
+    }
+    Ok(())
+}
+
+/// The session currency as a decimal for SOL sessions, else raw units.
+fn units(vault: &Vault, asset: &str, amount: u64) -> String {
+    if asset == "SOL" && vault.is_sol_session() {
+        display::sol_decimal(amount)
+    } else {
+        amount.to_string()
+    }
+}
+
+fn lots_csv(vault: &Vault, report: &LotReport) -> String {
+    let time = |time: Option<i64>| time.map(timestamp).unwrap_or_default();
+    let mut out = format!("{LOTS_HEADER}\n");
+    for d in &report.disposals {
+        let term = if d.acquired_at.is_none() { "" } else if d.long_term { "long" } else { "short" };
+        let kind = serde_json::to_value(d.kind).expect("kinds serialize");
+        let row = [
+            kind.as_str().unwrap_or_default(),
+            &d.asset,
+            &units(vault, &d.asset, d.amount),
+            &units(vault, "SOL", d.cost_basis),
+            &report.currency,
+            &time(d.acquired_at),
+            d.acquired_signature.as_deref().unwrap_or_default(),
+            &time(d.disposed_at),
+            &d.disposed_signature,
+            term,
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    for lot in &report.open {
+        let row = [
+            "open",
+            &lot.asset,
+            &units(vault, &lot.asset, lot.amount),
+            &units(vault, "SOL", lot.cost_basis),
+            &report.currency,
+            &time(lot.acquired_at),
+            &lot.acquired_signature,
+            "",
+            "",
+            "",
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// The session's trades, fees and transfers as sent/received rows. Tokens
+/// appear by mint in raw units; deposits and withdrawals carry no label, so
+/// tax tools can match them with the user's wallet as transfers.
+fn universal_csv(vault: &Vault, history: &[SessionTransaction]) -> String {
+    let currency = if vault.is_sol_session() { "SOL".to_string() } else { vault.base_mint.to_string() };
+    let base = |amount: u64| units(vault, "SOL", amount);
+    let session = vault.session_id;
+    let mut out = format!("{UNIVERSAL_HEADER}\n");
+    for tx in history {
+        let date = tx.block_time.map(timestamp).unwrap_or_default();
+        let mut row = |sent: Option<(String, &str)>, received: Option<(String, &str)>, fee: u64, label, description| {
+            let (sent_amount, sent_currency) = sent.unwrap_or_default();
+            let (received_amount, received_currency) = received.unwrap_or_default();
+            let (fee_amount, fee_currency) = if fee > 0 { (base(fee), currency.as_str()) } else { (String::new(), "") };
+            let row = [
+                date.as_str(),
+                &sent_amount,
+                sent_currency,
+                &received_amount,
+                received_currency,
+                &fee_amount,
+                fee_currency,
+                "",
+                "",
+                label,
+                description,
+                &tx.signature,
+            ];
+            out.push_str(&row.join(","));
+            out.push('\n');
+        };
+        for event in &tx.events {
+            match event {
+                Event::Deposited(e) if e.session_id == session => {
+                    row(None, Some((base(e.amount), &currency)), e.fee, "", "GentDex deposit");
+                }
+                Event::SessionTransferred(e) if e.destination_session_id == session => {
+                    row(None, Some((base(e.amount), &currency)), 0, "", "GentDex transfer in");
+                }
+                Event::SwapExecuted(e) if e.session_id == session => {
+                    let mint = e.output_mint.to_string();
+                    let received = Some((e.amount_out.to_string(), mint.as_str()));
+                    row(Some((base(e.amount_in), &currency)), received, 0, "", "GentDex swap");
+                }
+                Event::ComputeFeeDeducted(e) if e.session_id == session => {
+                    row(Some((base(e.fee), &currency)), None, 0, "cost", "GentDex compute fee");
+                }
+                Event::Withdrawn(e) if e.session_id == session => {
+                    row(Some((base(e.amount), &currency)), None, e.compute_fee, "", "GentDex withdrawal");
+                }
+                Event::SessionTransferred(e) if e.source_session_id == session => {
+                    row(Some((base(e.amount), &currency)), None, e.compute_fee, "", "GentDex transfer out");
+                }
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountDeserialize, Discriminator};
+    use anchor_spl::token::spl_token::native_mint;
+    use gentdex_client::program::{ComputeFeeDeducted, SwapExecuted};
+
+    use super::*;
+
+    #[test]
+    fn writes_swaps_and_fees_as_universal_rows() {
+        let session_id = [9; 16];
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(Vault::DISCRIMINATOR.len() + 2048, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.session_id = session_id;
+        vault.base_mint = native_mint::ID;
+
+        let mint = Pubkey::new_unique();
+        let history = [SessionTransaction {
+            signature: "sig".to_string(),
+            slot: 1,
+            block_time: Some(1_700_000_000),
+            events: vec![
+                Event::SwapExecuted(SwapExecuted {
+                    session_id,
+                    vault: Pubkey::new_unique(),
+                    bot: Pubkey::new_unique(),
+                    dex_program: Pubkey::new_unique(),
+                    amount_in: 250_000_000,
+                    minimum_amount_out: 0,
+                    timestamp: 0,
+                    output_mint: mint,
+                    amount_out: 1_234,
+                    memo: [0; 32],
+                }),
+                Event::ComputeFeeDeducted(ComputeFeeDeducted {
+                    session_id,
+                    vault: Pubkey::new_unique(),
+                    fee: 5_000_000,
+                    remaining_balance: 0,
+                }),
+            ],
+        }];
+
+        let csv = universal_csv(&vault, &history);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], UNIVERSAL_HEADER);
+        assert_eq!(rows[1], format!("2023-11-14T22:13:20Z,0.25,SOL,1234,{mint},,,,,,GentDex swap,sig"));
+        assert_eq!(rows[2], "2023-11-14T22:13:20Z,0.005,SOL,,,,,,,cost,GentDex compute fee,sig");
+    }
+}
@@ -0,0 +1,51 @@
+//! Transaction inspection.
+
+use gentdex_client::events::parse_logs;
+use gentdex_client::rpc::transaction_failed;
+use gentdex_client::ClientError;
+
+use crate::display;
+use crate::{CliError, Context};
+
+/// Print a transaction's outcome and the GentDex events it emitted.
+pub async fn decode(ctx: &Context, signature: &str, show_logs: bool) -> Result<(), CliError> {
+    let tx = ctx
+        .rpc()
+        .get_transaction(signature)
+        .await?
+        .ok_or_else(|| CliError::Invalid(format!("transaction {signature} not found")))?;
+
+    println!("Transaction  {signature}");
+    match tx.block_time {
+        Some(time) => println!("Slot         {} ({})", tx.slot, display::date(time)),
+        None => println!("Slot         {}", tx.slot),
+    }
+    match &tx.err {
+        None => println!("Result       Success"),
+        Some(err) => {
+            let err = transaction_failed(err, tx.logs.clone());
+            println!("Result       Failed: {err}");
+            if let ClientError::Program { error, .. } = &err {
+                if let Some(hint) = error.hint() {
+                    println!("Hint         {hint}");
+                }
+            }
+        }
+    }
+
+    // A failed transaction's events were rolled back, but still explain how far it got
+    let events = parse_logs(&tx.logs);
+    println!("Events       {}", events.len());
+    for event in &events {
+        println!();
+        println!("{event:#?}");
+    }
+
+    if show_logs {
+        println!();
+        for line in &tx.logs {
+            println!("  {line}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! Generates the client's event, instruction and account lists from the
+//! program's IDL, `idl/gentdex_escrow.json`, so a new event, instruction or
+//! account reaches the SDK with the next `cargo xtask idl` instead of
+//! waiting for someone to add it by hand. Each list is a macro invocation
+//! the including module defines; the types themselves are the program
+//! crate's, so their fields can't drift either.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+const IDL: &str = "idl/gentdex_escrow.json";
+
+/// `name` of every entry in the IDL's `section`.
+fn names<'a>(idl: &'a Value, section: &str) -> Vec<&'a str> {
+    idl[section]
+        .as_array()
+        .unwrap_or_else(|| panic!("{IDL}: no {section}"))
+        .iter()
+        .map(|entry| entry["name"].as_str().unwrap_or_else(|| panic!("{IDL}: unnamed entry in {section}")))
+        .collect()
+}
+
+/// `set_fee_route` → `SetFeeRoute`, the name of the instruction's args struct.
+fn camel_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        })
+        .collect()
+}
+
+fn write(out_dir: &Path, file: &str, invocation: String) {
+    let contents = format!("// Generated by build.rs from {IDL}; do not edit.\n{invocation}\n");
+    fs::write(out_dir.join(file), contents).unwrap_or_else(|err| panic!("writing {file}: {err}"));
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={IDL}");
+    let idl: Value = serde_json::from_str(&fs::read_to_string(IDL).unwrap_or_else(|err| panic!("{IDL}: {err}")))
+        .unwrap_or_else(|err| panic!("{IDL}: {err}"));
+    let out_dir = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    let out_dir = Path::new(&out_dir);
+
+    write(out_dir, "events.rs", format!("events!({});", names(&idl, "events").join(", ")));
+    let instructions: Vec<String> =
+        names(&idl, "instructions").iter().map(|name| format!("{name} => {}", camel_case(name))).collect();
+    write(out_dir, "instructions.rs", format!("instructions!({});", instructions.join(", ")));
+    write(out_dir, "accounts.rs", format!("accounts!({});", names(&idl, "accounts").join(", ")));
+}
@@ -0,0 +1,254 @@
+//! Atomic multi-instruction transactions.
+//!
+//! A [`Bundle`] collects instructions (wrap SOL, `execute_swap`, unwrap;
+//! `withdraw` then close, ...) into one v0 transaction so they land or fail
+//! together. Building it resolves lookup tables, including those of the
+//! sessions its GentDex instructions write to, sizes the compute-unit limit
+//! by simulation, and prices compute units from recent prioritization fees,
+//! unless either is set explicitly.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token::{self, native_mint};
+use solana_message::{v0, VersionedMessage};
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::instructions::{compute_unit_limit, compute_unit_price};
+use crate::rpc::GentdexRpc;
+use crate::signer::{self, WalletSigner};
+use crate::{ClientError, PROGRAM_ID};
+
+/// Most compute units a transaction may request
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+/// Headroom over simulated usage, in percent
+const UNIT_MARGIN_PCT: u64 = 10;
+/// Default percentile of recent prioritization fees to pay
+const DEFAULT_FEE_PERCENTILE: u8 = 75;
+
+/// How to price compute units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriorityFee {
+    /// No compute-unit price instruction
+    None,
+    /// This many micro-lamports per compute unit
+    Fixed(u64),
+    /// This percentile of the recent fees paid to write the bundle's accounts
+    Percentile(u8),
+}
+
+/// Instructions to send as one transaction, paid by `payer`.
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    lookup_tables: Vec<Pubkey>,
+    unit_limit: Option<u32>,
+    priority_fee: PriorityFee,
+}
+
+impl Bundle {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: Vec::new(),
+            lookup_tables: Vec::new(),
+            unit_limit: None,
+            priority_fee: PriorityFee::Percentile(DEFAULT_FEE_PERCENTILE),
+        }
+    }
+
+    pub fn push(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn extend(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    /// Move `lamports` into `owner`'s wrapped-SOL account, creating it if
+    /// needed. `owner` signs.
+    pub fn wrap_sol(self, owner: Pubkey, lamports: u64) -> Self {
+        let account = get_associated_token_address(&owner, &native_mint::ID);
+        let payer = self.payer;
+        self.push(create_associated_token_account_idempotent(&payer, &owner, &native_mint::ID, &spl_token::ID))
+            .push(system_transfer(owner, account, lamports))
+            .push(spl_token::instruction::sync_native(&spl_token::ID, &account).expect("valid sync_native"))
+    }
+
+    /// Close `owner`'s wrapped-SOL account, returning its SOL to `owner`.
+    /// `owner` signs.
+    pub fn unwrap_sol(self, owner: Pubkey) -> Self {
+        let account = get_associated_token_address(&owner, &native_mint::ID);
+        self.push(
+            spl_token::instruction::close_account(&spl_token::ID, &account, &owner, &owner, &[])
+                .expect("valid close_account"),
+        )
+    }
+
+    /// Resolve account keys through this lookup table, e.g. a Jupiter
+    /// route's. Sessions' own (`Vault::lookup_table`) are found when building.
+    pub fn lookup_table(mut self, address: Pubkey) -> Self {
+        if !self.lookup_tables.contains(&address) {
+            self.lookup_tables.push(address);
+        }
+        self
+    }
+
+    /// Request exactly `units` instead of simulating.
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.unit_limit = Some(units.min(MAX_COMPUTE_UNITS));
+        self
+    }
+
+    pub fn priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// Compile and sign the bundle. Returns the transaction with the last
+    /// block height its blockhash is valid for, for
+    /// `GentdexRpc::send_and_confirm_versioned`.
+    pub async fn build(
+        &self,
+        rpc: &GentdexRpc,
+        signers: &[&dyn WalletSigner],
+    ) -> Result<(VersionedTransaction, u64), ClientError> {
+        let mut addresses = self.lookup_tables.clone();
+        for table in rpc.session_lookup_tables(&self.session_candidates()).await? {
+            if !addresses.contains(&table) {
+                addresses.push(table);
+            }
+        }
+        let mut tables = Vec::with_capacity(addresses.len());
+        for address in &addresses {
+            tables.push(rpc.fetch_lookup_table(address).await?);
+        }
+
+        let price = match self.priority_fee {
+            PriorityFee::None => None,
+            PriorityFee::Fixed(micro_lamports) => Some(micro_lamports),
+            PriorityFee::Percentile(percentile) => Some(rpc.recent_priority_fee(&self.writable_accounts(), percentile).await?),
+        };
+        let units = match self.unit_limit {
+            Some(units) => units,
+            None => {
+                let (blockhash, _) = rpc.latest_blockhash().await?;
+                let ixs = self.with_budget(MAX_COMPUTE_UNITS, price);
+                let message = v0::Message::try_compile(&self.payer, &ixs, &tables, blockhash)
+                    .map_err(|err| ClientError::Rpc(format!("compiling transaction: {err}")))?;
+                let simulation = rpc.simulate_message(&VersionedMessage::V0(message)).await?;
+                let used = simulation.units_consumed.unwrap_or(MAX_COMPUTE_UNITS as u64);
+                (used + used * UNIT_MARGIN_PCT / 100).min(MAX_COMPUTE_UNITS as u64) as u32
+            }
+        };
+
+        let (blockhash, last_valid_block_height) = rpc.latest_blockhash().await?;
+        let message = v0::Message::try_compile(&self.payer, &self.with_budget(units, price), &tables, blockhash)
+            .map_err(|err| ClientError::Rpc(format!("compiling transaction: {err}")))?;
+        let tx = signer::sign_versioned(VersionedMessage::V0(message), signers).await?;
+        Ok((tx, last_valid_block_height))
+    }
+
+    /// Build, send and confirm the bundle.
+    pub async fn send(&self, rpc: &GentdexRpc, signers: &[&dyn WalletSigner]) -> Result<String, ClientError> {
+        let (tx, last_valid_block_height) = self.build(rpc, signers).await?;
+        rpc.send_and_confirm_versioned(&tx, last_valid_block_height).await
+    }
+
+    /// The bundle's instructions behind compute-budget instructions.
+    fn with_budget(&self, units: u32, price: Option<u64>) -> Vec<Instruction> {
+        let mut ixs = vec![compute_unit_limit(units)];
+        ixs.extend(price.map(compute_unit_price));
+        ixs.extend(self.instructions.iter().cloned());
+        ixs
+    }
+
+    /// Accounts the bundle writes, which set its priority fee market.
+    fn writable_accounts(&self) -> Vec<Pubkey> {
+        let mut accounts: Vec<Pubkey> = self
+            .instructions
+            .iter()
+            .flat_map(|ix| &ix.accounts)
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        accounts.sort_unstable();
+        accounts.dedup();
+        accounts
+    }
+
+    /// Accounts GentDex instructions write without signing, among them the
+    /// vaults whose lookup tables the bundle resolves through.
+    fn session_candidates(&self) -> Vec<Pubkey> {
+        let mut accounts: Vec<Pubkey> = self
+            .instructions
+            .iter()
+            .filter(|ix| ix.program_id == PROGRAM_ID)
+            .flat_map(|ix| &ix.accounts)
+            .filter(|meta| meta.is_writable && !meta.is_signer)
+            .map(|meta| meta.pubkey)
+            .collect();
+        accounts.sort_unstable();
+        accounts.dedup();
+        accounts
+    }
+}
+
+/// System program transfer of `lamports` from `from` to `to`.
+fn system_transfer(from: Pubkey, to: Pubkey, lamports: u64) -> Instruction {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction::new_with_bytes(
+        system_program::ID,
+        &data,
+        vec![AccountMeta::new(from, true), AccountMeta::new(to, false)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::COMPUTE_BUDGET_PROGRAM_ID;
+
+    #[test]
+    fn wraps_around_a_swap_behind_the_budget() {
+        let (payer, owner, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let swap = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(other, false), AccountMeta::new_readonly(owner, true)],
+        );
+        let bundle = Bundle::new(payer).wrap_sol(owner, 5_000).push(swap).unwrap_sol(owner);
+
+        let ixs = bundle.with_budget(200_000, Some(10));
+        assert_eq!(ixs.len(), 7);
+        assert!(ixs[..2].iter().all(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM_ID));
+        assert_eq!(ixs[3].program_id, system_program::ID);
+        assert_eq!(&ixs[3].data[4..], &5_000u64.to_le_bytes());
+        assert_eq!(ixs[6].program_id, spl_token::ID);
+        assert_eq!(bundle.with_budget(200_000, None).len(), 6);
+
+        let wsol = get_associated_token_address(&owner, &native_mint::ID);
+        let writable = bundle.writable_accounts();
+        assert!(writable.contains(&wsol) && writable.contains(&other) && writable.contains(&payer));
+        assert_eq!(writable.iter().filter(|key| **key == wsol).count(), 1);
+        assert!(bundle.session_candidates().is_empty());
+    }
+
+    #[test]
+    fn looks_for_sessions_among_the_vaults_gentdex_writes() {
+        let (user, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let treasury = Pubkey::new_unique();
+        let withdraw = crate::instructions::withdraw(user, vault, treasury, Pubkey::new_unique(), user);
+        let bundle = Bundle::new(user).push(withdraw).unwrap_sol(user);
+
+        let candidates = bundle.session_candidates();
+        assert!(candidates.contains(&vault) && candidates.contains(&treasury));
+        assert!(!candidates.contains(&user));
+    }
+}
@@ -0,0 +1,78 @@
+//! The clusters GentDex is deployed to, and which one this build targets.
+//!
+//! Each cluster runs its own program ID, fixed at compile time: the program
+//! crate, and this SDK over it, build for mainnet by default and for devnet
+//! with the `devnet` feature, so [`PROGRAM_ID`](crate::PROGRAM_ID), every
+//! instruction builder and every PDA follow the build. [`Cluster::BUILD`]
+//! says which cluster that is; check an endpoint against it with
+//! `GentdexRpc::check_cluster` before sending to it.
+
+use anchor_lang::prelude::{pubkey, Pubkey};
+
+pub const MAINNET_PROGRAM_ID: Pubkey = pubkey!("9hyscAyfR2puBXWFoGzeBq3QtSn5e83B7AUkcS1qC5RJ");
+pub const DEVNET_PROGRAM_ID: Pubkey = pubkey!("D3cViTirWf3zkEcgoLkKEgXr73biwwaih4WxZUR4N2j5");
+
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    /// A local validator, running whatever build was deployed to it
+    Localnet,
+}
+
+impl Cluster {
+    /// The cluster this build's program ID belongs to
+    #[cfg(not(feature = "devnet"))]
+    pub const BUILD: Cluster = Cluster::Mainnet;
+    #[cfg(feature = "devnet")]
+    pub const BUILD: Cluster = Cluster::Devnet;
+
+    /// The program ID deployed on the cluster; localnet deploys the default build.
+    pub const fn program_id(self) -> Pubkey {
+        match self {
+            Cluster::Mainnet | Cluster::Localnet => MAINNET_PROGRAM_ID,
+            Cluster::Devnet => DEVNET_PROGRAM_ID,
+        }
+    }
+
+    /// The cluster's public JSON-RPC endpoint.
+    pub const fn default_rpc_url(self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "https://api.mainnet-beta.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Localnet => "http://127.0.0.1:8899",
+        }
+    }
+
+    /// The cluster with genesis hash `hash`; any unknown one is a local validator.
+    pub fn from_genesis_hash(hash: &str) -> Cluster {
+        match hash {
+            MAINNET_GENESIS_HASH => Cluster::Mainnet,
+            DEVNET_GENESIS_HASH => Cluster::Devnet,
+            _ => Cluster::Localnet,
+        }
+    }
+
+    /// Whether this build's instructions are meant for the program on `self`.
+    /// A local validator may run either build.
+    pub fn matches_build(self) -> bool {
+        self == Cluster::Localnet || self.program_id() == crate::PROGRAM_ID
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_target_their_own_program() {
+        assert_eq!(Cluster::BUILD.program_id(), crate::PROGRAM_ID);
+        assert!(Cluster::BUILD.matches_build());
+        assert_eq!(Cluster::from_genesis_hash(DEVNET_GENESIS_HASH), Cluster::Devnet);
+        // A mainnet build must never sign for devnet's program, nor a devnet build for mainnet's
+        assert_ne!(Cluster::Mainnet.matches_build(), Cluster::Devnet.matches_build());
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::prelude::Pubkey;
+
+use crate::cluster::Cluster;
+use crate::program::EscrowError;
+use crate::PROGRAM_ID;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} is not owned by the GentDex program")]
+    WrongOwner(Pubkey),
+    #[error("could not decode account data: {0}")]
+    Decode(#[from] anchor_lang::error::Error),
+    #[error("account source: {0}")]
+    Source(String),
+    #[error("rpc: {0}")]
+    Rpc(String),
+    /// An instruction failed with a custom program error. Match on `error`
+    /// to handle specific failures; [`ProgramError::hint`] says what to do.
+    #[error("instruction {instruction} failed: {error}")]
+    Program {
+        instruction: u8,
+        error: ProgramError,
+        logs: Vec<String>,
+    },
+    /// The transaction failed in simulation or on chain for any other reason.
+    #[error("transaction failed: {err}")]
+    TransactionFailed {
+        instruction: Option<u8>,
+        err: String,
+        logs: Vec<String>,
+    },
+    #[error("blockhash expired before the transaction was confirmed")]
+    BlockhashExpired,
+    /// The endpoint is on a cluster this build's program ID isn't deployed to.
+    #[error("endpoint is on {cluster:?}, but this build targets {build:?}")]
+    WrongCluster { cluster: Cluster, build: Cluster },
+}
+
+/// A custom program error code, decoded.
+#[derive(Clone, Debug)]
+pub enum ProgramError {
+    /// One of GentDex's own errors
+    Escrow(EscrowError),
+    /// An Anchor framework error raised by GentDex, such as a failed account
+    /// constraint. Name and message come from the program's logs.
+    Anchor { code: u32, name: String, message: String },
+    /// A code GentDex doesn't define, usually from a program it called into
+    Other { program: Option<Pubkey>, code: u32 },
+}
+
+impl ProgramError {
+    /// Decode custom error `code` from a failed transaction, using its logs to
+    /// tell which program raised it.
+    pub fn decode(code: u32, logs: &[String]) -> Self {
+        let program = failed_program(logs);
+        if program.is_some_and(|program| program != PROGRAM_ID) {
+            return ProgramError::Other { program, code };
+        }
+        if let Some(error) = ESCROW_ERRORS.iter().find(|error| u32::from(**error) == code) {
+            return ProgramError::Escrow(*error);
+        }
+        if code < ERROR_CODE_OFFSET {
+            if let Some((name, message)) = anchor_error(code, logs) {
+                return ProgramError::Anchor { code, name, message };
+            }
+        }
+        ProgramError::Other { program, code }
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            ProgramError::Escrow(error) => u32::from(*error),
+            ProgramError::Anchor { code, .. } | ProgramError::Other { code, .. } => *code,
+        }
+    }
+
+    /// What an operator can do about it, where there's something to say.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ProgramError::Escrow(error) => Some(escrow_hint(*error)),
+            ProgramError::Anchor { name, .. } => anchor_hint(name),
+            ProgramError::Other { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramError::Escrow(error) => write!(f, "{}: {error}", error.name()),
+            ProgramError::Anchor { name, message, .. } => write!(f, "{name}: {message}"),
+            ProgramError::Other { program: Some(program), code } => {
+                write!(f, "custom program error {code:#x} from {program}")
+            }
+            ProgramError::Other { program: None, code } => write!(f, "custom program error {code:#x}"),
+        }
+    }
+}
+
+macro_rules! escrow_errors {
+    ($($variant:ident => $hint:literal,)*) => {
+        const ESCROW_ERRORS: &[EscrowError] = &[$(EscrowError::$variant),*];
+
+        // Exhaustive, so a new program error fails the build until it's listed
+        fn escrow_hint(error: EscrowError) -> &'static str {
+            match error {
+                $(EscrowError::$variant => $hint,)*
+            }
+        }
+    };
+}
+
+escrow_errors! {
+    Unauthorized => "sign with the session's owner, or its bot for trading instructions",
+    InvalidStatus => "check the session's status; it may need funding, resuming or may already be closed",
+    DepositTooSmall => "deposit at least 0.1 SOL",
+    InsufficientBalance => "reduce the amount or deposit more",
+    DexNotWhitelisted => "route through a DEX on the protocol whitelist",
+    SessionExpired => "extend the session, or withdraw and start a new one",
+    SessionNotExpired => "wait for the session to end, or have the owner withdraw",
+    MathOverflow => "reduce the amount",
+    TooEarlyForDeduction => "compute fees are deducted at most once a day; retry later",
+    InvalidTreasury => "pass the treasury from the protocol config",
+    ReentrantCall => "wait for the in-flight swap to finish",
+    SwapOverspent => "the route spent more than amount_in; check the route accounts",
+    InvalidDexAccount => "the route's accounts don't match the DEX adapter's layout",
+    SlippageExceeded => "the price moved; re-quote or widen slippage",
+    PerpsNotEnabled => "enable perps on the session first",
+    ReduceOnly => "only reduce-only orders are allowed until the session is trading again",
+    LendingNotEnabled => "enable lending on the session first",
+    InvalidLendCap => "use a lend cap of at most 10000 bps",
+    LendCapExceeded => "lend less, or raise the session's lend cap",
+    LendingNotUnwound => "withdraw lent funds before withdrawing from the session",
+    BaseCurrencyMismatch => "use the instruction variant for the session's base currency",
+    WhitelistFull => "remove a DEX from the whitelist before adding another",
+    FeeTooHigh => "lower the fee to the protocol maximum",
+    InvalidRewardsSchedule => "set the rewards start before its end",
+    StakeLocked => "wait for the stake's lock to end",
+    CpiOnly => "call this instruction from another program",
+    InvalidTemplate => "pass an existing, active session template",
+    UserStillActive => "recovery is only allowed after the owner has been inactive for the recovery window",
+    PositionLimitReached => "close a position before opening another",
+    InvalidPriceFeed => "pass a verified Pyth price update for the registered feed",
+    StalePrice => "post a fresh Pyth price update in the same transaction",
+    PriceFeedMissing => "pass the price feeds registered for the swap's tokens",
+    ExposureCapExceeded => "trade a smaller amount into this token",
+    EpochNotOver => "wait until the epoch has ended",
+    InvalidLookupTable => "use the lookup table created for this session",
+    BotNotVerified => "pick a bot the guardian has verified, or open the session without requiring one",
+    BotBlacklisted => "the admin has blacklisted this bot; pick another",
+    BlacklistFull => "unblacklist a bot before adding another",
+    InviteRequired => "ask the operator for an invite and open the session with initialize_from_invite",
+    InvalidInvite => "the invite has expired, was already used or is for another wallet",
+    BotResigned => "the bot has left this session; withdraw and open a new one with another bot",
+    InvalidFeeRoute => "keep fee shares unique, at most MAX_FEE_RECIPIENTS and within what operators' shares leave",
+    UnclaimedFees => "have the recipient claim its routed fees before removing it",
+    DepositTooLarge => "deposit less; the protocol caps each session's funded balance for now",
+    InvalidDepositLimits => "set a deposit cap of 0 (none) or at least the minimum deposit",
+    StaleTradeNonce => "sign the swap again with a nonce above the session's trade_nonce",
+    InvalidUserSignature => "put the ed25519 verification of the user's withdraw_message directly before the withdrawal",
+    AuthorizationExpired => "have the user sign the withdrawal again with a later deadline",
+    InvalidBridgeTransfer => "pass a posted transfer to the vault's token account, then the Token Bridge's accounts in order",
+    InvalidTradeBatch => "pass the session's trade_batch account after the route, with a batch size of at most 32",
+    InvalidBotTier => "use a tier, or a list of tier fees, no longer than MAX_BOT_TIERS",
+    InvalidOperatorCredit => "pass the bot's operator_credit PDA",
+    SessionNotEmpty => "close out token positions, perps collateral and the lookup table, and empty the token accounts passed",
+    InvalidWithdrawalNotice => "pass a notice between 0 and MAX_WITHDRAWAL_NOTICE_DAYS, and set one before requesting a withdrawal",
+    WithdrawalNoticePending => "call request_withdrawal, then withdraw once the notice has passed (within the request window)",
+    InvalidPauseReason => "pass the reason code for the exploitation the session is under",
+    NotGuardianPaused => "only a session the guardian paused can be lifted or confirmed; use resume for the user's own pause",
+    GuardianPauseActive => "wait until MAX_GUARDIAN_PAUSE_DAYS after the guardian paused it, or have the guardian lift it",
+    PerpsNotUnwound => "perps_withdraw the session's collateral before withdrawing from it",
+    UnauthorizedFunder => "have the user set_funder to the DLN external-call authority or bridge sender first",
+    DexAdapterMissing => "trade through a venue the program has an adapter for; see the adapters module",
+    InvalidExtension => "pass a number of days to extend by",
+}
+
+fn anchor_hint(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "AccountNotInitialized" => "the account doesn't exist yet; check the address or initialize it first",
+        "AccountOwnedByWrongProgram" | "AccountDiscriminatorMismatch" => "wrong account passed; check the address",
+        "ConstraintSeeds" => "a PDA doesn't match its seeds; re-derive it from the session's user and id",
+        "ConstraintHasOne" => "an account doesn't belong to this session or config",
+        "ConstraintSigner" | "AccountNotSigner" => "a required signer is missing",
+        "InstructionFallbackNotFound" => "the deployed program doesn't have this instruction; update the client",
+        _ => return None,
+    })
+}
+
+/// The innermost program that failed, from a `Program <id> failed: ...` line.
+fn failed_program(logs: &[String]) -> Option<Pubkey> {
+    logs.iter().find_map(|line| {
+        let rest = line.strip_prefix("Program ")?;
+        let (program, outcome) = rest.split_once(' ')?;
+        outcome.starts_with("failed:").then(|| program.parse().ok()).flatten()
+    })
+}
+
+/// Name and message of Anchor error `code` from its
+/// `AnchorError ... Error Code: <name>. Error Number: <code>. Error Message: <message>.` log.
+fn anchor_error(code: u32, logs: &[String]) -> Option<(String, String)> {
+    logs.iter().find_map(|line| {
+        let (_, rest) = line.split_once("Error Code: ")?;
+        let (name, rest) = rest.split_once(". Error Number: ")?;
+        let (number, message) = rest.split_once(". Error Message: ")?;
+        (number.parse() == Ok(code)).then(|| (name.to_string(), message.trim_end_matches('.').to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_escrow_and_anchor_errors() {
+        let failed = format!("Program {PROGRAM_ID} failed: custom program error: 0x1775");
+        let error = ProgramError::decode(6005, &[failed]);
+        assert!(matches!(error, ProgramError::Escrow(EscrowError::SessionExpired)));
+        assert_eq!(error.to_string(), "SessionExpired: Trading session has expired");
+        assert!(error.hint().is_some());
+
+        let logs = [
+            "Program log: AnchorError caused by account: vault. Error Code: ConstraintSeeds. Error Number: 2006. \
+             Error Message: A seeds constraint was violated."
+                .to_string(),
+            format!("Program {PROGRAM_ID} failed: custom program error: 0x7d6"),
+        ];
+        match ProgramError::decode(2006, &logs) {
+            ProgramError::Anchor { name, message, .. } => {
+                assert_eq!(name, "ConstraintSeeds");
+                assert_eq!(message, "A seeds constraint was violated");
+            }
+            other => panic!("unexpected {other}"),
+        }
+
+        let token_program = Pubkey::new_unique();
+        let logs = [format!("Program {token_program} failed: custom program error: 0x1")];
+        assert!(matches!(
+            ProgramError::decode(1, &logs),
+            ProgramError::Other { program: Some(program), code: 1 } if program == token_program
+        ));
+    }
+}
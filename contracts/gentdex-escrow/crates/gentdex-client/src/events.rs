@@ -0,0 +1,203 @@
+//! Decoding GentDex events from transaction logs.
+//!
+//! `emit!` writes `Program data: <base64>` log lines: the event's 8-byte
+//! discriminator followed by its Borsh encoding. Only lines logged while the
+//! GentDex program is the executing program are decoded, so another program
+//! can't inject look-alike events through its own logs.
+//!
+//! Sessions that batch their swap events log one `TradesBatched` for many
+//! swaps; [`unbatch`] and [`expand_batches`] recover the `SwapExecuted`s.
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::Engine;
+use gentdex_escrow::{batching, SwapExecuted, TradesBatched};
+
+use crate::PROGRAM_ID;
+
+macro_rules! events {
+    ($($name:ident),* $(,)?) => {
+        /// Any GentDex event.
+        pub enum Event {
+            $($name(gentdex_escrow::$name),)*
+        }
+
+        // Formats as the inner event, without the enum's wrapper
+        impl std::fmt::Debug for Event {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $(Event::$name(event) => event.fmt(f),)*
+                }
+            }
+        }
+
+        impl Event {
+            /// Decode an event from discriminator-prefixed data, or `None` if
+            /// it isn't a GentDex event.
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                $(
+                    if let Some(body) = data.strip_prefix(gentdex_escrow::$name::DISCRIMINATOR) {
+                        return gentdex_escrow::$name::try_from_slice(body).ok().map(Event::$name);
+                    }
+                )*
+                None
+            }
+
+            /// The event's discriminator-prefixed data, as `emit!` logs it.
+            pub fn encode(&self) -> Vec<u8> {
+                match self {
+                    $(Event::$name(event) => anchor_lang::Event::data(event),)*
+                }
+            }
+
+            /// The event's name, as in the IDL.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Event::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+// One variant per event in the IDL
+include!(concat!(env!("OUT_DIR"), "/events.rs"));
+
+/// All GentDex events in a transaction's log messages, in emission order.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<Event> {
+    let program_id = PROGRAM_ID.to_string();
+    let mut call_stack: Vec<bool> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let log = log.as_ref();
+        if let Some(rest) = log.strip_prefix("Program ") {
+            if rest.contains(" invoke [") {
+                call_stack.push(rest.starts_with(&program_id));
+                continue;
+            }
+            if rest.ends_with(" success") || rest.contains(" failed") {
+                call_stack.pop();
+                continue;
+            }
+        }
+        let Some(data) = log.strip_prefix("Program data: ") else {
+            continue;
+        };
+        if call_stack.last() != Some(&true) {
+            continue;
+        }
+        if let Some(event) = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .ok()
+            .and_then(|bytes| Event::decode(&bytes))
+        {
+            events.push(event);
+        }
+    }
+    events
+}
+
+/// The swaps a `TradesBatched` stands for, or `None` if its data is malformed.
+pub fn unbatch(batch: &TradesBatched) -> Option<Vec<SwapExecuted>> {
+    let trades = batching::decode(&batch.trades, batch.count as usize)?;
+    Some(
+        trades
+            .into_iter()
+            .map(|trade| SwapExecuted {
+                session_id: batch.session_id,
+                vault: batch.vault,
+                bot: batch.bot,
+                dex_program: trade.dex_program,
+                amount_in: trade.amount_in,
+                minimum_amount_out: trade.minimum_amount_out,
+                timestamp: trade.timestamp,
+                output_mint: trade.output_mint,
+                amount_out: trade.amount_out,
+                memo: trade.memo,
+            })
+            .collect(),
+    )
+}
+
+/// `events` with each `TradesBatched` replaced by its `SwapExecuted`s, in
+/// order. Malformed batches are left as they are.
+pub fn expand_batches(events: Vec<Event>) -> Vec<Event> {
+    let mut expanded = Vec::with_capacity(events.len());
+    for event in events {
+        match &event {
+            Event::TradesBatched(batch) => match unbatch(batch) {
+                Some(swaps) => expanded.extend(swaps.into_iter().map(Event::SwapExecuted)),
+                None => expanded.push(event),
+            },
+            _ => expanded.push(event),
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_log(event: &impl anchor_lang::Event) -> String {
+        format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn decodes_only_events_logged_by_the_program() {
+        let paused = gentdex_escrow::SessionPaused {
+            session_id: [3; 16],
+            vault: PROGRAM_ID,
+        };
+        let other = "11111111111111111111111111111111";
+        let logs = vec![
+            format!("Program {PROGRAM_ID} invoke [1]"),
+            data_log(&paused),
+            format!("Program {other} invoke [2]"),
+            data_log(&paused),
+            format!("Program {other} success"),
+            format!("Program {PROGRAM_ID} success"),
+        ];
+
+        let events = parse_logs(&logs);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            Event::SessionPaused(event) => assert_eq!(event.session_id, [3; 16]),
+            other => panic!("unexpected {}", other.name()),
+        }
+        assert_eq!(Event::decode(&events[0].encode()).map(|event| event.name()), Some("SessionPaused"));
+    }
+
+    #[test]
+    fn expands_batched_swaps() {
+        let trade = gentdex_escrow::BatchedTrade {
+            timestamp: 1_700_000_000,
+            dex_program: PROGRAM_ID,
+            amount_in: 5,
+            minimum_amount_out: 1,
+            output_mint: PROGRAM_ID,
+            amount_out: 2,
+            memo: [0; 32],
+        };
+        let batch = TradesBatched {
+            session_id: [3; 16],
+            vault: PROGRAM_ID,
+            bot: PROGRAM_ID,
+            sequence: 0,
+            count: 2,
+            trades: batching::encode(&[trade.clone(), trade]),
+        };
+        let paused = gentdex_escrow::SessionPaused {
+            session_id: [3; 16],
+            vault: PROGRAM_ID,
+        };
+
+        let events = expand_batches(vec![Event::TradesBatched(batch), Event::SessionPaused(paused)]);
+        let names: Vec<_> = events.iter().map(Event::name).collect();
+        assert_eq!(names, ["SwapExecuted", "SwapExecuted", "SessionPaused"]);
+        match &events[1] {
+            Event::SwapExecuted(swap) => assert_eq!((swap.session_id, swap.amount_in, swap.amount_out), ([3; 16], 5, 2)),
+            other => panic!("unexpected {}", other.name()),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! The program's IDL, and decoders for any instruction or account in it.
+//!
+//! `build.rs` generates the instruction and account lists here, and the
+//! event list in [`crate::events`], from `idl/gentdex_escrow.json`.
+//! Regenerate that file with `cargo xtask idl` after changing the program;
+//! `cargo xtask idl --check` fails while it's stale.
+
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+
+/// The program's Anchor IDL, as JSON.
+pub const IDL: &str = include_str!("../idl/gentdex_escrow.json");
+
+macro_rules! instructions {
+    ($($name:ident => $args:ident),* $(,)?) => {
+        /// Any GentDex instruction's arguments.
+        pub enum ProgramInstruction {
+            $($args(gentdex_escrow::instruction::$args),)*
+        }
+
+        impl ProgramInstruction {
+            /// Decode instruction data, or `None` if it isn't a GentDex
+            /// instruction. Trailing bytes are ignored, as the program does.
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                $(
+                    if let Some(mut body) = data.strip_prefix(gentdex_escrow::instruction::$args::DISCRIMINATOR) {
+                        return gentdex_escrow::instruction::$args::deserialize(&mut body).ok().map(Self::$args);
+                    }
+                )*
+                None
+            }
+
+            /// The instruction's name, as in the IDL.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$args(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+macro_rules! accounts {
+    ($($name:ident),* $(,)?) => {
+        /// Any GentDex account. Boxed: a `Vault` is several times the size
+        /// of the smallest accounts.
+        pub enum ProgramAccount {
+            $($name(Box<gentdex_escrow::$name>),)*
+        }
+
+        impl ProgramAccount {
+            /// Decode account data, or `None` if it isn't a GentDex account.
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                $(
+                    if data.starts_with(gentdex_escrow::$name::DISCRIMINATOR) {
+                        return gentdex_escrow::$name::try_deserialize(&mut &data[..])
+                            .ok()
+                            .map(|account| Self::$name(Box::new(account)));
+                    }
+                )*
+                None
+            }
+
+            /// The account's type name, as in the IDL.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+// One variant per instruction and per account in the IDL
+include!(concat!(env!("OUT_DIR"), "/instructions.rs"));
+include!(concat!(env!("OUT_DIR"), "/accounts.rs"));
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Space;
+    use gentdex_escrow::Vault;
+
+    use super::*;
+    use crate::PROGRAM_ID;
+
+    #[test]
+    fn decodes_what_the_idl_lists() {
+        assert!(IDL.contains(&format!("\"address\": \"{PROGRAM_ID}\"")));
+
+        let ix = crate::instructions::resign(Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(ProgramInstruction::decode(&ix.data).map(|ix| ix.name()), Some("resign"));
+        assert!(ProgramInstruction::decode(&[0; 8]).is_none());
+
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(8 + Vault::INIT_SPACE, 0);
+        assert_eq!(ProgramAccount::decode(&data).map(|account| account.name()), Some("Vault"));
+        assert!(ProgramAccount::decode(&data[..8]).is_none());
+    }
+}
@@ -0,0 +1,779 @@
+//! Instruction builders.
+//!
+//! Every instruction can be built from the program's generated account and
+//! argument structs with [`build`]:
+//!
+//! ```ignore
+//! let ix = build(
+//!     accounts::UserAction { vault, user },
+//!     args::SetMaxPositions { max_positions: 4 },
+//! );
+//! ```
+//!
+//! The helpers below cover the session lifecycle and derive the PDAs for you.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{bpf_loader_upgradeable, system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+use gentdex_escrow::{pda, PauseReason, Vault};
+
+pub use gentdex_escrow::ed25519::ED25519_PROGRAM_ID;
+/// The message a user signs for [`withdraw_with_signature`]
+pub use gentdex_escrow::ed25519::withdraw_message;
+
+use crate::PROGRAM_ID;
+
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = anchor_lang::pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Account structs, one per instruction context (`accounts::Deposit`, ...)
+pub use gentdex_escrow::accounts;
+/// Argument structs, one per instruction (`args::Deposit { amount }`, ...)
+pub use gentdex_escrow::instruction as args;
+
+/// Build any GentDex instruction from its accounts and arguments.
+pub fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+/// Open a session at `session_id`, its rent paid by `payer` (the user, or a
+/// relayer's fee payer). Returns the instruction and the vault address.
+pub fn initialize(
+    user: Pubkey,
+    treasury: Pubkey,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot: Pubkey,
+    payer: Pubkey,
+) -> (Instruction, Pubkey) {
+    let vault = pda::vault_address(&session_id, &user).0;
+    let ix = build(
+        accounts::Initialize {
+            vault,
+            user,
+            config: pda::config_address().0,
+            treasury,
+            system_program: system_program::ID,
+            payer,
+        },
+        args::Initialize {
+            session_id,
+            duration_days,
+            bot_pubkey: bot,
+        },
+    );
+    (ix, vault)
+}
+
+/// Open a session already funded with `amount`, in one instruction. `payer`
+/// covers the rent; the deposit comes from `user`. Returns the vault address.
+pub fn initialize_and_deposit(
+    user: Pubkey,
+    treasury: Pubkey,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot: Pubkey,
+    amount: u64,
+    payer: Pubkey,
+) -> (Instruction, Pubkey) {
+    let vault = pda::vault_address(&session_id, &user).0;
+    let ix = build(
+        accounts::InitializeAndDeposit {
+            vault,
+            user,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&user).0,
+            stake: pda::stake_address(&user).0,
+            treasury,
+            fee_router: pda::fee_router_address().0,
+            system_program: system_program::ID,
+            payer,
+        },
+        args::InitializeAndDeposit { session_id, duration_days, bot_pubkey: bot, amount },
+    );
+    (ix, vault)
+}
+
+/// Open the user's next indexed session. `index` is the registry's current
+/// `session_count` (0 if the user has no registry yet); `payer` covers rent.
+pub fn initialize_indexed(
+    user: Pubkey,
+    treasury: Pubkey,
+    index: u64,
+    duration_days: u16,
+    bot: Pubkey,
+    payer: Pubkey,
+) -> (Instruction, Pubkey) {
+    let vault = pda::indexed_vault_address(&user, index).0;
+    let ix = build(
+        accounts::InitializeIndexed {
+            registry: pda::registry_address(&user).0,
+            vault,
+            user,
+            config: pda::config_address().0,
+            treasury,
+            system_program: system_program::ID,
+            payer,
+        },
+        args::InitializeIndexed {
+            duration_days,
+            bot_pubkey: bot,
+        },
+    );
+    (ix, vault)
+}
+
+/// Open a session from `template` on the terms of `invite`, which the
+/// template's `operator` created and gets the rent of. `payer` pays the
+/// vault's.
+pub fn initialize_from_invite(
+    user: Pubkey,
+    treasury: Pubkey,
+    session_id: [u8; 16],
+    template: Pubkey,
+    invite: Pubkey,
+    operator: Pubkey,
+    payer: Pubkey,
+) -> (Instruction, Pubkey) {
+    let vault = pda::vault_address(&session_id, &user).0;
+    let ix = build(
+        accounts::InitializeFromInvite {
+            vault,
+            user,
+            template,
+            invite,
+            operator,
+            config: pda::config_address().0,
+            treasury,
+            system_program: system_program::ID,
+            payer,
+        },
+        args::InitializeFromInvite { session_id },
+    );
+    (ix, vault)
+}
+
+/// Make an initialize instruction fail unless the guardian has verified `bot`.
+pub fn require_verified_bot(mut initialize: Instruction, bot: &Pubkey) -> Instruction {
+    initialize.accounts.push(AccountMeta::new_readonly(pda::bot_profile_address(bot).0, false));
+    initialize
+}
+
+/// Fund a pending session. Sessions opened from a template must pass it, and
+/// sessions opened requiring a verified bot pass that bot as `verified_bot`.
+pub fn deposit(
+    user: Pubkey,
+    vault: Pubkey,
+    treasury: Pubkey,
+    amount: u64,
+    template: Option<Pubkey>,
+    verified_bot: Option<Pubkey>,
+) -> Instruction {
+    let mut ix = build(
+        accounts::Deposit {
+            vault,
+            user,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&user).0,
+            stake: pda::stake_address(&user).0,
+            treasury,
+            system_program: system_program::ID,
+            fee_router: pda::fee_router_address().0,
+            bot_profile: verified_bot.map(|bot| pda::bot_profile_address(&bot).0),
+        },
+        args::Deposit { amount },
+    );
+    if let Some(template) = template {
+        ix.accounts.push(AccountMeta::new(template, false));
+    }
+    ix
+}
+
+/// Open a SOL session for `recipient`, funded with `amount` by `giver` once
+/// the recipient accepts. Returns the vault address.
+pub fn gift_session(
+    giver: Pubkey,
+    treasury: Pubkey,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+) -> (Instruction, Pubkey) {
+    let vault = pda::vault_address(&session_id, &recipient).0;
+    let ix = build(
+        accounts::GiftSession {
+            vault,
+            gift: pda::gift_address(&vault).0,
+            giver,
+            config: pda::config_address().0,
+            treasury,
+            system_program: system_program::ID,
+        },
+        args::GiftSession { session_id, duration_days, bot_pubkey: bot, recipient, amount },
+    );
+    (ix, vault)
+}
+
+/// Recipient: accept a gifted session, starting it. `verified_bot` as for
+/// `deposit`. `payer` creates the recipient's rewards account if they have
+/// none: the user, or a relayer.
+pub fn accept_gift(
+    user: Pubkey,
+    vault: Pubkey,
+    giver: Pubkey,
+    treasury: Pubkey,
+    verified_bot: Option<Pubkey>,
+    payer: Pubkey,
+) -> Instruction {
+    build(
+        accounts::AcceptGift {
+            vault,
+            gift: pda::gift_address(&vault).0,
+            giver,
+            user,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&user).0,
+            stake: pda::stake_address(&user).0,
+            treasury,
+            fee_router: pda::fee_router_address().0,
+            system_program: system_program::ID,
+            bot_profile: verified_bot.map(|bot| pda::bot_profile_address(&bot).0),
+            payer,
+        },
+        args::AcceptGift {},
+    )
+}
+
+/// Giver: take back a gift the recipient hasn't accepted.
+pub fn reclaim_gift(giver: Pubkey, vault: Pubkey) -> Instruction {
+    build(
+        accounts::ReclaimGift { vault, gift: pda::gift_address(&vault).0, giver },
+        args::ReclaimGift {},
+    )
+}
+
+/// Fund a Pending SOL session so it starts with exactly `trading_balance`,
+/// the setup fee added on top. `state::exact_deposit_amount` quotes what it
+/// will cost.
+pub fn deposit_exact_balance(
+    user: Pubkey,
+    vault: Pubkey,
+    treasury: Pubkey,
+    trading_balance: u64,
+    template: Option<Pubkey>,
+    verified_bot: Option<Pubkey>,
+) -> Instruction {
+    let mut ix = deposit(user, vault, treasury, 0, template, verified_bot);
+    ix.data = args::DepositExactBalance { trading_balance }.data();
+    ix
+}
+
+/// Fund the Pending stablecoin session at `address` with a Wormhole Token
+/// Bridge transfer posted at `posted_vaa`, sent by the session's `funder` to
+/// the vault's token account. `bridge_accounts` are the Token Bridge's
+/// accounts for completing it, in its order, with the vault as redeemer;
+/// `wrapped` if the token isn't native to Solana. The fee goes to the treasury's associated token account.
+pub fn deposit_from_bridge(
+    payer: Pubkey,
+    address: Pubkey,
+    vault: &Vault,
+    posted_vaa: Pubkey,
+    bridge_accounts: Vec<AccountMeta>,
+    wrapped: bool,
+) -> Instruction {
+    let mut ix = build(
+        accounts::DepositFromBridge {
+            vault: address,
+            payer,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&vault.user).0,
+            stake: pda::stake_address(&vault.user).0,
+            posted_vaa,
+            vault_token_account: get_associated_token_address(&address, &vault.base_mint),
+            treasury_token_account: get_associated_token_address(&vault.treasury, &vault.base_mint),
+            token_bridge_program: gentdex_escrow::wormhole::TOKEN_BRIDGE_PROGRAM_ID,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        args::DepositFromBridge { wrapped },
+    );
+    ix.accounts.extend(bridge_accounts);
+    ix
+}
+
+/// Fund the Pending stablecoin session at `address` with everything in
+/// `funder`'s associated token account for the base mint: the destination
+/// call of a deBridge DLN order, `funder` being its external-call authority,
+/// which the user must have named with `set_funder`.
+/// `payer` creates the user's rewards account if needed.
+pub fn deposit_from_dln(funder: Pubkey, payer: Pubkey, address: Pubkey, vault: &Vault) -> Instruction {
+    build(
+        accounts::DepositFromDln {
+            vault: address,
+            funder,
+            payer,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&vault.user).0,
+            stake: pda::stake_address(&vault.user).0,
+            funder_token_account: get_associated_token_address(&funder, &vault.base_mint),
+            vault_token_account: get_associated_token_address(&address, &vault.base_mint),
+            treasury_token_account: get_associated_token_address(&vault.treasury, &vault.base_mint),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        args::DepositFromDln {},
+    )
+}
+
+/// A bot swap. Picks `execute_swap`, `execute_swap_with_memo`,
+/// `execute_swap_protected`, `execute_swap_with_nonce` or
+/// `execute_swap_with_route` from which fields are set; dry runs take
+/// `execute_swap`, or `execute_swap_with_route` when they need its options.
+pub struct Swap {
+    pub vault: Pubkey,
+    /// Vault owner, for the rewards PDA
+    pub user: Pubkey,
+    /// The session's treasury, paid the compute fee a SOL session's swap settles
+    pub treasury: Pubkey,
+    pub bot: Pubkey,
+    pub dex_program: Pubkey,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub memo: Option<[u8; 32]>,
+    /// Slot the quote was taken at, for sessions with slot-age protection
+    pub recent_slot: Option<u64>,
+    /// Pass the instructions sysvar, for sessions requiring a Jito tip
+    pub jito_tip: bool,
+    /// Replay protection: above the vault's `trade_nonce`, e.g. its value
+    /// plus one, or a timestamp
+    pub nonce: Option<u64>,
+    /// Check policy and the route without swapping; simulate it and read the
+    /// `SwapResult` with [`GentdexRpc::view`](crate::rpc::GentdexRpc::view)
+    pub dry_run: bool,
+    /// Pass the session's `TradeBatch` after the route, for sessions batching
+    /// their swap events
+    pub batched: bool,
+    /// Vault token account receiving the output, for position tracking
+    pub output_token_account: Option<Pubkey>,
+    /// Pyth price updates for the session's base currency and the output
+    /// token, for exposure and slippage checks
+    pub price_feeds: Option<(Pubkey, Pubkey)>,
+    /// Vault's base-mint token account, which a token session's swaps sell from
+    pub base_token_account: Option<Pubkey>,
+    /// The venue's accounts, appended as remaining accounts
+    pub route: Vec<AccountMeta>,
+    /// The venue's instruction data, for aggregator routes built off-chain
+    /// (see [`jupiter`](crate::jupiter))
+    pub route_data: Option<Vec<u8>>,
+}
+
+pub fn execute_swap(swap: &Swap) -> Instruction {
+    let accounts = accounts::ExecuteSwap {
+        vault: swap.vault,
+        bot: swap.bot,
+        config: pda::config_address().0,
+        rewards: pda::rewards_address(&swap.user).0,
+        treasury: swap.treasury,
+        fee_router: pda::fee_router_address().0,
+        operator_credit: pda::operator_credit_address(&swap.bot).0,
+        dex_program: swap.dex_program,
+        output_token_account: swap.output_token_account,
+        base_price_feed: swap.price_feeds.map(|(base, _)| base),
+        output_price_feed: swap.price_feeds.map(|(_, output)| output),
+        instructions_sysvar: swap.jito_tip.then_some(sysvar::instructions::ID),
+        base_token_account: swap.base_token_account,
+    };
+    let (amount_in, minimum_amount_out) = (swap.amount_in, swap.minimum_amount_out);
+    let options = (swap.nonce, swap.memo, swap.recent_slot);
+    // Only `execute_swap` and `execute_swap_with_route` take `dry_run`
+    let routed = swap.route_data.is_some() || swap.dry_run && options != (None, None, None);
+
+    let mut ix = match options {
+        (nonce, memo, recent_slot) if routed => build(
+            accounts,
+            args::ExecuteSwapWithRoute {
+                amount_in,
+                minimum_amount_out,
+                route_data: swap.route_data.clone().unwrap_or_default(),
+                memo: memo.unwrap_or_default(),
+                recent_slot,
+                nonce,
+                dry_run: swap.dry_run,
+            },
+        ),
+        (Some(nonce), memo, recent_slot) => build(
+            accounts,
+            args::ExecuteSwapWithNonce {
+                amount_in,
+                minimum_amount_out,
+                memo: memo.unwrap_or_default(),
+                recent_slot,
+                nonce,
+            },
+        ),
+        (None, None, None) => build(
+            accounts,
+            args::ExecuteSwap { amount_in, minimum_amount_out, dry_run: swap.dry_run },
+        ),
+        (None, Some(memo), None) => build(
+            accounts,
+            args::ExecuteSwapWithMemo { amount_in, minimum_amount_out, memo },
+        ),
+        (None, memo, Some(recent_slot)) => build(
+            accounts,
+            args::ExecuteSwapProtected {
+                amount_in,
+                minimum_amount_out,
+                memo: memo.unwrap_or_default(),
+                recent_slot,
+            },
+        ),
+    };
+    ix.accounts.extend(swap.route.iter().cloned());
+    if swap.batched {
+        ix.accounts.push(AccountMeta::new(pda::trade_batch_address(&swap.vault).0, false));
+    }
+    ix
+}
+
+pub fn pause(user: Pubkey, vault: Pubkey) -> Instruction {
+    build(accounts::UserAction { vault, user }, args::Pause {})
+}
+
+pub fn resume(user: Pubkey, vault: Pubkey) -> Instruction {
+    build(accounts::UserAction { vault, user }, args::Resume {})
+}
+
+/// Push the session's expiry back by `days`.
+pub fn extend_session(user: Pubkey, vault: Pubkey, days: u16) -> Instruction {
+    build(accounts::UserAction { vault, user }, args::ExtendSession { days })
+}
+
+/// `bot` is the session's bot key, whose `BotStats` the payout updates and
+/// whose operator credit pays any compute fee settled. `payer` creates those stats if this is the bot's first payout: the user,
+/// or a [`Relayer`](crate::relayer::Relayer)'s fee payer.
+pub fn withdraw(user: Pubkey, vault: Pubkey, treasury: Pubkey, bot: Pubkey, payer: Pubkey) -> Instruction {
+    build(
+        accounts::Withdraw {
+            vault,
+            user,
+            treasury,
+            bot_stats: pda::bot_stats_address(&bot).0,
+            payer,
+            system_program: system_program::ID,
+            fee_router: pda::fee_router_address().0,
+            operator_credit: pda::operator_credit_address(&bot).0,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&user).0,
+        },
+        args::Withdraw {},
+    )
+}
+
+/// Withdraw everything and close the vault, returning its rent to `user`.
+pub fn withdraw_and_close(user: Pubkey, vault: Pubkey, treasury: Pubkey, bot: Pubkey, payer: Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawAndClose {
+            vault,
+            user,
+            treasury,
+            bot_stats: pda::bot_stats_address(&bot).0,
+            payer,
+            system_program: system_program::ID,
+            fee_router: pda::fee_router_address().0,
+            operator_credit: pda::operator_credit_address(&bot).0,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&user).0,
+            token_program: token::ID,
+        },
+        args::WithdrawAndClose {},
+    )
+}
+
+/// Have a `withdraw_and_close` also close the vault's empty token accounts,
+/// e.g. its WSOL account, returning their rent to the user.
+pub fn close_vault_token_accounts(mut withdraw_and_close: Instruction, token_accounts: &[Pubkey]) -> Instruction {
+    withdraw_and_close.accounts.extend(token_accounts.iter().map(|account| AccountMeta::new(*account, false)));
+    withdraw_and_close
+}
+
+/// Withdraw to `user` without their signing the transaction: `signature` is
+/// theirs over [`withdraw_message`]`(vault, deadline)`, made off-chain, and
+/// `payer` submits it and pays the fees. Returns the ed25519 verification and
+/// the withdrawal, which must be sent in that order, back to back.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_with_signature(
+    user: Pubkey,
+    vault: Pubkey,
+    treasury: Pubkey,
+    bot: Pubkey,
+    payer: Pubkey,
+    deadline: i64,
+    signature: &[u8; 64],
+) -> [Instruction; 2] {
+    let withdraw = build(
+        accounts::WithdrawWithSignature {
+            vault,
+            user,
+            treasury,
+            bot_stats: pda::bot_stats_address(&bot).0,
+            payer,
+            system_program: system_program::ID,
+            fee_router: pda::fee_router_address().0,
+            operator_credit: pda::operator_credit_address(&bot).0,
+            instructions_sysvar: sysvar::instructions::ID,
+            config: pda::config_address().0,
+            rewards: pda::rewards_address(&user).0,
+        },
+        args::WithdrawWithSignature { deadline },
+    );
+    [ed25519_verify(&user, signature, &withdraw_message(&vault, deadline)), withdraw]
+}
+
+/// Crank: collect accrued compute fees and settle the `user`'s duration points.
+pub fn deduct_compute_fee(cranker: Pubkey, vault: Pubkey, user: Pubkey, treasury: Pubkey, bot: Pubkey) -> Instruction {
+    build(deduct_compute_fee_accounts(cranker, vault, user, treasury, bot), args::DeductComputeFee {})
+}
+
+/// Crank: `deduct_compute_fee`, as a no-op when no full day is due.
+pub fn deduct_compute_fee_if_due(
+    cranker: Pubkey,
+    vault: Pubkey,
+    user: Pubkey,
+    treasury: Pubkey,
+    bot: Pubkey,
+) -> Instruction {
+    build(deduct_compute_fee_accounts(cranker, vault, user, treasury, bot), args::DeductComputeFeeIfDue {})
+}
+
+fn deduct_compute_fee_accounts(
+    cranker: Pubkey,
+    vault: Pubkey,
+    user: Pubkey,
+    treasury: Pubkey,
+    bot: Pubkey,
+) -> accounts::DeductComputeFee {
+    accounts::DeductComputeFee {
+        vault,
+        treasury,
+        cranker,
+        fee_router: pda::fee_router_address().0,
+        operator_credit: pda::operator_credit_address(&bot).0,
+        config: pda::config_address().0,
+        rewards: pda::rewards_address(&user).0,
+    }
+}
+
+/// Crank: mark a session past its expiry as expired.
+pub fn expire(cranker: Pubkey, vault: Pubkey) -> Instruction {
+    build(accounts::Expire { vault, cranker }, args::Expire {})
+}
+
+/// Crank: `expire`, as a no-op when the session isn't due to expire.
+pub fn expire_if_due(cranker: Pubkey, vault: Pubkey) -> Instruction {
+    build(accounts::Expire { vault, cranker }, args::ExpireIfDue {})
+}
+
+/// Bot: give notice that it will stop servicing the session.
+pub fn resign(bot: Pubkey, vault: Pubkey) -> Instruction {
+    build(
+        accounts::Resign {
+            vault,
+            bot,
+            config: pda::config_address().0,
+        },
+        args::Resign {},
+    )
+}
+
+/// Crank: check the vault still holds its balance, pausing it if not.
+pub fn assert_solvent(cranker: Pubkey, vault: Pubkey) -> Instruction {
+    build(accounts::AssertSolvent { vault, cranker }, args::AssertSolvent {})
+}
+
+/// Crank: pause a session whose bot the admin has blacklisted.
+pub fn pause_blacklisted(cranker: Pubkey, vault: Pubkey) -> Instruction {
+    build(
+        accounts::PauseBlacklisted { vault, config: pda::config_address().0, cranker },
+        args::PauseBlacklisted {},
+    )
+}
+
+/// Guardian: pause a session under active exploitation, for `reason`.
+pub fn guardian_pause(guardian: Pubkey, vault: Pubkey, reason: PauseReason) -> Instruction {
+    build(
+        accounts::GuardianPause { vault, config: pda::config_address().0, guardian },
+        args::GuardianPause { reason },
+    )
+}
+
+/// Crank: lift a guardian pause that has run MAX_GUARDIAN_PAUSE_DAYS (or,
+/// signed by the guardian, any guardian pause).
+pub fn lift_guardian_pause(cranker: Pubkey, vault: Pubkey) -> Instruction {
+    build(
+        accounts::LiftGuardianPause { vault, config: pda::config_address().0, cranker },
+        args::LiftGuardianPause {},
+    )
+}
+
+/// Fee router recipient: claim the share of fees accrued to `recipient`.
+pub fn claim_routed_fees(recipient: Pubkey) -> Instruction {
+    build(
+        accounts::ClaimRoutedFees { fee_router: pda::fee_router_address().0, recipient },
+        args::ClaimRoutedFees {},
+    )
+}
+
+/// Anyone: re-read the upgrade authority and deploy slot into `upgrade_info`.
+pub fn refresh_upgrade_info() -> Instruction {
+    let program_data = Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID).0;
+    build(
+        accounts::RefreshUpgradeInfo { upgrade_info: pda::upgrade_info_address().0, program: PROGRAM_ID, program_data },
+        args::RefreshUpgradeInfo {},
+    )
+}
+
+/// Guardian: record whether `bot` is verified and audited.
+pub fn attest_bot(guardian: Pubkey, bot: Pubkey, verified: bool, audited: bool) -> Instruction {
+    build(
+        accounts::AttestBot {
+            config: pda::config_address().0,
+            guardian,
+            bot_profile: pda::bot_profile_address(&bot).0,
+            system_program: system_program::ID,
+        },
+        args::AttestBot { bot, verified, audited },
+    )
+}
+
+/// Admin: put an attested `bot` in compute fee `tier`.
+pub fn set_bot_tier(admin: Pubkey, bot: Pubkey, tier: u8) -> Instruction {
+    build(
+        accounts::SetBotTier { config: pda::config_address().0, admin, bot_profile: pda::bot_profile_address(&bot).0 },
+        args::SetBotTier { bot, tier },
+    )
+}
+
+/// View: simulate and decode the return data as `SessionSummary`.
+pub fn get_session_summary(vault: Pubkey) -> Instruction {
+    build(accounts::ViewSession { vault }, args::GetSessionSummary {})
+}
+
+/// View: simulate and decode the return data as `AccruedFees`.
+pub fn get_accrued_fees(vault: Pubkey) -> Instruction {
+    build(accounts::ViewSession { vault }, args::GetAccruedFees {})
+}
+
+/// Compute budget: price each compute unit at `micro_lamports` (priority fee).
+pub fn compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, Vec::new())
+}
+
+/// Compute budget: cap the transaction at `units` compute units.
+pub fn compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, Vec::new())
+}
+
+/// ed25519 program: verify `signer`'s `signature` over `message`, all carried
+/// in the instruction's own data, failing the transaction if it's invalid.
+pub fn ed25519_verify(signer: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+    // Count and padding, one set of offsets, then the key, signature and message
+    const PUBKEY_OFFSET: u16 = 16;
+    const SIGNATURE_OFFSET: u16 = PUBKEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+    let mut data = vec![1, 0];
+    for field in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBKEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    Instruction::new_with_bytes(ED25519_PROGRAM_ID, &data, Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn picks_the_swap_variant_from_its_options() {
+        let mut swap = Swap {
+            vault: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            bot: Pubkey::new_unique(),
+            dex_program: Pubkey::new_unique(),
+            amount_in: 1,
+            minimum_amount_out: 0,
+            memo: None,
+            recent_slot: None,
+            jito_tip: false,
+            nonce: None,
+            dry_run: false,
+            batched: false,
+            output_token_account: None,
+            price_feeds: None,
+            base_token_account: None,
+            route: vec![AccountMeta::new(Pubkey::new_unique(), false)],
+            route_data: None,
+        };
+        let ix = execute_swap(&swap);
+        assert!(ix.data.starts_with(args::ExecuteSwap::DISCRIMINATOR));
+        // 13 declared accounts (absent optionals as the program id) + route
+        assert_eq!(ix.accounts.len(), 14);
+        assert_eq!(ix.accounts[8].pubkey, PROGRAM_ID);
+
+        swap.memo = Some([7; 32]);
+        assert!(execute_swap(&swap).data.starts_with(args::ExecuteSwapWithMemo::DISCRIMINATOR));
+        swap.recent_slot = Some(42);
+        assert!(execute_swap(&swap).data.starts_with(args::ExecuteSwapProtected::DISCRIMINATOR));
+        swap.nonce = Some(1);
+        assert!(execute_swap(&swap).data.starts_with(args::ExecuteSwapWithNonce::DISCRIMINATOR));
+        swap.route_data = Some(vec![1, 2, 3]);
+        assert!(execute_swap(&swap).data.starts_with(args::ExecuteSwapWithRoute::DISCRIMINATOR));
+
+        // Dry runs are a flag on the swap, passed through whichever carries it
+        swap.dry_run = true;
+        let ix = execute_swap(&swap);
+        assert!(ix.data.starts_with(args::ExecuteSwapWithRoute::DISCRIMINATOR));
+        assert_eq!(ix.data.last(), Some(&1));
+        swap.route_data = None;
+        assert!(execute_swap(&swap).data.starts_with(args::ExecuteSwapWithRoute::DISCRIMINATOR));
+        (swap.memo, swap.recent_slot, swap.nonce) = (None, None, None);
+        let ix = execute_swap(&swap);
+        assert!(ix.data.starts_with(args::ExecuteSwap::DISCRIMINATOR));
+        assert_eq!(ix.data.last(), Some(&1));
+    }
+
+    #[test]
+    fn carries_the_signed_withdrawal_in_the_verification() {
+        let (user, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let key = Pubkey::new_unique();
+        let [verify, withdraw] = withdraw_with_signature(user, vault, key, key, key, 1_000, &[9; 64]);
+        assert_eq!(verify.program_id, ED25519_PROGRAM_ID);
+        assert_eq!(&verify.data[16..48], user.as_ref());
+        assert_eq!(verify.data[48..112], [9; 64]);
+        assert_eq!(verify.data[112..], withdraw_message(&vault, 1_000));
+        assert!(withdraw.data.starts_with(args::WithdrawWithSignature::DISCRIMINATOR));
+    }
+}
@@ -0,0 +1,376 @@
+//! Jupiter routes for `execute_swap_with_route`.
+//!
+//! Gets a quote for selling the vault's base currency (SOL, or a token
+//! session's stablecoin), asks Jupiter for its `route`
+//! instruction with the vault PDA as the trader, and turns it into a [`Swap`]:
+//! Jupiter's program as `dex_program`, its accounts as the route (signer
+//! flags cleared, since only the program can sign for the vault), its data
+//! as `route_data` and the quote's `otherAmountThreshold` as
+//! `minimum_amount_out`. The program's Jupiter adapter checks the route
+//! before signing it. The transaction is compiled as v0 against Jupiter's
+//! lookup tables plus the session's own.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token::{self, native_mint};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_message::{v0, VersionedMessage};
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::instructions::{self, Swap};
+use crate::program::Vault;
+use crate::rpc::GentdexRpc;
+use crate::signer::{self, WalletSigner};
+use crate::ClientError;
+
+pub const JUPITER_PROGRAM_ID: Pubkey = anchor_lang::pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+pub const DEFAULT_API_URL: &str = "https://lite-api.jup.ag/swap/v1";
+
+/// A Jupiter quote, kept verbatim to hand back to `/swap-instructions`.
+#[derive(Clone, Debug)]
+pub struct Quote {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    /// `out_amount` less slippage
+    pub other_amount_threshold: u64,
+    raw: Value,
+}
+
+/// A Jupiter route ready for `execute_swap`.
+pub struct JupiterRoute {
+    pub swap: Swap,
+    /// Creates the vault's input (WSOL) and output token accounts if missing, paid by the bot
+    pub prepare: Vec<Instruction>,
+    /// Lookup tables the route's accounts live in
+    pub lookup_tables: Vec<Pubkey>,
+}
+
+pub struct JupiterClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Default for JupiterClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_API_URL)
+    }
+}
+
+impl JupiterClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Quote selling `amount_in` lamports of SOL for `output_mint`.
+    pub async fn quote(&self, output_mint: &Pubkey, amount_in: u64, slippage_bps: u16) -> Result<Quote, ClientError> {
+        self.quote_from(&native_mint::ID, output_mint, amount_in, slippage_bps).await
+    }
+
+    /// Quote selling `amount_in` base units of `input_mint` for `output_mint`,
+    /// e.g. a token session's base mint.
+    pub async fn quote_from(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount_in: u64,
+        slippage_bps: u16,
+    ) -> Result<Quote, ClientError> {
+        let raw: Value = self
+            .http
+            .get(format!("{}/quote", self.base_url))
+            .query(&[
+                ("inputMint", input_mint.to_string()),
+                ("outputMint", output_mint.to_string()),
+                ("amount", amount_in.to_string()),
+                ("slippageBps", slippage_bps.to_string()),
+            ])
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| ClientError::Rpc(format!("jupiter quote: {err}")))?
+            .json()
+            .await
+            .map_err(|err| ClientError::Rpc(format!("jupiter quote: {err}")))?;
+        parse_quote(raw)
+    }
+
+    /// Build the route for `quote` traded by `vault` (owned by `user`, paying
+    /// fees to `treasury`) and submitted by `bot`.
+    pub async fn route(
+        &self,
+        quote: &Quote,
+        vault: Pubkey,
+        user: Pubkey,
+        treasury: Pubkey,
+        bot: Pubkey,
+    ) -> Result<JupiterRoute, ClientError> {
+        let response: SwapInstructions = self
+            .http
+            .post(format!("{}/swap-instructions", self.base_url))
+            .json(&json!({
+                "quoteResponse": quote.raw,
+                "userPublicKey": vault.to_string(),
+                // The program wraps the SOL leg into the vault's WSOL account itself
+                "wrapAndUnwrapSol": false,
+                // The adapter takes the plain `route` instruction, trading
+                // straight between the vault's own token accounts
+                "useSharedAccounts": false,
+            }))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| ClientError::Rpc(format!("jupiter swap-instructions: {err}")))?
+            .json()
+            .await
+            .map_err(|err| ClientError::Rpc(format!("jupiter swap-instructions: {err}")))?;
+
+        build_route(quote, &response, vault, user, treasury, bot)
+    }
+}
+
+/// Quote, route and compile a signed v0 transaction selling `amount_in` of
+/// the vault's base currency for `output_mint`. The bot pays fees and any
+/// token account rent.
+#[allow(clippy::too_many_arguments)]
+pub async fn swap_transaction(
+    rpc: &GentdexRpc,
+    jupiter: &JupiterClient,
+    bot: &dyn WalletSigner,
+    vault_address: Pubkey,
+    output_mint: &Pubkey,
+    amount_in: u64,
+    slippage_bps: u16,
+    memo: Option<[u8; 32]>,
+) -> Result<(VersionedTransaction, u64), ClientError> {
+    let vault: Vault = rpc.fetch(&vault_address).await?;
+    let quote = jupiter.quote_from(&vault.base_mint, output_mint, amount_in, slippage_bps).await?;
+    let mut route = jupiter.route(&quote, vault_address, vault.user, vault.treasury, bot.pubkey()).await?;
+    route.swap.memo = memo;
+
+    build_transaction(rpc, bot, &route).await
+}
+
+/// Compile and sign `route` as a v0 transaction paid by `bot`, against the
+/// route's lookup tables and the session's own if it has one. Returns it
+/// with the last block height its blockhash is valid for, for
+/// `GentdexRpc::send_and_confirm_versioned`.
+pub async fn build_transaction(
+    rpc: &GentdexRpc,
+    bot: &dyn WalletSigner,
+    route: &JupiterRoute,
+) -> Result<(VersionedTransaction, u64), ClientError> {
+    let mut addresses = route.lookup_tables.clone();
+    for table in rpc.session_lookup_tables(&[route.swap.vault]).await? {
+        if !addresses.contains(&table) {
+            addresses.push(table);
+        }
+    }
+    let mut tables = Vec::with_capacity(addresses.len());
+    for address in &addresses {
+        tables.push(rpc.fetch_lookup_table(address).await?);
+    }
+
+    let mut ixs = route.prepare.clone();
+    ixs.push(instructions::execute_swap(&route.swap));
+
+    let (blockhash, last_valid_block_height) = rpc.latest_blockhash().await?;
+    let message = v0::Message::try_compile(&bot.pubkey(), &ixs, &tables, blockhash)
+        .map_err(|err| ClientError::Rpc(format!("compiling transaction: {err}")))?;
+    let tx = signer::sign_versioned(VersionedMessage::V0(message), &[bot]).await?;
+    Ok((tx, last_valid_block_height))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapInstructions {
+    swap_instruction: ApiInstruction,
+    #[serde(default)]
+    address_lookup_table_addresses: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiInstruction {
+    program_id: String,
+    accounts: Vec<ApiAccount>,
+    data: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiAccount {
+    pubkey: String,
+    is_writable: bool,
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, ClientError> {
+    value
+        .parse()
+        .map_err(|_| ClientError::Rpc(format!("jupiter: invalid pubkey {value}")))
+}
+
+fn parse_amount(raw: &Value, field: &str) -> Result<u64, ClientError> {
+    raw.get(field)
+        .and_then(Value::as_str)
+        .and_then(|amount| amount.parse().ok())
+        .ok_or_else(|| ClientError::Rpc(format!("jupiter quote: missing {field}")))
+}
+
+fn parse_mint(raw: &Value, field: &str) -> Result<Pubkey, ClientError> {
+    let mint = raw
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ClientError::Rpc(format!("jupiter quote: missing {field}")))?;
+    parse_pubkey(mint)
+}
+
+fn parse_quote(raw: Value) -> Result<Quote, ClientError> {
+    Ok(Quote {
+        input_mint: parse_mint(&raw, "inputMint")?,
+        output_mint: parse_mint(&raw, "outputMint")?,
+        in_amount: parse_amount(&raw, "inAmount")?,
+        out_amount: parse_amount(&raw, "outAmount")?,
+        other_amount_threshold: parse_amount(&raw, "otherAmountThreshold")?,
+        raw,
+    })
+}
+
+fn build_route(
+    quote: &Quote,
+    response: &SwapInstructions,
+    vault: Pubkey,
+    user: Pubkey,
+    treasury: Pubkey,
+    bot: Pubkey,
+) -> Result<JupiterRoute, ClientError> {
+    let swap_ix = &response.swap_instruction;
+    let route = swap_ix
+        .accounts
+        .iter()
+        .map(|account| {
+            Ok(AccountMeta {
+                pubkey: parse_pubkey(&account.pubkey)?,
+                is_signer: false,
+                is_writable: account.is_writable,
+            })
+        })
+        .collect::<Result<Vec<_>, ClientError>>()?;
+    let route_data = BASE64
+        .decode(&swap_ix.data)
+        .map_err(|err| ClientError::Rpc(format!("jupiter: invalid instruction data: {err}")))?;
+
+    let prepare = [quote.input_mint, quote.output_mint]
+        .iter()
+        .map(|mint| create_associated_token_account_idempotent(&bot, &vault, mint, &spl_token::ID))
+        .collect();
+
+    Ok(JupiterRoute {
+        swap: Swap {
+            vault,
+            user,
+            treasury,
+            bot,
+            dex_program: parse_pubkey(&swap_ix.program_id)?,
+            amount_in: quote.in_amount,
+            minimum_amount_out: quote.other_amount_threshold,
+            memo: None,
+            recent_slot: None,
+            jito_tip: false,
+            nonce: None,
+            dry_run: false,
+            batched: false,
+            output_token_account: Some(get_associated_token_address(&vault, &quote.output_mint)),
+            price_feeds: None,
+            // Token sessions sell from their base token account
+            base_token_account: (quote.input_mint != native_mint::ID)
+                .then(|| get_associated_token_address(&vault, &quote.input_mint)),
+            route,
+            route_data: Some(route_data),
+        },
+        prepare,
+        lookup_tables: response
+            .address_lookup_table_addresses
+            .iter()
+            .map(|address| parse_pubkey(address))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_a_jupiter_route_into_a_vault_swap() {
+        let vault = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let quote = parse_quote(json!({
+            "inputMint": native_mint::ID.to_string(),
+            "outputMint": usdc.to_string(),
+            "inAmount": "1000000000",
+            "outAmount": "150000000",
+            "otherAmountThreshold": "149250000",
+        }))
+        .unwrap();
+        let response: SwapInstructions = serde_json::from_value(json!({
+            "swapInstruction": {
+                "programId": JUPITER_PROGRAM_ID.to_string(),
+                "accounts": [
+                    { "pubkey": vault.to_string(), "isSigner": true, "isWritable": true },
+                    { "pubkey": Pubkey::new_unique().to_string(), "isSigner": false, "isWritable": false },
+                ],
+                "data": BASE64.encode([1, 2, 3]),
+            },
+            "addressLookupTableAddresses": [Pubkey::new_unique().to_string()],
+        }))
+        .unwrap();
+
+        let [user, treasury, bot] = [(); 3].map(|_| Pubkey::new_unique());
+        let route = build_route(&quote, &response, vault, user, treasury, bot).unwrap();
+        assert_eq!(route.swap.dex_program, JUPITER_PROGRAM_ID);
+        assert_eq!(route.swap.amount_in, 1_000_000_000);
+        assert_eq!(route.swap.minimum_amount_out, 149_250_000);
+        assert!(route.swap.route.iter().all(|meta| !meta.is_signer));
+        assert_eq!(route.swap.route_data, Some(vec![1, 2, 3]));
+        assert_eq!(route.prepare.len(), 2);
+        assert_eq!(route.lookup_tables.len(), 1);
+        assert_eq!(route.swap.base_token_account, None);
+    }
+
+    #[test]
+    fn token_sessions_sell_from_their_base_account() {
+        let vault = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let quote = parse_quote(json!({
+            "inputMint": usdc.to_string(),
+            "outputMint": native_mint::ID.to_string(),
+            "inAmount": "150000000",
+            "outAmount": "1000000000",
+            "otherAmountThreshold": "995000000",
+        }))
+        .unwrap();
+        let response: SwapInstructions = serde_json::from_value(json!({
+            "swapInstruction": {
+                "programId": JUPITER_PROGRAM_ID.to_string(),
+                "accounts": [],
+                "data": BASE64.encode([1, 2, 3]),
+            },
+        }))
+        .unwrap();
+
+        let [user, treasury, bot] = [(); 3].map(|_| Pubkey::new_unique());
+        let route = build_route(&quote, &response, vault, user, treasury, bot).unwrap();
+        assert_eq!(route.swap.base_token_account, Some(get_associated_token_address(&vault, &usdc)));
+        assert_eq!(route.swap.output_token_account, Some(get_associated_token_address(&vault, &native_mint::ID)));
+    }
+}
@@ -0,0 +1,178 @@
+//! Ledger hardware wallets running the Solana app, over USB HID.
+//!
+//! Speaks the app's APDU protocol directly: the key at
+//! `m/44'/501'/<account>'/<change>'` is read once on connect, and every
+//! message is confirmed on the device. Signing blocks the calling task until
+//! the user approves or rejects it.
+
+use std::sync::Mutex;
+
+use anchor_lang::prelude::Pubkey;
+use futures_util::future::BoxFuture;
+use hidapi::{HidApi, HidDevice};
+use solana_signature::Signature;
+
+use crate::signer::WalletSigner;
+use crate::ClientError;
+
+const LEDGER_VENDOR_ID: u16 = 0x2c97;
+/// HID packets are 64 bytes, sent after a zero report id
+const HID_PACKET_SIZE: usize = 64;
+const CHANNEL: [u8; 2] = [0x01, 0x01];
+const TAG_APDU: u8 = 0x05;
+/// Channel, tag and sequence number, on every packet
+const TRANSPORT_HEADER_LEN: usize = 5;
+/// Total length, then CLA, INS, P1, P2 and Lc, on the first packet
+const APDU_HEADER_LEN: usize = 7;
+/// Longest APDU payload
+const MAX_CHUNK_SIZE: usize = 255;
+
+const CLA: u8 = 0xe0;
+const INS_GET_PUBKEY: u8 = 0x05;
+const INS_SIGN_MESSAGE: u8 = 0x06;
+const P1_NON_CONFIRM: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P2_EXTEND: u8 = 0x01;
+const P2_MORE: u8 = 0x02;
+
+const STATUS_OK: u16 = 0x9000;
+const STATUS_REJECTED: u16 = 0x6985;
+
+const HARDENED: u32 = 0x8000_0000;
+
+pub struct LedgerSigner {
+    device: Mutex<HidDevice>,
+    derivation_path: Vec<u8>,
+    pubkey: Pubkey,
+}
+
+impl LedgerSigner {
+    /// Open the first connected Ledger and read the key at
+    /// `m/44'/501'/<account>'/<change>'`. `solana-keygen`'s `usb://ledger`
+    /// default is account 0 with no change level; pass `None` for that.
+    pub fn connect(account: u32, change: Option<u32>) -> Result<Self, ClientError> {
+        let api = HidApi::new().map_err(ledger_error)?;
+        let info = api
+            .device_list()
+            .find(|info| info.vendor_id() == LEDGER_VENDOR_ID)
+            .ok_or_else(|| ClientError::Rpc("ledger: no device connected".to_string()))?;
+        let device = info.open_device(&api).map_err(ledger_error)?;
+
+        let mut path = vec![44 | HARDENED, 501 | HARDENED, account | HARDENED];
+        path.extend(change.map(|change| change | HARDENED));
+        let mut derivation_path = vec![path.len() as u8];
+        for index in path {
+            derivation_path.extend(index.to_be_bytes());
+        }
+
+        let mut signer = Self {
+            device: Mutex::new(device),
+            derivation_path,
+            pubkey: Pubkey::default(),
+        };
+        let key = signer.exchange(INS_GET_PUBKEY, P1_NON_CONFIRM, 0, &signer.derivation_path)?;
+        signer.pubkey = Pubkey::try_from(key.as_slice())
+            .map_err(|_| ClientError::Rpc(format!("ledger: unexpected {}-byte public key", key.len())))?;
+        Ok(signer)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, ClientError> {
+        // One derivation path, then as much of the message as fits; the rest
+        // follows in extension chunks
+        let mut first = vec![1];
+        first.extend_from_slice(&self.derivation_path);
+        let (head, rest) = message.split_at(message.len().min(MAX_CHUNK_SIZE - first.len()));
+        first.extend_from_slice(head);
+
+        let chunks: Vec<&[u8]> = rest.chunks(MAX_CHUNK_SIZE).collect();
+        let mut response = self.exchange(INS_SIGN_MESSAGE, P1_CONFIRM, if chunks.is_empty() { 0 } else { P2_MORE }, &first)?;
+        for (i, chunk) in chunks.iter().enumerate() {
+            let p2 = if i + 1 < chunks.len() { P2_EXTEND | P2_MORE } else { P2_EXTEND };
+            response = self.exchange(INS_SIGN_MESSAGE, P1_CONFIRM, p2, chunk)?;
+        }
+
+        Signature::try_from(response.as_slice())
+            .map_err(|_| ClientError::Rpc(format!("ledger: unexpected {}-byte signature", response.len())))
+    }
+
+    /// Send one APDU and return the response data.
+    fn exchange(&self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>, ClientError> {
+        let device = self.device.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_apdu(&device, ins, p1, p2, data)?;
+        let mut response = read_response(&device)?;
+        if response.len() < 2 {
+            return Err(ClientError::Rpc("ledger: truncated response".to_string()));
+        }
+        let status = u16::from_be_bytes([response[response.len() - 2], response[response.len() - 1]]);
+        response.truncate(response.len() - 2);
+        match status {
+            STATUS_OK => Ok(response),
+            STATUS_REJECTED => Err(ClientError::Rpc("ledger: rejected on the device".to_string())),
+            status => Err(ClientError::Rpc(format!(
+                "ledger: status {status:#06x} (is the Solana app open, with blind signing enabled?)"
+            ))),
+        }
+    }
+}
+
+impl WalletSigner for LedgerSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, ClientError>> {
+        Box::pin(async move { self.sign(message) })
+    }
+}
+
+/// Frame `[CLA, ins, p1, p2, len, data]` into HID packets.
+fn write_apdu(device: &HidDevice, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<(), ClientError> {
+    let mut apdu = Vec::with_capacity(APDU_HEADER_LEN + data.len());
+    apdu.extend(((data.len() + 5) as u16).to_be_bytes());
+    apdu.extend([CLA, ins, p1, p2, data.len() as u8]);
+    apdu.extend_from_slice(data);
+
+    for (sequence, chunk) in apdu.chunks(HID_PACKET_SIZE - TRANSPORT_HEADER_LEN).enumerate() {
+        let mut packet = [0u8; HID_PACKET_SIZE + 1];
+        packet[1..3].copy_from_slice(&CHANNEL);
+        packet[3] = TAG_APDU;
+        packet[4..6].copy_from_slice(&(sequence as u16).to_be_bytes());
+        packet[1 + TRANSPORT_HEADER_LEN..1 + TRANSPORT_HEADER_LEN + chunk.len()].copy_from_slice(chunk);
+        device.write(&packet).map_err(ledger_error)?;
+    }
+    Ok(())
+}
+
+/// Reassemble a response, status word included, from HID packets.
+fn read_response(device: &HidDevice) -> Result<Vec<u8>, ClientError> {
+    let mut response = Vec::new();
+    let mut length = None;
+    let mut sequence: u16 = 0;
+    loop {
+        let mut packet = [0u8; HID_PACKET_SIZE];
+        let read = device.read(&mut packet).map_err(ledger_error)?;
+        if read < TRANSPORT_HEADER_LEN
+            || packet[0..2] != CHANNEL
+            || packet[2] != TAG_APDU
+            || u16::from_be_bytes([packet[3], packet[4]]) != sequence
+        {
+            return Err(ClientError::Rpc("ledger: malformed response packet".to_string()));
+        }
+        let mut body = &packet[TRANSPORT_HEADER_LEN..read];
+        if sequence == 0 {
+            length = Some(u16::from_be_bytes([body[0], body[1]]) as usize);
+            body = &body[2..];
+        }
+        response.extend_from_slice(body);
+        let length = length.unwrap_or_default();
+        if response.len() >= length {
+            response.truncate(length);
+            return Ok(response);
+        }
+        sequence += 1;
+    }
+}
+
+fn ledger_error(err: hidapi::HidError) -> ClientError {
+    ClientError::Rpc(format!("ledger: {err}"))
+}
@@ -0,0 +1,69 @@
+//! Rust SDK for the GentDex escrow program.
+//!
+//! Builds instructions, derives PDAs and decodes accounts and events using the
+//! program crate's own Anchor types, so discriminators and layouts can't drift
+//! from what's deployed. Transport-agnostic: bring your own RPC client and
+//! hand account data or transaction logs to the decoders.
+//!
+//! - [`cluster`]: mainnet and devnet program IDs, and which one this build
+//!   targets (feature `devnet` for devnet)
+//! - [`pda`]: vault, config, registry, rewards, stake and report addresses
+//! - [`instructions`]: a typed builder for any instruction, plus helpers for
+//!   the session lifecycle that fill in derived accounts
+//! - [`state`]: fetch and decode `Vault`, `ProtocolConfig`, `UserRegistry`, ...
+//! - [`events`]: decode GentDex events out of transaction logs
+//! - [`idl`]: the program's IDL, which the event, instruction and account
+//!   lists are generated from, and decoders for any instruction or account
+//! - [`error`]: [`ProgramError`] turns failed transactions' error codes back
+//!   into `EscrowError` variants, with hints on what to do about them
+//! - `rpc` (feature `rpc`, on by default): an async JSON-RPC client that
+//!   sends, simulates and confirms transactions, and `jupiter`, which turns
+//!   a Jupiter route into an `execute_swap_with_route` transaction; `stream`, a
+//!   reconnecting websocket subscription to decoded events; `solana_pay`,
+//!   transaction-request links and responses for funding from any wallet;
+//!   `signer`, signing with local keys, a remote signing service, or a
+//!   Ledger (feature `ledger`); `bundle`, atomic multi-instruction v0
+//!   transactions with compute budget and priority fee filled in; `pool`,
+//!   endpoint health for spreading calls over several RPC providers;
+//!   `statement`, end-of-session statements reconciled against the vault;
+//!   `lots`, FIFO or LIFO cost-basis lots for tax reporting; `nonce`,
+//!   durable-nonce transactions for bots on unreliable connections;
+//!   `relayer`, fee-paying relayers for users with no SOL to spare
+
+#[cfg(feature = "rpc")]
+pub mod bundle;
+pub mod cluster;
+pub mod error;
+pub mod events;
+pub mod idl;
+pub mod instructions;
+#[cfg(feature = "rpc")]
+pub mod jupiter;
+#[cfg(feature = "ledger")]
+mod ledger;
+#[cfg(feature = "rpc")]
+pub mod lots;
+#[cfg(feature = "rpc")]
+pub mod nonce;
+#[cfg(feature = "rpc")]
+pub mod pool;
+#[cfg(feature = "rpc")]
+pub mod relayer;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "rpc")]
+pub mod signer;
+#[cfg(feature = "rpc")]
+pub mod solana_pay;
+pub mod state;
+#[cfg(feature = "rpc")]
+pub mod statement;
+#[cfg(feature = "rpc")]
+pub mod stream;
+
+pub use error::{ClientError, ProgramError};
+pub use gentdex_escrow::pda;
+pub use gentdex_escrow::ID as PROGRAM_ID;
+
+/// Program types (accounts, events, argument structs) re-exported for callers.
+pub use gentdex_escrow as program;
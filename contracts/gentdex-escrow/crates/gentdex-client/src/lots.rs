@@ -0,0 +1,263 @@
+//! Lot-level cost basis for a session, from its decoded events.
+//!
+//! Deposits and transfers in open lots of the session's currency. Swaps,
+//! fees, withdrawals and transfers out use them up, in FIFO or LIFO order,
+//! and each swap opens a lot of the token it bought whose cost basis is the
+//! currency it spent. Swaps never sell tokens, so token lots stay open.
+//!
+//! Amounts and cost basis are raw units: lamports, base-mint units or
+//! output-mint units. Converting to fiat is left to the tax tool.
+
+use serde::Serialize;
+
+use gentdex_escrow::Vault;
+
+use crate::events::Event;
+use crate::statement::SessionTransaction;
+
+/// Held longer than this, a disposal is long-term
+pub const LONG_TERM_SECONDS: i64 = 365 * 86_400;
+
+/// Which lots a disposal uses up first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    /// Oldest first
+    Fifo,
+    /// Newest first
+    Lifo,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Lot {
+    /// `SOL`, or a mint
+    pub asset: String,
+    pub amount: u64,
+    /// What the lot cost, in the session's currency
+    pub cost_basis: u64,
+    pub acquired_at: Option<i64>,
+    pub acquired_signature: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisposalKind {
+    Swap,
+    Fee,
+    Withdrawal,
+    TransferOut,
+}
+
+/// Part of one lot leaving the session.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Disposal {
+    pub kind: DisposalKind,
+    pub asset: String,
+    pub amount: u64,
+    /// This part's share of the lot's cost basis
+    pub cost_basis: u64,
+    /// `None` when no lot covered it: currency a swap route refunded, which
+    /// raises the balance without an event
+    pub acquired_at: Option<i64>,
+    pub acquired_signature: Option<String>,
+    pub disposed_at: Option<i64>,
+    pub disposed_signature: String,
+    pub long_term: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LotReport {
+    pub method: Method,
+    pub currency: String,
+    pub disposals: Vec<Disposal>,
+    /// Lots still held, currency and tokens
+    pub open: Vec<Lot>,
+}
+
+impl LotReport {
+    /// Match `history`'s disposals against its lots with `method`.
+    pub fn build(vault: &Vault, history: &[SessionTransaction], method: Method) -> Self {
+        let currency = if vault.is_sol_session() { "SOL".to_string() } else { vault.base_mint.to_string() };
+        let mut book = Book { method, lots: Vec::new(), disposals: Vec::new() };
+        let session = vault.session_id;
+
+        for tx in history {
+            let at = |asset: &str| At { asset: asset.to_string(), time: tx.block_time, signature: &tx.signature };
+            let base = at(&currency);
+            for event in &tx.events {
+                match event {
+                    Event::Deposited(e) if e.session_id == session => {
+                        book.acquire(&base, e.amount, e.amount);
+                        book.dispose(&base, e.fee, DisposalKind::Fee);
+                    }
+                    Event::SessionTransferred(e) if e.destination_session_id == session => {
+                        book.acquire(&base, e.amount, e.amount);
+                    }
+                    Event::SwapExecuted(e) if e.session_id == session => {
+                        book.dispose(&base, e.amount_in, DisposalKind::Swap);
+                        if e.amount_out > 0 {
+                            book.acquire(&at(&e.output_mint.to_string()), e.amount_out, e.amount_in);
+                        }
+                    }
+                    Event::ComputeFeeDeducted(e) if e.session_id == session => {
+                        book.dispose(&base, e.fee, DisposalKind::Fee);
+                    }
+                    Event::Withdrawn(e) if e.session_id == session => {
+                        book.dispose(&base, e.compute_fee, DisposalKind::Fee);
+                        book.dispose(&base, e.amount, DisposalKind::Withdrawal);
+                    }
+                    Event::SessionTransferred(e) if e.source_session_id == session => {
+                        book.dispose(&base, e.compute_fee, DisposalKind::Fee);
+                        book.dispose(&base, e.amount, DisposalKind::TransferOut);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Self { method, currency, disposals: book.disposals, open: book.lots }
+    }
+}
+
+/// Where and when a lot moves.
+struct At<'a> {
+    asset: String,
+    time: Option<i64>,
+    signature: &'a str,
+}
+
+struct Book {
+    method: Method,
+    /// Oldest first
+    lots: Vec<Lot>,
+    disposals: Vec<Disposal>,
+}
+
+impl Book {
+    fn acquire(&mut self, at: &At, amount: u64, cost_basis: u64) {
+        self.lots.push(Lot {
+            asset: at.asset.clone(),
+            amount,
+            cost_basis,
+            acquired_at: at.time,
+            acquired_signature: at.signature.to_string(),
+        });
+    }
+
+    fn dispose(&mut self, at: &At, mut amount: u64, kind: DisposalKind) {
+        let disposal = |amount, cost_basis, lot: Option<&Lot>| Disposal {
+            kind,
+            asset: at.asset.clone(),
+            amount,
+            cost_basis,
+            acquired_at: lot.and_then(|lot| lot.acquired_at),
+            acquired_signature: lot.map(|lot| lot.acquired_signature.clone()),
+            disposed_at: at.time,
+            disposed_signature: at.signature.to_string(),
+            long_term: match (lot.and_then(|lot| lot.acquired_at), at.time) {
+                (Some(acquired), Some(disposed)) => disposed - acquired > LONG_TERM_SECONDS,
+                _ => false,
+            },
+        };
+
+        while amount > 0 {
+            let mut held = self.lots.iter().enumerate().filter(|(_, lot)| lot.asset == at.asset);
+            let next = match self.method {
+                Method::Fifo => held.next(),
+                Method::Lifo => held.next_back(),
+            };
+            let Some((index, _)) = next else {
+                self.disposals.push(disposal(amount, amount, None));
+                return;
+            };
+
+            let lot = &mut self.lots[index];
+            let taken = amount.min(lot.amount);
+            let cost = (lot.cost_basis as u128 * taken as u128 / lot.amount as u128) as u64;
+            let part = disposal(taken, cost, Some(lot));
+            lot.amount -= taken;
+            lot.cost_basis -= cost;
+            if lot.amount == 0 {
+                self.lots.remove(index);
+            }
+            self.disposals.push(part);
+            amount -= taken;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::{AccountDeserialize, Discriminator};
+    use anchor_spl::token::spl_token::native_mint;
+    use gentdex_escrow::{Deposited, SwapExecuted, Withdrawn};
+
+    use super::*;
+
+    #[test]
+    fn matches_disposals_to_lots_in_order() {
+        let session_id = [3; 16];
+        let address = Pubkey::new_unique();
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(Vault::DISCRIMINATOR.len() + 2048, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.session_id = session_id;
+        vault.base_mint = native_mint::ID;
+
+        let deposit = |amount| {
+            Event::Deposited(Deposited { session_id, vault: address, amount, fee: 0, trading_balance: amount, expires_at: 0 })
+        };
+        let mint = Pubkey::new_unique();
+        let tx = |signature: &str, block_time, events| SessionTransaction {
+            signature: signature.to_string(),
+            slot: 0,
+            block_time: Some(block_time),
+            events,
+        };
+        let history = [
+            tx("first", 0, vec![deposit(100)]),
+            tx("second", LONG_TERM_SECONDS, vec![deposit(50)]),
+            tx("swap", LONG_TERM_SECONDS + 10, vec![Event::SwapExecuted(SwapExecuted {
+                session_id,
+                vault: address,
+                bot: Pubkey::new_unique(),
+                dex_program: Pubkey::new_unique(),
+                amount_in: 120,
+                minimum_amount_out: 0,
+                timestamp: 0,
+                output_mint: mint,
+                amount_out: 7,
+                memo: [0; 32],
+            })]),
+            // 20 more than the lots left: a route refunded it
+            tx("withdraw", LONG_TERM_SECONDS + 20, vec![Event::Withdrawn(Withdrawn {
+                session_id,
+                vault: address,
+                amount: 50,
+                compute_fee: 0,
+                user: vault.user,
+            })]),
+        ];
+
+        let of_kind = |report: &LotReport, kind| -> Vec<(u64, Option<String>, bool)> {
+            report
+                .disposals
+                .iter()
+                .filter(|d| d.kind == kind)
+                .map(|d| (d.amount, d.acquired_signature.clone(), d.long_term))
+                .collect()
+        };
+        let lot = |signature: &str| Some(signature.to_string());
+
+        let fifo = LotReport::build(&vault, &history, Method::Fifo);
+        assert_eq!(of_kind(&fifo, DisposalKind::Swap), [(100, lot("first"), true), (20, lot("second"), false)]);
+        assert_eq!(of_kind(&fifo, DisposalKind::Withdrawal), [(30, lot("second"), false), (20, None, false)]);
+        assert_eq!(fifo.open.len(), 1);
+        assert_eq!((fifo.open[0].asset.clone(), fifo.open[0].amount), (mint.to_string(), 7));
+        assert_eq!(fifo.open[0].cost_basis, 120);
+
+        let lifo = LotReport::build(&vault, &history, Method::Lifo);
+        assert_eq!(of_kind(&lifo, DisposalKind::Swap), [(50, lot("second"), false), (70, lot("first"), true)]);
+    }
+}
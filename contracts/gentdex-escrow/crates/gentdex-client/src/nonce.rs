@@ -0,0 +1,139 @@
+//! Durable nonces, for bots on connections too unreliable to land a
+//! transaction within a blockhash's ~60s. Signed against a nonce account's
+//! stored blockhash instead of a recent one, a transaction stays valid until
+//! the nonce advances, which every such transaction does first, so it still
+//! lands at most once and can be re-sent as often as it takes.
+//!
+//! A bot creates one [`NonceAccount`] per signing key, then assembles each
+//! swap with [`NonceAccount::swap_transaction`] and sends it with
+//! [`GentdexRpc::send_and_confirm_durable`]. Give those swaps a trade nonce
+//! ([`Swap::nonce`]) too, so one signed long ago can't land after a newer
+//! trade.
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::solana_program::instruction::Instruction;
+use solana_hash::Hash;
+use solana_system_interface::instruction as system_instruction;
+use solana_transaction::Transaction;
+
+use crate::instructions::{self, Swap};
+use crate::rpc::GentdexRpc;
+use crate::signer::{self, WalletSigner};
+use crate::ClientError;
+
+/// Size of a system nonce account
+pub const NONCE_ACCOUNT_LENGTH: usize = 80;
+/// Offset of the stored blockhash, after the version, state and authority
+const DURABLE_NONCE_OFFSET: usize = 40;
+const NONCE_STATE_INITIALIZED: u32 = 1;
+
+/// A system nonce account and the key allowed to advance it, usually the bot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceAccount {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl NonceAccount {
+    pub fn new(address: Pubkey, authority: Pubkey) -> Self {
+        Self { address, authority }
+    }
+
+    /// Create and initialize the account, rent-exempt, funded by `payer`.
+    /// The account's own keypair signs as well.
+    pub fn create(&self, payer: Pubkey) -> Vec<Instruction> {
+        let lamports = Rent::default().minimum_balance(NONCE_ACCOUNT_LENGTH);
+        system_instruction::create_nonce_account(&payer, &self.address, &self.authority, lamports)
+    }
+
+    /// Advance the nonce, invalidating every transaction signed against the
+    /// current one. First instruction of any transaction using it.
+    pub fn advance(&self) -> Instruction {
+        system_instruction::advance_nonce_account(&self.address, &self.authority)
+    }
+
+    /// The blockhash currently stored in the account.
+    pub async fn fetch(&self, rpc: &GentdexRpc) -> Result<Hash, ClientError> {
+        let (owner, data) = rpc
+            .get_account(&self.address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(self.address))?;
+        stored_nonce(&data)
+            .filter(|_| owner == anchor_lang::system_program::ID)
+            .ok_or_else(|| ClientError::Rpc(format!("{} is not an initialized nonce account", self.address)))
+    }
+
+    /// `instructions` behind an advance of the nonce, signed against its
+    /// current value. `payer` pays the fee; `signers` must include it and
+    /// the nonce authority.
+    pub async fn transaction(
+        &self,
+        rpc: &GentdexRpc,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        signers: &[&dyn WalletSigner],
+    ) -> Result<Transaction, ClientError> {
+        let nonce = self.fetch(rpc).await?;
+        let mut tx = Transaction::new_with_payer(&self.with_advance(instructions), Some(payer));
+        signer::sign_transaction(&mut tx, signers, nonce).await?;
+        Ok(tx)
+    }
+
+    /// [`transaction`](Self::transaction) for one bot swap, paid by the bot.
+    pub async fn swap_transaction(
+        &self,
+        rpc: &GentdexRpc,
+        swap: &Swap,
+        signers: &[&dyn WalletSigner],
+    ) -> Result<Transaction, ClientError> {
+        self.transaction(rpc, &swap.bot, &[instructions::execute_swap(swap)], signers).await
+    }
+
+    fn with_advance(&self, instructions: &[Instruction]) -> Vec<Instruction> {
+        let mut ixs = vec![self.advance()];
+        ixs.extend(instructions.iter().cloned());
+        ixs
+    }
+}
+
+/// The blockhash stored in a nonce account's data, or `None` if it isn't an
+/// initialized nonce account.
+pub fn stored_nonce(data: &[u8]) -> Option<Hash> {
+    if data.len() != NONCE_ACCOUNT_LENGTH {
+        return None;
+    }
+    let state = u32::from_le_bytes(data[4..8].try_into().unwrap());
+    if state != NONCE_STATE_INITIALIZED {
+        return None;
+    }
+    let nonce: [u8; 32] = data[DURABLE_NONCE_OFFSET..DURABLE_NONCE_OFFSET + 32].try_into().unwrap();
+    Some(Hash::new_from_array(nonce))
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::system_program;
+
+    use super::*;
+
+    #[test]
+    fn advances_the_nonce_first() {
+        let nonce = NonceAccount::new(Pubkey::new_unique(), Pubkey::new_unique());
+        let created = nonce.create(Pubkey::new_unique());
+        assert_eq!(created.len(), 2);
+        assert!(created.iter().all(|ix| ix.program_id == system_program::ID));
+
+        let swap = Instruction::new_with_bytes(crate::PROGRAM_ID, &[], vec![]);
+        let ixs = nonce.with_advance(&[swap]);
+        assert_eq!(ixs[0], nonce.advance());
+        assert_eq!(ixs[0].accounts[0].pubkey, nonce.address);
+        assert!(ixs[0].accounts[2].is_signer);
+        assert_eq!(ixs[1].program_id, crate::PROGRAM_ID);
+
+        let mut data = vec![0; NONCE_ACCOUNT_LENGTH];
+        assert_eq!(stored_nonce(&data), None);
+        data[..8].copy_from_slice(&[1, 0, 0, 0, 1, 0, 0, 0]);
+        data[DURABLE_NONCE_OFFSET..DURABLE_NONCE_OFFSET + 32].fill(9);
+        assert_eq!(stored_nonce(&data), Some(Hash::new_from_array([9; 32])));
+    }
+}
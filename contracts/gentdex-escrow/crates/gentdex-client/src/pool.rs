@@ -0,0 +1,231 @@
+//! Health tracking for a set of RPC endpoints, so one client can spread its
+//! reads over several providers and keep sending when one of them throttles
+//! or falls behind.
+//!
+//! Reads go round-robin over the healthy endpoints. Sends go to the first
+//! healthy endpoint in the order given, so a paid or staked primary handles
+//! them while it's up. An endpoint that returns HTTP 429 rests for its
+//! `Retry-After` (or [`RATE_LIMIT_COOLDOWN`]); one that fails
+//! [`FAILURES_BEFORE_COOLDOWN`] times in a row rests for a doubling period;
+//! one whose average latency passes [`DEGRADED_LATENCY`] is only used when
+//! nothing better is available. `GentdexRpc` reports every call here.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a rate-limited endpoint rests without a `Retry-After`
+pub const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(10);
+/// Consecutive failures before an endpoint is rested
+pub const FAILURES_BEFORE_COOLDOWN: u32 = 3;
+/// First rest after repeated failures, doubling with each further failure
+const FAILURE_COOLDOWN: Duration = Duration::from_secs(5);
+const MAX_COOLDOWN: Duration = Duration::from_secs(120);
+/// Average latency past which an endpoint counts as degraded
+pub const DEGRADED_LATENCY: Duration = Duration::from_secs(2);
+
+/// What a call is for, which decides where it goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Route {
+    Read,
+    Send,
+}
+
+/// How a call to an endpoint went.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The node answered, even if with an error, after this long
+    Answered(Duration),
+    /// HTTP 429, with the `Retry-After` delay if one was given
+    RateLimited(Option<Duration>),
+    /// Transport error, 5xx, or the node reported it's behind
+    Failed,
+}
+
+/// An endpoint's health, for metrics and logs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndpointStatus {
+    pub url: String,
+    /// Not resting after a rate limit or repeated failures
+    pub available: bool,
+    pub degraded: bool,
+    /// Moving average of answered calls' latency
+    pub latency: Option<Duration>,
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    resting_until: Option<Instant>,
+    latency: Option<Duration>,
+}
+
+impl Health {
+    fn available(&self, now: Instant) -> bool {
+        self.resting_until.is_none_or(|until| until <= now)
+    }
+
+    fn degraded(&self) -> bool {
+        self.latency.is_some_and(|latency| latency > DEGRADED_LATENCY)
+    }
+}
+
+#[derive(Debug)]
+pub struct EndpointPool {
+    urls: Vec<String>,
+    health: Vec<Mutex<Health>>,
+    next_read: AtomicUsize,
+}
+
+impl EndpointPool {
+    /// Panics if `urls` is empty.
+    pub fn new(urls: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let urls: Vec<String> = urls.into_iter().map(Into::into).collect();
+        assert!(!urls.is_empty(), "an endpoint pool needs at least one URL");
+        Self {
+            health: urls.iter().map(|_| Mutex::default()).collect(),
+            urls,
+            next_read: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    pub fn url(&self, index: usize) -> &str {
+        &self.urls[index]
+    }
+
+    /// The endpoint to use next for `route`.
+    pub fn pick(&self, route: Route) -> usize {
+        self.pick_at(route, Instant::now())
+    }
+
+    /// Whether any endpoint other than `index` is available right now, i.e.
+    /// whether a retry can go straight there instead of backing off.
+    pub fn has_alternative(&self, index: usize) -> bool {
+        let now = Instant::now();
+        (0..self.len()).any(|other| other != index && self.health(other).available(now))
+    }
+
+    pub fn report(&self, index: usize, outcome: Outcome) {
+        self.report_at(index, outcome, Instant::now());
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        (0..self.len())
+            .map(|index| {
+                let health = self.health(index);
+                EndpointStatus {
+                    url: self.urls[index].clone(),
+                    available: health.available(now),
+                    degraded: health.degraded(),
+                    latency: health.latency,
+                    consecutive_failures: health.consecutive_failures,
+                }
+            })
+            .collect()
+    }
+
+    fn health(&self, index: usize) -> std::sync::MutexGuard<'_, Health> {
+        self.health[index].lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn pick_at(&self, route: Route, now: Instant) -> usize {
+        let (mut healthy, mut available) = (Vec::new(), Vec::new());
+        for index in 0..self.len() {
+            let health = self.health(index);
+            if health.available(now) {
+                available.push(index);
+                if !health.degraded() {
+                    healthy.push(index);
+                }
+            }
+        }
+        let candidates = if healthy.is_empty() { &available } else { &healthy };
+        if candidates.is_empty() {
+            // Everything is resting: use whichever comes back first
+            return (0..self.len())
+                .min_by_key(|&index| self.health(index).resting_until)
+                .unwrap_or_default();
+        }
+        match route {
+            Route::Send => candidates[0],
+            Route::Read => candidates[self.next_read.fetch_add(1, Ordering::Relaxed) % candidates.len()],
+        }
+    }
+
+    fn report_at(&self, index: usize, outcome: Outcome, now: Instant) {
+        let mut health = self.health(index);
+        match outcome {
+            Outcome::Answered(latency) => {
+                health.consecutive_failures = 0;
+                health.resting_until = None;
+                // Weight the newest call a quarter, so one slow call doesn't
+                // mark an endpoint degraded but a slow streak does
+                health.latency = Some(health.latency.map_or(latency, |average| (average * 3 + latency) / 4));
+            }
+            Outcome::RateLimited(retry_after) => {
+                health.resting_until = Some(now + retry_after.unwrap_or(RATE_LIMIT_COOLDOWN).min(MAX_COOLDOWN));
+            }
+            Outcome::Failed => {
+                health.consecutive_failures += 1;
+                if let Some(extra) = health.consecutive_failures.checked_sub(FAILURES_BEFORE_COOLDOWN) {
+                    let factor = 1u32.checked_shl(extra).unwrap_or(u32::MAX);
+                    health.resting_until = Some(now + FAILURE_COOLDOWN.saturating_mul(factor).min(MAX_COOLDOWN));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_reads_and_keeps_sends_on_the_primary() {
+        let pool = EndpointPool::new(["primary", "second", "third"]);
+        let now = Instant::now();
+        let reads: Vec<usize> = (0..6).map(|_| pool.pick_at(Route::Read, now)).collect();
+        assert_eq!(reads, [0, 1, 2, 0, 1, 2]);
+        assert_eq!(pool.pick_at(Route::Send, now), 0);
+
+        // A slow streak degrades the primary; sends move to the next one
+        for _ in 0..4 {
+            pool.report_at(0, Outcome::Answered(Duration::from_secs(5)), now);
+        }
+        assert_eq!(pool.pick_at(Route::Send, now), 1);
+        assert!(pool.status()[0].degraded);
+    }
+
+    #[test]
+    fn rests_rate_limited_and_failing_endpoints() {
+        let pool = EndpointPool::new(["primary", "backup"]);
+        let now = Instant::now();
+        pool.report_at(0, Outcome::RateLimited(Some(Duration::from_secs(3))), now);
+        assert_eq!(pool.pick_at(Route::Send, now), 1);
+        assert_eq!(pool.pick_at(Route::Send, now + Duration::from_secs(3)), 0);
+
+        for _ in 0..FAILURES_BEFORE_COOLDOWN - 1 {
+            pool.report_at(1, Outcome::Failed, now);
+        }
+        assert!(pool.health(1).available(now));
+        pool.report_at(1, Outcome::Failed, now);
+        assert!(!pool.health(1).available(now));
+        assert!(pool.health(1).available(now + FAILURE_COOLDOWN));
+
+        // With both resting, the one back soonest is used
+        pool.report_at(0, Outcome::RateLimited(None), now);
+        assert_eq!(pool.pick_at(Route::Read, now), 1);
+        pool.report_at(1, Outcome::Answered(Duration::from_millis(50)), now);
+        assert_eq!(pool.health(1).consecutive_failures, 0);
+    }
+}
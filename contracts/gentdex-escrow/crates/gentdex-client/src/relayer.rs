@@ -0,0 +1,153 @@
+//! Gasless transactions through an Octane-style relayer, for users with no
+//! SOL outside their session: a token-session user, or one whose SOL is all
+//! in the vault they want back.
+//!
+//! The relayer's key is the transaction's fee payer, and the `payer` account
+//! of any instruction that creates accounts; the user only signs as
+//! authority. The SDK compiles and signs for the user, then POSTs
+//! `{"transaction"}` (base64, the fee payer's signature left blank) to the
+//! relayer, which checks it, co-signs and submits it, answering
+//! `{"signature"}`. A relayer that wants paying for a withdrawal can ask for
+//! a [`fee`](Relayer::with_fee) out of the lamports the user just received.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+use solana_hash::Hash;
+use solana_system_interface::instruction as system_instruction;
+use solana_transaction::Transaction;
+
+use crate::instructions;
+use crate::program::Vault;
+use crate::rpc::GentdexRpc;
+use crate::signer::{self, WalletSigner};
+use crate::ClientError;
+
+/// A relayer at `url` paying fees from `fee_payer`.
+pub struct Relayer {
+    http: reqwest::Client,
+    url: String,
+    fee_payer: Pubkey,
+    fee: u64,
+}
+
+impl Relayer {
+    pub fn new(url: impl Into<String>, fee_payer: Pubkey) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.into(),
+            fee_payer,
+            fee: 0,
+        }
+    }
+
+    /// Pay the relayer `lamports` from the user at the end of each withdrawal.
+    pub fn with_fee(mut self, lamports: u64) -> Self {
+        self.fee = lamports;
+        self
+    }
+
+    pub fn fee_payer(&self) -> Pubkey {
+        self.fee_payer
+    }
+
+    /// `instructions` paid for by the relayer, signed by `signers` against
+    /// `blockhash`, awaiting the relayer's signature.
+    pub async fn prepare(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&dyn WalletSigner],
+        blockhash: Hash,
+    ) -> Result<Transaction, ClientError> {
+        let mut tx = Transaction::new_with_payer(instructions, Some(&self.fee_payer));
+        signer::partially_sign_transaction(&mut tx, signers, blockhash).await?;
+        Ok(tx)
+    }
+
+    /// Hand a prepared transaction to the relayer. Returns its signature once
+    /// the relayer has submitted it; confirmation is up to the caller.
+    pub async fn relay(&self, tx: &Transaction) -> Result<String, ClientError> {
+        #[derive(Deserialize)]
+        struct Response {
+            signature: String,
+        }
+
+        let bytes = bincode::serialize(tx).map_err(|err| ClientError::Rpc(format!("serializing transaction: {err}")))?;
+        let response: Response = self
+            .http
+            .post(&self.url)
+            .json(&json!({ "transaction": BASE64.encode(bytes) }))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| ClientError::Rpc(format!("relayer: {err}")))?
+            .json()
+            .await
+            .map_err(|err| ClientError::Rpc(format!("relayer: {err}")))?;
+        Ok(response.signature)
+    }
+
+    /// Prepare `instructions` against a fresh blockhash and relay them.
+    pub async fn send(
+        &self,
+        rpc: &GentdexRpc,
+        instructions: &[Instruction],
+        signers: &[&dyn WalletSigner],
+    ) -> Result<String, ClientError> {
+        let (blockhash, _) = rpc.latest_blockhash().await?;
+        self.relay(&self.prepare(instructions, signers, blockhash).await?).await
+    }
+
+    /// The instructions withdrawing the session at `address` to its user, with the
+    /// relayer paying and taking its fee, if any.
+    pub fn withdraw_instructions(&self, address: Pubkey, vault: &Vault) -> Vec<Instruction> {
+        let mut ixs = vec![instructions::withdraw(vault.user, address, vault.treasury, vault.bot, self.fee_payer)];
+        if self.fee > 0 {
+            ixs.push(system_instruction::transfer(&vault.user, &self.fee_payer, self.fee));
+        }
+        ixs
+    }
+
+    /// Withdraw the session at `address` for a user who may hold no SOL.
+    pub async fn withdraw(
+        &self,
+        rpc: &GentdexRpc,
+        address: Pubkey,
+        user: &dyn WalletSigner,
+    ) -> Result<String, ClientError> {
+        let vault: Vault = rpc.fetch(&address).await?;
+        self.send(rpc, &self.withdraw_instructions(address, &vault), &[user]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountDeserialize, Discriminator, Space};
+    use solana_keypair::Keypair;
+    use solana_signature::Signature;
+    use solana_signer::Signer;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn leaves_the_fee_payer_to_the_relayer() {
+        let user = Keypair::new();
+        let key = Signer::pubkey(&user);
+        let relayer = Relayer::new("https://relayer.example", Pubkey::new_unique()).with_fee(5_000);
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(8 + Vault::INIT_SPACE, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.user = key;
+
+        let ixs = relayer.withdraw_instructions(Pubkey::new_unique(), &vault);
+        assert_eq!(ixs.len(), 2);
+        let tx = relayer.prepare(&ixs, &[&user], Hash::new_unique()).await.unwrap();
+        assert_eq!(tx.message.account_keys[0], relayer.fee_payer());
+        assert_eq!(tx.signatures[0], Signature::default());
+        assert_eq!(tx.message.account_keys[1], key);
+        assert!(tx.signatures[1].verify(key.as_ref(), &tx.message_data()));
+    }
+}
@@ -0,0 +1,808 @@
+//! Async JSON-RPC client with the plumbing every integrator ends up writing:
+//! a cached blockhash, preflight simulation with the program error parsed
+//! out, backoff on flaky RPC nodes, and confirmation polling that re-signs
+//! with a fresh blockhash if the first one expires. Given several endpoints
+//! ([`GentdexRpc::pooled`]) it spreads reads across them and fails over away
+//! from throttled or lagging ones; see [`pool`](crate::pool).
+
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_hash::Hash;
+use solana_message::{AddressLookupTableAccount, VersionedMessage};
+use solana_signature::Signature;
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction::Transaction;
+use tokio::sync::Mutex;
+
+use crate::cluster::Cluster;
+use crate::error::ProgramError;
+use crate::pool::{EndpointPool, EndpointStatus, Outcome, Route};
+use crate::signer::{self, WalletSigner};
+use crate::program::{Vault, VaultStatus};
+use crate::state::{self, VaultFilter};
+use crate::{ClientError, PROGRAM_ID};
+
+/// How long a fetched blockhash is reused before asking for a new one. Well
+/// inside the ~60s a blockhash stays valid.
+const BLOCKHASH_REFRESH: Duration = Duration::from_secs(20);
+/// How often `send_and_confirm` polls signature status
+const CONFIRMATION_POLL: Duration = Duration::from_millis(500);
+/// Blocks `send_and_confirm_durable` waits, about a blockhash's lifetime
+const DURABLE_CONFIRM_BLOCKS: u64 = 150;
+
+/// Most signatures `getSignaturesForAddress` returns per call
+pub const SIGNATURE_PAGE: usize = 1000;
+/// Most accounts `getMultipleAccounts` returns per call
+const MULTIPLE_ACCOUNTS_PAGE: usize = 100;
+
+const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey = anchor_lang::pubkey!("AddressLookupTab1e1111111111111111111111111");
+/// Lookup table metadata preceding the addresses
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+/// Backoff for transient RPC failures (transport errors, HTTP 429/5xx, node
+/// errors) and for re-sending after a blockhash expires. Retries go straight
+/// to another endpoint when the pool has one available, and back off only
+/// when it doesn't.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (1-based), doubling each time.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// A landed transaction's outcome and logs.
+#[derive(Debug)]
+pub struct TransactionLogs {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// The `TransactionError` as JSON, if it failed
+    pub err: Option<Value>,
+    pub logs: Vec<String>,
+}
+
+/// An entry in an address's signature history.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// The `TransactionError` as JSON, if it failed
+    pub err: Option<Value>,
+}
+
+/// Result of a successful simulation.
+#[derive(Debug)]
+pub struct Simulation {
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// Return data set by the GentDex program, if any
+    pub return_data: Option<Vec<u8>>,
+}
+
+struct CachedBlockhash {
+    hash: Hash,
+    last_valid_block_height: u64,
+    fetched_at: Instant,
+}
+
+pub struct GentdexRpc {
+    http: reqwest::Client,
+    endpoints: EndpointPool,
+    commitment: String,
+    retry: RetryPolicy,
+    blockhash: Mutex<Option<CachedBlockhash>>,
+}
+
+impl GentdexRpc {
+    /// A client for `url` at `confirmed` commitment with the default retry policy.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::pooled([url])
+    }
+
+    /// A client spreading calls over `urls`. Sends prefer them in the order
+    /// given. Panics if `urls` is empty.
+    pub fn pooled(urls: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoints: EndpointPool::new(urls),
+            commitment: "confirmed".to_string(),
+            retry: RetryPolicy::default(),
+            blockhash: Mutex::new(None),
+        }
+    }
+
+    pub fn with_commitment(mut self, commitment: impl Into<String>) -> Self {
+        self.commitment = commitment.into();
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Health of each endpoint, in the order given.
+    pub fn endpoints(&self) -> Vec<EndpointStatus> {
+        self.endpoints.status()
+    }
+
+    /// A JSON-RPC call, retried on transient failures.
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, ClientError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let route = if method == "sendTransaction" { Route::Send } else { Route::Read };
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let endpoint = self.endpoints.pick(route);
+            let started = Instant::now();
+            let result = self.call_once(self.endpoints.url(endpoint), &body).await;
+            let message = match result {
+                Ok(value) => {
+                    self.endpoints.report(endpoint, Outcome::Answered(started.elapsed()));
+                    return serde_json::from_value(value)
+                        .map_err(|err| ClientError::Rpc(format!("{method}: unexpected response: {err}")));
+                }
+                Err(RpcFailure::Fatal(err)) => {
+                    self.endpoints.report(endpoint, Outcome::Answered(started.elapsed()));
+                    return Err(err);
+                }
+                Err(RpcFailure::RateLimited(retry_after)) => {
+                    self.endpoints.report(endpoint, Outcome::RateLimited(retry_after));
+                    "HTTP 429 Too Many Requests".to_string()
+                }
+                Err(RpcFailure::Transient(message)) => {
+                    self.endpoints.report(endpoint, Outcome::Failed);
+                    message
+                }
+            };
+            if attempt >= self.retry.max_attempts {
+                return Err(ClientError::Rpc(format!("{method}: {message}")));
+            }
+            if !self.endpoints.has_alternative(endpoint) {
+                tokio::time::sleep(self.retry.backoff(attempt)).await;
+            }
+        }
+    }
+
+    async fn call_once(&self, url: &str, body: &Value) -> Result<Value, RpcFailure> {
+        let response = self
+            .http
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|err| RpcFailure::Transient(err.to_string()))?;
+        let status = response.status();
+        if status.as_u16() == 429 {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()?.parse().ok())
+                .map(Duration::from_secs);
+            return Err(RpcFailure::RateLimited(retry_after));
+        }
+        if status.is_server_error() {
+            return Err(RpcFailure::Transient(format!("HTTP {status}")));
+        }
+        let mut response: Value = response
+            .json()
+            .await
+            .map_err(|err| RpcFailure::Transient(err.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            // -32005: node is behind; -32004: block not available yet
+            let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+            let message = error.to_string();
+            return Err(if code == -32005 || code == -32004 {
+                RpcFailure::Transient(message)
+            } else {
+                RpcFailure::Fatal(ClientError::Rpc(message))
+            });
+        }
+        Ok(response["result"].take())
+    }
+
+    /// A recent blockhash and the last block height it's valid for, cached
+    /// for `BLOCKHASH_REFRESH`.
+    pub async fn latest_blockhash(&self) -> Result<(Hash, u64), ClientError> {
+        let mut cached = self.blockhash.lock().await;
+        if let Some(blockhash) = cached.as_ref().filter(|b| b.fetched_at.elapsed() < BLOCKHASH_REFRESH) {
+            return Ok((blockhash.hash, blockhash.last_valid_block_height));
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Latest {
+            blockhash: String,
+            last_valid_block_height: u64,
+        }
+        let latest: Contextual<Latest> = self
+            .call("getLatestBlockhash", json!([{ "commitment": self.commitment }]))
+            .await?;
+        let hash = latest
+            .value
+            .blockhash
+            .parse()
+            .map_err(|_| ClientError::Rpc("getLatestBlockhash: invalid blockhash".to_string()))?;
+
+        *cached = Some(CachedBlockhash {
+            hash,
+            last_valid_block_height: latest.value.last_valid_block_height,
+            fetched_at: Instant::now(),
+        });
+        Ok((hash, latest.value.last_valid_block_height))
+    }
+
+    async fn invalidate_blockhash(&self) {
+        *self.blockhash.lock().await = None;
+    }
+
+    /// The cluster the endpoint is on, from its genesis hash.
+    pub async fn cluster(&self) -> Result<Cluster, ClientError> {
+        let hash: String = self.call("getGenesisHash", json!([])).await?;
+        Ok(Cluster::from_genesis_hash(&hash))
+    }
+
+    /// The endpoint's cluster, or `WrongCluster` if this build's program ID
+    /// isn't the one deployed there — say, a devnet build pointed at mainnet.
+    pub async fn check_cluster(&self) -> Result<Cluster, ClientError> {
+        let cluster = self.cluster().await?;
+        if !cluster.matches_build() {
+            return Err(ClientError::WrongCluster { cluster, build: Cluster::BUILD });
+        }
+        Ok(cluster)
+    }
+
+    pub async fn block_height(&self) -> Result<u64, ClientError> {
+        self.call("getBlockHeight", json!([{ "commitment": self.commitment }])).await
+    }
+
+    /// Owner and data of `address`, or `None` if it doesn't exist.
+    pub async fn get_account(&self, address: &Pubkey) -> Result<Option<(Pubkey, Vec<u8>)>, ClientError> {
+        let account: Contextual<Option<RpcAccount>> = self
+            .call(
+                "getAccountInfo",
+                json!([address.to_string(), { "encoding": "base64", "commitment": self.commitment }]),
+            )
+            .await?;
+
+        account
+            .value
+            .map(|account| {
+                let owner = account
+                    .owner
+                    .parse()
+                    .map_err(|_| ClientError::Rpc("getAccountInfo: invalid owner".to_string()))?;
+                Ok((owner, decode_base64(&account.data.0)?))
+            })
+            .transpose()
+    }
+
+    /// Fetch and decode a GentDex account.
+    pub async fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T, ClientError> {
+        let (owner, data) = self
+            .get_account(address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        if owner != PROGRAM_ID {
+            return Err(ClientError::WrongOwner(*address));
+        }
+        state::decode(&data)
+    }
+
+    /// Slot, time, outcome and logs of a landed transaction, or `None` if the
+    /// node doesn't have it (not yet confirmed, or pruned from its history).
+    /// Decode its events with `events::parse_logs`.
+    pub async fn get_transaction(&self, signature: &str) -> Result<Option<TransactionLogs>, ClientError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Meta {
+            err: Option<Value>,
+            log_messages: Option<Vec<String>>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Confirmed {
+            slot: u64,
+            block_time: Option<i64>,
+            meta: Option<Meta>,
+        }
+        let commitment = self.history_commitment();
+        let confirmed: Option<Confirmed> = self
+            .call(
+                "getTransaction",
+                json!([signature, { "encoding": "json", "commitment": commitment, "maxSupportedTransactionVersion": 0 }]),
+            )
+            .await?;
+
+        Ok(confirmed.map(|confirmed| {
+            let meta = confirmed.meta;
+            TransactionLogs {
+                slot: confirmed.slot,
+                block_time: confirmed.block_time,
+                err: meta.as_ref().and_then(|meta| meta.err.clone()),
+                logs: meta.and_then(|meta| meta.log_messages).unwrap_or_default(),
+            }
+        }))
+    }
+
+    /// Up to 1000 transactions that touched `address`, newest first, starting
+    /// before `before` if given. Page through history by passing the last
+    /// signature of each page until one comes back short.
+    pub async fn signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+    ) -> Result<Vec<SignatureInfo>, ClientError> {
+        let commitment = self.history_commitment();
+        self.call(
+            "getSignaturesForAddress",
+            json!([address.to_string(), { "before": before, "limit": SIGNATURE_PAGE, "commitment": commitment }]),
+        )
+        .await
+    }
+
+    /// The client's commitment for history queries, which don't accept `processed`.
+    fn history_commitment(&self) -> &str {
+        if self.commitment == "processed" {
+            "confirmed"
+        } else {
+            &self.commitment
+        }
+    }
+
+    /// Every vault belonging to `user`, found with `getProgramAccounts`
+    /// filtered on the `Vault` discriminator and `user` field. Unlike
+    /// `state::fetch_indexed_vaults` this also finds sessions opened with
+    /// `initialize` at an arbitrary `session_id`.
+    pub async fn vaults_by_user(&self, user: &Pubkey) -> Result<Vec<(Pubkey, Vault)>, ClientError> {
+        self.vaults_matching(&VaultFilter::new().user(*user)).await
+    }
+
+    /// Every vault with `session_id`. Usually one, but indexed sessions
+    /// number their ids per user, so several users can share one.
+    pub async fn vaults_by_session(&self, session_id: &[u8; 16]) -> Result<Vec<(Pubkey, Vault)>, ClientError> {
+        self.vaults_matching(&VaultFilter::new().session_id(*session_id)).await
+    }
+
+    /// Every vault in `status`, e.g. all `Active` sessions for a crank.
+    pub async fn vaults_with_status(&self, status: VaultStatus) -> Result<Vec<(Pubkey, Vault)>, ClientError> {
+        self.vaults_matching(&VaultFilter::new().status(status)).await
+    }
+
+    /// `getProgramAccounts` for vaults matching `filter`.
+    pub async fn vaults_matching(&self, filter: &VaultFilter) -> Result<Vec<(Pubkey, Vault)>, ClientError> {
+        #[derive(Deserialize)]
+        struct Keyed {
+            pubkey: String,
+            account: RpcAccount,
+        }
+        let filters: Vec<Value> = filter
+            .memcmps()
+            .iter()
+            .map(|memcmp| {
+                json!({ "memcmp": { "offset": memcmp.offset, "bytes": BASE64.encode(&memcmp.bytes), "encoding": "base64" } })
+            })
+            .collect();
+        let accounts: Vec<Keyed> = self
+            .call(
+                "getProgramAccounts",
+                json!([
+                    PROGRAM_ID.to_string(),
+                    {
+                        "encoding": "base64",
+                        "commitment": self.commitment,
+                        "filters": filters,
+                    }
+                ]),
+            )
+            .await?;
+
+        accounts
+            .into_iter()
+            .map(|keyed| {
+                let address = keyed
+                    .pubkey
+                    .parse()
+                    .map_err(|_| ClientError::Rpc("getProgramAccounts: invalid pubkey".to_string()))?;
+                Ok((address, state::decode(&decode_base64(&keyed.account.data.0)?)?))
+            })
+            .collect()
+    }
+
+    /// Simulate `instructions` paid by `payer`. No signatures are needed; the
+    /// node substitutes a recent blockhash. Fails with the parsed program
+    /// error if the transaction would fail.
+    pub async fn simulate(&self, instructions: &[Instruction], payer: &Pubkey) -> Result<Simulation, ClientError> {
+        let tx = Transaction::new_with_payer(instructions, Some(payer));
+        self.simulate_transaction(&encode_transaction(&tx)?, false).await
+    }
+
+    /// Simulate a compiled, unsigned message, e.g. to size its compute budget.
+    pub async fn simulate_message(&self, message: &VersionedMessage) -> Result<Simulation, ClientError> {
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+            message: message.clone(),
+        };
+        self.simulate_transaction(&encode_transaction(&tx)?, false).await
+    }
+
+    /// The `percentile`th prioritization fee, in micro-lamports per compute
+    /// unit, paid over recent slots by transactions writing any of `accounts`.
+    pub async fn recent_priority_fee(&self, accounts: &[Pubkey], percentile: u8) -> Result<u64, ClientError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Fee {
+            prioritization_fee: u64,
+        }
+
+        let accounts: Vec<String> = accounts.iter().map(Pubkey::to_string).collect();
+        let fees: Vec<Fee> = self.call("getRecentPrioritizationFees", json!([accounts])).await?;
+        let mut fees: Vec<u64> = fees.into_iter().map(|fee| fee.prioritization_fee).collect();
+        fees.sort_unstable();
+        Ok(match fees.len() {
+            0 => 0,
+            len => fees[(len - 1) * percentile.min(100) as usize / 100],
+        })
+    }
+
+    /// Simulate a view instruction (`get_session_summary`, ...) and decode
+    /// its return data. Also dry-runs swaps: a [`Swap`](crate::instructions::Swap)
+    /// decodes as `SwapResult`.
+    pub async fn view<T: AnchorDeserialize>(&self, instruction: Instruction, payer: &Pubkey) -> Result<T, ClientError> {
+        let simulation = self.simulate(&[instruction], payer).await?;
+        let data = simulation
+            .return_data
+            .ok_or_else(|| ClientError::Rpc("view instruction set no return data".to_string()))?;
+        T::try_from_slice(&data).map_err(|err| ClientError::Rpc(format!("invalid return data: {err}")))
+    }
+
+    async fn simulate_transaction(&self, encoded: &str, sig_verify: bool) -> Result<Simulation, ClientError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SimulateResult {
+            err: Option<Value>,
+            logs: Option<Vec<String>>,
+            units_consumed: Option<u64>,
+            return_data: Option<ReturnData>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ReturnData {
+            program_id: String,
+            data: (String, String),
+        }
+
+        let result: Contextual<SimulateResult> = self
+            .call(
+                "simulateTransaction",
+                json!([
+                    encoded,
+                    {
+                        "encoding": "base64",
+                        "commitment": self.commitment,
+                        "sigVerify": sig_verify,
+                        "replaceRecentBlockhash": !sig_verify,
+                    }
+                ]),
+            )
+            .await?;
+        let result = result.value;
+        let logs = result.logs.unwrap_or_default();
+
+        if let Some(err) = result.err {
+            return Err(transaction_failed(&err, logs));
+        }
+        let return_data = match result.return_data {
+            Some(data) if data.program_id == PROGRAM_ID.to_string() => Some(decode_base64(&data.data.0)?),
+            _ => None,
+        };
+        Ok(Simulation {
+            logs,
+            units_consumed: result.units_consumed,
+            return_data,
+        })
+    }
+
+    /// Sign `instructions` with `signers` (the first pays), preflight them,
+    /// send, and wait for `commitment`. If the blockhash expires before the
+    /// transaction lands it's re-signed with a fresh one, up to the retry
+    /// policy's attempts.
+    pub async fn send_and_confirm(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<String, ClientError> {
+        let payer = signers
+            .first()
+            .ok_or_else(|| ClientError::Rpc("send_and_confirm needs at least one signer".to_string()))?
+            .pubkey();
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let (blockhash, last_valid_block_height) = self.latest_blockhash().await?;
+            let mut tx = Transaction::new_with_payer(instructions, Some(&payer));
+            tx.try_sign(signers, blockhash)
+                .map_err(|err| ClientError::Rpc(format!("signing failed: {err}")))?;
+
+            if let Some(signature) = self.submit(encode_transaction(&tx)?, last_valid_block_height).await? {
+                return Ok(signature);
+            }
+            self.invalidate_blockhash().await;
+            if attempt >= self.retry.max_attempts {
+                return Err(ClientError::BlockhashExpired);
+            }
+        }
+    }
+
+    /// [`send_and_confirm`](Self::send_and_confirm) with signers that may be
+    /// remote or hardware-backed. Each attempt asks them to sign again.
+    pub async fn send_and_confirm_with(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&dyn WalletSigner],
+    ) -> Result<String, ClientError> {
+        let payer = signers
+            .first()
+            .ok_or_else(|| ClientError::Rpc("send_and_confirm needs at least one signer".to_string()))?
+            .pubkey();
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let (blockhash, last_valid_block_height) = self.latest_blockhash().await?;
+            let mut tx = Transaction::new_with_payer(instructions, Some(&payer));
+            signer::sign_transaction(&mut tx, signers, blockhash).await?;
+
+            if let Some(signature) = self.submit(encode_transaction(&tx)?, last_valid_block_height).await? {
+                return Ok(signature);
+            }
+            self.invalidate_blockhash().await;
+            if attempt >= self.retry.max_attempts {
+                return Err(ClientError::BlockhashExpired);
+            }
+        }
+    }
+
+    /// Preflight, send and confirm an already signed versioned transaction,
+    /// e.g. one using lookup tables. `last_valid_block_height` comes from
+    /// `latest_blockhash`; if the transaction hasn't landed by then this fails
+    /// with `BlockhashExpired` and the caller should rebuild it.
+    pub async fn send_and_confirm_versioned(
+        &self,
+        tx: &VersionedTransaction,
+        last_valid_block_height: u64,
+    ) -> Result<String, ClientError> {
+        match self.submit(encode_transaction(tx)?, last_valid_block_height).await? {
+            Some(signature) => Ok(signature),
+            None => {
+                self.invalidate_blockhash().await;
+                Err(ClientError::BlockhashExpired)
+            }
+        }
+    }
+
+    /// Preflight, send and confirm a transaction signed against a durable
+    /// nonce (see [`nonce`](crate::nonce)). It doesn't expire, so this gives
+    /// up with `BlockhashExpired` after about a blockhash's lifetime; sending
+    /// the same transaction again is safe, as it can only land once.
+    pub async fn send_and_confirm_durable(&self, tx: &Transaction) -> Result<String, ClientError> {
+        let last_valid_block_height = self.block_height().await? + DURABLE_CONFIRM_BLOCKS;
+        self.submit(encode_transaction(tx)?, last_valid_block_height)
+            .await?
+            .ok_or(ClientError::BlockhashExpired)
+    }
+
+    /// Simulate, send and confirm a signed, encoded transaction. `None` if its
+    /// blockhash expired first.
+    async fn submit(&self, encoded: String, last_valid_block_height: u64) -> Result<Option<String>, ClientError> {
+        // Program errors surface here, with logs, instead of as a failed landing
+        self.simulate_transaction(&encoded, true).await?;
+
+        let signature: String = self
+            .call(
+                "sendTransaction",
+                json!([encoded, { "encoding": "base64", "skipPreflight": true, "maxRetries": 0 }]),
+            )
+            .await?;
+
+        Ok(self
+            .confirm(&signature, last_valid_block_height)
+            .await?
+            .then_some(signature))
+    }
+
+    /// Load an address lookup table, e.g. a session's (`Vault::lookup_table`)
+    /// or one returned with a Jupiter route.
+    pub async fn fetch_lookup_table(&self, address: &Pubkey) -> Result<AddressLookupTableAccount, ClientError> {
+        let (owner, data) = self
+            .get_account(address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        if owner != ADDRESS_LOOKUP_TABLE_PROGRAM_ID || data.len() < LOOKUP_TABLE_META_SIZE {
+            return Err(ClientError::WrongOwner(*address));
+        }
+
+        let addresses = data[LOOKUP_TABLE_META_SIZE..]
+            .chunks_exact(32)
+            .map(|key| Pubkey::new_from_array(key.try_into().unwrap()))
+            .collect();
+        Ok(AddressLookupTableAccount {
+            key: *address,
+            addresses,
+        })
+    }
+
+    /// The lookup tables created for whichever of `accounts` are sessions
+    /// with one (`Vault::lookup_table`); other accounts are skipped.
+    pub async fn session_lookup_tables(&self, accounts: &[Pubkey]) -> Result<Vec<Pubkey>, ClientError> {
+        let mut tables = Vec::new();
+        for chunk in accounts.chunks(MULTIPLE_ACCOUNTS_PAGE) {
+            let keys: Vec<String> = chunk.iter().map(Pubkey::to_string).collect();
+            let fetched: Contextual<Vec<Option<RpcAccount>>> = self
+                .call(
+                    "getMultipleAccounts",
+                    json!([keys, { "encoding": "base64", "commitment": self.commitment }]),
+                )
+                .await?;
+            for account in fetched.value.into_iter().flatten() {
+                if account.owner != PROGRAM_ID.to_string() {
+                    continue;
+                }
+                let Ok(vault) = state::decode::<Vault>(&decode_base64(&account.data.0)?) else {
+                    continue;
+                };
+                if vault.lookup_table != Pubkey::default() && !tables.contains(&vault.lookup_table) {
+                    tables.push(vault.lookup_table);
+                }
+            }
+        }
+        Ok(tables)
+    }
+
+    /// Poll until `signature` reaches the client's commitment (`true`) or the
+    /// chain passes `last_valid_block_height` without it (`false`).
+    async fn confirm(&self, signature: &str, last_valid_block_height: u64) -> Result<bool, ClientError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Status {
+            err: Option<Value>,
+            confirmation_status: Option<String>,
+        }
+
+        loop {
+            let statuses: Contextual<Vec<Option<Status>>> = self
+                .call("getSignatureStatuses", json!([[signature]]))
+                .await?;
+            if let Some(Some(status)) = statuses.value.into_iter().next() {
+                if let Some(err) = status.err {
+                    return Err(transaction_failed(&err, Vec::new()));
+                }
+                let reached = match status.confirmation_status.as_deref() {
+                    Some("finalized") => true,
+                    Some("confirmed") => self.commitment != "finalized",
+                    Some("processed") => self.commitment == "processed",
+                    _ => false,
+                };
+                if reached {
+                    return Ok(true);
+                }
+            } else if self.block_height().await? > last_valid_block_height {
+                return Ok(false);
+            }
+            tokio::time::sleep(CONFIRMATION_POLL).await;
+        }
+    }
+}
+
+enum RpcFailure {
+    /// HTTP 429, with the `Retry-After` delay if given
+    RateLimited(Option<Duration>),
+    Transient(String),
+    Fatal(ClientError),
+}
+
+#[derive(Deserialize)]
+struct Contextual<T> {
+    value: T,
+}
+
+#[derive(Deserialize)]
+struct RpcAccount {
+    owner: String,
+    data: (String, String),
+}
+
+fn encode_transaction(tx: &impl Serialize) -> Result<String, ClientError> {
+    let bytes = bincode::serialize(tx).map_err(|err| ClientError::Rpc(format!("serializing transaction: {err}")))?;
+    Ok(BASE64.encode(bytes))
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, ClientError> {
+    BASE64
+        .decode(data)
+        .map_err(|err| ClientError::Rpc(format!("invalid base64: {err}")))
+}
+
+/// Turn a `TransactionError` JSON value into a `ClientError`, decoding
+/// `{"InstructionError": [index, {"Custom": code}]}` into `ClientError::Program`.
+pub fn transaction_failed(err: &Value, logs: Vec<String>) -> ClientError {
+    let instruction_error = err.get("InstructionError");
+    let instruction = instruction_error
+        .and_then(|e| e.get(0))
+        .and_then(Value::as_u64)
+        .map(|index| index as u8);
+    let code = instruction_error
+        .and_then(|e| e.get(1))
+        .and_then(|e| e.get("Custom"))
+        .and_then(Value::as_u64)
+        .map(|code| code as u32);
+
+    if let (Some(instruction), Some(code)) = (instruction, code) {
+        return ClientError::Program {
+            instruction,
+            error: ProgramError::decode(code, &logs),
+            logs,
+        };
+    }
+    ClientError::TransactionFailed {
+        instruction,
+        err: err.to_string(),
+        logs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::EscrowError;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(250));
+        assert_eq!(policy.backoff(3), Duration::from_secs(1));
+        assert_eq!(policy.backoff(40), policy.max_backoff);
+    }
+
+    #[test]
+    fn parses_custom_program_errors() {
+        let err = json!({ "InstructionError": [1, { "Custom": 6004 }] });
+        match transaction_failed(&err, Vec::new()) {
+            ClientError::Program { instruction, error, .. } => {
+                assert_eq!(instruction, 1);
+                assert!(matches!(error, ProgramError::Escrow(EscrowError::DexNotWhitelisted)));
+            }
+            other => panic!("unexpected {other}"),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Signing behind a trait, so session and bot keys can live outside the
+//! process: in a Ledger (feature `ledger`), or behind an HTTP signing service
+//! such as a KMS or HSM proxy ([`RemoteSigner`]). Local keypairs, and any
+//! other `solana_signer::Signer`, work as they are.
+//!
+//! [`GentdexRpc::send_and_confirm_with`](crate::rpc::GentdexRpc::send_and_confirm_with)
+//! and the `jupiter` builders take `&dyn WalletSigner`; [`sign_transaction`]
+//! and [`sign_versioned`] sign anything else, and
+//! [`partially_sign_transaction`] anything someone else co-signs.
+
+use anchor_lang::prelude::Pubkey;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::json;
+use solana_hash::Hash;
+use solana_message::VersionedMessage;
+use solana_signature::Signature;
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction::Transaction;
+
+use crate::ClientError;
+
+#[cfg(feature = "ledger")]
+pub use crate::ledger::LedgerSigner;
+
+/// Something that can sign transaction messages for one address.
+pub trait WalletSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    /// Sign serialized message bytes. May wait on a device or the network.
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, ClientError>>;
+}
+
+impl<T: Signer + Send + Sync> WalletSigner for T {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, ClientError>> {
+        let signed = self
+            .try_sign_message(message)
+            .map_err(|err| ClientError::Rpc(format!("signing failed: {err}")));
+        Box::pin(async move { signed })
+    }
+}
+
+/// Signs through an HTTP service holding the key: POSTs
+/// `{"pubkey", "message"}` (message base64) to `url` and expects
+/// `{"signature"}` (base58) back. Put a KMS, HSM or policy engine behind it.
+pub struct RemoteSigner {
+    http: reqwest::Client,
+    url: String,
+    pubkey: Pubkey,
+    bearer_token: Option<String>,
+}
+
+impl RemoteSigner {
+    pub fn new(url: impl Into<String>, pubkey: Pubkey) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.into(),
+            pubkey,
+            bearer_token: None,
+        }
+    }
+
+    /// Send `Authorization: Bearer <token>` with every request.
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Signature, ClientError> {
+        #[derive(Deserialize)]
+        struct Response {
+            signature: String,
+        }
+
+        let body = json!({ "pubkey": self.pubkey.to_string(), "message": BASE64.encode(message) });
+        let mut request = self.http.post(&self.url).json(&body);
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| ClientError::Rpc(format!("remote signer: {err}")))?;
+        let response: Response = response
+            .json()
+            .await
+            .map_err(|err| ClientError::Rpc(format!("remote signer: {err}")))?;
+
+        let signature: Signature = response
+            .signature
+            .parse()
+            .map_err(|_| ClientError::Rpc(format!("remote signer: invalid signature {:?}", response.signature)))?;
+        // Don't pass on a signature the chain would reject as a confusing send error
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(ClientError::Rpc(format!("remote signer: signature doesn't verify for {}", self.pubkey)));
+        }
+        Ok(signature)
+    }
+}
+
+impl WalletSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, ClientError>> {
+        Box::pin(self.sign(message))
+    }
+}
+
+/// Signatures by `signers` for each of `required`, in order.
+async fn signatures(
+    message: &[u8],
+    required: &[Pubkey],
+    signers: &[&dyn WalletSigner],
+) -> Result<Vec<Signature>, ClientError> {
+    let mut signatures = Vec::with_capacity(required.len());
+    for key in required {
+        let signer = signers
+            .iter()
+            .find(|signer| signer.pubkey() == *key)
+            .ok_or_else(|| ClientError::Rpc(format!("signing failed: no signer for {key}")))?;
+        signatures.push(signer.sign_message(message).await?);
+    }
+    Ok(signatures)
+}
+
+/// Set `tx`'s blockhash and sign it. Every required signer must be given.
+pub async fn sign_transaction(
+    tx: &mut Transaction,
+    signers: &[&dyn WalletSigner],
+    blockhash: Hash,
+) -> Result<(), ClientError> {
+    tx.message.recent_blockhash = blockhash;
+    let required = &tx.message.account_keys[..tx.message.header.num_required_signatures as usize];
+    tx.signatures = signatures(&tx.message_data(), required, signers).await?;
+    Ok(())
+}
+
+/// Set `tx`'s blockhash and sign it with `signers`, leaving a blank
+/// signature for each required key without one, e.g. a relayer's that
+/// co-signs later.
+pub async fn partially_sign_transaction(
+    tx: &mut Transaction,
+    signers: &[&dyn WalletSigner],
+    blockhash: Hash,
+) -> Result<(), ClientError> {
+    tx.message.recent_blockhash = blockhash;
+    let message = tx.message_data();
+    let required = &tx.message.account_keys[..tx.message.header.num_required_signatures as usize];
+    let mut signatures = Vec::with_capacity(required.len());
+    for key in required {
+        signatures.push(match signers.iter().find(|signer| signer.pubkey() == *key) {
+            Some(signer) => signer.sign_message(&message).await?,
+            None => Signature::default(),
+        });
+    }
+    tx.signatures = signatures;
+    Ok(())
+}
+
+/// Sign a compiled versioned message. Every required signer must be given.
+pub async fn sign_versioned(
+    message: VersionedMessage,
+    signers: &[&dyn WalletSigner],
+) -> Result<VersionedTransaction, ClientError> {
+    let required = &message.static_account_keys()[..message.header().num_required_signatures as usize];
+    let signatures = signatures(&message.serialize(), required, signers).await?;
+    Ok(VersionedTransaction { signatures, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use solana_keypair::Keypair;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn signs_for_every_required_signer() {
+        let (payer, other) = (Keypair::new(), Keypair::new());
+        let ix = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new_readonly(Signer::pubkey(&other), true)],
+        );
+        let mut tx = Transaction::new_with_payer(&[ix], Some(&Signer::pubkey(&payer)));
+
+        assert!(sign_transaction(&mut tx, &[&payer], Hash::new_unique()).await.is_err());
+        sign_transaction(&mut tx, &[&other, &payer], Hash::new_unique()).await.unwrap();
+        let message = tx.message_data();
+        assert_eq!(tx.signatures.len(), 2);
+        for (signature, key) in tx.signatures.iter().zip(&tx.message.account_keys) {
+            assert!(signature.verify(key.as_ref(), &message));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Solana Pay transaction requests, so any mobile wallet can open and fund a
+//! session or withdraw one by scanning a QR code.
+//!
+//! A transaction request is a `solana:` URL pointing at an HTTPS endpoint you
+//! host. The wallet GETs it for a label and icon ([`metadata_response`]), then
+//! POSTs `{"account": "<wallet>"}` ([`requesting_account`]) and signs the
+//! transaction in the response ([`fund_response`], [`withdraw_response`]).
+//! [`PayLinks`] builds the URLs; render them with any QR encoder.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+use solana_hash::Hash;
+use solana_message::Message;
+use solana_transaction::Transaction;
+
+use crate::{instructions, pda, ClientError};
+
+/// A session the scanning wallet will open and fund, carried in the link.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionRequest {
+    pub session_id: [u8; 16],
+    pub duration_days: u16,
+    pub bot: Pubkey,
+    /// Lamports to deposit, setup fee included
+    pub amount: u64,
+}
+
+impl SessionRequest {
+    /// The session's vault once `account` opens it.
+    pub fn vault(&self, account: &Pubkey) -> Pubkey {
+        pda::vault_address(&self.session_id, account).0
+    }
+
+    fn to_query(&self) -> String {
+        let session_id: String = self.session_id.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!(
+            "session_id={session_id}&duration_days={}&bot={}&amount={}",
+            self.duration_days, self.bot, self.amount
+        )
+    }
+
+    /// Parse the query string of a fund link, as the endpoint receives it.
+    pub fn from_query(query: &str) -> Result<Self, ClientError> {
+        let field = |name: &str| {
+            query
+                .trim_start_matches('?')
+                .split('&')
+                .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                .ok_or_else(|| invalid(format!("missing {name}")))
+        };
+        let session_id = field("session_id")?;
+        if session_id.len() != 32 || !session_id.is_ascii() {
+            return Err(invalid(format!("session_id {session_id:?}: expected 32 hex characters")));
+        }
+        let mut id = [0; 16];
+        for (i, byte) in id.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&session_id[2 * i..2 * i + 2], 16)
+                .map_err(|_| invalid(format!("session_id {session_id:?}: expected 32 hex characters")))?;
+        }
+        Ok(Self {
+            session_id: id,
+            duration_days: field("duration_days")?.parse().map_err(|_| invalid("invalid duration_days"))?,
+            bot: field("bot")?.parse().map_err(|_| invalid("invalid bot"))?,
+            amount: field("amount")?.parse().map_err(|_| invalid("invalid amount"))?,
+        })
+    }
+}
+
+/// Builds transaction request URLs for an endpoint, such as
+/// `https://example.com/api/pay`, serving `/fund` and `/withdraw`.
+#[derive(Clone, Debug)]
+pub struct PayLinks {
+    endpoint: String,
+}
+
+impl PayLinks {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Open and fund `session` from the scanning wallet.
+    pub fn fund(&self, session: &SessionRequest) -> String {
+        transaction_request_url(&format!("{}/fund?{}", self.endpoint, session.to_query()))
+    }
+
+    /// Withdraw `vault`. Only its owner's wallet can sign the transaction.
+    pub fn withdraw(&self, vault: &Pubkey) -> String {
+        transaction_request_url(&format!("{}/withdraw?vault={vault}", self.endpoint))
+    }
+}
+
+/// The `solana:` URL for a transaction request to `link`. Links with a query
+/// string are percent-encoded, as the spec requires.
+pub fn transaction_request_url(link: &str) -> String {
+    if !link.contains('?') {
+        return format!("solana:{link}");
+    }
+    let mut url = String::from("solana:");
+    for byte in link.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => url.push(byte as char),
+            _ => url.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    url
+}
+
+/// Body for the wallet's GET: what it shows before asking to connect.
+pub fn metadata_response(label: &str, icon: &str) -> String {
+    json!({ "label": label, "icon": icon }).to_string()
+}
+
+/// The wallet address from the body of the wallet's POST.
+pub fn requesting_account(body: &str) -> Result<Pubkey, ClientError> {
+    #[derive(Deserialize)]
+    struct Request {
+        account: String,
+    }
+    let request: Request = serde_json::from_str(body).map_err(|err| invalid(format!("request body: {err}")))?;
+    request.account.parse().map_err(|_| invalid(format!("invalid account {:?}", request.account)))
+}
+
+/// POST response opening and funding `session` from `account`. `treasury` is
+/// the config's current treasury.
+pub fn fund_response(
+    account: Pubkey,
+    treasury: Pubkey,
+    session: &SessionRequest,
+    blockhash: Hash,
+) -> Result<String, ClientError> {
+    let (ix, _) = instructions::initialize_and_deposit(
+        account,
+        treasury,
+        session.session_id,
+        session.duration_days,
+        session.bot,
+        session.amount,
+        account,
+    );
+    let message = format!("Fund a {}-day GentDex session", session.duration_days);
+    response(&[ix], account, blockhash, &message)
+}
+
+/// POST response withdrawing `vault` to `account`. `treasury` and `bot` are
+/// the vault's own.
+pub fn withdraw_response(
+    account: Pubkey,
+    vault: Pubkey,
+    treasury: Pubkey,
+    bot: Pubkey,
+    blockhash: Hash,
+) -> Result<String, ClientError> {
+    let withdraw = instructions::withdraw(account, vault, treasury, bot, account);
+    response(&[withdraw], account, blockhash, "Withdraw your GentDex session")
+}
+
+/// `{"transaction", "message"}` with an unsigned transaction paid by `account`.
+fn response(ixs: &[Instruction], account: Pubkey, blockhash: Hash, message: &str) -> Result<String, ClientError> {
+    let tx = Transaction::new_unsigned(Message::new_with_blockhash(ixs, Some(&account), &blockhash));
+    let bytes = bincode::serialize(&tx).map_err(|err| ClientError::Rpc(format!("serializing transaction: {err}")))?;
+    Ok(json!({ "transaction": BASE64.encode(bytes), "message": message }).to_string())
+}
+
+fn invalid(message: impl Into<String>) -> ClientError {
+    ClientError::Source(format!("solana pay: {}", message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_fund_links() {
+        let session = SessionRequest {
+            session_id: [0xab; 16],
+            duration_days: 7,
+            bot: Pubkey::new_unique(),
+            amount: 2_000_000_000,
+        };
+        let url = PayLinks::new("https://example.com/api/pay/").fund(&session);
+        assert!(url.starts_with("solana:https%3A%2F%2Fexample.com%2Fapi%2Fpay%2Ffund%3Fsession_id%3Dabab"));
+        assert!(!url[7..].contains(['/', '?', '&', '=']));
+
+        let query = format!("?{}", session.to_query());
+        assert_eq!(SessionRequest::from_query(&query).unwrap(), session);
+        assert!(SessionRequest::from_query("session_id=ab&duration_days=7").is_err());
+        assert_eq!(transaction_request_url("https://example.com/pay"), "solana:https://example.com/pay");
+    }
+
+    #[test]
+    fn builds_an_unsigned_transaction_paid_by_the_wallet() {
+        let account = Pubkey::new_unique();
+        assert_eq!(requesting_account(&format!(r#"{{"account":"{account}"}}"#)).unwrap(), account);
+        assert!(requesting_account(r#"{"account":"nope"}"#).is_err());
+
+        let vault = Pubkey::new_unique();
+        let body: serde_json::Value =
+            serde_json::from_str(&withdraw_response(account, vault, Pubkey::new_unique(), Pubkey::new_unique(), Hash::default()).unwrap()).unwrap();
+        let bytes = BASE64.decode(body["transaction"].as_str().unwrap()).unwrap();
+        let tx: Transaction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(tx.message.account_keys[0], account);
+        assert!(tx.message.account_keys.contains(&vault));
+        assert_eq!(tx.signatures.len(), 1);
+    }
+}
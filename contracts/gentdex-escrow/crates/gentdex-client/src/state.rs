@@ -0,0 +1,247 @@
+//! Fetching and decoding program accounts.
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use gentdex_escrow::{
+    fee_bps_for_stake, gross_for_net, pda, BotProfile, BotStats, ProtocolConfig, StakeAccount, UpgradeInfo,
+    UserRegistry, Vault, VaultStatus,
+};
+
+use crate::{ClientError, PROGRAM_ID};
+
+/// Byte offset of `Vault::user`, for `getProgramAccounts` filters
+pub const VAULT_USER_OFFSET: usize = 8;
+/// Byte offset of `Vault::bot`: discriminator and the user
+pub const VAULT_BOT_OFFSET: usize = 8 + 32;
+/// Byte offset of `Vault::treasury`: discriminator, user and bot
+pub const VAULT_TREASURY_OFFSET: usize = 8 + 32 * 2;
+/// Byte offset of `Vault::session_id`: discriminator and three pubkeys
+pub const VAULT_SESSION_ID_OFFSET: usize = 8 + 32 * 3;
+/// Byte offset of `Vault::status`: discriminator, three pubkeys, session id,
+/// three u64s and the u16 duration
+pub const VAULT_STATUS_OFFSET: usize = 8 + 32 * 3 + 16 + 8 * 3 + 2;
+
+/// A `getProgramAccounts` memcmp filter: `bytes` at `offset`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Memcmp {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl Memcmp {
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.get(self.offset..self.offset + self.bytes.len()) == Some(&self.bytes[..])
+    }
+}
+
+/// Which vaults to query, e.g. all Active sessions of one bot:
+///
+/// ```ignore
+/// let filter = VaultFilter::new().bot(bot).status(VaultStatus::Active);
+/// let vaults = rpc.vaults_matching(&filter).await?;
+/// ```
+///
+/// Every field set must match; an empty filter matches every vault.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VaultFilter {
+    pub user: Option<Pubkey>,
+    pub bot: Option<Pubkey>,
+    pub treasury: Option<Pubkey>,
+    pub session_id: Option<[u8; 16]>,
+    pub status: Option<VaultStatus>,
+}
+
+impl VaultFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn user(mut self, user: Pubkey) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    pub fn bot(mut self, bot: Pubkey) -> Self {
+        self.bot = Some(bot);
+        self
+    }
+
+    pub fn treasury(mut self, treasury: Pubkey) -> Self {
+        self.treasury = Some(treasury);
+        self
+    }
+
+    pub fn session_id(mut self, session_id: [u8; 16]) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    pub fn status(mut self, status: VaultStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// The memcmp filters to send, starting with the `Vault` discriminator.
+    pub fn memcmps(&self) -> Vec<Memcmp> {
+        let memcmp = |offset, bytes: &[u8]| Memcmp { offset, bytes: bytes.to_vec() };
+        let mut filters = vec![memcmp(0, Vault::DISCRIMINATOR)];
+        filters.extend(self.user.map(|user| memcmp(VAULT_USER_OFFSET, user.as_ref())));
+        filters.extend(self.bot.map(|bot| memcmp(VAULT_BOT_OFFSET, bot.as_ref())));
+        filters.extend(self.treasury.map(|treasury| memcmp(VAULT_TREASURY_OFFSET, treasury.as_ref())));
+        filters.extend(self.session_id.map(|session_id| memcmp(VAULT_SESSION_ID_OFFSET, &session_id)));
+        filters.extend(self.status.map(|status| memcmp(VAULT_STATUS_OFFSET, &[status as u8])));
+        filters
+    }
+
+    /// Whether raw account data passes the filter, for sources without
+    /// server-side filtering: snapshots, gRPC streams, fixtures.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.memcmps().iter().all(|memcmp| memcmp.matches(data))
+    }
+}
+
+/// Where account data comes from: an RPC client, a snapshot, a test fixture.
+pub trait AccountSource {
+    /// Owner and data of `address`, or `None` if it doesn't exist.
+    fn get_account(&self, address: &Pubkey) -> Result<Option<(Pubkey, Vec<u8>)>, ClientError>;
+}
+
+impl AccountSource for HashMap<Pubkey, (Pubkey, Vec<u8>)> {
+    fn get_account(&self, address: &Pubkey) -> Result<Option<(Pubkey, Vec<u8>)>, ClientError> {
+        Ok(self.get(address).cloned())
+    }
+}
+
+/// Decode raw account data, checking the Anchor discriminator.
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T, ClientError> {
+    Ok(T::try_deserialize(&mut &data[..])?)
+}
+
+/// Fetch and decode a GentDex account.
+pub fn fetch<T: AccountDeserialize>(source: &impl AccountSource, address: &Pubkey) -> Result<T, ClientError> {
+    let (owner, data) = source
+        .get_account(address)?
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    if owner != PROGRAM_ID {
+        return Err(ClientError::WrongOwner(*address));
+    }
+    decode(&data)
+}
+
+pub fn fetch_vault(source: &impl AccountSource, address: &Pubkey) -> Result<Vault, ClientError> {
+    fetch(source, address)
+}
+
+pub fn fetch_config(source: &impl AccountSource) -> Result<ProtocolConfig, ClientError> {
+    fetch(source, &pda::config_address().0)
+}
+
+/// `user`'s registry, or `None` if they never opened an indexed session.
+pub fn fetch_registry(source: &impl AccountSource, user: &Pubkey) -> Result<Option<UserRegistry>, ClientError> {
+    match fetch(source, &pda::registry_address(user).0) {
+        Ok(registry) => Ok(Some(registry)),
+        Err(ClientError::AccountNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// `bot`'s settled-session stats, or `None` before its first session pays out.
+pub fn fetch_bot_stats(source: &impl AccountSource, bot: &Pubkey) -> Result<Option<BotStats>, ClientError> {
+    match fetch(source, &pda::bot_stats_address(bot).0) {
+        Ok(stats) => Ok(Some(stats)),
+        Err(ClientError::AccountNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// The guardian's attestation for `bot`, or `None` if it never made one.
+pub fn fetch_bot_profile(source: &impl AccountSource, bot: &Pubkey) -> Result<Option<BotProfile>, ClientError> {
+    match fetch(source, &pda::bot_profile_address(bot).0) {
+        Ok(profile) => Ok(Some(profile)),
+        Err(ClientError::AccountNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// The program's published governance posture, or `None` if the admin never
+/// set it.
+pub fn fetch_upgrade_info(source: &impl AccountSource) -> Result<Option<UpgradeInfo>, ClientError> {
+    match fetch(source, &pda::upgrade_info_address().0) {
+        Ok(info) => Ok(Some(info)),
+        Err(ClientError::AccountNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Lamports `user` sends with `deposit` for their session to start with
+/// exactly `trading_balance`, at the current setup fee and their stake
+/// discount; what `deposit_exact_balance` charges. `None` if no amount does.
+pub fn exact_deposit_amount(
+    source: &impl AccountSource,
+    user: &Pubkey,
+    trading_balance: u64,
+) -> Result<Option<u64>, ClientError> {
+    let config = fetch_config(source)?;
+    let staked = match fetch::<StakeAccount>(source, &pda::stake_address(user).0) {
+        Ok(stake) => stake.amount,
+        Err(ClientError::AccountNotFound(_)) => 0,
+        Err(err) => return Err(err),
+    };
+    let fee_bps = fee_bps_for_stake(config.fee_bps as u64, staked)?;
+    Ok(gross_for_net(trading_balance, fee_bps))
+}
+
+/// All of `user`'s sessions opened with `initialize_indexed`, in index order.
+/// Closed vaults are skipped.
+pub fn fetch_indexed_vaults(source: &impl AccountSource, user: &Pubkey) -> Result<Vec<(Pubkey, Vault)>, ClientError> {
+    let Some(registry) = fetch_registry(source, user)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut vaults = Vec::new();
+    for index in 0..registry.session_count {
+        let address = pda::indexed_vault_address(user, index).0;
+        match fetch_vault(source, &address) {
+            Ok(vault) => vaults.push((address, vault)),
+            Err(ClientError::AccountNotFound(_)) => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(vaults)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+
+    use super::*;
+
+    #[test]
+    fn filter_offsets_match_the_vault_layout() {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(4096, 0);
+        let mut vault: Vault = decode(&data).unwrap();
+        vault.user = Pubkey::new_unique();
+        vault.bot = Pubkey::new_unique();
+        vault.treasury = Pubkey::new_unique();
+        vault.session_id = [9; 16];
+        vault.status = VaultStatus::Paused;
+
+        let mut encoded = Vec::new();
+        vault.try_serialize(&mut encoded).unwrap();
+        assert_eq!(&encoded[VAULT_USER_OFFSET..VAULT_USER_OFFSET + 32], vault.user.as_ref());
+        assert_eq!(&encoded[VAULT_BOT_OFFSET..VAULT_BOT_OFFSET + 32], vault.bot.as_ref());
+        assert_eq!(&encoded[VAULT_TREASURY_OFFSET..VAULT_TREASURY_OFFSET + 32], vault.treasury.as_ref());
+        assert_eq!(&encoded[VAULT_SESSION_ID_OFFSET..VAULT_SESSION_ID_OFFSET + 16], &[9; 16]);
+        assert_eq!(encoded[VAULT_STATUS_OFFSET], VaultStatus::Paused as u8);
+
+        let filter = VaultFilter::new().bot(vault.bot).status(VaultStatus::Paused);
+        assert_eq!(filter.memcmps().len(), 3);
+        assert!(filter.matches(&encoded));
+        assert!(!filter.clone().status(VaultStatus::Active).matches(&encoded));
+        assert!(!filter.user(vault.bot).matches(&encoded));
+        assert!(!VaultFilter::new().matches(&encoded[..4]));
+    }
+}
@@ -0,0 +1,413 @@
+//! End-of-session statements: deposits, fees by category, trades, realized
+//! PnL and the final payout, built from a session's decoded events and
+//! reconciled against its vault.
+//!
+//! Amounts are raw units of the session's currency (lamports for SOL
+//! sessions). Swaps sell the base currency for tokens, so PnL counts base
+//! currency in and out of the session; tokens still held aren't valued.
+//! Realized PnL is only set once the session is withdrawn.
+
+use std::fmt::Write as _;
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_escrow::{Vault, VaultStatus};
+use serde::Serialize;
+
+use crate::events::{expand_batches, parse_logs, Event};
+use crate::rpc::{GentdexRpc, SignatureInfo, SIGNATURE_PAGE};
+use crate::ClientError;
+
+/// One successful transaction that touched the session.
+#[derive(Debug)]
+pub struct SessionTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub events: Vec<Event>,
+}
+
+/// A vault's transaction history, oldest first.
+#[derive(Debug, Default)]
+pub struct History {
+    pub transactions: Vec<SessionTransaction>,
+    /// Signatures the node no longer has the transaction for
+    pub unavailable: Vec<String>,
+}
+
+/// Walk `vault`'s signature history and decode each successful transaction.
+pub async fn history(rpc: &GentdexRpc, vault: &Pubkey) -> Result<History, ClientError> {
+    let mut signatures: Vec<SignatureInfo> = Vec::new();
+    loop {
+        let before = signatures.last().map(|info| info.signature.clone());
+        let page = rpc.signatures_for_address(vault, before.as_deref()).await?;
+        let done = page.len() < SIGNATURE_PAGE;
+        signatures.extend(page);
+        if done {
+            break;
+        }
+    }
+
+    let mut history = History::default();
+    for info in signatures.into_iter().rev().filter(|info| info.err.is_none()) {
+        match rpc.get_transaction(&info.signature).await? {
+            Some(tx) => history.transactions.push(SessionTransaction {
+                signature: info.signature,
+                slot: tx.slot,
+                block_time: tx.block_time,
+                events: expand_batches(parse_logs(&tx.logs)),
+            }),
+            None => history.unavailable.push(info.signature),
+        }
+    }
+    Ok(history)
+}
+
+/// Money moving into or out of the session.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Movement {
+    pub time: Option<i64>,
+    pub signature: String,
+    pub amount: u64,
+    /// Fee taken with it: the setup fee on a deposit, compute fees settled
+    /// on a withdrawal or transfer out
+    pub fee: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Trade {
+    pub time: Option<i64>,
+    pub signature: String,
+    pub dex_program: String,
+    pub amount_in: u64,
+    pub output_mint: String,
+    /// Raw units of `output_mint`
+    pub amount_out: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Fees {
+    /// Protocol setup fee on deposits, operator share included
+    pub setup: u64,
+    /// Daily compute fees, cranked or settled on withdrawal or transfer
+    pub compute: u64,
+    pub total: u64,
+}
+
+/// Whether the events account for the vault's balance.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Reconciliation {
+    /// Balance the events imply: deposits and transfers in, less trades,
+    /// fees, withdrawals and transfers out
+    pub expected_balance: i128,
+    pub vault_balance: u64,
+    /// `vault_balance - expected_balance`. Swap input a route didn't spend
+    /// and SOL a route paid back are credited without an event of their
+    /// own, so this is usually zero or small and positive.
+    pub difference: i128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Statement {
+    pub vault: String,
+    pub session_id: String,
+    pub user: String,
+    /// `SOL`, or the base mint of a stablecoin session
+    pub currency: String,
+    pub status: String,
+    pub opened_at: Option<i64>,
+    pub closed_at: Option<i64>,
+    pub deposits: Vec<Movement>,
+    pub transfers_in: Vec<Movement>,
+    pub trades: Vec<Trade>,
+    pub withdrawals: Vec<Movement>,
+    pub transfers_out: Vec<Movement>,
+    pub fees: Fees,
+    /// Deposited, fees included
+    pub total_deposited: u64,
+    pub traded_volume: u64,
+    /// Paid out to the user
+    pub final_payout: u64,
+    /// Out of the session less into it, once it's withdrawn
+    pub realized_pnl: Option<i128>,
+    pub reconciliation: Reconciliation,
+}
+
+impl Statement {
+    /// Build the statement for the vault at `address` from its history.
+    pub fn build(address: &Pubkey, vault: &Vault, history: &[SessionTransaction]) -> Self {
+        let session = vault.session_id;
+        let (mut deposits, mut transfers_in, mut trades, mut withdrawals, mut transfers_out) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let mut fees = Fees::default();
+        let mut expected: i128 = 0;
+
+        for tx in history {
+            let movement = |amount, fee| Movement {
+                time: tx.block_time,
+                signature: tx.signature.clone(),
+                amount,
+                fee,
+            };
+            for event in &tx.events {
+                match event {
+                    Event::Deposited(e) if e.session_id == session => {
+                        fees.setup += e.fee;
+                        expected += e.trading_balance as i128;
+                        deposits.push(movement(e.amount, e.fee));
+                    }
+                    Event::SessionTransferred(e) if e.destination_session_id == session => {
+                        expected += e.amount as i128;
+                        transfers_in.push(movement(e.amount, 0));
+                    }
+                    Event::SwapExecuted(e) if e.session_id == session => {
+                        expected -= e.amount_in as i128;
+                        trades.push(Trade {
+                            time: tx.block_time,
+                            signature: tx.signature.clone(),
+                            dex_program: e.dex_program.to_string(),
+                            amount_in: e.amount_in,
+                            output_mint: e.output_mint.to_string(),
+                            amount_out: e.amount_out,
+                        });
+                    }
+                    Event::ComputeFeeDeducted(e) if e.session_id == session => {
+                        fees.compute += e.fee;
+                        expected -= e.fee as i128;
+                    }
+                    Event::Withdrawn(e) if e.session_id == session => {
+                        fees.compute += e.compute_fee;
+                        expected -= e.amount as i128 + e.compute_fee as i128;
+                        withdrawals.push(movement(e.amount, e.compute_fee));
+                    }
+                    Event::SessionTransferred(e) if e.source_session_id == session => {
+                        fees.compute += e.compute_fee;
+                        expected -= e.amount as i128 + e.compute_fee as i128;
+                        transfers_out.push(movement(e.amount, e.compute_fee));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        fees.total = fees.setup + fees.compute;
+
+        let sum = |movements: &[Movement]| movements.iter().map(|m| m.amount).sum::<u64>();
+        let total_deposited = sum(&deposits);
+        let final_payout = sum(&withdrawals);
+        let money_in = total_deposited as i128 + sum(&transfers_in) as i128;
+        let money_out = final_payout as i128 + sum(&transfers_out) as i128;
+        let withdrawn = vault.status == VaultStatus::Withdrawn;
+
+        Self {
+            vault: address.to_string(),
+            session_id: session.iter().map(|byte| format!("{byte:02x}")).collect(),
+            user: vault.user.to_string(),
+            currency: if vault.is_sol_session() { "SOL".to_string() } else { vault.base_mint.to_string() },
+            status: format!("{:?}", vault.status),
+            opened_at: history.iter().find_map(|tx| tx.block_time),
+            closed_at: if withdrawn { history.iter().rev().find_map(|tx| tx.block_time) } else { None },
+            traded_volume: trades.iter().map(|trade| trade.amount_in).sum(),
+            deposits,
+            transfers_in,
+            trades,
+            withdrawals,
+            transfers_out,
+            fees,
+            total_deposited,
+            final_payout,
+            realized_pnl: withdrawn.then_some(money_out - money_in),
+            reconciliation: Reconciliation {
+                expected_balance: expected,
+                vault_balance: vault.balance,
+                difference: vault.balance as i128 - expected,
+            },
+        }
+    }
+
+    /// The statement as plain text, for terminals and email.
+    pub fn to_text(&self) -> String {
+        let amount = |units: i128| self.amount(units);
+        let mut out = String::new();
+        let mut line = |text: String| {
+            out.push_str(&text);
+            out.push('\n');
+        };
+
+        line("GentDex session statement".to_string());
+        line(format!("Session       {}", self.session_id));
+        line(format!("Vault         {}", self.vault));
+        line(format!("Owner         {}", self.user));
+        line(format!("Status        {}", self.status));
+        let period = |time: Option<i64>| time.map_or("-".to_string(), date);
+        line(format!("Period        {} to {}", period(self.opened_at), period(self.closed_at)));
+
+        let mut movements = |title: &str, movements: &[Movement]| {
+            if movements.is_empty() {
+                return;
+            }
+            line(String::new());
+            line(title.to_string());
+            for m in movements {
+                let fee = if m.fee > 0 { format!(" (fee {})", amount(m.fee as i128)) } else { String::new() };
+                line(format!("  {}  {}{fee}  {}", period(m.time), amount(m.amount as i128), m.signature));
+            }
+        };
+        movements("Deposits", &self.deposits);
+        movements("Transfers in", &self.transfers_in);
+        movements("Withdrawals", &self.withdrawals);
+        movements("Transfers out", &self.transfers_out);
+
+        line(String::new());
+        line(format!("Trades        {} for {}", self.trades.len(), amount(self.traded_volume as i128)));
+        for trade in &self.trades {
+            line(format!(
+                "  {}  {} for {} of {}  {}",
+                period(trade.time),
+                amount(trade.amount_in as i128),
+                trade.amount_out,
+                trade.output_mint,
+                trade.signature
+            ));
+        }
+
+        line(String::new());
+        line(format!("Fees          {}", amount(self.fees.total as i128)));
+        line(format!("  Setup       {}", amount(self.fees.setup as i128)));
+        line(format!("  Compute     {}", amount(self.fees.compute as i128)));
+        line(String::new());
+        line(format!("Deposited     {}", amount(self.total_deposited as i128)));
+        line(format!("Final payout  {}", amount(self.final_payout as i128)));
+        match self.realized_pnl {
+            Some(pnl) => line(format!("Realized PnL  {}", amount(pnl))),
+            None => {
+                let balance = amount(self.reconciliation.vault_balance as i128);
+                line(format!("Realized PnL  - (session open, {balance} in the vault)"))
+            }
+        }
+
+        let reconciliation = &self.reconciliation;
+        line(String::new());
+        if reconciliation.difference == 0 {
+            line("Reconciled: the events account for the vault balance".to_string());
+        } else {
+            line(format!(
+                "Reconciled within {}: swap input not spent or SOL paid back by routes",
+                amount(reconciliation.difference)
+            ));
+        }
+        out
+    }
+
+    /// `units` in the session's currency: `1.5 SOL`, or raw units of the mint.
+    fn amount(&self, units: i128) -> String {
+        if self.currency != "SOL" {
+            return format!("{units} {}", self.currency);
+        }
+        let sign = if units < 0 { "-" } else { "" };
+        let lamports = units.unsigned_abs();
+        let mut text = format!("{sign}{}", lamports / 1_000_000_000);
+        let fraction = format!("{:09}", lamports % 1_000_000_000);
+        let fraction = fraction.trim_end_matches('0');
+        if !fraction.is_empty() {
+            let _ = write!(text, ".{fraction}");
+        }
+        text + " SOL"
+    }
+}
+
+/// `YYYY-MM-DD HH:MM` UTC from a unix timestamp.
+fn date(time: i64) -> String {
+    // Days to civil date, from Howard Hinnant's `civil_from_days`
+    let (days, seconds) = (time.div_euclid(86_400), time.rem_euclid(86_400));
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02} {:02}:{:02}", seconds / 3_600, seconds % 3_600 / 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountDeserialize, Discriminator};
+    use anchor_spl::token::spl_token::native_mint;
+    use gentdex_escrow::{ComputeFeeDeducted, Deposited, SwapExecuted, Withdrawn};
+
+    use super::*;
+
+    fn tx(signature: &str, block_time: i64, events: Vec<Event>) -> SessionTransaction {
+        SessionTransaction {
+            signature: signature.to_string(),
+            slot: 0,
+            block_time: Some(block_time),
+            events,
+        }
+    }
+
+    #[test]
+    fn totals_fees_pnl_and_reconciles() {
+        let session_id = [5; 16];
+        let address = Pubkey::new_unique();
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(Vault::DISCRIMINATOR.len() + 2048, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.session_id = session_id;
+        vault.base_mint = native_mint::ID;
+        vault.status = VaultStatus::Withdrawn;
+
+        let history = [
+            tx("deposit", 1_709_251_200, vec![Event::Deposited(Deposited {
+                session_id,
+                vault: address,
+                amount: 2_000_000_000,
+                fee: 50_000_000,
+                trading_balance: 1_950_000_000,
+                expires_at: 0,
+            })]),
+            tx("swap", 1_709_254_800, vec![Event::SwapExecuted(SwapExecuted {
+                session_id,
+                vault: address,
+                bot: Pubkey::new_unique(),
+                dex_program: Pubkey::new_unique(),
+                amount_in: 500_000_000,
+                minimum_amount_out: 0,
+                timestamp: 0,
+                output_mint: Pubkey::new_unique(),
+                amount_out: 42,
+                memo: [0; 32],
+            })]),
+            tx("crank", 1_709_337_600, vec![Event::ComputeFeeDeducted(ComputeFeeDeducted {
+                session_id,
+                vault: address,
+                fee: 10_000_000,
+                remaining_balance: 0,
+            })]),
+            // The route returned 0.2 SOL it didn't spend
+            tx("withdraw", 1_709_424_000, vec![Event::Withdrawn(Withdrawn {
+                session_id,
+                vault: address,
+                amount: 1_630_000_000,
+                compute_fee: 10_000_000,
+                user: vault.user,
+            })]),
+        ];
+
+        let statement = Statement::build(&address, &vault, &history);
+        assert_eq!(statement.fees, Fees { setup: 50_000_000, compute: 20_000_000, total: 70_000_000 });
+        assert_eq!(statement.final_payout, 1_630_000_000);
+        assert_eq!(statement.realized_pnl, Some(-370_000_000));
+        assert_eq!(statement.reconciliation.expected_balance, -200_000_000);
+        assert_eq!(statement.reconciliation.difference, 200_000_000);
+        assert_eq!(statement.closed_at, Some(1_709_424_000));
+
+        let text = statement.to_text();
+        assert!(text.contains("Period        2024-03-01 00:00 to 2024-03-03 00:00"));
+        assert!(text.contains("Realized PnL  -0.37 SOL"));
+        assert!(text.contains("  Compute     0.02 SOL"));
+        let json = serde_json::to_value(&statement).unwrap();
+        assert_eq!(json["trades"][0]["amount_out"], 42);
+    }
+}
@@ -0,0 +1,217 @@
+//! Live GentDex events over a websocket `logsSubscribe`.
+//!
+//! The subscription runs in a background task that reconnects with backoff
+//! whenever the socket drops, so an [`EventStream`] only ends when it's
+//! dropped. Transactions are de-duplicated by signature, which a reconnect
+//! (or a node replaying notifications) would otherwise deliver twice. Events
+//! emitted while disconnected are missed; backfill from signature history
+//! if you need every one.
+
+use std::collections::{HashSet, VecDeque};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::events::{parse_logs, Event};
+use crate::rpc::RetryPolicy;
+use crate::{ClientError, PROGRAM_ID};
+
+/// Signatures remembered for de-duplication
+const SEEN_SIGNATURES: usize = 10_000;
+/// Transactions buffered between the socket and the consumer
+const CHANNEL_CAPACITY: usize = 1_024;
+
+/// GentDex events from one successful transaction.
+pub struct TransactionEvents {
+    pub signature: String,
+    pub slot: u64,
+    pub events: Vec<Event>,
+}
+
+pub struct EventStream {
+    receiver: mpsc::Receiver<TransactionEvents>,
+    task: JoinHandle<()>,
+}
+
+impl EventStream {
+    /// Subscribe to GentDex logs at `ws_url` (e.g. `wss://api.mainnet-beta.solana.com`)
+    /// at `commitment`. Must be called inside a Tokio runtime.
+    pub fn subscribe(ws_url: impl Into<String>, commitment: impl Into<String>, retry: RetryPolicy) -> Self {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        let task = tokio::spawn(run(ws_url.into(), commitment.into(), retry, sender));
+        Self { receiver, task }
+    }
+
+    /// The next transaction with GentDex events. `None` only if the background
+    /// task has stopped, which happens when the consumer side is closed.
+    pub async fn next(&mut self) -> Option<TransactionEvents> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Connect, forward, and reconnect until the consumer goes away.
+async fn run(ws_url: String, commitment: String, retry: RetryPolicy, sender: mpsc::Sender<TransactionEvents>) {
+    let mut seen = SeenSignatures::new(SEEN_SIGNATURES);
+    let mut failures = 0;
+    loop {
+        match forward(&ws_url, &commitment, &mut seen, &sender, &mut failures).await {
+            Ok(()) => return,
+            Err(_) => {
+                failures += 1;
+                tokio::time::sleep(retry.backoff(failures)).await;
+            }
+        }
+    }
+}
+
+/// One connection's worth of notifications. `Ok` means the consumer is gone;
+/// any connection problem is an `Err` to reconnect on. Resets `failures`
+/// once the subscription is confirmed.
+async fn forward(
+    ws_url: &str,
+    commitment: &str,
+    seen: &mut SeenSignatures,
+    sender: &mpsc::Sender<TransactionEvents>,
+    failures: &mut u32,
+) -> Result<(), ClientError> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(ws_url)
+        .await
+        .map_err(|err| ClientError::Rpc(format!("websocket connect: {err}")))?;
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "logsSubscribe",
+        "params": [{ "mentions": [PROGRAM_ID.to_string()] }, { "commitment": commitment }],
+    });
+    socket
+        .send(Message::text(request.to_string()))
+        .await
+        .map_err(|err| ClientError::Rpc(format!("websocket send: {err}")))?;
+
+    while let Some(message) = socket.next().await {
+        let message = message.map_err(|err| ClientError::Rpc(format!("websocket: {err}")))?;
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let Ok(message) = serde_json::from_str::<SubscriptionMessage>(&text) else {
+            continue;
+        };
+        if message.result.is_some() {
+            // Subscription confirmed
+            *failures = 0;
+            continue;
+        }
+        let Some(params) = message.params else {
+            continue;
+        };
+
+        let notification = params.result;
+        // Events from failed transactions were rolled back
+        if notification.value.err.is_some() || !seen.insert(&notification.value.signature) {
+            continue;
+        }
+        let events = parse_logs(&notification.value.logs);
+        if events.is_empty() {
+            continue;
+        }
+        let batch = TransactionEvents {
+            signature: notification.value.signature,
+            slot: notification.context.slot,
+            events,
+        };
+        if sender.send(batch).await.is_err() {
+            return Ok(());
+        }
+    }
+    Err(ClientError::Rpc("websocket closed".to_string()))
+}
+
+#[derive(Deserialize)]
+struct SubscriptionMessage {
+    result: Option<serde_json::Value>,
+    params: Option<NotificationParams>,
+}
+
+#[derive(Deserialize)]
+struct NotificationParams {
+    result: LogsNotification,
+}
+
+#[derive(Deserialize)]
+struct LogsNotification {
+    context: SlotContext,
+    value: LogsValue,
+}
+
+#[derive(Deserialize)]
+struct SlotContext {
+    slot: u64,
+}
+
+#[derive(Deserialize)]
+struct LogsValue {
+    signature: String,
+    err: Option<serde_json::Value>,
+    logs: Vec<String>,
+}
+
+/// The last `capacity` signatures seen, oldest evicted first.
+struct SeenSignatures {
+    order: VecDeque<String>,
+    set: HashSet<String>,
+    capacity: usize,
+}
+
+impl SeenSignatures {
+    fn new(capacity: usize) -> Self {
+        Self {
+            order: VecDeque::with_capacity(capacity),
+            set: HashSet::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record `signature`; `false` if it was already there.
+    fn insert(&mut self, signature: &str) -> bool {
+        if self.set.contains(signature) {
+            return false;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.set.remove(&oldest);
+            }
+        }
+        self.order.push_back(signature.to_string());
+        self.set.insert(signature.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_a_bounded_window_of_signatures() {
+        let mut seen = SeenSignatures::new(2);
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("a"));
+        assert!(seen.insert("b"));
+        assert!(seen.insert("c"));
+        // "a" was evicted
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("c"));
+    }
+}
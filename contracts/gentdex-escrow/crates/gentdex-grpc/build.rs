@@ -0,0 +1,12 @@
+// Compiles the protos with a vendored protoc, so building doesn't need one
+// installed. We serve VaultStream and are a client of Yellowstone's Geyser.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/vault_stream.proto"], &["proto"])?;
+    tonic_prost_build::configure()
+        .build_server(false)
+        .compile_protos(&["proto/geyser.proto"], &["proto"])?;
+    Ok(())
+}
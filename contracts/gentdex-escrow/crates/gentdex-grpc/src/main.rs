@@ -0,0 +1,227 @@
+//! `gentdex-grpc`: a gRPC feed of vault state transitions and swaps.
+//!
+//! One websocket subscription to the program's logs fans out to every
+//! subscriber, each filtered by session or owner (see
+//! `proto/vault_stream.proto`). Events that don't name the owner get it from
+//! a cache filled by the events that do, falling back to a
+//! `getProgramAccounts` lookup by session id.
+//!
+//! Given a Yellowstone gRPC endpoint it also serves `WatchVaults`: vault
+//! account writes diffed field by field (see [`yellowstone`]). Operators
+//! running their own validator load Yellowstone's Geyser plugin into it and
+//! point `--yellowstone-endpoint` at that; there is no GentDex plugin, as a
+//! plugin has to be built against the exact validator version loading it.
+
+mod updates;
+mod yellowstone;
+
+mod proto {
+    tonic::include_proto!("gentdex.stream.v1");
+}
+
+mod geyser {
+    tonic::include_proto!("geyser");
+}
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use clap::Parser;
+use gentdex_client::rpc::{GentdexRpc, RetryPolicy};
+use gentdex_client::stream::EventStream;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::{error, info, warn};
+
+use crate::proto::vault_stream_server::{VaultStream, VaultStreamServer};
+use crate::proto::{SubscribeRequest, VaultDiff, VaultUpdate};
+use crate::updates::Filter;
+
+/// Updates buffered for all subscribers; one further behind is dropped
+const BROADCAST_CAPACITY: usize = 4_096;
+/// Updates buffered per subscriber connection
+const SUBSCRIBER_BUFFER: usize = 256;
+
+#[derive(Parser)]
+#[command(name = "gentdex-grpc", version, about = "Stream GentDex vault updates over gRPC")]
+struct Args {
+    /// JSON-RPC endpoints, comma-separated, for resolving session owners
+    #[arg(
+        long,
+        short = 'u',
+        env = "GENTDEX_RPC_URL",
+        value_delimiter = ',',
+        default_value = "https://api.mainnet-beta.solana.com"
+    )]
+    url: Vec<String>,
+    /// Websocket endpoint [default: the RPC URL with a ws:// or wss:// scheme]
+    #[arg(long, env = "GENTDEX_WS_URL")]
+    ws_url: Option<String>,
+    /// Address to serve gRPC on
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    listen: SocketAddr,
+    /// Yellowstone gRPC endpoint for `WatchVaults`, e.g. `https://grpc.example.com:443`
+    #[arg(long, env = "GENTDEX_YELLOWSTONE_ENDPOINT")]
+    yellowstone_endpoint: Option<String>,
+    /// `x-token` for the Yellowstone endpoint
+    #[arg(long, env = "GENTDEX_YELLOWSTONE_TOKEN", hide_env_values = true)]
+    yellowstone_token: Option<String>,
+}
+
+/// `url` with its scheme swapped for the websocket one.
+fn websocket_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        url.to_string()
+    }
+}
+
+struct Service {
+    updates: broadcast::Sender<VaultUpdate>,
+    /// Set when following a Yellowstone feed
+    diffs: Option<broadcast::Sender<VaultDiff>>,
+}
+
+/// Forward the broadcast `items` that `keep` accepts to one subscriber.
+fn forward<T: Clone + Send + 'static>(
+    mut items: broadcast::Receiver<T>,
+    keep: impl Fn(&T) -> bool + Send + 'static,
+) -> ReceiverStream<Result<T, Status>> {
+    let (sender, receiver) = mpsc::channel(SUBSCRIBER_BUFFER);
+    tokio::spawn(async move {
+        loop {
+            let item = match items.recv().await {
+                Ok(item) => item,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    let status = Status::resource_exhausted(format!("fell {missed} updates behind; resubscribe"));
+                    let _ = sender.send(Err(status)).await;
+                    return;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            if keep(&item) && sender.send(Ok(item)).await.is_err() {
+                // Subscriber disconnected
+                return;
+            }
+        }
+    });
+    ReceiverStream::new(receiver)
+}
+
+#[tonic::async_trait]
+impl VaultStream for Service {
+    type SubscribeStream = ReceiverStream<Result<VaultUpdate, Status>>;
+    type WatchVaultsStream = ReceiverStream<Result<VaultDiff, Status>>;
+
+    async fn subscribe(&self, request: Request<SubscribeRequest>) -> Result<Response<Self::SubscribeStream>, Status> {
+        let filter = Filter::new(request.into_inner())?;
+        let stream = forward(self.updates.subscribe(), move |update: &VaultUpdate| {
+            filter.matches(&update.session_id, &update.user)
+        });
+        Ok(Response::new(stream))
+    }
+
+    async fn watch_vaults(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::WatchVaultsStream>, Status> {
+        let diffs = self
+            .diffs
+            .as_ref()
+            .ok_or_else(|| Status::unavailable("server has no Yellowstone feed configured"))?;
+        let filter = Filter::new(request.into_inner())?;
+        let stream = forward(diffs.subscribe(), move |diff: &VaultDiff| filter.matches(&diff.session_id, &diff.user));
+        Ok(Response::new(stream))
+    }
+}
+
+/// Session owners, learned from events or looked up once per session.
+struct Owners {
+    rpc: GentdexRpc,
+    known: HashMap<Vec<u8>, String>,
+}
+
+impl Owners {
+    /// Fill in `update.user`, or remember it if the event named it. Left
+    /// empty if the lookup fails, or if several users' indexed sessions
+    /// share the id.
+    async fn resolve(&mut self, update: &mut VaultUpdate) {
+        if !update.user.is_empty() {
+            self.known.insert(update.session_id.clone(), update.user.clone());
+            return;
+        }
+        if let Some(user) = self.known.get(&update.session_id) {
+            update.user = user.clone();
+            return;
+        }
+        let Ok(session_id) = <[u8; 16]>::try_from(update.session_id.as_slice()) else {
+            return;
+        };
+        match self.rpc.vaults_by_session(&session_id).await {
+            Ok(vaults) if vaults.len() == 1 => {
+                update.user = vaults[0].1.user.to_string();
+                self.known.insert(update.session_id.clone(), update.user.clone());
+            }
+            Ok(_) => {}
+            Err(err) => warn!(%err, "could not resolve session owner"),
+        }
+    }
+}
+
+/// Decode the program's logs into updates and broadcast them.
+async fn pump(mut stream: EventStream, mut owners: Owners, updates: broadcast::Sender<VaultUpdate>) {
+    while let Some(tx) = stream.next().await {
+        for mut update in updates::updates(&tx.signature, tx.slot, &tx.events) {
+            owners.resolve(&mut update).await;
+            // Only fails with no subscribers, which is fine
+            let _ = updates.send(update);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let args = Args::parse();
+    let ws_url = args.ws_url.unwrap_or_else(|| websocket_url(&args.url[0]));
+    let owners = Owners {
+        rpc: GentdexRpc::pooled(args.url),
+        known: HashMap::new(),
+    };
+    let (updates, _) = broadcast::channel(BROADCAST_CAPACITY);
+    let stream = EventStream::subscribe(ws_url, "confirmed", RetryPolicy::default());
+    tokio::spawn(pump(stream, owners, updates.clone()));
+
+    let diffs = args.yellowstone_endpoint.map(|endpoint| {
+        let (diffs, _) = broadcast::channel(BROADCAST_CAPACITY);
+        tokio::spawn(yellowstone::run(
+            endpoint,
+            args.yellowstone_token,
+            RetryPolicy::default(),
+            diffs.clone(),
+        ));
+        diffs
+    });
+
+    info!(addr = %args.listen, "serving gRPC");
+    let served = tonic::transport::Server::builder()
+        .add_service(VaultStreamServer::new(Service { updates, diffs }))
+        .serve_with_shutdown(args.listen, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await;
+    match served {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => {
+            error!(%err, "gRPC server failed");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! GentDex events as `VaultUpdate`s, and subscriber filters over them.
+
+use std::collections::HashSet;
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_client::events::Event;
+use tonic::Status;
+
+use crate::proto::vault_update::Event as Update;
+use crate::proto::{self, SubscribeRequest, VaultStatus, VaultUpdate};
+
+/// The updates for one transaction's events. `user` is filled in where the
+/// event names it; otherwise the caller resolves it from the session.
+pub fn updates(signature: &str, slot: u64, events: &[Event]) -> Vec<VaultUpdate> {
+    let update = |session_id: [u8; 16], user: Option<Pubkey>, status: VaultStatus, event: Update| VaultUpdate {
+        signature: signature.to_string(),
+        slot,
+        session_id: session_id.to_vec(),
+        user: user.map(|user| user.to_string()).unwrap_or_default(),
+        status: status as i32,
+        event: Some(event),
+    };
+    let unchanged = VaultStatus::Unspecified;
+
+    let mut updates = Vec::new();
+    for event in events {
+        match event {
+            Event::SessionCreated(e) => updates.push(update(
+                e.session_id,
+                Some(e.user),
+                VaultStatus::Pending,
+                Update::Created(proto::SessionCreated {
+                    bot: e.bot.to_string(),
+                    duration_days: e.duration_days.into(),
+                }),
+            )),
+            Event::Deposited(e) => updates.push(update(
+                e.session_id,
+                None,
+                VaultStatus::Active,
+                Update::Deposited(proto::Deposited {
+                    amount: e.amount,
+                    fee: e.fee,
+                    trading_balance: e.trading_balance,
+                    expires_at: e.expires_at,
+                }),
+            )),
+            Event::SwapExecuted(e) => updates.push(update(
+                e.session_id,
+                None,
+                unchanged,
+                Update::Swap(proto::SwapExecuted {
+                    bot: e.bot.to_string(),
+                    dex_program: e.dex_program.to_string(),
+                    amount_in: e.amount_in,
+                    minimum_amount_out: e.minimum_amount_out,
+                    output_mint: e.output_mint.to_string(),
+                    amount_out: e.amount_out,
+                    timestamp: e.timestamp,
+                    memo: e.memo.to_vec(),
+                }),
+            )),
+            Event::SwapRejected(e) => updates.push(update(
+                e.session_id,
+                None,
+                unchanged,
+                Update::SwapRejected(proto::SwapRejected {
+                    bot: e.bot.to_string(),
+                    dex_program: e.dex_program.to_string(),
+                    amount_in: e.amount_in,
+                    reason: format!("{:?}", e.reason),
+                    timestamp: e.timestamp,
+                    memo: e.memo.to_vec(),
+                }),
+            )),
+            // A deduction that empties the vault expires it
+            Event::ComputeFeeDeducted(e) => updates.push(update(
+                e.session_id,
+                None,
+                if e.remaining_balance == 0 { VaultStatus::Expired } else { unchanged },
+                Update::ComputeFee(proto::ComputeFeeDeducted {
+                    fee: e.fee,
+                    remaining_balance: e.remaining_balance,
+                }),
+            )),
+            Event::SessionPaused(e) => {
+                updates.push(update(e.session_id, None, VaultStatus::Paused, Update::Paused(proto::Paused {})))
+            }
+            Event::SessionResumed(e) => {
+                updates.push(update(e.session_id, None, VaultStatus::Active, Update::Resumed(proto::Resumed {})))
+            }
+            Event::Withdrawn(e) => updates.push(update(
+                e.session_id,
+                Some(e.user),
+                VaultStatus::Withdrawn,
+                Update::Withdrawn(proto::Withdrawn {
+                    amount: e.amount,
+                    compute_fee: e.compute_fee,
+                }),
+            )),
+            Event::SessionTransferred(e) => {
+                updates.push(update(
+                    e.source_session_id,
+                    Some(e.user),
+                    VaultStatus::Withdrawn,
+                    Update::TransferredOut(proto::TransferredOut {
+                        destination_session_id: e.destination_session_id.to_vec(),
+                        amount: e.amount,
+                        compute_fee: e.compute_fee,
+                    }),
+                ));
+                updates.push(update(
+                    e.destination_session_id,
+                    Some(e.user),
+                    VaultStatus::Active,
+                    Update::TransferredIn(proto::TransferredIn {
+                        source_session_id: e.source_session_id.to_vec(),
+                        amount: e.amount,
+                    }),
+                ));
+            }
+            Event::SessionExpiredEvent(e) => updates.push(update(
+                e.session_id,
+                None,
+                VaultStatus::Expired,
+                Update::Expired(proto::Expired {
+                    remaining_balance: e.remaining_balance,
+                }),
+            )),
+            _ => {}
+        }
+    }
+    updates
+}
+
+/// Which updates a subscriber wants.
+pub struct Filter {
+    session_ids: HashSet<Vec<u8>>,
+    users: HashSet<String>,
+}
+
+impl Filter {
+    pub fn new(request: SubscribeRequest) -> Result<Self, Status> {
+        if let Some(id) = request.session_ids.iter().find(|id| id.len() != 16) {
+            return Err(Status::invalid_argument(format!("session id must be 16 bytes, got {}", id.len())));
+        }
+        let users = request
+            .users
+            .into_iter()
+            .map(|user| match user.parse::<Pubkey>() {
+                Ok(pubkey) => Ok(pubkey.to_string()),
+                Err(_) => Err(Status::invalid_argument(format!("invalid user {user:?}"))),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            session_ids: request.session_ids.into_iter().collect(),
+            users,
+        })
+    }
+
+    pub fn matches(&self, session_id: &[u8], user: &str) -> bool {
+        (self.session_ids.is_empty() && self.users.is_empty())
+            || self.session_ids.contains(session_id)
+            || self.users.contains(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gentdex_client::program::{SessionPaused, SessionTransferred};
+
+    use super::*;
+
+    #[test]
+    fn splits_transfers_and_filters_by_session_or_user() {
+        let user = Pubkey::new_unique();
+        let events = vec![
+            Event::SessionPaused(SessionPaused {
+                session_id: [1; 16],
+                vault: Pubkey::new_unique(),
+            }),
+            Event::SessionTransferred(SessionTransferred {
+                source_session_id: [1; 16],
+                source_vault: Pubkey::new_unique(),
+                destination_session_id: [2; 16],
+                destination_vault: Pubkey::new_unique(),
+                amount: 500,
+                compute_fee: 5,
+                user,
+            }),
+        ];
+
+        let updates = updates("sig", 7, &events);
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[0].status(), VaultStatus::Paused);
+        assert_eq!(updates[0].user, "");
+        assert_eq!(updates[1].status(), VaultStatus::Withdrawn);
+        assert_eq!(updates[2].session_id, vec![2; 16]);
+        assert_eq!(updates[2].status(), VaultStatus::Active);
+
+        let by_session = Filter::new(SubscribeRequest {
+            session_ids: vec![vec![2; 16]],
+            users: vec![],
+        })
+        .unwrap();
+        let matched: Vec<bool> = updates
+            .iter()
+            .map(|update| by_session.matches(&update.session_id, &update.user))
+            .collect();
+        assert_eq!(matched, vec![false, false, true]);
+
+        let by_user = Filter::new(SubscribeRequest {
+            session_ids: vec![],
+            users: vec![user.to_string()],
+        })
+        .unwrap();
+        assert!(!by_user.matches(&updates[0].session_id, &updates[0].user));
+        assert!(by_user.matches(&updates[1].session_id, &updates[1].user));
+
+        assert!(Filter::new(SubscribeRequest {
+            session_ids: vec![vec![0; 3]],
+            users: vec![],
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Vault account diffs from a Yellowstone gRPC (Geyser) feed.
+//!
+//! Subscribes to writes of accounts owned by the program that start with the
+//! `Vault` discriminator, decodes each one and compares it with the last
+//! version seen, publishing the fields that changed. Reconnects with backoff;
+//! writes while disconnected are missed, but the next write to a vault diffs
+//! against the last one seen, so its changes aren't lost.
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use gentdex_client::program::Vault;
+use gentdex_client::rpc::RetryPolicy;
+use gentdex_client::{state, PROGRAM_ID};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::AsciiMetadataValue;
+use tonic::transport::{ClientTlsConfig, Endpoint};
+use tracing::{info, warn};
+
+use crate::geyser::geyser_client::GeyserClient;
+use crate::geyser::subscribe_request_filter_accounts_filter::Filter as AccountsFilter;
+use crate::geyser::subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData;
+use crate::geyser::subscribe_update::UpdateOneof;
+use crate::geyser::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter,
+    SubscribeRequestFilterAccountsFilterMemcmp, SubscribeRequestPing, SubscribeUpdateAccountInfo,
+};
+use crate::proto::{FieldChange, VaultDiff};
+
+#[derive(Debug, thiserror::Error)]
+enum FeedError {
+    #[error("connecting: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    #[error("stream: {0}")]
+    Status(#[from] tonic::Status),
+    #[error("x-token is not a valid header value")]
+    Token,
+    #[error("server closed the stream")]
+    Closed,
+}
+
+/// Fields compared between writes, rendered with `Display`.
+macro_rules! diff_fields {
+    ($old:expr, $new:expr, $changes:ident, [$($field:ident),* $(,)?]) => {
+        $(
+            let new = $new.$field.to_string();
+            match $old {
+                Some(old) if old.$field == $new.$field => {}
+                old => $changes.push(FieldChange {
+                    field: stringify!($field).to_string(),
+                    old: old.map(|old| old.$field.to_string()).unwrap_or_default(),
+                    new,
+                }),
+            }
+        )*
+    };
+}
+
+/// The changes from `old` (if seen before) to `new`, or `None` if no tracked
+/// field changed.
+pub fn diff(address: &Pubkey, slot: u64, old: Option<&Vault>, new: &Vault) -> Option<VaultDiff> {
+    let mut changes = Vec::new();
+    let status = |vault: &Vault| format!("{:?}", vault.status);
+    match old {
+        Some(old) if old.status == new.status => {}
+        old => changes.push(FieldChange {
+            field: "status".to_string(),
+            old: old.map(status).unwrap_or_default(),
+            new: status(new),
+        }),
+    }
+    diff_fields!(
+        old,
+        new,
+        changes,
+        [
+            bot,
+            balance,
+            compute_fees_paid,
+            expires_at,
+            last_compute_deduction,
+            locked,
+            perps_collateral,
+            lent_amount,
+            total_volume,
+            total_deposited,
+            total_withdrawn,
+            slippage_consumed,
+            recovery,
+        ]
+    );
+
+    (!changes.is_empty()).then(|| VaultDiff {
+        address: address.to_bytes().to_vec(),
+        slot,
+        session_id: new.session_id.to_vec(),
+        user: new.user.to_string(),
+        first_seen: old.is_none(),
+        closed: false,
+        changes,
+    })
+}
+
+/// Follow `endpoint` forever, publishing diffs to `diffs`.
+pub async fn run(endpoint: String, token: Option<String>, retry: RetryPolicy, diffs: broadcast::Sender<VaultDiff>) {
+    let mut vaults = HashMap::new();
+    let mut failures = 0;
+    loop {
+        let err = follow(&endpoint, token.as_deref(), &mut vaults, &diffs, &mut failures)
+            .await
+            .unwrap_err();
+        failures += 1;
+        warn!(%err, failures, "yellowstone feed dropped; reconnecting");
+        tokio::time::sleep(retry.backoff(failures)).await;
+    }
+}
+
+/// One connection. Only returns on error; resets `failures` once updates flow.
+async fn follow(
+    endpoint: &str,
+    token: Option<&str>,
+    vaults: &mut HashMap<Pubkey, Vault>,
+    diffs: &broadcast::Sender<VaultDiff>,
+    failures: &mut u32,
+) -> Result<(), FeedError> {
+    let mut channel = Endpoint::from_shared(endpoint.to_string())?;
+    if endpoint.starts_with("https://") {
+        channel = channel.tls_config(ClientTlsConfig::new().with_webpki_roots())?;
+    }
+    let token: Option<AsciiMetadataValue> = token.map(|token| token.parse().map_err(|_| FeedError::Token)).transpose()?;
+    let mut client = GeyserClient::with_interceptor(channel.connect().await?, move |mut request: tonic::Request<()>| {
+        if let Some(token) = &token {
+            request.metadata_mut().insert("x-token", token.clone());
+        }
+        Ok(request)
+    });
+
+    let (requests, outgoing) = mpsc::channel(16);
+    let subscription = SubscribeRequest {
+        accounts: HashMap::from([(
+            "gentdex_vaults".to_string(),
+            SubscribeRequestFilterAccounts {
+                account: vec![],
+                owner: vec![PROGRAM_ID.to_string()],
+                filters: vec![SubscribeRequestFilterAccountsFilter {
+                    filter: Some(AccountsFilter::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                        offset: 0,
+                        data: Some(MemcmpData::Bytes(Vault::DISCRIMINATOR.to_vec())),
+                    })),
+                }],
+            },
+        )]),
+        commitment: Some(CommitmentLevel::Confirmed as i32),
+        ping: None,
+    };
+    requests.send(subscription).await.map_err(|_| FeedError::Closed)?;
+    let mut updates = client.subscribe(ReceiverStream::new(outgoing)).await?.into_inner();
+    info!(endpoint, "following yellowstone vault writes");
+
+    while let Some(update) = updates.message().await? {
+        *failures = 0;
+        match update.update_oneof {
+            Some(UpdateOneof::Account(update)) => {
+                if let Some(account) = update.account {
+                    if let Some(diff) = apply(vaults, update.slot, account) {
+                        // Only fails with no subscribers, which is fine
+                        let _ = diffs.send(diff);
+                    }
+                }
+            }
+            // Answer server pings so proxies keep the stream open
+            Some(UpdateOneof::Ping(_)) => {
+                let ping = SubscribeRequest {
+                    ping: Some(SubscribeRequestPing { id: 1 }),
+                    ..Default::default()
+                };
+                requests.send(ping).await.map_err(|_| FeedError::Closed)?;
+            }
+            Some(UpdateOneof::Pong(_)) | None => {}
+        }
+    }
+    Err(FeedError::Closed)
+}
+
+/// Record one account write, returning its diff if anything tracked changed.
+fn apply(vaults: &mut HashMap<Pubkey, Vault>, slot: u64, account: SubscribeUpdateAccountInfo) -> Option<VaultDiff> {
+    let address = Pubkey::try_from(account.pubkey.as_slice()).ok()?;
+    if account.lamports == 0 || account.data.is_empty() {
+        let vault = vaults.remove(&address)?;
+        return Some(VaultDiff {
+            address: account.pubkey,
+            slot,
+            session_id: vault.session_id.to_vec(),
+            user: vault.user.to_string(),
+            first_seen: false,
+            closed: true,
+            changes: vec![],
+        });
+    }
+    let vault: Vault = match state::decode(&account.data) {
+        Ok(vault) => vault,
+        Err(err) => {
+            warn!(%address, %err, "undecodable vault write");
+            return None;
+        }
+    };
+    let diff = diff(&address, slot, vaults.get(&address), &vault);
+    vaults.insert(address, vault);
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountDeserialize;
+    use gentdex_client::program::VaultStatus;
+
+    use super::*;
+
+    fn vault() -> Vault {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(4096, 0);
+        Vault::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn diffs_tracked_fields_between_writes() {
+        let address = Pubkey::new_unique();
+        let before = vault();
+
+        let first = diff(&address, 1, None, &before).unwrap();
+        assert!(first.first_seen);
+        assert!(first.changes.iter().all(|change| change.old.is_empty()));
+        assert_eq!(first.changes[0].new, "Pending");
+
+        assert!(diff(&address, 2, Some(&before), &before).is_none());
+
+        let mut after = vault();
+        after.status = VaultStatus::Active;
+        after.balance = 990;
+        let changes = diff(&address, 3, Some(&before), &after).unwrap().changes;
+        let changed: Vec<(&str, &str, &str)> = changes
+            .iter()
+            .map(|change| (change.field.as_str(), change.old.as_str(), change.new.as_str()))
+            .collect();
+        assert_eq!(changed, vec![("status", "Pending", "Active"), ("balance", "0", "990")]);
+    }
+}
@@ -0,0 +1,108 @@
+-- GentDex indexer schema. Applied on startup; every statement is idempotent.
+--
+-- Every event row belongs to the transaction that emitted it and is deleted
+-- with it, so rolling back a transaction dropped by a fork is one DELETE.
+-- Amounts are NUMERIC since u64 doesn't fit BIGINT; addresses are base58.
+--
+-- Session ids are only unique per user (indexed sessions number theirs per
+-- user), so every table is keyed on the vault address the events carry. The
+-- id is kept on `sessions` for display and lookup.
+
+CREATE TABLE IF NOT EXISTS transactions (
+    signature   TEXT PRIMARY KEY,
+    slot        BIGINT NOT NULL,
+    block_time  BIGINT,
+    -- Confirmed transactions can still be rolled back until finalized
+    finalized   BOOLEAN NOT NULL DEFAULT FALSE,
+    indexed_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS transactions_unfinalized ON transactions (slot) WHERE NOT finalized;
+
+CREATE TABLE IF NOT EXISTS sessions (
+    vault          TEXT PRIMARY KEY,
+    session_id     BYTEA NOT NULL,
+    "user"         TEXT NOT NULL,
+    bot            TEXT NOT NULL,
+    duration_days  INTEGER NOT NULL,
+    signature      TEXT NOT NULL REFERENCES transactions ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS sessions_user ON sessions ("user");
+CREATE INDEX IF NOT EXISTS sessions_session_id ON sessions (session_id);
+
+CREATE TABLE IF NOT EXISTS deposits (
+    signature        TEXT NOT NULL REFERENCES transactions ON DELETE CASCADE,
+    event_index      INTEGER NOT NULL,
+    vault            TEXT NOT NULL,
+    amount           NUMERIC(20) NOT NULL,
+    fee              NUMERIC(20) NOT NULL,
+    trading_balance  NUMERIC(20) NOT NULL,
+    expires_at       BIGINT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS deposits_vault ON deposits (vault);
+
+CREATE TABLE IF NOT EXISTS swaps (
+    signature           TEXT NOT NULL REFERENCES transactions ON DELETE CASCADE,
+    event_index         INTEGER NOT NULL,
+    vault               TEXT NOT NULL,
+    bot                 TEXT NOT NULL,
+    dex_program         TEXT NOT NULL,
+    amount_in           NUMERIC(20) NOT NULL,
+    minimum_amount_out  NUMERIC(20) NOT NULL,
+    output_mint         TEXT NOT NULL,
+    amount_out          NUMERIC(20) NOT NULL,
+    memo                BYTEA NOT NULL,
+    "timestamp"         BIGINT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS swaps_vault ON swaps (vault);
+
+-- kind: 'deposit' (protocol fee on a deposit) or 'compute' (daily compute
+-- fee, whether deducted by a crank or settled on withdrawal or transfer)
+CREATE TABLE IF NOT EXISTS fees (
+    signature    TEXT NOT NULL REFERENCES transactions ON DELETE CASCADE,
+    event_index  INTEGER NOT NULL,
+    vault        TEXT NOT NULL,
+    kind         TEXT NOT NULL,
+    amount       NUMERIC(20) NOT NULL,
+    PRIMARY KEY (signature, event_index, kind)
+);
+CREATE INDEX IF NOT EXISTS fees_vault ON fees (vault);
+
+-- Withdrawals to the user, and transfers to another session
+-- (destination_vault set)
+CREATE TABLE IF NOT EXISTS withdrawals (
+    signature          TEXT NOT NULL REFERENCES transactions ON DELETE CASCADE,
+    event_index        INTEGER NOT NULL,
+    vault              TEXT NOT NULL,
+    "user"             TEXT NOT NULL,
+    amount             NUMERIC(20) NOT NULL,
+    destination_vault  TEXT,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS withdrawals_vault ON withdrawals (vault);
+
+CREATE TABLE IF NOT EXISTS indexer_state (
+    key    TEXT PRIMARY KEY,
+    value  TEXT NOT NULL
+);
+
+-- Notification sinks (see notify.rs). Each watches one session or every
+-- session of one wallet; `events` limits it to some kinds (NULL for all).
+-- `target` is the URL, or the bot token for Telegram. `secret` signs HTTP
+-- deliveries and authorizes deleting the webhook.
+CREATE TABLE IF NOT EXISTS webhooks (
+    id                    BIGSERIAL PRIMARY KEY,
+    vault                 TEXT,
+    wallet                TEXT,
+    sink                  TEXT NOT NULL,
+    target                TEXT NOT NULL,
+    chat_id               TEXT,
+    events                TEXT[],
+    secret                TEXT NOT NULL,
+    consecutive_failures  INTEGER NOT NULL DEFAULT 0,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((vault IS NULL) <> (wallet IS NULL))
+);
+CREATE INDEX IF NOT EXISTS webhooks_vault ON webhooks (vault);
+CREATE INDEX IF NOT EXISTS webhooks_wallet ON webhooks (wallet);
@@ -0,0 +1,360 @@
+//! REST API over the indexed tables, so front-ends don't have to go to RPC:
+//!
+//! - `GET /sessions/{vault}`: one session and its totals, by vault address
+//! - `GET /users/{pubkey}/sessions`: a user's sessions, newest first
+//! - `GET /sessions/{vault}/trades?limit=&offset=`: a session's swaps, newest first
+//! - `GET /stats`: protocol-wide totals
+//!
+//! and to register notifications (see [`notify`](crate::notify)):
+//!
+//! - `POST /webhooks`: `{"vault" | "wallet", "sink", "url" | "bot_token",
+//!   "chat_id"?, "events"?}`; returns `{"id", "secret"}`
+//! - `DELETE /webhooks/{id}` with `Authorization: Bearer <secret>`
+//!
+//! Everything on chain is public, so registering needs no authorization.
+//!
+//! Amounts are decimal strings of raw units, since u64 doesn't survive a
+//! JavaScript number.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anchor_lang::prelude::Pubkey;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::{Client, NoTls, Row};
+use tracing::error;
+
+use crate::notify::{self, Kind, Sink};
+
+const DEFAULT_PAGE: i64 = 100;
+const MAX_PAGE: i64 = 1_000;
+
+/// A session with its money movements summed.
+const SESSION_SUMMARY: &str = "
+    SELECT s.vault, encode(s.session_id, 'hex'), s.\"user\", s.bot, s.duration_days, s.signature, t.slot,
+        t.block_time,
+        (SELECT COALESCE(SUM(amount), 0)::TEXT FROM deposits d WHERE d.vault = s.vault),
+        (SELECT COALESCE(SUM(amount), 0)::TEXT FROM withdrawals w WHERE w.vault = s.vault),
+        (SELECT COALESCE(SUM(amount), 0)::TEXT FROM fees f WHERE f.vault = s.vault),
+        (SELECT COUNT(*) FROM swaps w WHERE w.vault = s.vault),
+        (SELECT COALESCE(SUM(amount_in), 0)::TEXT FROM swaps w WHERE w.vault = s.vault)
+    FROM sessions s JOIN transactions t USING (signature)";
+
+#[derive(Debug, thiserror::Error)]
+enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("not found")]
+    NotFound,
+    #[error("database: {0}")]
+    Database(#[from] tokio_postgres::Error),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Database(err) => {
+                error!(%err, "api query failed");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        let message = match self {
+            ApiError::Database(_) => "internal error".to_string(),
+            err => err.to_string(),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct Session {
+    vault: String,
+    session_id: String,
+    user: String,
+    bot: String,
+    duration_days: i32,
+    created_signature: String,
+    created_slot: i64,
+    created_at: Option<i64>,
+    deposited: String,
+    withdrawn: String,
+    fees: String,
+    swaps: i64,
+    volume: String,
+}
+
+impl From<&Row> for Session {
+    fn from(row: &Row) -> Self {
+        Self {
+            vault: row.get(0),
+            session_id: row.get(1),
+            user: row.get(2),
+            bot: row.get(3),
+            duration_days: row.get(4),
+            created_signature: row.get(5),
+            created_slot: row.get(6),
+            created_at: row.get(7),
+            deposited: row.get(8),
+            withdrawn: row.get(9),
+            fees: row.get(10),
+            swaps: row.get(11),
+            volume: row.get(12),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Trade {
+    signature: String,
+    slot: i64,
+    block_time: Option<i64>,
+    finalized: bool,
+    bot: String,
+    dex_program: String,
+    amount_in: String,
+    minimum_amount_out: String,
+    output_mint: String,
+    amount_out: String,
+    timestamp: i64,
+}
+
+#[derive(Serialize)]
+struct Stats {
+    sessions: i64,
+    users: i64,
+    deposited: String,
+    withdrawn: String,
+    swaps: i64,
+    volume: String,
+    deposit_fees: String,
+    compute_fees: String,
+}
+
+#[derive(Deserialize)]
+struct NewWebhook {
+    vault: Option<String>,
+    wallet: Option<String>,
+    sink: Sink,
+    /// For `http` and `discord` sinks
+    url: Option<String>,
+    /// For `telegram` sinks
+    bot_token: Option<String>,
+    chat_id: Option<String>,
+    /// Kinds to deliver; all if absent
+    events: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct CreatedWebhook {
+    id: i64,
+    secret: String,
+}
+
+#[derive(Deserialize)]
+struct Page {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+/// A vault address in the base58 form the tables store.
+fn vault(address: &str) -> Result<String, ApiError> {
+    address
+        .parse::<Pubkey>()
+        .map(|vault| vault.to_string())
+        .map_err(|_| ApiError::BadRequest(format!("invalid vault {address:?}")))
+}
+
+async fn get_session(State(db): State<Arc<Client>>, Path(address): Path<String>) -> Result<Json<Session>, ApiError> {
+    let vault = vault(&address)?;
+    let row = db
+        .query_opt(&format!("{SESSION_SUMMARY} WHERE s.vault = $1"), &[&vault])
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(Session::from(&row)))
+}
+
+async fn user_sessions(State(db): State<Arc<Client>>, Path(user): Path<String>) -> Result<Json<Vec<Session>>, ApiError> {
+    let user: Pubkey = user
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("invalid pubkey {user:?}")))?;
+    let rows = db
+        .query(
+            &format!("{SESSION_SUMMARY} WHERE s.\"user\" = $1 ORDER BY t.slot DESC"),
+            &[&user.to_string()],
+        )
+        .await?;
+    Ok(Json(rows.iter().map(Session::from).collect()))
+}
+
+async fn session_trades(
+    State(db): State<Arc<Client>>,
+    Path(address): Path<String>,
+    Query(page): Query<Page>,
+) -> Result<Json<Vec<Trade>>, ApiError> {
+    let vault = vault(&address)?;
+    let limit = page.limit.unwrap_or(DEFAULT_PAGE).clamp(1, MAX_PAGE);
+    let offset = page.offset.unwrap_or(0).max(0);
+    let rows = db
+        .query(
+            "SELECT t.signature, t.slot, t.block_time, t.finalized, w.bot, w.dex_program, w.amount_in::TEXT,
+                 w.minimum_amount_out::TEXT, w.output_mint, w.amount_out::TEXT, w.\"timestamp\"
+             FROM swaps w JOIN transactions t USING (signature)
+             WHERE w.vault = $1
+             ORDER BY t.slot DESC, w.event_index DESC
+             LIMIT $2 OFFSET $3",
+            &[&vault, &limit, &offset],
+        )
+        .await?;
+    let trades = rows
+        .iter()
+        .map(|row| Trade {
+            signature: row.get(0),
+            slot: row.get(1),
+            block_time: row.get(2),
+            finalized: row.get(3),
+            bot: row.get(4),
+            dex_program: row.get(5),
+            amount_in: row.get(6),
+            minimum_amount_out: row.get(7),
+            output_mint: row.get(8),
+            amount_out: row.get(9),
+            timestamp: row.get(10),
+        })
+        .collect();
+    Ok(Json(trades))
+}
+
+async fn create_webhook(
+    State(db): State<Arc<Client>>,
+    Json(webhook): Json<NewWebhook>,
+) -> Result<(StatusCode, Json<CreatedWebhook>), ApiError> {
+    let session = webhook.vault.as_deref().map(vault).transpose()?;
+    let wallet = match webhook.wallet.as_deref() {
+        Some(wallet) => Some(
+            wallet
+                .parse::<Pubkey>()
+                .map_err(|_| ApiError::BadRequest(format!("invalid wallet {wallet:?}")))?
+                .to_string(),
+        ),
+        None => None,
+    };
+    if session.is_some() == wallet.is_some() {
+        return Err(ApiError::BadRequest("give exactly one of vault and wallet".to_string()));
+    }
+    let target = match webhook.sink {
+        Sink::Telegram => webhook.bot_token,
+        Sink::Http | Sink::Discord => webhook.url,
+    }
+    .unwrap_or_default();
+    webhook
+        .sink
+        .validate(&target, webhook.chat_id.as_deref())
+        .map_err(ApiError::BadRequest)?;
+    if let Some(unknown) = webhook
+        .events
+        .iter()
+        .flatten()
+        .find(|name| !Kind::ALL.iter().any(|kind| kind.name() == name.as_str()))
+    {
+        return Err(ApiError::BadRequest(format!("unknown event {unknown:?}")));
+    }
+
+    let secret = notify::new_secret();
+    let row = db
+        .query_one(
+            "INSERT INTO webhooks (vault, wallet, sink, target, chat_id, events, secret)
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+            &[&session, &wallet, &webhook.sink.name(), &target, &webhook.chat_id, &webhook.events, &secret],
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(CreatedWebhook { id: row.get(0), secret })))
+}
+
+async fn delete_webhook(
+    State(db): State<Arc<Client>>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let secret = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok()?.strip_prefix("Bearer "))
+        .ok_or_else(|| ApiError::BadRequest("missing Authorization: Bearer <secret>".to_string()))?;
+    // A wrong secret looks the same as a missing webhook
+    let deleted = db
+        .execute("DELETE FROM webhooks WHERE id = $1 AND secret = $2", &[&id, &secret])
+        .await?;
+    if deleted == 0 {
+        return Err(ApiError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn stats(State(db): State<Arc<Client>>) -> Result<Json<Stats>, ApiError> {
+    let row = db
+        .query_one(
+            "SELECT
+                 (SELECT COUNT(*) FROM sessions),
+                 (SELECT COUNT(DISTINCT \"user\") FROM sessions),
+                 (SELECT COALESCE(SUM(amount), 0)::TEXT FROM deposits),
+                 (SELECT COALESCE(SUM(amount), 0)::TEXT FROM withdrawals WHERE destination_vault IS NULL),
+                 (SELECT COUNT(*) FROM swaps),
+                 (SELECT COALESCE(SUM(amount_in), 0)::TEXT FROM swaps),
+                 (SELECT COALESCE(SUM(amount), 0)::TEXT FROM fees WHERE kind = 'deposit'),
+                 (SELECT COALESCE(SUM(amount), 0)::TEXT FROM fees WHERE kind = 'compute')",
+            &[],
+        )
+        .await?;
+    Ok(Json(Stats {
+        sessions: row.get(0),
+        users: row.get(1),
+        deposited: row.get(2),
+        withdrawn: row.get(3),
+        swaps: row.get(4),
+        volume: row.get(5),
+        deposit_fees: row.get(6),
+        compute_fees: row.get(7),
+    }))
+}
+
+/// Serve the API on `addr` from its own connection to `database_url`.
+pub async fn serve(addr: SocketAddr, database_url: &str) -> std::io::Result<()> {
+    let (client, connection) = tokio_postgres::connect(database_url, NoTls)
+        .await
+        .map_err(std::io::Error::other)?;
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            error!(%err, "api database connection closed");
+        }
+    });
+
+    let app = Router::new()
+        .route("/sessions/{vault}", get(get_session))
+        .route("/sessions/{vault}/trades", get(session_trades))
+        .route("/users/{pubkey}/sessions", get(user_sessions))
+        .route("/stats", get(stats))
+        .route("/webhooks", post(create_webhook))
+        .route("/webhooks/{id}", delete(delete_webhook))
+        .with_state(Arc::new(client));
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_vault_addresses() {
+        let address = Pubkey::new_unique().to_string();
+        assert_eq!(vault(&address).unwrap(), address);
+        assert!(matches!(vault("00"), Err(ApiError::BadRequest(_))));
+        assert!(matches!(vault("000102030405060708090a0b0c0d0eff"), Err(ApiError::BadRequest(_))));
+    }
+}
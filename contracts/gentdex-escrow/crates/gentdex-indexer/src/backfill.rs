@@ -0,0 +1,157 @@
+//! `gentdex-backfill`: GentDex history from before the indexer was running.
+//!
+//! Walks the program's signature history at `finalized` commitment, newest
+//! first, back to the start of the date range; fetches each successful
+//! transaction in the range, decodes its events and writes them, oldest
+//! first, in the indexer's ingestion format (see [`ingest`]). Load the output
+//! with `gentdex-indexer --import`.
+
+mod ingest;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::NaiveDate;
+use clap::Parser;
+use gentdex_client::events::parse_logs;
+use gentdex_client::rpc::{GentdexRpc, SignatureInfo, SIGNATURE_PAGE};
+use gentdex_client::{ClientError, PROGRAM_ID};
+use tracing::{error, info, warn};
+
+use crate::ingest::Record;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Parser)]
+#[command(name = "gentdex-backfill", version, about = "Export historic GentDex events for the indexer")]
+struct Args {
+    /// JSON-RPC endpoints, comma-separated; each must keep the range's transactions, e.g. an archive node
+    #[arg(
+        long,
+        short = 'u',
+        env = "GENTDEX_RPC_URL",
+        value_delimiter = ',',
+        default_value = "https://api.mainnet-beta.solana.com"
+    )]
+    url: Vec<String>,
+    /// First day to export, as YYYY-MM-DD (UTC) or a unix timestamp
+    #[arg(long, value_parser = parse_time)]
+    from: i64,
+    /// Last day to export, as YYYY-MM-DD (UTC, inclusive) or a unix timestamp (exclusive) [default: now]
+    #[arg(long, value_parser = parse_end)]
+    to: Option<i64>,
+    /// Write here instead of stdout
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+}
+
+/// Seconds since the epoch, from a UTC date or a unix timestamp.
+fn parse_time(value: &str) -> Result<i64, String> {
+    if let Ok(timestamp) = value.parse() {
+        return Ok(timestamp);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).expect("midnight").and_utc().timestamp())
+        .map_err(|_| format!("{value:?}: expected YYYY-MM-DD or a unix timestamp"))
+}
+
+/// Exclusive end of a range: the day after a date, or a timestamp as is.
+fn parse_end(value: &str) -> Result<i64, String> {
+    let time = parse_time(value)?;
+    Ok(if value.contains('-') { time + SECONDS_PER_DAY } else { time })
+}
+
+/// Successful transactions with `from <= block_time < to`, oldest first.
+async fn signatures(rpc: &GentdexRpc, from: i64, to: i64) -> Result<Vec<SignatureInfo>, ClientError> {
+    let mut in_range = Vec::new();
+    let mut before: Option<String> = None;
+    'pages: loop {
+        let page = rpc.signatures_for_address(&PROGRAM_ID, before.as_deref()).await?;
+        let done = page.len() < SIGNATURE_PAGE;
+        before = page.last().map(|info| info.signature.clone());
+        for info in page {
+            match info.block_time {
+                Some(time) if time < from => break 'pages,
+                Some(time) if time >= to => {}
+                Some(_) if info.err.is_none() => in_range.push(info),
+                Some(_) => {}
+                None => warn!(signature = info.signature, "no block time; skipped"),
+            }
+        }
+        info!(found = in_range.len(), "walking signatures");
+        if done {
+            break;
+        }
+    }
+    in_range.reverse();
+    Ok(in_range)
+}
+
+async fn run(args: Args) -> Result<usize, String> {
+    let to = args.to.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    if args.from >= to {
+        return Err("--from must be before --to".to_string());
+    }
+    let rpc = GentdexRpc::pooled(args.url).with_commitment("finalized");
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|err| format!("{}: {err}", path.display()))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let pending = signatures(&rpc, args.from, to).await.map_err(|err| err.to_string())?;
+    let mut exported = 0;
+    for info in pending {
+        let Some(tx) = rpc.get_transaction(&info.signature).await.map_err(|err| err.to_string())? else {
+            warn!(signature = info.signature, "transaction no longer available; skipped");
+            continue;
+        };
+        let events = parse_logs(&tx.logs);
+        if events.is_empty() {
+            continue;
+        }
+        let record = Record::new(info.signature, tx.slot, tx.block_time, &events);
+        let line = serde_json::to_string(&record).expect("records serialize");
+        writeln!(out, "{line}").map_err(|err| format!("writing output: {err}"))?;
+        exported += 1;
+    }
+    out.flush().map_err(|err| format!("writing output: {err}"))?;
+    Ok(exported)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Logs go to stderr, leaving stdout for records
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    match run(Args::parse()).await {
+        Ok(exported) => {
+            info!(exported, "backfill complete");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            error!(%err, "backfill failed");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dates_as_whole_utc_days() {
+        assert_eq!(parse_time("2024-03-01"), Ok(1_709_251_200));
+        assert_eq!(parse_time("1709251200"), Ok(1_709_251_200));
+        assert_eq!(parse_end("2024-03-01"), Ok(1_709_251_200 + SECONDS_PER_DAY));
+        assert_eq!(parse_end("1709251200"), Ok(1_709_251_200));
+        assert!(parse_time("March 1st").is_err());
+    }
+}
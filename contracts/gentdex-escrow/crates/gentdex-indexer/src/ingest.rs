@@ -0,0 +1,74 @@
+//! The ingestion format: one JSON object per line per transaction, oldest
+//! first, with each event as the base64 data `emit!` logged for it.
+//! `gentdex-backfill` writes it and `gentdex-indexer --import` loads it.
+//!
+//! Shared by both binaries, each of which uses only one direction.
+#![allow(dead_code)]
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use gentdex_client::events::Event;
+use serde::{Deserialize, Serialize};
+
+/// A finalized, successful program transaction and its events.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub events: Vec<String>,
+}
+
+impl Record {
+    pub fn new(signature: String, slot: u64, block_time: Option<i64>, events: &[Event]) -> Self {
+        Self {
+            signature,
+            slot,
+            block_time,
+            events: events.iter().map(|event| BASE64.encode(event.encode())).collect(),
+        }
+    }
+
+    /// Decode the record's events, failing on any that aren't GentDex events.
+    pub fn events(&self) -> Result<Vec<Event>, String> {
+        self.events
+            .iter()
+            .map(|data| {
+                BASE64
+                    .decode(data)
+                    .ok()
+                    .and_then(|bytes| Event::decode(&bytes))
+                    .ok_or_else(|| format!("{}: undecodable event {data:?}", self.signature))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use gentdex_client::program::SessionPaused;
+
+    use super::*;
+
+    #[test]
+    fn round_trips_through_a_line() {
+        let events = [Event::SessionPaused(SessionPaused {
+            session_id: [7; 16],
+            vault: Pubkey::new_unique(),
+        })];
+        let record = Record::new("sig".to_string(), 42, Some(1_700_000_000), &events);
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(!line.contains('\n'));
+
+        let parsed: Record = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed, record);
+        match &parsed.events().unwrap()[..] {
+            [Event::SessionPaused(event)] => assert_eq!(event.session_id, [7; 16]),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let bad = Record { events: vec!["AAAA".to_string()], ..parsed };
+        assert!(bad.events().is_err());
+    }
+}
@@ -0,0 +1,411 @@
+//! Notifications to registered webhooks.
+//!
+//! A webhook (registered through the API, see [`api`](crate::api)) watches
+//! one session or every session of one wallet, optionally only some
+//! [`Kind`]s, and delivers to one of three sinks:
+//!
+//! - `http`: POSTs the notification as JSON, signed with the webhook's secret
+//!   in `X-GentDex-Signature: sha256=<hex HMAC-SHA256 of the body>`
+//! - `discord`: a Discord webhook URL, sent the notification's text
+//! - `telegram`: a bot token and chat id, sent the text via `sendMessage`
+//!
+//! Notifications go out as transactions are first indexed, live at
+//! `confirmed` or from backfill, so a fork can very occasionally drop a
+//! transaction something was already notified of. Deliveries are retried
+//! with backoff; a webhook failing [`DISABLE_AFTER_FAILURES`] deliveries in a
+//! row stops receiving until it's registered again.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use gentdex_client::events::Event;
+use gentdex_client::rpc::RetryPolicy;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_postgres::{Client, NoTls};
+use tracing::{error, warn};
+
+/// Consecutive failed deliveries after which a webhook is skipped
+pub const DISABLE_AFTER_FAILURES: i32 = 50;
+const DELIVERY_ATTEMPTS: u32 = 3;
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What happened, as webhooks filter on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Deposit,
+    Swap,
+    /// The session's slippage budget ran out and it stopped trading
+    StopLoss,
+    /// The compute fee crank found only a few days of fees left
+    LowBalance,
+    /// The compute fee crank found the session ending soon
+    ExpiryApproaching,
+    Expired,
+    /// The admin moved the session's fees to a new treasury
+    TreasuryMigrated,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 7] = [
+        Kind::Deposit,
+        Kind::Swap,
+        Kind::StopLoss,
+        Kind::LowBalance,
+        Kind::ExpiryApproaching,
+        Kind::Expired,
+        Kind::TreasuryMigrated,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Deposit => "deposit",
+            Kind::Swap => "swap",
+            Kind::StopLoss => "stop_loss",
+            Kind::LowBalance => "low_balance",
+            Kind::ExpiryApproaching => "expiry_approaching",
+            Kind::Expired => "expired",
+            Kind::TreasuryMigrated => "treasury_migrated",
+        }
+    }
+}
+
+/// Where a webhook delivers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sink {
+    Http,
+    Discord,
+    Telegram,
+}
+
+impl Sink {
+    pub fn name(self) -> &'static str {
+        match self {
+            Sink::Http => "http",
+            Sink::Discord => "discord",
+            Sink::Telegram => "telegram",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Sink::Http, Sink::Discord, Sink::Telegram].into_iter().find(|sink| sink.name() == name)
+    }
+
+    /// Check a sink's target: an HTTPS URL, a Discord webhook URL, or a
+    /// Telegram bot token. Targets are fetched by the indexer, so plain HTTP
+    /// isn't accepted.
+    pub fn validate(self, target: &str, chat_id: Option<&str>) -> Result<(), String> {
+        match self {
+            Sink::Http if target.starts_with("https://") => Ok(()),
+            Sink::Http => Err("http sinks need an https:// url".to_string()),
+            Sink::Discord if target.starts_with("https://discord.com/api/webhooks/") => Ok(()),
+            Sink::Discord => Err("discord sinks need a https://discord.com/api/webhooks/ url".to_string()),
+            Sink::Telegram if chat_id.is_none() => Err("telegram sinks need a chat_id".to_string()),
+            Sink::Telegram if target.contains(':') && !target.contains('/') => Ok(()),
+            Sink::Telegram => Err("telegram sinks need a bot token".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Notification {
+    pub kind: Kind,
+    #[serde(serialize_with = "hex")]
+    pub session_id: [u8; 16],
+    /// The session's vault address, base58
+    pub vault: String,
+    pub signature: String,
+    pub slot: u64,
+    /// One line for chat sinks
+    pub text: String,
+    /// The event's amounts, as decimal strings of lamports
+    pub details: Value,
+}
+
+fn hex<S: serde::Serializer>(bytes: &[u8; 16], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&to_hex(bytes))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Lamports as SOL, trailing zeros trimmed.
+fn sol(lamports: u64) -> String {
+    let fraction = format!("{:09}", lamports % 1_000_000_000);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{} SOL", lamports / 1_000_000_000)
+    } else {
+        format!("{}.{fraction} SOL", lamports / 1_000_000_000)
+    }
+}
+
+/// The notifications one transaction's events produce.
+pub fn notifications(signature: &str, slot: u64, events: &[Event]) -> Vec<Notification> {
+    let mut notifications = Vec::new();
+    for event in events {
+        let (kind, session_id, vault, text, details) = match event {
+            Event::Deposited(e) => (
+                Kind::Deposit,
+                e.session_id,
+                e.vault,
+                format!("Deposited {} ({} fee)", sol(e.amount), sol(e.fee)),
+                json!({ "amount": e.amount.to_string(), "fee": e.fee.to_string(), "expires_at": e.expires_at }),
+            ),
+            Event::SwapExecuted(e) => (
+                Kind::Swap,
+                e.session_id,
+                e.vault,
+                format!("Swapped {} for {} of {}", sol(e.amount_in), e.amount_out, e.output_mint),
+                json!({
+                    "amount_in": e.amount_in.to_string(),
+                    "output_mint": e.output_mint.to_string(),
+                    "amount_out": e.amount_out.to_string(),
+                    "dex_program": e.dex_program.to_string(),
+                }),
+            ),
+            Event::SlippageBudgetExhausted(e) => (
+                Kind::StopLoss,
+                e.session_id,
+                e.vault,
+                format!("Stop-loss hit: {} slippage against a {} budget", sol(e.slippage_consumed), sol(e.slippage_budget)),
+                json!({ "slippage_consumed": e.slippage_consumed.to_string(), "slippage_budget": e.slippage_budget.to_string() }),
+            ),
+            Event::LowBalanceWarning(e) => (
+                Kind::LowBalance,
+                e.session_id,
+                e.vault,
+                format!("Low balance: {} left, {} days of compute fees", sol(e.balance), e.days_remaining),
+                json!({ "balance": e.balance.to_string(), "days_remaining": e.days_remaining }),
+            ),
+            Event::ExpiryApproaching(e) => (
+                Kind::ExpiryApproaching,
+                e.session_id,
+                e.vault,
+                format!("Expires in {} hours", e.seconds_remaining / 3_600),
+                json!({ "expires_at": e.expires_at, "seconds_remaining": e.seconds_remaining }),
+            ),
+            Event::SessionExpiredEvent(e) => (
+                Kind::Expired,
+                e.session_id,
+                e.vault,
+                format!("Session expired with {} left to withdraw", sol(e.remaining_balance)),
+                json!({ "remaining_balance": e.remaining_balance.to_string() }),
+            ),
+            Event::TreasuryMigrated(e) => (
+                Kind::TreasuryMigrated,
+                e.session_id,
+                e.vault,
+                format!("Fees now go to the protocol's new treasury {} (was {})", e.treasury, e.previous),
+                json!({ "previous": e.previous.to_string(), "treasury": e.treasury.to_string() }),
+            ),
+            _ => continue,
+        };
+        notifications.push(Notification {
+            kind,
+            session_id,
+            vault: vault.to_string(),
+            signature: signature.to_string(),
+            slot,
+            text: format!("GentDex session {}: {text}", &to_hex(&session_id)[..8]),
+            details,
+        });
+    }
+    notifications
+}
+
+struct Webhook {
+    id: i64,
+    sink: Sink,
+    target: String,
+    chat_id: Option<String>,
+    secret: String,
+}
+
+/// Deliver notifications as they arrive on `notifications`, looking up
+/// webhooks over its own connection to `database_url`.
+pub async fn dispatch(database_url: String, mut notifications: UnboundedReceiver<Vec<Notification>>) {
+    let db = match tokio_postgres::connect(&database_url, NoTls).await {
+        Ok((client, connection)) => {
+            tokio::spawn(async move {
+                if let Err(err) = connection.await {
+                    error!(%err, "notifier database connection closed");
+                }
+            });
+            Arc::new(client)
+        }
+        Err(err) => {
+            error!(%err, "notifier couldn't connect; webhooks disabled");
+            return;
+        }
+    };
+    let http = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .expect("default TLS backend");
+
+    while let Some(batch) = notifications.recv().await {
+        for notification in batch {
+            let webhooks = match webhooks(&db, &notification).await {
+                Ok(webhooks) => webhooks,
+                Err(err) => {
+                    warn!(%err, "webhook lookup failed; notification dropped");
+                    continue;
+                }
+            };
+            let notification = Arc::new(notification);
+            for webhook in webhooks {
+                let (db, http, notification) = (db.clone(), http.clone(), notification.clone());
+                tokio::spawn(async move { deliver_with_retry(&db, &http, &webhook, &notification).await });
+            }
+        }
+    }
+}
+
+/// Enabled webhooks watching the notification's session, directly or
+/// through its owner's wallet, for its kind.
+async fn webhooks(db: &Client, notification: &Notification) -> Result<Vec<Webhook>, tokio_postgres::Error> {
+    let rows = db
+        .query(
+            "SELECT id, sink, target, chat_id, secret FROM webhooks
+             WHERE (vault = $1 OR wallet IN (SELECT \"user\" FROM sessions WHERE vault = $1))
+                 AND (events IS NULL OR $2 = ANY(events))
+                 AND consecutive_failures < $3",
+            &[&notification.vault, &notification.kind.name(), &DISABLE_AFTER_FAILURES],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            Some(Webhook {
+                id: row.get(0),
+                sink: Sink::from_name(row.get(1))?,
+                target: row.get(2),
+                chat_id: row.get(3),
+                secret: row.get(4),
+            })
+        })
+        .collect())
+}
+
+async fn deliver_with_retry(db: &Client, http: &reqwest::Client, webhook: &Webhook, notification: &Notification) {
+    let retry = RetryPolicy::default();
+    let mut attempt = 0;
+    let delivered = loop {
+        attempt += 1;
+        match deliver(http, webhook, notification).await {
+            Ok(()) => break true,
+            Err(err) if attempt >= DELIVERY_ATTEMPTS => {
+                warn!(webhook = webhook.id, %err, "webhook delivery failed");
+                break false;
+            }
+            Err(_) => tokio::time::sleep(retry.backoff(attempt)).await,
+        }
+    };
+    let update = if delivered {
+        "UPDATE webhooks SET consecutive_failures = 0 WHERE id = $1"
+    } else {
+        "UPDATE webhooks SET consecutive_failures = consecutive_failures + 1 WHERE id = $1"
+    };
+    if let Err(err) = db.execute(update, &[&webhook.id]).await {
+        warn!(webhook = webhook.id, %err, "recording webhook delivery failed");
+    }
+}
+
+async fn deliver(http: &reqwest::Client, webhook: &Webhook, notification: &Notification) -> Result<(), reqwest::Error> {
+    let request = match webhook.sink {
+        Sink::Http => {
+            let body = serde_json::to_vec(notification).expect("notifications serialize");
+            http.post(&webhook.target)
+                .header("Content-Type", "application/json")
+                .header("X-GentDex-Signature", format!("sha256={}", sign(&webhook.secret, &body)))
+                .body(body)
+        }
+        Sink::Discord => http.post(&webhook.target).json(&json!({ "content": notification.text })),
+        Sink::Telegram => http
+            .post(format!("https://api.telegram.org/bot{}/sendMessage", webhook.target))
+            .json(&json!({ "chat_id": webhook.chat_id, "text": notification.text })),
+    };
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// Hex HMAC-SHA256 of `body` under `secret`, for receivers to check.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(body);
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// A new webhook's secret: 32 random bytes as hex.
+pub fn new_secret() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect("system randomness");
+    to_hex(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use gentdex_client::program::{ComputeFeeDeducted, Deposited, LowBalanceWarning, SessionPaused};
+
+    use super::*;
+
+    #[test]
+    fn notifies_on_watched_events_only() {
+        let session_id = [0xab; 16];
+        let vault = Pubkey::new_unique();
+        let events = [
+            Event::Deposited(Deposited {
+                session_id,
+                vault,
+                amount: 1_500_000_000,
+                fee: 37_500_000,
+                trading_balance: 1_462_500_000,
+                expires_at: 0,
+            }),
+            Event::SessionPaused(SessionPaused { session_id, vault }),
+            Event::ComputeFeeDeducted(ComputeFeeDeducted {
+                session_id,
+                vault,
+                fee: 10_000_000,
+                remaining_balance: 10_000_000,
+            }),
+            Event::LowBalanceWarning(LowBalanceWarning {
+                session_id,
+                vault,
+                balance: 10_000_000,
+                daily_compute_fee: 10_000_000,
+                days_remaining: 1,
+            }),
+        ];
+
+        let notifications = notifications("sig", 7, &events);
+        let kinds: Vec<Kind> = notifications.iter().map(|n| n.kind).collect();
+        assert_eq!(kinds, [Kind::Deposit, Kind::LowBalance]);
+        assert_eq!(notifications[0].text, "GentDex session abababab: Deposited 1.5 SOL (0.0375 SOL fee)");
+
+        let body = serde_json::to_value(&notifications[1]).unwrap();
+        assert_eq!(body["kind"], "low_balance");
+        assert_eq!(body["session_id"], "abababababababababababababababab");
+        assert_eq!(body["vault"], vault.to_string());
+        assert_eq!(body["details"]["balance"], "10000000");
+    }
+
+    #[test]
+    fn validates_sink_targets() {
+        assert!(Sink::Http.validate("https://example.com/hook", None).is_ok());
+        assert!(Sink::Http.validate("http://10.0.0.1/hook", None).is_err());
+        assert!(Sink::Discord.validate("https://example.com/api/webhooks/1", None).is_err());
+        assert!(Sink::Telegram.validate("123:abc", None).is_err());
+        assert!(Sink::Telegram.validate("123:abc", Some("42")).is_ok());
+        assert_eq!(sign("key", b"body").len(), 64);
+        assert_ne!(new_secret(), new_secret());
+    }
+}
@@ -0,0 +1,191 @@
+//! Events normalized into table rows. Events that don't move money or open a
+//! session (pauses, admin changes, perps and lending) aren't indexed yet.
+
+use anchor_lang::prelude::Pubkey;
+use gentdex_client::events::{unbatch, Event};
+use gentdex_client::program::SwapExecuted;
+
+#[derive(Debug, PartialEq)]
+pub enum Row {
+    Session {
+        vault: Pubkey,
+        session_id: [u8; 16],
+        user: Pubkey,
+        bot: Pubkey,
+        duration_days: u16,
+    },
+    Deposit {
+        vault: Pubkey,
+        amount: u64,
+        fee: u64,
+        trading_balance: u64,
+        expires_at: i64,
+    },
+    Swap {
+        vault: Pubkey,
+        bot: Pubkey,
+        dex_program: Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        output_mint: Pubkey,
+        amount_out: u64,
+        memo: [u8; 32],
+        timestamp: i64,
+    },
+    Fee {
+        vault: Pubkey,
+        kind: FeeKind,
+        amount: u64,
+    },
+    Withdrawal {
+        vault: Pubkey,
+        user: Pubkey,
+        amount: u64,
+        destination_vault: Option<Pubkey>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeKind {
+    Deposit,
+    Compute,
+}
+
+impl FeeKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FeeKind::Deposit => "deposit",
+            FeeKind::Compute => "compute",
+        }
+    }
+}
+
+/// The rows for one transaction's events, each with the index of the event
+/// it came from. One event can yield several rows, e.g. a withdrawal and the
+/// compute fee settled with it.
+pub fn rows(events: &[Event]) -> Vec<(usize, Row)> {
+    let mut rows = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        let mut push = |row| rows.push((index, row));
+        match event {
+            Event::SessionCreated(e) => push(Row::Session {
+                vault: e.vault,
+                session_id: e.session_id,
+                user: e.user,
+                bot: e.bot,
+                duration_days: e.duration_days,
+            }),
+            Event::Deposited(e) => {
+                push(Row::Deposit {
+                    vault: e.vault,
+                    amount: e.amount,
+                    fee: e.fee,
+                    trading_balance: e.trading_balance,
+                    expires_at: e.expires_at,
+                });
+                if e.fee > 0 {
+                    push(fee(e.vault, FeeKind::Deposit, e.fee));
+                }
+            }
+            Event::SwapExecuted(e) => push(swap(e)),
+            // Undecodable batches yield nothing rather than failing the transaction
+            Event::TradesBatched(e) => unbatch(e).unwrap_or_default().iter().for_each(|e| push(swap(e))),
+            Event::ComputeFeeDeducted(e) => push(fee(e.vault, FeeKind::Compute, e.fee)),
+            Event::Withdrawn(e) => {
+                push(Row::Withdrawal {
+                    vault: e.vault,
+                    user: e.user,
+                    amount: e.amount,
+                    destination_vault: None,
+                });
+                if e.compute_fee > 0 {
+                    push(fee(e.vault, FeeKind::Compute, e.compute_fee));
+                }
+            }
+            Event::SessionTransferred(e) => {
+                push(Row::Withdrawal {
+                    vault: e.source_vault,
+                    user: e.user,
+                    amount: e.amount,
+                    destination_vault: Some(e.destination_vault),
+                });
+                if e.compute_fee > 0 {
+                    push(fee(e.source_vault, FeeKind::Compute, e.compute_fee));
+                }
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+fn swap(e: &SwapExecuted) -> Row {
+    Row::Swap {
+        vault: e.vault,
+        bot: e.bot,
+        dex_program: e.dex_program,
+        amount_in: e.amount_in,
+        minimum_amount_out: e.minimum_amount_out,
+        output_mint: e.output_mint,
+        amount_out: e.amount_out,
+        memo: e.memo,
+        timestamp: e.timestamp,
+    }
+}
+
+fn fee(vault: Pubkey, kind: FeeKind, amount: u64) -> Row {
+    Row::Fee {
+        vault,
+        kind,
+        amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gentdex_client::program::{Deposited, SessionPaused, Withdrawn};
+
+    use super::*;
+
+    #[test]
+    fn splits_settled_fees_into_their_own_rows() {
+        let session_id = [3; 16];
+        let vault = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let events = vec![
+            Event::SessionPaused(SessionPaused { session_id, vault }),
+            Event::Deposited(Deposited {
+                session_id,
+                vault,
+                amount: 1_000,
+                fee: 10,
+                trading_balance: 990,
+                expires_at: 0,
+            }),
+            Event::Withdrawn(Withdrawn {
+                session_id,
+                vault,
+                amount: 900,
+                compute_fee: 0,
+                user,
+            }),
+        ];
+
+        let rows = rows(&events);
+        assert_eq!(rows.len(), 3);
+        assert!(matches!(rows[0], (1, Row::Deposit { amount: 1_000, .. })));
+        assert_eq!(rows[1], (1, fee(vault, FeeKind::Deposit, 10)));
+        assert_eq!(
+            rows[2],
+            (
+                2,
+                Row::Withdrawal {
+                    vault,
+                    user,
+                    amount: 900,
+                    destination_vault: None,
+                }
+            )
+        );
+    }
+}
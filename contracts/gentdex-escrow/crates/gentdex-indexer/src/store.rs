@@ -0,0 +1,236 @@
+//! Postgres writes. Each transaction and its rows go in as one database
+//! transaction, and re-indexing a signature is a no-op, so the live stream
+//! and backfill can overlap freely.
+
+use gentdex_client::events::Event;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_postgres::{Client, NoTls};
+use tracing::error;
+
+use crate::notify::{notifications, Notification};
+use crate::rows::{rows, Row};
+
+const SCHEMA: &str = include_str!("../schema.sql");
+
+/// `indexer_state` key for the newest signature backfill has reached
+const BACKFILL_CURSOR: &str = "backfill_cursor";
+
+/// A transaction to index.
+pub struct IndexedTransaction<'a> {
+    pub signature: &'a str,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub finalized: bool,
+    pub events: &'a [Event],
+}
+
+pub struct Store {
+    client: Client,
+    notifier: Option<UnboundedSender<Vec<Notification>>>,
+}
+
+impl Store {
+    /// Connect to `database_url` and apply the schema.
+    pub async fn connect(database_url: &str) -> Result<Self, tokio_postgres::Error> {
+        let (client, connection) = tokio_postgres::connect(database_url, NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                error!(%err, "database connection closed");
+            }
+        });
+        client.batch_execute(SCHEMA).await?;
+        Ok(Self { client, notifier: None })
+    }
+
+    /// Send the notifications of every newly stored transaction to `notifier`.
+    pub fn with_notifier(mut self, notifier: UnboundedSender<Vec<Notification>>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Store `tx` and its rows. Returns whether it was new.
+    pub async fn insert(&mut self, tx: &IndexedTransaction<'_>) -> Result<bool, tokio_postgres::Error> {
+        let db = self.client.transaction().await?;
+        let inserted = db
+            .execute(
+                "INSERT INTO transactions (signature, slot, block_time, finalized) VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (signature) DO NOTHING",
+                &[&tx.signature, &(tx.slot as i64), &tx.block_time, &tx.finalized],
+            )
+            .await?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        for (index, row) in rows(tx.events) {
+            let index = index as i32;
+            match row {
+                Row::Session {
+                    vault,
+                    session_id,
+                    user,
+                    bot,
+                    duration_days,
+                } => {
+                    db.execute(
+                        "INSERT INTO sessions (vault, session_id, \"user\", bot, duration_days, signature)
+                         VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (vault) DO NOTHING",
+                        &[
+                            &vault.to_string(),
+                            &&session_id[..],
+                            &user.to_string(),
+                            &bot.to_string(),
+                            &i32::from(duration_days),
+                            &tx.signature,
+                        ],
+                    )
+                    .await?;
+                }
+                Row::Deposit {
+                    vault,
+                    amount,
+                    fee,
+                    trading_balance,
+                    expires_at,
+                } => {
+                    db.execute(
+                        "INSERT INTO deposits (signature, event_index, vault, amount, fee, trading_balance, expires_at)
+                         VALUES ($1, $2, $3, $4::TEXT::NUMERIC, $5::TEXT::NUMERIC, $6::TEXT::NUMERIC, $7)",
+                        &[
+                            &tx.signature,
+                            &index,
+                            &vault.to_string(),
+                            &amount.to_string(),
+                            &fee.to_string(),
+                            &trading_balance.to_string(),
+                            &expires_at,
+                        ],
+                    )
+                    .await?;
+                }
+                Row::Swap {
+                    vault,
+                    bot,
+                    dex_program,
+                    amount_in,
+                    minimum_amount_out,
+                    output_mint,
+                    amount_out,
+                    memo,
+                    timestamp,
+                } => {
+                    db.execute(
+                        "INSERT INTO swaps (signature, event_index, vault, bot, dex_program, amount_in,
+                             minimum_amount_out, output_mint, amount_out, memo, \"timestamp\")
+                         VALUES ($1, $2, $3, $4, $5, $6::TEXT::NUMERIC, $7::TEXT::NUMERIC, $8, $9::TEXT::NUMERIC, $10, $11)",
+                        &[
+                            &tx.signature,
+                            &index,
+                            &vault.to_string(),
+                            &bot.to_string(),
+                            &dex_program.to_string(),
+                            &amount_in.to_string(),
+                            &minimum_amount_out.to_string(),
+                            &output_mint.to_string(),
+                            &amount_out.to_string(),
+                            &&memo[..],
+                            &timestamp,
+                        ],
+                    )
+                    .await?;
+                }
+                Row::Fee {
+                    vault,
+                    kind,
+                    amount,
+                } => {
+                    db.execute(
+                        "INSERT INTO fees (signature, event_index, vault, kind, amount)
+                         VALUES ($1, $2, $3, $4, $5::TEXT::NUMERIC)",
+                        &[&tx.signature, &index, &vault.to_string(), &kind.name(), &amount.to_string()],
+                    )
+                    .await?;
+                }
+                Row::Withdrawal {
+                    vault,
+                    user,
+                    amount,
+                    destination_vault,
+                } => {
+                    db.execute(
+                        "INSERT INTO withdrawals (signature, event_index, vault, \"user\", amount, destination_vault)
+                         VALUES ($1, $2, $3, $4, $5::TEXT::NUMERIC, $6)",
+                        &[
+                            &tx.signature,
+                            &index,
+                            &vault.to_string(),
+                            &user.to_string(),
+                            &amount.to_string(),
+                            &destination_vault.map(|vault| vault.to_string()),
+                        ],
+                    )
+                    .await?;
+                }
+            }
+        }
+        db.commit().await?;
+
+        if let Some(notifier) = &self.notifier {
+            let batch = notifications(tx.signature, tx.slot, tx.events);
+            if !batch.is_empty() && notifier.send(batch).is_err() {
+                error!("notifier stopped; notifications dropped");
+            }
+        }
+        Ok(true)
+    }
+
+    /// Unfinalized transactions at or below `slot`, oldest first.
+    pub async fn unfinalized(&self, slot: u64) -> Result<Vec<(String, u64)>, tokio_postgres::Error> {
+        let rows = self
+            .client
+            .query(
+                "SELECT signature, slot FROM transactions WHERE NOT finalized AND slot <= $1 ORDER BY slot",
+                &[&(slot as i64)],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get::<_, i64>(1) as u64)).collect())
+    }
+
+    /// Mark `signature` finalized where it actually landed.
+    pub async fn finalize(&self, signature: &str, slot: u64, block_time: Option<i64>) -> Result<(), tokio_postgres::Error> {
+        self.client
+            .execute(
+                "UPDATE transactions SET finalized = TRUE, slot = $2, block_time = $3 WHERE signature = $1",
+                &[&signature, &(slot as i64), &block_time],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Drop a transaction that was rolled back, and every row it produced.
+    pub async fn remove(&self, signature: &str) -> Result<(), tokio_postgres::Error> {
+        self.client
+            .execute("DELETE FROM transactions WHERE signature = $1", &[&signature])
+            .await?;
+        Ok(())
+    }
+
+    pub async fn backfill_cursor(&self) -> Result<Option<String>, tokio_postgres::Error> {
+        let row = self
+            .client
+            .query_opt("SELECT value FROM indexer_state WHERE key = $1", &[&BACKFILL_CURSOR])
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    pub async fn set_backfill_cursor(&self, signature: &str) -> Result<(), tokio_postgres::Error> {
+        self.client
+            .execute(
+                "INSERT INTO indexer_state (key, value) VALUES ($1, $2)
+                 ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+                &[&BACKFILL_CURSOR, &signature],
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Backfill from signature history, and reconciliation of live rows against
+//! finalized history.
+//!
+//! Backfill reads at `finalized` commitment, so its rows are final as soon as
+//! they're written. Live rows arrive at `confirmed` and can still be rolled
+//! back by a fork: once the finalized slot passes them, each is looked up at
+//! `finalized` and either marked final (at the slot it really landed in) or
+//! deleted along with its rows. If it later lands in a newer block, the next
+//! backfill picks it up again.
+
+use gentdex_client::events::parse_logs;
+use gentdex_client::rpc::{GentdexRpc, SignatureInfo, SIGNATURE_PAGE};
+use gentdex_client::PROGRAM_ID;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::store::{IndexedTransaction, Store};
+use crate::IndexerError;
+
+/// Index every finalized program transaction newer than the backfill cursor,
+/// oldest first. Returns how many were new.
+pub async fn backfill(rpc: &GentdexRpc, store: &mut Store) -> Result<usize, IndexerError> {
+    let cursor = store.backfill_cursor().await?;
+
+    // Newest first, back to the cursor or the start of the node's history
+    let mut pending: Vec<SignatureInfo> = Vec::new();
+    'pages: loop {
+        let before = pending.last().map(|info| info.signature.clone());
+        let page = rpc.signatures_for_address(&PROGRAM_ID, before.as_deref()).await?;
+        let done = page.len() < SIGNATURE_PAGE;
+        for info in page {
+            if cursor.as_ref() == Some(&info.signature) {
+                break 'pages;
+            }
+            pending.push(info);
+        }
+        if done {
+            break;
+        }
+    }
+
+    let mut indexed = 0;
+    for info in pending.iter().rev().filter(|info| info.err.is_none()) {
+        let Some(tx) = rpc.get_transaction(&info.signature).await? else {
+            warn!(signature = info.signature, "transaction no longer available; skipped");
+            continue;
+        };
+        let events = parse_logs(&tx.logs);
+        let indexed_tx = IndexedTransaction {
+            signature: &info.signature,
+            slot: tx.slot,
+            block_time: tx.block_time,
+            finalized: true,
+            events: &events,
+        };
+        if store.insert(&indexed_tx).await? {
+            indexed += 1;
+        } else {
+            // Already stored by the live stream
+            store.finalize(&info.signature, tx.slot, tx.block_time).await?;
+        }
+    }
+    if let Some(newest) = pending.first() {
+        store.set_backfill_cursor(&newest.signature).await?;
+    }
+    if indexed > 0 {
+        info!(indexed, "backfilled transactions");
+    }
+    Ok(indexed)
+}
+
+/// Finalize or roll back live rows the finalized slot has passed.
+pub async fn reconcile(rpc: &GentdexRpc, store: &Store) -> Result<(), IndexerError> {
+    let finalized_slot: u64 = rpc.call("getSlot", json!([{ "commitment": "finalized" }])).await?;
+    for (signature, slot) in store.unfinalized(finalized_slot).await? {
+        match rpc.get_transaction(&signature).await? {
+            Some(tx) if tx.err.is_none() => store.finalize(&signature, tx.slot, tx.block_time).await?,
+            _ => {
+                warn!(signature, slot, "transaction rolled back; removing its rows");
+                store.remove(&signature).await?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Which cranks a vault needs, and the instructions for them.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use gentdex_client::instructions;
+use gentdex_client::program::{accrued_compute_fee, Vault, VaultStatus, MAX_GUARDIAN_PAUSE_DAYS};
+
+/// Seconds in one compute-fee day
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A permissionless instruction the keeper submits, in its `_if_due` form
+/// where there is one, so losing a race to another keeper isn't a failure.
+/// New cranks (DCA, limit
+/// orders) add a variant here and a check in [`due`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crank {
+    LiftGuardianPause,
+    DeductComputeFee,
+    Expire,
+}
+
+impl Crank {
+    pub fn name(self) -> &'static str {
+        match self {
+            Crank::LiftGuardianPause => "lift_guardian_pause",
+            Crank::DeductComputeFee => "deduct_compute_fee",
+            Crank::Expire => "expire",
+        }
+    }
+
+    pub fn instruction(self, cranker: Pubkey, address: Pubkey, vault: &Vault) -> Instruction {
+        match self {
+            Crank::LiftGuardianPause => instructions::lift_guardian_pause(cranker, address),
+            Crank::DeductComputeFee => {
+                instructions::deduct_compute_fee_if_due(cranker, address, vault.user, vault.treasury, vault.bot)
+            }
+            Crank::Expire => instructions::expire_if_due(cranker, address),
+        }
+    }
+}
+
+/// The cranks due on one vault, in submission order.
+pub struct Job {
+    pub address: Pubkey,
+    pub vault: Vault,
+    pub cranks: Vec<Crank>,
+    /// Compute fee the deduction will collect, if one is due
+    pub compute_fee: u64,
+    /// When the oldest of them became due; older jobs go first
+    pub due_since: i64,
+}
+
+/// What `vault` needs at `now`, or `None` if nothing is due.
+///
+/// Fees are deducted before expiring, since deduction needs a live session;
+/// accrual already stops at `expires_at`, so this collects the final days.
+/// Token sessions are only expired: their fee crank needs token accounts.
+/// Guardian pauses the user hasn't confirmed are lifted once they run out.
+pub fn due(address: Pubkey, vault: Vault, now: i64) -> Option<Job> {
+    if !matches!(vault.status, VaultStatus::Active | VaultStatus::Paused) || vault.locked {
+        return None;
+    }
+
+    let mut cranks = Vec::new();
+    let mut due_since = i64::MAX;
+    let mut compute_fee = 0;
+    let lifts_at = vault
+        .guardian_paused_at
+        .saturating_add(MAX_GUARDIAN_PAUSE_DAYS as i64 * SECONDS_PER_DAY);
+    if vault.guardian_paused_at > 0 && now >= lifts_at && now < vault.expires_at {
+        cranks.push(Crank::LiftGuardianPause);
+        due_since = lifts_at;
+    }
+    if vault.is_sol_session() && vault.balance > 0 {
+        if let Ok((days, fee)) = accrued_compute_fee(&vault, now) {
+            if days >= 1 {
+                cranks.push(Crank::DeductComputeFee);
+                compute_fee = fee;
+                due_since = vault.last_compute_deduction.saturating_add(SECONDS_PER_DAY);
+            }
+        }
+    }
+    if now >= vault.expires_at {
+        cranks.push(Crank::Expire);
+        due_since = due_since.min(vault.expires_at);
+    }
+
+    (!cranks.is_empty()).then_some(Job {
+        address,
+        vault,
+        cranks,
+        compute_fee,
+        due_since,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountDeserialize, Discriminator};
+    use anchor_spl::token::spl_token::native_mint;
+
+    use super::*;
+
+    fn active_vault(now: i64) -> Vault {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(4096, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.status = VaultStatus::Active;
+        vault.base_mint = native_mint::ID;
+        vault.balance = 1_000_000_000;
+        vault.daily_compute_fee = 1_000_000;
+        vault.funded_at = now;
+        vault.last_compute_deduction = now;
+        vault.expires_at = now + 7 * SECONDS_PER_DAY;
+        vault
+    }
+
+    #[test]
+    fn schedules_fees_before_expiry() {
+        let start = 1_700_000_000;
+        let address = Pubkey::new_unique();
+
+        assert!(due(address, active_vault(start), start + SECONDS_PER_DAY - 1).is_none());
+
+        let job = due(address, active_vault(start), start + SECONDS_PER_DAY).unwrap();
+        assert_eq!(job.cranks, vec![Crank::DeductComputeFee]);
+        assert_eq!(job.compute_fee, 1_000_000);
+        assert_eq!(job.due_since, start + SECONDS_PER_DAY);
+
+        let job = due(address, active_vault(start), start + 8 * SECONDS_PER_DAY).unwrap();
+        assert_eq!(job.cranks, vec![Crank::DeductComputeFee, Crank::Expire]);
+
+        let mut locked = active_vault(start);
+        locked.locked = true;
+        assert!(due(address, locked, start + 8 * SECONDS_PER_DAY).is_none());
+
+        let mut paid_up = active_vault(start);
+        paid_up.last_compute_deduction = paid_up.expires_at;
+        let job = due(address, paid_up, start + 8 * SECONDS_PER_DAY).unwrap();
+        assert_eq!(job.cranks, vec![Crank::Expire]);
+        assert_eq!(job.due_since, start + 7 * SECONDS_PER_DAY);
+    }
+
+    #[test]
+    fn lifts_guardian_pauses_that_ran_out() {
+        let start = 1_700_000_000;
+        let address = Pubkey::new_unique();
+        let mut paused = active_vault(start);
+        paused.status = VaultStatus::Paused;
+        paused.guardian_paused_at = start;
+        paused.expires_at = start + 30 * SECONDS_PER_DAY;
+        paused.last_compute_deduction = paused.expires_at;
+        let lifts_at = start + MAX_GUARDIAN_PAUSE_DAYS as i64 * SECONDS_PER_DAY;
+
+        assert!(due(address, paused.clone(), lifts_at - 1).is_none());
+        let job = due(address, paused.clone(), lifts_at).unwrap();
+        assert_eq!(job.cranks, vec![Crank::LiftGuardianPause]);
+        assert_eq!(job.due_since, lifts_at);
+
+        // Once the user confirms, it's theirs to resume
+        paused.guardian_paused_at = 0;
+        assert!(due(address, paused, lifts_at).is_none());
+    }
+}
@@ -0,0 +1,255 @@
+//! `gentdex-keeper`: runs GentDex's permissionless cranks.
+//!
+//! Every tick it finds live vaults with `getProgramAccounts`, works out which
+//! need a compute fee deduction or expiring, and submits those cranks oldest
+//! first, several vaults per transaction with an optional priority fee. If a
+//! batch fails its vaults are retried one by one, and a vault that keeps
+//! failing is backed off; the backoff is kept in a state file so restarts
+//! don't hammer it.
+//!
+//! Prometheus metrics are served at `/metrics`, and `/healthz` fails once
+//! scans stop succeeding.
+
+mod crank;
+mod metrics;
+mod state;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use gentdex_client::instructions::{compute_unit_limit, compute_unit_price};
+use gentdex_client::program::VaultStatus;
+use gentdex_client::rpc::GentdexRpc;
+use gentdex_client::ClientError;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use tracing::{error, info, warn};
+
+use crate::crank::Job;
+use crate::metrics::Metrics;
+use crate::state::KeeperState;
+
+/// Compute units budgeted per crank instruction, with headroom
+const UNITS_PER_CRANK: u32 = 40_000;
+
+#[derive(Parser)]
+#[command(name = "gentdex-keeper", version, about = "Run GentDex session cranks")]
+struct Args {
+    /// JSON-RPC endpoints, comma-separated; sends prefer the first
+    #[arg(
+        long,
+        short = 'u',
+        env = "GENTDEX_RPC_URL",
+        value_delimiter = ',',
+        default_value = "https://api.mainnet-beta.solana.com"
+    )]
+    url: Vec<String>,
+    /// Keypair that signs and pays for cranks
+    #[arg(long, short = 'k', env = "GENTDEX_KEYPAIR")]
+    keypair: PathBuf,
+    /// Seconds between scans
+    #[arg(long, default_value_t = 60)]
+    interval: u64,
+    /// Vaults cranked per transaction
+    #[arg(long, default_value_t = 4)]
+    batch_size: usize,
+    /// Priority fee, in micro-lamports per compute unit
+    #[arg(long, default_value_t = 0)]
+    priority_fee: u64,
+    /// Where backoff state is kept across restarts
+    #[arg(long, default_value = "gentdex-keeper-state.json")]
+    state_file: PathBuf,
+    /// Simulate cranks instead of sending them
+    #[arg(long)]
+    dry_run: bool,
+    /// Address for the `/metrics` and `/healthz` endpoints
+    #[arg(long, default_value = "127.0.0.1:9464")]
+    metrics_addr: SocketAddr,
+}
+
+struct Keeper {
+    rpc: GentdexRpc,
+    signer: Keypair,
+    args: Args,
+    state: KeeperState,
+    metrics: Arc<Metrics>,
+}
+
+impl Keeper {
+    /// One scan: find due vaults and crank them.
+    async fn tick(&mut self) -> Result<(), ClientError> {
+        let started = Instant::now();
+        let mut vaults = self.rpc.vaults_with_status(VaultStatus::Active).await?;
+        vaults.extend(self.rpc.vaults_with_status(VaultStatus::Paused).await?);
+        self.observe("scan", started);
+        let tracked = vaults.len();
+
+        let now = now();
+        let mut jobs: Vec<Job> = vaults
+            .into_iter()
+            .filter_map(|(address, vault)| crank::due(address, vault, now))
+            .collect();
+        let due: Vec<String> = jobs.iter().map(|job| job.address.to_string()).collect();
+        self.state.retain(|vault| due.iter().any(|address| address == vault));
+        jobs.retain(|job| !self.state.backed_off(&job.address.to_string(), now));
+        jobs.sort_by_key(|job| job.due_since);
+        info!(tracked, due = due.len(), ready = jobs.len(), "scanned vaults");
+        self.metrics.vaults_tracked.set(tracked as i64);
+        self.metrics.vaults_due.set(due.len() as i64);
+
+        for batch in jobs.chunks(self.args.batch_size.max(1)) {
+            match self.submit(batch).await {
+                Ok(signature) => self.succeeded(batch, &signature),
+                // One vault's failure shouldn't hold up the rest of its batch
+                Err(err) if batch.len() > 1 => {
+                    warn!(%err, "batch failed, retrying vaults one by one");
+                    for job in batch {
+                        match self.submit(std::slice::from_ref(job)).await {
+                            Ok(signature) => self.succeeded(std::slice::from_ref(job), &signature),
+                            Err(err) => self.failed(job, now, err),
+                        }
+                    }
+                }
+                Err(err) => self.failed(&batch[0], now, err),
+            }
+            self.save_state();
+        }
+        self.metrics.last_scan.set(crate::now());
+        Ok(())
+    }
+
+    fn observe(&self, operation: &str, started: Instant) {
+        self.metrics
+            .rpc_latency
+            .with_label_values(&[operation])
+            .observe(started.elapsed().as_secs_f64());
+    }
+
+    async fn submit(&self, jobs: &[Job]) -> Result<String, ClientError> {
+        let cranker = self.signer.pubkey();
+        let units: usize = jobs.iter().map(|job| job.cranks.len()).sum();
+
+        let mut ixs = vec![compute_unit_limit(UNITS_PER_CRANK * units as u32)];
+        if self.args.priority_fee > 0 {
+            ixs.push(compute_unit_price(self.args.priority_fee));
+        }
+        for job in jobs {
+            ixs.extend(job.cranks.iter().map(|crank| crank.instruction(cranker, job.address, &job.vault)));
+        }
+
+        let started = Instant::now();
+        if self.args.dry_run {
+            let simulated = self.rpc.simulate(&ixs, &cranker).await;
+            self.observe("simulate", started);
+            simulated?;
+            return Ok("(simulated)".to_string());
+        }
+        let sent = self.rpc.send_and_confirm(&ixs, &[&self.signer]).await;
+        self.observe("send_and_confirm", started);
+        sent
+    }
+
+    fn succeeded(&mut self, jobs: &[Job], signature: &str) {
+        for job in jobs {
+            let cranks: Vec<&str> = job.cranks.iter().map(|crank| crank.name()).collect();
+            info!(vault = %job.address, ?cranks, signature, "cranked");
+            self.state.record_success(&job.address.to_string());
+            for crank in &job.cranks {
+                self.metrics.cranks_submitted.with_label_values(&[crank.name()]).inc();
+            }
+            if !self.args.dry_run {
+                self.metrics.fees_collected.inc_by(job.compute_fee);
+            }
+        }
+    }
+
+    fn failed(&mut self, job: &Job, now: i64, err: ClientError) {
+        error!(vault = %job.address, %err, "crank failed");
+        for crank in &job.cranks {
+            self.metrics.cranks_failed.with_label_values(&[crank.name()]).inc();
+        }
+        self.state.record_failure(&job.address.to_string(), now, err.to_string());
+    }
+
+    fn save_state(&self) {
+        if let Err(err) = self.state.save(&self.args.state_file) {
+            error!(%err, path = %self.args.state_file.display(), "could not save state");
+        }
+    }
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let args = Args::parse();
+    let signer = match read_keypair_file(&args.keypair) {
+        Ok(signer) => signer,
+        Err(err) => {
+            error!(%err, path = %args.keypair.display(), "could not read keypair");
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+    let state = match KeeperState::load(&args.state_file) {
+        Ok(state) => state,
+        Err(err) => {
+            error!(%err, path = %args.state_file.display(), "could not load state");
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+    info!(cranker = %signer.pubkey(), dry_run = args.dry_run, "keeper starting");
+
+    // Healthy while scans finish within a few intervals of each other
+    let metrics = Arc::new(Metrics::new(now(), 3 * args.interval as i64 + 60));
+    let server = tokio::spawn({
+        let (addr, metrics) = (args.metrics_addr, metrics.clone());
+        async move {
+            if let Err(err) = metrics::serve(addr, metrics).await {
+                error!(%err, %addr, "metrics server stopped");
+            }
+        }
+    });
+    info!(addr = %args.metrics_addr, "serving /metrics and /healthz");
+
+    let mut keeper = Keeper {
+        rpc: GentdexRpc::pooled(args.url.clone()),
+        signer,
+        args,
+        state,
+        metrics,
+    };
+    // Cranks built for one cluster's program ID would only fail on another's
+    match keeper.rpc.check_cluster().await {
+        Ok(cluster) => info!(?cluster, "connected"),
+        Err(err) => {
+            error!(%err, "refusing to crank");
+            server.abort();
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+    let interval = Duration::from_secs(keeper.args.interval);
+    loop {
+        if let Err(err) = keeper.tick().await {
+            error!(%err, "scan failed");
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    keeper.save_state();
+    server.abort();
+    info!("keeper stopped");
+    std::process::ExitCode::SUCCESS
+}
@@ -0,0 +1,128 @@
+//! Prometheus metrics and a health check, served over HTTP at `/metrics` and
+//! `/healthz`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+pub struct Metrics {
+    registry: Registry,
+    pub vaults_tracked: IntGauge,
+    pub vaults_due: IntGauge,
+    pub cranks_submitted: IntCounterVec,
+    pub cranks_failed: IntCounterVec,
+    pub fees_collected: IntCounter,
+    pub rpc_latency: HistogramVec,
+    pub last_scan: IntGauge,
+    started_at: i64,
+    /// A scan older than this makes `/healthz` fail
+    max_scan_age: i64,
+}
+
+impl Metrics {
+    pub fn new(started_at: i64, max_scan_age: i64) -> Self {
+        let registry = Registry::new_custom(Some("gentdex_keeper".to_string()), None).expect("valid prefix");
+        let metrics = Self {
+            vaults_tracked: IntGauge::new("vaults_tracked", "Active and paused vaults found by the last scan")
+                .expect("valid metric"),
+            vaults_due: IntGauge::new("vaults_due", "Vaults with a crank due at the last scan").expect("valid metric"),
+            cranks_submitted: IntCounterVec::new(
+                Opts::new("cranks_submitted_total", "Crank instructions confirmed"),
+                &["crank"],
+            )
+            .expect("valid metric"),
+            cranks_failed: IntCounterVec::new(Opts::new("cranks_failed_total", "Crank instructions that failed"), &["crank"])
+                .expect("valid metric"),
+            fees_collected: IntCounter::new(
+                "compute_fees_collected_lamports_total",
+                "Compute fees moved to the treasury by this keeper's cranks",
+            )
+            .expect("valid metric"),
+            rpc_latency: HistogramVec::new(
+                HistogramOpts::new("rpc_latency_seconds", "Latency of RPC operations")
+                    .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]),
+                &["operation"],
+            )
+            .expect("valid metric"),
+            last_scan: IntGauge::new("last_scan_timestamp_seconds", "When the last successful scan finished")
+                .expect("valid metric"),
+            registry,
+            started_at,
+            max_scan_age,
+        };
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 7] = [
+            Box::new(metrics.vaults_tracked.clone()),
+            Box::new(metrics.vaults_due.clone()),
+            Box::new(metrics.cranks_submitted.clone()),
+            Box::new(metrics.cranks_failed.clone()),
+            Box::new(metrics.fees_collected.clone()),
+            Box::new(metrics.rpc_latency.clone()),
+            Box::new(metrics.last_scan.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector).expect("metric registered once");
+        }
+        metrics
+    }
+
+    /// Healthy while scans keep succeeding; a fresh keeper gets one
+    /// `max_scan_age` to finish its first.
+    pub fn healthy(&self, now: i64) -> bool {
+        let last = self.last_scan.get().max(self.started_at);
+        now - last <= self.max_scan_age
+    }
+
+    fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding can't fail");
+        String::from_utf8(buffer).expect("text encoding is UTF-8")
+    }
+}
+
+/// Serve `/metrics` and `/healthz` on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr, metrics: Arc<Metrics>) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(|State(metrics): State<Arc<Metrics>>| async move { metrics.render() }))
+        .route(
+            "/healthz",
+            get(|State(metrics): State<Arc<Metrics>>| async move {
+                if metrics.healthy(crate::now()) {
+                    (StatusCode::OK, "ok")
+                } else {
+                    (StatusCode::SERVICE_UNAVAILABLE, "no recent successful scan")
+                }
+            }),
+        )
+        .with_state(metrics);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_unhealthy_once_scans_go_stale() {
+        let metrics = Metrics::new(1_000, 180);
+        assert!(metrics.healthy(1_100));
+        assert!(!metrics.healthy(1_181));
+
+        metrics.last_scan.set(1_200);
+        assert!(metrics.healthy(1_380));
+        assert!(!metrics.healthy(1_381));
+
+        metrics.cranks_submitted.with_label_values(&["expire"]).inc();
+        assert!(metrics.render().contains("gentdex_keeper_cranks_submitted_total{crank=\"expire\"} 1"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Keeper state that survives restarts: per-vault failure backoff, so a vault
+//! whose crank keeps failing isn't retried every tick (or right after a crash).
+//! Written atomically (temp file + rename), so a crash mid-write leaves the
+//! previous state intact.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// First retry delay after a failure; doubles per consecutive failure
+const BASE_BACKOFF_SECS: i64 = 60;
+const MAX_BACKOFF_SECS: i64 = 6 * 3_600;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Backoff {
+    pub failures: u32,
+    pub retry_after: i64,
+    pub last_error: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct KeeperState {
+    /// Keyed by vault address
+    pub backoff: HashMap<String, Backoff>,
+}
+
+impl KeeperState {
+    /// Load from `path`, starting empty if it doesn't exist yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data).map_err(io::Error::from),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut tmp = PathBuf::from(path);
+        tmp.as_mut_os_string().push(".tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn backed_off(&self, vault: &str, now: i64) -> bool {
+        self.backoff.get(vault).is_some_and(|backoff| now < backoff.retry_after)
+    }
+
+    pub fn record_failure(&mut self, vault: &str, now: i64, error: String) {
+        let backoff = self.backoff.entry(vault.to_string()).or_default();
+        backoff.failures += 1;
+        let delay = BASE_BACKOFF_SECS
+            .saturating_mul(1 << (backoff.failures - 1).min(16))
+            .min(MAX_BACKOFF_SECS);
+        backoff.retry_after = now + delay;
+        backoff.last_error = error;
+    }
+
+    pub fn record_success(&mut self, vault: &str) {
+        self.backoff.remove(vault);
+    }
+
+    /// Forget vaults that no longer need cranking.
+    pub fn retain(&mut self, mut due: impl FnMut(&str) -> bool) {
+        self.backoff.retain(|vault, _| due(vault));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backs_off_exponentially_and_persists() {
+        let mut state = KeeperState::default();
+        state.record_failure("vault", 0, "boom".to_string());
+        assert!(state.backed_off("vault", BASE_BACKOFF_SECS - 1));
+        assert!(!state.backed_off("vault", BASE_BACKOFF_SECS));
+
+        state.record_failure("vault", 0, "boom".to_string());
+        assert_eq!(state.backoff["vault"].retry_after, 2 * BASE_BACKOFF_SECS);
+        for _ in 0..40 {
+            state.record_failure("vault", 0, "boom".to_string());
+        }
+        assert_eq!(state.backoff["vault"].retry_after, MAX_BACKOFF_SECS);
+
+        let path = std::env::temp_dir().join(format!("gentdex-keeper-{}.json", std::process::id()));
+        state.save(&path).unwrap();
+        let loaded = KeeperState::load(&path).unwrap();
+        assert!(loaded.backed_off("vault", 0));
+        fs::remove_file(&path).unwrap();
+
+        state.record_success("vault");
+        assert!(!state.backed_off("vault", 0));
+    }
+}
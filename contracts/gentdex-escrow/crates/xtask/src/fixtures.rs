@@ -0,0 +1,143 @@
+//! Account fixtures for `solana-test-validator --account-dir`: a protocol
+//! config, and optionally a funded SOL session, written as the program would
+//! leave them so localnet and the TS tests can start from a configured
+//! deployment without running `initialize_config` (which needs the program's
+//! upgrade authority) and a deposit first.
+
+use std::fs;
+use std::path::Path;
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator, Space};
+use anchor_spl::token::spl_token::native_mint;
+use base64::Engine;
+use gentdex_escrow::{
+    default_dex_whitelist, pda, ProtocolConfig, Vault, VaultStatus, DAILY_COMPUTE_FEE, DEFAULT_LEND_CAP_BPS, FEE_BPS,
+    MIN_DEPOSIT, MIN_STABLE_DEPOSIT,
+};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A funded session to write beside the config.
+pub struct SessionFixture {
+    pub user: Pubkey,
+    pub bot: Pubkey,
+    pub session_id: [u8; 16],
+    pub balance: u64,
+    pub duration_days: u16,
+}
+
+/// An account of type `T` with every field zeroed: empty vecs, first enum
+/// variants, default keys.
+fn zeroed<T: AccountDeserialize + Discriminator + Space>() -> T {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    data.resize(T::DISCRIMINATOR.len() + T::INIT_SPACE, 0);
+    T::try_deserialize(&mut data.as_slice()).expect("zeroed accounts deserialize")
+}
+
+pub fn config(admin: Pubkey, treasury: Pubkey) -> ProtocolConfig {
+    let mut config: ProtocolConfig = zeroed();
+    config.admin = admin;
+    config.guardian = admin;
+    config.treasury = treasury;
+    config.fee_bps = FEE_BPS as u16;
+    config.daily_compute_fee = DAILY_COMPUTE_FEE;
+    config.whitelist = default_dex_whitelist();
+    config.whitelist_version = 1;
+    config.min_deposit = MIN_DEPOSIT;
+    config.min_stable_deposit = MIN_STABLE_DEPOSIT;
+    config.bump = pda::config_address().1;
+    config
+}
+
+/// An Active session funded at `now`, as `fund_session` leaves it.
+pub fn session(fixture: &SessionFixture, treasury: Pubkey, now: i64) -> Vault {
+    let mut vault: Vault = zeroed();
+    vault.user = fixture.user;
+    vault.bot = fixture.bot;
+    vault.session_id = fixture.session_id;
+    vault.treasury = treasury;
+    vault.base_mint = native_mint::ID;
+    vault.balance = fixture.balance;
+    vault.total_deposited = fixture.balance;
+    vault.duration_days = fixture.duration_days;
+    vault.status = VaultStatus::Active;
+    vault.created_at = now;
+    vault.funded_at = now;
+    vault.expires_at = now + fixture.duration_days as i64 * SECONDS_PER_DAY;
+    vault.last_compute_deduction = now;
+    vault.last_user_activity = now;
+    vault.daily_compute_fee = DAILY_COMPUTE_FEE;
+    vault.lend_cap_bps = DEFAULT_LEND_CAP_BPS;
+    vault.whitelist_version = 1;
+    vault.bump = pda::vault_address(&fixture.session_id, &fixture.user).1;
+    vault
+}
+
+/// `account` in `solana account --output json` form, rent-exempt at its
+/// full `INIT_SPACE` plus `extra_lamports`.
+fn dump<T: AccountSerialize + Space>(address: &Pubkey, account: &T, extra_lamports: u64) -> serde_json::Value {
+    let mut data = Vec::with_capacity(8 + T::INIT_SPACE);
+    account.try_serialize(&mut data).expect("accounts serialize");
+    data.resize(8 + T::INIT_SPACE, 0);
+    serde_json::json!({
+        "pubkey": address.to_string(),
+        "account": {
+            "lamports": Rent::default().minimum_balance(data.len()) + extra_lamports,
+            "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"],
+            "owner": gentdex_escrow::ID.to_string(),
+            "executable": false,
+            "rentEpoch": u64::MAX,
+            "space": data.len(),
+        },
+    })
+}
+
+/// Writes the fixtures into `dir`, returning the files written.
+pub fn write(dir: &Path, admin: Pubkey, session: Option<SessionFixture>, now: i64) -> Result<Vec<String>, String> {
+    fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    let mut dumps = vec![("config", dump(&pda::config_address().0, &config(admin, admin), 0))];
+    if let Some(fixture) = session {
+        let vault = self::session(&fixture, admin, now);
+        let address = pda::vault_address(&fixture.session_id, &fixture.user).0;
+        dumps.push(("session", dump(&address, &vault, fixture.balance)));
+    }
+
+    let mut written = Vec::new();
+    for (name, account) in dumps {
+        let path = dir.join(format!("{name}.json"));
+        let contents = serde_json::to_string_pretty(&account).expect("fixtures serialize");
+        fs::write(&path, contents + "\n").map_err(|err| format!("{}: {err}", path.display()))?;
+        written.push(path.display().to_string());
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumps_accounts_the_program_can_read() {
+        let fixture = SessionFixture {
+            user: Pubkey::new_unique(),
+            bot: Pubkey::new_unique(),
+            session_id: [7; 16],
+            balance: 1_000_000_000,
+            duration_days: 7,
+        };
+        let address = pda::vault_address(&fixture.session_id, &fixture.user).0;
+        let json = dump(&address, &session(&fixture, Pubkey::new_unique(), 1_000), fixture.balance);
+
+        let account = &json["account"];
+        let data = base64::engine::general_purpose::STANDARD.decode(account["data"][0].as_str().unwrap()).unwrap();
+        assert_eq!(data.len(), 8 + Vault::INIT_SPACE);
+        let rent = Rent::default().minimum_balance(data.len());
+        assert_eq!(account["lamports"].as_u64(), Some(rent + fixture.balance));
+
+        let vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(vault.status, VaultStatus::Active);
+        assert_eq!((vault.user, vault.bot), (fixture.user, fixture.bot));
+        assert_eq!(vault.expires_at, 1_000 + 7 * SECONDS_PER_DAY);
+    }
+}
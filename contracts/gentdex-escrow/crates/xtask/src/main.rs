@@ -0,0 +1,324 @@
+//! `cargo xtask`: one entry point for the workspace's dev and release tasks.
+//!
+//! - `localnet` builds the program and starts `solana-test-validator` with
+//!   it deployed and any fixtures loaded.
+//! - `deploy` builds the program with the devnet program ID (feature
+//!   `devnet`) and deploys it to devnet.
+//! - `idl` regenerates the IDL: the copy the client crate generates its
+//!   event, instruction and account types from, and the TS types the tests
+//!   import. `--check` fails instead if the client's copy is stale.
+//! - `fixtures` writes account fixtures for localnet (see [`fixtures`]).
+//! - `build` runs `anchor build --verifiable` with the git commit passed in
+//!   for the program's security.txt, and writes a manifest next to the
+//!   binary: the commit, and the executable hash `solana-verify` and
+//!   `anchor verify` compare against the deployed program. Publish that hash
+//!   with `set_upgrade_info`.
+//! - `hash` prints the executable hash of any program binary.
+//! - `publish-idl` uploads the built IDL on-chain with `anchor idl`.
+
+mod fixtures;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang_idl::build::IdlBuilder;
+use clap::{Parser, Subcommand};
+use sha2::{Digest, Sha256};
+
+use crate::fixtures::SessionFixture;
+
+const PROGRAM: &str = "gentdex_escrow";
+const PROGRAM_ID: &str = "9hyscAyfR2puBXWFoGzeBq3QtSn5e83B7AUkcS1qC5RJ";
+const FIXTURES: &str = "target/fixtures";
+/// The IDL the client crate's `build.rs` reads, checked in
+const CLIENT_IDL: &str = "crates/gentdex-client/idl/gentdex_escrow.json";
+/// Session id of the fixture session
+const FIXTURE_SESSION_ID: [u8; 16] = *b"localnet-session";
+
+#[derive(Parser)]
+#[command(name = "cargo xtask", about = "Dev and release tasks for the GentDex escrow workspace")]
+struct Args {
+    #[command(subcommand)]
+    task: Task,
+}
+
+#[derive(Subcommand)]
+enum Task {
+    /// Build the program and run a local validator with it and the fixtures loaded
+    Localnet {
+        /// Use the program already in target/deploy
+        #[arg(long)]
+        skip_build: bool,
+        /// Program upgrade authority [default: the Solana CLI wallet]
+        #[arg(long)]
+        upgrade_authority: Option<Pubkey>,
+    },
+    /// Build the program and deploy it to devnet
+    Deploy {
+        /// Cluster to deploy to; mainnet releases go through `build` instead
+        #[arg(long, short = 'u', default_value = "devnet")]
+        url: String,
+        /// Deployer and upgrade authority keypair
+        #[arg(long, default_value = "~/.config/solana/id.json")]
+        keypair: String,
+        /// Keypair of the devnet program ID the `devnet` build declares
+        #[arg(long, default_value = "target/deploy/gentdex_escrow-devnet-keypair.json")]
+        program_keypair: String,
+    },
+    /// Regenerate the IDL, the client crate's copy and the TS types in target/types
+    Idl {
+        /// Fail if the client crate's IDL is out of date, without writing anything
+        #[arg(long)]
+        check: bool,
+    },
+    /// Write account fixtures for the local validator into target/fixtures
+    Fixtures {
+        /// Admin, guardian and treasury of the config [default: the Solana CLI wallet]
+        #[arg(long)]
+        admin: Option<Pubkey>,
+        /// Also write an Active SOL session owned by this user
+        #[arg(long, requires = "bot")]
+        user: Option<Pubkey>,
+        /// The fixture session's bot key
+        #[arg(long, requires = "user")]
+        bot: Option<Pubkey>,
+        /// The fixture session's trading balance, in lamports
+        #[arg(long, default_value_t = 1_000_000_000)]
+        balance: u64,
+        /// The fixture session's duration
+        #[arg(long, default_value_t = 7)]
+        duration_days: u16,
+    },
+    /// Build the program in Anchor's verifiable container and write its manifest
+    Build {
+        /// Build even with uncommitted changes; the manifest records the tree as dirty
+        #[arg(long)]
+        allow_dirty: bool,
+    },
+    /// Print the executable hash of a program binary [default: the verifiable build]
+    Hash { binary: Option<PathBuf> },
+    /// Upload the IDL from the last build to the program's on-chain IDL account
+    PublishIdl {
+        /// Cluster to publish to: a JSON-RPC URL or a moniker like `devnet`
+        #[arg(long, short = 'u', env = "GENTDEX_RPC_URL")]
+        url: String,
+        /// Upgrade authority keypair, which owns the IDL account
+        #[arg(long, default_value = "~/.config/solana/id.json")]
+        keypair: String,
+        /// Create the IDL account; later publishes upgrade it
+        #[arg(long)]
+        init: bool,
+    },
+}
+
+/// The Anchor workspace, two levels above this crate.
+fn workspace() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).ancestors().nth(2).expect("xtask lives in crates/xtask").to_path_buf()
+}
+
+fn verifiable_binary(root: &Path) -> PathBuf {
+    root.join("target/verifiable").join(format!("{PROGRAM}.so"))
+}
+
+/// Runs `program` in the workspace, failing unless it exits cleanly.
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(program)
+        .args(args)
+        .current_dir(workspace())
+        .status()
+        .map_err(|err| format!("running {program}: {err}"))?;
+    status.success().then_some(()).ok_or_else(|| format!("{program} {}: {status}", args.join(" ")))
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output =
+        Command::new("git").args(args).current_dir(workspace()).output().map_err(|err| format!("running git: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("git {}: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// SHA-256 of the binary with trailing zero bytes dropped, as `solana-verify`
+/// computes it: deployed program data is padded to the account's length, so
+/// hashes of the local build and the on-chain program only agree without them.
+fn executable_hash(binary: &[u8]) -> String {
+    let end = binary.iter().rposition(|&byte| byte != 0).map_or(0, |last| last + 1);
+    Sha256::digest(&binary[..end]).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    let binary = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(executable_hash(&binary))
+}
+
+/// The Solana CLI's default wallet.
+fn wallet() -> Result<Pubkey, String> {
+    let output = Command::new("solana").arg("address").output().map_err(|err| format!("running solana: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("solana address: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().map_err(|err| format!("solana address: {err}"))
+}
+
+fn localnet(skip_build: bool, upgrade_authority: Option<Pubkey>) -> Result<(), String> {
+    if !skip_build {
+        run("anchor", &["build"])?;
+    }
+    let authority = upgrade_authority.map_or_else(wallet, Ok)?.to_string();
+    let binary = format!("target/deploy/{PROGRAM}.so");
+    let mut args = vec!["--reset", "--upgradeable-program", PROGRAM_ID, &binary, &authority];
+    if workspace().join(FIXTURES).is_dir() {
+        args.extend(["--account-dir", FIXTURES]);
+    }
+    run("solana-test-validator", &args)
+}
+
+fn deploy(url: &str, keypair: &str, program_keypair: &str) -> Result<(), String> {
+    if url.contains("mainnet") {
+        return Err("mainnet releases go through `cargo xtask build` and the upgrade timelock".to_string());
+    }
+    run("anchor", &["build", "--", "--features", "devnet"])?;
+    run(
+        "anchor",
+        &[
+            "deploy",
+            "--program-name",
+            PROGRAM,
+            "--program-keypair",
+            program_keypair,
+            "--provider.cluster",
+            url,
+            "--provider.wallet",
+            keypair,
+        ],
+    )
+}
+
+fn idl(check: bool) -> Result<(), String> {
+    // The IDL build needs nightly, as under `anchor idl build`, and picks the
+    // toolchain from this variable, which `cargo xtask` sets to the pinned one
+    std::env::remove_var("RUSTUP_TOOLCHAIN");
+    let root = workspace();
+    let idl = IdlBuilder::new()
+        .program_path(root.join("programs/gentdex-escrow"))
+        .build()
+        .map_err(|err| format!("building the IDL: {err}"))?;
+    let json = serde_json::to_string_pretty(&idl).expect("IDL serializes") + "\n";
+
+    let client = root.join(CLIENT_IDL);
+    if check {
+        let current = fs::read_to_string(&client).map_err(|err| format!("{}: {err}", client.display()))?;
+        return (current == json)
+            .then_some(())
+            .ok_or_else(|| format!("{CLIENT_IDL} is out of date: run `cargo xtask idl`"));
+    }
+
+    let target = root.join("target/idl").join(format!("{PROGRAM}.json"));
+    for path in [&target, &client] {
+        fs::create_dir_all(path.parent().expect("IDL paths have parents"))
+            .and_then(|()| fs::write(path, &json))
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        eprintln!("wrote {}", path.display());
+    }
+    let types = format!("target/types/{PROGRAM}.ts");
+    run("anchor", &["idl", "type", "--out", &types, &target.to_string_lossy()])
+}
+
+fn write_fixtures(admin: Option<Pubkey>, session: Option<SessionFixture>) -> Result<(), String> {
+    let admin = admin.map_or_else(wallet, Ok)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("clock after 1970").as_secs() as i64;
+    for path in fixtures::write(&workspace().join(FIXTURES), admin, session, now)? {
+        eprintln!("wrote {path}");
+    }
+    Ok(())
+}
+
+fn build(allow_dirty: bool) -> Result<(), String> {
+    let commit = git(&["rev-parse", "HEAD"])?;
+    let dirty = !git(&["status", "--porcelain"])?.is_empty();
+    if dirty && !allow_dirty {
+        return Err("uncommitted changes: commit them, or pass --allow-dirty".to_string());
+    }
+
+    run("anchor", &["build", "--verifiable", "--env", &format!("GENTDEX_GIT_COMMIT={commit}")])?;
+
+    let root = workspace();
+    let binary = verifiable_binary(&root);
+    let hash = hash_file(&binary)?;
+    let manifest = serde_json::json!({
+        "program": PROGRAM,
+        "program_id": PROGRAM_ID,
+        "commit": commit,
+        "dirty": dirty,
+        "executable_hash": hash,
+        "binary": binary.strip_prefix(&root).unwrap_or(&binary),
+    });
+    let path = binary.with_extension("json");
+    let contents = serde_json::to_string_pretty(&manifest).expect("manifest serializes");
+    fs::write(&path, contents + "\n").map_err(|err| format!("{}: {err}", path.display()))?;
+
+    eprintln!("wrote {}", path.display());
+    println!("{hash}");
+    Ok(())
+}
+
+fn publish_idl(url: &str, keypair: &str, init: bool) -> Result<(), String> {
+    let idl = workspace().join("target/idl").join(format!("{PROGRAM}.json"));
+    if !idl.exists() {
+        return Err(format!("{}: not found; run `cargo xtask build` first", idl.display()));
+    }
+    let idl = idl.to_string_lossy();
+    let action = if init { "init" } else { "upgrade" };
+    run(
+        "anchor",
+        &["idl", action, "--filepath", &idl, PROGRAM_ID, "--provider.cluster", url, "--provider.wallet", keypair],
+    )
+}
+
+fn main() -> ExitCode {
+    let result = match Args::parse().task {
+        Task::Localnet { skip_build, upgrade_authority } => localnet(skip_build, upgrade_authority),
+        Task::Deploy { url, keypair, program_keypair } => deploy(&url, &keypair, &program_keypair),
+        Task::Idl { check } => idl(check),
+        Task::Fixtures { admin, user, bot, balance, duration_days } => {
+            let session = user.zip(bot).map(|(user, bot)| SessionFixture {
+                user,
+                bot,
+                session_id: FIXTURE_SESSION_ID,
+                balance,
+                duration_days,
+            });
+            write_fixtures(admin, session)
+        }
+        Task::Build { allow_dirty } => build(allow_dirty),
+        Task::Hash { binary } => {
+            hash_file(&binary.unwrap_or_else(|| verifiable_binary(&workspace()))).map(|hash| println!("{hash}"))
+        }
+        Task::PublishIdl { url, keypair, init } => publish_idl(&url, &keypair, init),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_ignore_account_padding() {
+        let empty = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert_eq!(executable_hash(&[]), empty);
+        assert_eq!(executable_hash(&[0; 8]), empty);
+        assert_eq!(executable_hash(b"elf\0\0\0"), executable_hash(b"elf"));
+        assert_ne!(executable_hash(b"\0elf"), executable_hash(b"elf"));
+    }
+}
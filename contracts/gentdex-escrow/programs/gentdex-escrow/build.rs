@@ -0,0 +1,23 @@
+//! Embeds the source commit in the program's security.txt, so a deployed
+//! binary names the tree it was built from.
+//!
+//! `GENTDEX_GIT_COMMIT` wins when set: verifiable builds run in a container
+//! that may not see the repository's `.git`, so `cargo xtask build` passes it.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GENTDEX_GIT_COMMIT");
+    let commit = std::env::var("GENTDEX_GIT_COMMIT").ok().filter(|commit| !commit.is_empty()).or_else(|| {
+        if let Some(head) = git(&["rev-parse", "--git-path", "HEAD"]) {
+            println!("cargo:rerun-if-changed={head}");
+        }
+        git(&["rev-parse", "HEAD"])
+    });
+    println!("cargo:rustc-env=GENTDEX_GIT_COMMIT={}", commit.as_deref().unwrap_or("unknown"));
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
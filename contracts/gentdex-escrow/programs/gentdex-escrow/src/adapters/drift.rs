@@ -0,0 +1,339 @@
+//! Drift perps adapter.
+//!
+//! In perps mode the vault PDA is the authority of Drift sub-account 0. Every
+//! Drift CPI is signed by the vault, so funds can only ever move between the
+//! vault's own token accounts and its own Drift sub-account. Market, oracle
+//! and spot-market accounts Drift needs are passed via remaining_accounts.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::sysvar;
+
+use super::invoke_vault_signed;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
+
+/// The only sub-account the vault ever opens
+pub const SUB_ACCOUNT_ID: u16 = 0;
+/// Drift spot market index for SOL collateral
+pub const SOL_SPOT_MARKET_INDEX: u16 = 1;
+
+// Anchor discriminators
+const INITIALIZE_USER_STATS: [u8; 8] = [254, 243, 72, 98, 251, 130, 168, 213];
+const INITIALIZE_USER: [u8; 8] = [111, 17, 185, 250, 60, 122, 38, 254];
+const DEPOSIT: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+const WITHDRAW: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+const PLACE_PERP_ORDER: [u8; 8] = [69, 161, 93, 202, 120, 126, 76, 185];
+const CANCEL_ORDER: [u8; 8] = [95, 129, 237, 240, 8, 49, 223, 132];
+
+pub fn state_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"drift_state"], &PROGRAM_ID).0
+}
+
+pub fn signer_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"drift_signer"], &PROGRAM_ID).0
+}
+
+pub fn user_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user", authority.as_ref(), &SUB_ACCOUNT_ID.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn user_stats_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"user_stats", authority.as_ref()], &PROGRAM_ID).0
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerpDirection {
+    Long,
+    Short,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PerpOrderType {
+    Market,
+    Limit,
+}
+
+/// The subset of Drift's `OrderParams` a bot may set. Trigger and oracle-offset
+/// orders aren't exposed; everything else is filled with Drift's defaults.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PerpOrderParams {
+    pub order_type: PerpOrderType,
+    pub direction: PerpDirection,
+    pub market_index: u16,
+    pub base_asset_amount: u64,
+    pub price: u64,
+    pub reduce_only: bool,
+    pub post_only: bool,
+    pub immediate_or_cancel: bool,
+    pub max_ts: Option<i64>,
+}
+
+/// Drift's `OrderParams`, in wire order.
+#[derive(AnchorSerialize)]
+struct OrderParams {
+    order_type: u8,
+    market_type: u8,
+    direction: u8,
+    user_order_id: u8,
+    base_asset_amount: u64,
+    price: u64,
+    market_index: u16,
+    reduce_only: bool,
+    post_only: u8,
+    immediate_or_cancel: bool,
+    max_ts: Option<i64>,
+    trigger_price: Option<u64>,
+    trigger_condition: u8,
+    oracle_price_offset: Option<i32>,
+    auction_duration: Option<u8>,
+    auction_start_price: Option<i64>,
+    auction_end_price: Option<i64>,
+}
+
+impl From<PerpOrderParams> for OrderParams {
+    fn from(params: PerpOrderParams) -> Self {
+        Self {
+            order_type: params.order_type as u8,
+            market_type: 1, // MarketType::Perp
+            direction: params.direction as u8,
+            user_order_id: 0,
+            base_asset_amount: params.base_asset_amount,
+            price: params.price,
+            market_index: params.market_index,
+            reduce_only: params.reduce_only,
+            post_only: if params.post_only { 1 } else { 0 }, // PostOnlyParam::MustPostOnly
+            immediate_or_cancel: params.immediate_or_cancel,
+            max_ts: params.max_ts,
+            trigger_price: None,
+            trigger_condition: 0,
+            oracle_price_offset: None,
+            auction_duration: None,
+            auction_start_price: None,
+            auction_end_price: None,
+        }
+    }
+}
+
+fn invoke_drift<'info>(
+    data: Vec<u8>,
+    metas: Vec<AccountMeta>,
+    account_infos: &[AccountInfo<'info>],
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    invoke_vault_signed(
+        PROGRAM_ID,
+        data,
+        metas,
+        account_infos,
+        remaining_accounts,
+        vault_seeds,
+    )
+}
+
+/// Open the vault's Drift user-stats and sub-account, rent paid by `payer`.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize<'info>(
+    drift_program: &AccountInfo<'info>,
+    state: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    user_stats: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    rent: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    invoke_drift(
+        INITIALIZE_USER_STATS.to_vec(),
+        vec![
+            AccountMeta::new(user_stats.key(), false),
+            AccountMeta::new(state.key(), false),
+            AccountMeta::new_readonly(vault.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(System::id(), false),
+        ],
+        &[
+            drift_program.clone(),
+            user_stats.clone(),
+            state.clone(),
+            vault.clone(),
+            payer.clone(),
+            rent.clone(),
+            system_program.clone(),
+        ],
+        &[],
+        vault_seeds,
+    )?;
+
+    let mut data = INITIALIZE_USER.to_vec();
+    SUB_ACCOUNT_ID.serialize(&mut data)?;
+    let mut name = [b' '; 32];
+    name[..7].copy_from_slice(b"GentDex");
+    name.serialize(&mut data)?;
+    invoke_drift(
+        data,
+        vec![
+            AccountMeta::new(user.key(), false),
+            AccountMeta::new(user_stats.key(), false),
+            AccountMeta::new(state.key(), false),
+            AccountMeta::new_readonly(vault.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(System::id(), false),
+        ],
+        &[
+            drift_program.clone(),
+            user.clone(),
+            user_stats.clone(),
+            state.clone(),
+            vault.clone(),
+            payer.clone(),
+            rent.clone(),
+            system_program.clone(),
+        ],
+        &[],
+        vault_seeds,
+    )
+}
+
+/// Token-transfer accounts shared by Drift `deposit` and `withdraw`.
+pub struct CollateralAccounts<'a, 'info> {
+    pub drift_program: &'a AccountInfo<'info>,
+    pub state: &'a AccountInfo<'info>,
+    pub user: &'a AccountInfo<'info>,
+    pub user_stats: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub spot_market_vault: &'a AccountInfo<'info>,
+    pub drift_signer: &'a AccountInfo<'info>,
+    pub token_account: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+/// Move `amount` of SOL collateral from the vault's WSOL account into Drift.
+pub fn deposit<'info>(
+    accounts: &CollateralAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    let mut data = DEPOSIT.to_vec();
+    (SOL_SPOT_MARKET_INDEX, amount, false).serialize(&mut data)?;
+    invoke_drift(
+        data,
+        vec![
+            AccountMeta::new_readonly(accounts.state.key(), false),
+            AccountMeta::new(accounts.user.key(), false),
+            AccountMeta::new(accounts.user_stats.key(), false),
+            AccountMeta::new_readonly(accounts.vault.key(), true),
+            AccountMeta::new(accounts.spot_market_vault.key(), false),
+            AccountMeta::new(accounts.token_account.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        &[
+            accounts.drift_program.clone(),
+            accounts.state.clone(),
+            accounts.user.clone(),
+            accounts.user_stats.clone(),
+            accounts.vault.clone(),
+            accounts.spot_market_vault.clone(),
+            accounts.token_account.clone(),
+            accounts.token_program.clone(),
+        ],
+        remaining_accounts,
+        vault_seeds,
+    )
+}
+
+/// Withdraw `amount` of SOL collateral from Drift into the vault's WSOL account.
+pub fn withdraw<'info>(
+    accounts: &CollateralAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    let mut data = WITHDRAW.to_vec();
+    (SOL_SPOT_MARKET_INDEX, amount, false).serialize(&mut data)?;
+    invoke_drift(
+        data,
+        vec![
+            AccountMeta::new_readonly(accounts.state.key(), false),
+            AccountMeta::new(accounts.user.key(), false),
+            AccountMeta::new(accounts.user_stats.key(), false),
+            AccountMeta::new_readonly(accounts.vault.key(), true),
+            AccountMeta::new(accounts.spot_market_vault.key(), false),
+            AccountMeta::new_readonly(accounts.drift_signer.key(), false),
+            AccountMeta::new(accounts.token_account.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        &[
+            accounts.drift_program.clone(),
+            accounts.state.clone(),
+            accounts.user.clone(),
+            accounts.user_stats.clone(),
+            accounts.vault.clone(),
+            accounts.spot_market_vault.clone(),
+            accounts.drift_signer.clone(),
+            accounts.token_account.clone(),
+            accounts.token_program.clone(),
+        ],
+        remaining_accounts,
+        vault_seeds,
+    )
+}
+
+/// Place a perp order on the vault's sub-account.
+pub fn place_perp_order<'info>(
+    drift_program: &AccountInfo<'info>,
+    state: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_seeds: &[&[u8]],
+    params: PerpOrderParams,
+) -> Result<()> {
+    let mut data = PLACE_PERP_ORDER.to_vec();
+    OrderParams::from(params).serialize(&mut data)?;
+    invoke_drift(
+        data,
+        vec![
+            AccountMeta::new_readonly(state.key(), false),
+            AccountMeta::new(user.key(), false),
+            AccountMeta::new_readonly(vault.key(), true),
+        ],
+        &[drift_program.clone(), state.clone(), user.clone(), vault.clone()],
+        remaining_accounts,
+        vault_seeds,
+    )
+}
+
+/// Cancel one order (or the most recent, if `order_id` is None) on the vault's sub-account.
+pub fn cancel_order<'info>(
+    drift_program: &AccountInfo<'info>,
+    state: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_seeds: &[&[u8]],
+    order_id: Option<u32>,
+) -> Result<()> {
+    let mut data = CANCEL_ORDER.to_vec();
+    order_id.serialize(&mut data)?;
+    invoke_drift(
+        data,
+        vec![
+            AccountMeta::new_readonly(state.key(), false),
+            AccountMeta::new(user.key(), false),
+            AccountMeta::new_readonly(vault.key(), true),
+        ],
+        &[drift_program.clone(), state.clone(), user.clone(), vault.clone()],
+        remaining_accounts,
+        vault_seeds,
+    )
+}
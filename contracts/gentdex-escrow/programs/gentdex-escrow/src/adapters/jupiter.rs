@@ -0,0 +1,150 @@
+//! Jupiter (aggregator v6) adapter.
+//!
+//! Sells the vault's base currency along a route Jupiter's API built for the
+//! vault, with its `route` instruction signed by the vault PDA. The only
+//! adapter that `SELLS_TOKENS`, so token sessions trade here. The bot passes the
+//! instruction data as `route_data` and its accounts as remaining_accounts;
+//! the adapter checks who trades and where the proceeds land, and that the
+//! route sells exactly `amount_in`. Jupiter enforces the quote's slippage,
+//! `reconcile` enforces `minimum_amount_out`.
+//!
+//! remaining_accounts:
+//!   0. token_program
+//!   1. authority            — the vault
+//!   2. source               — vault's WSOL or base token account (writable)
+//!   3. destination          — vault-owned output token account (writable)
+//!   4. destination_account  — unset (Jupiter's program id)
+//!   5. destination_mint
+//!   6. platform_fee_account — unset (Jupiter's program id)
+//!   7. event_authority
+//!   8. program              — Jupiter
+//!
+//! followed by the accounts of the route's legs.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_spl::token::{self, spl_token::native_mint};
+
+use super::{owned_token_account, vault_token_account, DexAdapter, SwapContext, SwapCpi};
+use crate::EscrowError;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// Anchor discriminator of Jupiter's `route` instruction
+const ROUTE_DISCRIMINATOR: [u8; 8] = [229, 23, 203, 151, 122, 227, 173, 42];
+/// `in_amount: u64, quoted_out_amount: u64, slippage_bps: u16, platform_fee_bps: u8`
+/// trail the variable-length route plan
+const ROUTE_ARGS_LEN: usize = 19;
+
+const ACCOUNT_COUNT: usize = 9;
+
+/// Validated Jupiter accounts and route.
+pub struct Jupiter<'info> {
+    source: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    in_amount: u64,
+}
+
+impl<'info> DexAdapter<'info> for Jupiter<'info> {
+    const PROGRAM_ID: Pubkey = PROGRAM_ID;
+    const SELLS_TOKENS: bool = true;
+
+    fn validate_accounts(ctx: &SwapContext<'_, 'info>) -> Result<Self> {
+        let vault = ctx.vault.key();
+        require!(ctx.remaining_accounts.len() >= ACCOUNT_COUNT, EscrowError::InvalidDexAccount);
+        let [
+            token_program,
+            authority,
+            source,
+            destination,
+            destination_token_account,
+            destination_mint,
+            platform_fee_account,
+            _event_authority,
+            program,
+        ] = &ctx.remaining_accounts[..ACCOUNT_COUNT]
+        else {
+            unreachable!()
+        };
+
+        let data = ctx.route_data;
+        require!(
+            data.len() >= ROUTE_DISCRIMINATOR.len() + ROUTE_ARGS_LEN && data.starts_with(&ROUTE_DISCRIMINATOR),
+            EscrowError::InvalidDexAccount
+        );
+        let args = &data[data.len() - ROUTE_ARGS_LEN..];
+        let in_amount = u64::from_le_bytes(args[..8].try_into().unwrap());
+
+        require_keys_eq!(token_program.key(), token::ID, EscrowError::InvalidDexAccount);
+        require_keys_eq!(authority.key(), vault, EscrowError::InvalidDexAccount);
+        require_keys_eq!(program.key(), PROGRAM_ID, EscrowError::InvalidDexAccount);
+        // Proceeds stay in the vault's own account, with no platform fee taken
+        require_keys_eq!(destination_token_account.key(), PROGRAM_ID, EscrowError::InvalidDexAccount);
+        require_keys_eq!(platform_fee_account.key(), PROGRAM_ID, EscrowError::InvalidDexAccount);
+
+        let input = match ctx.base_account {
+            Some(base_account) => {
+                require_keys_eq!(source.key(), base_account.key(), EscrowError::InvalidDexAccount);
+                owned_token_account(source, &vault)?
+            }
+            None => vault_token_account(source, &vault, &native_mint::ID)?,
+        };
+        let output = vault_token_account(destination, &vault, &destination_mint.key())?;
+        require_keys_neq!(output.mint, input.mint, EscrowError::InvalidDexAccount);
+
+        // The vault signs for the whole route, so a leg may only pass through
+        // vault accounts holding nothing: what it holds elsewhere stays put
+        for info in &ctx.remaining_accounts[ACCOUNT_COUNT..] {
+            if info.key() == source.key() || info.key() == destination.key() {
+                continue;
+            }
+            if let Ok(account) = owned_token_account(info, &vault) {
+                require!(account.amount == 0, EscrowError::InvalidDexAccount);
+            }
+        }
+
+        Ok(Self {
+            source: source.clone(),
+            destination: destination.clone(),
+            token_program: token_program.clone(),
+            in_amount,
+        })
+    }
+
+    fn build_cpi(
+        &self,
+        ctx: &SwapContext<'_, 'info>,
+        amount_in: u64,
+        _minimum_amount_out: u64,
+    ) -> Result<SwapCpi<'info>> {
+        require!(self.in_amount == amount_in, EscrowError::InvalidDexAccount);
+
+        let vault = ctx.vault.key();
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|info| AccountMeta {
+                    pubkey: info.key(),
+                    is_signer: info.key() == vault,
+                    is_writable: info.is_writable,
+                })
+                .collect(),
+            data: ctx.route_data.to_vec(),
+        };
+
+        let mut account_infos = vec![ctx.dex_program.clone()];
+        account_infos.extend_from_slice(ctx.remaining_accounts);
+
+        Ok(SwapCpi {
+            ix,
+            account_infos,
+            input_account: self.source.clone(),
+            output_account: self.destination.clone(),
+            token_program: self.token_program.clone(),
+            input_amount: amount_in,
+        })
+    }
+}
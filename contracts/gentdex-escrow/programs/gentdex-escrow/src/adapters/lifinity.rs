@@ -0,0 +1,148 @@
+//! Lifinity v2 (proactive market maker) adapter.
+//!
+//! Sells SOL into a Lifinity pool with its `swap` instruction, signed by the
+//! vault PDA. Lifinity reprices from its oracle accounts, which are passed
+//! through unchanged; the pool itself validates them against the AMM state.
+//!
+//! remaining_accounts:
+//!   0.  authority        — pool authority PDA
+//!   1.  amm              — pool state
+//!   2.  source_info      — vault-owned WSOL account (writable)
+//!   3.  destination_info — vault-owned output token account (writable)
+//!   4.  swap_source      — pool's SOL reserve (writable)
+//!   5.  swap_destination — pool's output reserve (writable)
+//!   6.  pool_mint        — (writable)
+//!   7.  fee_account      — (writable)
+//!   8.  token_program
+//!   9.  oracle_main_account
+//!   10. oracle_sub_account
+//!   11. oracle_pc_account
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_spl::token::{self, spl_token::native_mint};
+
+use super::{owned_token_account, vault_token_account, DexAdapter, SwapContext, SwapCpi};
+use crate::EscrowError;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEyPy64bL7aD3c");
+
+/// Anchor discriminator for `swap`
+const SWAP: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+const ACCOUNT_COUNT: usize = 12;
+
+/// Validated Lifinity accounts.
+pub struct Lifinity<'info> {
+    authority: AccountInfo<'info>,
+    amm: AccountInfo<'info>,
+    source_info: AccountInfo<'info>,
+    destination_info: AccountInfo<'info>,
+    swap_source: AccountInfo<'info>,
+    swap_destination: AccountInfo<'info>,
+    pool_mint: AccountInfo<'info>,
+    fee_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    oracle_main: AccountInfo<'info>,
+    oracle_sub: AccountInfo<'info>,
+    oracle_pc: AccountInfo<'info>,
+}
+
+impl<'info> DexAdapter<'info> for Lifinity<'info> {
+    const PROGRAM_ID: Pubkey = PROGRAM_ID;
+
+    fn validate_accounts(ctx: &SwapContext<'_, 'info>) -> Result<Self> {
+        let vault = ctx.vault;
+        require!(ctx.remaining_accounts.len() >= ACCOUNT_COUNT, EscrowError::InvalidDexAccount);
+        let [authority, amm, source_info, destination_info, swap_source, swap_destination, pool_mint, fee_account, token_program, oracle_main, oracle_sub, oracle_pc] =
+            &ctx.remaining_accounts[..ACCOUNT_COUNT]
+        else {
+            unreachable!()
+        };
+
+        // Pool state and its authority PDA
+        require_keys_eq!(*amm.owner, PROGRAM_ID, EscrowError::InvalidDexAccount);
+        let (expected_authority, _) = Pubkey::find_program_address(&[amm.key().as_ref()], &PROGRAM_ID);
+        require_keys_eq!(authority.key(), expected_authority, EscrowError::InvalidDexAccount);
+        require_keys_eq!(token_program.key(), token::ID, EscrowError::InvalidDexAccount);
+
+        // Reserves belong to the pool; we only ever sell into its SOL side
+        let reserve_in = owned_token_account(swap_source, &authority.key())?;
+        let reserve_out = owned_token_account(swap_destination, &authority.key())?;
+        require_keys_eq!(reserve_in.mint, native_mint::ID, EscrowError::InvalidDexAccount);
+
+        // Trader token accounts must belong to the vault
+        vault_token_account(source_info, &vault.key(), &native_mint::ID)?;
+        vault_token_account(destination_info, &vault.key(), &reserve_out.mint)?;
+
+        Ok(Self {
+            authority: authority.clone(),
+            amm: amm.clone(),
+            source_info: source_info.clone(),
+            destination_info: destination_info.clone(),
+            swap_source: swap_source.clone(),
+            swap_destination: swap_destination.clone(),
+            pool_mint: pool_mint.clone(),
+            fee_account: fee_account.clone(),
+            token_program: token_program.clone(),
+            oracle_main: oracle_main.clone(),
+            oracle_sub: oracle_sub.clone(),
+            oracle_pc: oracle_pc.clone(),
+        })
+    }
+
+    fn build_cpi(
+        &self,
+        ctx: &SwapContext<'_, 'info>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<SwapCpi<'info>> {
+        let mut data = SWAP.to_vec();
+        amount_in.serialize(&mut data)?;
+        minimum_amount_out.serialize(&mut data)?;
+
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.authority.key(), false),
+                AccountMeta::new(self.amm.key(), false),
+                AccountMeta::new_readonly(ctx.vault.key(), true),
+                AccountMeta::new(self.source_info.key(), false),
+                AccountMeta::new(self.destination_info.key(), false),
+                AccountMeta::new(self.swap_source.key(), false),
+                AccountMeta::new(self.swap_destination.key(), false),
+                AccountMeta::new(self.pool_mint.key(), false),
+                AccountMeta::new(self.fee_account.key(), false),
+                AccountMeta::new_readonly(token::ID, false),
+                AccountMeta::new_readonly(self.oracle_main.key(), false),
+                AccountMeta::new_readonly(self.oracle_sub.key(), false),
+                AccountMeta::new_readonly(self.oracle_pc.key(), false),
+            ],
+            data,
+        };
+
+        Ok(SwapCpi {
+            ix,
+            account_infos: vec![
+                ctx.dex_program.clone(),
+                self.authority.clone(),
+                self.amm.clone(),
+                ctx.vault.clone(),
+                self.source_info.clone(),
+                self.destination_info.clone(),
+                self.swap_source.clone(),
+                self.swap_destination.clone(),
+                self.pool_mint.clone(),
+                self.fee_account.clone(),
+                self.token_program.clone(),
+                self.oracle_main.clone(),
+                self.oracle_sub.clone(),
+                self.oracle_pc.clone(),
+            ],
+            input_account: self.source_info.clone(),
+            output_account: self.destination_info.clone(),
+            token_program: self.token_program.clone(),
+            input_amount: amount_in,
+        })
+    }
+}
@@ -0,0 +1,145 @@
+//! marginfi v2 lending adapter.
+//!
+//! Lends idle SOL from the trading balance into a whitelisted marginfi group.
+//! The vault PDA is the authority of its marginfi account, so collateral can
+//! only come back to the vault's own WSOL account. Bank and oracle accounts
+//! marginfi needs for health checks are passed via remaining_accounts.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+
+use super::invoke_vault_signed;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA");
+
+// Anchor discriminators
+const MARGINFI_ACCOUNT_INITIALIZE: [u8; 8] = [43, 78, 61, 255, 148, 52, 249, 154];
+const LENDING_ACCOUNT_DEPOSIT: [u8; 8] = [171, 94, 235, 103, 82, 64, 212, 140];
+const LENDING_ACCOUNT_WITHDRAW: [u8; 8] = [36, 72, 74, 19, 210, 210, 192, 192];
+
+pub fn is_whitelisted_group(group: &Pubkey) -> bool {
+    let whitelisted: [Pubkey; 1] = [
+        // marginfi main pool
+        pubkey!("4qp6Fx6tnZkY5Wropq9wUYgtFxXKwE6viZxFHg3rdAG8"),
+    ];
+    whitelisted.contains(group)
+}
+
+/// Create the vault's marginfi account. `marginfi_account` is a fresh keypair
+/// account that co-signs the transaction; rent is paid by `fee_payer`.
+pub fn initialize_account<'info>(
+    marginfi_program: &AccountInfo<'info>,
+    group: &AccountInfo<'info>,
+    marginfi_account: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    fee_payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    invoke_vault_signed(
+        PROGRAM_ID,
+        MARGINFI_ACCOUNT_INITIALIZE.to_vec(),
+        vec![
+            AccountMeta::new_readonly(group.key(), false),
+            AccountMeta::new(marginfi_account.key(), true),
+            AccountMeta::new_readonly(vault.key(), true),
+            AccountMeta::new(fee_payer.key(), true),
+            AccountMeta::new_readonly(System::id(), false),
+        ],
+        &[
+            marginfi_program.clone(),
+            group.clone(),
+            marginfi_account.clone(),
+            vault.clone(),
+            fee_payer.clone(),
+            system_program.clone(),
+        ],
+        &[],
+        vault_seeds,
+    )
+}
+
+/// Accounts shared by marginfi deposits and withdrawals.
+pub struct LendingAccounts<'a, 'info> {
+    pub marginfi_program: &'a AccountInfo<'info>,
+    pub group: &'a AccountInfo<'info>,
+    pub marginfi_account: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub bank: &'a AccountInfo<'info>,
+    pub token_account: &'a AccountInfo<'info>,
+    pub liquidity_vault_authority: &'a AccountInfo<'info>,
+    pub liquidity_vault: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+/// Deposit `amount` from the vault's WSOL account into the bank.
+pub fn deposit<'info>(
+    accounts: &LendingAccounts<'_, 'info>,
+    vault_seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    let mut data = LENDING_ACCOUNT_DEPOSIT.to_vec();
+    amount.serialize(&mut data)?;
+    invoke_vault_signed(
+        PROGRAM_ID,
+        data,
+        vec![
+            AccountMeta::new_readonly(accounts.group.key(), false),
+            AccountMeta::new(accounts.marginfi_account.key(), false),
+            AccountMeta::new_readonly(accounts.vault.key(), true),
+            AccountMeta::new(accounts.bank.key(), false),
+            AccountMeta::new(accounts.token_account.key(), false),
+            AccountMeta::new(accounts.liquidity_vault.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        &[
+            accounts.marginfi_program.clone(),
+            accounts.group.clone(),
+            accounts.marginfi_account.clone(),
+            accounts.vault.clone(),
+            accounts.bank.clone(),
+            accounts.token_account.clone(),
+            accounts.liquidity_vault.clone(),
+            accounts.token_program.clone(),
+        ],
+        &[],
+        vault_seeds,
+    )
+}
+
+/// Withdraw the vault's entire position in the bank into its WSOL account.
+pub fn withdraw_all<'info>(
+    accounts: &LendingAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    let mut data = LENDING_ACCOUNT_WITHDRAW.to_vec();
+    (0u64, Some(true)).serialize(&mut data)?;
+    invoke_vault_signed(
+        PROGRAM_ID,
+        data,
+        vec![
+            AccountMeta::new_readonly(accounts.group.key(), false),
+            AccountMeta::new(accounts.marginfi_account.key(), false),
+            AccountMeta::new_readonly(accounts.vault.key(), true),
+            AccountMeta::new(accounts.bank.key(), false),
+            AccountMeta::new(accounts.token_account.key(), false),
+            AccountMeta::new_readonly(accounts.liquidity_vault_authority.key(), false),
+            AccountMeta::new(accounts.liquidity_vault.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        &[
+            accounts.marginfi_program.clone(),
+            accounts.group.clone(),
+            accounts.marginfi_account.clone(),
+            accounts.vault.clone(),
+            accounts.bank.clone(),
+            accounts.token_account.clone(),
+            accounts.liquidity_vault_authority.clone(),
+            accounts.liquidity_vault.clone(),
+            accounts.token_program.clone(),
+        ],
+        remaining_accounts,
+        vault_seeds,
+    )
+}
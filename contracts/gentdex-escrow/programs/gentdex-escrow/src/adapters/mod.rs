@@ -0,0 +1,243 @@
+//! DEX adapters: per-venue account validation and CPI construction.
+//!
+//! Adapters sell the vault's trading balance. A SOL session's leg is wrapped
+//! into a vault-owned WSOL account just before the CPI; a token session's is
+//! sold straight from the vault's base token account, through venues whose
+//! adapter `SELLS_TOKENS`. Proceeds land in a vault-owned token account. All
+//! adapters run inside a `SwapGuard`. Positions leave through
+//! `withdraw_position` to the user.
+//!
+//! Adding a venue: a module with a `DexAdapter` impl and an entry in `swap`.
+//! A whitelisted venue without one can't be traded through.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, SyncNative, TokenAccount};
+
+use crate::session::move_lamports;
+use crate::EscrowError;
+
+pub mod drift;
+pub mod jupiter;
+pub mod lifinity;
+pub mod marginfi;
+pub mod openbook;
+pub mod phoenix;
+pub mod solfi;
+
+/// Accounts and signer seeds every adapter works from.
+pub struct SwapContext<'a, 'info> {
+    pub dex_program: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub bot: &'a AccountInfo<'info>,
+    pub vault_seeds: &'a [&'a [u8]],
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+    /// The venue's instruction data, for aggregators whose routes are built
+    /// off-chain; empty for venues whose instruction the adapter builds
+    pub route_data: &'a [u8],
+    /// A token session's base token account, which the route sells from;
+    /// `None` for SOL sessions
+    pub base_account: Option<&'a AccountInfo<'info>>,
+}
+
+/// What a swap delivered to the vault. `mint` is the default pubkey when
+/// nothing was received.
+#[derive(Default)]
+pub struct SwapOutput {
+    pub amount_out: u64,
+    pub mint: Pubkey,
+}
+
+/// A swap instruction built by an adapter, plus where its input leg and
+/// proceeds live.
+pub struct SwapCpi<'info> {
+    pub ix: Instruction,
+    pub account_infos: Vec<AccountInfo<'info>>,
+    /// Vault-owned account the input is sold from: the WSOL account the SOL
+    /// leg is wrapped into, or a token session's base token account
+    pub input_account: AccountInfo<'info>,
+    /// Vault-owned token account the proceeds land in
+    pub output_account: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// Input actually sold; venues trading in lots may round `amount_in` down
+    pub input_amount: u64,
+}
+
+/// A venue the vault can sell its balance into.
+pub trait DexAdapter<'info>: Sized {
+    /// Program the adapter CPIs into
+    const PROGRAM_ID: Pubkey;
+
+    /// Whether the adapter can sell a token session's base mint, not just SOL
+    const SELLS_TOKENS: bool = false;
+
+    /// Check `remaining_accounts` against the venue's on-chain state and keep
+    /// what `build_cpi` needs.
+    fn validate_accounts(ctx: &SwapContext<'_, 'info>) -> Result<Self>;
+
+    /// Build the venue's swap instruction selling `amount_in` of the base currency.
+    fn build_cpi(
+        &self,
+        ctx: &SwapContext<'_, 'info>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<SwapCpi<'info>>;
+
+    /// Measure what the CPI delivered to `cpi.output_account` and enforce
+    /// `minimum_amount_out`.
+    fn reconcile(
+        &self,
+        cpi: &SwapCpi<'info>,
+        output_before: u64,
+        minimum_amount_out: u64,
+    ) -> Result<SwapOutput> {
+        let output = TokenAccount::try_deserialize(&mut &cpi.output_account.try_borrow_data()?[..])?;
+        let amount_out = output.amount
+            .checked_sub(output_before)
+            .ok_or(EscrowError::MathOverflow)?;
+        require!(amount_out >= minimum_amount_out, EscrowError::SlippageExceeded);
+
+        Ok(SwapOutput {
+            amount_out,
+            mint: output.mint,
+        })
+    }
+}
+
+/// Dispatch a swap to the adapter registered for the context's DEX program.
+/// Returns the output token received.
+pub fn swap(ctx: &SwapContext, amount_in: u64, minimum_amount_out: u64) -> Result<SwapOutput> {
+    match ctx.dex_program.key() {
+        phoenix::PROGRAM_ID => run::<phoenix::Phoenix>(ctx, amount_in, minimum_amount_out),
+        openbook::PROGRAM_ID => run::<openbook::OpenBook>(ctx, amount_in, minimum_amount_out),
+        lifinity::PROGRAM_ID => run::<lifinity::Lifinity>(ctx, amount_in, minimum_amount_out),
+        solfi::PROGRAM_ID => run::<solfi::SolFi>(ctx, amount_in, minimum_amount_out),
+        jupiter::PROGRAM_ID => run::<jupiter::Jupiter>(ctx, amount_in, minimum_amount_out),
+        _ => err!(EscrowError::DexAdapterMissing),
+    }
+}
+
+/// `swap` up to the CPI: validate the route's accounts and build its
+/// instruction, moving nothing. For dry runs.
+pub fn check_route(ctx: &SwapContext, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+    match ctx.dex_program.key() {
+        phoenix::PROGRAM_ID => check::<phoenix::Phoenix>(ctx, amount_in, minimum_amount_out),
+        openbook::PROGRAM_ID => check::<openbook::OpenBook>(ctx, amount_in, minimum_amount_out),
+        lifinity::PROGRAM_ID => check::<lifinity::Lifinity>(ctx, amount_in, minimum_amount_out),
+        solfi::PROGRAM_ID => check::<solfi::SolFi>(ctx, amount_in, minimum_amount_out),
+        jupiter::PROGRAM_ID => check::<jupiter::Jupiter>(ctx, amount_in, minimum_amount_out),
+        _ => err!(EscrowError::DexAdapterMissing),
+    }
+}
+
+fn check<'info, A: DexAdapter<'info>>(
+    ctx: &SwapContext<'_, 'info>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    require!(A::SELLS_TOKENS || ctx.base_account.is_none(), EscrowError::BaseCurrencyMismatch);
+    A::validate_accounts(ctx)?.build_cpi(ctx, amount_in, minimum_amount_out)?;
+    Ok(())
+}
+
+/// Validate, wrap a SOL leg, run the adapter's instruction signed by the
+/// vault PDA, and reconcile what came back.
+fn run<'info, A: DexAdapter<'info>>(
+    ctx: &SwapContext<'_, 'info>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<SwapOutput> {
+    debug_assert_eq!(ctx.dex_program.key(), A::PROGRAM_ID);
+    require!(A::SELLS_TOKENS || ctx.base_account.is_none(), EscrowError::BaseCurrencyMismatch);
+    let adapter = A::validate_accounts(ctx)?;
+    let cpi = adapter.build_cpi(ctx, amount_in, minimum_amount_out)?;
+
+    if ctx.base_account.is_none() {
+        wrap_sol(ctx.vault, &cpi.input_account, &cpi.token_program, cpi.input_amount)?;
+    }
+    let output_before = token_amount(&cpi.output_account)?;
+
+    invoke_signed(&cpi.ix, &cpi.account_infos, &[ctx.vault_seeds])?;
+
+    adapter.reconcile(&cpi, output_before, minimum_amount_out)
+}
+
+/// Load an SPL token account and check it's owned (as in token authority) by `owner`.
+pub fn owned_token_account(info: &AccountInfo, owner: &Pubkey) -> Result<TokenAccount> {
+    require_keys_eq!(*info.owner, token::ID, EscrowError::InvalidDexAccount);
+    let account = TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require_keys_eq!(account.owner, *owner, EscrowError::InvalidDexAccount);
+    Ok(account)
+}
+
+/// Load an SPL token account and check it belongs to the vault and holds `mint`.
+pub fn vault_token_account(
+    info: &AccountInfo,
+    vault: &Pubkey,
+    mint: &Pubkey,
+) -> Result<TokenAccount> {
+    let account = owned_token_account(info, vault)?;
+    require_keys_eq!(account.mint, *mint, EscrowError::InvalidDexAccount);
+    Ok(account)
+}
+
+/// Move `amount` lamports from the vault PDA into its WSOL account and sync it,
+/// so the DEX can pull the SOL leg as a regular token transfer.
+pub fn wrap_sol<'info>(
+    vault: &AccountInfo<'info>,
+    wsol_account: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    move_lamports(vault, wsol_account, amount)?;
+
+    token::sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        SyncNative {
+            account: wsol_account.to_account_info(),
+        },
+    ))
+}
+
+/// Invoke `program_id` signed by the vault PDA, appending `remaining_accounts`
+/// (market, oracle, bank accounts the venue needs) to both the metas and infos.
+pub fn invoke_vault_signed<'info>(
+    program_id: Pubkey,
+    data: Vec<u8>,
+    mut metas: Vec<AccountMeta>,
+    account_infos: &[AccountInfo<'info>],
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    metas.extend(remaining_accounts.iter().map(|info| AccountMeta {
+        pubkey: info.key(),
+        is_signer: false,
+        is_writable: info.is_writable,
+    }));
+    let ix = Instruction {
+        program_id,
+        accounts: metas,
+        data,
+    };
+
+    let mut infos = account_infos.to_vec();
+    infos.extend_from_slice(remaining_accounts);
+    invoke_signed(&ix, &infos, &[vault_seeds])?;
+    Ok(())
+}
+
+/// Read a token account's amount.
+pub fn token_amount(info: &AccountInfo) -> Result<u64> {
+    Ok(TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?.amount)
+}
+
+/// Read a pubkey at `offset` in raw account data (bounds checked by the caller).
+pub fn pubkey_at(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+/// Read a little-endian u64 at `offset` in raw account data (bounds checked by the caller).
+pub fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
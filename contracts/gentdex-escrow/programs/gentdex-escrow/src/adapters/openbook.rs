@@ -0,0 +1,278 @@
+//! OpenBook v2 (central limit order book) adapter.
+//!
+//! Sells SOL into a SOL/quote market with `place_take_order`, signed by the
+//! vault PDA. Take orders settle straight into the vault-owned token accounts
+//! in the same CPI, so no open-orders account or separate settle step is
+//! needed. Orders are fill-or-kill so no unspent WSOL is left behind.
+//!
+//! remaining_accounts:
+//!   0.  market             — OpenBook market (writable)
+//!   1.  market_authority   — market's authority PDA
+//!   2.  bids               — (writable)
+//!   3.  asks               — (writable)
+//!   4.  market_base_vault  — (writable)
+//!   5.  market_quote_vault — (writable)
+//!   6.  event_heap         — (writable)
+//!   7.  base_account       — vault-owned WSOL account (writable)
+//!   8.  quote_account      — vault-owned quote token account (writable)
+//!   9.  token_program
+//!   10. system_program
+//!   11. oracle_a           — only if the market has one
+//!   12. oracle_b           — only if the market has one
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_spl::token::{self, spl_token::native_mint};
+
+use super::{pubkey_at, u64_at, vault_token_account, DexAdapter, SwapContext, SwapCpi};
+use crate::EscrowError;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb");
+
+/// Anchor discriminator for `place_take_order`
+const PLACE_TAKE_ORDER: [u8; 8] = [3, 44, 71, 3, 26, 199, 203, 85];
+/// `Side::Ask` — selling base (SOL) for quote
+const SIDE_ASK: u8 = 1;
+/// `PlaceOrderType::FillOrKill`
+const ORDER_TYPE_FILL_OR_KILL: u8 = 5;
+/// Max book levels matched per order
+const MATCH_LIMIT: u8 = 16;
+
+// Market field offsets (after the 8-byte Anchor discriminator)
+const MARKET_AUTHORITY_OFFSET: usize = 16;
+const OPEN_ORDERS_ADMIN_OFFSET: usize = 88;
+const BIDS_OFFSET: usize = 200;
+const ASKS_OFFSET: usize = 232;
+const EVENT_HEAP_OFFSET: usize = 264;
+const ORACLE_A_OFFSET: usize = 296;
+const ORACLE_B_OFFSET: usize = 328;
+const QUOTE_LOT_SIZE_OFFSET: usize = 448;
+const BASE_LOT_SIZE_OFFSET: usize = 456;
+const BASE_MINT_OFFSET: usize = 576;
+const QUOTE_MINT_OFFSET: usize = 608;
+const MARKET_BASE_VAULT_OFFSET: usize = 640;
+const MARKET_QUOTE_VAULT_OFFSET: usize = 680;
+const MARKET_MIN_LEN: usize = 712;
+
+const BASE_ACCOUNT_COUNT: usize = 11;
+
+/// OpenBook's `PlaceTakeOrderArgs`, in wire order.
+#[derive(AnchorSerialize)]
+struct PlaceTakeOrderArgs {
+    side: u8,
+    price_lots: i64,
+    max_base_lots: i64,
+    max_quote_lots_including_fees: i64,
+    order_type: u8,
+    limit: u8,
+}
+
+/// The parts of the market account the adapter validates against.
+struct Market {
+    market_authority: Pubkey,
+    open_orders_admin: Pubkey,
+    bids: Pubkey,
+    asks: Pubkey,
+    event_heap: Pubkey,
+    oracle_a: Pubkey,
+    oracle_b: Pubkey,
+    quote_lot_size: u64,
+    base_lot_size: u64,
+    base_mint: Pubkey,
+    quote_mint: Pubkey,
+    market_base_vault: Pubkey,
+    market_quote_vault: Pubkey,
+}
+
+impl Market {
+    fn load(market: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*market.owner, PROGRAM_ID, EscrowError::InvalidDexAccount);
+        let data = market.try_borrow_data()?;
+        require!(data.len() >= MARKET_MIN_LEN, EscrowError::InvalidDexAccount);
+
+        Ok(Self {
+            market_authority: pubkey_at(&data, MARKET_AUTHORITY_OFFSET),
+            open_orders_admin: pubkey_at(&data, OPEN_ORDERS_ADMIN_OFFSET),
+            bids: pubkey_at(&data, BIDS_OFFSET),
+            asks: pubkey_at(&data, ASKS_OFFSET),
+            event_heap: pubkey_at(&data, EVENT_HEAP_OFFSET),
+            oracle_a: pubkey_at(&data, ORACLE_A_OFFSET),
+            oracle_b: pubkey_at(&data, ORACLE_B_OFFSET),
+            quote_lot_size: u64_at(&data, QUOTE_LOT_SIZE_OFFSET),
+            base_lot_size: u64_at(&data, BASE_LOT_SIZE_OFFSET),
+            base_mint: pubkey_at(&data, BASE_MINT_OFFSET),
+            quote_mint: pubkey_at(&data, QUOTE_MINT_OFFSET),
+            market_base_vault: pubkey_at(&data, MARKET_BASE_VAULT_OFFSET),
+            market_quote_vault: pubkey_at(&data, MARKET_QUOTE_VAULT_OFFSET),
+        })
+    }
+}
+
+/// Validated OpenBook accounts.
+pub struct OpenBook<'info> {
+    market: AccountInfo<'info>,
+    market_authority: AccountInfo<'info>,
+    bids: AccountInfo<'info>,
+    asks: AccountInfo<'info>,
+    market_base_vault: AccountInfo<'info>,
+    market_quote_vault: AccountInfo<'info>,
+    event_heap: AccountInfo<'info>,
+    base_account: AccountInfo<'info>,
+    quote_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    oracle_a: Option<AccountInfo<'info>>,
+    oracle_b: Option<AccountInfo<'info>>,
+    header: Market,
+}
+
+impl<'info> DexAdapter<'info> for OpenBook<'info> {
+    const PROGRAM_ID: Pubkey = PROGRAM_ID;
+
+    fn validate_accounts(ctx: &SwapContext<'_, 'info>) -> Result<Self> {
+        let vault = ctx.vault;
+        let accounts = ctx.remaining_accounts;
+        require!(accounts.len() >= BASE_ACCOUNT_COUNT, EscrowError::InvalidDexAccount);
+        let [market, market_authority, bids, asks, market_base_vault, market_quote_vault, event_heap, base_account, quote_account, token_program, system_program] =
+            &accounts[..BASE_ACCOUNT_COUNT]
+        else {
+            unreachable!()
+        };
+
+        // Market and the book accounts it points at
+        let header = Market::load(market)?;
+        require_keys_eq!(header.base_mint, native_mint::ID, EscrowError::InvalidDexAccount);
+        require_keys_eq!(market_authority.key(), header.market_authority, EscrowError::InvalidDexAccount);
+        require_keys_eq!(bids.key(), header.bids, EscrowError::InvalidDexAccount);
+        require_keys_eq!(asks.key(), header.asks, EscrowError::InvalidDexAccount);
+        require_keys_eq!(event_heap.key(), header.event_heap, EscrowError::InvalidDexAccount);
+        require_keys_eq!(market_base_vault.key(), header.market_base_vault, EscrowError::InvalidDexAccount);
+        require_keys_eq!(market_quote_vault.key(), header.market_quote_vault, EscrowError::InvalidDexAccount);
+        require_keys_eq!(token_program.key(), token::ID, EscrowError::InvalidDexAccount);
+        require_keys_eq!(system_program.key(), System::id(), EscrowError::InvalidDexAccount);
+        // Permissioned markets need an admin co-signature the vault can't provide
+        require_keys_eq!(header.open_orders_admin, Pubkey::default(), EscrowError::InvalidDexAccount);
+        require!(
+            header.base_lot_size > 0 && header.quote_lot_size > 0,
+            EscrowError::InvalidDexAccount
+        );
+
+        // Oracles are optional accounts; absent ones are passed as the program ID
+        let mut oracles = accounts[BASE_ACCOUNT_COUNT..].iter();
+        let mut oracle_meta = |expected: Pubkey| -> Result<Option<AccountInfo<'info>>> {
+            if expected == Pubkey::default() {
+                return Ok(None);
+            }
+            let oracle = oracles.next().ok_or(EscrowError::InvalidDexAccount)?;
+            require_keys_eq!(oracle.key(), expected, EscrowError::InvalidDexAccount);
+            Ok(Some(oracle.clone()))
+        };
+        let oracle_a = oracle_meta(header.oracle_a)?;
+        let oracle_b = oracle_meta(header.oracle_b)?;
+
+        // Trader token accounts must belong to the vault
+        vault_token_account(base_account, &vault.key(), &header.base_mint)?;
+        vault_token_account(quote_account, &vault.key(), &header.quote_mint)?;
+
+        Ok(Self {
+            market: market.clone(),
+            market_authority: market_authority.clone(),
+            bids: bids.clone(),
+            asks: asks.clone(),
+            market_base_vault: market_base_vault.clone(),
+            market_quote_vault: market_quote_vault.clone(),
+            event_heap: event_heap.clone(),
+            base_account: base_account.clone(),
+            quote_account: quote_account.clone(),
+            token_program: token_program.clone(),
+            system_program: system_program.clone(),
+            oracle_a,
+            oracle_b,
+            header,
+        })
+    }
+
+    fn build_cpi(
+        &self,
+        ctx: &SwapContext<'_, 'info>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<SwapCpi<'info>> {
+        let header = &self.header;
+
+        // Whole lots only; the unspent remainder stays in the vault balance
+        let max_base_lots = amount_in / header.base_lot_size;
+        require!(max_base_lots > 0, EscrowError::InsufficientBalance);
+        let base_atoms = max_base_lots * header.base_lot_size;
+        // Worst acceptable price, in quote lots per base lot
+        let price_lots = (minimum_amount_out / header.quote_lot_size / max_base_lots).max(1);
+
+        let args = PlaceTakeOrderArgs {
+            side: SIDE_ASK,
+            price_lots: i64::try_from(price_lots).map_err(|_| EscrowError::MathOverflow)?,
+            max_base_lots: i64::try_from(max_base_lots).map_err(|_| EscrowError::MathOverflow)?,
+            max_quote_lots_including_fees: i64::MAX,
+            order_type: ORDER_TYPE_FILL_OR_KILL,
+            limit: MATCH_LIMIT,
+        };
+        let mut data = PLACE_TAKE_ORDER.to_vec();
+        args.serialize(&mut data)?;
+
+        let optional_meta = |account: &Option<AccountInfo>| match account {
+            Some(info) => AccountMeta::new_readonly(info.key(), false),
+            None => AccountMeta::new_readonly(PROGRAM_ID, false),
+        };
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.vault.key(), true),
+                // The bot covers the small penalty OpenBook charges on no-fill takes
+                AccountMeta::new(ctx.bot.key(), true),
+                AccountMeta::new(self.market.key(), false),
+                AccountMeta::new_readonly(self.market_authority.key(), false),
+                AccountMeta::new(self.bids.key(), false),
+                AccountMeta::new(self.asks.key(), false),
+                AccountMeta::new(self.market_base_vault.key(), false),
+                AccountMeta::new(self.market_quote_vault.key(), false),
+                AccountMeta::new(self.event_heap.key(), false),
+                AccountMeta::new(self.base_account.key(), false),
+                AccountMeta::new(self.quote_account.key(), false),
+                optional_meta(&self.oracle_a),
+                optional_meta(&self.oracle_b),
+                AccountMeta::new_readonly(token::ID, false),
+                AccountMeta::new_readonly(System::id(), false),
+                // open_orders_admin: none
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data,
+        };
+
+        let mut account_infos = vec![
+            ctx.dex_program.clone(),
+            ctx.vault.clone(),
+            ctx.bot.clone(),
+            self.market.clone(),
+            self.market_authority.clone(),
+            self.bids.clone(),
+            self.asks.clone(),
+            self.market_base_vault.clone(),
+            self.market_quote_vault.clone(),
+            self.event_heap.clone(),
+            self.base_account.clone(),
+            self.quote_account.clone(),
+            self.token_program.clone(),
+            self.system_program.clone(),
+        ];
+        account_infos.extend(self.oracle_a.clone());
+        account_infos.extend(self.oracle_b.clone());
+
+        Ok(SwapCpi {
+            ix,
+            account_infos,
+            input_account: self.base_account.clone(),
+            output_account: self.quote_account.clone(),
+            token_program: self.token_program.clone(),
+            input_amount: base_atoms,
+        })
+    }
+}
@@ -0,0 +1,210 @@
+//! Phoenix (central limit order book) adapter.
+//!
+//! Sells SOL into a SOL/quote market with an immediate-or-cancel `Swap`
+//! against the book, signed by the vault PDA. The order must fill completely
+//! and return at least `minimum_amount_out` quote atoms, or the CPI fails.
+//!
+//! remaining_accounts:
+//!   0. log_authority — Phoenix event log PDA
+//!   1. market        — Phoenix market (writable)
+//!   2. base_account  — vault-owned WSOL account (writable)
+//!   3. quote_account — vault-owned quote token account (writable)
+//!   4. base_vault    — market's base vault (writable)
+//!   5. quote_vault   — market's quote vault (writable)
+//!   6. token_program
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_spl::token::{self, spl_token::native_mint};
+
+use super::{pubkey_at, u64_at, vault_token_account, DexAdapter, SwapContext, SwapCpi};
+use crate::EscrowError;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY");
+
+/// Phoenix instruction tag for `Swap`
+const SWAP_TAG: u8 = 0;
+/// `OrderPacket::ImmediateOrCancel` variant index
+const ORDER_PACKET_IOC: u8 = 2;
+/// `Side::Ask` — selling base (SOL) for quote
+const SIDE_ASK: u8 = 1;
+/// `SelfTradeBehavior::Abort`
+const SELF_TRADE_ABORT: u8 = 0;
+
+// MarketHeader field offsets
+const BASE_MINT_OFFSET: usize = 48;
+const BASE_VAULT_OFFSET: usize = 80;
+const BASE_LOT_SIZE_OFFSET: usize = 112;
+const QUOTE_MINT_OFFSET: usize = 128;
+const QUOTE_VAULT_OFFSET: usize = 160;
+const QUOTE_LOT_SIZE_OFFSET: usize = 192;
+const MARKET_HEADER_MIN_LEN: usize = 200;
+
+const ACCOUNT_COUNT: usize = 7;
+
+/// Body of Phoenix's `OrderPacket::ImmediateOrCancel`, in wire order.
+#[derive(AnchorSerialize)]
+struct ImmediateOrCancel {
+    side: u8,
+    price_in_ticks: Option<u64>,
+    num_base_lots: u64,
+    num_quote_lots: u64,
+    min_base_lots_to_fill: u64,
+    min_quote_lots_to_fill: u64,
+    self_trade_behavior: u8,
+    match_limit: Option<u64>,
+    client_order_id: u128,
+    use_only_deposited_funds: bool,
+    last_valid_slot: Option<u64>,
+    last_valid_unix_timestamp_in_seconds: Option<u64>,
+}
+
+/// The parts of the market header the adapter validates against.
+struct MarketHeader {
+    base_mint: Pubkey,
+    base_vault: Pubkey,
+    base_lot_size: u64,
+    quote_mint: Pubkey,
+    quote_vault: Pubkey,
+    quote_lot_size: u64,
+}
+
+impl MarketHeader {
+    fn load(market: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*market.owner, PROGRAM_ID, EscrowError::InvalidDexAccount);
+        let data = market.try_borrow_data()?;
+        require!(data.len() >= MARKET_HEADER_MIN_LEN, EscrowError::InvalidDexAccount);
+
+        Ok(Self {
+            base_mint: pubkey_at(&data, BASE_MINT_OFFSET),
+            base_vault: pubkey_at(&data, BASE_VAULT_OFFSET),
+            base_lot_size: u64_at(&data, BASE_LOT_SIZE_OFFSET),
+            quote_mint: pubkey_at(&data, QUOTE_MINT_OFFSET),
+            quote_vault: pubkey_at(&data, QUOTE_VAULT_OFFSET),
+            quote_lot_size: u64_at(&data, QUOTE_LOT_SIZE_OFFSET),
+        })
+    }
+}
+
+/// Validated Phoenix accounts.
+pub struct Phoenix<'info> {
+    log_authority: AccountInfo<'info>,
+    market: AccountInfo<'info>,
+    base_account: AccountInfo<'info>,
+    quote_account: AccountInfo<'info>,
+    base_vault: AccountInfo<'info>,
+    quote_vault: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    header: MarketHeader,
+}
+
+impl<'info> DexAdapter<'info> for Phoenix<'info> {
+    const PROGRAM_ID: Pubkey = PROGRAM_ID;
+
+    fn validate_accounts(ctx: &SwapContext<'_, 'info>) -> Result<Self> {
+        let vault = ctx.vault;
+        require!(ctx.remaining_accounts.len() >= ACCOUNT_COUNT, EscrowError::InvalidDexAccount);
+        let [log_authority, market, base_account, quote_account, base_vault, quote_vault, token_program] =
+            &ctx.remaining_accounts[..ACCOUNT_COUNT]
+        else {
+            unreachable!()
+        };
+
+        // Market and its vaults
+        let header = MarketHeader::load(market)?;
+        require_keys_eq!(header.base_mint, native_mint::ID, EscrowError::InvalidDexAccount);
+        require_keys_eq!(base_vault.key(), header.base_vault, EscrowError::InvalidDexAccount);
+        require_keys_eq!(quote_vault.key(), header.quote_vault, EscrowError::InvalidDexAccount);
+        require!(
+            header.base_lot_size > 0 && header.quote_lot_size > 0,
+            EscrowError::InvalidDexAccount
+        );
+
+        // Phoenix's log authority PDA and the token program
+        let (expected_log_authority, _) = Pubkey::find_program_address(&[b"log"], &PROGRAM_ID);
+        require_keys_eq!(log_authority.key(), expected_log_authority, EscrowError::InvalidDexAccount);
+        require_keys_eq!(token_program.key(), token::ID, EscrowError::InvalidDexAccount);
+
+        // Trader token accounts must belong to the vault
+        vault_token_account(base_account, &vault.key(), &header.base_mint)?;
+        vault_token_account(quote_account, &vault.key(), &header.quote_mint)?;
+
+        Ok(Self {
+            log_authority: log_authority.clone(),
+            market: market.clone(),
+            base_account: base_account.clone(),
+            quote_account: quote_account.clone(),
+            base_vault: base_vault.clone(),
+            quote_vault: quote_vault.clone(),
+            token_program: token_program.clone(),
+            header,
+        })
+    }
+
+    fn build_cpi(
+        &self,
+        ctx: &SwapContext<'_, 'info>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<SwapCpi<'info>> {
+        let header = &self.header;
+
+        // Round down to whole lots; the unspent remainder stays in the vault balance
+        let num_base_lots = amount_in / header.base_lot_size;
+        require!(num_base_lots > 0, EscrowError::InsufficientBalance);
+        let base_atoms = num_base_lots * header.base_lot_size;
+        let min_quote_lots_to_fill = minimum_amount_out.div_ceil(header.quote_lot_size);
+
+        let order = ImmediateOrCancel {
+            side: SIDE_ASK,
+            price_in_ticks: None,
+            num_base_lots,
+            num_quote_lots: 0,
+            min_base_lots_to_fill: num_base_lots,
+            min_quote_lots_to_fill,
+            self_trade_behavior: SELF_TRADE_ABORT,
+            match_limit: None,
+            client_order_id: 0,
+            use_only_deposited_funds: false,
+            last_valid_slot: None,
+            last_valid_unix_timestamp_in_seconds: None,
+        };
+        let mut data = vec![SWAP_TAG, ORDER_PACKET_IOC];
+        order.serialize(&mut data)?;
+
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(self.log_authority.key(), false),
+                AccountMeta::new(self.market.key(), false),
+                AccountMeta::new_readonly(ctx.vault.key(), true),
+                AccountMeta::new(self.base_account.key(), false),
+                AccountMeta::new(self.quote_account.key(), false),
+                AccountMeta::new(self.base_vault.key(), false),
+                AccountMeta::new(self.quote_vault.key(), false),
+                AccountMeta::new_readonly(token::ID, false),
+            ],
+            data,
+        };
+
+        Ok(SwapCpi {
+            ix,
+            account_infos: vec![
+                ctx.dex_program.clone(),
+                self.log_authority.clone(),
+                self.market.clone(),
+                ctx.vault.clone(),
+                self.base_account.clone(),
+                self.quote_account.clone(),
+                self.base_vault.clone(),
+                self.quote_vault.clone(),
+                self.token_program.clone(),
+            ],
+            input_account: self.base_account.clone(),
+            output_account: self.quote_account.clone(),
+            token_program: self.token_program.clone(),
+            input_amount: base_atoms,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+//! SolFi (proactive market maker) adapter.
+//!
+//! Sells SOL into a SolFi pair with its swap instruction, signed by the vault
+//! PDA. Quotes are maintained by the market maker off-chain, so the pair
+//! introspects the instructions sysvar; it's passed through unchanged.
+//!
+//! remaining_accounts:
+//!   0. pair         — pair state (writable)
+//!   1. pool_token_a — pair's token A reserve (writable)
+//!   2. pool_token_b — pair's token B reserve (writable)
+//!   3. user_token_a — vault-owned token A account (writable)
+//!   4. user_token_b — vault-owned token B account (writable)
+//!   5. token_program
+//!   6. instructions_sysvar
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, spl_token::native_mint};
+
+use super::{owned_token_account, vault_token_account, DexAdapter, SwapContext, SwapCpi};
+use crate::EscrowError;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("SoLFiHG9TfgtdUXUjWAxi3LtvYuFyDLVhBWxdMZxyCe");
+
+/// SolFi instruction tag for `Swap`
+const SWAP_TAG: u8 = 7;
+
+const ACCOUNT_COUNT: usize = 7;
+
+/// SolFi swap arguments, in wire order.
+#[derive(AnchorSerialize)]
+struct SwapArgs {
+    amount_in: u64,
+    minimum_amount_out: u64,
+    a_to_b: bool,
+}
+
+/// Validated SolFi accounts.
+pub struct SolFi<'info> {
+    pair: AccountInfo<'info>,
+    pool_token_a: AccountInfo<'info>,
+    pool_token_b: AccountInfo<'info>,
+    user_token_a: AccountInfo<'info>,
+    user_token_b: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    instructions_sysvar: AccountInfo<'info>,
+    a_to_b: bool,
+}
+
+impl<'info> DexAdapter<'info> for SolFi<'info> {
+    const PROGRAM_ID: Pubkey = PROGRAM_ID;
+
+    fn validate_accounts(ctx: &SwapContext<'_, 'info>) -> Result<Self> {
+        let vault = ctx.vault;
+        require!(ctx.remaining_accounts.len() >= ACCOUNT_COUNT, EscrowError::InvalidDexAccount);
+        let [pair, pool_token_a, pool_token_b, user_token_a, user_token_b, token_program, instructions_sysvar] =
+            &ctx.remaining_accounts[..ACCOUNT_COUNT]
+        else {
+            unreachable!()
+        };
+
+        require_keys_eq!(*pair.owner, PROGRAM_ID, EscrowError::InvalidDexAccount);
+        require_keys_eq!(token_program.key(), token::ID, EscrowError::InvalidDexAccount);
+        require_keys_eq!(instructions_sysvar.key(), sysvar::instructions::ID, EscrowError::InvalidDexAccount);
+
+        // Reserves belong to the pair; one side must be SOL, which we sell
+        let reserve_a = owned_token_account(pool_token_a, &pair.key())?;
+        let reserve_b = owned_token_account(pool_token_b, &pair.key())?;
+        let a_to_b = reserve_a.mint == native_mint::ID;
+        require!(
+            a_to_b || reserve_b.mint == native_mint::ID,
+            EscrowError::InvalidDexAccount
+        );
+
+        // Trader token accounts must belong to the vault and match the reserves
+        vault_token_account(user_token_a, &vault.key(), &reserve_a.mint)?;
+        vault_token_account(user_token_b, &vault.key(), &reserve_b.mint)?;
+
+        Ok(Self {
+            pair: pair.clone(),
+            pool_token_a: pool_token_a.clone(),
+            pool_token_b: pool_token_b.clone(),
+            user_token_a: user_token_a.clone(),
+            user_token_b: user_token_b.clone(),
+            token_program: token_program.clone(),
+            instructions_sysvar: instructions_sysvar.clone(),
+            a_to_b,
+        })
+    }
+
+    fn build_cpi(
+        &self,
+        ctx: &SwapContext<'_, 'info>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<SwapCpi<'info>> {
+        let (wsol_account, output_account) = if self.a_to_b {
+            (&self.user_token_a, &self.user_token_b)
+        } else {
+            (&self.user_token_b, &self.user_token_a)
+        };
+
+        let mut data = vec![SWAP_TAG];
+        SwapArgs {
+            amount_in,
+            minimum_amount_out,
+            a_to_b: self.a_to_b,
+        }
+        .serialize(&mut data)?;
+
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.vault.key(), true),
+                AccountMeta::new(self.pair.key(), false),
+                AccountMeta::new(self.pool_token_a.key(), false),
+                AccountMeta::new(self.pool_token_b.key(), false),
+                AccountMeta::new(self.user_token_a.key(), false),
+                AccountMeta::new(self.user_token_b.key(), false),
+                AccountMeta::new_readonly(token::ID, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+            data,
+        };
+
+        Ok(SwapCpi {
+            ix,
+            account_infos: vec![
+                ctx.dex_program.clone(),
+                ctx.vault.clone(),
+                self.pair.clone(),
+                self.pool_token_a.clone(),
+                self.pool_token_b.clone(),
+                self.user_token_a.clone(),
+                self.user_token_b.clone(),
+                self.token_program.clone(),
+                self.instructions_sysvar.clone(),
+            ],
+            input_account: wsol_account.clone(),
+            output_account: output_account.clone(),
+            token_program: self.token_program.clone(),
+            input_amount: amount_in,
+        })
+    }
+}
@@ -0,0 +1,172 @@
+//! Batched swap events, for sessions trading often enough that one
+//! `SwapExecuted` per swap floods the logs.
+//!
+//! A session opts in with `set_trade_batching`. Its swaps are then buffered
+//! in a `TradeBatch` account, passed as the last remaining account after the
+//! DEX route, and emitted together as one `TradesBatched` once `batch_size`
+//! trades are buffered or the oldest is `window_slots` old; anyone can
+//! `flush_trade_batch` a quiet session. The trades travel compressed:
+//!
+//! - a key table: a count byte, then each distinct DEX program and output
+//!   mint once, in first-use order;
+//! - per trade: the timestamp as a varint delta from the previous trade's
+//!   (from 0 for the first), the DEX and mint as key table indexes, varint
+//!   `amount_in`, `minimum_amount_out` and `amount_out`, then a byte that's 1
+//!   if a memo follows, 0 if it was all zeroes.
+//!
+//! [`decode`] turns them back into trades; the SDK expands them into the
+//! `SwapExecuted` events they stand for.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::{SwapExecuted, TradesBatched};
+use crate::state::{BatchedTrade, TradeBatch};
+
+/// Buffer `swap` in the session's batch at `info`, emitting the batch if it's due.
+pub fn record<'info>(info: &'info AccountInfo<'info>, vault: &Pubkey, swap: &SwapExecuted, slot: u64) -> Result<()> {
+    let mut batch: Account<TradeBatch> = Account::try_from(info)?;
+    require_keys_eq!(batch.vault, *vault, EscrowError::InvalidTradeBatch);
+    if batch.push(BatchedTrade::from(swap), slot) {
+        emit_batch(&mut batch, *vault, swap.session_id, swap.bot);
+    }
+    batch.exit(&crate::ID)
+}
+
+/// Emit whatever `batch` holds as one `TradesBatched`, if anything.
+pub fn emit_batch(batch: &mut TradeBatch, vault: Pubkey, session_id: [u8; 16], bot: Pubkey) {
+    if batch.trades.is_empty() {
+        return;
+    }
+    let (sequence, trades) = batch.take();
+    emit!(TradesBatched {
+        session_id,
+        vault,
+        bot,
+        sequence,
+        count: trades.len() as u8,
+        trades: encode(&trades),
+    });
+}
+
+pub fn encode(trades: &[BatchedTrade]) -> Vec<u8> {
+    let mut keys: Vec<Pubkey> = Vec::new();
+    let mut index_of = |key: Pubkey| match keys.iter().position(|known| *known == key) {
+        Some(index) => index as u8,
+        None => {
+            keys.push(key);
+            (keys.len() - 1) as u8
+        }
+    };
+
+    let mut body = Vec::new();
+    let mut previous = 0i64;
+    for trade in trades {
+        write_varint(&mut body, trade.timestamp.wrapping_sub(previous) as u64);
+        previous = trade.timestamp;
+        body.push(index_of(trade.dex_program));
+        body.push(index_of(trade.output_mint));
+        for amount in [trade.amount_in, trade.minimum_amount_out, trade.amount_out] {
+            write_varint(&mut body, amount);
+        }
+        if trade.memo == [0; 32] {
+            body.push(0);
+        } else {
+            body.push(1);
+            body.extend_from_slice(&trade.memo);
+        }
+    }
+
+    let mut data = vec![keys.len() as u8];
+    keys.iter().for_each(|key| data.extend_from_slice(key.as_ref()));
+    data.extend_from_slice(&body);
+    data
+}
+
+/// `count` trades from [`encode`]d data, or `None` if it's malformed.
+pub fn decode(data: &[u8], count: usize) -> Option<Vec<BatchedTrade>> {
+    let mut reader = Reader(data);
+    let keys = (0..reader.byte()?)
+        .map(|_| reader.take(32).map(|key| Pubkey::try_from(key).unwrap()))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut trades = Vec::with_capacity(count);
+    let mut previous = 0i64;
+    for _ in 0..count {
+        let timestamp = previous.wrapping_add(reader.varint()? as i64);
+        previous = timestamp;
+        let dex_program = *keys.get(reader.byte()? as usize)?;
+        let output_mint = *keys.get(reader.byte()? as usize)?;
+        let (amount_in, minimum_amount_out, amount_out) = (reader.varint()?, reader.varint()?, reader.varint()?);
+        let memo = match reader.byte()? {
+            0 => [0; 32],
+            1 => reader.take(32)?.try_into().unwrap(),
+            _ => return None,
+        };
+        trades.push(BatchedTrade { timestamp, dex_program, amount_in, minimum_amount_out, output_mint, amount_out, memo });
+    }
+    reader.0.is_empty().then_some(trades)
+}
+
+/// LEB128: seven bits per byte, low bits first, high bit set on all but the last.
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let (taken, rest) = (self.0.get(..length)?, self.0.get(length..)?);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        self.take(1).map(|byte| byte[0])
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_in_a_fraction_of_the_space() {
+        let (dex, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let trades: Vec<BatchedTrade> = (0..20)
+            .map(|i| BatchedTrade {
+                timestamp: 1_700_000_000 + i,
+                dex_program: dex,
+                amount_in: 50_000_000 + i as u64,
+                minimum_amount_out: 1_000_000,
+                output_mint: mint,
+                amount_out: 1_200_000 + i as u64,
+                memo: if i == 3 { [7; 32] } else { [0; 32] },
+            })
+            .collect();
+
+        let data = encode(&trades);
+        assert_eq!(decode(&data, trades.len()), Some(trades.clone()));
+        // Two keys, then ~16 bytes a trade instead of SwapExecuted's 184
+        assert!(data.len() < 1 + 64 + 20 * 20 + 32);
+        assert_eq!(decode(&data, trades.len() + 1), None);
+        assert_eq!(decode(&data[..data.len() - 1], trades.len()), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Daily compute fee accrual, shared by the crank, withdrawals and
+//! the stablecoin session variants.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{EXPIRY_WARNING_DAYS, LOW_BALANCE_WARNING_DAYS};
+use crate::errors::EscrowError;
+use crate::fee_router::{route_fee, FeeSource};
+use crate::events::{ComputeFeeSubsidized, ExpiryApproaching, LowBalanceWarning};
+use crate::session::{debug_assert_solvent, transfer_from_vault};
+use crate::state::{OperatorCredit, ProtocolConfig, Vault};
+use crate::{math, oracle};
+
+/// Whole days of compute fee accrued since the last deduction, and the fee owed
+/// for them (capped at the vault's balance). Accrual stops at `expires_at`, so
+/// a late crank can't charge for days after the session ended.
+pub fn accrued_compute_fee(vault: &Vault, now: i64) -> Result<(u64, u64)> {
+    let accrual_end = now.min(vault.expires_at);
+    let days_elapsed = math::whole_days_between(vault.last_compute_deduction, accrual_end)?;
+    let fee = math::accrued_fee(days_elapsed, vault.daily_compute_fee, vault.balance);
+
+    Ok((days_elapsed, fee))
+}
+
+/// Route `fee` from the vault PDA to the treasury and fee router, and record
+/// the deduction. `last_compute_deduction` advances by whole days only, so a
+/// partial day carries over to the next crank instead of being dropped.
+pub fn collect_compute_fee<'info>(
+    vault: &mut Account<'info, Vault>,
+    treasury: &UncheckedAccount<'info>,
+    fee_router: &UncheckedAccount<'info>,
+    fee: u64,
+    days_elapsed: u64,
+) -> Result<()> {
+    // The vault PDA is owned by this program, so we can debit it directly
+    let vault_info = vault.to_account_info();
+    route_fee(&FeeSource::Program(&vault_info), fee, treasury, None, fee_router)?;
+
+    record_compute_fee(vault, fee, days_elapsed)?;
+    debug_assert_solvent(vault);
+    Ok(())
+}
+
+/// The bot's operator credit at `info`, if its operator has funded one.
+fn load_operator_credit(info: &UncheckedAccount) -> Result<Option<OperatorCredit>> {
+    if info.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*info.owner, crate::ID, EscrowError::InvalidOperatorCredit);
+    Ok(Some(OperatorCredit::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+}
+
+/// Pay what it can of `fee` from the bot's operator credit at `info`, if its
+/// operator has funded one, routed like any compute fee. Returns the amount
+/// covered, which the vault no longer owes.
+pub fn draw_operator_credit<'info>(
+    info: &UncheckedAccount<'info>,
+    vault: &Account<'info, Vault>,
+    treasury: &UncheckedAccount<'info>,
+    fee_router: &UncheckedAccount<'info>,
+    fee: u64,
+) -> Result<u64> {
+    let Some(mut credit) = load_operator_credit(info)? else {
+        return Ok(0);
+    };
+    let covered = credit.cover(fee);
+    if covered == 0 {
+        return Ok(0);
+    }
+    route_fee(&FeeSource::Program(info), covered, treasury, None, fee_router)?;
+    credit.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    emit!(ComputeFeeSubsidized {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        bot: vault.bot,
+        amount: covered,
+        credit_remaining: credit.balance,
+    });
+    Ok(covered)
+}
+
+/// Settle the SOL session's compute fee accrued up to `now`, the bot's
+/// operator credit paying first. Returns the whole days settled and the fee
+/// the vault itself paid.
+pub fn settle_compute_fee<'info>(
+    vault: &mut Account<'info, Vault>,
+    operator_credit: &UncheckedAccount<'info>,
+    treasury: &UncheckedAccount<'info>,
+    fee_router: &UncheckedAccount<'info>,
+    now: i64,
+) -> Result<(u64, u64)> {
+    let (days_elapsed, accrued) = accrued_compute_fee(vault, now)?;
+    if days_elapsed == 0 {
+        return Ok((0, 0));
+    }
+    let covered = draw_operator_credit(operator_credit, vault, treasury, fee_router, accrued)?;
+    let fee = accrued - covered;
+    collect_compute_fee(vault, treasury, fee_router, fee, days_elapsed)?;
+    Ok((days_elapsed, fee))
+}
+
+/// SOL-denominated accounts a stablecoin session's compute fee settlement
+/// needs to draw on the bot's operator credit.
+pub struct OperatorCreditAccounts<'a, 'info> {
+    pub operator_credit: &'a UncheckedAccount<'info>,
+    pub treasury: &'a UncheckedAccount<'info>,
+    pub fee_router: &'a UncheckedAccount<'info>,
+    pub config: &'a ProtocolConfig,
+    /// SOL and base-mint Pyth price updates, read only if there's credit to draw on
+    pub price_feeds: &'a [AccountInfo<'info>],
+}
+
+/// `settle_compute_fee` for a stablecoin session. The operator credit pays the
+/// fee's value in lamports at Pyth prices; the vault pays, in the base mint,
+/// the share of the fee the credit didn't cover.
+pub fn settle_compute_fee_token<'info>(
+    vault: &mut Account<'info, Vault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    treasury_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    credit: OperatorCreditAccounts<'_, 'info>,
+    now: i64,
+) -> Result<(u64, u64)> {
+    let (days_elapsed, accrued) = accrued_compute_fee(vault, now)?;
+    if days_elapsed == 0 {
+        return Ok((0, 0));
+    }
+    let covered = draw_operator_credit_token(vault, &credit, accrued, now)?;
+    let fee = accrued - covered;
+    collect_compute_fee_token(vault, vault_token_account, treasury_token_account, token_program, fee, days_elapsed)?;
+    Ok((days_elapsed, fee))
+}
+
+/// Draw the lamport value of `fee` base-mint units from the operator credit.
+/// Returns the base-mint amount covered, rounded down.
+fn draw_operator_credit_token<'info>(
+    vault: &Account<'info, Vault>,
+    credit: &OperatorCreditAccounts<'_, 'info>,
+    fee: u64,
+    now: i64,
+) -> Result<u64> {
+    let funded = load_operator_credit(credit.operator_credit)?.is_some_and(|credit| credit.balance > 0);
+    if fee == 0 || !funded {
+        return Ok(0);
+    }
+    let [sol_feed, base_feed] = credit.price_feeds else {
+        return err!(EscrowError::PriceFeedMissing);
+    };
+    let sol = credit.config.price_feed(&native_mint::ID).ok_or(EscrowError::PriceFeedMissing)?;
+    let base = credit.config.price_feed(&vault.base_mint).ok_or(EscrowError::PriceFeedMissing)?;
+    let fee_lamports = oracle::value_in_lamports(
+        fee,
+        base.decimals,
+        oracle::load_price(base_feed, &base.feed_id, now)?,
+        oracle::load_price(sol_feed, &sol.feed_id, now)?,
+    )?;
+    if fee_lamports == 0 {
+        return Ok(0);
+    }
+
+    let covered = draw_operator_credit(
+        credit.operator_credit,
+        vault,
+        credit.treasury,
+        credit.fee_router,
+        fee_lamports,
+    )?;
+    math::mul_div(fee, covered, fee_lamports)
+}
+
+/// Move `fee` of the base mint from the vault's token account to the treasury's.
+pub fn collect_compute_fee_token<'info>(
+    vault: &mut Account<'info, Vault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    treasury_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    fee: u64,
+    days_elapsed: u64,
+) -> Result<()> {
+    transfer_from_vault(vault, vault_token_account, treasury_token_account, token_program, fee)?;
+
+    record_compute_fee(vault, fee, days_elapsed)
+}
+
+pub fn record_compute_fee(vault: &mut Vault, fee: u64, days_elapsed: u64) -> Result<()> {
+    vault.balance = vault.balance
+        .checked_sub(fee)
+        .ok_or(EscrowError::MathOverflow)?;
+    vault.compute_fees_paid = vault.compute_fees_paid
+        .checked_add(fee)
+        .ok_or(EscrowError::MathOverflow)?;
+    vault.last_compute_deduction = math::add_days(vault.last_compute_deduction, days_elapsed)?;
+
+    Ok(())
+}
+
+/// Warnings for a crank to emit after deducting: a low balance once fewer
+/// than `LOW_BALANCE_WARNING_DAYS` of fees are left, and the approaching end
+/// within `EXPIRY_WARNING_DAYS`. An emptied session gets neither, since the
+/// crank just expired it. `address` is the vault's own.
+pub fn warnings(vault: &Vault, address: Pubkey, now: i64) -> (Option<LowBalanceWarning>, Option<ExpiryApproaching>) {
+    if vault.balance == 0 {
+        return (None, None);
+    }
+    let days_remaining = vault.balance.checked_div(vault.daily_compute_fee).unwrap_or(u64::MAX);
+    let low_balance = (days_remaining < LOW_BALANCE_WARNING_DAYS).then_some(LowBalanceWarning {
+        session_id: vault.session_id,
+        vault: address,
+        balance: vault.balance,
+        daily_compute_fee: vault.daily_compute_fee,
+        days_remaining,
+    });
+    let seconds_remaining = vault.expires_at.saturating_sub(now);
+    let expiring = (seconds_remaining > 0 && seconds_remaining <= EXPIRY_WARNING_DAYS as i64 * math::SECONDS_PER_DAY)
+        .then_some(ExpiryApproaching {
+            session_id: vault.session_id,
+            vault: address,
+            expires_at: vault.expires_at,
+            seconds_remaining,
+        });
+    (low_balance, expiring)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::SECONDS_PER_DAY;
+    use proptest::prelude::*;
+
+    /// A SOL session funded at `start` with `amount`, as `fund_session` leaves it.
+    fn funded(amount: u64, fee_bps: u64, daily_compute_fee: u64, start: i64, duration_days: u16) -> (Vault, u64) {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(8 + Vault::INIT_SPACE, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        let (fee, trading_balance) = math::split_fee(amount, fee_bps).unwrap();
+        vault.balance = trading_balance;
+        vault.daily_compute_fee = daily_compute_fee;
+        vault.last_compute_deduction = start;
+        vault.expires_at = math::add_days(start, duration_days as u64).unwrap();
+        (vault, fee)
+    }
+
+    /// Crank at `now` the way `deduct_compute_fee` and `pay_out` do.
+    fn crank(vault: &mut Vault, now: i64) -> u64 {
+        let (days_elapsed, fee) = accrued_compute_fee(vault, now).unwrap();
+        if days_elapsed >= 1 {
+            record_compute_fee(vault, fee, days_elapsed).unwrap();
+        }
+        fee
+    }
+
+    #[test]
+    fn warns_near_the_end_of_balance_and_duration() {
+        let daily = 10_000_000;
+        let (mut vault, _) = funded(daily * 10, 0, daily, 0, 7);
+        assert!(matches!(warnings(&vault, Pubkey::default(), 0), (None, None)));
+
+        vault.balance = daily * 3 - 1;
+        let (low, expiring) = warnings(&vault, Pubkey::default(), 5 * SECONDS_PER_DAY);
+        assert_eq!(low.unwrap().days_remaining, 2);
+        assert_eq!(expiring.unwrap().seconds_remaining, 2 * SECONDS_PER_DAY);
+
+        // Past expiry, or emptied by the crank, there's nothing left to warn about
+        assert!(warnings(&vault, Pubkey::default(), 7 * SECONDS_PER_DAY).1.is_none());
+        vault.balance = 0;
+        assert!(matches!(warnings(&vault, Pubkey::default(), 6 * SECONDS_PER_DAY), (None, None)));
+    }
+
+    proptest! {
+        #[test]
+        fn accrual_is_path_independent(
+            balance in 0..1u64 << 50,
+            daily in 0..1u64 << 40,
+            duration_days in 1..=365u16,
+            gaps in prop::collection::vec(0..3 * SECONDS_PER_DAY, 0..16),
+            end in 0..400 * SECONDS_PER_DAY,
+        ) {
+            let (mut cranked, _) = funded(balance, 0, daily, 0, duration_days);
+            let (mut once, _) = funded(balance, 0, daily, 0, duration_days);
+
+            // Any cranks before `end`, then settle at `end` as a withdrawal would
+            let mut now = 0;
+            let mut collected = 0;
+            for gap in gaps {
+                now = (now + gap).min(end);
+                collected += crank(&mut cranked, now);
+            }
+            collected += crank(&mut cranked, end);
+
+            prop_assert_eq!(collected, crank(&mut once, end));
+            prop_assert_eq!(cranked.balance, once.balance);
+            prop_assert_eq!(cranked.compute_fees_paid, once.compute_fees_paid);
+            prop_assert_eq!(cranked.last_compute_deduction, once.last_compute_deduction);
+        }
+
+        #[test]
+        fn settling_never_overdraws_and_conserves_lamports(
+            amount in 0..1u64 << 60,
+            fee_bps in 0..=1_000u64,
+            daily in any::<u64>(),
+            duration_days in 1..=365u16,
+            steps in prop::collection::vec((0..2 * SECONDS_PER_DAY, any::<u64>()), 0..16),
+        ) {
+            let (mut vault, setup_fee) = funded(amount, fee_bps, daily, 0, duration_days);
+            prop_assert_eq!(setup_fee + vault.balance, amount);
+
+            // Interleave cranks with swaps spending part of the balance
+            let (mut now, mut treasury, mut spent) = (0, setup_fee as u128, 0u128);
+            for (gap, swap) in steps {
+                now += gap;
+                treasury += crank(&mut vault, now) as u128;
+                let amount_in = swap % (vault.balance + 1);
+                vault.balance -= amount_in;
+                spent += amount_in as u128;
+            }
+
+            // Withdrawal settles the rest and pays out whatever balance is left
+            treasury += crank(&mut vault, now + SECONDS_PER_DAY) as u128;
+            let paid_out = vault.balance as u128;
+            prop_assert_eq!(treasury + spent + paid_out, amount as u128);
+            prop_assert_eq!(treasury, setup_fee as u128 + vault.compute_fees_paid as u128);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Protocol-wide fees, limits, PDA seeds and well-known program IDs.
+
+use anchor_lang::prelude::*;
+
+/// SPL Governance (Realms). An admin owned by this program is a governance
+/// account, so admin instructions only run through executed proposals.
+pub const SPL_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+/// Upper bound on whitelist entries, fixes the ProtocolConfig size
+pub const MAX_WHITELISTED_DEXES: usize = 32;
+
+/// Upper bound on a template's allowed DEXes, fixes the Vault size
+pub const MAX_TEMPLATE_DEXES: usize = 8;
+
+/// Upper bound on DEXes a user can turn off per session, fixes the Vault size
+pub const MAX_DISABLED_DEXES: usize = 8;
+
+/// Upper bound on distinct token positions per session, fixes the Vault size
+pub const MAX_POSITIONS: usize = 16;
+
+/// Upper bound on registered price feeds, fixes the ProtocolConfig size
+pub const MAX_PRICE_FEEDS: usize = 16;
+
+/// Upper bound on blacklisted bot keys, fixes the ProtocolConfig size
+pub const MAX_BLACKLISTED_BOTS: usize = 64;
+
+/// Upper bound on priced bot tiers (besides the default, tier 0), fixes the ProtocolConfig size
+pub const MAX_BOT_TIERS: usize = 8;
+
+/// Upper bound on buffered swaps per batched event, fixes the TradeBatch size
+pub const MAX_BATCHED_TRADES: usize = 32;
+
+/// Upper bound on fee router recipients besides the treasury, fixes the FeeRouter size
+pub const MAX_FEE_RECIPIENTS: usize = 4;
+
+/// Default setup fee basis points (2.5% = 250 bps)
+pub const FEE_BPS: u64 = 250;
+
+/// Highest setup fee governance can set (10%)
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Default daily compute fee in lamports (0.01 SOL)
+pub const DAILY_COMPUTE_FEE: u64 = 10_000_000;
+
+/// Minimum deposit in lamports (0.1 SOL)
+pub const MIN_DEPOSIT: u64 = 100_000_000;
+
+/// Daily compute fee for stablecoin sessions, in bps of the funded balance
+/// (1%/day — the same as 0.01 SOL on a 1 SOL session)
+pub const DAILY_COMPUTE_FEE_BPS: u64 = 100;
+
+/// Minimum deposit for stablecoin sessions, in base units (10 of a 6-decimal stable)
+pub const MIN_STABLE_DEPOSIT: u64 = 10_000_000;
+
+/// Highest share of the setup fee a template operator can take (50%)
+pub const MAX_OPERATOR_FEE_SHARE_BPS: u16 = 5_000;
+
+/// Default share of the trading balance that may be lent out (50%)
+pub const DEFAULT_LEND_CAP_BPS: u16 = 5_000;
+
+/// Days without a user-signed instruction before the recovery key may withdraw
+pub const RECOVERY_INACTIVITY_DAYS: u64 = 180;
+
+/// The compute fee crank warns when fewer days of fees than this are left
+pub const LOW_BALANCE_WARNING_DAYS: u64 = 3;
+
+/// The compute fee crank warns when the session expires within this many days
+pub const EXPIRY_WARNING_DAYS: u64 = 2;
+
+/// Days a session stays open after its bot resigns, for the user to withdraw
+pub const RESIGNATION_NOTICE_DAYS: u64 = 3;
+
+/// Days a guardian pause lasts before anyone can lift it, unless the user confirms it
+pub const MAX_GUARDIAN_PAUSE_DAYS: u64 = 7;
+
+/// Longest withdrawal notice a user can impose on their own session
+pub const MAX_WITHDRAWAL_NOTICE_DAYS: u64 = 30;
+
+/// Days a withdrawal request stays good once its notice is served
+pub const WITHDRAWAL_REQUEST_WINDOW_DAYS: u64 = 3;
+
+// PDA seeds — stable interface, see `pda`
+#[constant]
+pub const VAULT_SEED: &[u8] = b"vault";
+#[constant]
+pub const CONFIG_SEED: &[u8] = b"config";
+#[constant]
+pub const REWARDS_SEED: &[u8] = b"rewards";
+#[constant]
+pub const STAKE_SEED: &[u8] = b"stake";
+#[constant]
+pub const EPOCH_REPORT_SEED: &[u8] = b"epoch";
+#[constant]
+pub const REGISTRY_SEED: &[u8] = b"registry";
+#[constant]
+pub const BOT_STATS_SEED: &[u8] = b"bot_stats";
+#[constant]
+pub const BOT_PROFILE_SEED: &[u8] = b"bot_profile";
+#[constant]
+pub const INVITE_SEED: &[u8] = b"invite";
+#[constant]
+pub const FEE_ROUTER_SEED: &[u8] = b"fee_router";
+#[constant]
+pub const UPGRADE_INFO_SEED: &[u8] = b"upgrade_info";
+#[constant]
+pub const TRADE_BATCH_SEED: &[u8] = b"trade_batch";
+#[constant]
+pub const OPERATOR_CREDIT_SEED: &[u8] = b"operator_credit";
+#[constant]
+pub const GIFT_SEED: &[u8] = b"gift";
@@ -0,0 +1,100 @@
+//! Off-chain authorizations, checked through the ed25519 program.
+//!
+//! The user signs a message off-chain; whoever submits it puts an ed25519
+//! program instruction verifying that signature right before ours. The
+//! runtime fails the whole transaction if the signature is bad, so finding
+//! that instruction in the instructions sysvar, with the user's key and the
+//! expected message in its own data, proves the user signed.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+
+use crate::errors::EscrowError;
+
+pub const ED25519_PROGRAM_ID: Pubkey = pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+/// Prefix of every withdrawal authorization, so no other signed message reads as one
+pub const WITHDRAW_MESSAGE_PREFIX: &[u8] = b"gentdex-escrow:withdraw";
+
+/// One signature's offsets follow the count and a padding byte
+const OFFSETS_START: usize = 2;
+const OFFSETS_LENGTH: usize = 14;
+/// Instruction index meaning "this instruction's own data"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// The message a user signs to let anyone withdraw `vault` to them until `deadline`.
+pub fn withdraw_message(vault: &Pubkey, deadline: i64) -> Vec<u8> {
+    [WITHDRAW_MESSAGE_PREFIX, crate::ID.as_ref(), vault.as_ref(), &deadline.to_le_bytes()].concat()
+}
+
+/// Require the instruction before the current one to verify `signer`'s
+/// signature over `message`. `instructions_sysvar` must be the instructions sysvar.
+pub fn require_signed(instructions_sysvar: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions_sysvar)? as usize;
+    require!(current > 0, EscrowError::InvalidUserSignature);
+    let ix = load_instruction_at_checked(current - 1, instructions_sysvar)?;
+    require_keys_eq!(ix.program_id, ED25519_PROGRAM_ID, EscrowError::InvalidUserSignature);
+    require!(verifies(&ix.data, signer, message), EscrowError::InvalidUserSignature);
+    Ok(())
+}
+
+/// Whether ed25519 program instruction data verifies exactly one signature,
+/// by `signer` over `message`, all read from the instruction itself — offsets
+/// into another instruction could point anywhere.
+fn verifies(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    if data.len() < OFFSETS_START + OFFSETS_LENGTH || data[0] != 1 {
+        return false;
+    }
+    let field = |index: usize| {
+        let at = OFFSETS_START + 2 * index;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    // signature, its instruction, pubkey, its instruction, message, its size, its instruction
+    let [_, signature_ix, pubkey_offset, pubkey_ix, message_offset, message_size, message_ix] =
+        [0, 1, 2, 3, 4, 5, 6].map(field);
+    let (pubkey_offset, message_offset) = (pubkey_offset as usize, message_offset as usize);
+    [signature_ix, pubkey_ix, message_ix].iter().all(|&ix| ix == CURRENT_INSTRUCTION)
+        && data.get(pubkey_offset..pubkey_offset + 32) == Some(signer.as_ref())
+        && message_size as usize == message.len()
+        && data.get(message_offset..message_offset + message.len()) == Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Instruction data in the layout the ed25519 program's own builder uses
+    fn instruction_data(signer: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let (pubkey_offset, signature_offset, message_offset) = (16u16, 48u16, 112u16);
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            instruction_index,
+            pubkey_offset,
+            instruction_index,
+            message_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn checks_the_signer_and_message_in_the_instruction_itself() {
+        let (signer, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let message = withdraw_message(&vault, 1_000);
+        assert!(message.starts_with(WITHDRAW_MESSAGE_PREFIX));
+        assert!(verifies(&instruction_data(&signer, &message, CURRENT_INSTRUCTION), &signer, &message));
+
+        assert!(!verifies(&instruction_data(&Pubkey::new_unique(), &message, CURRENT_INSTRUCTION), &signer, &message));
+        let other = withdraw_message(&vault, 2_000);
+        assert!(!verifies(&instruction_data(&signer, &other, CURRENT_INSTRUCTION), &signer, &message));
+        assert!(!verifies(&instruction_data(&signer, &message, 0), &signer, &message));
+        assert!(!verifies(&[1, 0], &signer, &message));
+    }
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("Unauthorized: caller is not the vault owner or bot")]
+    Unauthorized,
+    #[msg("Invalid vault status for this operation")]
+    InvalidStatus,
+    #[msg("Deposit amount below minimum (0.1 SOL)")]
+    DepositTooSmall,
+    #[msg("Insufficient balance in vault")]
+    InsufficientBalance,
+    #[msg("DEX program is not whitelisted")]
+    DexNotWhitelisted,
+    #[msg("Trading session has expired")]
+    SessionExpired,
+    #[msg("Session has not expired yet")]
+    SessionNotExpired,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Too early for compute fee deduction")]
+    TooEarlyForDeduction,
+    #[msg("Invalid treasury account")]
+    InvalidTreasury,
+    #[msg("Vault is locked by an in-flight swap")]
+    ReentrantCall,
+    #[msg("Swap route spent more than amount_in")]
+    SwapOverspent,
+    #[msg("Invalid account for DEX adapter")]
+    InvalidDexAccount,
+    #[msg("Swap output below minimum_amount_out")]
+    SlippageExceeded,
+    #[msg("Perps mode is not enabled for this session")]
+    PerpsNotEnabled,
+    #[msg("Only reduce-only orders are allowed while the session isn't trading")]
+    ReduceOnly,
+    #[msg("Lending is not enabled for this session")]
+    LendingNotEnabled,
+    #[msg("Lend cap must be at most 10000 bps")]
+    InvalidLendCap,
+    #[msg("Lending would exceed the session's lend cap")]
+    LendCapExceeded,
+    #[msg("Lent balance must be unwound before withdrawing")]
+    LendingNotUnwound,
+    #[msg("Instruction doesn't support this session's base currency")]
+    BaseCurrencyMismatch,
+    #[msg("DEX whitelist is full")]
+    WhitelistFull,
+    #[msg("Setup fee above the protocol maximum")]
+    FeeTooHigh,
+    #[msg("Rewards schedule must start before it ends")]
+    InvalidRewardsSchedule,
+    #[msg("Stake is still locked")]
+    StakeLocked,
+    #[msg("Instruction can only be called via CPI")]
+    CpiOnly,
+    #[msg("Invalid or missing session template")]
+    InvalidTemplate,
+    #[msg("User has been active within the recovery window")]
+    UserStillActive,
+    #[msg("Swap would open more positions than the session allows")]
+    PositionLimitReached,
+    #[msg("Price feed account is not a verified Pyth update for the expected feed")]
+    InvalidPriceFeed,
+    #[msg("Price update is too old")]
+    StalePrice,
+    #[msg("Required price feed not provided or not registered")]
+    PriceFeedMissing,
+    #[msg("Swap would exceed the session's single-token exposure cap")]
+    ExposureCapExceeded,
+    #[msg("The epoch being reported hasn't ended yet")]
+    EpochNotOver,
+    #[msg("Lookup table is not the session's")]
+    InvalidLookupTable,
+    #[msg("Bot has not been verified by the guardian")]
+    BotNotVerified,
+    #[msg("Bot is blacklisted")]
+    BotBlacklisted,
+    #[msg("Bot blacklist is full")]
+    BlacklistFull,
+    #[msg("Template only opens sessions from an invite")]
+    InviteRequired,
+    #[msg("Invite is expired, for another user or has invalid terms")]
+    InvalidInvite,
+    #[msg("Bot has resigned from the session")]
+    BotResigned,
+    #[msg("Fee shares are duplicated, too many or add up to too much")]
+    InvalidFeeRoute,
+    #[msg("Recipient has unclaimed fees")]
+    UnclaimedFees,
+    #[msg("Deposit would take the session past the protocol's cap")]
+    DepositTooLarge,
+    #[msg("Deposit cap is below the minimum deposit")]
+    InvalidDepositLimits,
+    #[msg("Trade nonce was already used")]
+    StaleTradeNonce,
+    #[msg("No valid ed25519 signature by the user over the expected message")]
+    InvalidUserSignature,
+    #[msg("Authorization is past its deadline")]
+    AuthorizationExpired,
+    #[msg("Not a Token Bridge transfer to this session")]
+    InvalidBridgeTransfer,
+    #[msg("Trade batch is missing, not the session's or has an invalid size")]
+    InvalidTradeBatch,
+    #[msg("Bot tier is above MAX_BOT_TIERS")]
+    InvalidBotTier,
+    #[msg("Operator credit is not this program's")]
+    InvalidOperatorCredit,
+    #[msg("Token positions and perps collateral must be closed first")]
+    SessionNotEmpty,
+    #[msg("Withdrawal notice must be 0 to MAX_WITHDRAWAL_NOTICE_DAYS, and set to request a withdrawal")]
+    InvalidWithdrawalNotice,
+    #[msg("Session's withdrawal notice hasn't been served; request_withdrawal and wait it out")]
+    WithdrawalNoticePending,
+    #[msg("Guardian pauses need a reason")]
+    InvalidPauseReason,
+    #[msg("Session isn't paused by the guardian")]
+    NotGuardianPaused,
+    #[msg("Guardian pause hasn't reached MAX_GUARDIAN_PAUSE_DAYS")]
+    GuardianPauseActive,
+    #[msg("Perps collateral must be withdrawn first")]
+    PerpsNotUnwound,
+    #[msg("Funder isn't the one the user authorized for this session")]
+    UnauthorizedFunder,
+    #[msg("Whitelisted venue has no adapter, so swaps can't trade through it")]
+    DexAdapterMissing,
+    #[msg("Sessions are extended by at least a day")]
+    InvalidExtension,
+}
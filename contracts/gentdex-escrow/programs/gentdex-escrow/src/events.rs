@@ -0,0 +1,574 @@
+use anchor_lang::prelude::*;
+
+use crate::adapters::drift;
+use crate::state::{FeeShare, PauseReason, RewardsSchedule, SwapRejectReason};
+
+#[event]
+#[derive(Debug)]
+pub struct SessionCreated {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub bot: Pubkey,
+    pub duration_days: u16,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct Deposited {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub trading_balance: u64,
+    pub expires_at: i64,
+}
+
+/// A session funded through the Wormhole Token Bridge; a `Deposited` follows.
+#[event]
+#[derive(Debug)]
+pub struct BridgeDeposited {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    /// Wormhole chain id the transfer came from
+    pub emitter_chain: u16,
+    pub sequence: u64,
+    /// Sending address on that chain, left-padded to 32 bytes
+    pub sender: [u8; 32],
+    pub amount: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SwapExecuted {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub bot: Pubkey,
+    pub dex_program: Pubkey,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub timestamp: i64,
+    pub output_mint: Pubkey,
+    pub amount_out: u64,
+    pub memo: [u8; 32],
+}
+
+/// Swaps of a session that batches its events, in place of their
+/// `SwapExecuted`s. `trades` is compressed; see `batching`.
+#[event]
+#[derive(Debug)]
+pub struct TradesBatched {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub bot: Pubkey,
+    /// Batches the session emitted before this one
+    pub sequence: u64,
+    pub count: u8,
+    pub trades: Vec<u8>,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SwapRejected {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub bot: Pubkey,
+    pub dex_program: Pubkey,
+    pub amount_in: u64,
+    pub reason: SwapRejectReason,
+    pub timestamp: i64,
+    pub memo: [u8; 32],
+}
+
+#[event]
+#[derive(Debug)]
+pub struct ComputeFeeDeducted {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub fee: u64,
+    pub remaining_balance: u64,
+}
+
+/// Compute fee the bot's operator credit paid instead of the session; the
+/// session's own share is its `ComputeFeeDeducted`.
+#[event]
+#[derive(Debug)]
+pub struct ComputeFeeSubsidized {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub bot: Pubkey,
+    pub amount: u64,
+    pub credit_remaining: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionPaused {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionResumed {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionExtended {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub days: u16,
+    pub expires_at: i64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct Withdrawn {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub compute_fee: u64,
+    pub user: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct WithdrawalNoticeSet {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub notice: i64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct WithdrawalRequested {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub available_at: i64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionClosed {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub rent: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionTransferred {
+    pub source_session_id: [u8; 16],
+    pub source_vault: Pubkey,
+    pub destination_session_id: [u8; 16],
+    pub destination_vault: Pubkey,
+    pub amount: u64,
+    pub compute_fee: u64,
+    pub user: Pubkey,
+}
+
+/// Fewer than `LOW_BALANCE_WARNING_DAYS` of compute fees left after a crank.
+#[event]
+#[derive(Debug)]
+pub struct LowBalanceWarning {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub balance: u64,
+    pub daily_compute_fee: u64,
+    /// Whole days of compute fees the balance still covers
+    pub days_remaining: u64,
+}
+
+/// The session expires within `EXPIRY_WARNING_DAYS` of a crank.
+#[event]
+#[derive(Debug)]
+pub struct ExpiryApproaching {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub expires_at: i64,
+    pub seconds_remaining: i64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionExpiredEvent {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub remaining_balance: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct PerpsEnabled {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub drift_user: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct PerpsCollateralMoved {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub perps_collateral: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct PerpOrderPlaced {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub market_index: u16,
+    pub direction: drift::PerpDirection,
+    pub base_asset_amount: u64,
+    pub price: u64,
+    pub reduce_only: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct LendingEnabled {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub lending_account: Pubkey,
+    pub lend_cap_bps: u16,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct LendingMoved {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub lent: u64,
+    pub unwound: u64,
+    pub lent_amount: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DexWhitelistUpdated {
+    pub program_id: Pubkey,
+    pub whitelisted: bool,
+    pub whitelist_version: u32,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct FeesUpdated {
+    pub fee_bps: u16,
+    pub daily_compute_fee: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DepositLimitsUpdated {
+    pub min_deposit: u64,
+    pub max_deposit: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct StableDepositLimitsUpdated {
+    pub min_deposit: u64,
+    pub max_deposit: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct GuardianUpdated {
+    pub previous: Pubkey,
+    pub guardian: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct BotTierSet {
+    pub bot: Pubkey,
+    pub tier: u8,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct TierComputeFeesUpdated {
+    pub fees: Vec<u64>,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct TreasuryUpdated {
+    pub previous: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct TreasuryMigrated {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub previous: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct AdminTransferred {
+    pub previous: Pubkey,
+    pub admin: Pubkey,
+    pub governance: bool,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct RewardsScheduleUpdated {
+    pub schedule: RewardsSchedule,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct StakeChanged {
+    pub user: Pubkey,
+    pub staked: u64,
+    pub unstaked: u64,
+    pub amount: u64,
+    pub discount_bps: u16,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionGifted {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub giver: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct GiftAccepted {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub giver: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct GiftReclaimed {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub giver: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct OperatorCreditChanged {
+    pub bot: Pubkey,
+    pub funded: u64,
+    pub withdrawn: u64,
+    pub balance: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct TemplateUpdated {
+    pub template: Pubkey,
+    pub operator: Pubkey,
+    pub template_id: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct RecoveryUpdated {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub recovery: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct FunderUpdated {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub funder: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SessionDexToggled {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub program_id: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct PriceFeedUpdated {
+    pub mint: Pubkey,
+    pub feed_id: [u8; 32],
+}
+
+#[event]
+#[derive(Debug)]
+pub struct SlippageBudgetExhausted {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub slippage_consumed: u64,
+    pub slippage_budget: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct EpochClosed {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub volume: u64,
+    pub fees: u64,
+    pub pnl: i64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct BotStatsUpdated {
+    pub bot: Pubkey,
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub session_pnl: i64,
+    pub sessions: u64,
+    pub median_pnl_bps: i32,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct BotAttested {
+    pub bot: Pubkey,
+    pub verified: bool,
+    pub audited: bool,
+    pub guardian: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct BotBlacklistUpdated {
+    pub bot: Pubkey,
+    pub blacklisted: bool,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct BlacklistedBotPaused {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub bot: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct GuardianPaused {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub guardian: Pubkey,
+    pub reason: PauseReason,
+    pub lifts_at: i64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct GuardianPauseEnded {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub reason: PauseReason,
+    /// Whether the user kept the session paused; otherwise it's active again
+    pub confirmed: bool,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct InviteCreated {
+    pub invite: Pubkey,
+    pub template: Pubkey,
+    pub invitee: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct InviteRedeemed {
+    pub invite: Pubkey,
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub user: Pubkey,
+}
+
+/// The bot gave notice; the session now expires at `expires_at`.
+#[event]
+#[derive(Debug)]
+pub struct BotResigned {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub bot: Pubkey,
+    pub expires_at: i64,
+}
+
+/// A token position left the vault for the user's token account.
+#[event]
+#[derive(Debug)]
+pub struct PositionWithdrawn {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub user: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct FeeRouteUpdated {
+    pub shares: Vec<FeeShare>,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct RoutedFeesClaimed {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// `assert_solvent` found the vault holding less than its accounted balance
+/// plus rent; an Active session was paused.
+#[event]
+#[derive(Debug)]
+pub struct InsolvencyDetected {
+    pub session_id: [u8; 16],
+    pub vault: Pubkey,
+    pub lamports: u64,
+    pub required: u64,
+    pub paused: bool,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct UpgradeInfoUpdated {
+    pub upgrade_authority: Pubkey,
+    pub deployed_slot: u64,
+    pub timelock_seconds: u64,
+    pub build_hash: [u8; 32],
+}
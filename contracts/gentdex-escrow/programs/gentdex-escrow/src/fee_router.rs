@@ -0,0 +1,98 @@
+//! Fee routing shared by every fee-bearing SOL instruction. A fee's shares go
+//! to the session's template operator, if any, and to the fee router's
+//! recipients, each in bps of the whole fee; the treasury gets the rest.
+//!
+//! Stablecoin session fees are paid in their mint and still go to the
+//! treasury's token account in full.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::constants::MAX_FEE_RECIPIENTS;
+use crate::errors::EscrowError;
+use crate::lamports;
+use crate::session::move_lamports;
+use crate::state::{FeeRouter, SessionTemplate};
+
+/// Where a fee is paid from.
+pub enum FeeSource<'a, 'info> {
+    /// A system account signing the transaction
+    Signer {
+        from: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>,
+    },
+    /// A program-owned account, debited directly
+    Program(&'a AccountInfo<'info>),
+}
+
+impl<'info> FeeSource<'_, 'info> {
+    fn pay(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        match self {
+            Self::Signer { from, system_program } => system_program::transfer(
+                CpiContext::new(
+                    (*system_program).clone(),
+                    system_program::Transfer {
+                        from: (*from).clone(),
+                        to: to.clone(),
+                    },
+                ),
+                amount,
+            ),
+            Self::Program(from) => move_lamports(from, to, amount),
+        }
+    }
+}
+
+/// Pay `fee` from `source`: `operator_bps` of it to `template`, each router
+/// recipient's share to `fee_router`, both claimable from there, and the rest
+/// to `treasury`. `fee_router` is the router PDA, skipped until the admin
+/// creates it.
+pub fn route_fee<'info>(
+    source: &FeeSource<'_, 'info>,
+    fee: u64,
+    treasury: &AccountInfo<'info>,
+    template: Option<(&mut Account<'info, SessionTemplate>, u16)>,
+    fee_router: &AccountInfo<'info>,
+) -> Result<()> {
+    let mut router = match fee_router.data_is_empty() {
+        true => None,
+        false => {
+            require_keys_eq!(*fee_router.owner, crate::ID, EscrowError::InvalidFeeRoute);
+            Some(FeeRouter::try_deserialize(&mut &fee_router.try_borrow_data()?[..])?)
+        }
+    };
+
+    // Operator first, then the router's recipients in order
+    let mut bps = [0; MAX_FEE_RECIPIENTS + 1];
+    let mut shares = [0; MAX_FEE_RECIPIENTS + 1];
+    bps[0] = template.as_ref().map_or(0, |(_, bps)| *bps);
+    let recipients = router.iter().flat_map(|router| &router.recipients);
+    for (slot, recipient) in bps[1..].iter_mut().zip(recipients) {
+        *slot = recipient.bps;
+    }
+    let count = 1 + router.as_ref().map_or(0, |router| router.recipients.len());
+    let rest = lamports::route(fee, &bps[..count], &mut shares).ok_or(EscrowError::InvalidFeeRoute)?;
+
+    source.pay(treasury, rest)?;
+    if let Some((template, _)) = template {
+        source.pay(&template.to_account_info(), shares[0])?;
+        template.fees_accrued = template.fees_accrued
+            .checked_add(shares[0])
+            .ok_or(EscrowError::MathOverflow)?;
+        template.exit(&crate::ID)?;
+    }
+    if let Some(router) = router.as_mut() {
+        source.pay(fee_router, shares[1..count].iter().sum())?;
+        for (recipient, share) in router.recipients.iter_mut().zip(&shares[1..]) {
+            recipient.accrued = recipient.accrued
+                .checked_add(*share)
+                .ok_or(EscrowError::MathOverflow)?;
+        }
+        router.try_serialize(&mut &mut fee_router.try_borrow_mut_data()?[..])?;
+    }
+
+    Ok(())
+}
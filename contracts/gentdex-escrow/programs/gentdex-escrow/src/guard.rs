@@ -0,0 +1,168 @@
+//! Reentrancy guard and state ordering for DEX CPI paths.
+//!
+//! Every adapter wraps its CPI in a `SwapGuard`: the vault's accounted balance
+//! is debited and the `locked` flag is written to account data *before* the
+//! CPI, then reconciled against the real lamport delta afterwards — or, for a
+//! token session, the delta of its base token account. A route that tries to
+//! re-enter `execute_swap` or `withdraw` mid-CPI sees the vault locked.
+
+use anchor_lang::prelude::*;
+
+use crate::adapters::token_amount;
+use crate::session::debug_assert_solvent;
+use crate::{EscrowError, Vault};
+
+/// Fail if a CPI is currently in flight for this vault.
+pub fn ensure_unlocked(vault: &Vault) -> Result<()> {
+    require!(!vault.locked, EscrowError::ReentrantCall);
+    Ok(())
+}
+
+pub struct SwapGuard<'info> {
+    amount_in: u64,
+    held_before: u64,
+    /// A token session's base token account; `None` measures vault lamports
+    base_account: Option<AccountInfo<'info>>,
+}
+
+impl<'info> SwapGuard<'info> {
+    /// Debit `amount_in`, lock the vault and persist both before any CPI runs.
+    pub fn enter(vault: &mut Account<Vault>, amount_in: u64) -> Result<Self> {
+        Self::lock(vault, amount_in, None)
+    }
+
+    /// `enter` for a token session, whose balance is held in `base_account`.
+    pub fn enter_token(
+        vault: &mut Account<Vault>,
+        base_account: &AccountInfo<'info>,
+        amount_in: u64,
+    ) -> Result<Self> {
+        Self::lock(vault, amount_in, Some(base_account.clone()))
+    }
+
+    fn lock(vault: &mut Account<Vault>, amount_in: u64, base_account: Option<AccountInfo<'info>>) -> Result<Self> {
+        ensure_unlocked(vault)?;
+        require!(amount_in <= vault.balance, EscrowError::InsufficientBalance);
+
+        vault.balance -= amount_in;
+        vault.locked = true;
+        // Anchor only serializes on instruction exit; write now so the callee
+        // (and anything it calls back into) sees the debited, locked state.
+        vault.exit(&crate::ID)?;
+
+        let held_before = held(vault, base_account.as_ref())?;
+        Ok(Self {
+            amount_in,
+            held_before,
+            base_account,
+        })
+    }
+
+    /// Reconcile the accounted balance with what the CPI actually moved, then
+    /// unlock. Returns the base currency the route consumed.
+    pub fn exit(self, vault: &mut Account<Vault>) -> Result<u64> {
+        let held_after = held(vault, self.base_account.as_ref())?;
+        let spent = self.held_before.saturating_sub(held_after);
+        let received = held_after.saturating_sub(self.held_before);
+        require!(spent <= self.amount_in, EscrowError::SwapOverspent);
+
+        // Return whatever the route didn't spend, plus any it paid out
+        let refund = self.amount_in - spent;
+        vault.balance = vault.balance
+            .checked_add(refund)
+            .and_then(|b| b.checked_add(received))
+            .ok_or(EscrowError::MathOverflow)?;
+        vault.locked = false;
+        debug_assert_solvent(vault);
+
+        Ok(spent)
+    }
+}
+
+/// What backs the vault's balance: its lamports, or its base token account's amount.
+fn held(vault: &Account<Vault>, base_account: Option<&AccountInfo>) -> Result<u64> {
+    match base_account {
+        Some(info) => token_amount(info),
+        None => Ok(vault.to_account_info().lamports()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token::{self, spl_token::state::{Account as TokenAccount, AccountState}};
+    use anchor_lang::solana_program::program_pack::Pack;
+
+    use super::*;
+
+    /// A vault account holding `balance` of `lamports`, owned by the program.
+    fn vault_info(balance: u64, lamports: u64) -> &'static AccountInfo<'static> {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.resize(8 + Vault::INIT_SPACE, 0);
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.balance = balance;
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+        data.resize(8 + Vault::INIT_SPACE, 0);
+
+        let key = Box::leak(Box::new(Pubkey::new_unique()));
+        let lamports = Box::leak(Box::new(lamports));
+        let data = Box::leak(data.into_boxed_slice());
+        Box::leak(Box::new(AccountInfo::new(key, false, true, lamports, data, &crate::ID, false, 0)))
+    }
+
+    #[test]
+    fn locks_across_the_cpi_and_reconciles_after() {
+        let info = vault_info(1_000, 1_000);
+        let mut vault = Account::<Vault>::try_from(info).unwrap();
+
+        let guard = SwapGuard::enter(&mut vault, 400).unwrap();
+        assert_eq!((vault.balance, vault.locked), (600, true));
+        // Written to the account before the CPI, not just in memory
+        let stored = Vault::try_deserialize(&mut &info.try_borrow_data().unwrap()[..]).unwrap();
+        assert_eq!((stored.balance, stored.locked), (600, true));
+
+        assert!(SwapGuard::enter(&mut vault, 1).is_err());
+        assert!(ensure_unlocked(&vault).is_err());
+
+        // The route spent 300 of the 400: the rest comes back to the balance
+        **info.try_borrow_mut_lamports().unwrap() -= 300;
+        assert_eq!(guard.exit(&mut vault).unwrap(), 300);
+        assert_eq!((vault.balance, vault.locked), (700, false));
+        assert!(ensure_unlocked(&vault).is_ok());
+    }
+
+    #[test]
+    fn rejects_overspending_and_overdrafts() {
+        let info = vault_info(1_000, 1_000);
+        let mut vault = Account::<Vault>::try_from(info).unwrap();
+        assert!(SwapGuard::enter(&mut vault, 1_001).is_err());
+
+        let guard = SwapGuard::enter(&mut vault, 100).unwrap();
+        **info.try_borrow_mut_lamports().unwrap() -= 101;
+        assert!(guard.exit(&mut vault).is_err());
+    }
+
+    #[test]
+    fn token_sessions_reconcile_against_their_base_account() {
+        let info = vault_info(1_000, 10);
+        let mut vault = Account::<Vault>::try_from(info).unwrap();
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount { amount: 1_000, state: AccountState::Initialized, ..Default::default() },
+            &mut data,
+        )
+        .unwrap();
+        let (key, lamports) = (Pubkey::new_unique(), &mut 0);
+        let base_account = AccountInfo::new(&key, false, true, lamports, &mut data, &token::ID, false, 0);
+
+        let guard = SwapGuard::enter_token(&mut vault, &base_account, 400).unwrap();
+        assert_eq!(vault.balance, 600);
+        // Lamports don't count; the route sold 250 of the base mint
+        **info.try_borrow_mut_lamports().unwrap() -= 5;
+        let mut stored = TokenAccount::unpack(&base_account.try_borrow_data().unwrap()).unwrap();
+        stored.amount = 750;
+        TokenAccount::pack(stored, &mut base_account.try_borrow_mut_data().unwrap()).unwrap();
+        assert_eq!(guard.exit(&mut vault).unwrap(), 250);
+        assert_eq!(vault.balance, 750);
+    }
+}
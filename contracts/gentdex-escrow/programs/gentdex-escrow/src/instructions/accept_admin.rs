@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::SPL_GOVERNANCE_PROGRAM_ID;
+use crate::errors::EscrowError;
+use crate::events::AdminTransferred;
+use crate::state::ProtocolConfig;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    pub pending_admin: Signer<'info>,
+}
+
+pub(crate) fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let new_admin = ctx.accounts.pending_admin.key();
+    require!(
+        config.pending_admin != Pubkey::default() && config.pending_admin == new_admin,
+        EscrowError::Unauthorized
+    );
+
+    let previous = config.admin;
+    config.admin = new_admin;
+    config.pending_admin = Pubkey::default();
+    config.admin_is_governance = *ctx.accounts.pending_admin.owner == SPL_GOVERNANCE_PROGRAM_ID;
+
+    emit!(AdminTransferred {
+        previous,
+        admin: new_admin,
+        governance: config.admin_is_governance,
+    });
+
+    Ok(())
+}
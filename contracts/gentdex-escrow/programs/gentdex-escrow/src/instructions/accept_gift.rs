@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::{Deposited, GiftAccepted};
+use crate::fee_router::{route_fee, FeeSource};
+use crate::session::{activate_session, move_lamports};
+use crate::state::{BotProfile, ProtocolConfig, RewardsAccount, SessionGift, Vault};
+use crate::{math, stake_for_discount};
+
+#[derive(Accounts)]
+pub struct AcceptGift<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump,
+        has_one = user @ EscrowError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"gift", vault.key().as_ref()],
+        bump = gift.bump,
+        has_one = giver @ EscrowError::Unauthorized,
+        close = giver
+    )]
+    pub gift: Account<'info, SessionGift>,
+
+    /// CHECK: The gift's giver, refunded its rent
+    #[account(mut)]
+    pub giver: UncheckedAccount<'info>,
+
+    /// The recipient
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RewardsAccount::INIT_SPACE,
+        seeds = [b"rewards", user.key().as_ref()],
+        bump
+    )]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// CHECK: The user's stake account, if any — read for the fee discount tier
+    #[account(seeds = [b"stake", user.key().as_ref()], bump)]
+    pub stake: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet for fee collection
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The bot's profile, for sessions opened requiring a verified bot — its
+    /// tier at funding prices the compute fee
+    #[account(seeds = [b"bot_profile", vault.bot.as_ref()], bump = bot_profile.bump)]
+    pub bot_profile: Option<Account<'info, BotProfile>>,
+
+    /// Pays for `rewards` the first time the recipient is rewarded — the
+    /// user, or a relayer for recipients with no SOL of their own
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn accept_gift(ctx: Context<AcceptGift>) -> Result<()> {
+    let amount = ctx.accounts.gift.amount;
+    let fee_bps = stake_for_discount::discounted_fee_bps(ctx.accounts.config.fee_bps as u64, &ctx.accounts.stake)?;
+    let (fee, trading_balance) = math::split_fee(amount, fee_bps)?;
+
+    // Both legs come out of the gift PDA, which `close` then returns to the giver
+    let gift_info = ctx.accounts.gift.to_account_info();
+    move_lamports(&gift_info, &ctx.accounts.vault.to_account_info(), trading_balance)?;
+    route_fee(&FeeSource::Program(&gift_info), fee, &ctx.accounts.treasury, None, &ctx.accounts.fee_router)?;
+
+    let vault = &mut ctx.accounts.vault;
+    activate_session(vault, &ctx.accounts.config, false, ctx.accounts.bot_profile.as_deref(), trading_balance, fee)?;
+
+    ctx.accounts.rewards.register(vault.user, ctx.bumps.rewards);
+
+    emit!(GiftAccepted {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        giver: ctx.accounts.gift.giver,
+        user: vault.user,
+        amount,
+    });
+    emit!(Deposited {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        amount,
+        fee,
+        trading_balance,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::{ProtocolConfig, Vault};
+
+#[derive(Accounts)]
+pub struct AdoptLatestWhitelist<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub(crate) fn adopt_latest_whitelist(ctx: Context<AdoptLatestWhitelist>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    vault.whitelist_version = ctx.accounts.config.whitelist_version;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::{InsolvencyDetected, SessionPaused};
+use crate::session::solvency;
+use crate::state::{Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct AssertSolvent<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Anyone can crank this
+    pub cranker: Signer<'info>,
+}
+
+pub(crate) fn assert_solvent(ctx: Context<AssertSolvent>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+
+    let (lamports, required) = solvency(vault)?;
+    if lamports >= required {
+        return Ok(());
+    }
+
+    // Succeed so the pause and the incident are recorded
+    let paused = vault.status == VaultStatus::Active;
+    if paused {
+        vault.status = VaultStatus::Paused;
+        emit!(SessionPaused {
+            session_id: vault.session_id,
+            vault: vault.key(),
+        });
+    }
+    emit!(InsolvencyDetected {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        lamports,
+        required,
+        paused,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::BotAttested;
+use crate::state::{BotProfile, ProtocolConfig};
+
+#[derive(Accounts)]
+#[instruction(bot: Pubkey)]
+pub struct AttestBot<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = guardian @ EscrowError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        space = 8 + BotProfile::INIT_SPACE,
+        seeds = [b"bot_profile", bot.as_ref()],
+        bump
+    )]
+    pub bot_profile: Account<'info, BotProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn attest_bot(ctx: Context<AttestBot>, bot: Pubkey, verified: bool, audited: bool) -> Result<()> {
+    let profile = &mut ctx.accounts.bot_profile;
+    profile.bot = bot;
+    profile.verified = verified;
+    profile.audited = audited;
+    profile.attested_by = ctx.accounts.guardian.key();
+    profile.attested_at = Clock::get()?.unix_timestamp;
+    profile.bump = ctx.bumps.bot_profile;
+
+    emit!(BotAttested {
+        bot,
+        verified,
+        audited,
+        guardian: profile.attested_by,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::session::move_lamports;
+use super::UpdateTemplate;
+
+pub(crate) fn claim_operator_fees(ctx: Context<UpdateTemplate>) -> Result<()> {
+    let template = &mut ctx.accounts.template;
+    let amount = template.fees_accrued;
+    require!(amount > 0, EscrowError::InsufficientBalance);
+
+    move_lamports(&template.to_account_info(), &ctx.accounts.operator.to_account_info(), amount)?;
+    template.fees_accrued = 0;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::RoutedFeesClaimed;
+use crate::session::move_lamports;
+use crate::state::FeeRouter;
+
+#[derive(Accounts)]
+pub struct ClaimRoutedFees<'info> {
+    #[account(mut, seeds = [b"fee_router"], bump = fee_router.bump)]
+    pub fee_router: Account<'info, FeeRouter>,
+
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+}
+
+pub(crate) fn claim_routed_fees(ctx: Context<ClaimRoutedFees>) -> Result<()> {
+    let recipient = ctx.accounts.recipient.key();
+    let router = &mut ctx.accounts.fee_router;
+    let entry = router
+        .recipients
+        .iter_mut()
+        .find(|entry| entry.recipient == recipient)
+        .ok_or(EscrowError::Unauthorized)?;
+    let amount = entry.accrued;
+    require!(amount > 0, EscrowError::InsufficientBalance);
+    entry.accrued = 0;
+
+    move_lamports(&router.to_account_info(), &ctx.accounts.recipient.to_account_info(), amount)?;
+
+    emit!(RoutedFeesClaimed { recipient, amount });
+
+    Ok(())
+}
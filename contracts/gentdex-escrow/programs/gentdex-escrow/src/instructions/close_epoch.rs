@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::{adapters, oracle};
+use crate::errors::EscrowError;
+use crate::events::EpochClosed;
+use crate::state::{EpochReport, ProtocolConfig, Vault};
+
+#[derive(Accounts)]
+pub struct CloseEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + EpochReport::INIT_SPACE,
+        seeds = [b"epoch", vault.key().as_ref(), &vault.report_epoch.to_le_bytes()],
+        bump
+    )]
+    pub report: Account<'info, EpochReport>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    // With open positions: the SOL price feed, then each position's vault
+    // token account and price feed, in `position_mints` order, via remaining_accounts
+}
+
+/// Value of the vault's token positions in lamports, at the Pyth prices of the
+/// feeds registered in config.
+fn positions_value(
+    vault: &Account<Vault>,
+    config: &ProtocolConfig,
+    remaining_accounts: &[AccountInfo],
+    now: i64,
+) -> Result<u64> {
+    if vault.position_mints.is_empty() {
+        return Ok(0);
+    }
+    let (sol_feed, positions) = remaining_accounts.split_first().ok_or(EscrowError::PriceFeedMissing)?;
+    require!(positions.len() == 2 * vault.position_mints.len(), EscrowError::PriceFeedMissing);
+    let sol = config.price_feed(&native_mint::ID).ok_or(EscrowError::PriceFeedMissing)?;
+    let sol = oracle::load_price(sol_feed, &sol.feed_id, now)?;
+
+    let mut total: u64 = 0;
+    for (mint, accounts) in vault.position_mints.iter().zip(positions.chunks_exact(2)) {
+        let token_account = adapters::vault_token_account(&accounts[0], &vault.key(), mint)?;
+        let feed = config.price_feed(mint).ok_or(EscrowError::PriceFeedMissing)?;
+        let token = oracle::load_price(&accounts[1], &feed.feed_id, now)?;
+        let value = oracle::value_in_lamports(token_account.amount, feed.decimals, token, sol)?;
+        total = total.checked_add(value).ok_or(EscrowError::MathOverflow)?;
+    }
+    Ok(total)
+}
+
+pub(crate) fn close_epoch(ctx: Context<CloseEpoch>) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(clock.epoch > ctx.accounts.vault.report_epoch, EscrowError::EpochNotOver);
+    let positions = positions_value(
+        &ctx.accounts.vault,
+        &ctx.accounts.config,
+        ctx.remaining_accounts,
+        clock.unix_timestamp,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+
+    let start = vault.epoch_start;
+    let mut end = vault.snapshot()?;
+    end.value = end.value.checked_add(positions).ok_or(EscrowError::MathOverflow)?;
+    let net_flows = (end.deposited - start.deposited) as i128 - (end.withdrawn - start.withdrawn) as i128;
+    let pnl = end.value as i128 - start.value as i128 - net_flows;
+
+    let report = &mut ctx.accounts.report;
+    report.vault = vault.key();
+    report.epoch = vault.report_epoch;
+    report.volume = end.volume - start.volume;
+    report.fees = end.fees - start.fees;
+    report.start_value = start.value;
+    report.end_value = end.value;
+    report.pnl = i64::try_from(pnl).map_err(|_| EscrowError::MathOverflow)?;
+    report.closed_at = clock.unix_timestamp;
+    report.bump = ctx.bumps.report;
+
+    vault.report_epoch = clock.epoch;
+    vault.epoch_start = end;
+
+    emit!(EpochClosed {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        epoch: report.epoch,
+        volume: report.volume,
+        fees: report.fees,
+        pnl: report.pnl,
+    });
+
+    Ok(())
+}
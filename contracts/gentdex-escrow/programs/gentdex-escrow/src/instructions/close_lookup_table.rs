@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::lookup_table;
+use super::ManageLookupTable;
+
+pub(crate) fn close_lookup_table(ctx: Context<ManageLookupTable>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.authority.key(), EscrowError::Unauthorized);
+    require!(vault.lookup_table != Pubkey::default(), EscrowError::InvalidLookupTable);
+    require_keys_eq!(ctx.accounts.lookup_table.key(), vault.lookup_table, EscrowError::InvalidLookupTable);
+
+    // First call deactivates; the table can only be closed once its cooldown is over
+    if !lookup_table::is_deactivated(&ctx.accounts.lookup_table)? {
+        lookup_table::deactivate(&ctx.accounts.lookup_table, &vault.to_account_info(), &vault.signer_seeds())?;
+    } else {
+        lookup_table::close(
+            &ctx.accounts.lookup_table,
+            &vault.to_account_info(),
+            &ctx.accounts.authority,
+            &vault.signer_seeds(),
+        )?;
+        ctx.accounts.vault.lookup_table = Pubkey::default();
+    }
+    ctx.accounts.vault.record_user_activity()?;
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::GuardianPauseEnded;
+use crate::state::{PauseReason, VaultStatus};
+use super::UserAction;
+
+pub(crate) fn confirm_guardian_pause(ctx: Context<UserAction>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(
+        vault.status == VaultStatus::Paused && vault.guardian_paused_at > 0,
+        EscrowError::NotGuardianPaused
+    );
+
+    // From here it's the user's own pause, lifted only by `resume`
+    let reason = vault.pause_reason;
+    vault.guardian_paused_at = 0;
+    vault.pause_reason = PauseReason::None;
+    vault.record_user_activity()?;
+
+    emit!(GuardianPauseEnded {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        reason,
+        confirmed: true,
+    });
+
+    Ok(())
+}
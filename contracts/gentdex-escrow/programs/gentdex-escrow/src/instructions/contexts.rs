@@ -0,0 +1,244 @@
+//! Account contexts shared by more than one instruction.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+
+use crate::adapters::{drift, marginfi};
+use crate::errors::EscrowError;
+use crate::lookup_table;
+use crate::state::{ProtocolConfig, SessionTemplate, Vault};
+
+/// Read-only view of a session, for the `get_*` instructions.
+#[derive(Accounts)]
+pub struct ViewSession<'info> {
+    #[account(
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct UserAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageLookupTable<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// User (create, extend, close) or bot (extend); pays rent, or gets it back on close
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: The vault's lookup table — address checked in instruction logic
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+
+    /// CHECK: Address lookup table program
+    #[account(address = lookup_table::PROGRAM_ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PerpsCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Drift global state
+    #[account(address = drift::state_address())]
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's Drift sub-account
+    #[account(mut, address = drift::user_address(&vault.key()))]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's Drift user stats
+    #[account(mut, address = drift::user_stats_address(&vault.key()))]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Drift's SOL spot market vault — validated by Drift against the spot market
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Drift's signer PDA
+    #[account(address = drift::signer_address())]
+    pub drift_signer: UncheckedAccount<'info>,
+
+    /// CHECK: Vault-owned WSOL account — validated in instruction logic
+    #[account(mut)]
+    pub wsol_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Drift program
+    #[account(address = drift::PROGRAM_ID)]
+    pub drift_program: UncheckedAccount<'info>,
+    // Drift spot market + oracle accounts passed via remaining_accounts
+}
+
+impl<'info> PerpsCollateral<'info> {
+    pub fn drift_collateral_accounts<'a>(
+        &'a self,
+        vault: &'a AccountInfo<'info>,
+    ) -> drift::CollateralAccounts<'a, 'info> {
+        drift::CollateralAccounts {
+            drift_program: &self.drift_program,
+            state: &self.drift_state,
+            user: &self.drift_user,
+            user_stats: &self.drift_user_stats,
+            vault,
+            spot_market_vault: &self.spot_market_vault,
+            drift_signer: &self.drift_signer,
+            token_account: &self.wsol_account,
+            token_program: &self.token_program,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct PerpsOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Drift global state
+    #[account(address = drift::state_address())]
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's Drift sub-account
+    #[account(mut, address = drift::user_address(&vault.key()))]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: Drift program
+    #[account(address = drift::PROGRAM_ID)]
+    pub drift_program: UncheckedAccount<'info>,
+    // Drift perp market + oracle accounts passed via remaining_accounts
+}
+
+#[derive(Accounts)]
+pub struct Lending<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: marginfi group — must be whitelisted
+    #[account(constraint = marginfi::is_whitelisted_group(&marginfi_group.key()) @ EscrowError::InvalidDexAccount)]
+    pub marginfi_group: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's marginfi account
+    #[account(mut, address = vault.lending_account)]
+    pub marginfi_account: UncheckedAccount<'info>,
+
+    /// CHECK: marginfi SOL bank — validated by marginfi against the group
+    #[account(mut)]
+    pub bank: UncheckedAccount<'info>,
+
+    /// CHECK: Bank's liquidity vault authority PDA — validated by marginfi
+    pub liquidity_vault_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bank's liquidity vault — validated by marginfi
+    #[account(mut)]
+    pub liquidity_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Vault-owned WSOL account — validated in instruction logic
+    #[account(mut)]
+    pub wsol_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: marginfi program
+    #[account(address = marginfi::PROGRAM_ID)]
+    pub marginfi_program: UncheckedAccount<'info>,
+    // marginfi bank + oracle accounts for the health check passed via remaining_accounts
+}
+
+impl<'info> Lending<'info> {
+    pub fn lending_accounts<'a>(
+        &'a self,
+        vault: &'a AccountInfo<'info>,
+    ) -> marginfi::LendingAccounts<'a, 'info> {
+        marginfi::LendingAccounts {
+            marginfi_program: &self.marginfi_program,
+            group: &self.marginfi_group,
+            marginfi_account: &self.marginfi_account,
+            vault,
+            bank: &self.bank,
+            token_account: &self.wsol_account,
+            liquidity_vault_authority: &self.liquidity_vault_authority,
+            liquidity_vault: &self.liquidity_vault,
+            token_program: &self.token_program,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateTemplate<'info> {
+    #[account(
+        mut,
+        seeds = [b"template", operator.key().as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+        has_one = operator @ EscrowError::Unauthorized
+    )]
+    pub template: Account<'info, SessionTemplate>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The single-key admin, or a Realms governance account via an executed proposal
+    pub admin: Signer<'info>,
+}
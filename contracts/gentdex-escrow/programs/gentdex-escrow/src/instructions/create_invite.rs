@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::InviteCreated;
+use crate::state::{InviteParams, SessionInvite, SessionTemplate};
+
+#[derive(Accounts)]
+#[instruction(invite_id: u64)]
+pub struct CreateInvite<'info> {
+    #[account(
+        seeds = [b"template", operator.key().as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+        has_one = operator @ EscrowError::Unauthorized
+    )]
+    pub template: Account<'info, SessionTemplate>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + SessionInvite::INIT_SPACE,
+        seeds = [b"invite", template.key().as_ref(), &invite_id.to_le_bytes()],
+        bump
+    )]
+    pub invite: Account<'info, SessionInvite>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn create_invite(ctx: Context<CreateInvite>, invite_id: u64, params: InviteParams) -> Result<()> {
+    params.validate()?;
+    let invite = &mut ctx.accounts.invite;
+    invite.template = ctx.accounts.template.key();
+    invite.invite_id = invite_id;
+    invite.operator = ctx.accounts.operator.key();
+    invite.invitee = params.invitee;
+    invite.duration_days = params.duration_days;
+    invite.max_trade_lamports = params.max_trade_lamports;
+    invite.operator_fee_share_bps = params.operator_fee_share_bps;
+    invite.expires_at = params.expires_at;
+    invite.bump = ctx.bumps.invite;
+
+    emit!(InviteCreated {
+        invite: invite.key(),
+        template: invite.template,
+        invitee: invite.invitee,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::lookup_table;
+use super::ManageLookupTable;
+
+pub(crate) fn create_lookup_table(ctx: Context<ManageLookupTable>, recent_slot: u64) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.authority.key(), EscrowError::Unauthorized);
+    require!(vault.lookup_table == Pubkey::default(), EscrowError::InvalidStatus);
+
+    let (address, bump) = lookup_table::derive_address(&vault.key(), recent_slot);
+    require_keys_eq!(ctx.accounts.lookup_table.key(), address, EscrowError::InvalidLookupTable);
+    lookup_table::create(
+        &ctx.accounts.lookup_table,
+        &vault.to_account_info(),
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        &vault.signer_seeds(),
+        recent_slot,
+        bump,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.lookup_table = address;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
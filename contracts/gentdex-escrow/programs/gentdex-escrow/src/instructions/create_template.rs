@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::events::TemplateUpdated;
+use crate::state::{SessionTemplate, TemplateParams};
+
+#[derive(Accounts)]
+#[instruction(template_id: u64)]
+pub struct CreateTemplate<'info> {
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + SessionTemplate::INIT_SPACE,
+        seeds = [b"template", operator.key().as_ref(), &template_id.to_le_bytes()],
+        bump
+    )]
+    pub template: Account<'info, SessionTemplate>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn create_template(
+    ctx: Context<CreateTemplate>,
+    template_id: u64,
+    params: TemplateParams,
+) -> Result<()> {
+    params.validate()?;
+    let template = &mut ctx.accounts.template;
+    template.operator = ctx.accounts.operator.key();
+    template.template_id = template_id;
+    template.fees_accrued = 0;
+    template.bump = ctx.bumps.template;
+    template.apply(params);
+
+    emit!(TemplateUpdated {
+        template: template.key(),
+        operator: template.operator,
+        template_id,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::compute_fee::{accrued_compute_fee, settle_compute_fee, warnings};
+use crate::errors::EscrowError;
+use crate::events::ComputeFeeDeducted;
+use crate::guard;
+use crate::session::settle_duration_points;
+use crate::state::{ProtocolConfig, RewardsAccount, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct DeductComputeFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// CHECK: Treasury wallet
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Anyone can crank this
+    pub cranker: Signer<'info>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// CHECK: The bot's operator credit, if its operator has funded one — drawn on before the vault
+    #[account(mut, seeds = [b"operator_credit", vault.bot.as_ref()], bump)]
+    pub operator_credit: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The user's reward points — credited with the session's unsettled duration points
+    #[account(mut, seeds = [b"rewards", vault.user.as_ref()], bump = rewards.bump)]
+    pub rewards: Account<'info, RewardsAccount>,
+}
+
+pub(crate) fn deduct_compute_fee(ctx: Context<DeductComputeFee>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.status == VaultStatus::Active || vault.status == VaultStatus::Paused,
+        EscrowError::InvalidStatus
+    );
+    require!(vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+    guard::ensure_unlocked(vault)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let (days_elapsed, _) = accrued_compute_fee(vault, now)?;
+    // Minimum 1 day between deductions
+    require!(days_elapsed >= 1, EscrowError::TooEarlyForDeduction);
+    settle_duration_points(vault, &mut ctx.accounts.rewards, &ctx.accounts.config.rewards, now);
+
+    // The operator's credit pays first; the session pays whatever it doesn't cover
+    let (_, fee) = settle_compute_fee(
+        vault,
+        &ctx.accounts.operator_credit,
+        &ctx.accounts.treasury,
+        &ctx.accounts.fee_router,
+        now,
+    )?;
+
+    // If balance is zero, expire the session
+    if vault.balance == 0 {
+        vault.status = VaultStatus::Expired;
+    }
+
+    emit!(ComputeFeeDeducted {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        fee,
+        remaining_balance: vault.balance,
+    });
+    let (low_balance, expiring) = warnings(vault, vault.key(), now);
+    if let Some(warning) = low_balance {
+        emit!(warning);
+    }
+    if let Some(warning) = expiring {
+        emit!(warning);
+    }
+
+    Ok(())
+}
+
+/// `deduct_compute_fee`, but a no-op when the session isn't live or a full
+/// day hasn't passed since the last deduction. Returns whether it deducted.
+pub(crate) fn deduct_compute_fee_if_due(ctx: Context<DeductComputeFee>) -> Result<bool> {
+    let vault = &ctx.accounts.vault;
+    if !matches!(vault.status, VaultStatus::Active | VaultStatus::Paused) {
+        return Ok(false);
+    }
+    let (days_elapsed, _) = accrued_compute_fee(vault, Clock::get()?.unix_timestamp)?;
+    if days_elapsed < 1 {
+        return Ok(false);
+    }
+    deduct_compute_fee(ctx)?;
+    Ok(true)
+}
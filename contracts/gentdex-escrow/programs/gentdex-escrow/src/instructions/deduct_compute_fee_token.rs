@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::compute_fee::{accrued_compute_fee, settle_compute_fee_token, warnings, OperatorCreditAccounts};
+use crate::errors::EscrowError;
+use crate::events::ComputeFeeDeducted;
+use crate::guard;
+use crate::state::{ProtocolConfig, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct DeductComputeFeeToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        associated_token::mint = vault.base_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.base_mint,
+        constraint = treasury_token_account.owner == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Anyone can crank this
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Treasury wallet — receives, in SOL, whatever the bot's operator credit covers
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// CHECK: The bot's operator credit, if its operator has funded one — drawn on before the vault
+    #[account(mut, seeds = [b"operator_credit", vault.bot.as_ref()], bump)]
+    pub operator_credit: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    // SOL and base-mint price updates via remaining_accounts, if the bot has operator credit
+}
+
+pub(crate) fn deduct_compute_fee_token<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DeductComputeFeeToken<'info>>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.status == VaultStatus::Active || vault.status == VaultStatus::Paused,
+        EscrowError::InvalidStatus
+    );
+    guard::ensure_unlocked(vault)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let (days_elapsed, _) = accrued_compute_fee(vault, now)?;
+    require!(days_elapsed >= 1, EscrowError::TooEarlyForDeduction);
+
+    // The operator's credit pays first; the session pays whatever it doesn't cover
+    let (_, fee) = settle_compute_fee_token(
+        vault,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+        OperatorCreditAccounts {
+            operator_credit: &ctx.accounts.operator_credit,
+            treasury: &ctx.accounts.treasury,
+            fee_router: &ctx.accounts.fee_router,
+            config: &ctx.accounts.config,
+            price_feeds: ctx.remaining_accounts,
+        },
+        now,
+    )?;
+
+    if vault.balance == 0 {
+        vault.status = VaultStatus::Expired;
+    }
+
+    emit!(ComputeFeeDeducted {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        fee,
+        remaining_balance: vault.balance,
+    });
+    let (low_balance, expiring) = warnings(vault, vault.key(), now);
+    if let Some(warning) = low_balance {
+        emit!(warning);
+    }
+    if let Some(warning) = expiring {
+        emit!(warning);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::Deposited;
+use crate::session::{activate_session, fund_session, load_template};
+use crate::stake_for_discount;
+use crate::state::{BotProfile, ProtocolConfig, RewardsAccount, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + RewardsAccount::INIT_SPACE,
+        seeds = [b"rewards", user.key().as_ref()],
+        bump
+    )]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// CHECK: The user's stake account, if any — read for the fee discount tier
+    #[account(seeds = [b"stake", user.key().as_ref()], bump)]
+    pub stake: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet for fee collection
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// The bot's profile, for sessions opened requiring a verified bot — its
+    /// tier at funding prices the compute fee
+    #[account(seeds = [b"bot_profile", vault.bot.as_ref()], bump = bot_profile.bump)]
+    pub bot_profile: Option<Account<'info, BotProfile>>,
+    // The session's template (writable) passed via remaining_accounts, if it has one
+}
+
+pub(crate) fn deposit<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let fee_bps = fee_bps(ctx.accounts)?;
+    fund(ctx, amount, fee_bps)
+}
+
+/// The setup fee for this deposit, discounted by the user's stake tier.
+pub(crate) fn fee_bps(accounts: &Deposit) -> Result<u64> {
+    stake_for_discount::discounted_fee_bps(accounts.config.fee_bps as u64, &accounts.stake)
+}
+
+/// Fund the session with `amount`, `fee_bps` of it going to fees.
+pub(crate) fn fund<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+    amount: u64,
+    fee_bps: u64,
+) -> Result<()> {
+    // Read-only checks first
+    require!(ctx.accounts.vault.status == VaultStatus::Pending, EscrowError::InvalidStatus);
+    require!(ctx.accounts.vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(ctx.accounts.vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+
+    let mut template = load_template(&ctx.accounts.vault, ctx.remaining_accounts)?;
+    let with_template = template.is_some();
+    let (fee, trading_balance) = fund_session(
+        &mut ctx.accounts.vault,
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+        template.as_mut(),
+        ctx.accounts.fee_router.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        fee_bps,
+        amount,
+    )?;
+    activate_session(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.config,
+        with_template,
+        ctx.accounts.bot_profile.as_deref(),
+        trading_balance,
+        fee,
+    )?;
+
+    let vault = &ctx.accounts.vault;
+    ctx.accounts.rewards.register(vault.user, ctx.bumps.rewards);
+
+    emit!(Deposited {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        amount,
+        fee,
+        trading_balance,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
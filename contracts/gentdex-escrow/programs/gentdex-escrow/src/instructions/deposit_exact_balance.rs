@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::lamports;
+use super::deposit::{fee_bps, fund};
+use super::Deposit;
+
+pub(crate) fn deposit_exact_balance<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+    trading_balance: u64,
+) -> Result<()> {
+    let fee_bps = fee_bps(ctx.accounts)?;
+    let amount = lamports::gross_for_net(trading_balance, fee_bps).ok_or(EscrowError::MathOverflow)?;
+    fund(ctx, amount, fee_bps)
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::EscrowError;
+use crate::events::{BridgeDeposited, Deposited};
+use crate::session::activate_session;
+use crate::state::{ProtocolConfig, RewardsAccount, Vault};
+use crate::{math, stake_for_discount, wormhole};
+
+#[derive(Accounts)]
+pub struct DepositFromBridge<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Whoever redeems the transfer — also the Token Bridge's payer, and pays
+    /// for `rewards` the first time the user deposits
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RewardsAccount::INIT_SPACE,
+        seeds = [b"rewards", vault.user.as_ref()],
+        bump
+    )]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// CHECK: The user's stake account, if any — read for the fee discount tier
+    #[account(seeds = [b"stake", vault.user.as_ref()], bump)]
+    pub stake: UncheckedAccount<'info>,
+
+    /// CHECK: The guardian-signed transfer, as posted by the core bridge
+    #[account(owner = wormhole::CORE_BRIDGE_PROGRAM_ID @ EscrowError::InvalidBridgeTransfer)]
+    pub posted_vaa: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = vault.base_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.base_mint,
+        constraint = treasury_token_account.owner == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Checked against the Token Bridge program id
+    pub token_bridge_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn deposit_from_bridge<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DepositFromBridge<'info>>,
+    wrapped: bool,
+) -> Result<()> {
+    // The Token Bridge delivers tokens, so it funds token sessions
+    require!(!ctx.accounts.vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+    let transfer = wormhole::read_transfer(&ctx.accounts.posted_vaa)?;
+    // Only the sender the user authorized may activate the session
+    let funder = ctx.accounts.vault.funder;
+    require!(
+        funder != Pubkey::default() && transfer.sender == funder.to_bytes(),
+        EscrowError::UnauthorizedFunder
+    );
+
+    // Whatever the Token Bridge delivers to the vault's token account is the deposit
+    let before = ctx.accounts.vault_token_account.amount;
+    let vault = &ctx.accounts.vault;
+    wormhole::complete_transfer(
+        &ctx.accounts.token_bridge_program,
+        ctx.remaining_accounts,
+        &ctx.accounts.posted_vaa.key(),
+        &ctx.accounts.vault_token_account.key(),
+        &vault.key(),
+        &vault.signer_seeds(),
+        wrapped,
+    )?;
+    ctx.accounts.vault_token_account.reload()?;
+    let amount = ctx.accounts.vault_token_account.amount
+        .checked_sub(before)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    let fee_bps = stake_for_discount::discounted_fee_bps(
+        ctx.accounts.config.fee_bps as u64,
+        &ctx.accounts.stake,
+    )?;
+    let (fee, trading_balance) = math::split_fee(amount, fee_bps)?;
+
+    // Fee from the vault's token account to the treasury's
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &[&vault.signer_seeds()],
+        ),
+        fee,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    activate_session(vault, &ctx.accounts.config, false, None, trading_balance, fee)?;
+
+    ctx.accounts.rewards.register(vault.user, ctx.bumps.rewards);
+
+    emit!(BridgeDeposited {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        emitter_chain: transfer.emitter_chain,
+        sequence: transfer.sequence,
+        sender: transfer.sender,
+        amount,
+    });
+    emit!(Deposited {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        amount,
+        fee,
+        trading_balance,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::EscrowError;
+use crate::events::Deposited;
+use crate::session::activate_session;
+use crate::state::{ProtocolConfig, RewardsAccount, Vault};
+use crate::{math, stake_for_discount};
+
+#[derive(Accounts)]
+pub struct DepositFromDln<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Holder of the order's proceeds — DLN's external-call authority, as
+    /// authorized by the user with `set_funder`
+    #[account(constraint = funder.key() == vault.funder @ EscrowError::UnauthorizedFunder)]
+    pub funder: Signer<'info>,
+
+    /// Pays for `rewards` the first time the user deposits
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RewardsAccount::INIT_SPACE,
+        seeds = [b"rewards", vault.user.as_ref()],
+        bump
+    )]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// CHECK: The user's stake account, if any — read for the fee discount tier
+    #[account(seeds = [b"stake", vault.user.as_ref()], bump)]
+    pub stake: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = vault.base_mint, token::authority = funder)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = vault.base_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.base_mint,
+        constraint = treasury_token_account.owner == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn deposit_from_dln(ctx: Context<DepositFromDln>) -> Result<()> {
+    // The whole of what the order delivered, so the call needn't know the fill amount
+    let amount = ctx.accounts.funder_token_account.amount;
+    // DLN delivers tokens, so it funds token sessions
+    require!(!ctx.accounts.vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+
+    let fee_bps = stake_for_discount::discounted_fee_bps(
+        ctx.accounts.config.fee_bps as u64,
+        &ctx.accounts.stake,
+    )?;
+    let (fee, trading_balance) = math::split_fee(amount, fee_bps)?;
+
+    // Trading balance to the vault's token account, fee to the treasury's
+    for (to, amount) in [
+        (&ctx.accounts.vault_token_account, trading_balance),
+        (&ctx.accounts.treasury_token_account, fee),
+    ] {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.funder_token_account.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    let vault = &mut ctx.accounts.vault;
+    activate_session(vault, &ctx.accounts.config, false, None, trading_balance, fee)?;
+
+    ctx.accounts.rewards.register(vault.user, ctx.bumps.rewards);
+
+    emit!(Deposited {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        amount,
+        fee,
+        trading_balance,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::EscrowError;
+use crate::events::Deposited;
+use crate::session::activate_session;
+use crate::{math, stake_for_discount};
+use crate::state::{ProtocolConfig, RewardsAccount, Vault};
+
+#[derive(Accounts)]
+pub struct DepositToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    /// Pays for `rewards` the first time the user deposits — the user, or a
+    /// relayer for users holding only the session's token
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RewardsAccount::INIT_SPACE,
+        seeds = [b"rewards", user.key().as_ref()],
+        bump
+    )]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// CHECK: The user's stake account, if any — read for the fee discount tier
+    #[account(seeds = [b"stake", user.key().as_ref()], bump)]
+    pub stake: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = vault.base_mint, token::authority = user)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = vault.base_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.base_mint,
+        constraint = treasury_token_account.owner == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn deposit_token(ctx: Context<DepositToken>, amount: u64) -> Result<()> {
+    require!(ctx.accounts.vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(!ctx.accounts.vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+
+    let fee_bps = stake_for_discount::discounted_fee_bps(
+        ctx.accounts.config.fee_bps as u64,
+        &ctx.accounts.stake,
+    )?;
+    let (fee, trading_balance) = math::split_fee(amount, fee_bps)?;
+
+    // Trading balance from user to the vault's token account
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        trading_balance,
+    )?;
+
+    // Fee from user to the treasury's token account
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        fee,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    activate_session(vault, &ctx.accounts.config, false, None, trading_balance, fee)?;
+
+    ctx.accounts.rewards.register(vault.user, ctx.bumps.rewards);
+
+    emit!(Deposited {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        amount,
+        fee,
+        trading_balance,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
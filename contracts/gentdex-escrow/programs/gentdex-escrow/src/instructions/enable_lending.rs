@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::adapters::marginfi;
+use crate::errors::EscrowError;
+use crate::events::LendingEnabled;
+use crate::math;
+use crate::state::{Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct EnableLending<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: marginfi group — must be whitelisted
+    #[account(constraint = marginfi::is_whitelisted_group(&marginfi_group.key()) @ EscrowError::InvalidDexAccount)]
+    pub marginfi_group: UncheckedAccount<'info>,
+
+    /// Fresh keypair for the vault's marginfi account — created by marginfi
+    #[account(mut)]
+    pub marginfi_account: Signer<'info>,
+
+    /// CHECK: marginfi program
+    #[account(address = marginfi::PROGRAM_ID)]
+    pub marginfi_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn enable_lending(ctx: Context<EnableLending>, lend_cap_bps: u16) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(
+        vault.status == VaultStatus::Active || vault.status == VaultStatus::Paused,
+        EscrowError::InvalidStatus
+    );
+    require!(vault.lending_account == Pubkey::default(), EscrowError::InvalidStatus);
+    require!(lend_cap_bps as u64 <= math::BPS_DENOMINATOR, EscrowError::InvalidLendCap);
+
+    marginfi::initialize_account(
+        &ctx.accounts.marginfi_program,
+        &ctx.accounts.marginfi_group,
+        &ctx.accounts.marginfi_account,
+        &vault.to_account_info(),
+        &ctx.accounts.user,
+        &ctx.accounts.system_program,
+        &vault.signer_seeds(),
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.lending_account = ctx.accounts.marginfi_account.key();
+    vault.lend_cap_bps = lend_cap_bps;
+    vault.record_user_activity()?;
+
+    emit!(LendingEnabled {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        lending_account: vault.lending_account,
+        lend_cap_bps,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::adapters::drift;
+use crate::errors::EscrowError;
+use crate::events::PerpsEnabled;
+use crate::state::{Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct EnablePerps<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Drift global state
+    #[account(mut, address = drift::state_address())]
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's Drift sub-account — created by Drift
+    #[account(mut, address = drift::user_address(&vault.key()))]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's Drift user stats — created by Drift
+    #[account(mut, address = drift::user_stats_address(&vault.key()))]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Drift program
+    #[account(address = drift::PROGRAM_ID)]
+    pub drift_program: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn enable_perps(ctx: Context<EnablePerps>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(
+        vault.status == VaultStatus::Active || vault.status == VaultStatus::Paused,
+        EscrowError::InvalidStatus
+    );
+    require!(!vault.perps_enabled, EscrowError::InvalidStatus);
+
+    drift::initialize(
+        &ctx.accounts.drift_program,
+        &ctx.accounts.drift_state,
+        &ctx.accounts.drift_user,
+        &ctx.accounts.drift_user_stats,
+        &vault.to_account_info(),
+        &ctx.accounts.user,
+        &ctx.accounts.rent.to_account_info(),
+        &ctx.accounts.system_program,
+        &vault.signer_seeds(),
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.perps_enabled = true;
+    vault.record_user_activity()?;
+
+    emit!(PerpsEnabled {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        drift_user: ctx.accounts.drift_user.key(),
+    });
+
+    Ok(())
+}
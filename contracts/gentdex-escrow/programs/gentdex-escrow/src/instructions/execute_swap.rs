@@ -0,0 +1,397 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{adapters, batching, math, oracle, protection};
+use crate::compute_fee::settle_compute_fee;
+use crate::errors::EscrowError;
+use crate::events::{SlippageBudgetExhausted, SwapExecuted, SwapRejected};
+use crate::guard::SwapGuard;
+use crate::session::{pause_if_blacklisted, settle_duration_points};
+use crate::state::{ProtocolConfig, RewardsAccount, SwapRejectReason, SwapResult, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct ExecuteSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub bot: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The session owner's rewards, created on their first deposit
+    #[account(mut, seeds = [b"rewards", vault.user.as_ref()], bump = rewards.bump)]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// CHECK: Treasury wallet, paid the compute fee a SOL session's swap settles
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// CHECK: The bot's operator credit, if its operator has funded one — drawn on before the vault
+    #[account(mut, seeds = [b"operator_credit", vault.bot.as_ref()], bump)]
+    pub operator_credit: UncheckedAccount<'info>,
+
+    /// CHECK: The DEX program to CPI into — validated in instruction logic
+    pub dex_program: UncheckedAccount<'info>,
+
+    /// Vault's token account for the output mint — required with an exposure cap
+    #[account(mut)]
+    pub output_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Pyth price update for the session's base currency (SOL/USD for SOL
+    /// sessions) — validated against config in `load_swap_prices`
+    pub base_price_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Pyth price update for the output mint — validated against config
+    pub output_price_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Instructions sysvar — required for Jito-tip protection
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// Vault's token account for the base mint — required for token sessions,
+    /// whose swaps sell from it
+    #[account(
+        mut,
+        associated_token::mint = vault.base_mint,
+        associated_token::authority = vault
+    )]
+    pub base_token_account: Option<Account<'info, TokenAccount>>,
+    // Additional DEX accounts passed via remaining_accounts
+}
+
+/// Whether the swap meets the session's anti-sandwich requirements, if any.
+pub fn is_protected(accounts: &ExecuteSwap, recent_slot: Option<u64>) -> Result<bool> {
+    let vault = &accounts.vault;
+    if vault.max_slot_age > 0 {
+        let current_slot = Clock::get()?.slot;
+        match recent_slot {
+            Some(slot) if protection::slot_is_recent(slot, current_slot, vault.max_slot_age) => {}
+            _ => return Ok(false),
+        }
+    }
+    if vault.require_jito_tip {
+        match accounts.instructions_sysvar.as_ref() {
+            Some(sysvar) if protection::has_jito_tip(sysvar)? => {}
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+/// Pyth prices for the session's base currency and `mint`, from the feeds
+/// registered in config.
+pub fn load_swap_prices(accounts: &ExecuteSwap, mint: &Pubkey, now: i64) -> Result<oracle::SwapPrices> {
+    let (Some(base_feed), Some(mint_feed)) = (
+        accounts.base_price_feed.as_ref(),
+        accounts.output_price_feed.as_ref(),
+    ) else {
+        return err!(EscrowError::PriceFeedMissing);
+    };
+    let config = &accounts.config;
+    let base = config.price_feed(&accounts.vault.base_mint).ok_or(EscrowError::PriceFeedMissing)?;
+    let token = config.price_feed(mint).ok_or(EscrowError::PriceFeedMissing)?;
+
+    Ok(oracle::SwapPrices {
+        base: oracle::load_price(base_feed, &base.feed_id, now)?,
+        base_decimals: base.decimals,
+        token: oracle::load_price(mint_feed, &token.feed_id, now)?,
+        decimals: token.decimals,
+    })
+}
+
+/// A token session's base token account, which its swaps sell from and the
+/// guard measures; `None` for SOL sessions.
+fn base_account<'info>(accounts: &ExecuteSwap<'info>) -> Result<Option<AccountInfo<'info>>> {
+    if accounts.vault.is_sol_session() {
+        return Ok(None);
+    }
+    let account = accounts.base_token_account.as_ref().ok_or(EscrowError::BaseCurrencyMismatch)?;
+    Ok(Some(account.to_account_info()))
+}
+
+/// Fail if the vault's holding of `mint` exceeds its exposure cap, valuing the
+/// portfolio in the session's base currency. Other token positions aren't
+/// valued, which only understates the portfolio and makes the check stricter.
+pub fn check_exposure(accounts: &mut ExecuteSwap, mint: &Pubkey, prices: &oracle::SwapPrices) -> Result<()> {
+    let vault_key = accounts.vault.key();
+    let Some(token_account) = accounts.output_token_account.as_mut() else {
+        return err!(EscrowError::PriceFeedMissing);
+    };
+    require_keys_eq!(token_account.owner, vault_key, EscrowError::InvalidDexAccount);
+    require_keys_eq!(token_account.mint, *mint, EscrowError::InvalidDexAccount);
+    token_account.reload()?;
+
+    let vault = &accounts.vault;
+    let position = prices.value_in_base(token_account.amount)?;
+    let total = vault.balance
+        .checked_add(vault.perps_collateral)
+        .and_then(|t| t.checked_add(vault.lent_amount))
+        .and_then(|t| t.checked_add(position))
+        .ok_or(EscrowError::MathOverflow)?;
+    require!(
+        position <= math::bps_of(total, vault.max_exposure_bps as u64)?,
+        EscrowError::ExposureCapExceeded
+    );
+
+    Ok(())
+}
+
+/// The DEX route and, for a session batching its events, its `TradeBatch`,
+/// which the bot passes after the route.
+fn split_route<'info>(
+    vault: &Vault,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<(&'info [AccountInfo<'info>], Option<&'info AccountInfo<'info>>)> {
+    if !vault.batch_trades {
+        return Ok((remaining_accounts, None));
+    }
+    let (trade_batch, route) = remaining_accounts.split_last().ok_or(EscrowError::InvalidTradeBatch)?;
+    Ok((route, Some(trade_batch)))
+}
+
+/// `swap_with_policy` for a bot that numbers its trades: spends `nonce`
+/// first, so a captured transaction can't be replayed. A `dry_run` only
+/// checks it.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_with_nonce<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    memo: [u8; 32],
+    recent_slot: Option<u64>,
+    route_data: &[u8],
+    nonce: u64,
+    dry_run: bool,
+) -> Result<SwapResult> {
+    require!(ctx.accounts.vault.bot == ctx.accounts.bot.key(), EscrowError::Unauthorized);
+    match dry_run {
+        true => require!(nonce > ctx.accounts.vault.trade_nonce, EscrowError::StaleTradeNonce),
+        false => ctx.accounts.vault.use_trade_nonce(nonce)?,
+    }
+    swap_with_policy(ctx, amount_in, minimum_amount_out, memo, recent_slot, route_data, dry_run)
+}
+
+/// Policy checks, then the DEX CPI through its adapter. Shared by the
+/// `execute_swap*` instructions; `memo` is all zeroes when unset and
+/// `route_data` empty for venues whose instruction the adapter builds. A
+/// `dry_run` writes nothing: it reports a blacklisted bot without pausing
+/// the session, settles no compute fee, emits no events, and stops after
+/// validating the route.
+pub fn swap_with_policy<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    memo: [u8; 32],
+    recent_slot: Option<u64>,
+    route_data: &[u8],
+    dry_run: bool,
+) -> Result<SwapResult> {
+    require!(ctx.accounts.vault.bot == ctx.accounts.bot.key(), EscrowError::Unauthorized);
+    // A dry run reports the rejection a real swap would get, without the pause
+    let blacklisted = match dry_run {
+        true => {
+            ctx.accounts.vault.status == VaultStatus::Active
+                && ctx.accounts.config.is_bot_blacklisted(&ctx.accounts.vault.bot)
+        }
+        false => pause_if_blacklisted(&mut ctx.accounts.vault, &ctx.accounts.config),
+    };
+    if blacklisted {
+        if dry_run {
+            return Ok(SwapResult::rejected(SwapRejectReason::BotBlacklisted, 0, ctx.accounts.vault.balance));
+        }
+        emit!(SwapRejected {
+            session_id: ctx.accounts.vault.session_id,
+            vault: ctx.accounts.vault.key(),
+            bot: ctx.accounts.bot.key(),
+            dex_program: ctx.accounts.dex_program.key(),
+            amount_in,
+            reason: SwapRejectReason::BotBlacklisted,
+            timestamp: Clock::get()?.unix_timestamp,
+            memo,
+        });
+        return Ok(SwapResult::rejected(SwapRejectReason::BotBlacklisted, 0, ctx.accounts.vault.balance));
+    }
+
+    let vault = &ctx.accounts.vault;
+    require!(vault.status == VaultStatus::Active, EscrowError::InvalidStatus);
+    
+    // Check not expired
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < vault.expires_at, EscrowError::SessionExpired);
+    
+    // A DEX off the whitelist is an authorization failure, never a rejection
+    let dex_program = ctx.accounts.dex_program.key();
+    require!(
+        ctx.accounts.config.allows_dex(&dex_program, vault.whitelist_version)
+            && (vault.allowed_dexes.is_empty() || vault.allowed_dexes.contains(&dex_program)),
+        EscrowError::DexNotWhitelisted
+    );
+
+    // A SOL session settles the compute fee it owes first, so the swap can't
+    // spend it. Token sessions settle through `deduct_compute_fee_token`
+    let fee = match !dry_run && vault.is_sol_session() {
+        true => {
+            let vault = &mut ctx.accounts.vault;
+            settle_duration_points(vault, &mut ctx.accounts.rewards, &ctx.accounts.config.rewards, now);
+            let (_, fee) = settle_compute_fee(
+                vault,
+                &ctx.accounts.operator_credit,
+                &ctx.accounts.treasury,
+                &ctx.accounts.fee_router,
+                now,
+            )?;
+            fee
+        }
+        false => 0,
+    };
+    let vault = &ctx.accounts.vault;
+
+    // Policy checks are rejected gracefully: the instruction succeeds and
+    // emits SwapRejected so bots can see why instead of an opaque error code
+    let rejection = if vault.resigned_at != 0 {
+        Some(SwapRejectReason::BotResigned)
+    } else if amount_in > vault.balance {
+        Some(SwapRejectReason::InsufficientBalance)
+    } else if vault.disabled_dexes.contains(&dex_program) {
+        Some(SwapRejectReason::DexDisabled)
+    } else if vault.slippage_budget > 0 && vault.slippage_consumed >= vault.slippage_budget {
+        Some(SwapRejectReason::SlippageBudgetExhausted)
+    } else if !is_protected(ctx.accounts, recent_slot)? {
+        Some(SwapRejectReason::Unprotected)
+    } else if vault.max_trade_lamports > 0 && amount_in > vault.max_trade_lamports {
+        Some(SwapRejectReason::TradeLimitExceeded)
+    } else {
+        None
+    };
+    if let Some(reason) = rejection {
+        if dry_run {
+            return Ok(SwapResult::rejected(reason, fee, vault.balance));
+        }
+        emit!(SwapRejected {
+            session_id: vault.session_id,
+            vault: vault.key(),
+            bot: ctx.accounts.bot.key(),
+            dex_program,
+            amount_in,
+            reason,
+            timestamp: now,
+            memo,
+        });
+        return Ok(SwapResult::rejected(reason, fee, vault.balance));
+    }
+
+    let session_id = vault.session_id;
+    let user = vault.user;
+    let bump = [vault.bump];
+    let vault_seeds: &[&[u8]] = &[b"vault", session_id.as_ref(), user.as_ref(), &bump];
+    let (route, trade_batch) = split_route(vault, ctx.remaining_accounts)?;
+    let base_account = base_account(ctx.accounts)?;
+
+    // The DEX-specific adapter validates its accounts (passed via
+    // remaining_accounts) and performs the CPI, signed by the vault PDA
+    let swap_ctx = adapters::SwapContext {
+        dex_program: &ctx.accounts.dex_program.to_account_info(),
+        vault: &ctx.accounts.vault.to_account_info(),
+        bot: &ctx.accounts.bot.to_account_info(),
+        vault_seeds,
+        remaining_accounts: route,
+        route_data,
+        base_account: base_account.as_ref(),
+    };
+    if dry_run {
+        adapters::check_route(&swap_ctx, amount_in, minimum_amount_out)?;
+        return Ok(SwapResult {
+            rejected: None,
+            spent: 0,
+            output_mint: Pubkey::default(),
+            amount_out: 0,
+            slippage: 0,
+            fee,
+            balance: ctx.accounts.vault.balance,
+        });
+    }
+
+    // Debit and lock the vault before handing control to the DEX
+    let guard = match &base_account {
+        Some(info) => SwapGuard::enter_token(&mut ctx.accounts.vault, info, amount_in)?,
+        None => SwapGuard::enter(&mut ctx.accounts.vault, amount_in)?,
+    };
+    let output = adapters::swap(&swap_ctx, amount_in, minimum_amount_out)?;
+
+    let spent = guard.exit(&mut ctx.accounts.vault)?;
+    ctx.accounts.vault.total_volume = ctx.accounts.vault.total_volume
+        .checked_add(spent)
+        .ok_or(EscrowError::MathOverflow)?;
+    ctx.accounts.vault.track_position(output.mint)?;
+    let vault = &ctx.accounts.vault;
+    let mut slippage = 0;
+    let needs_prices = vault.max_exposure_bps > 0 || vault.slippage_budget > 0;
+    if needs_prices && output.mint != Pubkey::default() {
+        let prices = load_swap_prices(ctx.accounts, &output.mint, now)?;
+        if ctx.accounts.vault.max_exposure_bps > 0 {
+            check_exposure(ctx.accounts, &output.mint, &prices)?;
+        }
+        // Slippage: base currency spent beyond the oracle value of what came back
+        let vault = &mut ctx.accounts.vault;
+        if vault.slippage_budget > 0 {
+            slippage = spent.saturating_sub(prices.value_in_base(output.amount_out)?);
+            vault.slippage_consumed = vault.slippage_consumed
+                .checked_add(slippage)
+                .ok_or(EscrowError::MathOverflow)?;
+            if vault.slippage_consumed >= vault.slippage_budget {
+                emit!(SlippageBudgetExhausted {
+                    session_id: vault.session_id,
+                    vault: vault.key(),
+                    slippage_consumed: vault.slippage_consumed,
+                    slippage_budget: vault.slippage_budget,
+                });
+            }
+        }
+    }
+
+    let points = ctx.accounts.config.rewards.volume_points(spent, now);
+    let rewards = &mut ctx.accounts.rewards;
+    let (user, bump) = (rewards.user, rewards.bump);
+    rewards.accrue(user, bump, points, spent, now);
+
+    let vault = &ctx.accounts.vault;
+    let executed = SwapExecuted {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        bot: ctx.accounts.bot.key(),
+        dex_program,
+        amount_in,
+        minimum_amount_out,
+        timestamp: now,
+        output_mint: output.mint,
+        amount_out: output.amount_out,
+        memo,
+    };
+    match trade_batch {
+        Some(info) => batching::record(info, &vault.key(), &executed, Clock::get()?.slot)?,
+        None => emit!(executed),
+    }
+
+    Ok(SwapResult {
+        rejected: None,
+        spent,
+        output_mint: output.mint,
+        amount_out: output.amount_out,
+        slippage,
+        fee,
+        balance: vault.balance,
+    })
+}
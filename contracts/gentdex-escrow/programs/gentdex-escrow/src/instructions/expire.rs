@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::SessionExpiredEvent;
+use crate::state::{Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct Expire<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub cranker: Signer<'info>,
+}
+
+pub(crate) fn expire(ctx: Context<Expire>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.status == VaultStatus::Active || vault.status == VaultStatus::Paused,
+        EscrowError::InvalidStatus
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= vault.expires_at, EscrowError::SessionNotExpired);
+
+    vault.status = VaultStatus::Expired;
+
+    emit!(SessionExpiredEvent {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        remaining_balance: vault.balance,
+    });
+
+    Ok(())
+}
+
+/// `expire`, but a no-op when the session isn't live or hasn't reached its
+/// expiry. Returns whether it expired the session.
+pub(crate) fn expire_if_due(ctx: Context<Expire>) -> Result<bool> {
+    let vault = &ctx.accounts.vault;
+    let live = matches!(vault.status, VaultStatus::Active | VaultStatus::Paused);
+    if !live || Clock::get()?.unix_timestamp < vault.expires_at {
+        return Ok(false);
+    }
+    expire(ctx)?;
+    Ok(true)
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::lookup_table;
+use crate::session::stop_blacklisted_bot;
+use super::ManageLookupTable;
+
+pub(crate) fn extend_lookup_table(
+    ctx: Context<ManageLookupTable>,
+    addresses: Vec<Pubkey>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    if authority == ctx.accounts.vault.bot && stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &ctx.accounts.vault;
+    require!(
+        authority == vault.user || authority == vault.bot,
+        EscrowError::Unauthorized
+    );
+    require!(vault.lookup_table != Pubkey::default(), EscrowError::InvalidLookupTable);
+    require_keys_eq!(ctx.accounts.lookup_table.key(), vault.lookup_table, EscrowError::InvalidLookupTable);
+
+    lookup_table::extend(
+        &ctx.accounts.lookup_table,
+        &vault.to_account_info(),
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        &vault.signer_seeds(),
+        &addresses,
+    )
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::SessionExtended;
+use crate::math;
+use crate::state::VaultStatus;
+use super::UserAction;
+
+pub(crate) fn extend_session(ctx: Context<UserAction>, days: u16) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(
+        matches!(vault.status, VaultStatus::Active | VaultStatus::Paused),
+        EscrowError::InvalidStatus
+    );
+    require!(days > 0, EscrowError::InvalidExtension);
+    // A resigning bot's notice period caps the session
+    require!(vault.resigned_at == 0, EscrowError::BotResigned);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < vault.expires_at, EscrowError::SessionExpired);
+
+    vault.duration_days = vault.duration_days.checked_add(days).ok_or(EscrowError::MathOverflow)?;
+    vault.expires_at = math::add_days(vault.expires_at, days as u64)?;
+    vault.last_user_activity = now;
+
+    emit!(SessionExtended {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        days,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+use crate::batching;
+use crate::state::{TradeBatch, Vault};
+
+#[derive(Accounts)]
+pub struct FlushTradeBatch<'info> {
+    #[account(
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut, seeds = [b"trade_batch", vault.key().as_ref()], bump = trade_batch.bump)]
+    pub trade_batch: Account<'info, TradeBatch>,
+}
+
+pub(crate) fn flush_trade_batch(ctx: Context<FlushTradeBatch>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    batching::emit_batch(&mut ctx.accounts.trade_batch, vault.key(), vault.session_id, vault.bot);
+    Ok(())
+}
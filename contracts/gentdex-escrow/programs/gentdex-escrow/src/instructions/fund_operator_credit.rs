@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::errors::EscrowError;
+use crate::events::OperatorCreditChanged;
+use crate::state::OperatorCredit;
+
+#[derive(Accounts)]
+pub struct FundOperatorCredit<'info> {
+    #[account(
+        init_if_needed,
+        payer = bot,
+        space = 8 + OperatorCredit::INIT_SPACE,
+        seeds = [b"operator_credit", bot.key().as_ref()],
+        bump
+    )]
+    pub operator_credit: Account<'info, OperatorCredit>,
+
+    #[account(mut)]
+    pub bot: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn fund_operator_credit(ctx: Context<FundOperatorCredit>, amount: u64) -> Result<()> {
+    require!(amount > 0, EscrowError::InsufficientBalance);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.bot.to_account_info(),
+                to: ctx.accounts.operator_credit.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let credit = &mut ctx.accounts.operator_credit;
+    credit.bot = ctx.accounts.bot.key();
+    credit.bump = ctx.bumps.operator_credit;
+    credit.balance = credit.balance
+        .checked_add(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    emit!(OperatorCreditChanged {
+        bot: credit.bot,
+        funded: amount,
+        withdrawn: 0,
+        balance: credit.balance,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+use crate::compute_fee::accrued_compute_fee;
+use crate::state::AccruedFees;
+use super::ViewSession;
+
+pub(crate) fn get_accrued_fees(ctx: Context<ViewSession>) -> Result<AccruedFees> {
+    let now = Clock::get()?.unix_timestamp;
+    let (days, compute_fee) = accrued_compute_fee(&ctx.accounts.vault, now)?;
+
+    Ok(AccruedFees { days, compute_fee })
+}
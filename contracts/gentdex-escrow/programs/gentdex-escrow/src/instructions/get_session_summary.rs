@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::compute_fee::accrued_compute_fee;
+use crate::state::SessionSummary;
+use super::ViewSession;
+
+pub(crate) fn get_session_summary(ctx: Context<ViewSession>) -> Result<SessionSummary> {
+    let vault = &ctx.accounts.vault;
+    let now = Clock::get()?.unix_timestamp;
+    let (_, accrued_compute_fee) = accrued_compute_fee(vault, now)?;
+    let snapshot = vault.snapshot()?;
+
+    // Withdrawals count towards what the user got back
+    let returned = snapshot.value.saturating_add(snapshot.withdrawn);
+    let seconds_remaining = if vault.funded_at == 0 { 0 } else { (vault.expires_at - now).max(0) };
+
+    Ok(SessionSummary {
+        status: vault.status,
+        balance: vault.balance,
+        value: snapshot.value,
+        accrued_compute_fee,
+        seconds_remaining,
+        drawdown: snapshot.deposited.saturating_sub(returned),
+    })
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::errors::EscrowError;
+use crate::events::{SessionCreated, SessionGifted};
+use crate::session::{open_session, require_verified_bot};
+use crate::state::{ProtocolConfig, SessionGift, Vault};
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 16], duration_days: u16, bot_pubkey: Pubkey, recipient: Pubkey)]
+pub struct GiftSession<'info> {
+    #[account(
+        init,
+        payer = giver,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", session_id.as_ref(), recipient.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = giver,
+        space = 8 + SessionGift::INIT_SPACE,
+        seeds = [b"gift", vault.key().as_ref()],
+        bump
+    )]
+    pub gift: Account<'info, SessionGift>,
+
+    #[account(mut)]
+    pub giver: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Treasury wallet for fee collection — must be the protocol's
+    #[account(constraint = treasury.key() == config.treasury @ EscrowError::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn gift_session(
+    ctx: Context<GiftSession>,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot_pubkey: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(amount >= ctx.accounts.config.deposit_floor(), EscrowError::DepositTooSmall);
+    require!(!ctx.accounts.config.is_bot_blacklisted(&bot_pubkey), EscrowError::BotBlacklisted);
+    let verified_bot = require_verified_bot(&bot_pubkey, ctx.remaining_accounts)?.is_some();
+    open_session(
+        &mut ctx.accounts.vault,
+        recipient,
+        ctx.accounts.treasury.key(),
+        session_id,
+        duration_days,
+        bot_pubkey,
+        ctx.bumps.vault,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+    vault.base_mint = native_mint::ID;
+    vault.verified_bot = verified_bot;
+    // Set from the bot's tier at funding
+    vault.daily_compute_fee = 0;
+
+    // The gift waits, fee and all, until the recipient accepts
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.giver.to_account_info(),
+                to: ctx.accounts.gift.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    let gift = &mut ctx.accounts.gift;
+    gift.vault = vault.key();
+    gift.giver = ctx.accounts.giver.key();
+    gift.amount = amount;
+    gift.created_at = vault.created_at;
+    gift.bump = ctx.bumps.gift;
+
+    emit!(SessionCreated {
+        session_id,
+        vault: vault.key(),
+        user: recipient,
+        bot: bot_pubkey,
+        duration_days,
+    });
+    emit!(SessionGifted {
+        session_id,
+        vault: vault.key(),
+        giver: gift.giver,
+        user: recipient,
+        amount,
+    });
+
+    Ok(())
+}
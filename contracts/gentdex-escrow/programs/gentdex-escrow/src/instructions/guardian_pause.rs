@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_GUARDIAN_PAUSE_DAYS;
+use crate::errors::EscrowError;
+use crate::events::{GuardianPaused, SessionPaused};
+use crate::math::SECONDS_PER_DAY;
+use crate::state::{PauseReason, ProtocolConfig, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = guardian @ EscrowError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    pub guardian: Signer<'info>,
+}
+
+pub(crate) fn guardian_pause(ctx: Context<GuardianPause>, reason: PauseReason) -> Result<()> {
+    require!(reason != PauseReason::None, EscrowError::InvalidPauseReason);
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.status == VaultStatus::Active, EscrowError::InvalidStatus);
+
+    // Only trading stops; withdrawals don't check the status beyond Pending
+    let now = Clock::get()?.unix_timestamp;
+    vault.status = VaultStatus::Paused;
+    vault.guardian_paused_at = now;
+    vault.pause_reason = reason;
+
+    emit!(SessionPaused {
+        session_id: vault.session_id,
+        vault: vault.key(),
+    });
+    emit!(GuardianPaused {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        guardian: ctx.accounts.guardian.key(),
+        reason,
+        lifts_at: now.saturating_add(MAX_GUARDIAN_PAUSE_DAYS as i64 * SECONDS_PER_DAY),
+    });
+
+    Ok(())
+}
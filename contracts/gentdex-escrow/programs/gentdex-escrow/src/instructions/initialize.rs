@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::errors::EscrowError;
+use crate::events::SessionCreated;
+use crate::session::{open_session, require_verified_bot};
+use crate::state::{ProtocolConfig, Vault};
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 16])]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", session_id.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Treasury wallet for fee collection — must be the protocol's
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Pays the vault's rent — the user, or a relayer opening it for them
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn initialize(
+    ctx: Context<Initialize>,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot_pubkey: Pubkey,
+) -> Result<()> {
+    require!(!ctx.accounts.config.is_bot_blacklisted(&bot_pubkey), EscrowError::BotBlacklisted);
+    let verified_bot = require_verified_bot(&bot_pubkey, ctx.remaining_accounts)?.is_some();
+    open_session(
+        &mut ctx.accounts.vault,
+        ctx.accounts.user.key(),
+        ctx.accounts.treasury.key(),
+        session_id,
+        duration_days,
+        bot_pubkey,
+        ctx.bumps.vault,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+    vault.base_mint = native_mint::ID;
+    vault.verified_bot = verified_bot;
+    // Set from the bot's tier at funding
+    vault.daily_compute_fee = 0;
+
+    emit!(SessionCreated {
+        session_id,
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        bot: bot_pubkey,
+        duration_days,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::errors::EscrowError;
+use crate::events::{Deposited, SessionCreated};
+use crate::session::{activate_session, fund_session, open_session, require_verified_bot};
+use crate::stake_for_discount;
+use crate::state::{ProtocolConfig, RewardsAccount, Vault};
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 16])]
+pub struct InitializeAndDeposit<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", session_id.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RewardsAccount::INIT_SPACE,
+        seeds = [b"rewards", user.key().as_ref()],
+        bump
+    )]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// CHECK: The user's stake account, if any — read for the fee discount tier
+    #[account(seeds = [b"stake", user.key().as_ref()], bump)]
+    pub stake: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet for fee collection — must be the protocol's
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Pays rent for the vault and `rewards`; the deposit itself comes from the user
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn initialize_and_deposit(
+    ctx: Context<InitializeAndDeposit>,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot_pubkey: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(!ctx.accounts.config.is_bot_blacklisted(&bot_pubkey), EscrowError::BotBlacklisted);
+    let bot_profile = require_verified_bot(&bot_pubkey, ctx.remaining_accounts)?;
+    open_session(
+        &mut ctx.accounts.vault,
+        ctx.accounts.user.key(),
+        ctx.accounts.treasury.key(),
+        session_id,
+        duration_days,
+        bot_pubkey,
+        ctx.bumps.vault,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+    vault.base_mint = native_mint::ID;
+    vault.verified_bot = bot_profile.is_some();
+
+    let fee_bps = stake_for_discount::discounted_fee_bps(ctx.accounts.config.fee_bps as u64, &ctx.accounts.stake)?;
+    let (fee, trading_balance) = fund_session(
+        vault,
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+        None,
+        ctx.accounts.fee_router.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        fee_bps,
+        amount,
+    )?;
+    activate_session(vault, &ctx.accounts.config, false, bot_profile.as_ref(), trading_balance, fee)?;
+
+    ctx.accounts.rewards.register(vault.user, ctx.bumps.rewards);
+
+    emit!(SessionCreated {
+        session_id,
+        vault: vault.key(),
+        user: vault.user,
+        bot: bot_pubkey,
+        duration_days,
+    });
+    emit!(Deposited {
+        session_id,
+        vault: vault.key(),
+        amount,
+        fee,
+        trading_balance,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{DAILY_COMPUTE_FEE, FEE_BPS, MIN_DEPOSIT, MIN_STABLE_DEPOSIT};
+use crate::errors::EscrowError;
+use crate::state::{ProtocolConfig, RewardsSchedule};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ EscrowError::Unauthorized)]
+    pub program: Program<'info, crate::program::GentdexEscrow>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    guardian: Pubkey,
+    treasury: Pubkey,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.admin.key();
+    config.pending_admin = Pubkey::default();
+    config.admin_is_governance = false;
+    config.guardian = guardian;
+    config.treasury = treasury;
+    config.fee_bps = FEE_BPS as u16;
+    config.daily_compute_fee = DAILY_COMPUTE_FEE;
+    config.whitelist = default_dex_whitelist();
+    config.grandfathered = Vec::new();
+    config.whitelist_version = 1;
+    config.rewards = RewardsSchedule::default();
+    config.price_feeds = Vec::new();
+    config.min_deposit = MIN_DEPOSIT;
+    config.max_deposit = 0;
+    config.min_stable_deposit = MIN_STABLE_DEPOSIT;
+    config.max_stable_deposit = 0;
+    config.bump = ctx.bumps.config;
+
+    Ok(())
+}
+
+/// The whitelist a fresh ProtocolConfig starts from; governance edits it from there.
+pub fn default_dex_whitelist() -> Vec<Pubkey> {
+    let whitelisted: [&str; 9] = [
+        // Jupiter Aggregator v6
+        "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
+        // Raydium AMM
+        "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        // Raydium CLMM
+        "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK",
+        // Orca Whirlpool
+        "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+        // PumpSwap (Pump.fun AMM)
+        "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA",
+        // Phoenix (order book)
+        "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY",
+        // OpenBook v2 (order book)
+        "opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb",
+        // Lifinity v2 (proactive MM)
+        "2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEyPy64bL7aD3c",
+        // SolFi (proactive MM)
+        "SoLFiHG9TfgtdUXUjWAxi3LtvYuFyDLVhBWxdMZxyCe",
+    ];
+
+    whitelisted
+        .iter()
+        .map(|addr| addr.parse::<Pubkey>().unwrap())
+        .collect()
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::errors::EscrowError;
+use crate::events::{InviteRedeemed, SessionCreated};
+use crate::session::{open_session, require_verified_bot};
+use crate::state::{ProtocolConfig, SessionInvite, SessionTemplate, Vault};
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 16])]
+pub struct InitializeFromInvite<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", session_id.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"template", template.operator.as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, SessionTemplate>,
+
+    // Single use: closed back to the operator once redeemed
+    #[account(
+        mut,
+        close = operator,
+        seeds = [b"invite", template.key().as_ref(), &invite.invite_id.to_le_bytes()],
+        bump = invite.bump,
+        has_one = template @ EscrowError::InvalidInvite,
+        has_one = operator @ EscrowError::InvalidInvite
+    )]
+    pub invite: Account<'info, SessionInvite>,
+
+    /// CHECK: The invite's operator, receives its rent
+    #[account(mut)]
+    pub operator: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Treasury wallet for fee collection — must be the protocol's
+    #[account(constraint = treasury.key() == config.treasury @ EscrowError::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Pays the vault's rent; the invite's goes back to its operator
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn initialize_from_invite(
+    ctx: Context<InitializeFromInvite>,
+    session_id: [u8; 16],
+) -> Result<()> {
+    let template = &ctx.accounts.template;
+    let invite = &ctx.accounts.invite;
+    let user = ctx.accounts.user.key();
+    require!(
+        invite.redeemable_by(&user, Clock::get()?.unix_timestamp),
+        EscrowError::InvalidInvite
+    );
+    require!(!ctx.accounts.config.is_bot_blacklisted(&template.bot), EscrowError::BotBlacklisted);
+    let verified_bot = require_verified_bot(&template.bot, ctx.remaining_accounts)?.is_some();
+    open_session(
+        &mut ctx.accounts.vault,
+        user,
+        ctx.accounts.treasury.key(),
+        session_id,
+        invite.duration_days,
+        template.bot,
+        ctx.bumps.vault,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+    vault.base_mint = native_mint::ID;
+    vault.verified_bot = verified_bot;
+    // Set from the bot's tier at funding
+    vault.daily_compute_fee = 0;
+    vault.template = template.key();
+    vault.operator_fee_share_bps = invite.operator_fee_share_bps;
+    vault.max_trade_lamports = invite.max_trade_lamports;
+    vault.allowed_dexes = template.allowed_dexes.clone();
+
+    emit!(InviteRedeemed {
+        invite: invite.key(),
+        session_id,
+        vault: vault.key(),
+        user,
+    });
+    emit!(SessionCreated {
+        session_id,
+        vault: vault.key(),
+        user,
+        bot: template.bot,
+        duration_days: invite.duration_days,
+    });
+
+    Ok(())
+}
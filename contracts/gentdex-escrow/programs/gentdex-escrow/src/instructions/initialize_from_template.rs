@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::errors::EscrowError;
+use crate::events::SessionCreated;
+use crate::session::{open_session, require_verified_bot};
+use crate::state::{ProtocolConfig, SessionTemplate, Vault};
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 16])]
+pub struct InitializeFromTemplate<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", session_id.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"template", template.operator.as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, SessionTemplate>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Treasury wallet for fee collection — must be the protocol's
+    #[account(constraint = treasury.key() == config.treasury @ EscrowError::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Pays the vault's rent — the user, or a relayer on their behalf
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn initialize_from_template(
+    ctx: Context<InitializeFromTemplate>,
+    session_id: [u8; 16],
+) -> Result<()> {
+    let template = &ctx.accounts.template;
+    require!(!template.invite_only, EscrowError::InviteRequired);
+    require!(!ctx.accounts.config.is_bot_blacklisted(&template.bot), EscrowError::BotBlacklisted);
+    let verified_bot = require_verified_bot(&template.bot, ctx.remaining_accounts)?.is_some();
+    open_session(
+        &mut ctx.accounts.vault,
+        ctx.accounts.user.key(),
+        ctx.accounts.treasury.key(),
+        session_id,
+        template.duration_days,
+        template.bot,
+        ctx.bumps.vault,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+    vault.base_mint = native_mint::ID;
+    vault.verified_bot = verified_bot;
+    // Set from the bot's tier at funding
+    vault.daily_compute_fee = 0;
+    vault.template = template.key();
+    vault.operator_fee_share_bps = template.operator_fee_share_bps;
+    vault.max_trade_lamports = template.max_trade_lamports;
+    vault.allowed_dexes = template.allowed_dexes.clone();
+
+    emit!(SessionCreated {
+        session_id,
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        bot: template.bot,
+        duration_days: template.duration_days,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::errors::EscrowError;
+use crate::events::SessionCreated;
+use crate::pda;
+use crate::session::{open_session, require_verified_bot};
+use crate::state::{ProtocolConfig, UserRegistry, Vault};
+
+#[derive(Accounts)]
+pub struct InitializeIndexed<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserRegistry::INIT_SPACE,
+        seeds = [b"registry", user.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, UserRegistry>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Vault::INIT_SPACE,
+        // Sliced, not `.as_ref()`: IDL seed resolution can't follow the call,
+        // and only leaves the PDA out of the IDL for this form
+        seeds = [b"vault", &pda::indexed_session_id(registry.session_count)[..], user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Treasury wallet for fee collection — must be the protocol's
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Pays rent for the vault, and for the registry on the user's first
+    /// indexed session
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn initialize_indexed(
+    ctx: Context<InitializeIndexed>,
+    duration_days: u16,
+    bot_pubkey: Pubkey,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    let session_id = pda::indexed_session_id(registry.session_count);
+    registry.user = ctx.accounts.user.key();
+    registry.bump = ctx.bumps.registry;
+    registry.session_count = registry.session_count
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    require!(!ctx.accounts.config.is_bot_blacklisted(&bot_pubkey), EscrowError::BotBlacklisted);
+    let verified_bot = require_verified_bot(&bot_pubkey, ctx.remaining_accounts)?.is_some();
+    open_session(
+        &mut ctx.accounts.vault,
+        ctx.accounts.user.key(),
+        ctx.accounts.treasury.key(),
+        session_id,
+        duration_days,
+        bot_pubkey,
+        ctx.bumps.vault,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+    vault.base_mint = native_mint::ID;
+    vault.verified_bot = verified_bot;
+    // Set from the bot's tier at funding
+    vault.daily_compute_fee = 0;
+
+    emit!(SessionCreated {
+        session_id,
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        bot: bot_pubkey,
+        duration_days,
+    });
+
+    Ok(())
+}
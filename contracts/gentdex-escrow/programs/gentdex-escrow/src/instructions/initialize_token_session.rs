@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::errors::EscrowError;
+use crate::events::SessionCreated;
+use crate::session::{is_approved_base_mint, open_session, require_verified_bot};
+use crate::state::{ProtocolConfig, Vault};
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 16])]
+pub struct InitializeTokenSession<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", session_id.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    /// Pays rent for the vault and its token account — the user, or a
+    /// relayer for users holding only the session's token
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Treasury wallet for fee collection — must be the protocol's
+    #[account(constraint = treasury.key() == config.treasury @ EscrowError::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(constraint = is_approved_base_mint(&base_mint.key()) @ EscrowError::BaseCurrencyMismatch)]
+    pub base_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = base_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn initialize_token_session(
+    ctx: Context<InitializeTokenSession>,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot_pubkey: Pubkey,
+) -> Result<()> {
+    require!(!ctx.accounts.config.is_bot_blacklisted(&bot_pubkey), EscrowError::BotBlacklisted);
+    require_verified_bot(&bot_pubkey, ctx.remaining_accounts)?;
+    open_session(
+        &mut ctx.accounts.vault,
+        ctx.accounts.user.key(),
+        ctx.accounts.treasury.key(),
+        session_id,
+        duration_days,
+        bot_pubkey,
+        ctx.bumps.vault,
+    )?;
+    let vault = &mut ctx.accounts.vault;
+    vault.base_mint = ctx.accounts.base_mint.key();
+    // Set from the funded balance at deposit
+    vault.daily_compute_fee = 0;
+
+    emit!(SessionCreated {
+        session_id,
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        bot: bot_pubkey,
+        duration_days,
+    });
+
+    Ok(())
+}
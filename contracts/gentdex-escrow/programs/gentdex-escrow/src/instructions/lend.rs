@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::{adapters, guard, math};
+use crate::adapters::marginfi;
+use crate::errors::EscrowError;
+use crate::events::LendingMoved;
+use crate::session::stop_blacklisted_bot;
+use crate::state::VaultStatus;
+use super::Lending;
+
+pub(crate) fn lend<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Lending<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    if authority == ctx.accounts.vault.bot && stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &ctx.accounts.vault;
+    require!(
+        authority == vault.user || authority == vault.bot,
+        EscrowError::Unauthorized
+    );
+    require!(vault.status == VaultStatus::Active, EscrowError::InvalidStatus);
+    require!(vault.lending_account != Pubkey::default(), EscrowError::LendingNotEnabled);
+    require!(vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+    require!(amount <= vault.balance, EscrowError::InsufficientBalance);
+    guard::ensure_unlocked(vault)?;
+
+    let total = vault.balance
+        .checked_add(vault.lent_amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    let lent_after = vault.lent_amount
+        .checked_add(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    require!(
+        lent_after <= math::bps_of(total, vault.lend_cap_bps as u64)?,
+        EscrowError::LendCapExceeded
+    );
+
+    let vault_info = vault.to_account_info();
+    adapters::vault_token_account(&ctx.accounts.wsol_account, &vault.key(), &native_mint::ID)?;
+    adapters::wrap_sol(
+        &vault_info,
+        &ctx.accounts.wsol_account,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+    marginfi::deposit(
+        &ctx.accounts.lending_accounts(&vault_info),
+        &vault.signer_seeds(),
+        amount,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.balance = vault.balance
+        .checked_sub(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    vault.lent_amount = lent_after;
+
+    emit!(LendingMoved {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        lent: amount,
+        unwound: 0,
+        lent_amount: vault.lent_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_GUARDIAN_PAUSE_DAYS;
+use crate::errors::EscrowError;
+use crate::events::{GuardianPauseEnded, SessionResumed};
+use crate::math::SECONDS_PER_DAY;
+use crate::state::{PauseReason, ProtocolConfig, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct LiftGuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The guardian, or once the pause has run its course, anyone
+    pub cranker: Signer<'info>,
+}
+
+pub(crate) fn lift_guardian_pause(ctx: Context<LiftGuardianPause>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.status == VaultStatus::Paused && vault.guardian_paused_at > 0,
+        EscrowError::NotGuardianPaused
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let lifts_at = vault
+        .guardian_paused_at
+        .saturating_add(MAX_GUARDIAN_PAUSE_DAYS as i64 * SECONDS_PER_DAY);
+    require!(
+        ctx.accounts.cranker.key() == ctx.accounts.config.guardian || now >= lifts_at,
+        EscrowError::GuardianPauseActive
+    );
+    require!(now < vault.expires_at, EscrowError::SessionExpired);
+
+    let reason = vault.pause_reason;
+    vault.status = VaultStatus::Active;
+    vault.guardian_paused_at = 0;
+    vault.pause_reason = PauseReason::None;
+
+    emit!(GuardianPauseEnded {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        reason,
+        confirmed: false,
+    });
+    emit!(SessionResumed {
+        session_id: vault.session_id,
+        vault: vault.key(),
+    });
+
+    Ok(())
+}
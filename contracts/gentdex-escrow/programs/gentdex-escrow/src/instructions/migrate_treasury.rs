@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::TreasuryMigrated;
+use crate::state::{ProtocolConfig, Vault};
+
+#[derive(Accounts)]
+pub struct MigrateTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+pub(crate) fn migrate_treasury(ctx: Context<MigrateTreasury>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let treasury = ctx.accounts.config.treasury;
+    require_keys_neq!(vault.treasury, treasury, EscrowError::InvalidTreasury);
+
+    emit!(TreasuryMigrated {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        user: vault.user,
+        previous: vault.treasury,
+        treasury,
+    });
+    vault.treasury = treasury;
+
+    Ok(())
+}
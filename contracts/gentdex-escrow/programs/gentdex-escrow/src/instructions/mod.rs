@@ -0,0 +1,196 @@
+//! Instruction handlers, one file per instruction with its accounts struct.
+//! Entry points and their docs live in the `#[program]` module in lib.rs.
+
+mod accept_admin;
+mod accept_gift;
+mod adopt_latest_whitelist;
+mod assert_solvent;
+mod attest_bot;
+mod claim_operator_fees;
+mod claim_routed_fees;
+mod close_epoch;
+mod close_lookup_table;
+mod confirm_guardian_pause;
+mod contexts;
+mod create_lookup_table;
+mod create_invite;
+mod create_template;
+mod deduct_compute_fee;
+mod deduct_compute_fee_token;
+mod deposit;
+mod deposit_exact_balance;
+mod deposit_for_program;
+mod deposit_from_bridge;
+mod deposit_from_dln;
+mod deposit_token;
+mod enable_lending;
+mod enable_perps;
+mod execute_swap;
+mod expire;
+mod get_accrued_fees;
+mod get_session_summary;
+mod gift_session;
+mod guardian_pause;
+mod extend_lookup_table;
+mod extend_session;
+mod flush_trade_batch;
+mod fund_operator_credit;
+mod initialize;
+mod initialize_and_deposit;
+mod initialize_config;
+mod initialize_for_program;
+mod initialize_from_invite;
+mod initialize_from_template;
+mod initialize_indexed;
+mod initialize_token_session;
+mod lend;
+mod lift_guardian_pause;
+mod migrate_treasury;
+mod pause;
+mod pause_blacklisted;
+mod perps_cancel_order;
+mod perps_deposit;
+mod perps_place_order;
+mod perps_withdraw;
+mod propose_admin;
+mod reclaim_gift;
+mod recover;
+mod refresh_upgrade_info;
+mod release_position;
+mod request_withdrawal;
+mod resign;
+mod resume;
+mod revoke_dex;
+mod revoke_invite;
+mod set_bot_blacklisted;
+mod set_bot_tier;
+mod set_deposit_limits;
+mod set_dex_enabled;
+mod set_dex_whitelisted;
+mod set_fee_route;
+mod set_fees;
+mod set_funder;
+mod set_guardian;
+mod set_lend_cap;
+mod set_max_exposure;
+mod set_max_positions;
+mod set_price_feed;
+mod set_recovery;
+mod set_rewards_schedule;
+mod set_slippage_budget;
+mod set_stable_deposit_limits;
+mod set_swap_protection;
+mod set_tier_compute_fees;
+mod set_trade_batching;
+mod set_treasury;
+mod set_withdrawal_notice;
+mod set_upgrade_info;
+mod stake;
+mod transfer_to_session;
+mod unstake;
+mod unwind_lending;
+mod update_template;
+mod withdraw;
+mod withdraw_and_close;
+mod withdraw_for_program;
+mod withdraw_operator_credit;
+mod withdraw_position;
+mod withdraw_token;
+mod withdraw_with_signature;
+
+pub use accept_admin::*;
+pub(crate) use accept_gift::*;
+pub use adopt_latest_whitelist::*;
+pub use assert_solvent::*;
+pub use attest_bot::*;
+pub(crate) use claim_operator_fees::*;
+pub use claim_routed_fees::*;
+pub use close_epoch::*;
+pub(crate) use close_lookup_table::*;
+pub(crate) use confirm_guardian_pause::*;
+pub use contexts::*;
+pub(crate) use create_lookup_table::*;
+pub use create_invite::*;
+pub use create_template::*;
+pub use deduct_compute_fee::*;
+pub use deduct_compute_fee_token::*;
+pub use deposit::*;
+pub(crate) use deposit_exact_balance::*;
+pub use deposit_for_program::*;
+pub(crate) use deposit_from_bridge::*;
+pub(crate) use deposit_from_dln::*;
+pub use deposit_token::*;
+pub use enable_lending::*;
+pub use enable_perps::*;
+pub use execute_swap::*;
+pub use expire::*;
+pub(crate) use get_accrued_fees::*;
+pub(crate) use get_session_summary::*;
+pub(crate) use gift_session::*;
+pub use guardian_pause::*;
+pub(crate) use extend_lookup_table::*;
+pub(crate) use extend_session::*;
+pub(crate) use flush_trade_batch::*;
+pub(crate) use fund_operator_credit::*;
+pub use initialize::*;
+pub(crate) use initialize_and_deposit::*;
+pub use initialize_config::*;
+pub use initialize_for_program::*;
+pub use initialize_from_invite::*;
+pub use initialize_from_template::*;
+pub use initialize_indexed::*;
+pub use initialize_token_session::*;
+pub(crate) use lend::*;
+pub use lift_guardian_pause::*;
+pub(crate) use migrate_treasury::*;
+pub(crate) use pause::*;
+pub use pause_blacklisted::*;
+pub(crate) use perps_cancel_order::*;
+pub(crate) use perps_deposit::*;
+pub(crate) use perps_place_order::*;
+pub(crate) use perps_withdraw::*;
+pub(crate) use propose_admin::*;
+pub(crate) use reclaim_gift::*;
+pub use recover::*;
+pub use refresh_upgrade_info::*;
+pub use release_position::*;
+pub(crate) use request_withdrawal::*;
+pub use resign::*;
+pub(crate) use resume::*;
+pub(crate) use revoke_dex::*;
+pub use revoke_invite::*;
+pub(crate) use set_bot_blacklisted::*;
+pub use set_bot_tier::*;
+pub(crate) use set_deposit_limits::*;
+pub(crate) use set_dex_enabled::*;
+pub(crate) use set_dex_whitelisted::*;
+pub use set_fee_route::*;
+pub(crate) use set_fees::*;
+pub(crate) use set_funder::*;
+pub(crate) use set_guardian::*;
+pub(crate) use set_lend_cap::*;
+pub(crate) use set_max_exposure::*;
+pub(crate) use set_max_positions::*;
+pub(crate) use set_price_feed::*;
+pub(crate) use set_recovery::*;
+pub(crate) use set_rewards_schedule::*;
+pub(crate) use set_slippage_budget::*;
+pub(crate) use set_stable_deposit_limits::*;
+pub(crate) use set_swap_protection::*;
+pub(crate) use set_tier_compute_fees::*;
+pub(crate) use set_trade_batching::*;
+pub(crate) use set_treasury::*;
+pub(crate) use set_withdrawal_notice::*;
+pub use set_upgrade_info::*;
+pub use stake::*;
+pub use transfer_to_session::*;
+pub use unstake::*;
+pub(crate) use unwind_lending::*;
+pub(crate) use update_template::*;
+pub use withdraw::*;
+pub(crate) use withdraw_and_close::*;
+pub use withdraw_for_program::*;
+pub(crate) use withdraw_operator_credit::*;
+pub use withdraw_position::*;
+pub use withdraw_token::*;
+pub(crate) use withdraw_with_signature::*;
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::SessionPaused;
+use crate::state::VaultStatus;
+use super::UserAction;
+
+pub(crate) fn pause(ctx: Context<UserAction>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(vault.status == VaultStatus::Active, EscrowError::InvalidStatus);
+    
+    vault.status = VaultStatus::Paused;
+    vault.record_user_activity()?;
+
+    emit!(SessionPaused {
+        session_id: vault.session_id,
+        vault: vault.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::session::pause_if_blacklisted;
+use crate::state::{ProtocolConfig, Vault};
+
+#[derive(Accounts)]
+pub struct PauseBlacklisted<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    pub cranker: Signer<'info>,
+}
+
+pub(crate) fn pause_blacklisted(ctx: Context<PauseBlacklisted>) -> Result<()> {
+    require!(
+        pause_if_blacklisted(&mut ctx.accounts.vault, &ctx.accounts.config),
+        EscrowError::InvalidStatus
+    );
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::adapters::drift;
+use crate::errors::EscrowError;
+use crate::session::stop_blacklisted_bot;
+use super::PerpsOrder;
+
+pub(crate) fn perps_cancel_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PerpsOrder<'info>>,
+    order_id: Option<u32>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    if authority == ctx.accounts.vault.bot && stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &ctx.accounts.vault;
+    require!(
+        authority == vault.user || authority == vault.bot,
+        EscrowError::Unauthorized
+    );
+    require!(vault.perps_enabled, EscrowError::PerpsNotEnabled);
+
+    drift::cancel_order(
+        &ctx.accounts.drift_program,
+        &ctx.accounts.drift_state,
+        &ctx.accounts.drift_user,
+        &vault.to_account_info(),
+        ctx.remaining_accounts,
+        &vault.signer_seeds(),
+        order_id,
+    )
+}
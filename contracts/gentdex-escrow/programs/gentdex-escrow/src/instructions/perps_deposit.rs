@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::{adapters, guard};
+use crate::adapters::drift;
+use crate::errors::EscrowError;
+use crate::events::PerpsCollateralMoved;
+use crate::state::VaultStatus;
+use super::PerpsCollateral;
+
+pub(crate) fn perps_deposit<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PerpsCollateral<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.authority.key(), EscrowError::Unauthorized);
+    require!(vault.status == VaultStatus::Active, EscrowError::InvalidStatus);
+    require!(vault.perps_enabled, EscrowError::PerpsNotEnabled);
+    require!(vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+    require!(amount <= vault.balance, EscrowError::InsufficientBalance);
+    guard::ensure_unlocked(vault)?;
+
+    let vault_info = vault.to_account_info();
+    adapters::vault_token_account(&ctx.accounts.wsol_account, &vault.key(), &native_mint::ID)?;
+    adapters::wrap_sol(
+        &vault_info,
+        &ctx.accounts.wsol_account,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+    drift::deposit(
+        &ctx.accounts.drift_collateral_accounts(&vault_info),
+        ctx.remaining_accounts,
+        &vault.signer_seeds(),
+        amount,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.balance = vault.balance
+        .checked_sub(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    vault.perps_collateral = vault.perps_collateral
+        .checked_add(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    vault.record_user_activity()?;
+
+    emit!(PerpsCollateralMoved {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        deposited: amount,
+        withdrawn: 0,
+        perps_collateral: vault.perps_collateral,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::adapters::drift::PerpOrderParams;
+use crate::adapters::drift;
+use crate::errors::EscrowError;
+use crate::events::PerpOrderPlaced;
+use crate::session::stop_blacklisted_bot;
+use crate::state::VaultStatus;
+use super::PerpsOrder;
+
+pub(crate) fn perps_place_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PerpsOrder<'info>>,
+    params: PerpOrderParams,
+) -> Result<()> {
+    require!(ctx.accounts.vault.bot == ctx.accounts.authority.key(), EscrowError::Unauthorized);
+    if stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &ctx.accounts.vault;
+    require!(vault.perps_enabled, EscrowError::PerpsNotEnabled);
+    require!(
+        vault.status == VaultStatus::Active
+            || vault.status == VaultStatus::Paused
+            || vault.status == VaultStatus::Expired,
+        EscrowError::InvalidStatus
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let trading = vault.status == VaultStatus::Active && now < vault.expires_at && vault.resigned_at == 0;
+    require!(trading || params.reduce_only, EscrowError::ReduceOnly);
+
+    drift::place_perp_order(
+        &ctx.accounts.drift_program,
+        &ctx.accounts.drift_state,
+        &ctx.accounts.drift_user,
+        &vault.to_account_info(),
+        ctx.remaining_accounts,
+        &vault.signer_seeds(),
+        params,
+    )?;
+
+    emit!(PerpOrderPlaced {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        market_index: params.market_index,
+        direction: params.direction,
+        base_asset_amount: params.base_asset_amount,
+        price: params.price,
+        reduce_only: params.reduce_only,
+        timestamp: now,
+    });
+
+    Ok(())
+}
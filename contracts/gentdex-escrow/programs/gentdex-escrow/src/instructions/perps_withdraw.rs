@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, spl_token::native_mint, CloseAccount};
+
+use crate::{adapters, guard};
+use crate::adapters::drift;
+use crate::errors::EscrowError;
+use crate::events::PerpsCollateralMoved;
+use crate::session::stop_blacklisted_bot;
+use crate::state::VaultStatus;
+use super::PerpsCollateral;
+
+pub(crate) fn perps_withdraw<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PerpsCollateral<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    if authority == ctx.accounts.vault.bot && stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &ctx.accounts.vault;
+    require!(
+        authority == vault.user || authority == vault.bot,
+        EscrowError::Unauthorized
+    );
+    require!(
+        !matches!(vault.status, VaultStatus::Pending | VaultStatus::Withdrawn),
+        EscrowError::InvalidStatus
+    );
+    require!(vault.perps_enabled, EscrowError::PerpsNotEnabled);
+    guard::ensure_unlocked(vault)?;
+
+    let vault_info = vault.to_account_info();
+    adapters::vault_token_account(&ctx.accounts.wsol_account, &vault.key(), &native_mint::ID)?;
+    drift::withdraw(
+        &ctx.accounts.drift_collateral_accounts(&vault_info),
+        ctx.remaining_accounts,
+        &vault.signer_seeds(),
+        amount,
+    )?;
+
+    // Unwrap back into the trading balance, as `unwind_lending` does. Only
+    // the token amount is credited; the account's rent isn't balance.
+    let withdrawn = adapters::token_amount(&ctx.accounts.wsol_account)?;
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.wsol_account.to_account_info(),
+            destination: vault_info.clone(),
+            authority: vault_info,
+        },
+        &[&vault.signer_seeds()],
+    ))?;
+
+    // Collateral tracks principal; PnL withdrawn beyond it just zeroes it
+    let vault = &mut ctx.accounts.vault;
+    vault.balance = vault.balance
+        .checked_add(withdrawn)
+        .ok_or(EscrowError::MathOverflow)?;
+    vault.perps_collateral = vault.perps_collateral.saturating_sub(withdrawn);
+
+    emit!(PerpsCollateralMoved {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        deposited: 0,
+        withdrawn,
+        perps_collateral: vault.perps_collateral,
+    });
+
+    Ok(())
+}
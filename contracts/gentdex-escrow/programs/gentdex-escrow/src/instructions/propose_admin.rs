@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+use crate::events::AdminProposed;
+use super::AdminAction;
+
+pub(crate) fn propose_admin(ctx: Context<AdminAction>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_admin = new_admin;
+
+    emit!(AdminProposed {
+        admin: config.admin,
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
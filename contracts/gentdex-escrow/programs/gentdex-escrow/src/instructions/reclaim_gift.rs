@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::GiftReclaimed;
+use crate::state::{SessionGift, Vault};
+
+#[derive(Accounts)]
+pub struct ReclaimGift<'info> {
+    #[account(
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"gift", vault.key().as_ref()],
+        bump = gift.bump,
+        has_one = giver @ EscrowError::Unauthorized,
+        close = giver
+    )]
+    pub gift: Account<'info, SessionGift>,
+
+    #[account(mut)]
+    pub giver: Signer<'info>,
+}
+
+pub(crate) fn reclaim_gift(ctx: Context<ReclaimGift>) -> Result<()> {
+    // `close` returns the gift and its rent; the recipient keeps the empty session
+    emit!(GiftReclaimed {
+        session_id: ctx.accounts.vault.session_id,
+        vault: ctx.accounts.vault.key(),
+        giver: ctx.accounts.giver.key(),
+        amount: ctx.accounts.gift.amount,
+    });
+
+    Ok(())
+}
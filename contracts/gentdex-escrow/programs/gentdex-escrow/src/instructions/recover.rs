@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::RECOVERY_INACTIVITY_DAYS;
+use crate::errors::EscrowError;
+use crate::events::Withdrawn;
+use crate::{guard, math};
+use crate::session::pay_out;
+use crate::state::{BotStats, ProtocolConfig, RewardsAccount, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct Recover<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Also pays for `bot_stats` the first time the bot settles a session
+    #[account(mut)]
+    pub recovery: Signer<'info>,
+
+    /// CHECK: The session's user — the only possible destination for recovered funds
+    #[account(
+        mut,
+        constraint = user.key() == vault.user @ EscrowError::Unauthorized
+    )]
+    pub user: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet — receives any compute fee settled on recovery
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = recovery,
+        space = 8 + BotStats::INIT_SPACE,
+        seeds = [b"bot_stats", vault.bot.as_ref()],
+        bump
+    )]
+    pub bot_stats: Account<'info, BotStats>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// CHECK: The bot's operator credit, if its operator has funded one — drawn on before the vault
+    #[account(mut, seeds = [b"operator_credit", vault.bot.as_ref()], bump)]
+    pub operator_credit: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The user's reward points — credited with the session's unsettled duration points
+    #[account(mut, seeds = [b"rewards", vault.user.as_ref()], bump = rewards.bump)]
+    pub rewards: Account<'info, RewardsAccount>,
+}
+
+pub(crate) fn recover(ctx: Context<Recover>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.recovery != Pubkey::default(), EscrowError::Unauthorized);
+    require!(vault.recovery == ctx.accounts.recovery.key(), EscrowError::Unauthorized);
+    require!(vault.status != VaultStatus::Pending, EscrowError::InvalidStatus);
+    require!(vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+    require!(vault.balance > 0, EscrowError::InsufficientBalance);
+    require!(vault.lent_amount == 0, EscrowError::LendingNotUnwound);
+    require!(vault.perps_collateral == 0, EscrowError::PerpsNotUnwound);
+    guard::ensure_unlocked(vault)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let recoverable_at = math::add_days(vault.last_user_activity, RECOVERY_INACTIVITY_DAYS)?;
+    require!(now >= recoverable_at, EscrowError::UserStillActive);
+
+    let user_info = ctx.accounts.user.to_account_info();
+    let (balance, compute_fee) = pay_out(
+        vault,
+        &ctx.accounts.treasury,
+        &ctx.accounts.fee_router,
+        &ctx.accounts.operator_credit,
+        &user_info,
+        &mut ctx.accounts.bot_stats,
+        ctx.bumps.bot_stats,
+        &mut ctx.accounts.rewards,
+        &ctx.accounts.config.rewards,
+    )?;
+
+    emit!(Withdrawn {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        amount: balance,
+        compute_fee,
+        user: vault.user,
+    });
+
+    Ok(())
+}
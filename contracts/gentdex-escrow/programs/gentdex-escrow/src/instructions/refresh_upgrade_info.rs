@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::UpgradeInfoUpdated;
+use crate::state::UpgradeInfo;
+
+#[derive(Accounts)]
+pub struct RefreshUpgradeInfo<'info> {
+    #[account(mut, seeds = [b"upgrade_info"], bump = upgrade_info.bump)]
+    pub upgrade_info: Account<'info, UpgradeInfo>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ EscrowError::Unauthorized)]
+    pub program: Program<'info, crate::program::GentdexEscrow>,
+
+    pub program_data: Account<'info, ProgramData>,
+}
+
+pub(crate) fn refresh_upgrade_info(ctx: Context<RefreshUpgradeInfo>) -> Result<()> {
+    let info = &mut ctx.accounts.upgrade_info;
+    info.observe(&ctx.accounts.program_data, Clock::get()?.unix_timestamp);
+
+    emit!(UpgradeInfoUpdated {
+        upgrade_authority: info.upgrade_authority,
+        deployed_slot: info.deployed_slot,
+        timelock_seconds: info.timelock_seconds,
+        build_hash: info.build_hash,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::errors::EscrowError;
+use crate::session::stop_blacklisted_bot;
+use crate::state::{ProtocolConfig, Vault};
+
+#[derive(Accounts)]
+pub struct ReleasePosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// User or bot
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        constraint = token_account.owner == vault.key() @ EscrowError::InvalidDexAccount,
+        constraint = token_account.amount == 0 @ EscrowError::InvalidStatus
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+}
+
+pub(crate) fn release_position(ctx: Context<ReleasePosition>) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    if authority == ctx.accounts.vault.bot && stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        authority == vault.user || authority == vault.bot,
+        EscrowError::Unauthorized
+    );
+    let mint = ctx.accounts.token_account.mint;
+    vault.position_mints.retain(|position| position != &mint);
+
+    Ok(())
+}
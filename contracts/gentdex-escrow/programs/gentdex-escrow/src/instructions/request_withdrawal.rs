@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::WithdrawalRequested;
+use super::UserAction;
+
+pub(crate) fn request_withdrawal(ctx: Context<UserAction>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(vault.withdrawal_notice > 0, EscrowError::InvalidWithdrawalNotice);
+
+    let now = Clock::get()?.unix_timestamp;
+    vault.withdraw_requested_at = now;
+    vault.record_user_activity()?;
+
+    emit!(WithdrawalRequested {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        available_at: now.saturating_add(vault.withdrawal_notice),
+    });
+
+    Ok(())
+}
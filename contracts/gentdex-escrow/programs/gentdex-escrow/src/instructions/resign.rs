@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::RESIGNATION_NOTICE_DAYS;
+use crate::errors::EscrowError;
+use crate::events::BotResigned;
+use crate::math;
+use crate::session::stop_blacklisted_bot;
+use crate::state::{ProtocolConfig, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct Resign<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump,
+        has_one = bot @ EscrowError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub bot: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub(crate) fn resign(ctx: Context<Resign>) -> Result<()> {
+    if stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        matches!(vault.status, VaultStatus::Pending | VaultStatus::Active | VaultStatus::Paused),
+        EscrowError::InvalidStatus
+    );
+    require!(vault.resigned_at == 0, EscrowError::BotResigned);
+
+    let now = Clock::get()?.unix_timestamp;
+    vault.resigned_at = now;
+    // Unfunded sessions have no expiry yet, and can no longer be funded
+    if vault.status != VaultStatus::Pending {
+        vault.expires_at = vault.expires_at.min(math::add_days(now, RESIGNATION_NOTICE_DAYS)?);
+    }
+
+    emit!(BotResigned {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        bot: vault.bot,
+        expires_at: vault.expires_at,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::SessionResumed;
+use crate::state::{PauseReason, VaultStatus};
+use super::UserAction;
+
+pub(crate) fn resume(ctx: Context<UserAction>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(vault.status == VaultStatus::Paused, EscrowError::InvalidStatus);
+    
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < vault.expires_at, EscrowError::SessionExpired);
+    
+    // The user may overrule a guardian pause; it's their session
+    vault.status = VaultStatus::Active;
+    vault.guardian_paused_at = 0;
+    vault.pause_reason = PauseReason::None;
+    vault.last_user_activity = now;
+
+    emit!(SessionResumed {
+        session_id: vault.session_id,
+        vault: vault.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::DexWhitelistUpdated;
+use super::AdminAction;
+
+pub(crate) fn revoke_dex(ctx: Context<AdminAction>, program_id: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.whitelist.retain(|key| *key != program_id);
+    config.grandfathered.retain(|entry| entry.program_id != program_id);
+    config.whitelist_version = config.whitelist_version
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    emit!(DexWhitelistUpdated {
+        program_id,
+        whitelisted: false,
+        whitelist_version: config.whitelist_version,
+    });
+
+    Ok(())
+}
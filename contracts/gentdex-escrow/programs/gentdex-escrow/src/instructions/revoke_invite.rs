@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::state::SessionInvite;
+
+#[derive(Accounts)]
+pub struct RevokeInvite<'info> {
+    #[account(
+        mut,
+        close = operator,
+        seeds = [b"invite", invite.template.as_ref(), &invite.invite_id.to_le_bytes()],
+        bump = invite.bump,
+        has_one = operator @ EscrowError::Unauthorized
+    )]
+    pub invite: Account<'info, SessionInvite>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+}
+
+pub(crate) fn revoke_invite(_ctx: Context<RevokeInvite>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_BLACKLISTED_BOTS;
+use crate::errors::EscrowError;
+use crate::events::BotBlacklistUpdated;
+use super::AdminAction;
+
+pub(crate) fn set_bot_blacklisted(ctx: Context<AdminAction>, bot: Pubkey, blacklisted: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let position = config.blacklisted_bots.iter().position(|key| *key == bot);
+    match (blacklisted, position) {
+        (true, None) => {
+            require!(
+                config.blacklisted_bots.len() < MAX_BLACKLISTED_BOTS,
+                EscrowError::BlacklistFull
+            );
+            config.blacklisted_bots.push(bot);
+        }
+        (false, Some(index)) => {
+            config.blacklisted_bots.swap_remove(index);
+        }
+        // Already in the requested state
+        _ => return Ok(()),
+    }
+
+    emit!(BotBlacklistUpdated { bot, blacklisted });
+
+    Ok(())
+}
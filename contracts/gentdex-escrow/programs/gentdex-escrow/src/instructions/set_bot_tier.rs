@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_BOT_TIERS;
+use crate::errors::EscrowError;
+use crate::events::BotTierSet;
+use crate::state::{BotProfile, ProtocolConfig};
+
+#[derive(Accounts)]
+#[instruction(bot: Pubkey)]
+pub struct SetBotTier<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The single-key admin, or a Realms governance account via an executed proposal
+    pub admin: Signer<'info>,
+
+    /// The guardian attests a bot before the admin can tier it
+    #[account(mut, seeds = [b"bot_profile", bot.as_ref()], bump = bot_profile.bump)]
+    pub bot_profile: Account<'info, BotProfile>,
+}
+
+pub(crate) fn set_bot_tier(ctx: Context<SetBotTier>, bot: Pubkey, tier: u8) -> Result<()> {
+    require!(tier as usize <= MAX_BOT_TIERS, EscrowError::InvalidBotTier);
+    ctx.accounts.bot_profile.tier = tier;
+
+    emit!(BotTierSet {
+        bot,
+        tier,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::DepositLimitsUpdated;
+use super::AdminAction;
+
+pub(crate) fn set_deposit_limits(ctx: Context<AdminAction>, min_deposit: u64, max_deposit: u64) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.min_deposit = min_deposit;
+    config.max_deposit = max_deposit;
+    require!(config.within_deposit_cap(config.deposit_floor()), EscrowError::InvalidDepositLimits);
+
+    emit!(DepositLimitsUpdated {
+        min_deposit,
+        max_deposit,
+    });
+
+    Ok(())
+}
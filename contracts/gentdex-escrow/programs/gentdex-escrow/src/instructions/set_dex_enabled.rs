@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_DISABLED_DEXES;
+use crate::errors::EscrowError;
+use crate::events::SessionDexToggled;
+use super::UserAction;
+
+pub(crate) fn set_dex_enabled(ctx: Context<UserAction>, program_id: Pubkey, enabled: bool) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+
+    if enabled {
+        vault.disabled_dexes.retain(|dex| dex != &program_id);
+    } else if !vault.disabled_dexes.contains(&program_id) {
+        require!(
+            vault.disabled_dexes.len() < MAX_DISABLED_DEXES,
+            EscrowError::WhitelistFull
+        );
+        vault.disabled_dexes.push(program_id);
+    }
+    vault.record_user_activity()?;
+
+    emit!(SessionDexToggled {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        program_id,
+        enabled,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_WHITELISTED_DEXES;
+use crate::errors::EscrowError;
+use crate::events::DexWhitelistUpdated;
+use crate::state::GrandfatheredDex;
+use super::AdminAction;
+
+pub(crate) fn set_dex_whitelisted(
+    ctx: Context<AdminAction>,
+    program_id: Pubkey,
+    whitelisted: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let position = config.whitelist.iter().position(|key| *key == program_id);
+    match (whitelisted, position) {
+        (true, None) => {
+            require!(
+                config.whitelist.len() < MAX_WHITELISTED_DEXES,
+                EscrowError::WhitelistFull
+            );
+            config.whitelist.push(program_id);
+            config.grandfathered.retain(|entry| entry.program_id != program_id);
+        }
+        (false, Some(index)) => {
+            require!(
+                config.grandfathered.len() < MAX_WHITELISTED_DEXES,
+                EscrowError::WhitelistFull
+            );
+            config.whitelist.swap_remove(index);
+        }
+        // Already in the requested state
+        _ => return Ok(()),
+    }
+    config.whitelist_version = config.whitelist_version
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+    if !whitelisted {
+        let removed_in_version = config.whitelist_version;
+        config.grandfathered.push(GrandfatheredDex {
+            program_id,
+            removed_in_version,
+        });
+    }
+
+    emit!(DexWhitelistUpdated {
+        program_id,
+        whitelisted,
+        whitelist_version: config.whitelist_version,
+    });
+
+    Ok(())
+}
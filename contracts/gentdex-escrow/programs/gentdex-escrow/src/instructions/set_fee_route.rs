@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::FeeRouteUpdated;
+use crate::state::{FeeRouter, FeeShare, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct SetFeeRoute<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The single-key admin, or a Realms governance account via an executed proposal
+    pub admin: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + FeeRouter::INIT_SPACE,
+        seeds = [b"fee_router"],
+        bump
+    )]
+    pub fee_router: Account<'info, FeeRouter>,
+
+    /// Pays for `fee_router` the first time a route is set
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn set_fee_route(ctx: Context<SetFeeRoute>, shares: Vec<FeeShare>) -> Result<()> {
+    let router = &mut ctx.accounts.fee_router;
+    router.bump = ctx.bumps.fee_router;
+    router.set_shares(shares.clone())?;
+
+    emit!(FeeRouteUpdated { shares });
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_FEE_BPS;
+use crate::errors::EscrowError;
+use crate::events::FeesUpdated;
+use super::AdminAction;
+
+pub(crate) fn set_fees(ctx: Context<AdminAction>, fee_bps: u16, daily_compute_fee: u64) -> Result<()> {
+    require!(fee_bps <= MAX_FEE_BPS, EscrowError::FeeTooHigh);
+    let config = &mut ctx.accounts.config;
+    config.fee_bps = fee_bps;
+    config.daily_compute_fee = daily_compute_fee;
+
+    emit!(FeesUpdated {
+        fee_bps,
+        daily_compute_fee,
+    });
+
+    Ok(())
+}
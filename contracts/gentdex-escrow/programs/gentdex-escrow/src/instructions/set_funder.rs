@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::FunderUpdated;
+use super::UserAction;
+
+pub(crate) fn set_funder(ctx: Context<UserAction>, funder: Pubkey) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+
+    vault.funder = funder;
+    vault.record_user_activity()?;
+
+    emit!(FunderUpdated {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        funder,
+    });
+
+    Ok(())
+}
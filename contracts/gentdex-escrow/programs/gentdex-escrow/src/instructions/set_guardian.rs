@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+use crate::events::GuardianUpdated;
+use super::AdminAction;
+
+pub(crate) fn set_guardian(ctx: Context<AdminAction>, guardian: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    emit!(GuardianUpdated {
+        previous: config.guardian,
+        guardian,
+    });
+    config.guardian = guardian;
+
+    Ok(())
+}
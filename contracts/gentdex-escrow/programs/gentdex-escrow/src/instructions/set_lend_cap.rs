@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::math;
+use super::UserAction;
+
+pub(crate) fn set_lend_cap(ctx: Context<UserAction>, lend_cap_bps: u16) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(lend_cap_bps as u64 <= math::BPS_DENOMINATOR, EscrowError::InvalidLendCap);
+
+    vault.lend_cap_bps = lend_cap_bps;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::math;
+use super::UserAction;
+
+pub(crate) fn set_max_exposure(ctx: Context<UserAction>, max_exposure_bps: u16) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(max_exposure_bps as u64 <= math::BPS_DENOMINATOR, EscrowError::ExposureCapExceeded);
+
+    vault.max_exposure_bps = max_exposure_bps;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_POSITIONS;
+use crate::errors::EscrowError;
+use super::UserAction;
+
+pub(crate) fn set_max_positions(ctx: Context<UserAction>, max_positions: u8) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(max_positions as usize <= MAX_POSITIONS, EscrowError::PositionLimitReached);
+
+    vault.max_positions = max_positions;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
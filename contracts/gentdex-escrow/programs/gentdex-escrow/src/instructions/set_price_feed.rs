@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_PRICE_FEEDS;
+use crate::errors::EscrowError;
+use crate::events::PriceFeedUpdated;
+use crate::state::MintPriceFeed;
+use super::AdminAction;
+
+pub(crate) fn set_price_feed(
+    ctx: Context<AdminAction>,
+    mint: Pubkey,
+    feed_id: [u8; 32],
+    decimals: u8,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let entry = MintPriceFeed { mint, feed_id, decimals };
+    match config.price_feeds.iter_mut().find(|feed| feed.mint == mint) {
+        Some(existing) => *existing = entry,
+        None => {
+            require!(config.price_feeds.len() < MAX_PRICE_FEEDS, EscrowError::WhitelistFull);
+            config.price_feeds.push(entry);
+        }
+    }
+
+    emit!(PriceFeedUpdated { mint, feed_id });
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::RecoveryUpdated;
+use super::UserAction;
+
+pub(crate) fn set_recovery(ctx: Context<UserAction>, recovery: Pubkey) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+
+    vault.recovery = recovery;
+    vault.record_user_activity()?;
+
+    emit!(RecoveryUpdated {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        recovery,
+    });
+
+    Ok(())
+}
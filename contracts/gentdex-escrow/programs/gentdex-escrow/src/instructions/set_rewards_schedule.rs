@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::RewardsScheduleUpdated;
+use crate::state::RewardsSchedule;
+use super::AdminAction;
+
+pub(crate) fn set_rewards_schedule(ctx: Context<AdminAction>, schedule: RewardsSchedule) -> Result<()> {
+    require!(schedule.starts_at <= schedule.ends_at, EscrowError::InvalidRewardsSchedule);
+    ctx.accounts.config.rewards = schedule;
+
+    emit!(RewardsScheduleUpdated { schedule });
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use super::UserAction;
+
+pub(crate) fn set_slippage_budget(ctx: Context<UserAction>, slippage_budget: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+
+    vault.slippage_budget = slippage_budget;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
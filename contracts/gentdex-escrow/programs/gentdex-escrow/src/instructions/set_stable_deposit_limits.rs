@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::StableDepositLimitsUpdated;
+use super::AdminAction;
+
+pub(crate) fn set_stable_deposit_limits(
+    ctx: Context<AdminAction>,
+    min_deposit: u64,
+    max_deposit: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.min_stable_deposit = min_deposit;
+    config.max_stable_deposit = max_deposit;
+    require!(
+        config.within_stable_deposit_cap(config.stable_deposit_floor()),
+        EscrowError::InvalidDepositLimits
+    );
+
+    emit!(StableDepositLimitsUpdated {
+        min_deposit,
+        max_deposit,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use super::UserAction;
+
+pub(crate) fn set_swap_protection(
+    ctx: Context<UserAction>,
+    max_slot_age: u64,
+    require_jito_tip: bool,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+
+    vault.max_slot_age = max_slot_age;
+    vault.require_jito_tip = require_jito_tip;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_BOT_TIERS;
+use crate::errors::EscrowError;
+use crate::events::TierComputeFeesUpdated;
+use super::AdminAction;
+
+pub(crate) fn set_tier_compute_fees(ctx: Context<AdminAction>, fees: Vec<u64>) -> Result<()> {
+    require!(fees.len() <= MAX_BOT_TIERS, EscrowError::InvalidBotTier);
+    ctx.accounts.config.tier_compute_fees = fees.clone();
+
+    emit!(TierComputeFeesUpdated {
+        fees,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::batching;
+use crate::constants::MAX_BATCHED_TRADES;
+use crate::errors::EscrowError;
+use crate::state::{TradeBatch, Vault};
+
+#[derive(Accounts)]
+pub struct SetTradeBatching<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + TradeBatch::INIT_SPACE,
+        seeds = [b"trade_batch", vault.key().as_ref()],
+        bump
+    )]
+    pub trade_batch: Account<'info, TradeBatch>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Pays for the trade batch the first time the session batches
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn set_trade_batching(ctx: Context<SetTradeBatching>, batch_size: u8, window_slots: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(batch_size as usize <= MAX_BATCHED_TRADES, EscrowError::InvalidTradeBatch);
+
+    // Anything buffered under the old settings goes out first
+    let batch = &mut ctx.accounts.trade_batch;
+    batching::emit_batch(batch, vault.key(), vault.session_id, vault.bot);
+    batch.vault = vault.key();
+    batch.batch_size = batch_size;
+    batch.window_slots = window_slots;
+    batch.bump = ctx.bumps.trade_batch;
+
+    vault.batch_trades = batch_size > 0;
+    vault.record_user_activity()?;
+
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+use crate::events::TreasuryUpdated;
+use super::AdminAction;
+
+pub(crate) fn set_treasury(ctx: Context<AdminAction>, treasury: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    emit!(TreasuryUpdated {
+        previous: config.treasury,
+        treasury,
+    });
+    config.treasury = treasury;
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::UpgradeInfoUpdated;
+use crate::state::{ProtocolConfig, UpgradeInfo};
+
+#[derive(Accounts)]
+pub struct SetUpgradeInfo<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The single-key admin, or a Realms governance account via an executed proposal
+    pub admin: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UpgradeInfo::INIT_SPACE,
+        seeds = [b"upgrade_info"],
+        bump
+    )]
+    pub upgrade_info: Account<'info, UpgradeInfo>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ EscrowError::Unauthorized)]
+    pub program: Program<'info, crate::program::GentdexEscrow>,
+
+    pub program_data: Account<'info, ProgramData>,
+
+    /// Pays for `upgrade_info` the first time it's set
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn set_upgrade_info(
+    ctx: Context<SetUpgradeInfo>,
+    timelock_seconds: u64,
+    build_hash: [u8; 32],
+) -> Result<()> {
+    let info = &mut ctx.accounts.upgrade_info;
+    info.observe(&ctx.accounts.program_data, Clock::get()?.unix_timestamp);
+    info.timelock_seconds = timelock_seconds;
+    info.build_hash = build_hash;
+    info.build_hash_slot = info.deployed_slot;
+    info.bump = ctx.bumps.upgrade_info;
+
+    emit!(UpgradeInfoUpdated {
+        upgrade_authority: info.upgrade_authority,
+        deployed_slot: info.deployed_slot,
+        timelock_seconds,
+        build_hash,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_WITHDRAWAL_NOTICE_DAYS;
+use crate::errors::EscrowError;
+use crate::events::WithdrawalNoticeSet;
+use crate::math::SECONDS_PER_DAY;
+use super::UserAction;
+
+pub(crate) fn set_withdrawal_notice(ctx: Context<UserAction>, notice: i64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(
+        (0..=MAX_WITHDRAWAL_NOTICE_DAYS as i64 * SECONDS_PER_DAY).contains(&notice),
+        EscrowError::InvalidWithdrawalNotice
+    );
+    // Shortening it is a way out, so it waits out the current notice like a withdrawal
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        notice >= vault.withdrawal_notice || vault.withdrawal_notice_served(now),
+        EscrowError::WithdrawalNoticePending
+    );
+
+    vault.withdrawal_notice = notice;
+    vault.record_user_activity()?;
+
+    emit!(WithdrawalNoticeSet {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        notice,
+    });
+
+    Ok(())
+}
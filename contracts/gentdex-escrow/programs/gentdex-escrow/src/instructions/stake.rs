@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::errors::EscrowError;
+use crate::events::StakeChanged;
+use crate::{math, stake_for_discount};
+use crate::state::StakeAccount;
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + StakeAccount::INIT_SPACE,
+        seeds = [b"stake", user.key().as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, StakeAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Pays for the stake account the first time; the stake itself comes from the user
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub(crate) fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+    require!(amount > 0, EscrowError::InsufficientBalance);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.stake.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let stake = &mut ctx.accounts.stake;
+    stake.user = ctx.accounts.user.key();
+    stake.bump = ctx.bumps.stake;
+    stake.amount = stake.amount
+        .checked_add(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    stake.locked_until = math::add_days(
+        Clock::get()?.unix_timestamp,
+        stake_for_discount::LOCK_DAYS,
+    )?;
+
+    emit!(StakeChanged {
+        user: stake.user,
+        staked: amount,
+        unstaked: 0,
+        amount: stake.amount,
+        discount_bps: stake_for_discount::discount_bps(stake.amount) as u16,
+    });
+
+    Ok(())
+}
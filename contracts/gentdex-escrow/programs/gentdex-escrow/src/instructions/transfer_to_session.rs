@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+use crate::compute_fee::settle_compute_fee;
+use crate::errors::EscrowError;
+use crate::events::SessionTransferred;
+use crate::session::{activate_session, move_lamports, settle_duration_points};
+use crate::guard;
+use crate::state::{BotProfile, ProtocolConfig, RewardsAccount, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct TransferToSession<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", source_vault.session_id.as_ref(), source_vault.user.as_ref()],
+        bump = source_vault.bump
+    )]
+    pub source_vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", destination_vault.session_id.as_ref(), destination_vault.user.as_ref()],
+        bump = destination_vault.bump,
+        constraint = destination_vault.key() != source_vault.key() @ EscrowError::InvalidStatus
+    )]
+    pub destination_vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Source session's treasury — receives compute fee settled on transfer
+    #[account(
+        mut,
+        constraint = treasury.key() == source_vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// CHECK: The bot's operator credit, if its operator has funded one — drawn on before the vault
+    #[account(mut, seeds = [b"operator_credit", source_vault.bot.as_ref()], bump)]
+    pub operator_credit: UncheckedAccount<'info>,
+
+    /// The user's reward points — credited with both sessions' unsettled duration points
+    #[account(mut, seeds = [b"rewards", source_vault.user.as_ref()], bump = rewards.bump)]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    /// The bot's profile, for a Pending destination opened requiring a verified bot — its
+    /// tier at funding prices the compute fee
+    #[account(seeds = [b"bot_profile", destination_vault.bot.as_ref()], bump = bot_profile.bump)]
+    pub bot_profile: Option<Account<'info, BotProfile>>,
+}
+
+pub(crate) fn transfer_to_session(ctx: Context<TransferToSession>) -> Result<()> {
+    let user = ctx.accounts.user.key();
+    let source = &mut ctx.accounts.source_vault;
+    require!(source.user == user, EscrowError::Unauthorized);
+    require!(ctx.accounts.destination_vault.user == user, EscrowError::Unauthorized);
+    require!(
+        source.is_sol_session() && ctx.accounts.destination_vault.is_sol_session(),
+        EscrowError::BaseCurrencyMismatch
+    );
+    require!(
+        source.status != VaultStatus::Pending && source.status != VaultStatus::Withdrawn,
+        EscrowError::InvalidStatus
+    );
+    require!(source.lent_amount == 0, EscrowError::LendingNotUnwound);
+    require!(source.perps_collateral == 0, EscrowError::PerpsNotUnwound);
+    let now = Clock::get()?.unix_timestamp;
+    require!(source.withdrawal_notice_served(now), EscrowError::WithdrawalNoticePending);
+    guard::ensure_unlocked(source)?;
+    guard::ensure_unlocked(&ctx.accounts.destination_vault)?;
+
+    let schedule = &ctx.accounts.config.rewards;
+    settle_duration_points(source, &mut ctx.accounts.rewards, schedule, now);
+    let (_, compute_fee) = settle_compute_fee(
+        source,
+        &ctx.accounts.operator_credit,
+        &ctx.accounts.treasury,
+        &ctx.accounts.fee_router,
+        now,
+    )?;
+
+    let amount = source.balance;
+    require!(amount > 0, EscrowError::InsufficientBalance);
+
+    // Both PDAs are owned by this program, so lamports move directly
+    move_lamports(&source.to_account_info(), &ctx.accounts.destination_vault.to_account_info(), amount)?;
+
+    source.balance = 0;
+    source.status = VaultStatus::Withdrawn;
+    source.total_withdrawn = source.total_withdrawn
+        .checked_add(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    let source_session_id = source.session_id;
+    let source_vault = source.key();
+
+    let destination = &mut ctx.accounts.destination_vault;
+    match destination.status {
+        // Funded by the transfer, with no setup fee and no template to pay
+        VaultStatus::Pending => activate_session(
+            destination,
+            &ctx.accounts.config,
+            false,
+            ctx.accounts.bot_profile.as_deref(),
+            amount,
+            0,
+        )?,
+        VaultStatus::Active | VaultStatus::Paused => {
+            require!(now < destination.expires_at, EscrowError::SessionExpired);
+            settle_duration_points(destination, &mut ctx.accounts.rewards, schedule, now);
+            destination.balance = destination.balance
+                .checked_add(amount)
+                .ok_or(EscrowError::MathOverflow)?;
+            destination.total_deposited = destination.total_deposited
+                .checked_add(amount)
+                .ok_or(EscrowError::MathOverflow)?;
+            require!(
+                ctx.accounts.config.within_deposit_cap(destination.total_deposited),
+                EscrowError::DepositTooLarge
+            );
+            destination.last_user_activity = now;
+        }
+        _ => return err!(EscrowError::InvalidStatus),
+    }
+
+    emit!(SessionTransferred {
+        source_session_id,
+        source_vault,
+        destination_session_id: destination.session_id,
+        destination_vault: destination.key(),
+        amount,
+        compute_fee,
+        user,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::StakeChanged;
+use crate::session::move_lamports;
+use crate::stake_for_discount;
+use crate::state::StakeAccount;
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump = stake.bump,
+        has_one = user @ EscrowError::Unauthorized
+    )]
+    pub stake: Account<'info, StakeAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+pub(crate) fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+    let stake = &mut ctx.accounts.stake;
+    require!(amount <= stake.amount, EscrowError::InsufficientBalance);
+    require!(
+        Clock::get()?.unix_timestamp >= stake.locked_until,
+        EscrowError::StakeLocked
+    );
+
+    move_lamports(&stake.to_account_info(), &ctx.accounts.user.to_account_info(), amount)?;
+
+    stake.amount -= amount;
+
+    emit!(StakeChanged {
+        user: stake.user,
+        staked: 0,
+        unstaked: amount,
+        amount: stake.amount,
+        discount_bps: stake_for_discount::discount_bps(stake.amount) as u16,
+    });
+
+    Ok(())
+}
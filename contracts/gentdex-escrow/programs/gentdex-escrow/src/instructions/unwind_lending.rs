@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, spl_token::native_mint, CloseAccount};
+
+use crate::{adapters, guard};
+use crate::adapters::marginfi;
+use crate::errors::EscrowError;
+use crate::events::LendingMoved;
+use crate::session::stop_blacklisted_bot;
+use super::Lending;
+
+pub(crate) fn unwind_lending<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Lending<'info>>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    if authority == ctx.accounts.vault.bot && stop_blacklisted_bot(&mut ctx.accounts.vault, &ctx.accounts.config)? {
+        return Ok(());
+    }
+    let vault = &ctx.accounts.vault;
+    require!(
+        authority == vault.user || authority == vault.bot,
+        EscrowError::Unauthorized
+    );
+    require!(vault.lending_account != Pubkey::default(), EscrowError::LendingNotEnabled);
+    guard::ensure_unlocked(vault)?;
+
+    let vault_info = vault.to_account_info();
+    adapters::vault_token_account(&ctx.accounts.wsol_account, &vault.key(), &native_mint::ID)?;
+    marginfi::withdraw_all(
+        &ctx.accounts.lending_accounts(&vault_info),
+        ctx.remaining_accounts,
+        &vault.signer_seeds(),
+    )?;
+
+    // Unwrap: closing the WSOL account sends its lamports to the vault PDA.
+    // Only the token amount is credited; the account's rent isn't balance.
+    let unwound = adapters::token_amount(&ctx.accounts.wsol_account)?;
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.wsol_account.to_account_info(),
+            destination: vault_info.clone(),
+            authority: vault_info,
+        },
+        &[&vault.signer_seeds()],
+    ))?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.balance = vault.balance
+        .checked_add(unwound)
+        .ok_or(EscrowError::MathOverflow)?;
+    vault.lent_amount = 0;
+
+    emit!(LendingMoved {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        lent: 0,
+        unwound,
+        lent_amount: 0,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+use crate::events::TemplateUpdated;
+use crate::state::TemplateParams;
+use super::UpdateTemplate;
+
+pub(crate) fn update_template(ctx: Context<UpdateTemplate>, params: TemplateParams) -> Result<()> {
+    params.validate()?;
+    let template = &mut ctx.accounts.template;
+    template.apply(params);
+
+    emit!(TemplateUpdated {
+        template: template.key(),
+        operator: template.operator,
+        template_id: template.template_id,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token};
+
+use crate::adapters::owned_token_account;
+use crate::errors::EscrowError;
+use crate::events::{SessionClosed, Withdrawn};
+use crate::guard;
+use crate::session::pay_out;
+use crate::state::{BotStats, ProtocolConfig, RewardsAccount, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct WithdrawAndClose<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump,
+        has_one = user @ EscrowError::Unauthorized,
+        close = user
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Treasury wallet — receives any compute fee settled on withdrawal
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + BotStats::INIT_SPACE,
+        seeds = [b"bot_stats", vault.bot.as_ref()],
+        bump
+    )]
+    pub bot_stats: Account<'info, BotStats>,
+
+    /// Pays for `bot_stats` the first time the bot settles a session — the
+    /// user, or a relayer for users with no SOL outside the vault
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// CHECK: The bot's operator credit, if its operator has funded one — drawn on before the vault
+    #[account(mut, seeds = [b"operator_credit", vault.bot.as_ref()], bump)]
+    pub operator_credit: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The user's reward points — credited with the session's unsettled duration points
+    #[account(mut, seeds = [b"rewards", vault.user.as_ref()], bump = rewards.bump)]
+    pub rewards: Account<'info, RewardsAccount>,
+
+    pub token_program: Program<'info, Token>,
+    // The vault's empty token accounts (writable), closed with it, via remaining_accounts
+}
+
+pub(crate) fn withdraw_and_close<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawAndClose<'info>>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.status != VaultStatus::Pending, EscrowError::InvalidStatus);
+    require!(vault.is_sol_session(), EscrowError::BaseCurrencyMismatch);
+    require!(vault.lent_amount == 0, EscrowError::LendingNotUnwound);
+    require!(
+        vault.withdrawal_notice_served(Clock::get()?.unix_timestamp),
+        EscrowError::WithdrawalNoticePending
+    );
+    // Nothing holding value or needing the vault PDA's signature may outlive it
+    require!(
+        vault.position_mints.is_empty()
+            && vault.perps_collateral == 0
+            && vault.lookup_table == Pubkey::default(),
+        EscrowError::SessionNotEmpty
+    );
+    guard::ensure_unlocked(vault)?;
+
+    // A session already withdrawn has nothing left to settle
+    if vault.status != VaultStatus::Withdrawn {
+        let (balance, compute_fee) = pay_out(
+            vault,
+            &ctx.accounts.treasury,
+            &ctx.accounts.fee_router,
+            &ctx.accounts.operator_credit,
+            &ctx.accounts.user,
+            &mut ctx.accounts.bot_stats,
+            ctx.bumps.bot_stats,
+            &mut ctx.accounts.rewards,
+            &ctx.accounts.config.rewards,
+        )?;
+
+        emit!(Withdrawn {
+            session_id: vault.session_id,
+            vault: vault.key(),
+            amount: balance,
+            compute_fee,
+            user: ctx.accounts.user.key(),
+        });
+    }
+
+    // Token accounts the vault leaves behind go with it, their rent to the user
+    for info in ctx.remaining_accounts {
+        let account = owned_token_account(info, &vault.key())?;
+        require!(account.amount == 0, EscrowError::SessionNotEmpty);
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: info.clone(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &[&vault.signer_seeds()],
+        ))?;
+    }
+
+    // `close` returns the rent to the user once this returns
+    emit!(SessionClosed {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        user: vault.user,
+        rent: vault.to_account_info().lamports(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+use crate::events::OperatorCreditChanged;
+use crate::session::move_lamports;
+use crate::state::OperatorCredit;
+
+#[derive(Accounts)]
+pub struct WithdrawOperatorCredit<'info> {
+    #[account(
+        mut,
+        seeds = [b"operator_credit", bot.key().as_ref()],
+        bump = operator_credit.bump,
+        has_one = bot @ EscrowError::Unauthorized
+    )]
+    pub operator_credit: Account<'info, OperatorCredit>,
+
+    #[account(mut)]
+    pub bot: Signer<'info>,
+}
+
+pub(crate) fn withdraw_operator_credit(ctx: Context<WithdrawOperatorCredit>, amount: u64) -> Result<()> {
+    let credit = &mut ctx.accounts.operator_credit;
+    require!(amount <= credit.balance, EscrowError::InsufficientBalance);
+
+    move_lamports(&credit.to_account_info(), &ctx.accounts.bot.to_account_info(), amount)?;
+
+    credit.balance -= amount;
+
+    emit!(OperatorCreditChanged {
+        bot: credit.bot,
+        funded: 0,
+        withdrawn: amount,
+        balance: credit.balance,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, spl_token::native_mint, CloseAccount, Token, TokenAccount};
+
+use crate::errors::EscrowError;
+use crate::events::PositionWithdrawn;
+use crate::guard;
+use crate::session::transfer_from_vault;
+use crate::state::Vault;
+
+#[derive(Accounts)]
+pub struct WithdrawPosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Token account the position sits in; closed to the user once emptied
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ EscrowError::InvalidDexAccount,
+        constraint = vault_token_account.mint != vault.base_mint @ EscrowError::InvalidDexAccount,
+        constraint = vault_token_account.mint != native_mint::ID @ EscrowError::InvalidDexAccount
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = vault_token_account.mint, token::authority = user)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn withdraw_position(ctx: Context<WithdrawPosition>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    let now = Clock::get()?.unix_timestamp;
+    require!(vault.withdrawal_notice_served(now), EscrowError::WithdrawalNoticePending);
+    guard::ensure_unlocked(vault)?;
+
+    let mint = ctx.accounts.vault_token_account.mint;
+    let amount = ctx.accounts.vault_token_account.amount;
+    transfer_from_vault(
+        vault,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.vault_token_account.to_account_info(),
+            destination: ctx.accounts.user.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        &[&vault.signer_seeds()],
+    ))?;
+
+    vault.position_mints.retain(|position| position != &mint);
+    vault.record_user_activity()?;
+
+    emit!(PositionWithdrawn {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        mint,
+        amount,
+        user: vault.user,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::compute_fee::{settle_compute_fee_token, OperatorCreditAccounts};
+use crate::errors::EscrowError;
+use crate::events::Withdrawn;
+use crate::guard;
+use crate::session::transfer_from_vault;
+use crate::state::{ProtocolConfig, Vault, VaultStatus};
+
+#[derive(Accounts)]
+pub struct WithdrawToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = vault.base_mint, token::authority = user)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = vault.base_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.base_mint,
+        constraint = treasury_token_account.owner == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Treasury wallet — receives, in SOL, whatever the bot's operator credit covers
+    #[account(
+        mut,
+        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The fee router, if the admin has created one — its recipients share the fee
+    #[account(mut, seeds = [b"fee_router"], bump)]
+    pub fee_router: UncheckedAccount<'info>,
+
+    /// CHECK: The bot's operator credit, if its operator has funded one — drawn on before the vault
+    #[account(mut, seeds = [b"operator_credit", vault.bot.as_ref()], bump)]
+    pub operator_credit: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    // SOL and base-mint price updates via remaining_accounts, if the bot has operator credit
+}
+
+pub(crate) fn withdraw_token<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawToken<'info>>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
+    require!(vault.status != VaultStatus::Pending, EscrowError::InvalidStatus);
+    require!(vault.balance > 0, EscrowError::InsufficientBalance);
+    let now = Clock::get()?.unix_timestamp;
+    require!(vault.withdrawal_notice_served(now), EscrowError::WithdrawalNoticePending);
+    guard::ensure_unlocked(vault)?;
+
+    let (_, compute_fee) = settle_compute_fee_token(
+        vault,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.treasury_token_account,
+        &ctx.accounts.token_program,
+        OperatorCreditAccounts {
+            operator_credit: &ctx.accounts.operator_credit,
+            treasury: &ctx.accounts.treasury,
+            fee_router: &ctx.accounts.fee_router,
+            config: &ctx.accounts.config,
+            price_feeds: ctx.remaining_accounts,
+        },
+        now,
+    )?;
+
+    let balance = vault.balance;
+    transfer_from_vault(
+        vault,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.token_program,
+        balance,
+    )?;
+
+    vault.balance = 0;
+    vault.status = VaultStatus::Withdrawn;
+    vault.total_withdrawn = vault.total_withdrawn
+        .checked_add(balance)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    emit!(Withdrawn {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        amount: balance,
+        compute_fee,
+        user: ctx.accounts.user.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Raw lamport accounting, kept free of Anchor and `std` so it can be proved
+//! with Kani (`cargo kani`) as well as tested.
+//!
+//! Every direct debit of a program-owned account goes through [`transfer`],
+//! via `session::move_lamports`, every setup fee split through [`split`] (and
+//! quoted backwards through [`gross_for_net`]) and every fee shared out
+//! through [`route`].
+
+/// Why a lamport move was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LamportError {
+    /// The source doesn't hold the amount
+    Insufficient,
+    /// The source would drop below its rent-exempt minimum
+    BelowRentFloor,
+    /// The destination would exceed `u64::MAX`
+    Overflow,
+}
+
+/// `(from, to)` balances after moving `amount` from `from` to `to`, where
+/// `from` must keep at least `rent_floor`.
+pub fn transfer(from: u64, to: u64, amount: u64, rent_floor: u64) -> Result<(u64, u64), LamportError> {
+    let remaining = from.checked_sub(amount).ok_or(LamportError::Insufficient)?;
+    if remaining < rent_floor {
+        return Err(LamportError::BelowRentFloor);
+    }
+    let credited = to.checked_add(amount).ok_or(LamportError::Overflow)?;
+    Ok((remaining, credited))
+}
+
+/// Split `amount` into `(fee, net)` at `bps` basis points, fee rounded down.
+/// `None` if `bps` is above 100%.
+pub fn split(amount: u64, bps: u64) -> Option<(u64, u64)> {
+    if bps > 10_000 {
+        return None;
+    }
+    // At most `amount`, since bps <= 10_000
+    let fee = (amount as u128 * bps as u128 / 10_000) as u64;
+    Some((fee, amount - fee))
+}
+
+/// The least `amount` whose [`split`] at `bps` leaves exactly `net`. `None`
+/// if none does: a fee of 100% and a nonzero `net`, or more than `u64::MAX`.
+pub fn gross_for_net(net: u64, bps: u64) -> Option<u64> {
+    if net == 0 {
+        return Some(0);
+    }
+    if bps >= 10_000 {
+        return None;
+    }
+    // `split` leaves ceil(amount * (10_000 - bps) / 10_000), which grows by at
+    // most one per lamport, so the first amount past this bound hits `net`
+    let gross = (net as u128 - 1) * 10_000 / (10_000 - bps) as u128 + 1;
+    u64::try_from(gross).ok()
+}
+
+/// Write each of `bps`'s shares of `amount`, rounded down, to `shares` and
+/// return the remainder. `None` if the shares add up to more than 100% or
+/// `shares` is shorter than `bps`.
+pub fn route(amount: u64, bps: &[u16], shares: &mut [u64]) -> Option<u64> {
+    let total: u64 = bps.iter().map(|&bps| bps as u64).sum();
+    if total > 10_000 || shares.len() < bps.len() {
+        return None;
+    }
+    let mut remainder = amount;
+    for (share, &bps) in shares.iter_mut().zip(bps) {
+        *share = (amount as u128 * bps as u128 / 10_000) as u64;
+        // The shares round down and add up to at most `amount`
+        remainder -= *share;
+    }
+    Some(remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every combination of small balances against a wide-integer model.
+    #[test]
+    fn transfer_matches_model_exhaustively() {
+        const N: u64 = 24;
+        for from in 0..=N {
+            for amount in 0..=N {
+                for rent_floor in 0..=N {
+                    for to in [0, 1, N, u64::MAX - N, u64::MAX] {
+                        let result = transfer(from, to, amount, rent_floor);
+                        let expected = if amount > from {
+                            Err(LamportError::Insufficient)
+                        } else if from - amount < rent_floor {
+                            Err(LamportError::BelowRentFloor)
+                        } else if to as u128 + amount as u128 > u64::MAX as u128 {
+                            Err(LamportError::Overflow)
+                        } else {
+                            Ok((from - amount, to + amount))
+                        };
+                        assert_eq!(result, expected, "from {from} to {to} amount {amount} floor {rent_floor}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn split_conserves_amount_exhaustively() {
+        for amount in (0..=2_000).chain([u64::MAX - 1, u64::MAX]) {
+            for bps in 0..=10_000 {
+                let (fee, net) = split(amount, bps).unwrap();
+                assert_eq!(fee + net, amount);
+                assert_eq!(fee as u128, amount as u128 * bps as u128 / 10_000);
+            }
+        }
+        assert_eq!(split(1, 10_001), None);
+    }
+
+    #[test]
+    fn gross_for_net_inverts_split_exhaustively() {
+        for net in 0..=1_000 {
+            for bps in (0..10_000).step_by(7).chain([9_999]) {
+                let gross = gross_for_net(net, bps).unwrap();
+                assert_eq!(split(gross, bps).unwrap().1, net, "net {net} bps {bps}");
+                if gross > 0 {
+                    assert!(split(gross - 1, bps).unwrap().1 < net, "net {net} bps {bps}: not the least");
+                }
+            }
+        }
+        assert_eq!(gross_for_net(0, 10_000), Some(0));
+        assert_eq!(gross_for_net(1, 10_000), None);
+        assert_eq!(gross_for_net(u64::MAX, 1), None);
+        assert_eq!(gross_for_net(u64::MAX, 0), Some(u64::MAX));
+    }
+
+    #[test]
+    fn route_conserves_amount() {
+        let mut shares = [0; 3];
+        for amount in (0..=500).chain([u64::MAX]) {
+            for bps in [[0, 0, 0], [5_000, 2_500, 2_500], [3_333, 3_333, 3_333], [1, 9_998, 0]] {
+                let remainder = route(amount, &bps, &mut shares).unwrap();
+                assert_eq!(shares.iter().map(|&s| s as u128).sum::<u128>() + remainder as u128, amount as u128);
+                assert_eq!(shares[0] as u128, amount as u128 * bps[0] as u128 / 10_000);
+            }
+        }
+        assert_eq!(route(1, &[5_000, 5_001], &mut shares), None);
+        assert_eq!(route(1, &[1; 4], &mut shares), None);
+    }
+}
+
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    #[kani::proof]
+    fn transfer_never_overdraws_or_breaks_rent() {
+        let (from, to, amount, rent_floor): (u64, u64, u64, u64) = (kani::any(), kani::any(), kani::any(), kani::any());
+        if let Ok((remaining, credited)) = transfer(from, to, amount, rent_floor) {
+            assert!(amount <= from);
+            assert!(remaining >= rent_floor);
+            assert!(remaining as u128 + credited as u128 == from as u128 + to as u128);
+        }
+    }
+
+    #[kani::proof]
+    fn transfer_succeeds_when_covered() {
+        let (from, to, amount, rent_floor): (u64, u64, u64, u64) = (kani::any(), kani::any(), kani::any(), kani::any());
+        kani::assume(amount <= from && from - amount >= rent_floor && to <= u64::MAX - amount);
+        assert!(transfer(from, to, amount, rent_floor).is_ok());
+    }
+
+    #[kani::proof]
+    fn split_conserves_amount() {
+        let (amount, bps): (u64, u64) = (kani::any(), kani::any());
+        kani::assume(bps <= 10_000);
+        let (fee, net) = split(amount, bps).unwrap();
+        assert!(fee <= amount);
+        assert!(fee as u128 + net as u128 == amount as u128);
+    }
+
+    #[kani::proof]
+    fn gross_for_net_inverts_split() {
+        let (net, bps): (u64, u64) = (kani::any(), kani::any());
+        kani::assume(bps <= 10_000);
+        if let Some(gross) = gross_for_net(net, bps) {
+            assert!(split(gross, bps).unwrap().1 == net);
+        }
+    }
+
+    #[kani::proof]
+    fn route_conserves_amount() {
+        let (amount, bps): (u64, [u16; 2]) = (kani::any(), kani::any());
+        let mut shares = [0; 2];
+        if let Some(remainder) = route(amount, &bps, &mut shares) {
+            assert!(shares[0] as u128 + shares[1] as u128 + remainder as u128 == amount as u128);
+        }
+    }
+}
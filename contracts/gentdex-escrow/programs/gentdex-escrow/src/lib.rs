@@ -1,7 +1,63 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
 
+mod adapters;
+pub mod batching;
+mod compute_fee;
+mod constants;
+pub mod ed25519;
+mod errors;
+mod events;
+mod fee_router;
+mod guard;
+mod instructions;
+mod lamports;
+mod lookup_table;
+mod math;
+mod oracle;
+pub mod pda;
+mod protection;
+mod session;
+mod stake_for_discount;
+mod state;
+pub mod wormhole;
+
+pub use constants::*;
+pub use errors::*;
+pub use events::*;
+pub use instructions::*;
+pub use state::*;
+
+// Instruction argument types, re-exported for CPI callers
+pub use adapters::drift::{PerpDirection, PerpOrderParams, PerpOrderType};
+// Fee accrual, re-exported so clients can compute it off-chain
+pub use compute_fee::accrued_compute_fee;
+// Setup fee math, re-exported so clients can quote deposits off-chain
+pub use lamports::gross_for_net;
+pub use stake_for_discount::fee_bps_for_stake;
+
+// Devnet builds get their own program ID, so a test deployment can't pass
+// for the mainnet program; PDAs, derived from `ID`, differ with it
+#[cfg(not(feature = "devnet"))]
 declare_id!("9hyscAyfR2puBXWFoGzeBq3QtSn5e83B7AUkcS1qC5RJ");
+#[cfg(feature = "devnet")]
+declare_id!("D3cViTirWf3zkEcgoLkKEgXr73biwwaih4WxZUR4N2j5");
+
+// Embedded in the binary so explorers, scanners and whitehats can reach the
+// team; the revision is the commit `build.rs` found, so a verifiable build
+// names its source. Keep in step with SECURITY.md at the repository root.
+#[cfg(not(feature = "no-entrypoint"))]
+solana_security_txt::security_txt! {
+    name: "GentDex Escrow",
+    project_url: "https://gentdex.com",
+    contacts: "link:https://github.com/kilroycreative/gentdex/security/advisories/new,link:https://gentdex.com",
+    policy: "https://github.com/kilroycreative/gentdex/blob/main/SECURITY.md",
+    preferred_languages: "en",
+    source_code: "https://github.com/kilroycreative/gentdex",
+    source_release: env!("CARGO_PKG_VERSION"),
+    source_revision: env!("GENTDEX_GIT_COMMIT"),
+    auditors: "None yet; audits will be listed in SECURITY.md",
+    acknowledgements: "https://github.com/kilroycreative/gentdex/blob/main/SECURITY.md#acknowledgements"
+}
 
 /// GentDex Escrow Program
 /// 
@@ -12,562 +68,858 @@ declare_id!("9hyscAyfR2puBXWFoGzeBq3QtSn5e83B7AUkcS1qC5RJ");
 ///
 /// Architecture: Single PDA holds both state and SOL. The program owns
 /// the PDA so it can manipulate lamports directly.
+///
+/// Composing: build with the `cpi` feature for `gentdex_escrow::cpi::*`, or
+/// point `declare_program!` at the IDL. Instruction names, arguments and
+/// account orders are a stable interface; new accounts are only ever appended.
 
 #[program]
 pub mod gentdex_escrow {
     use super::*;
 
-    /// Fee basis points (2.5% = 250 bps)
-    pub const FEE_BPS: u64 = 250;
-    /// Daily compute fee in lamports (0.01 SOL)
-    pub const DAILY_COMPUTE_FEE: u64 = 10_000_000;
-    /// Minimum deposit in lamports (0.1 SOL)
-    pub const MIN_DEPOSIT: u64 = 100_000_000;
-
-    /// Initialize a new trading session with escrow vault
+    /// Initialize a new trading session with escrow vault.
+    /// To require a guardian-verified bot, pass its `BotProfile` as the first
+    /// remaining account (any initialize variant); the session's compute fee
+    /// is then the one for the bot's tier when it's funded.
     pub fn initialize(
         ctx: Context<Initialize>,
         session_id: [u8; 16],
         duration_days: u16,
         bot_pubkey: Pubkey,
     ) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        vault.user = ctx.accounts.user.key();
-        vault.bot = bot_pubkey;
-        vault.session_id = session_id;
-        vault.balance = 0;
-        vault.fee_collected = 0;
-        vault.compute_fees_paid = 0;
-        vault.duration_days = duration_days;
-        vault.status = VaultStatus::Pending;
-        vault.created_at = Clock::get()?.unix_timestamp;
-        vault.funded_at = 0;
-        vault.expires_at = 0;
-        vault.last_compute_deduction = 0;
-        vault.bump = ctx.bumps.vault;
-        vault.treasury = ctx.accounts.treasury.key();
-
-        emit!(SessionCreated {
-            session_id,
-            user: ctx.accounts.user.key(),
-            bot: bot_pubkey,
-            duration_days,
-        });
-
-        Ok(())
-    }
-
-    /// Deposit SOL into the escrow vault. 2.5% fee taken, remainder is trading balance.
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        require!(amount >= MIN_DEPOSIT, EscrowError::DepositTooSmall);
-        
-        // Read-only checks first
-        require!(ctx.accounts.vault.status == VaultStatus::Pending, EscrowError::InvalidStatus);
-        require!(ctx.accounts.vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
-
-        // Calculate fee (2.5%)
-        let fee = amount
-            .checked_mul(FEE_BPS)
-            .ok_or(EscrowError::MathOverflow)?
-            .checked_div(10_000)
-            .ok_or(EscrowError::MathOverflow)?;
-        let trading_balance = amount
-            .checked_sub(fee)
-            .ok_or(EscrowError::MathOverflow)?;
-
-        // Transfer trading balance from user to vault PDA
-        let vault_info = ctx.accounts.vault.to_account_info();
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.user.to_account_info(),
-                    to: vault_info,
-                },
-            ),
-            trading_balance,
-        )?;
-
-        // Transfer fee from user to treasury
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.user.to_account_info(),
-                    to: ctx.accounts.treasury.to_account_info(),
-                },
-            ),
-            fee,
-        )?;
-
-        // Now mutate vault state
-        let vault = &mut ctx.accounts.vault;
-        let now = Clock::get()?.unix_timestamp;
-        let duration_days = vault.duration_days;
-        vault.balance = trading_balance;
-        vault.fee_collected = fee;
-        vault.status = VaultStatus::Active;
-        vault.funded_at = now;
-        vault.last_compute_deduction = now;
-        vault.expires_at = now
-            .checked_add((duration_days as i64) * 86400)
-            .ok_or(EscrowError::MathOverflow)?;
-
-        emit!(Deposited {
-            session_id: vault.session_id,
-            amount,
-            fee,
-            trading_balance,
-            expires_at: vault.expires_at,
-        });
-
-        Ok(())
+        instructions::initialize(ctx, session_id, duration_days, bot_pubkey)
+    }
+
+    /// `initialize` and `deposit` in one instruction: the session is created
+    /// already funded and Active, so no Pending vault is left behind if the
+    /// user walks away. Not for template sessions, which keep the two steps.
+    /// Takes the same optional `BotProfile` as `initialize`.
+    pub fn initialize_and_deposit(
+        ctx: Context<InitializeAndDeposit>,
+        session_id: [u8; 16],
+        duration_days: u16,
+        bot_pubkey: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::initialize_and_deposit(ctx, session_id, duration_days, bot_pubkey, amount)
+    }
+
+    /// `initialize` with the session_id taken from the user's registry counter
+    /// (see `pda::indexed_session_id`), so clients can find every indexed
+    /// session of a user by walking indices 0..session_count.
+    pub fn initialize_indexed(
+        ctx: Context<InitializeIndexed>,
+        duration_days: u16,
+        bot_pubkey: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_indexed(ctx, duration_days, bot_pubkey)
+    }
+
+    /// Deposit SOL into the escrow vault. The protocol setup fee (2.5% by default)
+    /// is taken, remainder is trading balance.
+    /// Sessions opened from a template pass the template as the first remaining
+    /// account; the operator's share of the fee accrues to it. Sessions opened
+    /// requiring a verified bot pass its `BotProfile`, whose tier sets the
+    /// compute fee from here on.
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::deposit(ctx, amount)
+    }
+
+    /// Deposit whatever it takes for the session to start with exactly
+    /// `trading_balance`: the setup fee, after any stake discount, is added
+    /// on top instead of taken out. The `Deposited` event records the gross
+    /// amount. Same accounts as `deposit`.
+    pub fn deposit_exact_balance<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+        trading_balance: u64,
+    ) -> Result<()> {
+        instructions::deposit_exact_balance(ctx, trading_balance)
+    }
+
+    /// Open a SOL session for `recipient` and fund it with `amount`, held in
+    /// a gift PDA until the recipient accepts. The recipient is the session's
+    /// user: only they can accept, withdraw or change its settings. A
+    /// verified bot's `BotProfile` may be passed as for `initialize`.
+    pub fn gift_session(
+        ctx: Context<GiftSession>,
+        session_id: [u8; 16],
+        duration_days: u16,
+        bot_pubkey: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::gift_session(ctx, session_id, duration_days, bot_pubkey, recipient, amount)
+    }
+
+    /// Accept a gifted session: the setup fee (after the recipient's stake
+    /// discount) is taken from the gift, the rest funds the session, and it
+    /// starts under `deposit`'s checks and limits. The gift's rent goes back
+    /// to the giver. `BotProfile` as for `deposit`. Recipient only.
+    pub fn accept_gift(ctx: Context<AcceptGift>) -> Result<()> {
+        instructions::accept_gift(ctx)
+    }
+
+    /// Take back a gift the recipient hasn't accepted. Giver only.
+    pub fn reclaim_gift(ctx: Context<ReclaimGift>) -> Result<()> {
+        instructions::reclaim_gift(ctx)
     }
 
     /// Bot executes a swap via a whitelisted DEX program.
     /// This is the ONLY action the bot can take — it cannot withdraw or transfer arbitrarily.
-    pub fn execute_swap(
-        ctx: Context<ExecuteSwap>,
+    /// SOL sessions first settle the compute fee due, as `deduct_compute_fee` would.
+    /// The fill and that fee, or why policy rejected the swap, are also set as return data.
+    /// `dry_run` runs the policy checks and validates the route's accounts,
+    /// then stops before anything is written or any funds move — for bots to
+    /// simulate a trade before paying priority fees. Exposure and
+    /// slippage-budget checks need the fill, so a dry run can't cover them.
+    pub fn execute_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteSwap<'info>>,
         amount_in: u64,
         minimum_amount_out: u64,
-    ) -> Result<()> {
-        let vault = &ctx.accounts.vault;
-        require!(vault.status == VaultStatus::Active, EscrowError::InvalidStatus);
-        require!(vault.bot == ctx.accounts.bot.key(), EscrowError::Unauthorized);
-        
-        // Check not expired
-        let now = Clock::get()?.unix_timestamp;
-        require!(now < vault.expires_at, EscrowError::SessionExpired);
-        
-        // Check amount doesn't exceed balance
-        require!(amount_in <= vault.balance, EscrowError::InsufficientBalance);
-
-        // Validate DEX program is whitelisted
-        let dex_program = &ctx.accounts.dex_program;
-        require!(
-            is_whitelisted_dex(&dex_program.key()),
-            EscrowError::DexNotWhitelisted
-        );
-
-        // The actual CPI to the DEX happens here via remaining_accounts
-        // The DEX-specific instruction data is passed through
-        // This is where we'd build DEX-specific adapters
-        
-        emit!(SwapExecuted {
-            session_id: vault.session_id,
-            bot: ctx.accounts.bot.key(),
-            dex_program: dex_program.key(),
-            amount_in,
-            minimum_amount_out,
-            timestamp: now,
-        });
-
-        Ok(())
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        instructions::swap_with_policy(ctx, amount_in, minimum_amount_out, [0; 32], None, &[], dry_run)
+    }
+
+    /// `execute_swap` with a bot-supplied tag (e.g. a strategy signal ID),
+    /// recorded in the SwapExecuted / SwapRejected event so fills can be
+    /// reconciled against the bot's decision log.
+    pub fn execute_swap_with_memo<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        memo: [u8; 32],
+    ) -> Result<SwapResult> {
+        instructions::swap_with_policy(ctx, amount_in, minimum_amount_out, memo, None, &[], false)
+    }
+
+    /// `execute_swap_with_memo` for sessions with slot-age protection:
+    /// `recent_slot` is the slot the bot quoted at. Jito-tip protection
+    /// additionally needs the instructions sysvar account.
+    pub fn execute_swap_protected<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        memo: [u8; 32],
+        recent_slot: u64,
+    ) -> Result<SwapResult> {
+        instructions::swap_with_policy(ctx, amount_in, minimum_amount_out, memo, Some(recent_slot), &[], false)
+    }
+
+    /// `execute_swap_protected` with replay protection: `nonce` must exceed
+    /// every nonce the session's swaps have used (`Vault::trade_nonce`), so
+    /// a captured transaction, e.g. one on a durable nonce, can only land
+    /// once and never after a later trade. `recent_slot` is only needed for
+    /// sessions with slot-age protection.
+    pub fn execute_swap_with_nonce<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        memo: [u8; 32],
+        recent_slot: Option<u64>,
+        nonce: u64,
+    ) -> Result<SwapResult> {
+        instructions::swap_with_nonce(ctx, amount_in, minimum_amount_out, memo, recent_slot, &[], nonce, false)
+    }
+
+    /// `execute_swap_with_nonce` for aggregators whose route is built
+    /// off-chain (Jupiter): `route_data` is the venue's instruction data,
+    /// checked by its adapter before the vault signs it, and empty for other
+    /// venues. `nonce` is only needed for bots that number their trades;
+    /// `dry_run` as for `execute_swap`, checking the nonce without spending it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_swap_with_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        route_data: Vec<u8>,
+        memo: [u8; 32],
+        recent_slot: Option<u64>,
+        nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<SwapResult> {
+        match nonce {
+            Some(nonce) => instructions::swap_with_nonce(
+                ctx, amount_in, minimum_amount_out, memo, recent_slot, &route_data, nonce, dry_run,
+            ),
+            None => instructions::swap_with_policy(
+                ctx, amount_in, minimum_amount_out, memo, recent_slot, &route_data, dry_run,
+            ),
+        }
+    }
+
+    /// Compute fee accrued since the last deduction, as the crank would take
+    /// it now. Changes nothing; simulate it and read the return data.
+    pub fn get_accrued_fees(ctx: Context<ViewSession>) -> Result<AccruedFees> {
+        instructions::get_accrued_fees(ctx)
+    }
+
+    /// Balance, time remaining, accrued fee and drawdown of a session.
+    /// Changes nothing; simulate it and read the return data.
+    pub fn get_session_summary(ctx: Context<ViewSession>) -> Result<SessionSummary> {
+        instructions::get_session_summary(ctx)
     }
 
     /// Deduct daily compute fee from vault. Callable by anyone (protocol crank).
+    /// The bot's operator credit, if funded, pays what it can first. Also settles
+    /// the user's duration reward points.
     pub fn deduct_compute_fee(ctx: Context<DeductComputeFee>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        require!(
-            vault.status == VaultStatus::Active || vault.status == VaultStatus::Paused,
-            EscrowError::InvalidStatus
-        );
-
-        let now = Clock::get()?.unix_timestamp;
-        let seconds_since_last = now
-            .checked_sub(vault.last_compute_deduction)
-            .ok_or(EscrowError::MathOverflow)?;
-        
-        // Calculate days elapsed (minimum 1 day between deductions)
-        let days_elapsed = seconds_since_last / 86400;
-        require!(days_elapsed >= 1, EscrowError::TooEarlyForDeduction);
-
-        let fee = (days_elapsed as u64)
-            .checked_mul(DAILY_COMPUTE_FEE)
-            .ok_or(EscrowError::MathOverflow)?;
-        
-        let actual_fee = fee.min(vault.balance);
-
-        // Transfer compute fee from vault PDA to treasury
-        // The vault PDA is owned by this program, so we can debit it directly
-        let vault_info = vault.to_account_info();
-        let treasury_info = ctx.accounts.treasury.to_account_info();
-        **vault_info.try_borrow_mut_lamports()? -= actual_fee;
-        **treasury_info.try_borrow_mut_lamports()? += actual_fee;
-
-        vault.balance = vault.balance
-            .checked_sub(actual_fee)
-            .ok_or(EscrowError::MathOverflow)?;
-        vault.compute_fees_paid = vault.compute_fees_paid
-            .checked_add(actual_fee)
-            .ok_or(EscrowError::MathOverflow)?;
-        vault.last_compute_deduction = now;
-
-        // If balance is zero, expire the session
-        if vault.balance == 0 {
-            vault.status = VaultStatus::Expired;
-        }
+        instructions::deduct_compute_fee(ctx)
+    }
 
-        emit!(ComputeFeeDeducted {
-            session_id: vault.session_id,
-            fee: actual_fee,
-            remaining_balance: vault.balance,
-        });
+    /// `deduct_compute_fee` for schedulers that don't track when it's due:
+    /// succeeds without doing anything if no full day has accrued or the
+    /// session has ended. Returns whether a fee was deducted.
+    pub fn deduct_compute_fee_if_due(ctx: Context<DeductComputeFee>) -> Result<bool> {
+        instructions::deduct_compute_fee_if_due(ctx)
+    }
 
-        Ok(())
+    /// The bot gives notice that it will stop servicing the session: no new
+    /// swaps, perps orders or deposits, and the session expires within
+    /// RESIGNATION_NOTICE_DAYS so the user can withdraw. Bot only.
+    pub fn resign(ctx: Context<Resign>) -> Result<()> {
+        instructions::resign(ctx)
     }
 
     /// Pause trading. Only the user can pause.
     pub fn pause(ctx: Context<UserAction>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
-        require!(vault.status == VaultStatus::Active, EscrowError::InvalidStatus);
-        
-        vault.status = VaultStatus::Paused;
-
-        emit!(SessionPaused {
-            session_id: vault.session_id,
-        });
-
-        Ok(())
+        instructions::pause(ctx)
     }
 
     /// Resume trading. Only the user can resume.
     pub fn resume(ctx: Context<UserAction>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
-        require!(vault.status == VaultStatus::Paused, EscrowError::InvalidStatus);
-        
-        let now = Clock::get()?.unix_timestamp;
-        require!(now < vault.expires_at, EscrowError::SessionExpired);
-        
-        vault.status = VaultStatus::Active;
-
-        emit!(SessionResumed {
-            session_id: vault.session_id,
-        });
+        instructions::resume(ctx)
+    }
 
-        Ok(())
+    /// Push an Active or Paused session's expiry back by `days`. Compute fees
+    /// keep accruing daily. Only the user can extend, and not once the bot
+    /// has resigned.
+    pub fn extend_session(ctx: Context<UserAction>, days: u16) -> Result<()> {
+        instructions::extend_session(ctx, days)
     }
 
     /// Withdraw all funds. Only the user can withdraw. Works in ANY state except Pending.
     /// This is the emergency exit — user can ALWAYS get their funds back.
+    /// Lent-out SOL must be unwound first (`unwind_lending`, callable by the user).
+    /// Any compute fee accrued since the last crank is settled first, in the same instruction,
+    /// the bot's operator credit paying what it can.
+    /// The session is counted in the bot's `BotStats` (created on its first payout,
+    /// paid for by `payer`, which a relayer can sign as instead of the user).
     pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        require!(vault.user == ctx.accounts.user.key(), EscrowError::Unauthorized);
-        require!(vault.status != VaultStatus::Pending, EscrowError::InvalidStatus);
+        instructions::withdraw(ctx)
+    }
+
+    /// `withdraw`, then close the vault and return its rent to the user, for
+    /// sessions that are done. Also closes an already withdrawn session. Token
+    /// positions, perps collateral and the lookup table must be closed out
+    /// first, since the vault PDA can't sign for them once it's gone. Pass the
+    /// vault's remaining empty token accounts (e.g. WSOL) as remaining
+    /// accounts and they're closed too, their rent going to the user.
+    pub fn withdraw_and_close<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawAndClose<'info>>) -> Result<()> {
+        instructions::withdraw_and_close(ctx)
+    }
+
+    /// `withdraw` authorized by a message the user signed off-chain, so anyone
+    /// can submit it and pay the fees — for users with no SOL outside the vault.
+    /// The instruction before it must be an ed25519 program instruction
+    /// verifying the user's signature over "gentdex-escrow:withdraw", the
+    /// program id, the vault address and `deadline` (i64 LE); it's void after
+    /// `deadline`. Funds only ever go to the user.
+    pub fn withdraw_with_signature(ctx: Context<WithdrawWithSignature>, deadline: i64) -> Result<()> {
+        instructions::withdraw_with_signature(ctx, deadline)
+    }
+
+    /// Move the whole remaining balance of one of the user's sessions into another,
+    /// e.g. when switching bots, without paying the setup fee again. Accrued compute
+    /// fees on the source are settled first and the source ends up Withdrawn. A
+    /// Pending destination is activated fee-free, starting its duration now, under
+    /// `deposit`'s checks and limits, and takes its bot's `BotProfile` as `deposit` does.
+    pub fn transfer_to_session(ctx: Context<TransferToSession>) -> Result<()> {
+        instructions::transfer_to_session(ctx)
+    }
+
+    /// Turn a single DEX off (or back on) for this session while leaving the rest
+    /// of the whitelist usable. Only the user.
+    pub fn set_dex_enabled(ctx: Context<UserAction>, program_id: Pubkey, enabled: bool) -> Result<()> {
+        instructions::set_dex_enabled(ctx, program_id, enabled)
+    }
+
+    /// Cap the number of distinct token positions the vault may hold at once
+    /// (0 = up to MAX_POSITIONS). Only the user. Doesn't close existing positions.
+    pub fn set_max_positions(ctx: Context<UserAction>, max_positions: u8) -> Result<()> {
+        instructions::set_max_positions(ctx, max_positions)
+    }
+
+    /// Cap the share of the portfolio, in bps of its SOL valuation, that any one
+    /// token may make up after a swap (0 = no cap). Only the user.
+    pub fn set_max_exposure(ctx: Context<UserAction>, max_exposure_bps: u16) -> Result<()> {
+        instructions::set_max_exposure(ctx, max_exposure_bps)
+    }
+
+    /// Set the session's slippage budget: total lamports of execution shortfall
+    /// against oracle prices the bot may incur before swaps are refused
+    /// (0 = no budget). Only the user. Raising it lets trading continue.
+    pub fn set_slippage_budget(ctx: Context<UserAction>, slippage_budget: u64) -> Result<()> {
+        instructions::set_slippage_budget(ctx, slippage_budget)
+    }
+
+    /// Opt into anti-sandwich protection for large fills: swaps must land within
+    /// `max_slot_age` slots of the bot's quote slot (0 = off) and/or be tipped
+    /// to Jito. Unprotected swaps are rejected. Only the user.
+    pub fn set_swap_protection(
+        ctx: Context<UserAction>,
+        max_slot_age: u64,
+        require_jito_tip: bool,
+    ) -> Result<()> {
+        instructions::set_swap_protection(ctx, max_slot_age, require_jito_tip)
+    }
+
+    /// Batch the session's swap events: instead of a `SwapExecuted` each, swaps
+    /// are buffered in its `TradeBatch` (created here, paid by the user) and
+    /// emitted as one compressed `TradesBatched` per `batch_size` trades, or once
+    /// the oldest is `window_slots` old (0 = no window). The bot then passes the
+    /// `TradeBatch` after the route. A `batch_size` of 0 turns batching off.
+    /// Anything already buffered is emitted first. Only the user.
+    pub fn set_trade_batching(ctx: Context<SetTradeBatching>, batch_size: u8, window_slots: u64) -> Result<()> {
+        instructions::set_trade_batching(ctx, batch_size, window_slots)
+    }
+
+    /// Emit a batching session's buffered swaps now. Callable by anyone.
+    pub fn flush_trade_batch(ctx: Context<FlushTradeBatch>) -> Result<()> {
+        instructions::flush_trade_batch(ctx)
+    }
+
+    /// Stop counting a mint as an open position once the vault's token account
+    /// for it is empty. User or bot.
+    pub fn release_position(ctx: Context<ReleasePosition>) -> Result<()> {
+        instructions::release_position(ctx)
+    }
+
+    /// Send a token position to the user's token account and close the vault's
+    /// account for it. Adapters only sell the session's base currency, so this
+    /// is how bought tokens leave; it works in any state, including after the
+    /// balance is withdrawn.
+    /// Only the user.
+    pub fn withdraw_position(ctx: Context<WithdrawPosition>) -> Result<()> {
+        instructions::withdraw_position(ctx)
+    }
+
+    /// Opt in to a withdrawal notice: withdrawals and transfers out then need
+    /// a `request_withdrawal` `notice` seconds ahead (at most
+    /// MAX_WITHDRAWAL_NOTICE_DAYS), until the session ends. It can be lengthened
+    /// any time but only shortened once served, so it holds as a lockup. 0 turns
+    /// it off. Only the user.
+    pub fn set_withdrawal_notice(ctx: Context<UserAction>, notice: i64) -> Result<()> {
+        instructions::set_withdrawal_notice(ctx, notice)
+    }
 
-        let balance = vault.balance;
-        require!(balance > 0, EscrowError::InsufficientBalance);
+    /// Start the session's withdrawal notice. Funds can leave once it's
+    /// served, for WITHDRAWAL_REQUEST_WINDOW_DAYS; after that, request again.
+    /// Only the user.
+    pub fn request_withdrawal(ctx: Context<UserAction>) -> Result<()> {
+        instructions::request_withdrawal(ctx)
+    }
 
-        // Transfer remaining SOL back to user from the vault PDA
-        let vault_info = vault.to_account_info();
-        let user_info = ctx.accounts.user.to_account_info();
-        **vault_info.try_borrow_mut_lamports()? -= balance;
-        **user_info.try_borrow_mut_lamports()? += balance;
+    /// Set (or clear, with the default pubkey) the session's recovery key. Only the user.
+    pub fn set_recovery(ctx: Context<UserAction>, recovery: Pubkey) -> Result<()> {
+        instructions::set_recovery(ctx, recovery)
+    }
 
-        vault.balance = 0;
-        vault.status = VaultStatus::Withdrawn;
+    /// Authorize (or clear, with the default pubkey) who may fund the Pending
+    /// session cross-chain: the DLN external-call authority signing
+    /// `deposit_from_dln`, or the source-chain sender of a `deposit_from_bridge`
+    /// transfer, left-padded to 32 bytes. Only the user.
+    pub fn set_funder(ctx: Context<UserAction>, funder: Pubkey) -> Result<()> {
+        instructions::set_funder(ctx, funder)
+    }
 
-        emit!(Withdrawn {
-            session_id: vault.session_id,
-            amount: balance,
-            user: ctx.accounts.user.key(),
-        });
+    /// Recovery key withdraws everything to the original user's address, once
+    /// the user has signed nothing for RECOVERY_INACTIVITY_DAYS. Same settlement
+    /// as `withdraw`; the recovery key never receives funds.
+    pub fn recover(ctx: Context<Recover>) -> Result<()> {
+        instructions::recover(ctx)
+    }
 
-        Ok(())
+    /// Check that a SOL vault holds at least its accounted balance plus rent.
+    /// Callable by anyone; a vault that doesn't is paused, if Active, and
+    /// reported with InsolvencyDetected rather than failing.
+    pub fn assert_solvent(ctx: Context<AssertSolvent>) -> Result<()> {
+        instructions::assert_solvent(ctx)
+    }
+
+    /// Pause an Active session whose bot has been blacklisted. Callable by
+    /// anyone; anything a blacklisted bot signs pauses the session itself.
+    pub fn pause_blacklisted(ctx: Context<PauseBlacklisted>) -> Result<()> {
+        instructions::pause_blacklisted(ctx)
     }
 
     /// Expire a session that has passed its duration. Callable by anyone.
     /// Remaining funds stay in vault until user withdraws.
     pub fn expire(ctx: Context<Expire>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        require!(
-            vault.status == VaultStatus::Active || vault.status == VaultStatus::Paused,
-            EscrowError::InvalidStatus
-        );
+        instructions::expire(ctx)
+    }
 
-        let now = Clock::get()?.unix_timestamp;
-        require!(now >= vault.expires_at, EscrowError::SessionNotExpired);
+    /// `expire`, succeeding without doing anything if the session hasn't
+    /// reached its expiry or has already ended. Returns whether it expired it.
+    pub fn expire_if_due(ctx: Context<Expire>) -> Result<bool> {
+        instructions::expire_if_due(ctx)
+    }
 
-        vault.status = VaultStatus::Expired;
+    /// Finalize the session's aggregates for `vault.report_epoch` into an
+    /// EpochReport PDA once that Solana epoch is over. Callable by anyone, who
+    /// pays the report's rent. Epochs with no crank roll into the next report.
+    /// Token positions are valued at Pyth prices, so a session holding any
+    /// needs their feeds passed.
+    pub fn close_epoch(ctx: Context<CloseEpoch>) -> Result<()> {
+        instructions::close_epoch(ctx)
+    }
 
-        emit!(SessionExpiredEvent {
-            session_id: vault.session_id,
-            remaining_balance: vault.balance,
-        });
+    /// Create the session's address lookup table, with the vault PDA as its
+    /// authority. Only the user, who pays rent. `recent_slot` must be a recent
+    /// slot; it's part of the table address.
+    pub fn create_lookup_table(ctx: Context<ManageLookupTable>, recent_slot: u64) -> Result<()> {
+        instructions::create_lookup_table(ctx, recent_slot)
+    }
 
-        Ok(())
+    /// Add the session's recurring route accounts to its lookup table. User or
+    /// bot; the signer pays the extra rent.
+    pub fn extend_lookup_table(
+        ctx: Context<ManageLookupTable>,
+        addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::extend_lookup_table(ctx, addresses)
     }
-}
 
-// ============================================================
-// Whitelisted DEX programs
-// ============================================================
-
-fn is_whitelisted_dex(program_id: &Pubkey) -> bool {
-    let whitelisted: [&str; 5] = [
-        // Jupiter Aggregator v6
-        "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
-        // Raydium AMM
-        "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
-        // Raydium CLMM
-        "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK",
-        // Orca Whirlpool
-        "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
-        // PumpSwap (Pump.fun AMM)
-        "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA",
-    ];
-
-    for addr in whitelisted.iter() {
-        if let Ok(key) = addr.parse::<Pubkey>() {
-            if key == *program_id {
-                return true;
-            }
-        }
+    /// Retire the session's lookup table: the first call deactivates it, a call
+    /// once its cooldown is over (about 513 slots) closes it and refunds its
+    /// rent to the user. Only the user. Required before `withdraw_and_close`.
+    pub fn close_lookup_table(ctx: Context<ManageLookupTable>) -> Result<()> {
+        instructions::close_lookup_table(ctx)
     }
-    false
-}
 
-// ============================================================
-// Accounts
-// ============================================================
-
-#[derive(Accounts)]
-#[instruction(session_id: [u8; 16])]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = user,
-        space = 8 + Vault::INIT_SPACE,
-        seeds = [b"vault", session_id.as_ref(), user.key().as_ref()],
-        bump
-    )]
-    pub vault: Account<'info, Vault>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    /// CHECK: Treasury wallet for fee collection
-    #[account(mut)]
-    pub treasury: UncheckedAccount<'info>,
-
-    pub system_program: Program<'info, System>,
-}
+    /// Opt into perps mode: open a Drift sub-account whose authority is the vault PDA.
+    /// Only the user can enable it, and pays Drift's account rent.
+    pub fn enable_perps(ctx: Context<EnablePerps>) -> Result<()> {
+        instructions::enable_perps(ctx)
+    }
 
-#[derive(Accounts)]
-pub struct Deposit<'info> {
-    #[account(
-        mut,
-        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    /// CHECK: Treasury wallet for fee collection
-    #[account(
-        mut,
-        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
-    )]
-    pub treasury: UncheckedAccount<'info>,
-
-    pub system_program: Program<'info, System>,
-}
+    /// Move SOL from the trading balance into the vault's Drift sub-account as
+    /// collateral. Only the user can fund perps.
+    pub fn perps_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PerpsCollateral<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::perps_deposit(ctx, amount)
+    }
 
-#[derive(Accounts)]
-pub struct ExecuteSwap<'info> {
-    #[account(
-        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
+    /// Withdraw SOL collateral from Drift. Callable by the user or the bot, but
+    /// funds can only land in the vault's own WSOL account — never anywhere else —
+    /// which is then unwrapped back into the trading balance.
+    pub fn perps_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PerpsCollateral<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::perps_withdraw(ctx, amount)
+    }
 
-    #[account(mut)]
-    pub bot: Signer<'info>,
+    /// Bot places a perp order on the vault's Drift sub-account. While the
+    /// session isn't actively trading (paused or past expiry) only reduce-only
+    /// orders are allowed, so the bot can unwind but never add risk.
+    pub fn perps_place_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PerpsOrder<'info>>,
+        params: PerpOrderParams,
+    ) -> Result<()> {
+        instructions::perps_place_order(ctx, params)
+    }
 
-    /// CHECK: The DEX program to CPI into — validated in instruction logic
-    pub dex_program: UncheckedAccount<'info>,
-    // Additional DEX accounts passed via remaining_accounts
-}
+    /// Cancel a perp order on the vault's Drift sub-account. User or bot.
+    pub fn perps_cancel_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PerpsOrder<'info>>,
+        order_id: Option<u32>,
+    ) -> Result<()> {
+        instructions::perps_cancel_order(ctx, order_id)
+    }
 
-#[derive(Accounts)]
-pub struct DeductComputeFee<'info> {
-    #[account(
-        mut,
-        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
-
-    /// CHECK: Treasury wallet
-    #[account(
-        mut,
-        constraint = treasury.key() == vault.treasury @ EscrowError::InvalidTreasury
-    )]
-    pub treasury: UncheckedAccount<'info>,
-
-    /// Anyone can crank this
-    pub cranker: Signer<'info>,
-}
+    /// Opt into lending idle balance on marginfi. Opens a marginfi account in a
+    /// whitelisted group whose authority is the vault PDA; the user pays rent.
+    pub fn enable_lending(ctx: Context<EnableLending>, lend_cap_bps: u16) -> Result<()> {
+        instructions::enable_lending(ctx, lend_cap_bps)
+    }
 
-#[derive(Accounts)]
-pub struct UserAction<'info> {
-    #[account(
-        mut,
-        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-}
+    /// Change the share of the balance that may be lent out. Only the user.
+    /// Lowering it doesn't force an unwind; it only blocks further lending.
+    pub fn set_lend_cap(ctx: Context<UserAction>, lend_cap_bps: u16) -> Result<()> {
+        instructions::set_lend_cap(ctx, lend_cap_bps)
+    }
 
-#[derive(Accounts)]
-pub struct Withdraw<'info> {
-    #[account(
-        mut,
-        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-}
+    /// Lend `amount` of idle SOL from the trading balance into the marginfi bank.
+    /// User or bot. Total lent can't exceed `lend_cap_bps` of balance + lent.
+    pub fn lend<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Lending<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::lend(ctx, amount)
+    }
 
-#[derive(Accounts)]
-pub struct Expire<'info> {
-    #[account(
-        mut,
-        seeds = [b"vault", vault.session_id.as_ref(), vault.user.as_ref()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
+    /// Pull the whole lending position back into the trading balance. User or bot.
+    /// Withdraws into the vault's WSOL account, then closes it into the vault PDA
+    /// so principal plus interest comes back as plain SOL.
+    pub fn unwind_lending<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Lending<'info>>,
+    ) -> Result<()> {
+        instructions::unwind_lending(ctx)
+    }
 
-    pub cranker: Signer<'info>,
-}
+    /// Bot operators publish session defaults as a template. Sessions copy the
+    /// template when they're created, so later updates only affect new sessions.
+    pub fn create_template(
+        ctx: Context<CreateTemplate>,
+        template_id: u64,
+        params: TemplateParams,
+    ) -> Result<()> {
+        instructions::create_template(ctx, template_id, params)
+    }
 
-// ============================================================
-// State
-// ============================================================
-
-#[account]
-#[derive(InitSpace)]
-pub struct Vault {
-    pub user: Pubkey,               // 32 — owner, can always withdraw
-    pub bot: Pubkey,                // 32 — session key, can only swap
-    pub treasury: Pubkey,           // 32 — fee recipient
-    pub session_id: [u8; 16],       // 16 — unique session identifier
-    pub balance: u64,               // 8  — current trading balance (lamports)
-    pub fee_collected: u64,         // 8  — setup fee taken
-    pub compute_fees_paid: u64,     // 8  — total compute fees deducted
-    pub duration_days: u16,         // 2  — session length
-    pub status: VaultStatus,        // 1  — current state
-    pub bump: u8,                   // 1  — PDA bump seed
-    pub created_at: i64,            // 8  — unix timestamp
-    pub funded_at: i64,             // 8  — when deposit landed
-    pub expires_at: i64,            // 8  — when session ends
-    pub last_compute_deduction: i64,// 8  — last daily fee timestamp
-}
+    /// Update a template's defaults for future sessions. Operator only.
+    pub fn update_template(ctx: Context<UpdateTemplate>, params: TemplateParams) -> Result<()> {
+        instructions::update_template(ctx, params)
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
-pub enum VaultStatus {
-    Pending,    // Created, awaiting deposit
-    Active,     // Funded, bot is trading
-    Paused,     // User paused trading
-    Expired,    // Duration ended or balance depleted
-    Withdrawn,  // User withdrew all funds
-}
+    /// Claim the operator's accrued share of setup fees. Operator only.
+    pub fn claim_operator_fees(ctx: Context<UpdateTemplate>) -> Result<()> {
+        instructions::claim_operator_fees(ctx)
+    }
 
-// ============================================================
-// Errors
-// ============================================================
-
-#[error_code]
-pub enum EscrowError {
-    #[msg("Unauthorized: caller is not the vault owner or bot")]
-    Unauthorized,
-    #[msg("Invalid vault status for this operation")]
-    InvalidStatus,
-    #[msg("Deposit amount below minimum (0.1 SOL)")]
-    DepositTooSmall,
-    #[msg("Insufficient balance in vault")]
-    InsufficientBalance,
-    #[msg("DEX program is not whitelisted")]
-    DexNotWhitelisted,
-    #[msg("Trading session has expired")]
-    SessionExpired,
-    #[msg("Session has not expired yet")]
-    SessionNotExpired,
-    #[msg("Math overflow")]
-    MathOverflow,
-    #[msg("Too early for compute fee deduction")]
-    TooEarlyForDeduction,
-    #[msg("Invalid treasury account")]
-    InvalidTreasury,
-}
+    /// Initialize a session from an operator's template: bot, duration, trade
+    /// limit, allowed DEXes and fee split all come from the template.
+    /// Fails for `invite_only` templates.
+    pub fn initialize_from_template(
+        ctx: Context<InitializeFromTemplate>,
+        session_id: [u8; 16],
+    ) -> Result<()> {
+        instructions::initialize_from_template(ctx, session_id)
+    }
 
-// ============================================================
-// Events
-// ============================================================
+    /// Offer one user a session from a template on agreed terms: duration,
+    /// trade limit and fee split. `invitee` may be left default so whoever
+    /// holds the invite's address can redeem it. Operator only.
+    pub fn create_invite(
+        ctx: Context<CreateInvite>,
+        invite_id: u64,
+        params: InviteParams,
+    ) -> Result<()> {
+        instructions::create_invite(ctx, invite_id, params)
+    }
 
-#[event]
-pub struct SessionCreated {
-    pub session_id: [u8; 16],
-    pub user: Pubkey,
-    pub bot: Pubkey,
-    pub duration_days: u16,
-}
+    /// Withdraw an unredeemed invite, refunding its rent. Operator only.
+    pub fn revoke_invite(ctx: Context<RevokeInvite>) -> Result<()> {
+        instructions::revoke_invite(ctx)
+    }
 
-#[event]
-pub struct Deposited {
-    pub session_id: [u8; 16],
-    pub amount: u64,
-    pub fee: u64,
-    pub trading_balance: u64,
-    pub expires_at: i64,
-}
+    /// `initialize_from_template` on an invite's terms. The invite is closed,
+    /// so each one opens a single session.
+    pub fn initialize_from_invite(
+        ctx: Context<InitializeFromInvite>,
+        session_id: [u8; 16],
+    ) -> Result<()> {
+        instructions::initialize_from_invite(ctx, session_id)
+    }
 
-#[event]
-pub struct SwapExecuted {
-    pub session_id: [u8; 16],
-    pub bot: Pubkey,
-    pub dex_program: Pubkey,
-    pub amount_in: u64,
-    pub minimum_amount_out: u64,
-    pub timestamp: i64,
-}
+    /// CPI-only variant of `initialize` for sessions owned by another program's
+    /// PDA. The caller signs for `user` with `user_seeds` (bump included) under
+    /// `user_program`; a separate `payer` covers rent, so the PDA may hold data.
+    pub fn initialize_for_program(
+        ctx: Context<InitializeForProgram>,
+        session_id: [u8; 16],
+        duration_days: u16,
+        bot_pubkey: Pubkey,
+        user_program: Pubkey,
+        user_seeds: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        instructions::initialize_for_program(ctx, session_id, duration_days, bot_pubkey, user_program, user_seeds)
+    }
 
-#[event]
-pub struct ComputeFeeDeducted {
-    pub session_id: [u8; 16],
-    pub fee: u64,
-    pub remaining_balance: u64,
-}
+    /// `deposit` for a program-owned session: the PDA user authorizes, `payer` funds.
+    pub fn deposit_for_program(ctx: Context<DepositForProgram>, amount: u64) -> Result<()> {
+        instructions::deposit_for_program(ctx, amount)
+    }
 
-#[event]
-pub struct SessionPaused {
-    pub session_id: [u8; 16],
-}
+    /// `withdraw` for a program-owned session: the PDA user authorizes and names
+    /// the `recipient`, e.g. the calling program's own vault.
+    pub fn withdraw_for_program(ctx: Context<WithdrawForProgram>) -> Result<()> {
+        instructions::withdraw_for_program(ctx)
+    }
 
-#[event]
-pub struct SessionResumed {
-    pub session_id: [u8; 16],
-}
+    /// Initialize a session denominated in an approved stablecoin instead of SOL.
+    /// Creates the vault's token account for the base mint alongside the vault.
+    /// `payer` covers the rent, so a relayer can open sessions for users with no SOL.
+    /// Its balance trades through venues whose adapter can sell any mint (Jupiter).
+    pub fn initialize_token_session(
+        ctx: Context<InitializeTokenSession>,
+        session_id: [u8; 16],
+        duration_days: u16,
+        bot_pubkey: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_token_session(ctx, session_id, duration_days, bot_pubkey)
+    }
 
-#[event]
-pub struct Withdrawn {
-    pub session_id: [u8; 16],
-    pub amount: u64,
-    pub user: Pubkey,
-}
+    /// Deposit the base mint into a stablecoin session, under `deposit`'s checks
+    /// and `set_stable_deposit_limits`' limits. Setup fee taken, remainder is
+    /// trading balance; the daily compute fee is fixed at DAILY_COMPUTE_FEE_BPS of it.
+    pub fn deposit_token(ctx: Context<DepositToken>, amount: u64) -> Result<()> {
+        instructions::deposit_token(ctx, amount)
+    }
+
+    /// Fund a Pending stablecoin session from another chain: redeem a Wormhole
+    /// Token Bridge transfer-with-payload addressed to the vault's token account,
+    /// verified and claimed by the Token Bridge, and deposit what arrives as
+    /// `deposit_token` would. Callable by anyone holding the posted VAA, but the
+    /// transfer's sender must be the session's `funder`. The Token Bridge's
+    /// accounts follow as remaining accounts; `wrapped` picks its variant for
+    /// tokens not native to Solana.
+    pub fn deposit_from_bridge<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositFromBridge<'info>>,
+        wrapped: bool,
+    ) -> Result<()> {
+        instructions::deposit_from_bridge(ctx, wrapped)
+    }
+
+    /// Fund a Pending stablecoin session with the whole balance of `funder`'s
+    /// token account, as `deposit_token` would, under the same checks and
+    /// limits. Meant as the destination call of a deBridge DLN order, whose
+    /// external-call authority signs as `funder`, so a cross-chain order lands
+    /// straight in a session the user opened beforehand and activates it.
+    /// `funder` must be the one the user named with `set_funder`.
+    pub fn deposit_from_dln(ctx: Context<DepositFromDln>) -> Result<()> {
+        instructions::deposit_from_dln(ctx)
+    }
+
+    /// Daily compute fee crank for stablecoin sessions. Callable by anyone.
+    /// The bot's operator credit pays first, the fee valued in SOL at Pyth
+    /// prices: if it has any, pass the SOL and base-mint price updates as
+    /// remaining accounts.
+    pub fn deduct_compute_fee_token<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DeductComputeFeeToken<'info>>,
+    ) -> Result<()> {
+        instructions::deduct_compute_fee_token(ctx)
+    }
+
+    /// Withdraw a stablecoin session's balance to the user's token account.
+    /// Same rules as `withdraw`: user only, any state except Pending, fees
+    /// settled first, with price updates as for `deduct_compute_fee_token`.
+    pub fn withdraw_token<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawToken<'info>>) -> Result<()> {
+        instructions::withdraw_token(ctx)
+    }
+
+    /// Create the protocol config. Only the program's upgrade authority can call
+    /// this, once; it starts out as the single-key admin.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        guardian: Pubkey,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_config(ctx, guardian, treasury)
+    }
 
-#[event]
-pub struct SessionExpiredEvent {
-    pub session_id: [u8; 16],
-    pub remaining_balance: u64,
+    /// Add or remove a bot key from the blacklist. Admin only.
+    ///
+    /// Blacklisted bots can't be given new sessions. Their existing sessions
+    /// pause on the bot's next signed instruction, or via `pause_blacklisted`,
+    /// and their users can withdraw as usual.
+    pub fn set_bot_blacklisted(ctx: Context<AdminAction>, bot: Pubkey, blacklisted: bool) -> Result<()> {
+        instructions::set_bot_blacklisted(ctx, bot, blacklisted)
+    }
+
+    /// Add or remove a DEX program from the whitelist. Admin only.
+    ///
+    /// Additions apply to every session at once. Removals are grandfathered:
+    /// sessions funded before the removal keep the DEX until their user adopts
+    /// the latest whitelist. Use `revoke_dex` to cut a DEX off for everyone.
+    pub fn set_dex_whitelisted(
+        ctx: Context<AdminAction>,
+        program_id: Pubkey,
+        whitelisted: bool,
+    ) -> Result<()> {
+        instructions::set_dex_whitelisted(ctx, program_id, whitelisted)
+    }
+
+    /// Remove a DEX for every session immediately, grandfathered or not — for
+    /// venues that are compromised rather than merely retired. Admin only.
+    pub fn revoke_dex(ctx: Context<AdminAction>, program_id: Pubkey) -> Result<()> {
+        instructions::revoke_dex(ctx, program_id)
+    }
+
+    /// Opt a session out of grandfathered DEXes by moving its whitelist
+    /// snapshot to the current version. Only the user.
+    pub fn adopt_latest_whitelist(ctx: Context<AdoptLatestWhitelist>) -> Result<()> {
+        instructions::adopt_latest_whitelist(ctx)
+    }
+
+    /// Register (or replace) the Pyth feed used to value `mint` in risk checks.
+    /// SOL's own feed is registered under the native mint. Admin only.
+    pub fn set_price_feed(
+        ctx: Context<AdminAction>,
+        mint: Pubkey,
+        feed_id: [u8; 32],
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::set_price_feed(ctx, mint, feed_id, decimals)
+    }
+
+    /// Update the setup fee and daily compute fee for new sessions. Admin only.
+    /// Funded sessions keep the compute fee they were funded with.
+    pub fn set_fees(ctx: Context<AdminAction>, fee_bps: u16, daily_compute_fee: u64) -> Result<()> {
+        instructions::set_fees(ctx, fee_bps, daily_compute_fee)
+    }
+
+    /// Bound SOL deposits: `min_deposit` (never below `MIN_DEPOSIT`) per
+    /// deposit, and `max_deposit` of trading balance per session, counting
+    /// transfers in; 0 lifts the cap. Admin only. Funded sessions keep what
+    /// they hold.
+    pub fn set_deposit_limits(ctx: Context<AdminAction>, min_deposit: u64, max_deposit: u64) -> Result<()> {
+        instructions::set_deposit_limits(ctx, min_deposit, max_deposit)
+    }
+
+    /// Bound stablecoin deposits as `set_deposit_limits` does SOL ones, in
+    /// base units: `min_deposit` (never below `MIN_STABLE_DEPOSIT`) and
+    /// `max_deposit`, 0 lifting the cap. Admin only.
+    pub fn set_stable_deposit_limits(ctx: Context<AdminAction>, min_deposit: u64, max_deposit: u64) -> Result<()> {
+        instructions::set_stable_deposit_limits(ctx, min_deposit, max_deposit)
+    }
+
+    /// Set the reward points emission schedule. Admin only. Points already
+    /// settled are unaffected; a session's unsettled funded time earns duration
+    /// points at the schedule in force when the crank or a withdrawal settles it.
+    pub fn set_rewards_schedule(ctx: Context<AdminAction>, schedule: RewardsSchedule) -> Result<()> {
+        instructions::set_rewards_schedule(ctx, schedule)
+    }
+
+    /// Lock SOL in the user's stake account for a setup fee discount tier.
+    /// Every top-up restarts the lock.
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        instructions::stake(ctx, amount)
+    }
+
+    /// Pull staked SOL back once the lock has passed.
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        instructions::unstake(ctx, amount)
+    }
+
+    /// Prepay `amount` lamports of compute fees for the signing bot's
+    /// sessions. Every compute fee settlement, on the crank or when a session
+    /// is withdrawn or transferred, draws on the credit before the session's
+    /// balance.
+    pub fn fund_operator_credit(ctx: Context<FundOperatorCredit>, amount: u64) -> Result<()> {
+        instructions::fund_operator_credit(ctx, amount)
+    }
+
+    /// Take back unspent operator credit. Bot only.
+    pub fn withdraw_operator_credit(ctx: Context<WithdrawOperatorCredit>, amount: u64) -> Result<()> {
+        instructions::withdraw_operator_credit(ctx, amount)
+    }
+
+    /// Rotate the guardian. Admin only.
+    pub fn set_guardian(ctx: Context<AdminAction>, guardian: Pubkey) -> Result<()> {
+        instructions::set_guardian(ctx, guardian)
+    }
+
+    /// Pause a session flagged as under active exploitation, with a `reason`
+    /// code. The guardian can only stop trading: the user can still withdraw,
+    /// and the session lifts back to Active after MAX_GUARDIAN_PAUSE_DAYS
+    /// unless the user keeps it paused with `confirm_guardian_pause`. Guardian only.
+    pub fn guardian_pause(ctx: Context<GuardianPause>, reason: PauseReason) -> Result<()> {
+        instructions::guardian_pause(ctx, reason)
+    }
+
+    /// Lift a guardian pause: the guardian any time, anyone once
+    /// MAX_GUARDIAN_PAUSE_DAYS have passed.
+    pub fn lift_guardian_pause(ctx: Context<LiftGuardianPause>) -> Result<()> {
+        instructions::lift_guardian_pause(ctx)
+    }
+
+    /// Keep a guardian-paused session paused as if the user had paused it, so
+    /// it no longer lifts on its own; `resume` it when ready. Only the user.
+    pub fn confirm_guardian_pause(ctx: Context<UserAction>) -> Result<()> {
+        instructions::confirm_guardian_pause(ctx)
+    }
+
+    /// Record whether `bot`'s operator is verified and its strategy audited,
+    /// in the bot's `BotProfile`. Guardian only; call again to revoke.
+    pub fn attest_bot(ctx: Context<AttestBot>, bot: Pubkey, verified: bool, audited: bool) -> Result<()> {
+        instructions::attest_bot(ctx, bot, verified, audited)
+    }
+
+    /// Put an attested `bot` in a compute fee `tier`; 0 is the default fee.
+    /// SOL sessions opened requiring the bot's `BotProfile` snapshot that
+    /// tier's fee when they're funded. Admin only.
+    pub fn set_bot_tier(ctx: Context<SetBotTier>, bot: Pubkey, tier: u8) -> Result<()> {
+        instructions::set_bot_tier(ctx, bot, tier)
+    }
+
+    /// Set the daily compute fee for bot tiers 1, 2, … in order, up to
+    /// MAX_BOT_TIERS; tiers past the end pay the default. Admin only.
+    /// Funded sessions keep the compute fee they were funded with.
+    pub fn set_tier_compute_fees(ctx: Context<AdminAction>, fees: Vec<u64>) -> Result<()> {
+        instructions::set_tier_compute_fees(ctx, fees)
+    }
+
+    /// Share every SOL fee between the treasury and up to MAX_FEE_RECIPIENTS
+    /// recipients, each taking `bps` of the fee; the treasury keeps the rest.
+    /// Shares accrue on the fee router until each recipient claims. Admin only.
+    pub fn set_fee_route(ctx: Context<SetFeeRoute>, shares: Vec<FeeShare>) -> Result<()> {
+        instructions::set_fee_route(ctx, shares)
+    }
+
+    /// Claim a fee router recipient's accrued share. Recipient only.
+    pub fn claim_routed_fees(ctx: Context<ClaimRoutedFees>) -> Result<()> {
+        instructions::claim_routed_fees(ctx)
+    }
+
+    /// Change the treasury new sessions pay fees to; move existing sessions
+    /// over with `migrate_treasury`. Admin only.
+    pub fn set_treasury(ctx: Context<AdminAction>, treasury: Pubkey) -> Result<()> {
+        instructions::set_treasury(ctx, treasury)
+    }
+
+    /// Point an existing session's fees at the config's current treasury,
+    /// after `set_treasury`. Admin only; fails if the session already pays
+    /// it. Emits TreasuryMigrated, which the indexer notifies the user of.
+    pub fn migrate_treasury(ctx: Context<MigrateTreasury>) -> Result<()> {
+        instructions::migrate_treasury(ctx)
+    }
+
+    /// Publish the program's governance posture in the `upgrade_info` PDA:
+    /// the upgrade authority and deploy slot as ProgramData has them, plus the
+    /// admin's committed upgrade timelock and the deployed build's hash.
+    /// Admin only; commit a new hash after every upgrade.
+    pub fn set_upgrade_info(
+        ctx: Context<SetUpgradeInfo>,
+        timelock_seconds: u64,
+        build_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::set_upgrade_info(ctx, timelock_seconds, build_hash)
+    }
+
+    /// Re-read the upgrade authority and deploy slot from ProgramData, so an
+    /// authority change or an upgrade without a new build hash shows up.
+    /// Callable by anyone.
+    pub fn refresh_upgrade_info(ctx: Context<RefreshUpgradeInfo>) -> Result<()> {
+        instructions::refresh_upgrade_info(ctx)
+    }
+
+    /// First step of an admin handover, e.g. from the single-key admin to a
+    /// Realms governance account. Admin only; `new_admin` must accept.
+    pub fn propose_admin(ctx: Context<AdminAction>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin(ctx, new_admin)
+    }
+
+    /// Second step of an admin handover, signed by the proposed admin. For a
+    /// Realms governance account this is an instruction in an executed proposal,
+    /// which proves the proposal path works before the old key loses control.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
 }
@@ -0,0 +1,143 @@
+//! Address lookup tables owned by the vault PDA.
+//!
+//! DEX routes often need more accounts than fit in a legacy transaction. A
+//! session can keep its recurring accounts (vault, token accounts, markets) in
+//! a lookup table whose authority is the vault, so only the program can change
+//! it and clients can build versioned transactions against it. Instructions
+//! are encoded by hand, as the DEX adapters do, to avoid pulling in bincode.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+use crate::errors::EscrowError;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("AddressLookupTab1e1111111111111111111111111");
+
+/// `ProgramInstruction::CreateLookupTable` tag
+const CREATE_LOOKUP_TABLE: u32 = 0;
+/// `ProgramInstruction::ExtendLookupTable` tag
+const EXTEND_LOOKUP_TABLE: u32 = 2;
+/// `ProgramInstruction::DeactivateLookupTable` tag
+const DEACTIVATE_LOOKUP_TABLE: u32 = 3;
+/// `ProgramInstruction::CloseLookupTable` tag
+const CLOSE_LOOKUP_TABLE: u32 = 4;
+
+// `LookupTableMeta` follows a u32 state tag; a table is active until its
+// deactivation slot is set
+const DEACTIVATION_SLOT_OFFSET: usize = 4;
+
+/// Lookup table address for `authority` created at `recent_slot`.
+pub fn derive_address(authority: &Pubkey, recent_slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[authority.as_ref(), &recent_slot.to_le_bytes()], &PROGRAM_ID)
+}
+
+/// Create a lookup table with the vault as authority. `payer` funds its rent.
+pub fn create<'info>(
+    lookup_table: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+    recent_slot: u64,
+    bump: u8,
+) -> Result<()> {
+    let mut data = CREATE_LOOKUP_TABLE.to_le_bytes().to_vec();
+    data.extend_from_slice(&recent_slot.to_le_bytes());
+    data.push(bump);
+
+    invoke_table_instruction(data, lookup_table, vault, payer, system_program, vault_seeds)
+}
+
+/// Append `addresses` to a vault-owned lookup table. `payer` funds the extra rent.
+pub fn extend<'info>(
+    lookup_table: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+    addresses: &[Pubkey],
+) -> Result<()> {
+    let mut data = EXTEND_LOOKUP_TABLE.to_le_bytes().to_vec();
+    data.extend_from_slice(&(addresses.len() as u64).to_le_bytes());
+    for address in addresses {
+        data.extend_from_slice(address.as_ref());
+    }
+
+    invoke_table_instruction(data, lookup_table, vault, payer, system_program, vault_seeds)
+}
+
+/// Whether `lookup_table` has been deactivated, so it can be closed once its
+/// cooldown is over.
+pub fn is_deactivated(lookup_table: &AccountInfo) -> Result<bool> {
+    let data = lookup_table.try_borrow_data()?;
+    let slot = data
+        .get(DEACTIVATION_SLOT_OFFSET..DEACTIVATION_SLOT_OFFSET + 8)
+        .ok_or(EscrowError::InvalidLookupTable)?;
+    Ok(u64::from_le_bytes(slot.try_into().unwrap()) != u64::MAX)
+}
+
+/// Deactivate a vault-owned lookup table, starting the cooldown before it can be closed.
+pub fn deactivate<'info>(
+    lookup_table: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(lookup_table.key(), false),
+            AccountMeta::new_readonly(vault.key(), true),
+        ],
+        data: DEACTIVATE_LOOKUP_TABLE.to_le_bytes().to_vec(),
+    };
+    invoke_signed(&ix, &[lookup_table.clone(), vault.clone()], &[vault_seeds])?;
+    Ok(())
+}
+
+/// Close a deactivated vault-owned lookup table, its rent going to `recipient`.
+pub fn close<'info>(
+    lookup_table: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(lookup_table.key(), false),
+            AccountMeta::new_readonly(vault.key(), true),
+            AccountMeta::new(recipient.key(), false),
+        ],
+        data: CLOSE_LOOKUP_TABLE.to_le_bytes().to_vec(),
+    };
+    invoke_signed(&ix, &[lookup_table.clone(), vault.clone(), recipient.clone()], &[vault_seeds])?;
+    Ok(())
+}
+
+/// Create and extend share one account list: table, authority, payer, system program.
+fn invoke_table_instruction<'info>(
+    data: Vec<u8>,
+    lookup_table: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(lookup_table.key(), false),
+            AccountMeta::new_readonly(vault.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(System::id(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[lookup_table.clone(), vault.clone(), payer.clone(), system_program.clone()],
+        &[vault_seeds],
+    )?;
+    Ok(())
+}
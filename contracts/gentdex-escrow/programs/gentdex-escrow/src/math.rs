@@ -0,0 +1,120 @@
+//! Checked fee math shared by every fee-bearing instruction.
+//!
+//! Products of balances, bps rates and day counts are computed in u128 and
+//! only narrowed back to u64 at the end, so large deposits or long accrual
+//! windows can't overflow an intermediate.
+
+use anchor_lang::prelude::*;
+
+use crate::lamports;
+use crate::EscrowError;
+
+/// Denominator for basis-point rates (100% = 10_000 bps)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// Seconds in one compute-fee day
+pub const SECONDS_PER_DAY: i64 = 86_400;
+/// Lamports in one SOL
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// `amount * numerator / denominator`, rounded down.
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    require!(denominator != 0, EscrowError::MathOverflow);
+    let result = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / denominator as u128;
+    u64::try_from(result).map_err(|_| error!(EscrowError::MathOverflow))
+}
+
+/// `bps` basis points of `amount`, rounded down.
+pub fn bps_of(amount: u64, bps: u64) -> Result<u64> {
+    mul_div(amount, bps, BPS_DENOMINATOR)
+}
+
+/// Split a gross amount into `(fee, net)` at the given bps rate.
+pub fn split_fee(amount: u64, bps: u64) -> Result<(u64, u64)> {
+    lamports::split(amount, bps).ok_or_else(|| error!(EscrowError::MathOverflow))
+}
+
+/// Fee for `days` at `daily_fee` per day, capped at `cap`.
+/// Saturates at the cap instead of failing when the raw product exceeds u64.
+pub fn accrued_fee(days: u64, daily_fee: u64, cap: u64) -> u64 {
+    let fee = (days as u128) * (daily_fee as u128);
+    fee.min(cap as u128) as u64
+}
+
+/// Whole days from `from` to `to`; zero if `to` is not after `from`.
+pub fn whole_days_between(from: i64, to: i64) -> Result<u64> {
+    let seconds = (to as i128) - (from as i128);
+    if seconds <= 0 {
+        return Ok(0);
+    }
+    u64::try_from(seconds / SECONDS_PER_DAY as i128)
+        .map_err(|_| error!(EscrowError::MathOverflow))
+}
+
+/// `timestamp + days` whole days, as a unix timestamp.
+pub fn add_days(timestamp: i64, days: u64) -> Result<i64> {
+    let seconds = (days as i128) * (SECONDS_PER_DAY as i128);
+    let result = (timestamp as i128)
+        .checked_add(seconds)
+        .ok_or(EscrowError::MathOverflow)?;
+    i64::try_from(result).map_err(|_| error!(EscrowError::MathOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn bps_of_max_amount_does_not_overflow() {
+        assert_eq!(bps_of(u64::MAX, BPS_DENOMINATOR).unwrap(), u64::MAX);
+        assert_eq!(bps_of(u64::MAX, 250).unwrap(), (u64::MAX as u128 * 250 / 10_000) as u64);
+    }
+
+    #[test]
+    fn mul_div_rejects_results_wider_than_u64() {
+        assert!(mul_div(u64::MAX, 2, 1).is_err());
+        assert!(mul_div(1, 1, 0).is_err());
+    }
+
+    #[test]
+    fn whole_days_between_ignores_partial_and_negative_spans() {
+        assert_eq!(whole_days_between(0, SECONDS_PER_DAY - 1).unwrap(), 0);
+        assert_eq!(whole_days_between(0, SECONDS_PER_DAY).unwrap(), 1);
+        assert_eq!(whole_days_between(SECONDS_PER_DAY, 0).unwrap(), 0);
+        assert_eq!(
+            whole_days_between(i64::MIN, i64::MAX).unwrap(),
+            (u64::MAX / SECONDS_PER_DAY as u64)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn split_fee_conserves_amount(amount in any::<u64>(), bps in 0..=BPS_DENOMINATOR) {
+            let (fee, net) = split_fee(amount, bps).unwrap();
+            prop_assert_eq!(fee as u128 + net as u128, amount as u128);
+            prop_assert!(fee <= amount);
+        }
+
+        #[test]
+        fn bps_of_matches_u128_reference(amount in any::<u64>(), bps in 0..=BPS_DENOMINATOR) {
+            let expected = (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64;
+            prop_assert_eq!(bps_of(amount, bps).unwrap(), expected);
+        }
+
+        #[test]
+        fn accrued_fee_never_exceeds_cap(days in any::<u64>(), daily in any::<u64>(), cap in any::<u64>()) {
+            let fee = accrued_fee(days, daily, cap);
+            prop_assert!(fee <= cap);
+            prop_assert_eq!(fee as u128, (days as u128 * daily as u128).min(cap as u128));
+        }
+
+        #[test]
+        fn add_days_round_trips(start in -(1i64 << 40)..(1i64 << 40), days in 0u64..(1 << 20)) {
+            let end = add_days(start, days).unwrap();
+            prop_assert_eq!(whole_days_between(start, end).unwrap(), days);
+        }
+    }
+}
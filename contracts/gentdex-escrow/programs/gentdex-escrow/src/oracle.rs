@@ -0,0 +1,138 @@
+//! Pyth price reads for risk checks that need a valuation.
+//!
+//! Prices come from Pyth pull-oracle `PriceUpdateV2` accounts owned by the
+//! Pyth receiver program. Only fully verified updates are accepted, which
+//! fixes the layout, so fields are read at constant offsets like the DEX
+//! adapters do. Which feed belongs to which mint is set in `ProtocolConfig`.
+
+use anchor_lang::prelude::*;
+
+use crate::EscrowError;
+
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Oldest price update a risk check will accept
+pub const MAX_PRICE_AGE_SECONDS: i64 = 60;
+
+/// Decimals of SOL's base unit, the lamport
+const SOL_DECIMALS: u8 = 9;
+
+/// `VerificationLevel::Full` tag
+const VERIFICATION_FULL: u8 = 1;
+
+// PriceUpdateV2 field offsets (after the 8-byte Anchor discriminator)
+const VERIFICATION_LEVEL_OFFSET: usize = 40;
+const FEED_ID_OFFSET: usize = 41;
+const PRICE_OFFSET: usize = 73;
+const EXPONENT_OFFSET: usize = 89;
+const PUBLISH_TIME_OFFSET: usize = 93;
+const PRICE_UPDATE_MIN_LEN: usize = 101;
+
+/// A positive price, `price * 10^expo` USD per whole token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Price {
+    pub price: u64,
+    pub expo: i32,
+}
+
+/// Base-currency and output-token prices for valuing a swap's output.
+pub struct SwapPrices {
+    pub base: Price,
+    pub base_decimals: u8,
+    pub token: Price,
+    pub decimals: u8,
+}
+
+impl SwapPrices {
+    /// Value of `amount` of the output token, in base units of the session's
+    /// currency (lamports for SOL sessions).
+    pub fn value_in_base(&self, amount: u64) -> Result<u64> {
+        value_in(amount, self.decimals, self.token, self.base_decimals, self.base)
+    }
+}
+
+/// Read a fresh, fully verified price for `feed_id` from a Pyth price update account.
+pub fn load_price(info: &AccountInfo, feed_id: &[u8; 32], now: i64) -> Result<Price> {
+    require_keys_eq!(*info.owner, PYTH_RECEIVER_PROGRAM_ID, EscrowError::InvalidPriceFeed);
+    let data = info.try_borrow_data()?;
+    require!(data.len() >= PRICE_UPDATE_MIN_LEN, EscrowError::InvalidPriceFeed);
+    require!(data[VERIFICATION_LEVEL_OFFSET] == VERIFICATION_FULL, EscrowError::InvalidPriceFeed);
+    require!(
+        data[FEED_ID_OFFSET..FEED_ID_OFFSET + 32] == feed_id[..],
+        EscrowError::InvalidPriceFeed
+    );
+
+    let price = i64::from_le_bytes(data[PRICE_OFFSET..PRICE_OFFSET + 8].try_into().unwrap());
+    let expo = i32::from_le_bytes(data[EXPONENT_OFFSET..EXPONENT_OFFSET + 4].try_into().unwrap());
+    let publish_time =
+        i64::from_le_bytes(data[PUBLISH_TIME_OFFSET..PUBLISH_TIME_OFFSET + 8].try_into().unwrap());
+    require!(now - publish_time <= MAX_PRICE_AGE_SECONDS, EscrowError::StalePrice);
+    require!(price > 0, EscrowError::InvalidPriceFeed);
+
+    Ok(Price {
+        price: price as u64,
+        expo,
+    })
+}
+
+/// Value of `amount` base units of a `decimals`-decimal token, in lamports,
+/// given USD prices for the token and for SOL. Rounded down.
+pub fn value_in_lamports(amount: u64, decimals: u8, token: Price, sol: Price) -> Result<u64> {
+    value_in(amount, decimals, token, SOL_DECIMALS, sol)
+}
+
+/// Value of `amount` base units of a `decimals`-decimal token, in base units
+/// of another, `quote_decimals`-decimal token, given USD prices for both.
+/// Rounded down.
+pub fn value_in(amount: u64, decimals: u8, token: Price, quote_decimals: u8, quote: Price) -> Result<u64> {
+    // amount * token.price * 10^token.expo / 10^decimals,
+    // over quote.price * 10^quote.expo / 10^quote_decimals
+    let mut numerator = (amount as u128)
+        .checked_mul(token.price as u128)
+        .and_then(|n| n.checked_mul(10u128.checked_pow(quote_decimals as u32)?))
+        .ok_or(EscrowError::MathOverflow)?;
+    let mut denominator = 10u128
+        .checked_pow(decimals as u32)
+        .and_then(|d| d.checked_mul(quote.price as u128))
+        .ok_or(EscrowError::MathOverflow)?;
+
+    let scale = |expo: i32| 10u128.checked_pow(expo.unsigned_abs()).ok_or(EscrowError::MathOverflow);
+    let expo = token.expo - quote.expo;
+    if expo >= 0 {
+        numerator = numerator.checked_mul(scale(expo)?).ok_or(EscrowError::MathOverflow)?;
+    } else {
+        denominator = denominator.checked_mul(scale(expo)?).ok_or(EscrowError::MathOverflow)?;
+    }
+
+    u64::try_from(numerator / denominator).map_err(|_| error!(EscrowError::MathOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::LAMPORTS_PER_SOL;
+
+    #[test]
+    fn values_a_stablecoin_in_lamports() {
+        // 150 USDC at $1.00, SOL at $150.00 (both expo -8) = 1 SOL
+        let usdc = Price { price: 100_000_000, expo: -8 };
+        let sol = Price { price: 15_000_000_000, expo: -8 };
+        assert_eq!(value_in_lamports(150_000_000, 6, usdc, sol).unwrap(), LAMPORTS_PER_SOL);
+    }
+
+    #[test]
+    fn handles_mismatched_exponents() {
+        // Same prices, token quoted at expo -5
+        let usdc = Price { price: 100_000, expo: -5 };
+        let sol = Price { price: 15_000_000_000, expo: -8 };
+        assert_eq!(value_in_lamports(150_000_000, 6, usdc, sol).unwrap(), LAMPORTS_PER_SOL);
+    }
+
+    #[test]
+    fn values_sol_in_a_stablecoin() {
+        // 1 SOL at $150.00 = 150 USDC
+        let usdc = Price { price: 100_000_000, expo: -8 };
+        let sol = Price { price: 15_000_000_000, expo: -8 };
+        assert_eq!(value_in(LAMPORTS_PER_SOL, 9, sol, 6, usdc).unwrap(), 150_000_000);
+    }
+}
@@ -0,0 +1,94 @@
+//! PDA addresses for programs composing with GentDex via CPI.
+//!
+//! Seeds are part of the stable interface: they're exported as IDL constants
+//! so `declare_program!` consumers can derive the same addresses.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    BOT_PROFILE_SEED, BOT_STATS_SEED, CONFIG_SEED, EPOCH_REPORT_SEED, FEE_ROUTER_SEED, GIFT_SEED, INVITE_SEED,
+    OPERATOR_CREDIT_SEED, REGISTRY_SEED, REWARDS_SEED, STAKE_SEED, TRADE_BATCH_SEED, UPGRADE_INFO_SEED, VAULT_SEED,
+};
+
+/// The session vault for `session_id` owned by `user`.
+pub fn vault_address(session_id: &[u8; 16], user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, session_id, user.as_ref()], &crate::ID)
+}
+
+/// Session id of `user`'s `index`-th session opened with `initialize_indexed`:
+/// the index, little-endian, zero-padded to 16 bytes.
+pub fn indexed_session_id(index: u64) -> [u8; 16] {
+    let mut session_id = [0u8; 16];
+    session_id[..8].copy_from_slice(&index.to_le_bytes());
+    session_id
+}
+
+/// `user`'s `index`-th indexed session vault.
+pub fn indexed_vault_address(user: &Pubkey, index: u64) -> (Pubkey, u8) {
+    vault_address(&indexed_session_id(index), user)
+}
+
+/// `user`'s session registry, counting indexed sessions.
+pub fn registry_address(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REGISTRY_SEED, user.as_ref()], &crate::ID)
+}
+
+/// The protocol config.
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &crate::ID)
+}
+
+/// `user`'s reward points account.
+pub fn rewards_address(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARDS_SEED, user.as_ref()], &crate::ID)
+}
+
+/// `user`'s fee discount stake account.
+pub fn stake_address(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_SEED, user.as_ref()], &crate::ID)
+}
+
+/// `vault`'s report for Solana `epoch`.
+pub fn epoch_report_address(vault: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EPOCH_REPORT_SEED, vault.as_ref(), &epoch.to_le_bytes()], &crate::ID)
+}
+
+/// Settled-session stats for the bot key `bot`.
+pub fn bot_stats_address(bot: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BOT_STATS_SEED, bot.as_ref()], &crate::ID)
+}
+
+/// The guardian's attestation for the bot key `bot`.
+pub fn bot_profile_address(bot: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BOT_PROFILE_SEED, bot.as_ref()], &crate::ID)
+}
+
+/// Invite `invite_id` to sessions from `template`.
+pub fn invite_address(template: &Pubkey, invite_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INVITE_SEED, template.as_ref(), &invite_id.to_le_bytes()], &crate::ID)
+}
+
+/// The fee router sharing SOL fees with the treasury.
+pub fn fee_router_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_ROUTER_SEED], &crate::ID)
+}
+
+/// The program's published upgrade authority, timelock and build hash.
+pub fn upgrade_info_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UPGRADE_INFO_SEED], &crate::ID)
+}
+
+/// The swap buffer of a session that batches its swap events.
+pub fn trade_batch_address(vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TRADE_BATCH_SEED, vault.as_ref()], &crate::ID)
+}
+
+/// The funds gifted to a Pending session, awaiting its user's acceptance.
+pub fn gift_address(vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GIFT_SEED, vault.as_ref()], &crate::ID)
+}
+
+/// The prepaid credit covering `bot`'s sessions' compute fees.
+pub fn operator_credit_address(bot: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OPERATOR_CREDIT_SEED, bot.as_ref()], &crate::ID)
+}
@@ -0,0 +1,50 @@
+//! Evidence that a swap ran in a context that's hard to sandwich.
+//!
+//! Sessions can opt into either check (or both) with `set_swap_protection`:
+//! - slot age: the bot passes the slot it quoted at, and the swap must land
+//!   within `max_slot_age` slots of it, so a delayed transaction can't be
+//!   wrapped by a searcher later;
+//! - Jito tip: the transaction must also pay a Jito tip account, which in
+//!   practice means it was submitted as a bundle rather than via the public
+//!   mempool. Found by introspecting the instructions sysvar.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+
+/// Jito's tip payment accounts
+const JITO_TIP_ACCOUNTS: [Pubkey; 8] = [
+    pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+/// System program `Transfer` instruction tag
+const SYSTEM_TRANSFER_TAG: u32 = 2;
+
+/// Whether `recent_slot` is at most `max_slot_age` slots behind `current_slot`.
+pub fn slot_is_recent(recent_slot: u64, current_slot: u64, max_slot_age: u64) -> bool {
+    recent_slot <= current_slot && current_slot - recent_slot <= max_slot_age
+}
+
+/// Whether any instruction in the transaction is a System transfer to a Jito
+/// tip account. `instructions_sysvar` must be the instructions sysvar.
+pub fn has_jito_tip(instructions_sysvar: &AccountInfo) -> Result<bool> {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions_sysvar) {
+        let is_transfer = ix.program_id == System::id()
+            && ix.data.len() >= 4
+            && ix.data[..4] == SYSTEM_TRANSFER_TAG.to_le_bytes();
+        if is_transfer
+            && ix.accounts.get(1).is_some_and(|to| JITO_TIP_ACCOUNTS.contains(&to.pubkey))
+        {
+            return Ok(true);
+        }
+        index += 1;
+    }
+    Ok(false)
+}
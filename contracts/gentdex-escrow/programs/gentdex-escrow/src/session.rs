@@ -0,0 +1,361 @@
+//! Session setup and payout helpers shared by the SOL, stablecoin and
+//! program-owned session instructions.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::compute_fee::settle_compute_fee;
+use crate::constants::{DAILY_COMPUTE_FEE_BPS, DEFAULT_LEND_CAP_BPS};
+use crate::errors::EscrowError;
+use crate::fee_router::{route_fee, FeeSource};
+use crate::lamports::{self, LamportError};
+use crate::math;
+use crate::events::{BlacklistedBotPaused, BotStatsUpdated, SessionPaused};
+use crate::state::{
+    session_pnl, BotProfile, BotStats, EpochSnapshot, ProtocolConfig, RewardsAccount, RewardsSchedule, SessionTemplate,
+    Vault, VaultStatus,
+};
+
+/// Stablecoins a session may be denominated in instead of SOL.
+pub fn is_approved_base_mint(mint: &Pubkey) -> bool {
+    let approved: [Pubkey; 2] = [
+        // USDC
+        pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+        // USDT
+        pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"),
+    ];
+    approved.contains(mint)
+}
+
+/// Transfer `amount` of the base mint out of the vault's token account, signed by the vault PDA.
+pub fn transfer_from_vault<'info>(
+    vault: &Account<'info, Vault>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &[&vault.signer_seeds()],
+        ),
+        amount,
+    )
+}
+
+/// Move `amount` lamports out of a program-owned account, which must stay
+/// rent-exempt, into `to`.
+pub fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let rent_floor = Rent::get()?.minimum_balance(from.data_len());
+    let (remaining, credited) = lamports::transfer(from.lamports(), to.lamports(), amount, rent_floor)
+        .map_err(|err| match err {
+            LamportError::Insufficient | LamportError::BelowRentFloor => EscrowError::InsufficientBalance,
+            LamportError::Overflow => EscrowError::MathOverflow,
+        })?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? = credited;
+    Ok(())
+}
+
+/// The vault PDA's lamports and what it must hold: the accounted balance
+/// plus its rent-exempt minimum. SOL sessions only.
+pub fn solvency(vault: &Account<Vault>) -> Result<(u64, u64)> {
+    let info = vault.to_account_info();
+    let rent_floor = Rent::get()?.minimum_balance(info.data_len());
+    let required = vault.balance.checked_add(rent_floor).ok_or(EscrowError::MathOverflow)?;
+    Ok((info.lamports(), required))
+}
+
+/// Debug builds (the tests) check a SOL vault still covers its balance after
+/// lamports move; `assert_solvent` is the on-chain check.
+pub fn debug_assert_solvent(vault: &Account<Vault>) {
+    if cfg!(debug_assertions) && vault.is_sol_session() {
+        if let Ok((lamports, required)) = solvency(vault) {
+            debug_assert!(lamports >= required, "vault holds {lamports} lamports, needs {required}");
+        }
+    }
+}
+
+/// Users who want a guardian-verified operator pass `bot`'s `BotProfile` as
+/// the first remaining account to initialize; without it, any bot goes.
+/// Returns the bot's profile if one was required.
+pub fn require_verified_bot(bot: &Pubkey, remaining_accounts: &[AccountInfo]) -> Result<Option<BotProfile>> {
+    let Some(info) = remaining_accounts.first() else {
+        return Ok(None);
+    };
+    require_keys_eq!(*info.owner, crate::ID, EscrowError::BotNotVerified);
+    let profile = BotProfile::try_deserialize(&mut &info.try_borrow_data()?[..])
+        .map_err(|_| EscrowError::BotNotVerified)?;
+    require!(profile.bot == *bot && profile.verified, EscrowError::BotNotVerified);
+    Ok(Some(profile))
+}
+
+/// The daily compute fee a Pending session is funded at. Token sessions pay
+/// `DAILY_COMPUTE_FEE_BPS` of `trading_balance`. SOL sessions opened requiring
+/// a verified bot pay the fee of the bot's tier as of funding, so they must
+/// pass its `BotProfile` (still verified); the rest pay the default.
+fn funded_compute_fee(
+    vault: &Vault,
+    config: &ProtocolConfig,
+    bot_profile: Option<&BotProfile>,
+    trading_balance: u64,
+) -> Result<u64> {
+    if !vault.is_sol_session() {
+        return math::bps_of(trading_balance, DAILY_COMPUTE_FEE_BPS);
+    }
+    if !vault.verified_bot {
+        return Ok(config.daily_compute_fee);
+    }
+    let profile = bot_profile.ok_or(EscrowError::BotNotVerified)?;
+    require!(profile.bot == vault.bot && profile.verified, EscrowError::BotNotVerified);
+    Ok(config.daily_compute_fee_for(profile.tier))
+}
+
+/// Pause an Active session whose bot the admin has blacklisted. Returns
+/// whether it did; callers then stop without failing, so the pause sticks.
+pub fn pause_if_blacklisted(vault: &mut Account<Vault>, config: &ProtocolConfig) -> bool {
+    if vault.status != VaultStatus::Active || !config.is_bot_blacklisted(&vault.bot) {
+        return false;
+    }
+    vault.status = VaultStatus::Paused;
+    emit!(SessionPaused {
+        session_id: vault.session_id,
+        vault: vault.key(),
+    });
+    emit!(BlacklistedBotPaused {
+        session_id: vault.session_id,
+        vault: vault.key(),
+        bot: vault.bot,
+    });
+    true
+}
+
+/// For instructions the session's bot signs: whether to stop without acting
+/// because the bot is blacklisted. An Active session is paused and the caller
+/// returns Ok so the pause sticks; once it isn't Active there's nothing to
+/// pause, so the bot is refused outright.
+pub fn stop_blacklisted_bot(vault: &mut Account<Vault>, config: &ProtocolConfig) -> Result<bool> {
+    if pause_if_blacklisted(vault, config) {
+        return Ok(true);
+    }
+    require!(!config.is_bot_blacklisted(&vault.bot), EscrowError::BotBlacklisted);
+    Ok(false)
+}
+
+/// Fill in a freshly created vault. Base currency is set by the caller, the
+/// compute fee at funding.
+pub fn open_session(
+    vault: &mut Vault,
+    user: Pubkey,
+    treasury: Pubkey,
+    session_id: [u8; 16],
+    duration_days: u16,
+    bot: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    vault.user = user;
+    vault.bot = bot;
+    vault.session_id = session_id;
+    vault.balance = 0;
+    vault.fee_collected = 0;
+    vault.compute_fees_paid = 0;
+    vault.duration_days = duration_days;
+    vault.status = VaultStatus::Pending;
+    vault.created_at = Clock::get()?.unix_timestamp;
+    vault.funded_at = 0;
+    vault.expires_at = 0;
+    vault.last_compute_deduction = 0;
+    vault.bump = bump;
+    vault.locked = false;
+    vault.perps_enabled = false;
+    vault.perps_collateral = 0;
+    vault.lending_account = Pubkey::default();
+    vault.lend_cap_bps = DEFAULT_LEND_CAP_BPS;
+    vault.lent_amount = 0;
+    vault.user_program = Pubkey::default();
+    vault.whitelist_version = 0;
+    vault.template = Pubkey::default();
+    vault.operator_fee_share_bps = 0;
+    vault.max_trade_lamports = 0;
+    vault.allowed_dexes = Vec::new();
+    vault.recovery = Pubkey::default();
+    vault.disabled_dexes = Vec::new();
+    vault.max_positions = 0;
+    vault.position_mints = Vec::new();
+    vault.max_exposure_bps = 0;
+    vault.slippage_budget = 0;
+    vault.slippage_consumed = 0;
+    vault.total_volume = 0;
+    vault.total_deposited = 0;
+    vault.total_withdrawn = 0;
+    vault.report_epoch = Clock::get()?.epoch;
+    vault.epoch_start = EpochSnapshot::default();
+    vault.max_slot_age = 0;
+    vault.require_jito_tip = false;
+    vault.lookup_table = Pubkey::default();
+    vault.resigned_at = 0;
+    vault.last_user_activity = vault.created_at;
+    vault.treasury = treasury;
+
+    Ok(())
+}
+
+/// Take the setup fee out of `amount` and move the rest onto a Pending SOL
+/// session, for `activate_session` to start. `funder` pays every leg. The fee
+/// is routed by `fee_router::route_fee`: for template sessions the operator's
+/// share accrues to the template. Returns (fee, trading balance).
+#[allow(clippy::too_many_arguments)]
+pub fn fund_session<'info>(
+    vault: &mut Account<'info, Vault>,
+    funder: AccountInfo<'info>,
+    treasury: AccountInfo<'info>,
+    template: Option<&mut Account<'info, SessionTemplate>>,
+    fee_router: AccountInfo<'info>,
+    system_program_info: AccountInfo<'info>,
+    fee_bps: u64,
+    amount: u64,
+) -> Result<(u64, u64)> {
+    let (fee, trading_balance) = math::split_fee(amount, fee_bps)?;
+
+    // Transfer trading balance from the funder to vault PDA
+    let vault_info = vault.to_account_info();
+    system_program::transfer(
+        CpiContext::new(
+            system_program_info.clone(),
+            system_program::Transfer {
+                from: funder.clone(),
+                to: vault_info,
+            },
+        ),
+        trading_balance,
+    )?;
+
+    let source = FeeSource::Signer { from: &funder, system_program: &system_program_info };
+    let operator_share = vault.operator_fee_share_bps;
+    route_fee(&source, fee, &treasury, template.map(|template| (template, operator_share)), &fee_router)?;
+
+    Ok((fee, trading_balance))
+}
+
+/// Activate a Pending session whose `trading_balance` already sits on the
+/// vault PDA (or its base token account, for token sessions), after `fee`
+/// was taken, starting its duration now. Every path that funds a session
+/// activates it here, so each refuses the same sessions: a bot that resigned
+/// or was blacklisted, a template session funded without its template
+/// (`with_template`), and deposits outside the config's limits for the
+/// session's currency. Snapshots the whitelist version and the compute fee.
+pub fn activate_session(
+    vault: &mut Vault,
+    config: &ProtocolConfig,
+    with_template: bool,
+    bot_profile: Option<&BotProfile>,
+    trading_balance: u64,
+    fee: u64,
+) -> Result<()> {
+    require!(vault.status == VaultStatus::Pending, EscrowError::InvalidStatus);
+    require!(vault.resigned_at == 0, EscrowError::BotResigned);
+    require!(!config.is_bot_blacklisted(&vault.bot), EscrowError::BotBlacklisted);
+    require!(
+        with_template || vault.template == Pubkey::default(),
+        EscrowError::InvalidTemplate
+    );
+    let amount = trading_balance.checked_add(fee).ok_or(EscrowError::MathOverflow)?;
+    let (floor, within_cap) = match vault.is_sol_session() {
+        true => (config.deposit_floor(), config.within_deposit_cap(trading_balance)),
+        false => (config.stable_deposit_floor(), config.within_stable_deposit_cap(trading_balance)),
+    };
+    require!(amount >= floor, EscrowError::DepositTooSmall);
+    require!(within_cap, EscrowError::DepositTooLarge);
+
+    let now = Clock::get()?.unix_timestamp;
+    vault.whitelist_version = config.whitelist_version;
+    vault.daily_compute_fee = funded_compute_fee(vault, config, bot_profile, trading_balance)?;
+    vault.balance = trading_balance;
+    vault.total_deposited = trading_balance;
+    vault.fee_collected = fee;
+    vault.status = VaultStatus::Active;
+    vault.funded_at = now;
+    vault.last_compute_deduction = now;
+    vault.points_accrued_until = now;
+    vault.last_user_activity = now;
+    vault.expires_at = math::add_days(now, vault.duration_days as u64)?;
+
+    Ok(())
+}
+
+/// The vault's template, passed as the first remaining account, if it has one.
+pub fn load_template<'info>(
+    vault: &Vault,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<Option<Account<'info, SessionTemplate>>> {
+    if vault.template == Pubkey::default() {
+        return Ok(None);
+    }
+    let info = remaining_accounts.first().ok_or(EscrowError::InvalidTemplate)?;
+    require_keys_eq!(info.key(), vault.template, EscrowError::InvalidTemplate);
+    Ok(Some(Account::try_from(info)?))
+}
+
+/// Credit `rewards` with duration points for the balance the session has held
+/// since it was last settled. Like compute fees, accrual stops at `expires_at`.
+pub fn settle_duration_points(vault: &mut Vault, rewards: &mut RewardsAccount, schedule: &RewardsSchedule, now: i64) {
+    let accrual_end = now.min(vault.expires_at);
+    if accrual_end <= vault.points_accrued_until {
+        return;
+    }
+    let points = schedule.duration_points(vault.balance, vault.points_accrued_until, accrual_end);
+    vault.points_accrued_until = accrual_end;
+    rewards.accrue(rewards.user, rewards.bump, points, 0, now);
+}
+
+/// Settle accrued compute fees and duration points and pay the rest of a SOL session's balance to
+/// `recipient`, closing the session out and counting it in the bot's stats. The bot's operator
+/// credit pays the compute fee first, as on the crank.
+/// Returns (amount paid, compute fee the vault paid).
+#[allow(clippy::too_many_arguments)]
+pub fn pay_out<'info>(
+    vault: &mut Account<'info, Vault>,
+    treasury: &UncheckedAccount<'info>,
+    fee_router: &UncheckedAccount<'info>,
+    operator_credit: &UncheckedAccount<'info>,
+    recipient: &AccountInfo<'info>,
+    bot_stats: &mut Account<'info, BotStats>,
+    bot_stats_bump: u8,
+    rewards: &mut Account<'info, RewardsAccount>,
+    schedule: &RewardsSchedule,
+) -> Result<(u64, u64)> {
+    // Settle accrued compute fees so withdrawing can't race the crank.
+    // Accrual is clamped to the session window, so this is safe after expiry too.
+    let now = Clock::get()?.unix_timestamp;
+    settle_duration_points(vault, rewards, schedule, now);
+    let (_, compute_fee) = settle_compute_fee(vault, operator_credit, treasury, fee_router, now)?;
+
+    // Transfer remaining SOL from the vault PDA
+    let balance = vault.balance;
+    move_lamports(&vault.to_account_info(), recipient, balance)?;
+
+    vault.balance = 0;
+    vault.status = VaultStatus::Withdrawn;
+    vault.total_withdrawn = vault.total_withdrawn
+        .checked_add(balance)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    bot_stats.record(vault, bot_stats_bump, now);
+    emit!(BotStatsUpdated {
+        bot: vault.bot,
+        session_id: vault.session_id,
+        vault: vault.key(),
+        session_pnl: session_pnl(vault),
+        sessions: bot_stats.sessions,
+        median_pnl_bps: bot_stats.median_pnl_bps,
+    });
+
+    Ok((balance, compute_fee))
+}
@@ -0,0 +1,48 @@
+//! Staking-based fee discounts.
+//!
+//! Users lock SOL in a `StakeAccount` PDA (`[b"stake", user]`). When a setup
+//! fee is calculated, the user's stake at that moment picks a discount tier.
+//! Stake can't be pulled for `LOCK_DAYS` after it was last topped up, so it
+//! can't be parked just for the length of one deposit.
+
+use anchor_lang::prelude::*;
+
+use crate::{math, StakeAccount};
+
+/// Days stake stays locked after the last top-up
+pub const LOCK_DAYS: u64 = 7;
+
+/// (minimum staked lamports, discount in bps of the fee), highest tier first
+const TIERS: [(u64, u64); 3] = [
+    (1_000 * math::LAMPORTS_PER_SOL, 5_000),
+    (100 * math::LAMPORTS_PER_SOL, 2_500),
+    (10 * math::LAMPORTS_PER_SOL, 1_000),
+];
+
+/// Discount, in bps of the fee, for a stake of `staked` lamports.
+pub fn discount_bps(staked: u64) -> u64 {
+    TIERS
+        .iter()
+        .find(|(minimum, _)| staked >= *minimum)
+        .map_or(0, |(_, discount)| *discount)
+}
+
+/// Staked lamports recorded in `stake`, or 0 if the user never staked.
+/// The caller has already checked the account's address.
+pub fn staked_amount(stake: &AccountInfo) -> Result<u64> {
+    if stake.owner != &crate::ID || stake.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(StakeAccount::try_deserialize(&mut &stake.try_borrow_data()?[..])?.amount)
+}
+
+/// `fee_bps` after the discount tier for `stake`.
+pub fn discounted_fee_bps(fee_bps: u64, stake: &AccountInfo) -> Result<u64> {
+    fee_bps_for_stake(fee_bps, staked_amount(stake)?)
+}
+
+/// `fee_bps` after the discount tier for a stake of `staked` lamports.
+pub fn fee_bps_for_stake(fee_bps: u64, staked: u64) -> Result<u64> {
+    let discount = discount_bps(staked);
+    math::mul_div(fee_bps, math::BPS_DENOMINATOR - discount, math::BPS_DENOMINATOR)
+}
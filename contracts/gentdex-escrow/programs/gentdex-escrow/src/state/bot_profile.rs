@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+/// The protocol guardian's attestation about a bot key. Users opt into
+/// requiring `verified` by passing it to initialize, and the session is then
+/// charged the compute fee of the bot's `tier` when it's funded.
+#[account]
+#[derive(InitSpace)]
+pub struct BotProfile {
+    pub bot: Pubkey,                // 32 — session key attested
+    pub verified: bool,             // 1  — operator identity checked
+    pub audited: bool,              // 1  — strategy code reviewed
+    pub attested_by: Pubkey,        // 32 — guardian at the latest attestation
+    pub attested_at: i64,           // 8  — unix timestamp of the latest attestation
+    pub bump: u8,                   // 1  — PDA bump seed
+    pub tier: u8,                   // 1  — compute fee tier set by the admin, 0 = the default fee
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+use super::Vault;
+
+/// Upper bounds, in bps of the funded balance, of the session return
+/// buckets; returns at or past the last bound land in the final bucket.
+pub const PNL_BUCKET_BOUNDS_BPS: [i32; 15] = [
+    -5_000, -2_500, -1_000, -500, -200, -100, 0, 100, 200, 500, 1_000, 2_500, 5_000, 10_000, 20_000,
+];
+pub const PNL_BUCKETS: usize = PNL_BUCKET_BOUNDS_BPS.len() + 1;
+
+/// Lifetime performance of one bot key across its settled SOL sessions,
+/// written by the program at every payout so leaderboards can read it
+/// without trusting the operator.
+#[account]
+#[derive(InitSpace)]
+pub struct BotStats {
+    pub bot: Pubkey,                // 32 — session key the stats are for
+    pub sessions: u64,              // 8  — settled sessions
+    pub profitable_sessions: u64,   // 8  — settled with more out than in
+    pub volume: u64,                // 8  — SOL spent on swaps
+    pub deposited: u64,             // 8  — trading balance funded in
+    pub pnl: i64,                   // 8  — paid out less funded in, summed (lamports)
+    pub pnl_histogram: [u32; PNL_BUCKETS], // 64 — session returns by PNL_BUCKET_BOUNDS_BPS bucket
+    pub median_pnl_bps: i32,        // 4  — upper bound of the median session's bucket
+    pub last_settled_at: i64,       // 8  — unix timestamp of the latest payout
+    pub bump: u8,                   // 1  — PDA bump seed
+}
+
+impl BotStats {
+    /// Count `vault`'s session, just paid out. Saturates rather than failing
+    /// the user's withdrawal.
+    pub fn record(&mut self, vault: &Vault, bump: u8, now: i64) {
+        self.bot = vault.bot;
+        self.bump = bump;
+        let pnl = session_pnl(vault);
+        self.sessions = self.sessions.saturating_add(1);
+        if pnl > 0 {
+            self.profitable_sessions = self.profitable_sessions.saturating_add(1);
+        }
+        self.volume = self.volume.saturating_add(vault.total_volume);
+        self.deposited = self.deposited.saturating_add(vault.total_deposited);
+        self.pnl = self.pnl.saturating_add(pnl);
+
+        let bucket = bucket(return_bps(pnl, vault.total_deposited));
+        self.pnl_histogram[bucket] = self.pnl_histogram[bucket].saturating_add(1);
+        self.median_pnl_bps = median_bps(&self.pnl_histogram);
+        self.last_settled_at = now;
+    }
+}
+
+/// Paid or transferred out less funded in, in lamports.
+pub fn session_pnl(vault: &Vault) -> i64 {
+    let pnl = vault.total_withdrawn as i128 - vault.total_deposited as i128;
+    pnl.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn return_bps(pnl: i64, deposited: u64) -> i64 {
+    if deposited == 0 {
+        return 0;
+    }
+    (pnl as i128 * 10_000 / deposited as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn bucket(return_bps: i64) -> usize {
+    PNL_BUCKET_BOUNDS_BPS
+        .iter()
+        .position(|&bound| return_bps < bound as i64)
+        .unwrap_or(PNL_BUCKETS - 1)
+}
+
+/// Upper bound of the bucket holding the median session; the last bound for
+/// the open-ended top bucket.
+fn median_bps(histogram: &[u32; PNL_BUCKETS]) -> i32 {
+    let total: u64 = histogram.iter().map(|&count| count as u64).sum();
+    let mut seen = 0;
+    for (index, &count) in histogram.iter().enumerate() {
+        seen += count as u64;
+        if seen * 2 >= total && total > 0 {
+            return PNL_BUCKET_BOUNDS_BPS[index.min(PNL_BUCKET_BOUNDS_BPS.len() - 1)];
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_returns_and_finds_the_median() {
+        assert_eq!(return_bps(-250, 1_000), -2_500);
+        assert_eq!(bucket(-2_500), 2);
+        assert_eq!(bucket(-2_501), 1);
+        assert_eq!(bucket(0), 7);
+        assert_eq!(bucket(1_000_000), PNL_BUCKETS - 1);
+
+        let mut histogram = [0; PNL_BUCKETS];
+        assert_eq!(median_bps(&histogram), 0);
+        histogram[bucket(-300)] += 1;
+        histogram[bucket(150)] += 2;
+        histogram[bucket(600)] += 1;
+        assert_eq!(median_bps(&histogram), 200);
+    }
+}
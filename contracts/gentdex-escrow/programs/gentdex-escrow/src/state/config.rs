@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{
+    MAX_BLACKLISTED_BOTS, MAX_BOT_TIERS, MAX_PRICE_FEEDS, MAX_WHITELISTED_DEXES, MIN_DEPOSIT,
+    MIN_STABLE_DEPOSIT,
+};
+use super::RewardsSchedule;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,              // 32 — single key, or a Realms governance account
+    pub pending_admin: Pubkey,      // 32 — proposed admin awaiting accept_admin
+    pub admin_is_governance: bool,  // 1  — admin is owned by SPL Governance
+    pub guardian: Pubkey,           // 32 — emergency role
+    pub treasury: Pubkey,           // 32 — fee recipient for new sessions
+    pub fee_bps: u16,               // 2  — setup fee on deposits
+    pub daily_compute_fee: u64,     // 8  — SOL sessions' daily compute fee (lamports)
+    pub whitelist_version: u32,     // 4  — bumped on every whitelist change
+    #[max_len(MAX_WHITELISTED_DEXES)]
+    pub whitelist: Vec<Pubkey>,     // 4 + 32 * MAX_WHITELISTED_DEXES — allowed DEX programs
+    #[max_len(MAX_WHITELISTED_DEXES)]
+    pub grandfathered: Vec<GrandfatheredDex>, // 4 + 36 * MAX_WHITELISTED_DEXES — removed, still allowed for older sessions
+    pub rewards: RewardsSchedule,   // 32 — reward points emission schedule
+    #[max_len(MAX_PRICE_FEEDS)]
+    pub price_feeds: Vec<MintPriceFeed>, // 4 + 65 * MAX_PRICE_FEEDS — Pyth feeds for valuation
+    #[max_len(MAX_BLACKLISTED_BOTS)]
+    pub blacklisted_bots: Vec<Pubkey>, // 4 + 32 * MAX_BLACKLISTED_BOTS — compromised or malicious bot keys
+    pub bump: u8,                   // 1  — PDA bump seed
+    pub min_deposit: u64,           // 8  — smallest SOL deposit, never below MIN_DEPOSIT
+    pub max_deposit: u64,           // 8  — most trading balance one SOL session may be funded with, 0 = no cap
+    #[max_len(MAX_BOT_TIERS)]
+    pub tier_compute_fees: Vec<u64>, // 4 + 8 * MAX_BOT_TIERS — SOL sessions' daily compute fee for bot tiers 1, 2, …
+    pub min_stable_deposit: u64,    // 8  — smallest stablecoin deposit, never below MIN_STABLE_DEPOSIT
+    pub max_stable_deposit: u64,    // 8  — most trading balance one stablecoin session may be funded with, 0 = no cap
+}
+
+impl ProtocolConfig {
+    /// The smallest SOL deposit accepted.
+    pub fn deposit_floor(&self) -> u64 {
+        self.min_deposit.max(MIN_DEPOSIT)
+    }
+
+    /// Whether a SOL session may have been funded with `total_deposited`,
+    /// counting transfers in.
+    pub fn within_deposit_cap(&self, total_deposited: u64) -> bool {
+        self.max_deposit == 0 || total_deposited <= self.max_deposit
+    }
+
+    /// The smallest stablecoin deposit accepted, in base units.
+    pub fn stable_deposit_floor(&self) -> u64 {
+        self.min_stable_deposit.max(MIN_STABLE_DEPOSIT)
+    }
+
+    /// Whether a stablecoin session may have been funded with `total_deposited`.
+    pub fn within_stable_deposit_cap(&self, total_deposited: u64) -> bool {
+        self.max_stable_deposit == 0 || total_deposited <= self.max_stable_deposit
+    }
+
+    /// Whether a session that snapshotted `whitelist_version` at funding may
+    /// swap through `program_id`: it's on the current whitelist, or it was
+    /// removed (not revoked) after the snapshot.
+    pub fn allows_dex(&self, program_id: &Pubkey, whitelist_version: u32) -> bool {
+        self.whitelist.contains(program_id)
+            || self.grandfathered.iter().any(|entry| {
+                entry.program_id == *program_id && entry.removed_in_version > whitelist_version
+            })
+    }
+
+    /// The daily compute fee a new SOL session pays for a bot in `tier`:
+    /// the default for tier 0 and for tiers without a fee of their own.
+    pub fn daily_compute_fee_for(&self, tier: u8) -> u64 {
+        let priced = (tier as usize).checked_sub(1).and_then(|index| self.tier_compute_fees.get(index));
+        priced.copied().unwrap_or(self.daily_compute_fee)
+    }
+
+    pub fn is_bot_blacklisted(&self, bot: &Pubkey) -> bool {
+        self.blacklisted_bots.contains(bot)
+    }
+
+    /// The registered price feed for `mint`, if any.
+    pub fn price_feed(&self, mint: &Pubkey) -> Option<&MintPriceFeed> {
+        self.price_feeds.iter().find(|feed| feed.mint == *mint)
+    }
+}
+
+/// Pyth feed used to value a mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct MintPriceFeed {
+    pub mint: Pubkey,               // 32 — token mint (native mint for SOL)
+    pub feed_id: [u8; 32],          // 32 — Pyth price feed id
+    pub decimals: u8,               // 1  — mint decimals
+}
+
+/// A DEX removed from the whitelist that sessions funded before
+/// `removed_in_version` may keep using.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct GrandfatheredDex {
+    pub program_id: Pubkey,         // 32 — removed DEX program
+    pub removed_in_version: u32,    // 4  — whitelist version the removal created
+}
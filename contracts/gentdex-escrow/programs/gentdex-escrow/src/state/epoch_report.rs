@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+/// A vault's running totals at an epoch boundary.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct EpochSnapshot {
+    pub volume: u64,                // 8  — total_volume
+    pub fees: u64,                  // 8  — setup + compute fees paid
+    pub value: u64,                 // 8  — balance + perps collateral + lent + token positions
+    pub deposited: u64,             // 8  — total_deposited
+    pub withdrawn: u64,             // 8  — total_withdrawn
+}
+
+/// Finalized per-epoch statement for one session, written by `close_epoch`.
+#[account]
+#[derive(InitSpace)]
+pub struct EpochReport {
+    pub vault: Pubkey,              // 32 — session reported on
+    pub epoch: u64,                 // 8  — Solana epoch covered (from its start, or session open)
+    pub volume: u64,                // 8  — SOL spent on swaps during the epoch
+    pub fees: u64,                  // 8  — setup + compute fees charged during the epoch
+    pub start_value: u64,           // 8  — value at the start of the epoch
+    pub end_value: u64,             // 8  — value when the report was closed
+    pub pnl: i64,                   // 8  — value change net of deposits and withdrawals
+    pub closed_at: i64,             // 8  — unix timestamp of close_epoch
+    pub bump: u8,                   // 1  — PDA bump seed
+}
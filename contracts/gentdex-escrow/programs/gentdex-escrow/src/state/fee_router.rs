@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_FEE_RECIPIENTS, MAX_OPERATOR_FEE_SHARE_BPS};
+use crate::errors::EscrowError;
+
+/// Who shares the protocol's SOL fees with the treasury (an insurance fund,
+/// a referral program, ...). Shares accrue on this PDA until claimed.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeRouter {
+    #[max_len(MAX_FEE_RECIPIENTS)]
+    pub recipients: Vec<FeeRecipient>, // 4 + 42 * MAX_FEE_RECIPIENTS — shares besides the treasury's
+    pub bump: u8,                   // 1  — PDA bump seed
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FeeRecipient {
+    pub recipient: Pubkey,          // 32 — wallet that claims the share
+    pub bps: u16,                   // 2  — share of every fee
+    pub accrued: u64,               // 8  — unclaimed lamports held by the router
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FeeShare {
+    pub recipient: Pubkey,
+    pub bps: u16,
+}
+
+impl FeeRouter {
+    /// Replace the recipients with `shares`, keeping what the remaining ones
+    /// have accrued. Recipients with unclaimed fees can't be dropped, and the
+    /// shares must leave room for the largest template operator share.
+    pub fn set_shares(&mut self, shares: Vec<FeeShare>) -> Result<()> {
+        require!(shares.len() <= MAX_FEE_RECIPIENTS, EscrowError::InvalidFeeRoute);
+        let total: u64 = shares.iter().map(|share| share.bps as u64).sum();
+        require!(
+            total + MAX_OPERATOR_FEE_SHARE_BPS as u64 <= 10_000,
+            EscrowError::InvalidFeeRoute
+        );
+        for (index, share) in shares.iter().enumerate() {
+            require!(
+                shares[..index].iter().all(|other| other.recipient != share.recipient),
+                EscrowError::InvalidFeeRoute
+            );
+        }
+        for old in &self.recipients {
+            require!(
+                old.accrued == 0 || shares.iter().any(|share| share.recipient == old.recipient),
+                EscrowError::UnclaimedFees
+            );
+        }
+
+        self.recipients = shares
+            .into_iter()
+            .map(|share| FeeRecipient {
+                accrued: self.accrued_by(&share.recipient),
+                recipient: share.recipient,
+                bps: share.bps,
+            })
+            .collect();
+        Ok(())
+    }
+
+    pub fn accrued_by(&self, recipient: &Pubkey) -> u64 {
+        self.recipients
+            .iter()
+            .find(|entry| entry.recipient == *recipient)
+            .map_or(0, |entry| entry.accrued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_accrued_fees_across_updates() {
+        let (insurance, referrals) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let share = |recipient, bps| FeeShare { recipient, bps };
+        let mut router = FeeRouter { recipients: Vec::new(), bump: 0 };
+        router.set_shares(vec![share(insurance, 1_000), share(referrals, 500)]).unwrap();
+        router.recipients[0].accrued = 7;
+
+        router.set_shares(vec![share(insurance, 2_000)]).unwrap();
+        assert_eq!(router.accrued_by(&insurance), 7);
+        assert!(router.set_shares(vec![share(referrals, 500)]).is_err());
+        assert!(router.set_shares(vec![share(insurance, 1), share(insurance, 1)]).is_err());
+        assert!(router.set_shares(vec![share(insurance, 5_001)]).is_err());
+    }
+}
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+/// A funded session one wallet has opened for another, held here until the
+/// recipient (the vault's `user`) accepts it. The PDA holds the lamports.
+#[account]
+#[derive(InitSpace)]
+pub struct SessionGift {
+    pub vault: Pubkey,              // 32 — Pending session the gift funds
+    pub giver: Pubkey,              // 32 — funder, can reclaim until accepted
+    pub amount: u64,                // 8  — gifted lamports, setup fee included (excludes rent)
+    pub created_at: i64,            // 8  — unix timestamp
+    pub bump: u8,                   // 1  — PDA bump seed
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_OPERATOR_FEE_SHARE_BPS;
+use crate::errors::EscrowError;
+
+/// Single-use terms a template's operator offers one user. Redeeming it opens
+/// a session from the template with these terms and closes the invite.
+#[account]
+#[derive(InitSpace)]
+pub struct SessionInvite {
+    pub template: Pubkey,           // 32 — template the session is opened from
+    pub invite_id: u64,             // 8  — operator-chosen id, part of the seeds
+    pub operator: Pubkey,           // 32 — template operator, gets the rent back
+    pub invitee: Pubkey,            // 32 — only user who may redeem, default = whoever holds the code
+    pub duration_days: u16,         // 2  — agreed session length
+    pub max_trade_lamports: u64,    // 8  — agreed per-swap limit, 0 = no limit
+    pub operator_fee_share_bps: u16,// 2  — agreed operator share of the setup fee
+    pub expires_at: i64,            // 8  — last moment it can be redeemed, 0 = never
+    pub bump: u8,                   // 1  — PDA bump seed
+}
+
+impl SessionInvite {
+    /// Can `user` redeem this invite at `now`?
+    pub fn redeemable_by(&self, user: &Pubkey, now: i64) -> bool {
+        (self.invitee == Pubkey::default() || self.invitee == *user)
+            && (self.expires_at == 0 || now <= self.expires_at)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InviteParams {
+    pub invitee: Pubkey,
+    pub duration_days: u16,
+    pub max_trade_lamports: u64,
+    pub operator_fee_share_bps: u16,
+    pub expires_at: i64,
+}
+
+impl InviteParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.operator_fee_share_bps <= MAX_OPERATOR_FEE_SHARE_BPS,
+            EscrowError::InvalidInvite
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_invitee_redeems_before_expiry() {
+        let user = Pubkey::new_unique();
+        let mut invite = SessionInvite {
+            template: Pubkey::new_unique(),
+            invite_id: 0,
+            operator: Pubkey::new_unique(),
+            invitee: Pubkey::default(),
+            duration_days: 7,
+            max_trade_lamports: 0,
+            operator_fee_share_bps: 0,
+            expires_at: 0,
+            bump: 0,
+        };
+        assert!(invite.redeemable_by(&user, i64::MAX));
+
+        invite.invitee = user;
+        invite.expires_at = 100;
+        assert!(invite.redeemable_by(&user, 100));
+        assert!(!invite.redeemable_by(&user, 101));
+        assert!(!invite.redeemable_by(&Pubkey::new_unique(), 0));
+    }
+}
@@ -0,0 +1,35 @@
+//! On-chain account and argument types.
+
+mod bot_profile;
+mod bot_stats;
+mod config;
+mod epoch_report;
+mod fee_router;
+mod gift;
+mod invite;
+mod operator_credit;
+mod registry;
+mod rewards;
+mod stake;
+mod summary;
+mod template;
+mod trade_batch;
+mod upgrade_info;
+mod vault;
+
+pub use bot_profile::*;
+pub use bot_stats::*;
+pub use config::*;
+pub use epoch_report::*;
+pub use fee_router::*;
+pub use gift::*;
+pub use invite::*;
+pub use operator_credit::*;
+pub use registry::*;
+pub use rewards::*;
+pub use stake::*;
+pub use summary::*;
+pub use template::*;
+pub use trade_batch::*;
+pub use upgrade_info::*;
+pub use vault::*;
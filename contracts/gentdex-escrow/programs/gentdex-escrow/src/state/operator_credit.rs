@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// SOL a bot's operator has prepaid to cover its sessions' compute fees.
+/// The PDA holds the lamports; the crank draws on it before the vault.
+#[account]
+#[derive(InitSpace)]
+pub struct OperatorCredit {
+    pub bot: Pubkey,                // 32 — session key whose sessions are covered
+    pub balance: u64,               // 8  — prepaid lamports left (excludes rent)
+    pub fees_covered: u64,          // 8  — compute fees paid on users' behalf so far
+    pub bump: u8,                   // 1  — PDA bump seed
+}
+
+impl OperatorCredit {
+    /// Take up to `fee` from the credit, returning how much it covers.
+    pub fn cover(&mut self, fee: u64) -> u64 {
+        let covered = fee.min(self.balance);
+        self.balance -= covered;
+        self.fees_covered = self.fees_covered.saturating_add(covered);
+        covered
+    }
+}
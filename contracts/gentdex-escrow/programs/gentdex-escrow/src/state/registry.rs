@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+/// Per-user counter behind `initialize_indexed`. Indexed sessions are
+/// numbered from 0, so vaults 0..session_count can be derived without
+/// storing session ids.
+#[account]
+#[derive(InitSpace)]
+pub struct UserRegistry {
+    pub user: Pubkey,               // 32 — owner
+    pub session_count: u64,         // 8  — indexed sessions opened so far
+    pub bump: u8,                   // 1  — PDA bump seed
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::math;
+
+/// Reward points emission schedule. Points are only emitted inside
+/// `[starts_at, ends_at)`; the default schedule emits nothing.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct RewardsSchedule {
+    pub volume_points_per_sol: u64,       // 8 — per SOL swapped
+    pub duration_points_per_sol_day: u64, // 8 — per SOL of balance, per day it stays funded
+    pub starts_at: i64,                   // 8 — unix timestamp
+    pub ends_at: i64,                     // 8 — unix timestamp
+}
+
+impl RewardsSchedule {
+    fn is_live(&self, now: i64) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    /// Points for swapping `lamports` of volume.
+    pub fn volume_points(&self, lamports: u64, now: i64) -> u64 {
+        if !self.is_live(now) {
+            return 0;
+        }
+        math::mul_div(lamports, self.volume_points_per_sol, math::LAMPORTS_PER_SOL).unwrap_or(u64::MAX)
+    }
+
+    /// Points for holding `balance` in a session over `[from, to)`, pro rata by
+    /// the second. Only the part inside the emission window counts.
+    pub fn duration_points(&self, balance: u64, from: i64, to: i64) -> u64 {
+        let start = from.max(self.starts_at);
+        let end = to.min(self.ends_at);
+        if end <= start {
+            return 0;
+        }
+        let per_day = math::mul_div(balance, self.duration_points_per_sol_day, math::LAMPORTS_PER_SOL)
+            .unwrap_or(u64::MAX);
+        math::mul_div(per_day, (end - start) as u64, math::SECONDS_PER_DAY as u64).unwrap_or(u64::MAX)
+    }
+}
+
+/// Per-user reward points, updated by swaps and as sessions' funded time is
+/// settled, across all sessions.
+#[account]
+#[derive(InitSpace)]
+pub struct RewardsAccount {
+    pub user: Pubkey,               // 32 — session owner
+    pub points: u64,                // 8  — lifetime points
+    pub volume: u64,                // 8  — lifetime swap volume (lamports)
+    pub last_updated: i64,          // 8  — unix timestamp of the last accrual
+    pub bump: u8,                   // 1  — PDA bump seed
+}
+
+impl RewardsAccount {
+    /// Fill in the owner of a possibly new account, accruing nothing.
+    pub fn register(&mut self, user: Pubkey, bump: u8) {
+        self.user = user;
+        self.bump = bump;
+    }
+
+    /// Add points and volume. Saturates rather than failing the user's instruction.
+    pub fn accrue(&mut self, user: Pubkey, bump: u8, points: u64, volume: u64, now: i64) {
+        self.user = user;
+        self.bump = bump;
+        self.points = self.points.saturating_add(points);
+        self.volume = self.volume.saturating_add(volume);
+        self.last_updated = now;
+    }
+}
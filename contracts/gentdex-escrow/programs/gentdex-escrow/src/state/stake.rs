@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+/// SOL a user has locked for a fee discount tier. The PDA holds the lamports.
+#[account]
+#[derive(InitSpace)]
+pub struct StakeAccount {
+    pub user: Pubkey,               // 32 — staker
+    pub amount: u64,                // 8  — staked lamports (excludes rent)
+    pub locked_until: i64,          // 8  — unstake allowed from this timestamp
+    pub bump: u8,                   // 1  — PDA bump seed
+}
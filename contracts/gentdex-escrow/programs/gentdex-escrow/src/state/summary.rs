@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use super::{SwapRejectReason, VaultStatus};
+
+/// Return data of `get_accrued_fees`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct AccruedFees {
+    pub days: u64,                  // whole days not yet charged
+    pub compute_fee: u64,           // fee for them, capped at the balance
+}
+
+/// Return data of `get_session_summary`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SessionSummary {
+    pub status: VaultStatus,
+    pub balance: u64,               // trading balance, before accrued fees
+    pub value: u64,                 // balance + perps collateral + lent
+    pub accrued_compute_fee: u64,   // owed to the next crank
+    pub seconds_remaining: i64,     // until expiry; 0 if unfunded or expired
+    pub drawdown: u64,              // net deposits not covered by value, 0 if in profit
+}
+
+/// Return data of the `execute_swap*` instructions, for composing programs
+/// and simulations. Swaps carry no protocol fee; what a fill costs the
+/// session beyond the route's price shows up as `slippage`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SwapResult {
+    pub rejected: Option<SwapRejectReason>, // why policy refused the swap; None if it executed
+    pub spent: u64,                 // lamports the route consumed
+    pub output_mint: Pubkey,
+    pub amount_out: u64,
+    pub slippage: u64,              // charged to the slippage budget, 0 without one
+    pub fee: u64,                   // compute fee the swap settled from the vault, after operator credit
+    pub balance: u64,               // trading balance afterwards
+}
+
+impl SwapResult {
+    pub fn rejected(reason: SwapRejectReason, fee: u64, balance: u64) -> Self {
+        Self {
+            rejected: Some(reason),
+            spent: 0,
+            output_mint: Pubkey::default(),
+            amount_out: 0,
+            slippage: 0,
+            fee,
+            balance,
+        }
+    }
+}
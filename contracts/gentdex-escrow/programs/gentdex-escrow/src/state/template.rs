@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_OPERATOR_FEE_SHARE_BPS, MAX_TEMPLATE_DEXES};
+use crate::errors::EscrowError;
+
+/// Session defaults published by a bot operator.
+#[account]
+#[derive(InitSpace)]
+pub struct SessionTemplate {
+    pub operator: Pubkey,           // 32 — publisher, can update and claim fees
+    pub template_id: u64,           // 8  — operator-chosen id, part of the seeds
+    pub bot: Pubkey,                // 32 — session key used by sessions from this template
+    pub duration_days: u16,         // 2  — session length
+    pub max_trade_lamports: u64,    // 8  — per-swap limit, 0 = no limit
+    pub operator_fee_share_bps: u16,// 2  — operator's share of the setup fee
+    #[max_len(MAX_TEMPLATE_DEXES)]
+    pub allowed_dexes: Vec<Pubkey>, // 4 + 32 * MAX_TEMPLATE_DEXES — empty = whole whitelist
+    pub fees_accrued: u64,          // 8  — unclaimed operator fees held by this PDA
+    pub bump: u8,                   // 1  — PDA bump seed
+    pub invite_only: bool,          // 1  — sessions need one of the operator's invites
+}
+
+impl SessionTemplate {
+    pub fn apply(&mut self, params: TemplateParams) {
+        self.bot = params.bot;
+        self.duration_days = params.duration_days;
+        self.max_trade_lamports = params.max_trade_lamports;
+        self.operator_fee_share_bps = params.operator_fee_share_bps;
+        self.allowed_dexes = params.allowed_dexes;
+        self.invite_only = params.invite_only;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TemplateParams {
+    pub bot: Pubkey,
+    pub duration_days: u16,
+    pub max_trade_lamports: u64,
+    pub operator_fee_share_bps: u16,
+    pub allowed_dexes: Vec<Pubkey>,
+    pub invite_only: bool,
+}
+
+impl TemplateParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.operator_fee_share_bps <= MAX_OPERATOR_FEE_SHARE_BPS,
+            EscrowError::InvalidTemplate
+        );
+        require!(self.allowed_dexes.len() <= MAX_TEMPLATE_DEXES, EscrowError::InvalidTemplate);
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_BATCHED_TRADES;
+use crate::events::SwapExecuted;
+
+/// Swaps a session has made since its last `TradesBatched` event, for
+/// sessions that opted out of one `SwapExecuted` per trade.
+#[account]
+#[derive(InitSpace)]
+pub struct TradeBatch {
+    pub vault: Pubkey,              // 32 — session the trades belong to
+    pub batch_size: u8,             // 1  — trades per event, at most MAX_BATCHED_TRADES
+    pub window_slots: u64,          // 8  — also emit once the oldest trade is this many slots old, 0 = off
+    pub opened_slot: u64,           // 8  — slot of the oldest buffered trade
+    pub sequence: u64,              // 8  — batches emitted so far
+    #[max_len(MAX_BATCHED_TRADES)]
+    pub trades: Vec<BatchedTrade>,  // 4 + 128 * MAX_BATCHED_TRADES — buffered, oldest first
+    pub bump: u8,                   // 1  — PDA bump seed
+}
+
+/// One swap, as its `SwapExecuted` would have reported it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub struct BatchedTrade {
+    pub timestamp: i64,             // 8
+    pub dex_program: Pubkey,        // 32
+    pub amount_in: u64,             // 8
+    pub minimum_amount_out: u64,    // 8
+    pub output_mint: Pubkey,        // 32
+    pub amount_out: u64,            // 8
+    pub memo: [u8; 32],             // 32
+}
+
+impl From<&SwapExecuted> for BatchedTrade {
+    fn from(swap: &SwapExecuted) -> Self {
+        Self {
+            timestamp: swap.timestamp,
+            dex_program: swap.dex_program,
+            amount_in: swap.amount_in,
+            minimum_amount_out: swap.minimum_amount_out,
+            output_mint: swap.output_mint,
+            amount_out: swap.amount_out,
+            memo: swap.memo,
+        }
+    }
+}
+
+impl TradeBatch {
+    /// Buffer `trade`, made at `slot`. Returns whether the batch is now due.
+    pub fn push(&mut self, trade: BatchedTrade, slot: u64) -> bool {
+        if self.trades.is_empty() {
+            self.opened_slot = slot;
+        }
+        self.trades.push(trade);
+        self.trades.len() >= self.batch_size.max(1) as usize
+            || self.trades.len() >= MAX_BATCHED_TRADES
+            || (self.window_slots > 0 && slot.saturating_sub(self.opened_slot) >= self.window_slots)
+    }
+
+    /// Empty the buffer, returning its trades and their batch number.
+    pub fn take(&mut self) -> (u64, Vec<BatchedTrade>) {
+        let sequence = self.sequence;
+        self.sequence = self.sequence.saturating_add(1);
+        (sequence, std::mem::take(&mut self.trades))
+    }
+}
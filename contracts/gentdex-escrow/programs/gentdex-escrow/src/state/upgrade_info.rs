@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+/// The program's governance posture, for integrators to check on-chain.
+/// Authority and deploy slot are read from the program's ProgramData; the
+/// timelock and build hash are the admin's commitments.
+#[account]
+#[derive(InitSpace)]
+pub struct UpgradeInfo {
+    pub upgrade_authority: Pubkey,  // 32 — current upgrade authority, default = immutable
+    pub deployed_slot: u64,         // 8  — slot of the latest deploy
+    pub timelock_seconds: u64,      // 8  — committed notice before any upgrade
+    pub build_hash: [u8; 32],       // 32 — verifiable build hash of the deployed program
+    pub build_hash_slot: u64,       // 8  — deployed_slot the hash was committed for
+    pub updated_at: i64,            // 8  — unix timestamp of the latest update or refresh
+    pub bump: u8,                   // 1  — PDA bump seed
+}
+
+impl UpgradeInfo {
+    /// Refresh what ProgramData says.
+    pub fn observe(&mut self, program_data: &ProgramData, now: i64) {
+        self.upgrade_authority = program_data.upgrade_authority_address.unwrap_or_default();
+        self.deployed_slot = program_data.slot;
+        self.updated_at = now;
+    }
+
+    /// Whether the committed build hash is for the program deployed now. False
+    /// after an upgrade until the admin commits the new build's hash.
+    pub fn build_hash_current(&self) -> bool {
+        self.build_hash_slot == self.deployed_slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_hash_goes_stale_on_upgrade() {
+        let authority = Pubkey::new_unique();
+        let mut info = UpgradeInfo {
+            upgrade_authority: Pubkey::default(),
+            deployed_slot: 0,
+            timelock_seconds: 0,
+            build_hash: [0; 32],
+            build_hash_slot: 0,
+            updated_at: 0,
+            bump: 0,
+        };
+        info.observe(&ProgramData { slot: 10, upgrade_authority_address: Some(authority) }, 5);
+        info.build_hash_slot = info.deployed_slot;
+        assert!(info.build_hash_current());
+
+        info.observe(&ProgramData { slot: 20, upgrade_authority_address: None }, 6);
+        assert!(!info.build_hash_current());
+        assert_eq!(info.upgrade_authority, Pubkey::default());
+    }
+}
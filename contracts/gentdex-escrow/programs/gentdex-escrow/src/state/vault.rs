@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::constants::{MAX_DISABLED_DEXES, MAX_POSITIONS, MAX_TEMPLATE_DEXES, WITHDRAWAL_REQUEST_WINDOW_DAYS};
+use crate::errors::EscrowError;
+use crate::math::SECONDS_PER_DAY;
+use super::EpochSnapshot;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vault {
+    pub user: Pubkey,               // 32 — owner, can always withdraw
+    pub bot: Pubkey,                // 32 — session key, can only swap
+    pub treasury: Pubkey,           // 32 — fee recipient
+    pub session_id: [u8; 16],       // 16 — unique session identifier
+    pub balance: u64,               // 8  — current trading balance (lamports)
+    pub fee_collected: u64,         // 8  — setup fee taken
+    pub compute_fees_paid: u64,     // 8  — total compute fees deducted
+    pub duration_days: u16,         // 2  — session length
+    pub status: VaultStatus,        // 1  — current state
+    pub bump: u8,                   // 1  — PDA bump seed
+    pub locked: bool,               // 1  — reentrancy guard, set during DEX CPI
+    pub created_at: i64,            // 8  — unix timestamp
+    pub funded_at: i64,             // 8  — when deposit landed
+    pub expires_at: i64,            // 8  — when session ends
+    pub last_compute_deduction: i64,// 8  — last daily fee timestamp
+    pub perps_enabled: bool,        // 1  — Drift sub-account opened
+    pub perps_collateral: u64,      // 8  — SOL principal deposited into Drift
+    pub lending_account: Pubkey,    // 32 — marginfi account, default if lending is off
+    pub lend_cap_bps: u16,          // 2  — max share of balance + lent that may be lent
+    pub lent_amount: u64,           // 8  — SOL principal lent out, must be 0 to withdraw
+    pub base_mint: Pubkey,          // 32 — session currency, native mint for SOL sessions
+    pub daily_compute_fee: u64,     // 8  — compute fee per day, in base-mint units
+    pub user_program: Pubkey,       // 32 — program whose PDA is the user, default for wallets
+    pub whitelist_version: u32,     // 4  — config whitelist version snapshotted at funding
+    pub template: Pubkey,           // 32 — template the session was created from, if any
+    pub operator_fee_share_bps: u16,// 2  — operator's share of the setup fee (template sessions)
+    pub max_trade_lamports: u64,    // 8  — per-swap amount_in limit, 0 = no limit
+    #[max_len(MAX_TEMPLATE_DEXES)]
+    pub allowed_dexes: Vec<Pubkey>, // 4 + 32 * MAX_TEMPLATE_DEXES — subset of the whitelist, empty = all
+    pub recovery: Pubkey,           // 32 — may withdraw to `user` after long inactivity, default = none
+    pub last_user_activity: i64,    // 8  — last user-signed instruction, starts the recovery clock
+    #[max_len(MAX_DISABLED_DEXES)]
+    pub disabled_dexes: Vec<Pubkey>,// 4 + 32 * MAX_DISABLED_DEXES — DEXes the user turned off
+    pub max_positions: u8,          // 1  — user cap on distinct open mints, 0 = MAX_POSITIONS
+    #[max_len(MAX_POSITIONS)]
+    pub position_mints: Vec<Pubkey>,// 4 + 32 * MAX_POSITIONS — mints the vault currently holds
+    pub max_exposure_bps: u16,      // 2  — max share of portfolio value in one token, 0 = no cap
+    pub slippage_budget: u64,       // 8  — max cumulative slippage (lamports), 0 = no budget
+    pub slippage_consumed: u64,     // 8  — SOL spent beyond oracle value of swap output
+    pub total_volume: u64,          // 8  — lifetime SOL spent on swaps
+    pub total_deposited: u64,       // 8  — lifetime trading balance funded in
+    pub total_withdrawn: u64,       // 8  — lifetime balance paid or transferred out
+    pub report_epoch: u64,          // 8  — Solana epoch of the next EpochReport
+    pub epoch_start: EpochSnapshot, // 40 — running totals when `report_epoch` opened
+    pub max_slot_age: u64,          // 8  — swaps must land this close to the quote slot, 0 = off
+    pub require_jito_tip: bool,     // 1  — swaps must be in a Jito-tipped transaction
+    pub lookup_table: Pubkey,       // 32 — vault-owned address lookup table, default if none
+    pub resigned_at: i64,           // 8  — when the bot gave notice, 0 = still servicing
+    pub trade_nonce: u64,           // 8  — highest nonce a bot swap has used, 0 = none yet
+    pub batch_trades: bool,         // 1  — swaps go to the session's TradeBatch, not one event each
+    pub withdrawal_notice: i64,     // 8  — seconds a withdrawal must be requested ahead, 0 = withdraw any time
+    pub withdraw_requested_at: i64,// 8  — when the user last requested a withdrawal, 0 = never
+    pub guardian_paused_at: i64,    // 8  — when the guardian paused the session, 0 = not guardian-paused
+    pub pause_reason: PauseReason,  // 1  — why the guardian paused it
+    pub points_accrued_until: i64,  // 8  — funded time up to which duration points were settled
+    pub funder: Pubkey,             // 32 — DLN authority or bridge sender allowed to fund it, default = none
+    pub verified_bot: bool,         // 1  — opened requiring the bot's BotProfile, whose tier prices it at funding
+}
+
+impl Vault {
+    /// Record `mint` as an open position, failing if it would exceed the cap.
+    /// The default pubkey (nothing received) is ignored.
+    pub fn track_position(&mut self, mint: Pubkey) -> Result<()> {
+        if mint == Pubkey::default() || self.position_mints.contains(&mint) {
+            return Ok(());
+        }
+        let cap = match self.max_positions {
+            0 => MAX_POSITIONS,
+            max => max as usize,
+        };
+        require!(self.position_mints.len() < cap, EscrowError::PositionLimitReached);
+        self.position_mints.push(mint);
+        Ok(())
+    }
+
+    /// Current running totals, for epoch reporting. Value is the accounted
+    /// balance plus SOL in perps and lending; token positions need prices, so
+    /// `close_epoch` adds them.
+    pub fn snapshot(&self) -> Result<EpochSnapshot> {
+        let value = self.balance
+            .checked_add(self.perps_collateral)
+            .and_then(|v| v.checked_add(self.lent_amount))
+            .ok_or(EscrowError::MathOverflow)?;
+        Ok(EpochSnapshot {
+            volume: self.total_volume,
+            fees: self.fee_collected
+                .checked_add(self.compute_fees_paid)
+                .ok_or(EscrowError::MathOverflow)?,
+            value,
+            deposited: self.total_deposited,
+            withdrawn: self.total_withdrawn,
+        })
+    }
+
+    /// Spend a bot's trade nonce, which must exceed every one used before.
+    pub fn use_trade_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(nonce > self.trade_nonce, EscrowError::StaleTradeNonce);
+        self.trade_nonce = nonce;
+        Ok(())
+    }
+
+    /// Restart the recovery inactivity clock. Call from user-signed instructions.
+    pub fn record_user_activity(&mut self) -> Result<()> {
+        self.last_user_activity = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Whether the user's self-imposed withdrawal notice lets funds leave at
+    /// `now`: there's none, the session has ended, or a request has served
+    /// its notice within the last WITHDRAWAL_REQUEST_WINDOW_DAYS.
+    pub fn withdrawal_notice_served(&self, now: i64) -> bool {
+        let ended = matches!(self.status, VaultStatus::Expired | VaultStatus::Withdrawn)
+            || (self.expires_at > 0 && now >= self.expires_at);
+        let ready_at = self.withdraw_requested_at.saturating_add(self.withdrawal_notice);
+        let window = WITHDRAWAL_REQUEST_WINDOW_DAYS as i64 * SECONDS_PER_DAY;
+        self.withdrawal_notice == 0
+            || ended
+            || (self.withdraw_requested_at > 0 && now >= ready_at && now < ready_at.saturating_add(window))
+    }
+
+    /// Whether the session is denominated in SOL (held as lamports on the PDA).
+    pub fn is_sol_session(&self) -> bool {
+        self.base_mint == native_mint::ID
+    }
+
+    /// PDA signer seeds for CPIs signed by the vault.
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            b"vault",
+            self.session_id.as_ref(),
+            self.user.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum VaultStatus {
+    Pending,    // Created, awaiting deposit
+    Active,     // Funded, bot is trading
+    Paused,     // User (or the guardian, see `guardian_paused_at`) paused trading
+    Expired,    // Duration ended or balance depleted
+    Withdrawn,  // User withdrew all funds
+}
+
+/// Why the guardian paused a session (see `GuardianPaused`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum PauseReason {
+    None,                // not guardian-paused
+    ActiveExploit,       // the session is being drained or manipulated right now
+    BotCompromised,      // the bot's key or operator is believed compromised
+    DexCompromised,      // a DEX or oracle the session trades through is under attack
+    Other,               // see the guardian's off-chain notice
+}
+
+/// Why a swap was rejected by policy (see `SwapRejected`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapRejectReason {
+    InsufficientBalance, // amount_in exceeds trading balance
+    TradeLimitExceeded,  // amount_in above the session's per-trade limit
+    DexDisabled,         // user turned this DEX off for the session
+    SlippageBudgetExhausted, // session's cumulative slippage reached the user's budget
+    Unprotected,         // session requires swap protection the transaction didn't show
+    BotBlacklisted,      // admin blacklisted the bot; the session was paused
+    BotResigned,         // bot gave notice it's leaving the session
+}
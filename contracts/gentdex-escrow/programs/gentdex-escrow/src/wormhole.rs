@@ -0,0 +1,133 @@
+//! Deposits bridged in through the Wormhole Token Bridge.
+//!
+//! A user on another chain sends a transfer-with-payload whose recipient is
+//! the vault's token account. Once the guardians have signed it and the VAA
+//! is posted to the core bridge, anyone can redeem it through
+//! `deposit_from_bridge`: the program completes the transfer with the vault
+//! PDA signing as redeemer — so only transfers addressed to this vault can
+//! land — and credits whatever arrived. The Token Bridge verifies the VAA
+//! and claims it, so it can't be redeemed twice. Instructions are encoded by
+//! hand, as the DEX adapters do.
+//!
+//! The Token Bridge's own accounts are passed as remaining accounts, in its
+//! order: payer, config, posted VAA, claim, emitter registration, recipient
+//! token account, redeemer, fee token account, then the mint-specific and
+//! program accounts for the native or wrapped variant.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+use crate::errors::EscrowError;
+
+pub const CORE_BRIDGE_PROGRAM_ID: Pubkey = pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+pub const TOKEN_BRIDGE_PROGRAM_ID: Pubkey = pubkey!("wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb");
+
+/// `CompleteNativeWithPayload` / `CompleteWrappedWithPayload` tags
+const COMPLETE_NATIVE_WITH_PAYLOAD: u8 = 9;
+const COMPLETE_WRAPPED_WITH_PAYLOAD: u8 = 10;
+
+/// Positions in the Token Bridge's account list
+const POSTED_VAA_INDEX: usize = 2;
+const RECIPIENT_INDEX: usize = 5;
+const REDEEMER_INDEX: usize = 6;
+
+/// `PostedVAAData` layout: "vaa", version, consistency level, VAA time,
+/// signature set, submission time, nonce, then these
+const SEQUENCE_OFFSET: usize = 49;
+const EMITTER_CHAIN_OFFSET: usize = 57;
+const PAYLOAD_OFFSET: usize = 95;
+/// `TransferWithPayload` layout: id, amount, token, token chain, recipient,
+/// recipient chain, then the sender
+const TRANSFER_WITH_PAYLOAD: u8 = 3;
+const SENDER_OFFSET: usize = 101;
+
+/// Where a bridged deposit came from.
+pub struct BridgeTransfer {
+    pub emitter_chain: u16,
+    pub sequence: u64,
+    /// The sending address on the source chain, left-padded to 32 bytes
+    pub sender: [u8; 32],
+}
+
+/// Read a posted transfer-with-payload VAA. `posted_vaa` must be owned by the core bridge.
+pub fn read_transfer(posted_vaa: &AccountInfo) -> Result<BridgeTransfer> {
+    parse_transfer(&posted_vaa.try_borrow_data()?).ok_or_else(|| error!(EscrowError::InvalidBridgeTransfer))
+}
+
+fn parse_transfer(data: &[u8]) -> Option<BridgeTransfer> {
+    let payload = data.get(PAYLOAD_OFFSET..)?;
+    let valid = data.starts_with(b"vaa")
+        && payload.first() == Some(&TRANSFER_WITH_PAYLOAD)
+        && payload.len() >= SENDER_OFFSET + 32;
+    if !valid {
+        return None;
+    }
+
+    let read = |at: usize, length: usize| &data[at..at + length];
+    Some(BridgeTransfer {
+        emitter_chain: u16::from_le_bytes(read(EMITTER_CHAIN_OFFSET, 2).try_into().unwrap()),
+        sequence: u64::from_le_bytes(read(SEQUENCE_OFFSET, 8).try_into().unwrap()),
+        sender: payload[SENDER_OFFSET..SENDER_OFFSET + 32].try_into().unwrap(),
+    })
+}
+
+/// Complete the transfer in `posted_vaa` into `recipient`, the vault signing
+/// as redeemer. `accounts` are the Token Bridge's, in its order.
+pub fn complete_transfer<'info>(
+    token_bridge: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    posted_vaa: &Pubkey,
+    recipient: &Pubkey,
+    vault: &Pubkey,
+    vault_seeds: &[&[u8]],
+    wrapped: bool,
+) -> Result<()> {
+    require_keys_eq!(token_bridge.key(), TOKEN_BRIDGE_PROGRAM_ID, EscrowError::InvalidBridgeTransfer);
+    let at = |index: usize| accounts.get(index).map(|account| account.key());
+    let in_place = at(POSTED_VAA_INDEX) == Some(*posted_vaa)
+        && at(RECIPIENT_INDEX) == Some(*recipient)
+        && at(REDEEMER_INDEX) == Some(*vault);
+    require!(in_place, EscrowError::InvalidBridgeTransfer);
+
+    let metas = accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key == vault,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let tag = if wrapped { COMPLETE_WRAPPED_WITH_PAYLOAD } else { COMPLETE_NATIVE_WITH_PAYLOAD };
+    let ix = Instruction { program_id: TOKEN_BRIDGE_PROGRAM_ID, accounts: metas, data: vec![tag] };
+
+    let mut infos = accounts.to_vec();
+    infos.push(token_bridge.clone());
+    invoke_signed(&ix, &infos, &[vault_seeds])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_transfers_with_payload_only() {
+        let mut data = b"vaa".to_vec();
+        data.resize(PAYLOAD_OFFSET, 0);
+        data[SEQUENCE_OFFSET..SEQUENCE_OFFSET + 8].copy_from_slice(&42u64.to_le_bytes());
+        data[EMITTER_CHAIN_OFFSET..EMITTER_CHAIN_OFFSET + 2].copy_from_slice(&2u16.to_le_bytes());
+        let mut payload = vec![TRANSFER_WITH_PAYLOAD];
+        payload.resize(SENDER_OFFSET, 0);
+        payload.extend_from_slice(&[7; 32]);
+        data.extend_from_slice(&payload);
+
+        let transfer = parse_transfer(&data).unwrap();
+        assert_eq!((transfer.emitter_chain, transfer.sequence, transfer.sender), (2, 42, [7; 32]));
+
+        assert!(parse_transfer(&data[..PAYLOAD_OFFSET + 40]).is_none());
+        // A plain transfer (no payload) can't name a redeemer, so isn't accepted
+        data[PAYLOAD_OFFSET] = 1;
+        assert!(parse_transfer(&data).is_none());
+    }
+}
@@ -3,21 +3,31 @@ import { Program } from "@coral-xyz/anchor";
 import { GentdexEscrow } from "../target/types/gentdex_escrow";
 import { v4 as uuidv4 } from "uuid";
 
+const DEVNET_PROGRAM_ID = "D3cViTirWf3zkEcgoLkKEgXr73biwwaih4WxZUR4N2j5";
+
 async function main() {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
-  const program = anchor.workspace.GentdexEscrow as Program<GentdexEscrow>;
+  // Devnet runs its own program ID (the `devnet` build); the IDL carries mainnet's
+  const workspace = anchor.workspace.GentdexEscrow as Program<GentdexEscrow>;
+  const program = new Program<GentdexEscrow>({ ...workspace.idl, address: DEVNET_PROGRAM_ID }, provider);
   const user = provider.wallet;
   const bot = anchor.web3.Keypair.generate();
-  const treasury = anchor.web3.Keypair.generate();
+
+  // Sessions must pay the protocol treasury recorded in the config
+  const [configPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  const { treasury } = await program.account.protocolConfig.fetch(configPda);
 
   console.log("\n🦞 GentDex Escrow — Devnet Integration Test");
   console.log("=".repeat(50));
   console.log(`Program:  ${program.programId.toBase58()}`);
   console.log(`User:     ${user.publicKey.toBase58()}`);
   console.log(`Bot:      ${bot.publicKey.toBase58()}`);
-  console.log(`Treasury: ${treasury.publicKey.toBase58()}`);
+  console.log(`Treasury: ${treasury.toBase58()}`);
 
   // Generate session ID
   const uuid = uuidv4().replace(/-/g, "");
@@ -35,7 +45,7 @@ async function main() {
     new anchor.web3.Transaction().add(
       anchor.web3.SystemProgram.transfer({
         fromPubkey: user.publicKey,
-        toPubkey: treasury.publicKey,
+        toPubkey: treasury,
         lamports: 0.01 * anchor.web3.LAMPORTS_PER_SOL,
       })
     )
@@ -48,7 +58,7 @@ async function main() {
     .initialize(sessionId, 7, bot.publicKey)
     .accountsPartial({
       user: user.publicKey,
-      treasury: treasury.publicKey,
+      treasury: treasury,
     })
     .rpc();
   console.log(`   ✅ TX: ${tx1}`);
@@ -64,7 +74,7 @@ async function main() {
     .accountsPartial({
       vault: vaultPda,
       user: user.publicKey,
-      treasury: treasury.publicKey,
+      treasury: treasury,
     })
     .rpc();
   console.log(`   ✅ TX: ${tx2}`);
@@ -98,7 +108,11 @@ async function main() {
   const userBefore = await provider.connection.getBalance(user.publicKey);
   const tx5 = await program.methods
     .withdraw()
-    .accountsPartial({ vault: vaultPda, user: user.publicKey })
+    .accountsPartial({
+      vault: vaultPda,
+      user: user.publicKey,
+      treasury: treasury,
+    })
     .rpc();
   const userAfter = await provider.connection.getBalance(user.publicKey);
   console.log(`   ✅ TX: ${tx5}`);
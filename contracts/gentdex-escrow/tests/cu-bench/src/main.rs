@@ -0,0 +1,150 @@
+//! `gentdex-cu-bench`: compute units used by each escrow instruction.
+//!
+//! Runs every instruction once under LiteSVM and prints `{"name": units}` as
+//! JSON. `execute_swap` is measured with routes of several sizes, up to the
+//! policy checks: the DEX CPI itself depends on the venue and isn't counted.
+//!
+//! With `--baseline`, compares against an earlier run and exits non-zero if
+//! any instruction got more expensive than `--tolerance` allows, so CI can
+//! keep the baseline file checked in and catch regressions.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anchor_lang::prelude::Pubkey;
+use clap::Parser;
+use gentdex_client::instructions::{self, Swap};
+use gentdex_escrow_tests::{Harness, LAMPORTS_PER_SOL, SECONDS_PER_DAY};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+/// Remaining-account counts to measure swaps with. Jupiter routes are
+/// typically 20-30 accounts; a legacy transaction fits a few more.
+const ROUTE_SIZES: [usize; 4] = [0, 8, 16, 24];
+
+#[derive(Parser)]
+#[command(name = "gentdex-cu-bench", about = "Measure GentDex instruction compute units")]
+struct Args {
+    /// Earlier output to compare against
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+    /// Allowed increase over the baseline, in percent
+    #[arg(long, default_value_t = 2.0)]
+    tolerance: f64,
+    /// Write results here instead of stdout
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+}
+
+struct Bench {
+    harness: Harness,
+    units: BTreeMap<String, u64>,
+}
+
+impl Bench {
+    /// Send `ix` and record what it used under `name`.
+    fn measure(&mut self, name: &str, ix: Instruction, signers: &[&Keypair]) {
+        let meta = self
+            .harness
+            .send(&[ix], signers)
+            .unwrap_or_else(|failed| panic!("{name} failed: {:?}\n{:#?}", failed.err, failed.meta.logs));
+        self.units.insert(name.to_string(), meta.compute_units_consumed);
+    }
+}
+
+fn swap(vault: Pubkey, user: &Keypair, treasury: Pubkey, bot: &Keypair, route_size: usize) -> Instruction {
+    instructions::execute_swap(&Swap {
+        vault,
+        user: user.pubkey(),
+        treasury,
+        bot: bot.pubkey(),
+        // Off the whitelist, so the swap is rejected before the CPI
+        dex_program: Pubkey::new_unique(),
+        amount_in: LAMPORTS_PER_SOL / 10,
+        minimum_amount_out: 1,
+        memo: None,
+        recent_slot: None,
+        jito_tip: false,
+        nonce: None,
+        dry_run: false,
+        batched: false,
+        output_token_account: None,
+        price_feeds: None,
+        base_token_account: None,
+        route: (0..route_size).map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false)).collect(),
+        route_data: None,
+    })
+}
+
+fn run() -> BTreeMap<String, u64> {
+    let mut bench = Bench {
+        harness: Harness::new(),
+        units: BTreeMap::new(),
+    };
+    let user = bench.harness.wallet(10);
+    let bot = bench.harness.wallet(1);
+    let treasury = bench.harness.treasury;
+
+    let session_id = bench.harness.session_id();
+    let (ix, vault) = instructions::initialize(user.pubkey(), treasury, session_id, 7, bot.pubkey(), user.pubkey());
+    bench.measure("initialize", ix, &[&user]);
+    let ix = instructions::deposit(user.pubkey(), vault, treasury, LAMPORTS_PER_SOL, None, None);
+    bench.measure("deposit", ix, &[&user]);
+
+    for route_size in ROUTE_SIZES {
+        let ix = swap(vault, &user, treasury, &bot, route_size);
+        bench.measure(&format!("execute_swap/route_{route_size}"), ix, &[&bot]);
+    }
+
+    bench.harness.warp(SECONDS_PER_DAY);
+    let ix = instructions::deduct_compute_fee(bot.pubkey(), vault, user.pubkey(), treasury, bot.pubkey());
+    bench.measure("deduct_compute_fee", ix, &[&bot]);
+    bench.measure("pause", instructions::pause(user.pubkey(), vault), &[&user]);
+    bench.measure("resume", instructions::resume(user.pubkey(), vault), &[&user]);
+
+    bench.harness.warp(7 * SECONDS_PER_DAY);
+    bench.measure("expire", instructions::expire(bot.pubkey(), vault), &[&bot]);
+    let ix = instructions::withdraw(user.pubkey(), vault, treasury, bot.pubkey(), user.pubkey());
+    bench.measure("withdraw", ix, &[&user]);
+
+    bench.units
+}
+
+/// Instructions now above the baseline by more than `tolerance` percent.
+fn regressions(baseline: &BTreeMap<String, u64>, units: &BTreeMap<String, u64>, tolerance: f64) -> Vec<String> {
+    units
+        .iter()
+        .filter_map(|(name, &now)| {
+            let &before = baseline.get(name)?;
+            let limit = before as f64 * (1.0 + tolerance / 100.0);
+            (now as f64 > limit).then(|| format!("{name}: {before} -> {now} CU"))
+        })
+        .collect()
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let units = run();
+
+    let json = serde_json::to_string_pretty(&units).expect("serializing results");
+    match &args.output {
+        Some(path) => std::fs::write(path, json + "\n").unwrap_or_else(|err| panic!("writing {}: {err}", path.display())),
+        None => println!("{json}"),
+    }
+
+    let Some(path) = &args.baseline else {
+        return ExitCode::SUCCESS;
+    };
+    let text = std::fs::read_to_string(path).unwrap_or_else(|err| panic!("reading {}: {err}", path.display()));
+    let baseline: BTreeMap<String, u64> = serde_json::from_str(&text).expect("baseline is a {name: units} object");
+    let regressed = regressions(&baseline, &units, args.tolerance);
+    if regressed.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+    for line in regressed {
+        eprintln!("regressed {line}");
+    }
+    ExitCode::FAILURE
+}
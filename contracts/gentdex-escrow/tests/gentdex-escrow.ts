@@ -25,15 +25,86 @@ describe("gentdex-escrow", () => {
     );
   }
 
+  async function parseEvents(signature: string) {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(tx.meta.logMessages)];
+  }
+
   let sessionId: number[];
   let vaultPda: anchor.web3.PublicKey;
 
+  const [configPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
   before(async () => {
     const sig = await provider.connection.requestAirdrop(
       treasury.publicKey,
       anchor.web3.LAMPORTS_PER_SOL
     );
     await provider.connection.confirmTransaction(sig);
+
+    // The test validator deploys with the provider wallet as upgrade authority
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    await program.methods
+      .initializeConfig(user.publicKey, treasury.publicKey)
+      .accounts({
+        config: configPda,
+        admin: user.publicKey,
+        program: program.programId,
+        programData,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("Admin can edit the DEX whitelist; others cannot", async () => {
+    const dex = anchor.web3.Keypair.generate().publicKey;
+    const before = await program.account.protocolConfig.fetch(configPda);
+
+    await program.methods
+      .setDexWhitelisted(dex, true)
+      .accounts({ config: configPda, admin: user.publicKey })
+      .rpc();
+    let config = await program.account.protocolConfig.fetch(configPda);
+    assert.ok(config.whitelist.some((key) => key.equals(dex)));
+    assert.equal(config.whitelistVersion, before.whitelistVersion + 1);
+
+    await program.methods
+      .setDexWhitelisted(dex, false)
+      .accounts({ config: configPda, admin: user.publicKey })
+      .rpc();
+    config = await program.account.protocolConfig.fetch(configPda);
+    assert.notOk(config.whitelist.some((key) => key.equals(dex)));
+    // Removal is grandfathered for sessions funded before it
+    const entry = config.grandfathered.find((e) => e.programId.equals(dex));
+    assert.equal(entry.removedInVersion, config.whitelistVersion);
+
+    await program.methods
+      .revokeDex(dex)
+      .accounts({ config: configPda, admin: user.publicKey })
+      .rpc();
+    config = await program.account.protocolConfig.fetch(configPda);
+    assert.notOk(config.grandfathered.some((e) => e.programId.equals(dex)));
+
+    try {
+      await program.methods
+        .setDexWhitelisted(dex, true)
+        .accounts({ config: configPda, admin: bot.publicKey })
+        .signers([bot])
+        .rpc();
+      assert.fail("Non-admin should not edit the whitelist");
+    } catch (err) {
+      assert.include(err.toString(), "Unauthorized");
+    }
   });
 
   it("Initializes a session", async () => {
@@ -45,6 +116,7 @@ describe("gentdex-escrow", () => {
       .accounts({
         vault: vaultPda,
         user: user.publicKey,
+        payer: user.publicKey,
         treasury: treasury.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -56,6 +128,9 @@ describe("gentdex-escrow", () => {
     assert.equal(vault.durationDays, 7);
     assert.deepEqual(vault.status, { pending: {} });
     assert.equal(vault.balance.toNumber(), 0);
+    // SOL sessions are denominated in the native mint with a fixed daily fee
+    assert.equal(vault.baseMint.toBase58(), "So11111111111111111111111111111111111111112");
+    assert.equal(vault.dailyComputeFee.toNumber(), 10_000_000);
   });
 
   it("Rejects deposit below minimum (0.1 SOL)", async () => {
@@ -131,6 +206,8 @@ describe("gentdex-escrow", () => {
         .accounts({
           vault: vaultPda,
           user: bot.publicKey,
+          treasury: treasury.publicKey,
+          payer: bot.publicKey,
         })
         .signers([bot])
         .rpc();
@@ -147,11 +224,13 @@ describe("gentdex-escrow", () => {
       await program.methods
         .executeSwap(
           new anchor.BN(100_000_000),
-          new anchor.BN(90_000_000)
+          new anchor.BN(90_000_000),
+          false
         )
         .accounts({
           vault: vaultPda,
           bot: bot.publicKey,
+          treasury: treasury.publicKey,
           dexProgram: fakeDex.publicKey,
         })
         .signers([bot])
@@ -162,6 +241,92 @@ describe("gentdex-escrow", () => {
     }
   });
 
+  it("Rejects swaps on a DEX the user turned off", async () => {
+    const jupiterV6 = new anchor.web3.PublicKey(
+      "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"
+    );
+    await program.methods
+      .setDexEnabled(jupiterV6, false)
+      .accounts({ vault: vaultPda, user: user.publicKey })
+      .rpc();
+
+    const sig = await program.methods
+      .executeSwap(new anchor.BN(100_000_000), new anchor.BN(90_000_000), false)
+      .accounts({ vault: vaultPda, bot: bot.publicKey, treasury: treasury.publicKey, dexProgram: jupiterV6 })
+      .signers([bot])
+      .rpc({ commitment: "confirmed" });
+
+    const events = await parseEvents(sig);
+    const rejected = events.find((e) => e.name === "swapRejected");
+    assert.deepEqual(rejected.data.reason, { dexDisabled: {} });
+
+    await program.methods
+      .setDexEnabled(jupiterV6, true)
+      .accounts({ vault: vaultPda, user: user.publicKey })
+      .rpc();
+    const vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.disabledDexes.length, 0);
+  });
+
+  it("Carries the bot's memo into swap events", async () => {
+    const jupiterV6 = new anchor.web3.PublicKey(
+      "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"
+    );
+    const memo = Array.from(Buffer.from("signal-42".padEnd(32, "\0")));
+
+    // More than the vault holds, so the swap is rejected with the memo attached
+    const sig = await program.methods
+      .executeSwapWithMemo(new anchor.BN(1_000_000_000_000), new anchor.BN(90_000_000), memo)
+      .accounts({ vault: vaultPda, bot: bot.publicKey, treasury: treasury.publicKey, dexProgram: jupiterV6 })
+      .signers([bot])
+      .rpc({ commitment: "confirmed" });
+
+    const events = await parseEvents(sig);
+    const rejected = events.find((e) => e.name === "swapRejected");
+    assert.deepEqual(rejected.data.memo, memo);
+  });
+
+  it("Rejects unprotected swaps when the session requires protection", async () => {
+    const jupiterV6 = new anchor.web3.PublicKey(
+      "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"
+    );
+    await program.methods
+      .setSwapProtection(new anchor.BN(4), false)
+      .accounts({ vault: vaultPda, user: user.publicKey })
+      .rpc();
+
+    const sig = await program.methods
+      .executeSwap(new anchor.BN(100_000_000), new anchor.BN(90_000_000), false)
+      .accounts({ vault: vaultPda, bot: bot.publicKey, treasury: treasury.publicKey, dexProgram: jupiterV6 })
+      .signers([bot])
+      .rpc({ commitment: "confirmed" });
+    const events = await parseEvents(sig);
+    const rejected = events.find((e) => e.name === "swapRejected");
+    assert.deepEqual(rejected.data.reason, { unprotected: {} });
+
+    await program.methods
+      .setSwapProtection(new anchor.BN(0), false)
+      .accounts({ vault: vaultPda, user: user.publicKey })
+      .rpc();
+  });
+
+  it("Reads a session summary without changing state", async () => {
+    const summary = await program.methods
+      .getSessionSummary()
+      .accounts({ vault: vaultPda })
+      .view();
+    const vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(summary.balance.toString(), vault.balance.toString());
+    assert.isAbove(summary.secondsRemaining.toNumber(), 0);
+
+    const fees = await program.methods
+      .getAccruedFees()
+      .accounts({ vault: vaultPda })
+      .view();
+    assert.equal(fees.days.toNumber(), 0);
+    assert.equal(fees.computeFee.toNumber(), 0);
+  });
+
   it("Rejects swap from non-bot signer", async () => {
     const jupiterV6 = new anchor.web3.PublicKey(
       "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"
@@ -171,11 +336,13 @@ describe("gentdex-escrow", () => {
       await program.methods
         .executeSwap(
           new anchor.BN(100_000_000),
-          new anchor.BN(90_000_000)
+          new anchor.BN(90_000_000),
+          false
         )
         .accounts({
           vault: vaultPda,
           bot: user.publicKey,
+          treasury: treasury.publicKey,
           dexProgram: jupiterV6,
         })
         .rpc();
@@ -211,6 +378,33 @@ describe("gentdex-escrow", () => {
     assert.deepEqual(vault.status, { active: {} });
   });
 
+  it("User can set the lend cap, bounded at 100%", async () => {
+    await program.methods
+      .setLendCap(2_500)
+      .accounts({
+        vault: vaultPda,
+        user: user.publicKey,
+      })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.lendCapBps, 2_500);
+    assert.equal(vault.lentAmount.toNumber(), 0);
+
+    try {
+      await program.methods
+        .setLendCap(10_001)
+        .accounts({
+          vault: vaultPda,
+          user: user.publicKey,
+        })
+        .rpc();
+      assert.fail("Should reject a lend cap above 10000 bps");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidLendCap");
+    }
+  });
+
   it("Rejects compute fee deduction before 1 day", async () => {
     try {
       await program.methods
@@ -227,6 +421,69 @@ describe("gentdex-escrow", () => {
     }
   });
 
+  it("Does not accrue compute fees past expiry", async () => {
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+
+    // 0 day duration: the accrual window closes the moment it's funded
+    await program.methods
+      .initialize(sid, 0, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // Crank at the expiry boundary: nothing accrued inside the window
+    try {
+      await program.methods
+        .deductComputeFee()
+        .accounts({
+          vault: pda,
+          treasury: treasury.publicKey,
+          cranker: user.publicKey,
+        })
+        .rpc();
+      assert.fail("Should not charge for time after expiry");
+    } catch (err) {
+      assert.include(err.toString(), "TooEarlyForDeduction");
+    }
+
+    // Withdraw settles nothing either — the full balance comes back
+    const before = await program.account.vault.fetch(pda);
+    const treasuryBefore = await provider.connection.getBalance(treasury.publicKey);
+    await program.methods
+      .withdraw()
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+        payer: user.publicKey,
+      })
+      .rpc();
+
+    const after = await program.account.vault.fetch(pda);
+    const treasuryAfter = await provider.connection.getBalance(treasury.publicKey);
+    assert.equal(treasuryAfter, treasuryBefore);
+    assert.equal(after.computeFeesPaid.toNumber(), 0);
+    assert.equal(
+      after.lastComputeDeduction.toNumber(),
+      before.lastComputeDeduction.toNumber()
+    );
+  });
+
   it("User can withdraw all funds (emergency exit)", async () => {
     const vaultBefore = await program.account.vault.fetch(vaultPda);
     const balanceBefore = vaultBefore.balance.toNumber();
@@ -237,6 +494,8 @@ describe("gentdex-escrow", () => {
       .accounts({
         vault: vaultPda,
         user: user.publicKey,
+        treasury: treasury.publicKey,
+        payer: user.publicKey,
       })
       .rpc();
 
@@ -262,6 +521,7 @@ describe("gentdex-escrow", () => {
       .accounts({
         vault: vault2Pda,
         user: user.publicKey,
+        payer: user.publicKey,
         treasury: treasury.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -300,6 +560,8 @@ describe("gentdex-escrow", () => {
       .accounts({
         vault: vault2Pda,
         user: user.publicKey,
+        treasury: treasury.publicKey,
+        payer: user.publicKey,
       })
       .rpc();
 
@@ -309,4 +571,523 @@ describe("gentdex-escrow", () => {
 
     console.log("    ✓ Full lifecycle completed: Pending → Active → Expired → Withdrawn");
   });
+
+  it("Transfers remaining balance into another session without a second setup fee", async () => {
+    const sourceId = makeSessionId();
+    const destId = makeSessionId();
+    const [sourcePda] = getVaultPda(sourceId, user.publicKey);
+    const [destPda] = getVaultPda(destId, user.publicKey);
+
+    for (const [sid, pda] of [[sourceId, sourcePda], [destId, destPda]] as const) {
+      await program.methods
+        .initialize(sid, 7, bot.publicKey)
+        .accounts({
+          vault: pda,
+          user: user.publicKey,
+          payer: user.publicKey,
+          treasury: treasury.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({
+        vault: sourcePda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    const { balance } = await program.account.vault.fetch(sourcePda);
+
+    await program.methods
+      .transferToSession()
+      .accounts({
+        sourceVault: sourcePda,
+        destinationVault: destPda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+      })
+      .rpc();
+
+    const source = await program.account.vault.fetch(sourcePda);
+    assert.equal(source.balance.toNumber(), 0);
+    assert.deepEqual(source.status, { withdrawn: {} });
+
+    const dest = await program.account.vault.fetch(destPda);
+    assert.deepEqual(dest.status, { active: {} });
+    assert.equal(dest.balance.toNumber(), balance.toNumber());
+    assert.equal(dest.feeCollected.toNumber(), 0);
+  });
+
+  it("Pays no duration reward points up front on deposit", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .setRewardsSchedule({
+        volumePointsPerSol: new anchor.BN(100),
+        durationPointsPerSolDay: new anchor.BN(10),
+        startsAt: new anchor.BN(now - 3600),
+        endsAt: new anchor.BN(now + 3600),
+      })
+      .accounts({ config: configPda, admin: user.publicKey })
+      .rpc();
+
+    const [rewardsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rewards"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const before = await program.account.rewardsAccount.fetch(rewardsPda);
+
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initialize(sid, 7, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // Duration points accrue as the session stays funded, settled by the
+    // crank and withdrawals, so none are paid at deposit
+    const after = await program.account.rewardsAccount.fetch(rewardsPda);
+    assert.equal(after.points.toString(), before.points.toString());
+    const vault = await program.account.vault.fetch(pda);
+    assert.equal(vault.pointsAccruedUntil.toString(), vault.fundedAt.toString());
+  });
+
+  it("Discounts the setup fee by stake tier and locks the stake", async () => {
+    await program.methods
+      .stake(new anchor.BN(10 * anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ user: user.publicKey, payer: user.publicKey })
+      .rpc();
+
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initialize(sid, 7, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // 10 SOL staked: 10% off the 2.5% fee
+    const vault = await program.account.vault.fetch(pda);
+    assert.equal(vault.feeCollected.toNumber(), 22_500_000);
+
+    try {
+      await program.methods
+        .unstake(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accounts({ user: user.publicKey })
+        .rpc();
+      assert.fail("Stake should still be locked");
+    } catch (err) {
+      assert.include(err.toString(), "StakeLocked");
+    }
+  });
+
+  it("Rejects program-user initialization outside CPI", async () => {
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    try {
+      await program.methods
+        .initializeForProgram(sid, 7, bot.publicKey, anchor.web3.SystemProgram.programId, [])
+        .accounts({
+          vault: pda,
+          user: user.publicKey,
+          payer: user.publicKey,
+          treasury: treasury.publicKey,
+        })
+        .rpc();
+      assert.fail("Top-level initializeForProgram should fail");
+    } catch (err) {
+      assert.include(err.toString(), "CpiOnly");
+    }
+  });
+
+  it("Initializes sessions from an operator template", async () => {
+    const operator = bot;
+    const templateId = new anchor.BN(1);
+    const [templatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("template"), operator.publicKey.toBuffer(), templateId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .createTemplate(templateId, {
+        bot: bot.publicKey,
+        durationDays: 14,
+        maxTradeLamports: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10),
+        operatorFeeShareBps: 2_000,
+        allowedDexes: [],
+        inviteOnly: false,
+      })
+      .accounts({ template: templatePda, operator: operator.publicKey })
+      .signers([operator])
+      .rpc();
+
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initializeFromTemplate(sid)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        template: templatePda,
+        treasury: treasury.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([{ pubkey: templatePda, isSigner: false, isWritable: true }])
+      .rpc();
+
+    const vault = await program.account.vault.fetch(pda);
+    assert.equal(vault.durationDays, 14);
+    assert.ok(vault.template.equals(templatePda));
+    // 20% of the (stake-discounted) setup fee goes to the operator
+    const template = await program.account.sessionTemplate.fetch(templatePda);
+    assert.equal(template.feesAccrued.toNumber(), vault.feeCollected.toNumber() / 5);
+
+    // Later template updates don't touch the active session
+    await program.methods
+      .updateTemplate({ ...template, durationDays: 30 })
+      .accounts({ template: templatePda, operator: operator.publicKey })
+      .signers([operator])
+      .rpc();
+    assert.equal((await program.account.vault.fetch(pda)).durationDays, 14);
+  });
+
+  it("Opens invite-only template sessions from single-use invites", async () => {
+    const operator = bot;
+    const templateId = new anchor.BN(2);
+    const [templatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("template"), operator.publicKey.toBuffer(), templateId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .createTemplate(templateId, {
+        bot: bot.publicKey,
+        durationDays: 14,
+        maxTradeLamports: new anchor.BN(0),
+        operatorFeeShareBps: 2_000,
+        allowedDexes: [],
+        inviteOnly: true,
+      })
+      .accounts({ template: templatePda, operator: operator.publicKey })
+      .signers([operator])
+      .rpc();
+
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    try {
+      await program.methods
+        .initializeFromTemplate(sid)
+        .accounts({
+          vault: pda,
+          user: user.publicKey,
+          payer: user.publicKey,
+          template: templatePda,
+          treasury: treasury.publicKey,
+        })
+        .rpc();
+      assert.fail("Invite-only template should need an invite");
+    } catch (err) {
+      assert.include(err.toString(), "InviteRequired");
+    }
+
+    const inviteId = new anchor.BN(1);
+    const [invitePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("invite"), templatePda.toBuffer(), inviteId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .createInvite(inviteId, {
+        invitee: user.publicKey,
+        durationDays: 30,
+        maxTradeLamports: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10),
+        operatorFeeShareBps: 1_000,
+        expiresAt: new anchor.BN(0),
+      })
+      .accounts({ template: templatePda, invite: invitePda, operator: operator.publicKey })
+      .signers([operator])
+      .rpc();
+    await program.methods
+      .initializeFromInvite(sid)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        template: templatePda,
+        invite: invitePda,
+        operator: operator.publicKey,
+        treasury: treasury.publicKey,
+      })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(pda);
+    assert.equal(vault.durationDays, 30);
+    assert.equal(vault.operatorFeeShareBps, 1_000);
+    assert.ok(vault.template.equals(templatePda));
+    // Redeemed invites are closed, so they can't open a second session
+    assert.isNull(await program.account.sessionInvite.fetchNullable(invitePda));
+  });
+
+  it("Blocks the recovery key while the user is active", async () => {
+    const recovery = anchor.web3.Keypair.generate();
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initialize(sid, 7, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .setRecovery(recovery.publicKey)
+      .accounts({ vault: pda, user: user.publicKey })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(pda);
+    assert.ok(vault.recovery.equals(recovery.publicKey));
+
+    try {
+      await program.methods
+        .recover()
+        .accounts({
+          vault: pda,
+          recovery: recovery.publicKey,
+          user: user.publicKey,
+          treasury: treasury.publicKey,
+        })
+        .signers([recovery])
+        .rpc();
+      assert.fail("Recovery should wait out the inactivity window");
+    } catch (err) {
+      assert.include(err.toString(), "UserStillActive");
+    }
+  });
+
+  it("Caps the position limit a user can set", async () => {
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initialize(sid, 7, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .setMaxPositions(3)
+      .accounts({ vault: pda, user: user.publicKey })
+      .rpc();
+    assert.equal((await program.account.vault.fetch(pda)).maxPositions, 3);
+
+    try {
+      await program.methods
+        .setMaxPositions(17)
+        .accounts({ vault: pda, user: user.publicKey })
+        .rpc();
+      assert.fail("Limit above MAX_POSITIONS should fail");
+    } catch (err) {
+      assert.include(err.toString(), "PositionLimitReached");
+    }
+  });
+
+  it("Registers price feeds and sets an exposure cap", async () => {
+    const NATIVE_MINT = new anchor.web3.PublicKey("So11111111111111111111111111111111111111112");
+    const solFeedId = Array.from(Buffer.alloc(32, 1));
+    await program.methods
+      .setPriceFeed(NATIVE_MINT, solFeedId, 9)
+      .accounts({ config: configPda, admin: user.publicKey })
+      .rpc();
+    const config = await program.account.protocolConfig.fetch(configPda);
+    const feed = config.priceFeeds.find((f) => f.mint.equals(NATIVE_MINT));
+    assert.deepEqual(feed.feedId, solFeedId);
+
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initialize(sid, 7, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .setMaxExposure(2_500)
+      .accounts({ vault: pda, user: user.publicKey })
+      .rpc();
+    assert.equal((await program.account.vault.fetch(pda)).maxExposureBps, 2_500);
+  });
+
+  it("Tracks a user-set slippage budget", async () => {
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initialize(sid, 7, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .setSlippageBudget(new anchor.BN(50_000_000))
+      .accounts({ vault: pda, user: user.publicKey })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(pda);
+    assert.equal(vault.slippageBudget.toNumber(), 50_000_000);
+    assert.equal(vault.slippageConsumed.toNumber(), 0);
+  });
+
+  it("Creates and extends a vault-owned lookup table", async () => {
+    const sid = makeSessionId();
+    const [pda] = getVaultPda(sid, user.publicKey);
+    await program.methods
+      .initialize(sid, 7, bot.publicKey)
+      .accounts({
+        vault: pda,
+        user: user.publicKey,
+        payer: user.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const recentSlot = await provider.connection.getSlot();
+    const [lookupTable] = anchor.web3.PublicKey.findProgramAddressSync(
+      [pda.toBuffer(), new anchor.BN(recentSlot).toArrayLike(Buffer, "le", 8)],
+      anchor.web3.AddressLookupTableProgram.programId
+    );
+    await program.methods
+      .createLookupTable(new anchor.BN(recentSlot))
+      .accounts({
+        vault: pda,
+        authority: user.publicKey,
+        config: configPda,
+        lookupTable,
+        addressLookupTableProgram: anchor.web3.AddressLookupTableProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .extendLookupTable([pda, treasury.publicKey])
+      .accounts({
+        vault: pda,
+        authority: bot.publicKey,
+        config: configPda,
+        lookupTable,
+        addressLookupTableProgram: anchor.web3.AddressLookupTableProgram.programId,
+      })
+      .signers([bot])
+      .rpc();
+
+    const vault = await program.account.vault.fetch(pda);
+    assert.ok(vault.lookupTable.equals(lookupTable));
+    const table = await provider.connection.getAddressLookupTable(lookupTable);
+    assert.ok(table.value.state.authority.equals(pda));
+    assert.equal(table.value.state.addresses.length, 2);
+
+    // The first close deactivates it; closing it waits out the cooldown
+    await program.methods
+      .closeLookupTable()
+      .accounts({
+        vault: pda,
+        authority: user.publicKey,
+        config: configPda,
+        lookupTable,
+        addressLookupTableProgram: anchor.web3.AddressLookupTableProgram.programId,
+      })
+      .rpc();
+    const deactivated = await provider.connection.getAddressLookupTable(lookupTable);
+    assert.ok(!deactivated.value.isActive());
+  });
+
+  it("Opens indexed sessions at derivable addresses", async () => {
+    const [registryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const indexedSessionId = (index: number) => {
+      const id = Buffer.alloc(16);
+      id.writeBigUInt64LE(BigInt(index));
+      return Array.from(id);
+    };
+
+    for (const index of [0, 1]) {
+      const [pda] = getVaultPda(indexedSessionId(index), user.publicKey);
+      await program.methods
+        .initializeIndexed(7, bot.publicKey)
+        .accounts({
+          registry: registryPda,
+          vault: pda,
+          user: user.publicKey,
+          payer: user.publicKey,
+          treasury: treasury.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      const vault = await program.account.vault.fetch(pda);
+      assert.deepEqual(vault.sessionId, indexedSessionId(index));
+    }
+
+    const registry = await program.account.userRegistry.fetch(registryPda);
+    assert.equal(registry.sessionCount.toNumber(), 2);
+  });
 });
@@ -0,0 +1,217 @@
+//! Test harness: the built program loaded into LiteSVM with a protocol config
+//! already in place, plus helpers to drive sessions through their lifecycle.
+//!
+//! Loads `target/deploy/gentdex_escrow.so`, so run `anchor build` first.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator};
+use gentdex_client::events::{self, Event};
+use gentdex_client::program::{default_dex_whitelist, EscrowError, ProtocolConfig, Vault};
+use gentdex_client::{instructions, pda, state, PROGRAM_ID};
+use litesvm::types::{TransactionMetadata, TransactionResult};
+use litesvm::LiteSVM;
+use solana_account::Account;
+use solana_clock::Clock;
+use solana_instruction::error::InstructionError;
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const SECONDS_PER_DAY: i64 = 86_400;
+/// Setup fee and daily compute fee the config is seeded with
+pub const FEE_BPS: u16 = 250;
+pub const DAILY_COMPUTE_FEE: u64 = 10_000_000;
+
+/// SPL Token, which LiteSVM loads by default
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+const PROGRAM_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/gentdex_escrow.so");
+
+pub struct Harness {
+    pub svm: LiteSVM,
+    /// Pays every transaction's fees, so the accounts under test only move
+    /// lamports the program moves. Also the config's admin and guardian.
+    pub payer: Keypair,
+    pub treasury: Pubkey,
+    next_session: u8,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(PROGRAM_ID, PROGRAM_PATH)
+            .unwrap_or_else(|err| panic!("loading {PROGRAM_PATH} (run `anchor build` first): {err}"));
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 100 * LAMPORTS_PER_SOL).unwrap();
+        let treasury = Pubkey::new_unique();
+        svm.airdrop(&treasury, LAMPORTS_PER_SOL).unwrap();
+
+        let mut harness = Self {
+            svm,
+            payer,
+            treasury,
+            next_session: 0,
+        };
+        harness.seed_config();
+        harness
+    }
+
+    /// Write the config `initialize_config` would create. That instruction
+    /// checks the caller against the program's upgrade authority, which a
+    /// LiteSVM-loaded program doesn't have.
+    fn seed_config(&mut self) {
+        let mut config: ProtocolConfig = zeroed();
+        config.admin = self.payer.pubkey();
+        config.guardian = self.payer.pubkey();
+        config.treasury = self.treasury;
+        config.fee_bps = FEE_BPS;
+        config.daily_compute_fee = DAILY_COMPUTE_FEE;
+        config.whitelist = default_dex_whitelist();
+        config.whitelist_version = 1;
+        let (address, bump) = pda::config_address();
+        config.bump = bump;
+
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        data.resize(8 + ProtocolConfig::INIT_SPACE, 0);
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(address, account).unwrap();
+    }
+
+    /// A new wallet holding `sol` SOL.
+    pub fn wallet(&mut self, sol: u64) -> Keypair {
+        let wallet = Keypair::new();
+        self.svm.airdrop(&wallet.pubkey(), sol * LAMPORTS_PER_SOL).unwrap();
+        wallet
+    }
+
+    /// Send `ixs` signed by `signers`, fees paid by the harness payer. Each
+    /// call gets a fresh blockhash so repeating a transaction isn't a replay.
+    pub fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> TransactionResult {
+        let mut all: Vec<&Keypair> = vec![&self.payer];
+        all.extend(signers.iter().copied().filter(|signer| signer.pubkey() != self.payer.pubkey()));
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&self.payer.pubkey()), &all, self.svm.latest_blockhash());
+        let result = self.svm.send_transaction(tx);
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// A fresh session id.
+    pub fn session_id(&mut self) -> [u8; 16] {
+        self.next_session += 1;
+        [self.next_session; 16]
+    }
+
+    /// Open a Pending session for `user` traded by `bot`.
+    pub fn initialize(&mut self, user: &Keypair, bot: Pubkey, duration_days: u16) -> Pubkey {
+        let session_id = self.session_id();
+        let (ix, vault) =
+            instructions::initialize(user.pubkey(), self.treasury, session_id, duration_days, bot, user.pubkey());
+        self.send(&[ix], &[user]).expect("initialize");
+        vault
+    }
+
+    /// Open and fund a session with `amount` lamports.
+    pub fn open_session(&mut self, user: &Keypair, bot: Pubkey, duration_days: u16, amount: u64) -> Pubkey {
+        let vault = self.initialize(user, bot, duration_days);
+        let ix = instructions::deposit(user.pubkey(), vault, self.treasury, amount, None, None);
+        self.send(&[ix], &[user]).expect("deposit");
+        vault
+    }
+
+    pub fn vault(&self, address: &Pubkey) -> Vault {
+        let account = self.svm.get_account(address).expect("vault exists");
+        state::decode(&account.data).unwrap()
+    }
+
+    /// Overwrite a vault's state, for setups no instruction reaches directly.
+    pub fn set_vault(&mut self, address: &Pubkey, vault: &Vault) {
+        let mut account = self.svm.get_account(address).expect("vault exists");
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+        account.data[..data.len()].copy_from_slice(&data);
+        self.svm.set_account(*address, account).unwrap();
+    }
+
+    /// An initialized SPL token account for `mint` owned by `owner` holding
+    /// `amount`. The mint itself needn't exist for transfers and closes.
+    pub fn token_account(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1; // AccountState::Initialized
+        let address = Pubkey::new_unique();
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(TOKEN_ACCOUNT_LEN),
+            data,
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(address, account).unwrap();
+        address
+    }
+
+    /// A token account's amount, or `None` once it's closed.
+    pub fn token_amount(&self, address: &Pubkey) -> Option<u64> {
+        let account = self.svm.get_account(address).filter(|account| account.lamports > 0)?;
+        Some(u64::from_le_bytes(account.data[64..72].try_into().unwrap()))
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_account(address).map_or(0, |account| account.lamports)
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    /// Move the clock forward `seconds`.
+    pub fn warp(&mut self, seconds: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += seconds;
+        clock.slot += 1;
+        self.svm.set_sysvar::<Clock>(&clock);
+    }
+}
+
+/// A GentDex account with every field zeroed.
+pub fn zeroed<T: AccountDeserialize + Discriminator>() -> T {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    data.resize(10_240, 0);
+    T::try_deserialize(&mut data.as_slice()).unwrap()
+}
+
+/// The events a successful transaction emitted.
+pub fn events(meta: &TransactionMetadata) -> Vec<Event> {
+    events::parse_logs(&meta.logs)
+}
+
+/// Assert the transaction's first instruction failed with `error`.
+#[track_caller]
+pub fn assert_error(result: TransactionResult, error: EscrowError) {
+    let failed = result.expect_err("transaction should have failed");
+    assert_eq!(
+        failed.err,
+        TransactionError::InstructionError(0, InstructionError::Custom(u32::from(error))),
+        "logs: {:#?}",
+        failed.meta.logs
+    );
+}
@@ -0,0 +1,1388 @@
+//! Sessions driven through the real program: the happy path end to end, then
+//! instructions sent out of order or by the wrong party.
+//!
+//! Successful swaps CPI into a DEX, which would need the venue's program and
+//! market accounts loaded; these tests cover swaps up to the policy checks,
+//! and dry runs up to the adapter's route checks.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AnchorDeserialize;
+use gentdex_client::events::Event;
+use gentdex_client::instructions::{self, Swap};
+use gentdex_client::jupiter::JUPITER_PROGRAM_ID;
+use gentdex_client::program::{
+    gross_for_net, BotStats, EscrowError, PauseReason, RewardsAccount, RewardsSchedule, SwapRejectReason, SwapResult,
+    VaultStatus, MAX_GUARDIAN_PAUSE_DAYS,
+};
+use gentdex_client::pda;
+use gentdex_escrow_tests::{
+    assert_error, events, Harness, DAILY_COMPUTE_FEE, FEE_BPS, LAMPORTS_PER_SOL, SECONDS_PER_DAY,
+    TOKEN_PROGRAM_ID,
+};
+use solana_clock::Clock;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+fn swap(harness: &Harness, vault: Pubkey, user: &Keypair, bot: &Keypair, dex_program: Pubkey, amount_in: u64) -> Swap {
+    Swap {
+        vault,
+        user: user.pubkey(),
+        treasury: harness.treasury,
+        bot: bot.pubkey(),
+        dex_program,
+        amount_in,
+        minimum_amount_out: 1,
+        memo: None,
+        recent_slot: None,
+        jito_tip: false,
+        nonce: None,
+        dry_run: false,
+        batched: false,
+        output_token_account: None,
+        price_feeds: None,
+        base_token_account: None,
+        route: vec![],
+        route_data: None,
+    }
+}
+
+const NATIVE_MINT: Pubkey = Pubkey::from_str_const("So11111111111111111111111111111111111111112");
+
+/// A Jupiter `route` selling `amount_in` of the vault's base currency for a
+/// fresh mint, as the Jupiter adapter checks it: the vault's WSOL (or base
+/// token) and output accounts, no platform fee and an empty route plan.
+fn jupiter_route(harness: &mut Harness, swap: &mut Swap) {
+    let output_mint = Pubkey::new_unique();
+    let source = match swap.base_token_account {
+        Some(base_token_account) => base_token_account,
+        None => harness.token_account(&swap.vault, &NATIVE_MINT, 0),
+    };
+    let destination = harness.token_account(&swap.vault, &output_mint, 0);
+    swap.route = vec![
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(swap.vault, false),
+        AccountMeta::new(source, false),
+        AccountMeta::new(destination, false),
+        AccountMeta::new_readonly(JUPITER_PROGRAM_ID, false),
+        AccountMeta::new_readonly(output_mint, false),
+        AccountMeta::new_readonly(JUPITER_PROGRAM_ID, false),
+        AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        AccountMeta::new_readonly(JUPITER_PROGRAM_ID, false),
+    ];
+    let mut data = vec![229, 23, 203, 151, 122, 227, 173, 42];
+    data.extend_from_slice(&0u32.to_le_bytes()); // route_plan: empty
+    data.extend_from_slice(&swap.amount_in.to_le_bytes());
+    data.extend_from_slice(&swap.minimum_amount_out.to_le_bytes());
+    data.extend_from_slice(&50u16.to_le_bytes()); // slippage_bps
+    data.push(0); // platform_fee_bps
+    swap.route_data = Some(data);
+}
+
+const USDC_MINT: Pubkey = Pubkey::from_str_const("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+
+/// `owner`'s associated USDC account, holding `amount`.
+fn usdc_account(harness: &mut Harness, owner: &Pubkey, amount: u64) -> Pubkey {
+    let ata_program = Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+    let seeds = [owner.as_ref(), TOKEN_PROGRAM_ID.as_ref(), USDC_MINT.as_ref()];
+    let address = Pubkey::find_program_address(&seeds, &ata_program).0;
+    let filled = harness.token_account(owner, &USDC_MINT, amount);
+    let account = harness.svm.get_account(&filled).unwrap();
+    harness.svm.set_account(address, account).unwrap();
+    address
+}
+
+/// Redenominate a session in USDC, its associated token account holding
+/// `amount`. Returns that account.
+fn usdc_session(harness: &mut Harness, vault: &Pubkey, amount: u64) -> Pubkey {
+    let mut state = harness.vault(vault);
+    state.base_mint = USDC_MINT;
+    harness.set_vault(vault, &state);
+    usdc_account(harness, vault, amount)
+}
+
+/// Lamports held by everything a session touches except the fee payer.
+fn held(harness: &Harness, user: &Keypair, bot: &Pubkey, vault: &Pubkey) -> u64 {
+    let user_accounts = [pda::rewards_address(&user.pubkey()).0, pda::stake_address(&user.pubkey()).0];
+    [user.pubkey(), harness.treasury, *vault, pda::bot_stats_address(bot).0]
+        .iter()
+        .chain(&user_accounts)
+        .map(|address| harness.lamports(address))
+        .sum()
+}
+
+#[test]
+fn full_lifecycle() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.initialize(&user, bot.pubkey(), 3);
+    let before = held(&harness, &user, &bot.pubkey(), &vault);
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Pending);
+
+    let treasury_before = harness.lamports(&harness.treasury);
+    let ix = instructions::deposit(user.pubkey(), vault, harness.treasury, LAMPORTS_PER_SOL, None, None);
+    harness.send(&[ix], &[&user]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Active);
+    assert_eq!(state.balance, 975_000_000);
+    assert_eq!(harness.lamports(&harness.treasury) - treasury_before, 25_000_000);
+    assert_eq!(state.expires_at, harness.now() + 3 * SECONDS_PER_DAY);
+
+    // A DEX off the whitelist fails outright, as does one with no adapter
+    let ix = instructions::execute_swap(&swap(&harness, vault, &user, &bot, Pubkey::new_unique(), 1_000_000));
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::DexNotWhitelisted);
+    let raydium = Pubkey::from_str_const("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+    let ix = instructions::execute_swap(&swap(&harness, vault, &user, &bot, raydium, 1_000_000));
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::DexAdapterMissing);
+
+    // Policy failures succeed with a SwapRejected event and move nothing
+    let (amount_in, reason) = (2 * LAMPORTS_PER_SOL, SwapRejectReason::InsufficientBalance);
+    let ix = instructions::execute_swap(&swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, amount_in));
+    let meta = harness.send(&[ix], &[&bot]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::SwapRejected(rejected)] => {
+            assert_eq!(rejected.reason, reason);
+            assert_eq!(rejected.amount_in, amount_in);
+        }
+        other => panic!("expected one SwapRejected, got {other:?}"),
+    }
+    let result = SwapResult::try_from_slice(&meta.return_data.data).unwrap();
+    assert_eq!((result.rejected, result.spent, result.balance), (Some(reason), 0, 975_000_000));
+    assert_eq!(harness.vault(&vault).balance, 975_000_000);
+
+    harness.warp(SECONDS_PER_DAY);
+    let cranker = harness.wallet(1);
+    let ix = instructions::deduct_compute_fee(cranker.pubkey(), vault, user.pubkey(), harness.treasury, bot.pubkey());
+    harness.send(&[ix], &[&cranker]).unwrap();
+    assert_eq!(harness.vault(&vault).balance, 975_000_000 - DAILY_COMPUTE_FEE);
+
+    harness.send(&[instructions::pause(user.pubkey(), vault)], &[&user]).unwrap();
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Paused);
+    harness.send(&[instructions::resume(user.pubkey(), vault)], &[&user]).unwrap();
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Active);
+
+    harness.warp(3 * SECONDS_PER_DAY);
+    harness.send(&[instructions::expire(cranker.pubkey(), vault)], &[&cranker]).unwrap();
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Expired);
+
+    // Withdrawing settles the two days accrued since the crank, clamped to expiry
+    let user_before = harness.lamports(&user.pubkey());
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Withdrawn);
+    assert_eq!(state.balance, 0);
+    assert_eq!(state.total_withdrawn, 975_000_000 - 3 * DAILY_COMPUTE_FEE);
+    // The first payout for this bot also paid the rent for its stats
+    let stats_address = pda::bot_stats_address(&bot.pubkey()).0;
+    let stats_rent = harness.lamports(&stats_address);
+    assert_eq!(harness.lamports(&user.pubkey()) + stats_rent - user_before, state.total_withdrawn);
+
+    // The payout counted the session against its bot
+    let stats = harness.svm.get_account(&stats_address).unwrap();
+    let stats: BotStats = gentdex_client::state::decode(&stats.data).unwrap();
+    assert_eq!((stats.bot, stats.sessions, stats.profitable_sessions), (bot.pubkey(), 1, 0));
+    assert_eq!(stats.pnl, -3 * DAILY_COMPUTE_FEE as i64);
+
+    // Fees are paid by the harness payer, so the session's accounts only
+    // moved lamports among themselves
+    assert_eq!(held(&harness, &user, &bot.pubkey(), &vault), before);
+}
+
+#[test]
+fn sessions_open_funded_in_one_instruction() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let open = |harness: &mut Harness, amount| {
+        let session_id = harness.session_id();
+        let (ix, vault) = instructions::initialize_and_deposit(
+            user.pubkey(),
+            harness.treasury,
+            session_id,
+            3,
+            bot.pubkey(),
+            amount,
+            user.pubkey(),
+        );
+        (harness.send(&[ix], &[&user]), vault)
+    };
+
+    // Too small a deposit leaves nothing behind, not a Pending vault
+    let (result, vault) = open(&mut harness, 1);
+    assert_error(result, EscrowError::DepositTooSmall);
+    assert_eq!(harness.lamports(&vault), 0);
+
+    let (result, vault) = open(&mut harness, LAMPORTS_PER_SOL);
+    match events(&result.unwrap()).as_slice() {
+        [Event::SessionCreated(created), Event::Deposited(deposit)] => {
+            assert_eq!((created.user, created.bot), (user.pubkey(), bot.pubkey()));
+            assert_eq!(deposit.trading_balance, 975_000_000);
+        }
+        other => panic!("expected SessionCreated then Deposited, got {other:?}"),
+    }
+    let state = harness.vault(&vault);
+    assert_eq!((state.status, state.balance), (VaultStatus::Active, 975_000_000));
+    assert_eq!(state.expires_at, harness.now() + 3 * SECONDS_PER_DAY);
+}
+
+#[test]
+fn gifted_sessions_start_when_accepted() {
+    let mut harness = Harness::new();
+    let giver = harness.wallet(10);
+    let friend = harness.wallet(1);
+    let bot = harness.wallet(1);
+    let gift = |harness: &mut Harness| {
+        let session_id = harness.session_id();
+        let (ix, vault) = instructions::gift_session(
+            giver.pubkey(),
+            harness.treasury,
+            session_id,
+            3,
+            bot.pubkey(),
+            friend.pubkey(),
+            LAMPORTS_PER_SOL,
+        );
+        harness.send(&[ix], &[&giver]).unwrap();
+        vault
+    };
+
+    // The friend owns the session but it waits, unfunded, for them to accept
+    let vault = gift(&mut harness);
+    let state = harness.vault(&vault);
+    assert_eq!((state.user, state.status, state.balance), (friend.pubkey(), VaultStatus::Pending, 0));
+    let ix = instructions::accept_gift(giver.pubkey(), vault, giver.pubkey(), harness.treasury, None, giver.pubkey());
+    assert_error(harness.send(&[ix], &[&giver]), EscrowError::Unauthorized);
+
+    let gift_address = pda::gift_address(&vault).0;
+    let (giver_before, gift_rent) = (harness.lamports(&giver.pubkey()), harness.lamports(&gift_address) - LAMPORTS_PER_SOL);
+    let ix = instructions::accept_gift(friend.pubkey(), vault, giver.pubkey(), harness.treasury, None, friend.pubkey());
+    let meta = harness.send(&[ix], &[&friend]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::GiftAccepted(accepted), Event::Deposited(deposit)] => {
+            assert_eq!((accepted.giver, accepted.user, accepted.amount), (giver.pubkey(), friend.pubkey(), LAMPORTS_PER_SOL));
+            assert_eq!(deposit.trading_balance, 975_000_000);
+        }
+        other => panic!("expected GiftAccepted then Deposited, got {other:?}"),
+    }
+    let state = harness.vault(&vault);
+    assert_eq!((state.status, state.balance), (VaultStatus::Active, 975_000_000));
+    assert_eq!(harness.lamports(&gift_address), 0);
+    assert_eq!(harness.lamports(&giver.pubkey()) - giver_before, gift_rent);
+
+    // Until then, the giver can take it back
+    let vault = gift(&mut harness);
+    let giver_before = harness.lamports(&giver.pubkey());
+    let gift_address = pda::gift_address(&vault).0;
+    let held = harness.lamports(&gift_address);
+    harness.send(&[instructions::reclaim_gift(giver.pubkey(), vault)], &[&giver]).unwrap();
+    assert_eq!(harness.lamports(&giver.pubkey()) - giver_before, held);
+    let ix = instructions::accept_gift(friend.pubkey(), vault, giver.pubkey(), harness.treasury, None, friend.pubkey());
+    assert!(harness.send(&[ix], &[&friend]).is_err());
+}
+
+#[test]
+fn lamports_are_conserved() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 2, 3 * LAMPORTS_PER_SOL);
+    let total = held(&harness, &user, &bot.pubkey(), &vault);
+
+    harness.warp(SECONDS_PER_DAY);
+    let ix = instructions::deduct_compute_fee(user.pubkey(), vault, user.pubkey(), harness.treasury, bot.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    assert_eq!(held(&harness, &user, &bot.pubkey(), &vault), total);
+
+    harness.send(&[instructions::pause(user.pubkey(), vault)], &[&user]).unwrap();
+    harness.warp(2 * SECONDS_PER_DAY);
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    assert_eq!(held(&harness, &user, &bot.pubkey(), &vault), total);
+
+    // The vault keeps its rent; everything above it went to the user or treasury
+    let state = harness.vault(&vault);
+    let rent = harness.svm.minimum_balance_for_rent_exemption(harness.svm.get_account(&vault).unwrap().data.len());
+    assert_eq!(harness.lamports(&vault), rent);
+    assert_eq!(state.total_withdrawn, 3 * LAMPORTS_PER_SOL * 9_750 / 10_000 - 2 * DAILY_COMPUTE_FEE);
+}
+
+#[test]
+fn operator_credit_pays_compute_fees_first() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 5, LAMPORTS_PER_SOL);
+    let credit = pda::operator_credit_address(&bot.pubkey()).0;
+    let ix = instructions::build(
+        instructions::accounts::FundOperatorCredit {
+            operator_credit: credit,
+            bot: bot.pubkey(),
+            system_program: anchor_lang::system_program::ID,
+        },
+        instructions::args::FundOperatorCredit { amount: 2 * DAILY_COMPUTE_FEE },
+    );
+    harness.send(&[ix], &[&bot]).unwrap();
+    let withdraw_credit = |amount| {
+        instructions::build(
+            instructions::accounts::WithdrawOperatorCredit { operator_credit: credit, bot: bot.pubkey() },
+            instructions::args::WithdrawOperatorCredit { amount },
+        )
+    };
+    harness.send(&[withdraw_credit(DAILY_COMPUTE_FEE / 2)], &[&bot]).unwrap();
+
+    // A day and a half of credit: the first day is fully covered
+    harness.warp(SECONDS_PER_DAY);
+    let crank = instructions::deduct_compute_fee(user.pubkey(), vault, user.pubkey(), harness.treasury, bot.pubkey());
+    let meta = harness.send(&[crank.clone()], &[&user]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::ComputeFeeSubsidized(subsidized), Event::ComputeFeeDeducted(deducted)] => {
+            assert_eq!((subsidized.amount, subsidized.credit_remaining), (DAILY_COMPUTE_FEE, DAILY_COMPUTE_FEE / 2));
+            assert_eq!(deducted.fee, 0);
+        }
+        other => panic!("expected ComputeFeeSubsidized then ComputeFeeDeducted, got {other:?}"),
+    }
+    assert_eq!(harness.vault(&vault).balance, 975_000_000);
+
+    // Then it runs out and the session pays the rest
+    harness.warp(SECONDS_PER_DAY);
+    let treasury_before = harness.lamports(&harness.treasury);
+    harness.send(&[crank], &[&user]).unwrap();
+    assert_eq!(harness.vault(&vault).balance, 975_000_000 - DAILY_COMPUTE_FEE / 2);
+    assert_eq!(harness.lamports(&harness.treasury) - treasury_before, DAILY_COMPUTE_FEE);
+    assert_error(harness.send(&[withdraw_credit(1)], &[&bot]), EscrowError::InsufficientBalance);
+}
+
+#[test]
+fn operator_credit_pays_compute_fees_settled_on_withdrawal() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 5, LAMPORTS_PER_SOL);
+    let ix = instructions::build(
+        instructions::accounts::FundOperatorCredit {
+            operator_credit: pda::operator_credit_address(&bot.pubkey()).0,
+            bot: bot.pubkey(),
+            system_program: anchor_lang::system_program::ID,
+        },
+        instructions::args::FundOperatorCredit { amount: 5 * DAILY_COMPUTE_FEE },
+    );
+    harness.send(&[ix], &[&bot]).unwrap();
+
+    // Exiting before any crank ran still leaves the fee to the operator
+    harness.warp(2 * SECONDS_PER_DAY);
+    let user_before = harness.lamports(&user.pubkey());
+    let payer = harness.payer.pubkey();
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), payer);
+    let meta = harness.send(&[ix], &[&user]).unwrap();
+    let events = events(&meta);
+    let subsidized = events.iter().find_map(|event| match event {
+        Event::ComputeFeeSubsidized(subsidized) => Some(subsidized.amount),
+        _ => None,
+    });
+    assert_eq!(subsidized, Some(2 * DAILY_COMPUTE_FEE));
+    match events.last() {
+        Some(Event::Withdrawn(withdrawn)) => assert_eq!((withdrawn.amount, withdrawn.compute_fee), (975_000_000, 0)),
+        other => panic!("expected Withdrawn last, got {other:?}"),
+    }
+    assert_eq!(harness.lamports(&user.pubkey()) - user_before, 975_000_000);
+}
+
+#[test]
+fn swaps_settle_the_compute_fee_due() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let treasury_before = harness.lamports(&harness.treasury);
+
+    // The day due is settled before the balance is checked, even for a rejection
+    harness.warp(SECONDS_PER_DAY);
+    let swap = swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 975_000_000);
+    let meta = harness.send(&[instructions::execute_swap(&swap)], &[&bot]).unwrap();
+    let result = SwapResult::try_from_slice(&meta.return_data.data).unwrap();
+    assert_eq!(result.rejected, Some(SwapRejectReason::InsufficientBalance));
+    assert_eq!((result.fee, result.balance), (DAILY_COMPUTE_FEE, 975_000_000 - DAILY_COMPUTE_FEE));
+    assert_eq!(harness.lamports(&harness.treasury) - treasury_before, DAILY_COMPUTE_FEE);
+
+    // Nothing more is due until the next day
+    let meta = harness.send(&[instructions::execute_swap(&swap)], &[&bot]).unwrap();
+    let result = SwapResult::try_from_slice(&meta.return_data.data).unwrap();
+    assert_eq!(result.fee, 0);
+}
+
+#[test]
+fn rejects_the_wrong_signer() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let stranger = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+
+    // The bot trades the session but can't take its funds or change its state
+    let ix = instructions::withdraw(bot.pubkey(), vault, harness.treasury, bot.pubkey(), bot.pubkey());
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::Unauthorized);
+    assert_error(harness.send(&[instructions::pause(bot.pubkey(), vault)], &[&bot]), EscrowError::Unauthorized);
+
+    // Nor can the user trade as the bot
+    let ix = instructions::execute_swap(&swap(&harness, vault, &user, &user, JUPITER_PROGRAM_ID, 1_000_000));
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::Unauthorized);
+
+    let ix = instructions::pause(stranger.pubkey(), vault);
+    assert_error(harness.send(&[ix], &[&stranger]), EscrowError::Unauthorized);
+    let ix = instructions::deposit(stranger.pubkey(), vault, harness.treasury, LAMPORTS_PER_SOL, None, None);
+    assert_error(harness.send(&[ix], &[&stranger]), EscrowError::InvalidStatus);
+
+    // Fees and payouts only go to the session's own treasury
+    let elsewhere = Pubkey::new_unique();
+    let ix = instructions::withdraw(user.pubkey(), vault, elsewhere, bot.pubkey(), user.pubkey());
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::InvalidTreasury);
+
+    assert_eq!(harness.vault(&vault).balance, 975_000_000);
+}
+
+#[test]
+fn rejects_out_of_order_instructions() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+
+    let vault = harness.initialize(&user, bot.pubkey(), 2);
+    let ix = instructions::deposit(user.pubkey(), vault, harness.treasury, 1_000, None, None);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::DepositTooSmall);
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::InvalidStatus);
+
+    let ix = instructions::deposit(user.pubkey(), vault, harness.treasury, LAMPORTS_PER_SOL, None, None);
+    harness.send(&[ix], &[&user]).unwrap();
+    let ix = instructions::deposit(user.pubkey(), vault, harness.treasury, LAMPORTS_PER_SOL, None, None);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::InvalidStatus);
+
+    let ix = instructions::deduct_compute_fee(user.pubkey(), vault, user.pubkey(), harness.treasury, bot.pubkey());
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::TooEarlyForDeduction);
+    assert_error(harness.send(&[instructions::expire(user.pubkey(), vault)], &[&user]), EscrowError::SessionNotExpired);
+    assert_error(harness.send(&[instructions::resume(user.pubkey(), vault)], &[&user]), EscrowError::InvalidStatus);
+
+    // A paused session can't trade
+    harness.send(&[instructions::pause(user.pubkey(), vault)], &[&user]).unwrap();
+    let ix = instructions::execute_swap(&swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 1_000_000));
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::InvalidStatus);
+
+    // Nor be resumed once its time is up
+    harness.warp(2 * SECONDS_PER_DAY);
+    assert_error(harness.send(&[instructions::resume(user.pubkey(), vault)], &[&user]), EscrowError::SessionExpired);
+
+    harness.send(&[instructions::expire(user.pubkey(), vault)], &[&user]).unwrap();
+    assert_error(harness.send(&[instructions::expire(user.pubkey(), vault)], &[&user]), EscrowError::InvalidStatus);
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::InsufficientBalance);
+}
+
+#[test]
+fn expired_sessions_stop_trading() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 1, LAMPORTS_PER_SOL);
+
+    // Past expiry but before anyone cranks expire: still Active on chain
+    harness.warp(SECONDS_PER_DAY);
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Active);
+    let ix = instructions::execute_swap(&swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 1_000_000));
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::SessionExpired);
+
+    // Withdrawing without expiring first still settles the day's compute fee
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    assert_eq!(harness.vault(&vault).total_withdrawn, 975_000_000 - DAILY_COMPUTE_FEE);
+}
+
+#[test]
+fn users_extend_sessions_until_they_expire() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 2, LAMPORTS_PER_SOL);
+    let expires_at = harness.vault(&vault).expires_at;
+
+    let ix = instructions::extend_session(bot.pubkey(), vault, 5);
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::Unauthorized);
+    let ix = instructions::extend_session(user.pubkey(), vault, 0);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::InvalidExtension);
+
+    let meta = harness.send(&[instructions::extend_session(user.pubkey(), vault, 5)], &[&user]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!((state.duration_days, state.expires_at), (7, expires_at + 5 * SECONDS_PER_DAY));
+    assert!(matches!(events(&meta).as_slice(), [Event::SessionExtended(e)] if e.expires_at == state.expires_at));
+
+    // Past the original expiry, the session runs on
+    harness.warp(3 * SECONDS_PER_DAY);
+    let ix = instructions::expire(user.pubkey(), vault);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::SessionNotExpired);
+
+    // But a session whose time is up can't be revived
+    harness.warp(4 * SECONDS_PER_DAY);
+    let ix = instructions::extend_session(user.pubkey(), vault, 1);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::SessionExpired);
+}
+
+#[test]
+fn resigning_bots_give_notice() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 30, LAMPORTS_PER_SOL);
+
+    assert_error(harness.send(&[instructions::resign(user.pubkey(), vault)], &[&user]), EscrowError::Unauthorized);
+    harness.send(&[instructions::resign(bot.pubkey(), vault)], &[&bot]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!(state.resigned_at, harness.now());
+    assert_eq!(state.expires_at, harness.now() + 3 * SECONDS_PER_DAY);
+    let ix = instructions::extend_session(user.pubkey(), vault, 30);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::BotResigned);
+
+    // No new positions, but the user can still withdraw
+    let ix = instructions::execute_swap(&swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 1_000_000));
+    let meta = harness.send(&[ix], &[&bot]).unwrap();
+    assert!(matches!(events(&meta).as_slice(), [Event::SwapRejected(r)] if r.reason == SwapRejectReason::BotResigned));
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+}
+
+#[test]
+fn blacklisted_bots_pause_instead_of_acting() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 30, LAMPORTS_PER_SOL);
+    let ix = instructions::build(
+        instructions::accounts::AdminAction { config: pda::config_address().0, admin: harness.payer.pubkey() },
+        instructions::args::SetBotBlacklisted { bot: bot.pubkey(), blacklisted: true },
+    );
+    harness.send(&[ix], &[]).unwrap();
+
+    // The bot's instruction succeeds so the pause commits, but doesn't resign
+    let meta = harness.send(&[instructions::resign(bot.pubkey(), vault)], &[&bot]).unwrap();
+    assert!(matches!(events(&meta).as_slice(), [Event::SessionPaused(_), Event::BlacklistedBotPaused(_)]));
+    let state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Paused);
+    assert_eq!(state.resigned_at, 0);
+
+    // With nothing left to pause it's refused
+    assert_error(harness.send(&[instructions::resign(bot.pubkey(), vault)], &[&bot]), EscrowError::BotBlacklisted);
+}
+
+#[test]
+fn insolvent_vaults_are_paused() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let cranker = harness.wallet(1);
+
+    let meta = harness.send(&[instructions::assert_solvent(cranker.pubkey(), vault)], &[&cranker]).unwrap();
+    assert!(events(&meta).is_empty());
+
+    // Lose lamports the accounting still counts
+    let mut account = harness.svm.get_account(&vault).unwrap();
+    account.lamports -= 1;
+    harness.svm.set_account(vault, account).unwrap();
+    harness.warp(1);
+    let meta = harness.send(&[instructions::assert_solvent(cranker.pubkey(), vault)], &[&cranker]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::SessionPaused(_), Event::InsolvencyDetected(incident)] => {
+            assert_eq!(incident.required - incident.lamports, 1);
+            assert!(incident.paused);
+        }
+        other => panic!("expected SessionPaused and InsolvencyDetected, got {other:?}"),
+    }
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Paused);
+}
+
+#[test]
+fn dry_run_swaps_move_nothing() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let lamports = harness.lamports(&vault);
+
+    for (amount_in, rejected) in [
+        (LAMPORTS_PER_SOL / 2, None),
+        (2 * LAMPORTS_PER_SOL, Some(SwapRejectReason::InsufficientBalance)),
+    ] {
+        let mut dry_run = swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, amount_in);
+        jupiter_route(&mut harness, &mut dry_run);
+        dry_run.dry_run = true;
+        let meta = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]).unwrap();
+        let result = SwapResult::try_from_slice(&meta.return_data.data).unwrap();
+        assert_eq!((result.rejected, result.spent, result.balance), (rejected, 0, 975_000_000));
+    }
+    assert_eq!(harness.lamports(&vault), lamports);
+    assert_eq!(harness.vault(&vault).total_volume, 0);
+
+    // The route must sell what the swap says it does
+    let mut dry_run = swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, LAMPORTS_PER_SOL / 2);
+    jupiter_route(&mut harness, &mut dry_run);
+    dry_run.amount_in += 1;
+    dry_run.dry_run = true;
+    let result = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]);
+    assert_error(result, EscrowError::InvalidDexAccount);
+
+    // Nor does it write: no nonce spent, no event, no pause for a blacklisted bot
+    let mut dry_run = swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 2 * LAMPORTS_PER_SOL);
+    (dry_run.nonce, dry_run.dry_run) = (Some(1), true);
+    let meta = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]).unwrap();
+    assert!(events(&meta).is_empty());
+    assert_eq!(harness.vault(&vault).trade_nonce, 0);
+    let ix = instructions::build(
+        instructions::accounts::AdminAction { config: pda::config_address().0, admin: harness.payer.pubkey() },
+        instructions::args::SetBotBlacklisted { bot: bot.pubkey(), blacklisted: true },
+    );
+    harness.send(&[ix], &[]).unwrap();
+    let meta = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]).unwrap();
+    let result = SwapResult::try_from_slice(&meta.return_data.data).unwrap();
+    assert_eq!(result.rejected, Some(SwapRejectReason::BotBlacklisted));
+    assert!(events(&meta).is_empty());
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Active);
+}
+
+#[test]
+fn token_sessions_sell_their_base_mint_through_jupiter() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+
+    // Redenominate the session in USDC, 100 of it held in the vault's ATA
+    let base_token_account = usdc_session(&mut harness, &vault, 100_000_000);
+    let mut state = harness.vault(&vault);
+    state.balance = 100_000_000;
+    harness.set_vault(&vault, &state);
+
+    // Sold from the base account, never wrapped from the vault's lamports
+    let mut dry_run = swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 50_000_000);
+    let result = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]);
+    assert_error(result, EscrowError::BaseCurrencyMismatch);
+    dry_run.base_token_account = Some(base_token_account);
+    jupiter_route(&mut harness, &mut dry_run);
+    dry_run.dry_run = true;
+    let meta = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]).unwrap();
+    let result = SwapResult::try_from_slice(&meta.return_data.data).unwrap();
+    assert_eq!((result.rejected, result.balance), (None, 100_000_000));
+
+    // Venues whose adapter only sells SOL can't take it
+    let phoenix = Pubkey::from_str_const("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY");
+    dry_run.dex_program = phoenix;
+    let result = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]);
+    assert_error(result, EscrowError::BaseCurrencyMismatch);
+}
+
+#[test]
+fn trade_nonces_stop_replays() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+
+    // A rejected trade still spends its nonce
+    let mut numbered = swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 2 * LAMPORTS_PER_SOL);
+    numbered.nonce = Some(5);
+    let ix = instructions::execute_swap(&numbered);
+    harness.send(&[ix.clone()], &[&bot]).unwrap();
+    assert_eq!(harness.vault(&vault).trade_nonce, 5);
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::StaleTradeNonce);
+
+    numbered.nonce = Some(4);
+    assert_error(harness.send(&[instructions::execute_swap(&numbered)], &[&bot]), EscrowError::StaleTradeNonce);
+    numbered.nonce = Some(6);
+    harness.send(&[instructions::execute_swap(&numbered)], &[&bot]).unwrap();
+    assert_eq!(harness.vault(&vault).trade_nonce, 6);
+}
+
+#[test]
+fn exact_balance_deposits_add_the_fee_on_top() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.initialize(&user, bot.pubkey(), 3);
+
+    let trading_balance = LAMPORTS_PER_SOL + 1;
+    let ix = instructions::deposit_exact_balance(user.pubkey(), vault, harness.treasury, trading_balance, None, None);
+    let meta = harness.send(&[ix], &[&user]).unwrap();
+    assert_eq!(harness.vault(&vault).balance, trading_balance);
+    let gross = gross_for_net(trading_balance, FEE_BPS as u64).unwrap();
+    match events(&meta).as_slice() {
+        [Event::Deposited(deposit)] => assert_eq!((deposit.amount, deposit.trading_balance), (gross, trading_balance)),
+        other => panic!("expected Deposited, got {other:?}"),
+    }
+}
+
+#[test]
+fn deposits_respect_the_protocol_cap() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let admin = harness.payer.pubkey();
+    let config = pda::config_address().0;
+    let limits = |min_deposit, max_deposit| {
+        instructions::build(
+            instructions::accounts::AdminAction { config, admin },
+            instructions::args::SetDepositLimits { min_deposit, max_deposit },
+        )
+    };
+
+    let ix = limits(2 * LAMPORTS_PER_SOL, LAMPORTS_PER_SOL);
+    assert_error(harness.send(&[ix], &[]), EscrowError::InvalidDepositLimits);
+    harness.send(&[limits(0, LAMPORTS_PER_SOL)], &[]).unwrap();
+
+    let vault = harness.initialize(&user, bot.pubkey(), 3);
+    let over = LAMPORTS_PER_SOL + 1;
+    let ix = instructions::deposit_exact_balance(user.pubkey(), vault, harness.treasury, over, None, None);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::DepositTooLarge);
+    let ix = instructions::deposit_exact_balance(user.pubkey(), vault, harness.treasury, LAMPORTS_PER_SOL, None, None);
+    harness.send(&[ix], &[&user]).unwrap();
+
+    // Topping the session up through a transfer counts too
+    let source = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL / 2);
+    let ix = instructions::build(
+        instructions::accounts::TransferToSession {
+            source_vault: source,
+            destination_vault: vault,
+            user: user.pubkey(),
+            config,
+            treasury: harness.treasury,
+            fee_router: pda::fee_router_address().0,
+            operator_credit: pda::operator_credit_address(&bot.pubkey()).0,
+            rewards: pda::rewards_address(&user.pubkey()).0,
+            bot_profile: None,
+        },
+        instructions::args::TransferToSession {},
+    );
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::DepositTooLarge);
+}
+
+#[test]
+fn token_deposits_respect_the_stablecoin_limits() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let (admin, treasury) = (harness.payer.pubkey(), harness.treasury);
+    let config = pda::config_address().0;
+    let vault = harness.initialize(&user, bot.pubkey(), 3);
+    let vault_token_account = usdc_session(&mut harness, &vault, 0);
+    let user_token_account = harness.token_account(&user.pubkey(), &USDC_MINT, 1_000_000_000);
+    let treasury_token_account = harness.token_account(&treasury, &USDC_MINT, 0);
+    let deposit = |amount| {
+        instructions::build(
+            instructions::accounts::DepositToken {
+                vault,
+                user: user.pubkey(),
+                payer: user.pubkey(),
+                config,
+                rewards: pda::rewards_address(&user.pubkey()).0,
+                stake: pda::stake_address(&user.pubkey()).0,
+                user_token_account,
+                vault_token_account,
+                treasury_token_account,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instructions::args::DepositToken { amount },
+        )
+    };
+
+    // Ten USDC at least, and no more than the admin's cap
+    assert_error(harness.send(&[deposit(9_999_999)], &[&user]), EscrowError::DepositTooSmall);
+    let limits = instructions::build(
+        instructions::accounts::AdminAction { config, admin },
+        instructions::args::SetStableDepositLimits { min_deposit: 0, max_deposit: 100_000_000 },
+    );
+    harness.send(&[limits], &[]).unwrap();
+    assert_error(harness.send(&[deposit(200_000_000)], &[&user]), EscrowError::DepositTooLarge);
+
+    harness.send(&[deposit(100_000_000)], &[&user]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Active);
+    assert_eq!((state.balance, state.daily_compute_fee), (97_500_000, 975_000));
+    assert_eq!(harness.token_amount(&vault_token_account), Some(97_500_000));
+    assert_eq!(harness.token_amount(&treasury_token_account), Some(2_500_000));
+}
+
+#[test]
+fn dln_orders_activate_sessions_as_deposits_do() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let dln = harness.wallet(1);
+    let (admin, treasury) = (harness.payer.pubkey(), harness.treasury);
+    let config = pda::config_address().0;
+    let vault = harness.initialize(&user, bot.pubkey(), 3);
+    let vault_token_account = usdc_session(&mut harness, &vault, 0);
+    usdc_account(&mut harness, &dln.pubkey(), 100_000_000);
+    usdc_account(&mut harness, &treasury, 0);
+    let deposit = instructions::deposit_from_dln(dln.pubkey(), dln.pubkey(), vault, &harness.vault(&vault));
+
+    // Only the funder the user named
+    assert_error(harness.send(&[deposit.clone()], &[&dln]), EscrowError::UnauthorizedFunder);
+    let ix = instructions::build(
+        instructions::accounts::UserAction { vault, user: user.pubkey() },
+        instructions::args::SetFunder { funder: dln.pubkey() },
+    );
+    harness.send(&[ix], &[&user]).unwrap();
+
+    // Never for a blacklisted bot
+    let blacklist = |blacklisted| {
+        instructions::build(
+            instructions::accounts::AdminAction { config, admin },
+            instructions::args::SetBotBlacklisted { bot: bot.pubkey(), blacklisted },
+        )
+    };
+    harness.send(&[blacklist(true)], &[]).unwrap();
+    assert_error(harness.send(&[deposit.clone()], &[&dln]), EscrowError::BotBlacklisted);
+    harness.send(&[blacklist(false)], &[]).unwrap();
+
+    harness.send(&[deposit], &[&dln]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Active);
+    assert_eq!((state.balance, state.daily_compute_fee), (97_500_000, 975_000));
+    assert_eq!(harness.token_amount(&vault_token_account), Some(97_500_000));
+}
+
+#[test]
+fn transfers_activate_pending_sessions_as_deposits_do() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let (bot, next_bot) = (harness.wallet(1), harness.wallet(1));
+    let source = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let destination = harness.initialize(&user, next_bot.pubkey(), 3);
+    let config = pda::config_address().0;
+    let transfer = instructions::build(
+        instructions::accounts::TransferToSession {
+            source_vault: source,
+            destination_vault: destination,
+            user: user.pubkey(),
+            config,
+            treasury: harness.treasury,
+            fee_router: pda::fee_router_address().0,
+            operator_credit: pda::operator_credit_address(&bot.pubkey()).0,
+            rewards: pda::rewards_address(&user.pubkey()).0,
+            bot_profile: None,
+        },
+        instructions::args::TransferToSession {},
+    );
+    let admin = harness.payer.pubkey();
+    let blacklist = |blacklisted| {
+        instructions::build(
+            instructions::accounts::AdminAction { config, admin },
+            instructions::args::SetBotBlacklisted { bot: next_bot.pubkey(), blacklisted },
+        )
+    };
+
+    // A blacklisted bot's session can't be started by a transfer either
+    harness.send(&[blacklist(true)], &[]).unwrap();
+    assert_error(harness.send(&[transfer.clone()], &[&user]), EscrowError::BotBlacklisted);
+    harness.send(&[blacklist(false)], &[]).unwrap();
+
+    harness.send(&[transfer], &[&user]).unwrap();
+    let state = harness.vault(&destination);
+    assert_eq!(state.status, VaultStatus::Active);
+    assert!(state.balance > 0 && state.balance == state.total_deposited);
+    assert_eq!(state.daily_compute_fee, DAILY_COMPUTE_FEE);
+}
+
+#[test]
+fn sessions_migrate_to_a_new_treasury() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let admin = harness.payer.pubkey();
+    let config = pda::config_address().0;
+    let treasury = harness.wallet(1).pubkey();
+    let migrate = |admin| {
+        instructions::build(
+            instructions::accounts::MigrateTreasury { vault, config, admin },
+            instructions::args::MigrateTreasury {},
+        )
+    };
+
+    // Nothing to migrate to until the config's treasury changes
+    assert_error(harness.send(&[migrate(admin)], &[]), EscrowError::InvalidTreasury);
+    let ix = instructions::build(
+        instructions::accounts::AdminAction { config, admin },
+        instructions::args::SetTreasury { treasury },
+    );
+    harness.send(&[ix], &[]).unwrap();
+    assert_eq!(harness.vault(&vault).treasury, harness.treasury);
+
+    assert_error(harness.send(&[migrate(user.pubkey())], &[&user]), EscrowError::Unauthorized);
+    let meta = harness.send(&[migrate(admin)], &[]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::TreasuryMigrated(migrated)] => {
+            assert_eq!((migrated.user, migrated.previous, migrated.treasury), (user.pubkey(), harness.treasury, treasury));
+        }
+        other => panic!("expected TreasuryMigrated, got {other:?}"),
+    }
+    assert_eq!(harness.vault(&vault).treasury, treasury);
+}
+
+#[test]
+fn users_can_require_a_verified_bot() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let guardian = harness.payer.pubkey();
+
+    let ix = instructions::attest_bot(user.pubkey(), bot.pubkey(), true, true);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::Unauthorized);
+
+    let open = |harness: &mut Harness| {
+        let session_id = harness.session_id();
+        let (ix, _) =
+            instructions::initialize(user.pubkey(), harness.treasury, session_id, 3, bot.pubkey(), user.pubkey());
+        let ix = instructions::require_verified_bot(ix, &bot.pubkey());
+        harness.send(&[ix], &[&user])
+    };
+    assert_error(open(&mut harness), EscrowError::BotNotVerified);
+
+    harness.send(&[instructions::attest_bot(guardian, bot.pubkey(), true, false)], &[]).unwrap();
+    open(&mut harness).unwrap();
+
+    // Revoking applies to sessions opened afterwards
+    harness.send(&[instructions::attest_bot(guardian, bot.pubkey(), false, false)], &[]).unwrap();
+    assert_error(open(&mut harness), EscrowError::BotNotVerified);
+}
+
+#[test]
+fn sessions_snapshot_their_bots_tier_fee_at_funding() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let admin = harness.payer.pubkey();
+    let config = pda::config_address().0;
+    let tier_fees = |fees: Vec<u64>| {
+        instructions::build(
+            instructions::accounts::AdminAction { config, admin },
+            instructions::args::SetTierComputeFees { fees },
+        )
+    };
+    let open = |harness: &mut Harness| {
+        let session_id = harness.session_id();
+        let (ix, vault) =
+            instructions::initialize(user.pubkey(), harness.treasury, session_id, 3, bot.pubkey(), user.pubkey());
+        harness.send(&[instructions::require_verified_bot(ix, &bot.pubkey())], &[&user]).unwrap();
+        vault
+    };
+    let fund = |harness: &mut Harness, vault, verified_bot| {
+        let ix = instructions::deposit(user.pubkey(), vault, harness.treasury, LAMPORTS_PER_SOL, None, verified_bot);
+        harness.send(&[ix], &[&user])
+    };
+
+    assert_error(harness.send(&[tier_fees(vec![1; 9])], &[]), EscrowError::InvalidBotTier);
+    harness.send(&[tier_fees(vec![1_000_000, 250_000])], &[]).unwrap();
+    harness.send(&[instructions::attest_bot(admin, bot.pubkey(), true, false)], &[]).unwrap();
+    assert_error(harness.send(&[instructions::set_bot_tier(admin, bot.pubkey(), 9)], &[]), EscrowError::InvalidBotTier);
+    let ix = instructions::set_bot_tier(user.pubkey(), bot.pubkey(), 2);
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::Unauthorized);
+
+    let untiered = open(&mut harness);
+    fund(&mut harness, untiered, Some(bot.pubkey())).unwrap();
+    let default_fee = harness.vault(&untiered).daily_compute_fee;
+
+    // A session opened before the bot was tiered pays the tier it has at funding,
+    // and must show the bot's profile to be funded
+    let tiered = open(&mut harness);
+    harness.send(&[instructions::set_bot_tier(admin, bot.pubkey(), 2)], &[]).unwrap();
+    assert_error(fund(&mut harness, tiered, None), EscrowError::BotNotVerified);
+    fund(&mut harness, tiered, Some(bot.pubkey())).unwrap();
+    assert_eq!(harness.vault(&tiered).daily_compute_fee, 250_000);
+
+    // Re-pricing the tier leaves funded sessions alone; unpriced tiers pay the default
+    let later = open(&mut harness);
+    harness.send(&[tier_fees(vec![1_000_000])], &[]).unwrap();
+    assert_eq!(harness.vault(&tiered).daily_compute_fee, 250_000);
+    fund(&mut harness, later, Some(bot.pubkey())).unwrap();
+    assert_eq!(harness.vault(&later).daily_compute_fee, default_fee);
+}
+
+#[test]
+fn closing_returns_balance_and_rent() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let close = instructions::withdraw_and_close(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    let ix = instructions::withdraw_and_close(bot.pubkey(), vault, harness.treasury, bot.pubkey(), bot.pubkey());
+    assert_error(harness.send(&[ix], &[&bot]), EscrowError::Unauthorized);
+
+    // Everything the vault held goes back: the balance, then its rent
+    let held = harness.lamports(&vault);
+    let user_before = harness.lamports(&user.pubkey());
+    let meta = harness.send(&[close], &[&user]).unwrap();
+    match events(&meta).as_slice() {
+        [.., Event::Withdrawn(withdrawn), Event::SessionClosed(closed)] => {
+            assert_eq!(withdrawn.amount, 975_000_000);
+            assert_eq!(closed.rent, held - 975_000_000);
+        }
+        other => panic!("expected Withdrawn then SessionClosed, got {other:?}"),
+    }
+    let stats_rent = harness.lamports(&pda::bot_stats_address(&bot.pubkey()).0);
+    assert_eq!(harness.lamports(&user.pubkey()) + stats_rent - user_before, held);
+    assert_eq!(harness.lamports(&vault), 0);
+
+    // A session withdrawn the usual way can be closed afterwards
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    let ix = instructions::withdraw_and_close(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    let meta = harness.send(&[ix], &[&user]).unwrap();
+    assert!(matches!(events(&meta).as_slice(), [Event::SessionClosed(_)]));
+    assert_eq!(harness.lamports(&vault), 0);
+}
+
+#[test]
+fn withdrawal_notice_holds_funds_until_served() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 10, LAMPORTS_PER_SOL);
+    let set_notice = |notice: i64| {
+        instructions::build(
+            instructions::accounts::UserAction { vault, user: user.pubkey() },
+            instructions::args::SetWithdrawalNotice { notice },
+        )
+    };
+    let request = instructions::build(
+        instructions::accounts::UserAction { vault, user: user.pubkey() },
+        instructions::args::RequestWithdrawal {},
+    );
+    let withdraw = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+
+    assert_error(harness.send(&[request.clone()], &[&user]), EscrowError::InvalidWithdrawalNotice);
+    harness.send(&[set_notice(2 * SECONDS_PER_DAY)], &[&user]).unwrap();
+    assert_error(harness.send(&[withdraw.clone()], &[&user]), EscrowError::WithdrawalNoticePending);
+    // A lockup that could be shortened on demand wouldn't be one
+    assert_error(harness.send(&[set_notice(0)], &[&user]), EscrowError::WithdrawalNoticePending);
+
+    let meta = harness.send(&[request.clone()], &[&user]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::WithdrawalRequested(requested)] => {
+            assert_eq!(requested.available_at, harness.now() + 2 * SECONDS_PER_DAY)
+        }
+        other => panic!("expected WithdrawalRequested, got {other:?}"),
+    }
+    harness.warp(SECONDS_PER_DAY);
+    assert_error(harness.send(&[withdraw.clone()], &[&user]), EscrowError::WithdrawalNoticePending);
+
+    // A served request lapses if it isn't used within the window
+    harness.warp(4 * SECONDS_PER_DAY);
+    assert_error(harness.send(&[withdraw.clone()], &[&user]), EscrowError::WithdrawalNoticePending);
+
+    harness.send(&[request], &[&user]).unwrap();
+    harness.warp(2 * SECONDS_PER_DAY);
+    harness.send(&[withdraw], &[&user]).unwrap();
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Withdrawn);
+}
+
+#[test]
+fn guardian_pauses_lift_unless_the_user_confirms() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let cranker = harness.wallet(1);
+    let guardian = harness.payer.pubkey();
+    let vault = harness.open_session(&user, bot.pubkey(), 30, LAMPORTS_PER_SOL);
+    let pause = |signer: Pubkey| instructions::guardian_pause(signer, vault, PauseReason::ActiveExploit);
+    let lift = instructions::lift_guardian_pause(cranker.pubkey(), vault);
+
+    assert_error(harness.send(&[pause(cranker.pubkey())], &[&cranker]), EscrowError::Unauthorized);
+    let meta = harness.send(&[pause(guardian)], &[]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::SessionPaused(_), Event::GuardianPaused(paused)] => {
+            assert_eq!(paused.reason, PauseReason::ActiveExploit);
+            assert_eq!(paused.lifts_at, harness.now() + MAX_GUARDIAN_PAUSE_DAYS as i64 * SECONDS_PER_DAY);
+        }
+        other => panic!("expected SessionPaused then GuardianPaused, got {other:?}"),
+    }
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Paused);
+    assert_error(harness.send(&[lift.clone()], &[&cranker]), EscrowError::GuardianPauseActive);
+
+    // Left alone, it lifts for anyone once it runs out
+    harness.warp(MAX_GUARDIAN_PAUSE_DAYS as i64 * SECONDS_PER_DAY);
+    harness.send(&[lift.clone()], &[&cranker]).unwrap();
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Active);
+
+    // Confirmed, it's the user's own pause; withdrawing never waited on it
+    harness.send(&[pause(guardian)], &[]).unwrap();
+    let confirm = instructions::build(
+        instructions::accounts::UserAction { vault, user: user.pubkey() },
+        instructions::args::ConfirmGuardianPause {},
+    );
+    harness.send(&[confirm], &[&user]).unwrap();
+    harness.warp(MAX_GUARDIAN_PAUSE_DAYS as i64 * SECONDS_PER_DAY);
+    assert_error(harness.send(&[lift], &[&cranker]), EscrowError::NotGuardianPaused);
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Withdrawn);
+}
+
+#[test]
+fn if_due_cranks_are_no_ops_until_due() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let cranker = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 2, LAMPORTS_PER_SOL);
+    let deduct = instructions::deduct_compute_fee_if_due(cranker.pubkey(), vault, user.pubkey(), harness.treasury, bot.pubkey());
+    let expire = instructions::expire_if_due(cranker.pubkey(), vault);
+    let crank = |harness: &mut Harness, ix: &Instruction| {
+        let meta = harness.send(&[ix.clone()], &[&cranker]).unwrap();
+        (bool::try_from_slice(&meta.return_data.data).unwrap(), events(&meta).len())
+    };
+
+    assert_eq!(crank(&mut harness, &deduct), (false, 0));
+    assert_eq!(crank(&mut harness, &expire), (false, 0));
+    assert_eq!(harness.vault(&vault).balance, 975_000_000);
+
+    harness.warp(SECONDS_PER_DAY);
+    assert!(crank(&mut harness, &deduct).0);
+    assert_eq!(harness.vault(&vault).balance, 975_000_000 - DAILY_COMPUTE_FEE);
+    assert_eq!(crank(&mut harness, &deduct), (false, 0));
+
+    harness.warp(SECONDS_PER_DAY);
+    assert!(crank(&mut harness, &expire).0);
+    assert_eq!(harness.vault(&vault).status, VaultStatus::Expired);
+    // Once the session has ended there's nothing left for either to do
+    assert_eq!(crank(&mut harness, &expire), (false, 0));
+    assert_eq!(crank(&mut harness, &deduct), (false, 0));
+}
+
+#[test]
+fn relayers_pay_for_withdrawals() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(2);
+    let bot = harness.wallet(1);
+    let relayer = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 2, LAMPORTS_PER_SOL);
+
+    // The user signs only as authority; the relayer funds the bot's stats
+    let (user_before, relayer_before) = (harness.lamports(&user.pubkey()), harness.lamports(&relayer.pubkey()));
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), relayer.pubkey());
+    harness.send(&[ix], &[&user, &relayer]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Withdrawn);
+    assert_eq!(harness.lamports(&user.pubkey()) - user_before, state.total_withdrawn);
+    let stats_rent = harness.lamports(&pda::bot_stats_address(&bot.pubkey()).0);
+    assert_eq!(relayer_before - harness.lamports(&relayer.pubkey()), stats_rent);
+}
+
+#[test]
+fn signed_withdrawals_need_the_users_signature() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(2);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 2, LAMPORTS_PER_SOL);
+    let deadline = harness.now() + SECONDS_PER_DAY;
+    let message = instructions::withdraw_message(&vault, deadline);
+    let payer = harness.payer.pubkey();
+
+    // Signed by someone else, or for another deadline
+    let forged = bot.sign_message(&message);
+    let ixs = instructions::withdraw_with_signature(
+        user.pubkey(), vault, harness.treasury, bot.pubkey(), payer, deadline, forged.as_array(),
+    );
+    assert!(harness.send(&ixs, &[]).is_err());
+    let signature = user.sign_message(&message);
+    let ixs = instructions::withdraw_with_signature(
+        user.pubkey(), vault, harness.treasury, bot.pubkey(), payer, deadline + 1, signature.as_array(),
+    );
+    assert!(harness.send(&ixs, &[]).is_err());
+
+    // The user signs nothing on-chain and still gets every lamport
+    let user_before = harness.lamports(&user.pubkey());
+    let ixs = instructions::withdraw_with_signature(
+        user.pubkey(), vault, harness.treasury, bot.pubkey(), payer, deadline, signature.as_array(),
+    );
+    harness.send(&ixs, &[]).unwrap();
+    let state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Withdrawn);
+    assert_eq!(harness.lamports(&user.pubkey()) - user_before, state.total_withdrawn);
+}
+
+#[test]
+fn batching_sessions_pass_their_trade_batch() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let trade_batch = pda::trade_batch_address(&vault).0;
+    let ix = instructions::build(
+        instructions::accounts::SetTradeBatching {
+            vault,
+            user: user.pubkey(),
+            trade_batch,
+            system_program: anchor_lang::system_program::ID,
+            payer: user.pubkey(),
+        },
+        instructions::args::SetTradeBatching { batch_size: 8, window_slots: 150 },
+    );
+    harness.send(&[ix], &[&user]).unwrap();
+    assert!(harness.vault(&vault).batch_trades);
+
+    // The route's last account is taken as the batch, so it must be there
+    let mut dry_run = swap(&harness, vault, &user, &bot, JUPITER_PROGRAM_ID, 1_000_000);
+    dry_run.dry_run = true;
+    let result = harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]);
+    assert_error(result, EscrowError::InvalidTradeBatch);
+    jupiter_route(&mut harness, &mut dry_run);
+    dry_run.batched = true;
+    harness.send(&[instructions::execute_swap(&dry_run)], &[&bot]).unwrap();
+
+    // Nothing buffered, nothing to emit
+    let flush = instructions::build(
+        instructions::accounts::FlushTradeBatch { vault, trade_batch },
+        instructions::args::FlushTradeBatch {},
+    );
+    let meta = harness.send(&[flush], &[]).unwrap();
+    assert!(events(&meta).is_empty());
+}
+
+#[test]
+fn positions_leave_after_the_balance() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+
+    // A bought position is still in the vault after the SOL balance left
+    let mint = Pubkey::new_unique();
+    let mut state = harness.vault(&vault);
+    assert_eq!(state.status, VaultStatus::Withdrawn);
+    state.position_mints = vec![mint];
+    harness.set_vault(&vault, &state);
+    let vault_token_account = harness.token_account(&vault, &mint, 500);
+    let withdraw_position = |user: Pubkey, user_token_account: Pubkey| {
+        instructions::build(
+            instructions::accounts::WithdrawPosition {
+                vault,
+                user,
+                vault_token_account,
+                user_token_account,
+                token_program: TOKEN_PROGRAM_ID,
+            },
+            instructions::args::WithdrawPosition {},
+        )
+    };
+
+    let bot_token_account = harness.token_account(&bot.pubkey(), &mint, 0);
+    let result = harness.send(&[withdraw_position(bot.pubkey(), bot_token_account)], &[&bot]);
+    assert_error(result, EscrowError::Unauthorized);
+
+    let user_token_account = harness.token_account(&user.pubkey(), &mint, 0);
+    let meta = harness.send(&[withdraw_position(user.pubkey(), user_token_account)], &[&user]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::PositionWithdrawn(withdrawn)] => assert_eq!((withdrawn.mint, withdrawn.amount), (mint, 500)),
+        other => panic!("expected one PositionWithdrawn, got {other:?}"),
+    }
+    assert_eq!(harness.token_amount(&user_token_account), Some(500));
+    assert_eq!(harness.token_amount(&vault_token_account), None);
+    assert!(harness.vault(&vault).position_mints.is_empty());
+}
+
+#[test]
+fn perps_collateral_blocks_withdrawals() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let mut state = harness.vault(&vault);
+    state.perps_collateral = 1;
+    harness.set_vault(&vault, &state);
+
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    assert_error(harness.send(&[ix], &[&user]), EscrowError::PerpsNotUnwound);
+}
+
+#[test]
+fn duration_points_accrue_while_funded() {
+    let mut harness = Harness::new();
+    let now = harness.now();
+    let schedule = RewardsSchedule {
+        volume_points_per_sol: 0,
+        duration_points_per_sol_day: 10,
+        starts_at: now,
+        ends_at: now + 30 * SECONDS_PER_DAY,
+    };
+    let ix = instructions::build(
+        instructions::accounts::SetRewardsSchedule { config: pda::config_address().0, admin: harness.payer.pubkey() },
+        instructions::args::SetRewardsSchedule { schedule },
+    );
+    harness.send(&[ix], &[]).unwrap();
+
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let rewards = pda::rewards_address(&user.pubkey()).0;
+    let points = |harness: &Harness| {
+        let account = harness.svm.get_account(&rewards).unwrap();
+        let rewards: RewardsAccount = gentdex_client::state::decode(&account.data).unwrap();
+        rewards.points
+    };
+    assert_eq!(points(&harness), 0);
+
+    // 0.975 SOL earns 9 points a day, settled by the crank...
+    harness.warp(2 * SECONDS_PER_DAY);
+    let ix = instructions::deduct_compute_fee(user.pubkey(), vault, user.pubkey(), harness.treasury, bot.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    assert_eq!(points(&harness), 18);
+
+    // ...and by the withdrawal, stopping at expiry however late it comes
+    harness.warp(5 * SECONDS_PER_DAY);
+    let ix = instructions::withdraw(user.pubkey(), vault, harness.treasury, bot.pubkey(), user.pubkey());
+    harness.send(&[ix], &[&user]).unwrap();
+    assert_eq!(points(&harness), 18 + 9);
+}
+
+#[test]
+fn epoch_reports_need_positions_priced() {
+    let mut harness = Harness::new();
+    let user = harness.wallet(10);
+    let bot = harness.wallet(1);
+    let vault = harness.open_session(&user, bot.pubkey(), 3, LAMPORTS_PER_SOL);
+    let epoch = harness.vault(&vault).report_epoch;
+    let mut clock = harness.svm.get_sysvar::<Clock>();
+    clock.epoch += 1;
+    harness.svm.set_sysvar::<Clock>(&clock);
+
+    let cranker = harness.wallet(1);
+    let close_epoch = instructions::build(
+        instructions::accounts::CloseEpoch {
+            vault,
+            report: pda::epoch_report_address(&vault, epoch).0,
+            cranker: cranker.pubkey(),
+            system_program: anchor_lang::system_program::ID,
+            config: pda::config_address().0,
+        },
+        instructions::args::CloseEpoch {},
+    );
+
+    // A position can't be left out of the value
+    let mut state = harness.vault(&vault);
+    state.position_mints = vec![Pubkey::new_unique()];
+    harness.set_vault(&vault, &state);
+    assert_error(harness.send(&[close_epoch.clone()], &[&cranker]), EscrowError::PriceFeedMissing);
+
+    state.position_mints = Vec::new();
+    harness.set_vault(&vault, &state);
+    let meta = harness.send(&[close_epoch], &[&cranker]).unwrap();
+    match events(&meta).as_slice() {
+        [Event::EpochClosed(closed)] => assert_eq!((closed.pnl, closed.fees), (0, 25_000_000)),
+        other => panic!("expected one EpochClosed, got {other:?}"),
+    }
+}
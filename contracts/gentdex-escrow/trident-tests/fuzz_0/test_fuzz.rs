@@ -0,0 +1,257 @@
+//! Random instruction sequences against one session, each sent by the user,
+//! the bot or an attacker, with the treasury account sometimes swapped for the
+//! attacker's. After every transaction, checks that nobody but the user
+//! reduced their claim on the vault except by the compute fee owed to the
+//! treasury, and that the vault's lamports still cover its accounted balance.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator};
+use gentdex_client::instructions::{self, Swap};
+use gentdex_client::jupiter::JUPITER_PROGRAM_ID;
+use gentdex_client::program::{default_dex_whitelist, ProtocolConfig, Vault, VaultStatus};
+use gentdex_client::{pda, state, PROGRAM_ID};
+use trident_fuzz::fuzzing::*;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Actor {
+    User,
+    Bot,
+    Attacker,
+}
+
+/// What the invariant check needs from before a transaction.
+struct Snapshot {
+    vault: Vault,
+    treasury: u64,
+}
+
+#[derive(FuzzTestMethods)]
+struct FuzzTest {
+    trident: Trident,
+    user: Pubkey,
+    bot: Pubkey,
+    attacker: Pubkey,
+    treasury: Pubkey,
+    vault: Pubkey,
+}
+
+#[flow_executor]
+impl FuzzTest {
+    fn new() -> Self {
+        Self {
+            trident: Trident::default(),
+            user: Pubkey::new_unique(),
+            bot: Pubkey::new_unique(),
+            attacker: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            vault: Pubkey::default(),
+        }
+    }
+
+    #[init]
+    fn start(&mut self) {
+        for wallet in [self.user, self.bot, self.attacker, self.treasury] {
+            self.trident.airdrop(&wallet, 100 * LAMPORTS_PER_SOL);
+        }
+        self.seed_config();
+
+        let session_id: [u8; 16] = self.trident.gen_range(0..u128::MAX).to_le_bytes();
+        let duration_days = self.trident.gen_range(1..=30u16);
+        let (ix, vault) =
+            instructions::initialize(self.user, self.treasury, session_id, duration_days, self.bot, self.user);
+        assert!(self.trident.process_transaction(&[ix], Some("initialize")).is_success());
+        self.vault = vault;
+    }
+
+    #[flow]
+    fn deposit(&mut self) {
+        let actor = self.actor();
+        let amount = self.trident.gen_range(0..20 * LAMPORTS_PER_SOL);
+        let ix = instructions::deposit(self.signer(actor), self.vault, self.treasury_for(actor), amount, None, None);
+        self.send(actor, ix, "deposit");
+    }
+
+    #[flow]
+    fn swap(&mut self) {
+        let actor = self.actor();
+        let dex_program = if self.trident.gen_range(0..2u8) == 0 { JUPITER_PROGRAM_ID } else { Pubkey::new_unique() };
+        let treasury = self.treasury_for(actor);
+        let ix = instructions::execute_swap(&Swap {
+            vault: self.vault,
+            user: self.user,
+            treasury,
+            bot: self.signer(actor),
+            dex_program,
+            amount_in: self.trident.gen_range(0..20 * LAMPORTS_PER_SOL),
+            minimum_amount_out: 1,
+            memo: None,
+            recent_slot: None,
+            jito_tip: false,
+            nonce: None,
+            dry_run: false,
+            batched: false,
+            output_token_account: None,
+            price_feeds: None,
+            base_token_account: None,
+            route: vec![],
+            route_data: None,
+        });
+        self.send(actor, ix, "execute_swap");
+    }
+
+    #[flow]
+    fn deduct_compute_fee(&mut self) {
+        let actor = self.actor();
+        let ix = instructions::deduct_compute_fee(
+            self.signer(actor),
+            self.vault,
+            self.user,
+            self.treasury_for(actor),
+            self.bot,
+        );
+        self.send(actor, ix, "deduct_compute_fee");
+    }
+
+    #[flow]
+    fn pause_or_resume(&mut self) {
+        let actor = self.actor();
+        let ix = match self.trident.gen_range(0..2u8) {
+            0 => instructions::pause(self.signer(actor), self.vault),
+            _ => instructions::resume(self.signer(actor), self.vault),
+        };
+        self.send(actor, ix, "pause_or_resume");
+    }
+
+    #[flow]
+    fn expire(&mut self) {
+        let actor = self.actor();
+        let ix = instructions::expire(self.signer(actor), self.vault);
+        self.send(actor, ix, "expire");
+    }
+
+    #[flow]
+    fn withdraw(&mut self) {
+        let actor = self.actor();
+        let ix = instructions::withdraw(self.signer(actor), self.vault, self.treasury_for(actor));
+        self.send(actor, ix, "withdraw");
+    }
+
+    #[flow]
+    fn warp(&mut self) {
+        let seconds = self.trident.gen_range(0..3 * SECONDS_PER_DAY);
+        self.trident.forward_in_time(seconds);
+    }
+
+    #[end]
+    fn end(&mut self) {
+        // Whatever happened, the user can still get out everything accounted
+        let vault = self.vault();
+        if vault.status != VaultStatus::Pending && vault.balance > 0 && vault.lent_amount == 0 {
+            let before = self.trident.get_account(&self.user).lamports();
+            let ix = instructions::withdraw(self.user, self.vault, vault.treasury);
+            assert!(self.trident.process_transaction(&[ix], Some("final withdraw")).is_success());
+            let paid = self.trident.get_account(&self.user).lamports() - before;
+            assert!(paid <= vault.balance && paid == self.vault().total_withdrawn - vault.total_withdrawn);
+        }
+    }
+}
+
+impl FuzzTest {
+    /// Write the config `initialize_config` would create; that instruction
+    /// needs the upgrade authority, which a fuzzer-loaded program lacks.
+    fn seed_config(&mut self) {
+        let mut data = ProtocolConfig::DISCRIMINATOR.to_vec();
+        data.resize(8 + ProtocolConfig::INIT_SPACE, 0);
+        let mut config = ProtocolConfig::try_deserialize(&mut data.as_slice()).unwrap();
+        config.admin = self.attacker;
+        config.treasury = self.treasury;
+        config.fee_bps = 250;
+        config.daily_compute_fee = 10_000_000;
+        config.whitelist = default_dex_whitelist();
+        config.whitelist_version = 1;
+        let (address, bump) = pda::config_address();
+        config.bump = bump;
+
+        let mut serialized = Vec::new();
+        config.try_serialize(&mut serialized).unwrap();
+        data[..serialized.len()].copy_from_slice(&serialized);
+        let mut account = AccountSharedData::new(
+            self.trident.minimum_balance_for_rent_exemption(data.len()),
+            data.len(),
+            &PROGRAM_ID,
+        );
+        account.set_data_from_slice(&data);
+        self.trident.set_account_custom(&address, &account);
+    }
+
+    fn actor(&mut self) -> Actor {
+        match self.trident.gen_range(0..3u8) {
+            0 => Actor::User,
+            1 => Actor::Bot,
+            _ => Actor::Attacker,
+        }
+    }
+
+    fn signer(&self, actor: Actor) -> Pubkey {
+        match actor {
+            Actor::User => self.user,
+            Actor::Bot => self.bot,
+            Actor::Attacker => self.attacker,
+        }
+    }
+
+    /// The session's treasury, or for the attacker sometimes their own wallet.
+    fn treasury_for(&mut self, actor: Actor) -> Pubkey {
+        if actor == Actor::Attacker && self.trident.gen_range(0..2u8) == 0 {
+            self.attacker
+        } else {
+            self.treasury
+        }
+    }
+
+    fn vault(&self) -> Vault {
+        state::decode(self.trident.get_account(&self.vault).data()).unwrap()
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            vault: self.vault(),
+            treasury: self.trident.get_account(&self.treasury).lamports(),
+        }
+    }
+
+    fn send(&mut self, actor: Actor, ix: Instruction, name: &str) {
+        let before = self.snapshot();
+        self.trident.process_transaction(&[ix], Some(name));
+        self.check(actor, &before);
+    }
+
+    fn check(&self, actor: Actor, before: &Snapshot) {
+        let after = self.snapshot();
+
+        // The vault's lamports always cover its rent plus the accounted balance
+        let account = self.trident.get_account(&self.vault);
+        let rent = self.trident.minimum_balance_for_rent_exemption(account.data().len());
+        assert!(account.lamports() >= rent + after.vault.balance, "vault lamports below rent + balance");
+
+        if actor == Actor::User {
+            return;
+        }
+        // Anyone else may only move the compute fee owed, and only to the treasury
+        let taken = before.vault.balance.saturating_sub(after.vault.balance);
+        let fees = after.vault.compute_fees_paid - before.vault.compute_fees_paid;
+        assert_eq!(taken, fees, "{actor:?} reduced the balance by more than the compute fee");
+        assert_eq!(after.treasury - before.treasury, fees, "compute fee didn't reach the treasury");
+        assert_eq!(after.vault.total_withdrawn, before.vault.total_withdrawn, "{actor:?} withdrew");
+        assert_eq!(after.vault.user, before.vault.user);
+        assert_eq!(after.vault.treasury, before.vault.treasury);
+    }
+}
+
+fn main() {
+    // 1_000 iterations of up to 100 flows each
+    FuzzTest::fuzz(1_000, 100);
+}